@@ -33,13 +33,15 @@ interface PriceData {
 }
 
 /**
- * Normalize Pyth price from i64 + exponent to USD
+ * Normalize Pyth price from i64 + exponent to USD: price * 10^exponent
  * Example: price = 9500000000000, expo = -8 -> 95000
+ * Exponents can be zero or positive too, e.g. price = 95, expo = 3 -> 95000
  */
 function normalizePrice(price: bigint, exponent: number): number {
     const priceMagnitude = Number(price);
-    const normalizationFactor = Math.pow(10, Math.abs(exponent));
-    return priceMagnitude / normalizationFactor;
+    return exponent < 0
+        ? priceMagnitude / Math.pow(10, -exponent)
+        : priceMagnitude * Math.pow(10, exponent);
 }
 
 /**
@@ -65,11 +67,19 @@ function loadWallet(): Keypair {
 }
 
 /**
- * Get policy PDA
+ * Encode a policy id as the 8-byte little-endian seed used by the program
  */
-function getPolicyPDA(owner: PublicKey): [PublicKey, number] {
+function policyIdSeed(policyId: number): Buffer {
+    return new BN(policyId).toArrayLike(Buffer, 'le', 8);
+}
+
+/**
+ * Get policy PDA (seeded by the wallet that opened it, which stays fixed
+ * across ownership transfers)
+ */
+function getPolicyPDA(creator: PublicKey, policyId: number): [PublicKey, number] {
     return PublicKey.findProgramAddressSync(
-        [Buffer.from('policy'), owner.toBuffer()],
+        [Buffer.from('policy'), creator.toBuffer(), policyIdSeed(policyId)],
         PROGRAM_ID
     );
 }
@@ -77,9 +87,9 @@ function getPolicyPDA(owner: PublicKey): [PublicKey, number] {
 /**
  * Get vault PDA
  */
-function getVaultPDA(owner: PublicKey): [PublicKey, number] {
+function getVaultPDA(creator: PublicKey, policyId: number): [PublicKey, number] {
     return PublicKey.findProgramAddressSync(
-        [Buffer.from('vault'), owner.toBuffer()],
+        [Buffer.from('vault'), creator.toBuffer(), policyIdSeed(policyId)],
         PROGRAM_ID
     );
 }
@@ -87,10 +97,13 @@ function getVaultPDA(owner: PublicKey): [PublicKey, number] {
 /**
  * Check if liquidation condition is met
  */
+type TriggerDirection = 'protectLong' | 'protectShort' | 'outsideRange';
+
 function shouldLiquidate(
     currentPrice: number,
     strikePrice: number,
-    isLongInsurance: boolean,
+    direction: TriggerDirection,
+    upperStrikePrice: number,
     demoMode: string
 ): boolean {
     // In demo mode, use demo triggers
@@ -101,12 +114,16 @@ function shouldLiquidate(
     }
     
     // Normal mode: use policy logic
-    if (isLongInsurance) {
-        // Protect Long: Pay if price drops below strike
-        return currentPrice < strikePrice;
-    } else {
-        // Protect Short: Pay if price rises above strike
-        return currentPrice > strikePrice;
+    switch (direction) {
+        case 'protectLong':
+            // Protect Long: Pay if price drops below strike
+            return currentPrice < strikePrice;
+        case 'protectShort':
+            // Protect Short: Pay if price rises above strike
+            return currentPrice > strikePrice;
+        case 'outsideRange':
+            // Outside Range: Pay if price leaves [strike, upper strike]
+            return currentPrice < strikePrice || currentPrice > upperStrikePrice;
     }
 }
 
@@ -116,14 +133,15 @@ function shouldLiquidate(
 async function executeLiquidation(
     connection: Connection,
     wallet: Keypair,
-    policyOwner: PublicKey
+    policyOwner: PublicKey,
+    policyId: number
 ): Promise<void> {
     try {
         console.log('📦 Building liquidation transaction...');
         
         // Get PDAs
-        const [policyPDA] = getPolicyPDA(policyOwner);
-        const [vaultPDA] = getVaultPDA(policyOwner);
+        const [policyPDA] = getPolicyPDA(policyOwner, policyId);
+        const [vaultPDA] = getVaultPDA(policyOwner, policyId);
         
         // Create transaction
         const transaction = new Transaction();
@@ -148,7 +166,7 @@ async function executeLiquidation(
         //         policy: policyPDA,
         //         priceUpdate: PYTH_PRICE_UPDATE_ACCOUNT,
         //         vault: vaultPDA,
-        //         user: policyOwner,
+        //         beneficiary: policyOwner,
         //         signer: wallet.publicKey,
         //         systemProgram: SystemProgram.programId,
         //     })
@@ -306,8 +324,8 @@ async function main() {
                     const priceFeed = data.parsed[0];
                     if (priceFeed.price) {
                         const priceObj = priceFeed.price;
-                        currentPrice = Number(priceObj.price) * Math.pow(10, priceObj.expo || priceObj.exponent || -8);
-                        exponent = priceObj.expo || priceObj.exponent || -8;
+                        currentPrice = Number(priceObj.price) * Math.pow(10, priceObj.expo ?? priceObj.exponent ?? -8);
+                        exponent = priceObj.expo ?? priceObj.exponent ?? -8;
                     }
                 }
                 
@@ -63,7 +63,7 @@ async function startStreaming(connection: HermesClient, feedId: string) {
                 const priceFeed = latestPrice.parsed[0];
                 if (priceFeed.price_feed?.price?.price) {
                     const priceInfo = priceFeed.price_feed.price.price;
-                    const exponent = priceInfo.expo || priceInfo.exponent || -8;
+                    const exponent = priceInfo.expo ?? priceInfo.exponent ?? -8;
                     const currentPrice = Number(priceInfo.price) * Math.pow(10, exponent);
                     console.log(`   Current SOL/USD Price: $${currentPrice.toFixed(2)}\n`);
                 }
@@ -106,7 +106,7 @@ async function startStreaming(connection: HermesClient, feedId: string) {
                     if (priceFeed.price) {
                         const priceObj = priceFeed.price;
                         priceValue = priceObj.price;
-                        exponent = priceObj.expo || priceObj.exponent || -8;
+                        exponent = priceObj.expo ?? priceObj.exponent ?? -8;
                         if (priceObj.conf !== undefined) {
                             confidence = priceObj.conf;
                         }
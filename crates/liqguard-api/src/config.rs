@@ -0,0 +1,49 @@
+// The API's config file (TOML): where to listen and which database to
+// read. See api.example.toml
+
+use std::net::SocketAddr;
+use std::path::Path;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConfig {
+    pub listen_addr: SocketAddr,
+    pub database_url: String,
+    #[serde(default = "default_max_page_size")]
+    pub max_page_size: usize,
+}
+
+fn default_max_page_size() -> usize {
+    100
+}
+
+impl ApiConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("reading {}: {error}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|error| error.to_string())?;
+        if config.max_page_size == 0 {
+            return Err("max_page_size must be at least 1".to_string());
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_config_parses() {
+        let config = ApiConfig::parse(include_str!("../api.example.toml")).unwrap();
+        assert_eq!(config.listen_addr.port(), 8080);
+        assert_eq!(config.max_page_size, 100);
+        assert!(ApiConfig::parse("listen_addr = \"nowhere\"\ndatabase_url = \"a\"").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+// Reads against the indexer's tables. The stored status is as of the
+// indexer's last write, while expiry and lapses move a policy along with
+// time alone. Listings therefore select every row whose stored status can
+// have become the one asked for, then re-derive the status from the
+// account and keep the rows that match, in address order so a page ends
+// where the next starts
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use liqguard::{Policy, UnderlyingAsset};
+use liqguard_client::accounts::PolicyStatus;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, NoTls, Row};
+
+use crate::models::{AssetStats, ClaimResponse, PolicyPage, PolicyResponse};
+
+// Narrows a policy listing; filters combine with AND
+#[derive(Clone, Debug, Default)]
+pub struct PolicyQuery {
+    pub owner: Option<Pubkey>,
+    pub beneficiary: Option<Pubkey>,
+    pub underwriter: Option<Pubkey>,
+    pub asset: Option<UnderlyingAsset>,
+    pub status: Option<PolicyStatus>,
+    pub after: Option<Pubkey>,
+}
+
+// Stored statuses a policy with status could have at a later time
+fn candidate_statuses(status: PolicyStatus) -> Vec<&'static str> {
+    match status {
+        PolicyStatus::Terminal => vec!["terminal", "active", "unfunded"],
+        status => vec![status.as_str()],
+    }
+}
+
+impl PolicyQuery {
+    // The SELECT for up to limit rows after after, and its parameters
+    fn sql(&self, after: Option<&str>, limit: i64) -> (String, Vec<Box<dyn ToSql + Sync + Send>>) {
+        let mut clauses = Vec::new();
+        let mut params: Vec<Box<dyn ToSql + Sync + Send>> = Vec::new();
+        // clause with {} standing for the parameter's placeholder
+        let mut push = |clause: &str, param: Box<dyn ToSql + Sync + Send>| {
+            params.push(param);
+            clauses.push(clause.replace("{}", &format!("${}", params.len())));
+        };
+        for (clause, key) in [
+            ("owner = {}", self.owner),
+            ("beneficiary = {}", self.beneficiary),
+            ("underwriter = {}", self.underwriter),
+        ] {
+            if let Some(key) = key {
+                push(clause, Box::new(key.to_string()));
+            }
+        }
+        if let Some(asset) = self.asset {
+            push("asset = {}", Box::new(asset.name()));
+        }
+        if let Some(status) = self.status {
+            push("status = ANY({})", Box::new(candidate_statuses(status)));
+        }
+        if let Some(after) = after {
+            push("address > {}", Box::new(after.to_string()));
+        }
+        params.push(Box::new(limit));
+        let filter = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", clauses.join(" AND "))
+        };
+        let sql = format!(
+            "SELECT address, data, slot FROM policies{filter} ORDER BY address LIMIT ${}",
+            params.len()
+        );
+        (sql, params)
+    }
+}
+
+fn error(context: &str) -> impl Fn(tokio_postgres::Error) -> String + '_ {
+    move |error| format!("{context}: {error}")
+}
+
+// A policies row as its response, None if the stored account no longer
+// decodes or the row is malformed
+fn policy_response(row: &Row, now: i64) -> Option<PolicyResponse> {
+    let address: String = row.get(0);
+    let data: Vec<u8> = row.get(1);
+    let slot: i64 = row.get(2);
+    let policy = Policy::try_deserialize(&mut &data[..]).ok()?;
+    let address = address.parse().ok()?;
+    Some(PolicyResponse::new(&address, &policy, slot as u64, now))
+}
+
+pub struct Db {
+    client: Client,
+}
+
+impl Db {
+    pub async fn connect(database_url: &str) -> Result<Self, String> {
+        let (client, connection) = tokio_postgres::connect(database_url, NoTls)
+            .await
+            .map_err(error("connecting to postgres"))?;
+        tokio::spawn(async move {
+            if let Err(error) = connection.await {
+                log::error!("postgres connection closed: {error}");
+            }
+        });
+        Ok(Self { client })
+    }
+
+    pub async fn policy(
+        &self,
+        address: &Pubkey,
+        now: i64,
+    ) -> Result<Option<PolicyResponse>, String> {
+        let row = self
+            .client
+            .query_opt(
+                "SELECT address, data, slot FROM policies WHERE address = $1",
+                &[&address.to_string()],
+            )
+            .await
+            .map_err(error("reading policy"))?;
+        Ok(row.and_then(|row| policy_response(&row, now)))
+    }
+
+    // Up to limit policies matching query, in address order
+    pub async fn policies(
+        &self,
+        query: &PolicyQuery,
+        limit: usize,
+        now: i64,
+    ) -> Result<PolicyPage, String> {
+        let mut policies = Vec::new();
+        let mut after = query.after.map(|after| after.to_string());
+        loop {
+            let (sql, params) = query.sql(after.as_deref(), limit as i64);
+            let params: Vec<&(dyn ToSql + Sync)> = params
+                .iter()
+                .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+                .collect();
+            let rows = self
+                .client
+                .query(&sql, &params)
+                .await
+                .map_err(error("listing policies"))?;
+            let exhausted = rows.len() < limit;
+            for row in &rows {
+                after = Some(row.get(0));
+                let Some(policy) = policy_response(row, now) else {
+                    continue;
+                };
+                if query
+                    .status
+                    .is_some_and(|status| policy.status != status.as_str())
+                {
+                    continue;
+                }
+                policies.push(policy);
+                if policies.len() == limit {
+                    let next = after.clone();
+                    return Ok(PolicyPage { policies, next });
+                }
+            }
+            if exhausted {
+                return Ok(PolicyPage {
+                    policies,
+                    next: None,
+                });
+            }
+        }
+    }
+
+    pub async fn claims(&self, policy: &Pubkey) -> Result<Vec<ClaimResponse>, String> {
+        let rows = self
+            .client
+            .query(
+                "SELECT signature, slot, liquidator, asset, price, price_exponent, payout, bounty, \
+                     coverage_remaining, finalized \
+                 FROM claims WHERE policy = $1 ORDER BY slot, event_index",
+                &[&policy.to_string()],
+            )
+            .await
+            .map_err(error("reading claims"))?;
+        Ok(rows
+            .iter()
+            .map(|row| ClaimResponse {
+                signature: row.get(0),
+                slot: row.get(1),
+                liquidator: row.get(2),
+                asset: row.get(3),
+                price: row.get::<_, i64>(4).to_string(),
+                price_exponent: row.get(5),
+                payout: row.get(6),
+                bounty: row.get(7),
+                coverage_remaining: row.get(8),
+                finalized: row.get(9),
+            })
+            .collect())
+    }
+
+    pub async fn stats(&self) -> Result<Vec<AssetStats>, String> {
+        let rows = self
+            .client
+            .query(
+                "SELECT asset, policies, active_policies, active_coverage, claims, total_paid \
+                 FROM stats ORDER BY asset",
+                &[],
+            )
+            .await
+            .map_err(error("reading stats"))?;
+        Ok(rows
+            .iter()
+            .map(|row| AssetStats {
+                asset: row.get(0),
+                policies: row.get(1),
+                active_policies: row.get(2),
+                active_coverage: row.get(3),
+                claims: row.get(4),
+                total_paid: row.get(5),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sql_numbers_its_filters_in_order() {
+        let query = PolicyQuery {
+            owner: Some(Pubkey::new_unique()),
+            asset: Some(UnderlyingAsset::Btc),
+            status: Some(PolicyStatus::Terminal),
+            ..PolicyQuery::default()
+        };
+        let (sql, params) = query.sql(Some("abc"), 50);
+        assert_eq!(
+            sql,
+            "SELECT address, data, slot FROM policies WHERE owner = $1 AND asset = $2 \
+             AND status = ANY($3) AND address > $4 ORDER BY address LIMIT $5"
+        );
+        assert_eq!(params.len(), 5);
+
+        let (sql, params) = PolicyQuery::default().sql(None, 10);
+        assert_eq!(
+            sql,
+            "SELECT address, data, slot FROM policies ORDER BY address LIMIT $1"
+        );
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn time_can_only_make_live_policies_terminal() {
+        assert_eq!(candidate_statuses(PolicyStatus::Active), vec!["active"]);
+        assert_eq!(candidate_statuses(PolicyStatus::Claimed), vec!["claimed"]);
+        assert!(candidate_statuses(PolicyStatus::Terminal).contains(&"unfunded"));
+    }
+}
@@ -0,0 +1,46 @@
+// api [CONFIG]: serve the LiqGuard indexer's data over HTTP with the given
+// config file, api.toml by default. See routes.rs for the endpoints
+mod config;
+mod db;
+mod models;
+mod routes;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use config::ApiConfig;
+use db::Db;
+use routes::AppState;
+
+async fn serve(config: ApiConfig) -> Result<(), String> {
+    let db = Db::connect(&config.database_url).await?;
+    let state = Arc::new(AppState {
+        db,
+        max_page_size: config.max_page_size,
+    });
+    let listener = tokio::net::TcpListener::bind(config.listen_addr)
+        .await
+        .map_err(|error| format!("binding {}: {error}", config.listen_addr))?;
+    log::info!("listening on {}", config.listen_addr);
+    axum::serve(listener, routes::router(state))
+        .await
+        .map_err(|error| format!("serving: {error}"))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = PathBuf::from(std::env::args().nth(1).unwrap_or("api.toml".to_string()));
+    let result = match ApiConfig::load(&path) {
+        Ok(config) => serve(config).await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            log::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
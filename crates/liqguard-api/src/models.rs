@@ -0,0 +1,169 @@
+// JSON response bodies. Policies are decoded from the account bytes the
+// indexer stored, so every field and enum comes from the program's own
+// types. Enums serialize as their snake_case names, pubkeys left at the
+// default serialize as null, and prices, which may not fit a JSON double,
+// are decimal strings of their mantissa
+
+use anchor_lang::prelude::Pubkey;
+use liqguard::{
+    BarrierKind, ExerciseStyle, PayoutMode, Policy, TriggerDirection, UnderlyingAsset,
+    STRIKE_EXPONENT,
+};
+use liqguard_client::accounts::PolicyStatus;
+use serde::Serialize;
+
+fn key(key: &Pubkey) -> Option<String> {
+    (*key != Pubkey::default()).then(|| key.to_string())
+}
+
+#[derive(Serialize)]
+pub struct PolicyResponse {
+    pub address: String,
+    pub policy_id: u64,
+    pub version: u8,
+    pub status: &'static str,
+    pub owner: String,
+    pub creator: String,
+    pub beneficiary: String,
+    pub underwriter: Option<String>,
+    pub asset: UnderlyingAsset,
+    pub direction: TriggerDirection,
+    pub strike_price: u64,
+    pub upper_strike_price: Option<u64>,
+    pub strike_exponent: i32, // both strikes are USD at this exponent
+    pub coverage_amount: u64,
+    pub coverage_remaining: u64,
+    pub usd_coverage: bool,
+    pub collateral_mint: Option<String>, // null = native SOL
+    pub premium_amount: u64,
+    pub payout_mode: PayoutMode,
+    pub exercise_style: ExerciseStyle,
+    pub barrier_kind: BarrierKind,
+    pub created_at: i64,
+    pub expiration_datetime: i64,
+    pub is_funded: bool,
+    pub settlement: Option<SettlementResponse>,
+    pub indexed_slot: u64, // slot the indexer read the account at
+}
+
+#[derive(Serialize)]
+pub struct SettlementResponse {
+    pub price: String,
+    pub price_exponent: i32,
+    pub publish_time: i64,
+    pub slot: u64,
+    pub liquidator: String,
+    pub payout: u64,
+    pub bounty: u64,
+}
+
+impl PolicyResponse {
+    // policy as indexed at slot, with its status at now
+    pub fn new(address: &Pubkey, policy: &Policy, slot: u64, now: i64) -> Self {
+        let settlement = (policy.settlement_slot != 0).then(|| SettlementResponse {
+            price: policy.settlement_price.to_string(),
+            price_exponent: policy.settlement_price_exponent,
+            publish_time: policy.settlement_publish_time,
+            slot: policy.settlement_slot,
+            liquidator: policy.settlement_liquidator.to_string(),
+            payout: policy.settlement_payout,
+            bounty: policy.settlement_bounty,
+        });
+        Self {
+            address: address.to_string(),
+            policy_id: policy.policy_id,
+            version: policy.version,
+            status: PolicyStatus::of(policy, now).as_str(),
+            owner: policy.owner.to_string(),
+            creator: policy.creator.to_string(),
+            beneficiary: policy.beneficiary.to_string(),
+            underwriter: key(&policy.underwriter),
+            asset: policy.asset,
+            direction: policy.direction,
+            strike_price: policy.strike_price,
+            upper_strike_price: matches!(policy.direction, TriggerDirection::OutsideRange)
+                .then_some(policy.upper_strike_price),
+            strike_exponent: STRIKE_EXPONENT,
+            coverage_amount: policy.coverage_amount,
+            coverage_remaining: policy.coverage_remaining,
+            usd_coverage: policy.usd_coverage,
+            collateral_mint: key(&policy.collateral_mint),
+            premium_amount: policy.premium_amount,
+            payout_mode: policy.payout_mode,
+            exercise_style: policy.exercise_style,
+            barrier_kind: policy.barrier_kind,
+            created_at: policy.created_at,
+            expiration_datetime: policy.expiration_datetime,
+            is_funded: policy.is_funded,
+            settlement,
+            indexed_slot: slot,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PolicyPage {
+    pub policies: Vec<PolicyResponse>,
+    pub next: Option<String>, // pass as after= for the next page, null = last page
+}
+
+#[derive(Serialize)]
+pub struct ClaimResponse {
+    pub signature: String,
+    pub slot: i64,
+    pub liquidator: String,
+    pub asset: String,
+    pub price: String,
+    pub price_exponent: i32,
+    pub payout: i64,
+    pub bounty: i64,
+    pub coverage_remaining: i64,
+    pub finalized: bool,
+}
+
+#[derive(Serialize)]
+pub struct AssetStats {
+    pub asset: String,
+    pub policies: i64,
+    pub active_policies: i64,
+    pub active_coverage: i64,
+    pub claims: i64,
+    pub total_paid: i64,
+}
+
+#[derive(Serialize)]
+pub struct StatsResponse {
+    pub assets: Vec<AssetStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard_test_support::zeroed_policy;
+
+    #[test]
+    fn policies_serialize_with_names_and_nulls() {
+        let mut policy = zeroed_policy();
+        policy.owner = Pubkey::new_unique();
+        policy.asset = UnderlyingAsset::Sol;
+        policy.coverage_amount = 1_000;
+        policy.coverage_remaining = 1_000;
+        policy.expiration_datetime = 5_000;
+        policy.is_funded = true;
+        let json = serde_json::to_value(PolicyResponse::new(
+            &Pubkey::new_unique(),
+            &policy,
+            9,
+            1_000,
+        ))
+        .unwrap();
+        assert_eq!(json["status"], "active");
+        assert_eq!(json["asset"], "sol");
+        assert_eq!(json["direction"], "protect_long");
+        assert_eq!(json["strike_exponent"], STRIKE_EXPONENT);
+        assert!(json["collateral_mint"].is_null());
+        assert!(json["upper_strike_price"].is_null());
+        assert!(json["settlement"].is_null());
+        assert_eq!(json["indexed_slot"], 9);
+    }
+}
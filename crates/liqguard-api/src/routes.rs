@@ -0,0 +1,189 @@
+// HTTP routes. Every response is JSON; failures are {"error": "..."} with
+// a 400 for bad parameters, 404 for missing policies and 500 otherwise
+//
+//   GET /policies?owner=&beneficiary=&underwriter=&asset=&status=&after=&limit=
+//   GET /policies/{address}
+//   GET /policies/{address}/claims
+//   GET /stats
+//   GET /healthz
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use liqguard::UnderlyingAsset;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::db::{Db, PolicyQuery};
+use crate::models::{ClaimResponse, PolicyPage, PolicyResponse, StatsResponse};
+
+pub struct AppState {
+    pub db: Db,
+    pub max_page_size: usize,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound,
+    Internal(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            ApiError::Internal(message) => {
+                log::error!("{message}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal error".to_string(),
+                )
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+fn pubkey(name: &str, text: &str) -> Result<Pubkey, ApiError> {
+    text.parse()
+        .map_err(|_| ApiError::BadRequest(format!("{name} is not a pubkey: {text:?}")))
+}
+
+fn optional_pubkey(name: &str, text: &Option<String>) -> Result<Option<Pubkey>, ApiError> {
+    text.as_deref().map(|text| pubkey(name, text)).transpose()
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListParams {
+    pub owner: Option<String>,
+    pub beneficiary: Option<String>,
+    pub underwriter: Option<String>,
+    pub asset: Option<UnderlyingAsset>,
+    pub status: Option<String>,
+    pub after: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl ListParams {
+    // The query and page size the parameters ask for, the page size capped
+    // at max_page_size
+    pub fn query(&self, max_page_size: usize) -> Result<(PolicyQuery, usize), ApiError> {
+        let status = self
+            .status
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map_err(ApiError::BadRequest)?;
+        let query = PolicyQuery {
+            owner: optional_pubkey("owner", &self.owner)?,
+            beneficiary: optional_pubkey("beneficiary", &self.beneficiary)?,
+            underwriter: optional_pubkey("underwriter", &self.underwriter)?,
+            asset: self.asset,
+            status,
+            after: optional_pubkey("after", &self.after)?,
+        };
+        let limit = self.limit.unwrap_or(max_page_size).clamp(1, max_page_size);
+        Ok((query, limit))
+    }
+}
+
+async fn list_policies(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ListParams>,
+) -> ApiResult<PolicyPage> {
+    let (query, limit) = params.query(state.max_page_size)?;
+    let page = state.db.policies(&query, limit, unix_now()).await;
+    page.map(Json).map_err(ApiError::Internal)
+}
+
+async fn get_policy(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> ApiResult<PolicyResponse> {
+    let address = pubkey("address", &address)?;
+    match state.db.policy(&address, unix_now()).await {
+        Ok(Some(policy)) => Ok(Json(policy)),
+        Ok(None) => Err(ApiError::NotFound),
+        Err(error) => Err(ApiError::Internal(error)),
+    }
+}
+
+async fn policy_claims(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> ApiResult<Vec<ClaimResponse>> {
+    let address = pubkey("address", &address)?;
+    state
+        .db
+        .claims(&address)
+        .await
+        .map(Json)
+        .map_err(ApiError::Internal)
+}
+
+async fn stats(State(state): State<Arc<AppState>>) -> ApiResult<StatsResponse> {
+    state
+        .db
+        .stats()
+        .await
+        .map(|assets| Json(StatsResponse { assets }))
+        .map_err(ApiError::Internal)
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/policies", get(list_policies))
+        .route("/policies/:address", get(get_policy))
+        .route("/policies/:address/claims", get(policy_claims))
+        .route("/stats", get(stats))
+        .route("/healthz", get(|| async { "ok" }))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard_client::accounts::PolicyStatus;
+
+    #[test]
+    fn list_params_parse_and_cap_the_page() {
+        let owner = Pubkey::new_unique();
+        let params = ListParams {
+            owner: Some(owner.to_string()),
+            status: Some("active".to_string()),
+            limit: Some(500),
+            ..ListParams::default()
+        };
+        let (query, limit) = params.query(100).unwrap();
+        assert_eq!(query.owner, Some(owner));
+        assert_eq!(query.status, Some(PolicyStatus::Active));
+        assert_eq!(limit, 100);
+
+        let bad = |params: ListParams| matches!(params.query(100), Err(ApiError::BadRequest(_)));
+        assert!(bad(ListParams {
+            status: Some("open".to_string()),
+            ..ListParams::default()
+        }));
+        assert!(bad(ListParams {
+            after: Some("nope".to_string()),
+            ..ListParams::default()
+        }));
+    }
+}
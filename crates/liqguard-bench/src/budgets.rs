@@ -0,0 +1,169 @@
+// budgets.toml: the most compute each scenario may consume. A measurement
+// over its budget is a regression; one without a budget, or a budget
+// nothing measured, means the file and the scenarios have drifted apart.
+// --update rewrites the file from a run, with HEADROOM_BPS to spare
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::harness::Measurement;
+
+pub const HEADROOM_BPS: u64 = 500;
+
+const HEADER: &str = "\
+# Compute-unit budgets per benchmark scenario, checked by liqguard-bench
+# against the built program. Regenerate after an intended change with
+#   cargo run -p liqguard-bench -- --update
+";
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budgets {
+    pub budgets: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Finding {
+    OverBudget {
+        name: String,
+        units: u64,
+        budget: u64,
+    },
+    Unbudgeted {
+        name: String,
+        units: u64,
+    },
+    Unmeasured {
+        name: String,
+    },
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::OverBudget {
+                name,
+                units,
+                budget,
+            } => {
+                write!(f, "{name}: {units} CU is over its budget of {budget}")
+            }
+            Finding::Unbudgeted { name, units } => {
+                write!(f, "{name}: {units} CU has no budget")
+            }
+            Finding::Unmeasured { name } => write!(f, "{name}: budgeted but never measured"),
+        }
+    }
+}
+
+impl Budgets {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("reading {}: {error}", path.display()))?;
+        toml::from_str(&text).map_err(|error| format!("parsing {}: {error}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let body = toml::to_string(self).map_err(|error| error.to_string())?;
+        std::fs::write(path, format!("{HEADER}\n{body}"))
+            .map_err(|error| format!("writing {}: {error}", path.display()))
+    }
+
+    // Budgets measurements fit with HEADROOM_BPS to spare, rounded up to
+    // the next 100 units
+    pub fn from_measurements(measurements: &[Measurement]) -> Self {
+        let budgets = measurements
+            .iter()
+            .map(|measurement| {
+                let padded = measurement.units * (10_000 + HEADROOM_BPS) / 10_000;
+                (measurement.name.clone(), padded.div_ceil(100) * 100)
+            })
+            .collect();
+        Self { budgets }
+    }
+
+    pub fn check(&self, measurements: &[Measurement]) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for Measurement { name, units } in measurements {
+            match self.budgets.get(name) {
+                Some(&budget) if *units > budget => findings.push(Finding::OverBudget {
+                    name: name.clone(),
+                    units: *units,
+                    budget,
+                }),
+                Some(_) => {}
+                None => findings.push(Finding::Unbudgeted {
+                    name: name.clone(),
+                    units: *units,
+                }),
+            }
+        }
+        for name in self.budgets.keys() {
+            if !measurements
+                .iter()
+                .any(|measurement| &measurement.name == name)
+            {
+                findings.push(Finding::Unmeasured { name: name.clone() });
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(name: &str, units: u64) -> Measurement {
+        Measurement {
+            name: name.to_string(),
+            units,
+        }
+    }
+
+    #[test]
+    fn checks_flag_regressions_and_drift() {
+        let budgets = Budgets {
+            budgets: BTreeMap::from([
+                ("settle".to_string(), 20_000),
+                ("quote".to_string(), 10_000),
+                ("close_policy".to_string(), 5_000),
+            ]),
+        };
+        let findings = budgets.check(&[
+            measurement("settle", 20_001),
+            measurement("quote", 10_000),
+            measurement("fund_vault", 3_000),
+        ]);
+        assert_eq!(
+            findings,
+            vec![
+                Finding::OverBudget {
+                    name: "settle".to_string(),
+                    units: 20_001,
+                    budget: 20_000
+                },
+                Finding::Unbudgeted {
+                    name: "fund_vault".to_string(),
+                    units: 3_000
+                },
+                Finding::Unmeasured {
+                    name: "close_policy".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn updates_pad_and_round_up() {
+        let budgets = Budgets::from_measurements(&[measurement("settle", 20_001)]);
+        assert_eq!(budgets.budgets["settle"], 21_100);
+        assert!(budgets.check(&[measurement("settle", 21_100)]).is_empty());
+
+        let text = toml::to_string(&budgets).unwrap();
+        assert_eq!(toml::from_str::<Budgets>(&text).unwrap(), budgets);
+    }
+}
@@ -0,0 +1,260 @@
+// A solana-program-test bank with liqguard loaded, either as the built
+// SBF program, where every instruction is metered, or natively, where
+// scenarios run the same code unmetered. Prices are Pyth fixtures written
+// straight into the bank, and the clock moves only when warped
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anchor_lang::prelude::{AccountInfo, Clock, Pubkey};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::AccountDeserialize;
+use liqguard::UnderlyingAsset;
+use liqguard_client::errors::ProgramError;
+use liqguard_test_support::{Fixture, PriceUpdateBuilder};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{AccountSharedData, ReadableAccount};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+// Every transaction asks for the most a transaction may use, so an
+// instruction past the default 200k limit is measured, not cut off
+pub const COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+// Prices are published at PRICE_EXPONENT
+pub const PRICE_EXPONENT: i32 = -8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Native,
+    Sbf,
+}
+
+// Units one scenario's instruction consumed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub name: String,
+    pub units: u64,
+}
+
+pub struct Bench {
+    pub context: ProgramTestContext,
+    pub payer: Keypair,
+    pub measurements: Vec<Measurement>,
+    mode: Mode,
+    price_feeds: Vec<(UnderlyingAsset, Pubkey)>,
+    sent: HashSet<Signature>,
+}
+
+// Anchor's entrypoint wants its accounts to live as long as their data;
+// processor! hands over a shorter borrow, so the list is leaked per call
+fn process_native(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    liqguard::entry(program_id, accounts, data)
+}
+
+impl Bench {
+    // A bank with liqguard loaded natively
+    pub async fn native() -> Self {
+        let program_test = ProgramTest::new("liqguard", liqguard::ID, processor!(process_native));
+        Self::start(program_test, Mode::Native).await
+    }
+
+    // A bank running program, a liqguard.so built by `anchor build`
+    pub async fn sbf(program: &Path) -> Result<Self, String> {
+        if !program.is_file() {
+            return Err(format!(
+                "{} not found; build the program with `anchor build` first",
+                program.display()
+            ));
+        }
+        let directory = program.parent().unwrap_or(Path::new("."));
+        std::env::set_var("SBF_OUT_DIR", directory);
+        let mut program_test = ProgramTest::new("liqguard", liqguard::ID, None);
+        program_test.prefer_bpf(true);
+        Ok(Self::start(program_test, Mode::Sbf).await)
+    }
+
+    async fn start(program_test: ProgramTest, mode: Mode) -> Self {
+        let context = program_test.start_with_context().await;
+        let payer = context.payer.insecure_clone();
+        Self {
+            context,
+            payer,
+            measurements: Vec::new(),
+            mode,
+            price_feeds: Vec::new(),
+            sent: HashSet::new(),
+        }
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .expect("clock sysvar")
+    }
+
+    // Move the clock seconds forward
+    pub async fn warp(&mut self, seconds: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    // Publish a fresh asset price, in whole USD, and return its account
+    pub async fn set_price(&mut self, asset: UnderlyingAsset, usd: i64) -> Pubkey {
+        let now = self.now().await;
+        let known = self.price_feeds.iter().find(|(known, _)| *known == asset);
+        let address = match known {
+            Some((_, address)) => *address,
+            None => {
+                let address = Pubkey::new_unique();
+                self.price_feeds.push((asset, address));
+                address
+            }
+        };
+        let mantissa = usd * 10i64.pow(PRICE_EXPONENT.unsigned_abs());
+        let fixture = PriceUpdateBuilder::asset(asset)
+            .price(mantissa, PRICE_EXPONENT)
+            .conf(mantissa as u64 / 10_000)
+            .ema(mantissa, mantissa as u64 / 10_000)
+            .publish_time(now);
+        self.context
+            .set_account(&address, &AccountSharedData::from(fixture.account()));
+        address
+    }
+
+    // A funded keypair
+    pub async fn funded(&mut self, sol: u64) -> Keypair {
+        let keypair = Keypair::new();
+        let transfer = system_instruction::transfer(
+            &self.payer.pubkey(),
+            &keypair.pubkey(),
+            sol * LAMPORTS_PER_SOL,
+        );
+        self.send(&[transfer], &[]).await.expect("funding transfer");
+        keypair
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .expect("account fetch")
+            .unwrap_or_else(|| panic!("account {address} missing"));
+        T::try_deserialize(&mut account.data()).expect("account decodes")
+    }
+
+    // Send instructions, signed by the payer and signers, returning the
+    // units liqguard consumed across them
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<u64, String> {
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            COMPUTE_UNIT_LIMIT,
+        )];
+        all.extend_from_slice(instructions);
+        let mut keypairs = vec![&self.payer];
+        keypairs.extend_from_slice(signers);
+        let mut transaction = Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.payer.pubkey()),
+            &keypairs,
+            self.context.last_blockhash,
+        );
+        // A repeat of an earlier transaction would be dropped as a duplicate
+        if self.sent.contains(&transaction.signatures[0]) {
+            let blockhash = self
+                .context
+                .get_new_latest_blockhash()
+                .await
+                .map_err(|error| error.to_string())?;
+            transaction.sign(&keypairs, blockhash);
+        }
+        self.sent.insert(transaction.signatures[0]);
+
+        let outcome = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .map_err(|error| error.to_string())?;
+        let (logs, units) = outcome
+            .metadata
+            .map(|metadata| (metadata.log_messages, metadata.compute_units_consumed))
+            .unwrap_or_default();
+        if let Err(error) = outcome.result {
+            let reason = ProgramError::parse(&error, Some(&logs))
+                .map_or(error.to_string(), |error| error.to_string());
+            return Err(reason);
+        }
+        Ok(match self.mode {
+            Mode::Sbf => consumed(&logs),
+            Mode::Native => units,
+        })
+    }
+
+    // Send one liqguard instruction and record what it consumed as name
+    pub async fn measure(
+        &mut self,
+        name: &str,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<u64, String> {
+        let units = self
+            .send(&[instruction], signers)
+            .await
+            .map_err(|error| format!("{name}: {error}"))?;
+        self.measurements.push(Measurement {
+            name: name.to_string(),
+            units,
+        });
+        Ok(units)
+    }
+}
+
+// Units liqguard reported consuming, from the runtime's "Program <id>
+// consumed N of M compute units" lines
+fn consumed(logs: &[String]) -> u64 {
+    let prefix = format!("Program {} consumed ", liqguard::ID);
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(&prefix)?.split_once(' '))
+        .filter_map(|(units, _)| units.parse::<u64>().ok())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consumed_sums_liqguard_lines_only() {
+        let logs = vec![
+            format!("Program {} invoke [1]", liqguard::ID),
+            "Program 11111111111111111111111111111111 invoke [2]".to_string(),
+            "Program 11111111111111111111111111111111 success".to_string(),
+            format!(
+                "Program {} consumed 12345 of 1399850 compute units",
+                liqguard::ID
+            ),
+            "Program ComputeBudget111111111111111111111111111111 consumed 150 of 1400000 compute units"
+                .to_string(),
+        ];
+        assert_eq!(consumed(&logs), 12_345);
+        assert_eq!(consumed(&[]), 0);
+    }
+}
@@ -0,0 +1,9 @@
+// Compute-unit benchmarks for LiqGuard. scenarios drives the program's
+// instructions through solana-program-test against representative account
+// states and harness records what each consumed. The liqguard-bench binary
+// runs them on the built program and holds the results to budgets.toml;
+// this crate's tests run the same scenarios natively, where nothing is
+// metered, so they keep working between benchmark runs
+pub mod budgets;
+pub mod harness;
+pub mod scenarios;
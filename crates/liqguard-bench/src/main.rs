@@ -0,0 +1,89 @@
+// liqguard-bench [--program PATH] [--budgets PATH] [--update]
+//
+// Measures every scenario on the built program (target/deploy/liqguard.so
+// by default) and exits non-zero if any goes over its budget in
+// budgets.toml. --update writes the measurements back as the new budgets
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use liqguard_bench::budgets::Budgets;
+use liqguard_bench::harness::Bench;
+use liqguard_bench::scenarios;
+
+struct Args {
+    program: PathBuf,
+    budgets: PathBuf,
+    update: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        program: PathBuf::from("target/deploy/liqguard.so"),
+        budgets: PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/budgets.toml")),
+        update: false,
+    };
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--program" => args.program = argv.next().ok_or("--program needs a path")?.into(),
+            "--budgets" => args.budgets = argv.next().ok_or("--budgets needs a path")?.into(),
+            "--update" => args.update = true,
+            other => return Err(format!("unknown argument {other:?}")),
+        }
+    }
+    Ok(args)
+}
+
+async fn run(args: Args) -> Result<bool, String> {
+    let mut bench = Bench::sbf(&args.program).await?;
+    scenarios::run(&mut bench).await?;
+    let measurements = bench.measurements;
+
+    if args.update {
+        Budgets::from_measurements(&measurements).save(&args.budgets)?;
+        println!(
+            "wrote {} budgets to {}",
+            measurements.len(),
+            args.budgets.display()
+        );
+        return Ok(true);
+    }
+    let budgets = Budgets::load(&args.budgets)?;
+    let width = measurements
+        .iter()
+        .map(|measurement| measurement.name.len())
+        .max()
+        .unwrap_or(0);
+    for measurement in &measurements {
+        let budget = budgets
+            .budgets
+            .get(&measurement.name)
+            .map_or("-".to_string(), u64::to_string);
+        println!(
+            "{:width$}  {:>9}  {:>9}",
+            measurement.name, measurement.units, budget
+        );
+    }
+    let findings = budgets.check(&measurements);
+    for finding in &findings {
+        eprintln!("{finding}");
+    }
+    Ok(findings.is_empty())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let result = match parse_args() {
+        Ok(args) => run(args).await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
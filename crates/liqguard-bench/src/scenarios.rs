@@ -0,0 +1,1640 @@
+// The benchmarked workload: one protocol taken from initialize_config
+// through opening, settling and closing policies, measuring each
+// instruction on the way. States that change an instruction's cost are
+// measured separately, e.g. opening into an empty keeper queue and into one
+// slot short of full, settling with and without the audit log and claim
+// history, with a split payout or under a master agreement's aggregate
+// limit, and batches of one and of several policies, and one reading the
+// cached price.
+//
+// Not covered: policy books and compressed policies, SPL and LST
+// collateral, USD coverage, swaps, Wormhole premiums, Lazer, Switchboard,
+// Drift and Kamino triggers, automation, disputes, the backstop's draws
+// and repayments, and account migration
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use liqguard::{
+    accounts, instruction, BarrierKind, ConfidenceAdjustment, ConfigParams, CoverageRequest,
+    CoverageRequestParams, ExerciseStyle, FeedRegistryParams, InitializePolicyParams,
+    MasterAgreement, MasterAgreementParams, PayoutMode, PayoutShare, Policy, QuoteParams,
+    TriggerDirection, UnderlyingAsset, ABANDONED_VAULT_GRACE_PERIOD, KEEPER_QUEUE_CAPACITY,
+    MAX_ALLOWLISTED_LIQUIDATORS, SETTLEMENT_PRICE_WINDOW,
+};
+use liqguard_client::instructions::{self, InitializePolicyAccounts, LiquidateOptions};
+use liqguard_common::seeds::{self, PENDING_CONFIG_SEED};
+use liqguard_common::OracleSource;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::harness::{Bench, PRICE_EXPONENT};
+
+const ASSET: UnderlyingAsset = UnderlyingAsset::Btc;
+const SPOT: i64 = 100_000; // USD
+const COVERAGE: u64 = LAMPORTS_PER_SOL / 10;
+const DAY: i64 = 86_400;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: liqguard::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// USD at the strike exponent the program stores strikes in
+fn usd(dollars: i64) -> u64 {
+    dollars as u64 * 10u64.pow(PRICE_EXPONENT.unsigned_abs())
+}
+
+fn config_params(authority: Pubkey) -> ConfigParams {
+    ConfigParams {
+        max_confidence_bps: 200,
+        bounty_bps: 50,
+        bounty_flat_lamports: 5_000,
+        treasury: seeds::treasury(&liqguard::ID).0,
+        protocol_fee_bps: 500,
+        default_max_price_age: 60,
+        max_coverage_amount: 0,
+        max_owner_coverage: 0,
+        strike_update_delay: 0,
+        min_policy_age: 0,
+        max_price_deviation_bps: 0,
+        deviation_confirm_delay: 0,
+        volatility_bps: 6_000,
+        premium_markup_bps: 1_000,
+        backstop_fee_bps: 0,
+        max_backstop_draw: 0,
+        liquidator_allowlist: [Pubkey::default(); MAX_ALLOWLISTED_LIQUIDATORS],
+        config_change_delay: 0,
+        wormhole_emitter_chain: 0,
+        wormhole_emitter_address: [0; 32],
+        arbiter: Pubkey::default(),
+        dispute_window: 0,
+        dispute_holdback_bps: 0,
+        payout_fee_bps: 0,
+        risk_oracle: authority,
+        max_volatility_age: 0,
+        usdc_mint: Pubkey::default(),
+        max_swap_slippage_bps: 0,
+        compliance_mint: Pubkey::default(),
+        utilization_kink_bps: 0,
+        kink_markup_bps: 0,
+        full_utilization_markup_bps: 0,
+    }
+}
+
+fn feed_params() -> FeedRegistryParams {
+    let mut symbol = [0u8; 16];
+    symbol[..7].copy_from_slice(b"BTC/USD");
+    FeedRegistryParams {
+        symbol,
+        feed_id: ASSET.feed_id(),
+        max_price_age: 60,
+        max_confidence_bps: 200,
+        enabled: true,
+        oracle_source: OracleSource::PythPull,
+        oracle_account: Pubkey::default(),
+        lazer_feed_id: 0,
+    }
+}
+
+// A binary ProtectLong policy on ASSET paying COVERAGE at or below strike
+fn policy_params(
+    policy_id: u64,
+    strike: i64,
+    expiration: i64,
+    underwriter: Pubkey,
+) -> InitializePolicyParams {
+    InitializePolicyParams {
+        policy_id,
+        asset: ASSET,
+        strike_price: usd(strike),
+        direction: TriggerDirection::ProtectLong,
+        upper_strike_price: 0,
+        inclusive_strike: true,
+        trigger_buffer_bps: 0,
+        confidence_adjustment: ConfidenceAdjustment::None,
+        confidence_multiplier_bps: 0,
+        drawdown_bps: 0,
+        coverage_amount: COVERAGE,
+        usd_coverage: false,
+        lst_stake_pool: Pubkey::default(),
+        expiration_datetime: expiration,
+        max_price_age: 0,
+        payout_mode: PayoutMode::Binary,
+        payout_slope_bps: 0,
+        payout_floor_bps: 0,
+        payout_cap_bps: 0,
+        collateral_mint: Pubkey::default(),
+        premium_amount: COVERAGE, // at least any quote, which caps at coverage
+        underwriter,
+        beneficiary: Pubkey::default(),
+        usdc_payout_account: Pubkey::default(),
+        twap_window_seconds: 0,
+        switchboard_feed: Pubkey::default(),
+        oracle_tolerance_bps: 0,
+        chainlink_feed: Pubkey::default(),
+        roll_period_seconds: 0,
+        drift_user: Pubkey::default(),
+        drift_perp_market_index: 0,
+        kamino_obligation: Pubkey::default(),
+        health_threshold_bps: 0,
+        vesting_period_seconds: 0,
+        immediate_payout_bps: 0,
+        exercise_style: ExerciseStyle::American,
+        barrier_kind: BarrierKind::None,
+        barrier_price: 0,
+        premium_rate: 0,
+        stream_deposit: 0,
+        permissioned_liquidation: false,
+        premium_payment_id: [0; 32],
+        wormhole_premium: 0,
+        compliance_mint: Pubkey::default(),
+        breach_dwell_seconds: 0,
+        short_strike_price: 0,
+    }
+}
+
+// The accounts and keys every step shares
+struct Protocol {
+    admin: Keypair,
+    owner: Keypair,
+    underwriter: Keypair,
+    price_feed: Pubkey,
+    next_policy_id: u64,
+}
+
+impl Protocol {
+    // Open and fund a policy of the owner's, measuring the open as name
+    // and the funding as fund_name when they're given
+    async fn open(
+        &mut self,
+        bench: &mut Bench,
+        params: InitializePolicyParams,
+        name: Option<&str>,
+        fund_name: Option<&str>,
+    ) -> Result<(Pubkey, Policy), String> {
+        let owner = self.owner.pubkey();
+        let policy_id = params.policy_id;
+        let open = instructions::initialize_policy(
+            InitializePolicyAccounts {
+                owner,
+                payer: owner,
+                treasury: seeds::treasury(&liqguard::ID).0,
+                price_feed: self.price_feed,
+                backstop: false,
+                portfolio_vault: false,
+                compliance_mint: Pubkey::default(),
+            },
+            params,
+        );
+        let signers = [&self.owner, &self.underwriter];
+        match name {
+            Some(name) => bench.measure(name, open, &signers).await?,
+            None => bench.send(&[open], &signers).await?,
+        };
+        self.next_policy_id += 1;
+
+        let address = seeds::policy(&liqguard::ID, &owner, policy_id).0;
+        let policy: Policy = bench.account(address).await;
+        let fund = instructions::fund_vault(address, &policy, COVERAGE, None, Default::default())
+            .map_err(|error| error.to_string())?;
+        match fund_name {
+            Some(name) => bench.measure(name, fund, &[&self.underwriter]).await?,
+            None => bench.send(&[fund], &[&self.underwriter]).await?,
+        };
+        Ok((address, bench.account(address).await))
+    }
+
+    fn params(&self, strike: i64, expiration: i64) -> InitializePolicyParams {
+        policy_params(
+            self.next_policy_id,
+            strike,
+            expiration,
+            self.underwriter.pubkey(),
+        )
+    }
+}
+
+// Run every scenario on bench, leaving its measurements there
+pub async fn run(bench: &mut Bench) -> Result<(), String> {
+    let mut protocol = Protocol {
+        admin: bench.payer.insecure_clone(),
+        owner: bench.funded(1_000).await,
+        underwriter: bench.funded(1_000).await,
+        price_feed: bench.set_price(ASSET, SPOT).await,
+        next_policy_id: 0,
+    };
+    admin(bench, &protocol).await?;
+    policies(bench, &mut protocol).await?;
+    lifecycle(bench, &mut protocol).await?;
+    underwriting(bench, &protocol).await?;
+    requests(bench, &mut protocol).await?;
+    abandonment(bench, &mut protocol).await
+}
+
+async fn admin(bench: &mut Bench, protocol: &Protocol) -> Result<(), String> {
+    let admin = &protocol.admin;
+    let authority = admin.pubkey();
+    let program_id = &liqguard::ID;
+    let config = seeds::config(program_id).0;
+    let pending_config_change = Pubkey::find_program_address(&[PENDING_CONFIG_SEED], program_id).0;
+    let params = config_params(authority);
+
+    bench
+        .measure(
+            "initialize_config",
+            build(
+                accounts::InitializeConfig {
+                    config,
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializeConfig {
+                    params: params.clone(),
+                },
+            ),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "initialize_treasury",
+            build(
+                accounts::InitializeTreasury {
+                    treasury: seeds::treasury(program_id).0,
+                    config,
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializeTreasury {},
+            ),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "initialize_backstop",
+            build(
+                accounts::InitializeBackstop {
+                    backstop: seeds::backstop(program_id).0,
+                    config,
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializeBackstop {},
+            ),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "initialize_audit_log",
+            build(
+                accounts::InitializeAuditLog {
+                    audit_log: seeds::audit_log(program_id).0,
+                    config,
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializeAuditLog {},
+            ),
+            &[],
+        )
+        .await?;
+
+    let propose = build(
+        accounts::ProposeConfigChange {
+            pending_config_change,
+            config,
+            authority,
+            payer: authority,
+            system_program: system_program::ID,
+        },
+        instruction::ProposeConfigChange {
+            params: params.clone(),
+        },
+    );
+    let resolve = || accounts::ApplyConfigChange {
+        pending_config_change,
+        config,
+        authority,
+    };
+    bench
+        .measure("propose_config_change", propose.clone(), &[])
+        .await?;
+    bench
+        .measure(
+            "apply_config_change",
+            build(resolve(), instruction::ApplyConfigChange {}),
+            &[],
+        )
+        .await?;
+    bench.send(&[propose], &[]).await?;
+    bench
+        .measure(
+            "cancel_config_change",
+            build(resolve(), instruction::CancelConfigChange {}),
+            &[],
+        )
+        .await?;
+
+    let set_paused = |paused| {
+        build(
+            accounts::UpdateConfig { config, authority },
+            instruction::SetPaused { paused },
+        )
+    };
+    bench.measure("set_paused", set_paused(true), &[]).await?;
+    bench.send(&[set_paused(false)], &[]).await?;
+
+    let feed_registry = seeds::feed_registry(program_id, ASSET).0;
+    bench
+        .measure(
+            "register_feed",
+            build(
+                accounts::RegisterFeed {
+                    feed_registry,
+                    config,
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::RegisterFeed {
+                    asset: ASSET,
+                    params: feed_params(),
+                },
+            ),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "update_feed",
+            build(
+                accounts::UpdateFeed {
+                    feed_registry,
+                    config,
+                    authority,
+                },
+                instruction::UpdateFeed {
+                    params: feed_params(),
+                },
+            ),
+            &[],
+        )
+        .await?;
+    let set_feed_enabled = |enabled| {
+        build(
+            accounts::UpdateFeed {
+                feed_registry,
+                config,
+                authority,
+            },
+            instruction::SetFeedEnabled { enabled },
+        )
+    };
+    bench
+        .measure("set_feed_enabled", set_feed_enabled(false), &[])
+        .await?;
+    bench.send(&[set_feed_enabled(true)], &[]).await?;
+    bench
+        .measure(
+            "update_volatility",
+            build(
+                accounts::UpdateVolatility {
+                    feed_registry,
+                    config,
+                    risk_oracle: authority,
+                },
+                instruction::UpdateVolatility {
+                    volatility_bps: 6_500,
+                },
+            ),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "initialize_keeper_queue",
+            build(
+                accounts::InitializeKeeperQueue {
+                    keeper_queue: seeds::keeper_queue(program_id, ASSET).0,
+                    config,
+                    authority,
+                    payer: authority,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializeKeeperQueue { asset: ASSET },
+            ),
+            &[],
+        )
+        .await?;
+    Ok(())
+}
+
+async fn policies(bench: &mut Bench, protocol: &mut Protocol) -> Result<(), String> {
+    let program_id = &liqguard::ID;
+    let config = seeds::config(program_id).0;
+    let feed_registry = seeds::feed_registry(program_id, ASSET).0;
+    let now = bench.now().await;
+    let expiration = now + 30 * DAY;
+    let strike = SPOT * 9 / 10;
+
+    bench
+        .measure(
+            "quote",
+            build(
+                accounts::Quote {
+                    config,
+                    feed_registry,
+                    price_feed: protocol.price_feed,
+                    portfolio_vault: None,
+                },
+                instruction::Quote {
+                    params: QuoteParams {
+                        asset: ASSET,
+                        strike_price: usd(strike),
+                        direction: TriggerDirection::ProtectLong,
+                        upper_strike_price: 0,
+                        coverage_amount: COVERAGE,
+                        expiration_datetime: expiration,
+                        exercise_style: ExerciseStyle::American,
+                    },
+                },
+            ),
+            &[],
+        )
+        .await?;
+
+    let owner = protocol.owner.pubkey();
+    bench
+        .measure(
+            "initialize_claim_history",
+            build(
+                accounts::InitializeClaimHistory {
+                    claim_history: seeds::claim_history(program_id, &owner).0,
+                    owner,
+                    payer: owner,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializeClaimHistory {},
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    let params = protocol.params(strike, expiration);
+    let (first, first_policy) = protocol
+        .open(
+            bench,
+            params,
+            Some("initialize_policy"),
+            Some("fund_vault/empty_queue"),
+        )
+        .await?;
+    let eligibility = |policy: Pubkey, price_feed| {
+        build(
+            accounts::CheckLiquidationEligibility {
+                policy,
+                config,
+                feed_registry,
+                price_feed,
+                twap_update: None,
+                switchboard_feed: None,
+                drift_user: None,
+                kamino_obligation: None,
+            },
+            instruction::CheckLiquidationEligibility {},
+        )
+    };
+    bench
+        .measure(
+            "check_liquidation_eligibility/out_of_the_money",
+            eligibility(first, protocol.price_feed),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "top_up_coverage",
+            build(
+                accounts::TopUpCoverage {
+                    policy: first,
+                    keeper_queue: seeds::keeper_queue(program_id, ASSET).0,
+                    vault: seeds::vault(program_id, &owner, first_policy.policy_id).0,
+                    config,
+                    depositor: protocol.underwriter.pubkey(),
+                    owner_state: seeds::owner_state(program_id, &owner).0,
+                    token_vault: None,
+                    depositor_token_account: None,
+                    token_program: None,
+                    system_program: system_program::ID,
+                },
+                instruction::TopUpCoverage { amount: COVERAGE },
+            ),
+            &[&protocol.underwriter],
+        )
+        .await?;
+    bench
+        .measure(
+            "set_beneficiary",
+            build(
+                accounts::SetBeneficiary {
+                    policy: first,
+                    config,
+                    beneficiary_credential: None,
+                    owner,
+                },
+                instruction::SetBeneficiary { beneficiary: owner },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    // A policy paying 60/40 to two recipients
+    let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let params = protocol.params(strike, expiration);
+    let (split, _) = protocol.open(bench, params, None, None).await?;
+    bench
+        .measure(
+            "set_payout_split",
+            build(
+                accounts::SetBeneficiary {
+                    policy: split,
+                    config,
+                    beneficiary_credential: None,
+                    owner,
+                },
+                instruction::SetPayoutSplit {
+                    shares: vec![
+                        PayoutShare {
+                            recipient: recipients[0],
+                            share_bps: 6_000,
+                        },
+                        PayoutShare {
+                            recipient: recipients[1],
+                            share_bps: 4_000,
+                        },
+                    ],
+                },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    // Policies the settlements below pay out, each opened with the first
+    let mut triggered = vec![(first, bench.account::<Policy>(first).await)];
+    for _ in 0..6 {
+        let params = protocol.params(strike, expiration);
+        triggered.push(protocol.open(bench, params, None, None).await?);
+    }
+    let params = InitializePolicyParams {
+        exercise_style: ExerciseStyle::European,
+        ..protocol.params(strike, now + DAY)
+    };
+    let (european, _) = protocol.open(bench, params, None, None).await?;
+
+    // A policy under a master agreement whose aggregate limit is half its
+    // coverage
+    let params = protocol.params(strike, expiration);
+    let (member, _) = protocol.open(bench, params, None, None).await?;
+    let master_agreement = seeds::master_agreement(program_id, &owner, 0).0;
+    bench
+        .measure(
+            "initialize_master_agreement",
+            build(
+                accounts::InitializeMasterAgreement {
+                    master_agreement,
+                    counterparty: owner,
+                    underwriter: protocol.underwriter.pubkey(),
+                    payer: owner,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializeMasterAgreement {
+                    params: MasterAgreementParams {
+                        agreement_id: 0,
+                        collateral_mint: Pubkey::default(),
+                        usd_coverage: false,
+                        aggregate_limit: COVERAGE / 2,
+                        expiration_datetime: expiration,
+                    },
+                },
+            ),
+            &[&protocol.owner, &protocol.underwriter],
+        )
+        .await?;
+    bench
+        .measure(
+            "join_master_agreement",
+            build(
+                accounts::JoinMasterAgreement {
+                    policy: member,
+                    master_agreement,
+                    owner,
+                },
+                instruction::JoinMasterAgreement {},
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    // A policy that pays only on a breach sustained for a minute
+    let params = InitializePolicyParams {
+        breach_dwell_seconds: 60,
+        ..protocol.params(strike, expiration)
+    };
+    let (dwell, _) = protocol.open(bench, params, None, None).await?;
+
+    // A put spread whose short leg sits 2,000 under its long strike
+    let params = InitializePolicyParams {
+        payout_mode: PayoutMode::Spread,
+        short_strike_price: usd(strike - 2_000),
+        ..protocol.params(strike, expiration)
+    };
+    let (spread, _) = protocol.open(bench, params, None, None).await?;
+
+    // The transferred policy goes to a new owner, whose stats are opened
+    let params = protocol.params(strike / 2, expiration);
+    let (transferred, _) = protocol.open(bench, params, None, None).await?;
+    let new_owner = Pubkey::new_unique();
+    bench
+        .measure(
+            "transfer_policy",
+            build(
+                accounts::TransferPolicy {
+                    policy: transferred,
+                    owner_state: seeds::owner_state(program_id, &owner).0,
+                    new_owner_state: seeds::owner_state(program_id, &new_owner).0,
+                    config,
+                    owner,
+                    payer: owner,
+                    system_program: system_program::ID,
+                },
+                instruction::TransferPolicy { new_owner },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    // Fill the queue to one short of capacity with policies far out of the
+    // money, then fund one into its last slot
+    let queue: liqguard::KeeperQueue = bench
+        .account(seeds::keeper_queue(program_id, ASSET).0)
+        .await;
+    for _ in queue.entries.len()..KEEPER_QUEUE_CAPACITY - 1 {
+        let params = protocol.params(SPOT / 4, expiration);
+        protocol.open(bench, params, None, None).await?;
+    }
+    let params = protocol.params(SPOT / 4, expiration);
+    protocol
+        .open(bench, params, None, Some("fund_vault/full_queue"))
+        .await?;
+
+    // The price falls through the strike
+    let price_feed = bench.set_price(ASSET, strike - 1_000).await;
+    bench
+        .measure(
+            "check_liquidation_eligibility/in_the_money",
+            eligibility(first, price_feed),
+            &[],
+        )
+        .await?;
+    let liquidator = protocol.admin.pubkey();
+    let liquidate = |(address, policy): &(Pubkey, Policy), options| {
+        instructions::liquidate_policy(*address, policy, liquidator, price_feed, options)
+            .map_err(|error| error.to_string())
+    };
+    let mut triggered = triggered.into_iter();
+    let next = triggered.next().unwrap();
+    bench
+        .measure(
+            "liquidate_policy/full_queue",
+            liquidate(&next, LiquidateOptions::default())?,
+            &[],
+        )
+        .await?;
+    let closed = next.0;
+    let next = triggered.next().unwrap();
+    bench
+        .measure(
+            "liquidate_policy/audit_log_and_claim_history",
+            liquidate(
+                &next,
+                LiquidateOptions {
+                    audit_log: true,
+                    claim_history: true,
+                    ..LiquidateOptions::default()
+                },
+            )?,
+            &[],
+        )
+        .await?;
+    let split = (split, bench.account::<Policy>(split).await);
+    bench
+        .measure(
+            "liquidate_policy/payout_split",
+            liquidate(&split, LiquidateOptions::default())?,
+            &[],
+        )
+        .await?;
+    let settled: Policy = bench.account(split.0).await;
+    let mut paid = 0;
+    for recipient in recipients {
+        paid += bench
+            .context
+            .banks_client
+            .get_balance(recipient)
+            .await
+            .map_err(|error| error.to_string())?;
+    }
+    if paid != settled.settlement_payout {
+        return Err(format!(
+            "payout split paid {paid} of {}",
+            settled.settlement_payout
+        ));
+    }
+    let member = (member, bench.account::<Policy>(member).await);
+    bench
+        .measure(
+            "liquidate_policy/master_agreement",
+            liquidate(&member, LiquidateOptions::default())?,
+            &[],
+        )
+        .await?;
+    let agreement: MasterAgreement = bench.account(master_agreement).await;
+    if agreement.aggregate_paid != COVERAGE / 2 {
+        return Err(format!(
+            "master agreement drew {} of {}",
+            agreement.aggregate_paid,
+            COVERAGE / 2
+        ));
+    }
+    // The first breaching reading is only recorded; the second, a minute
+    // on, pays
+    let dwell = (dwell, bench.account::<Policy>(dwell).await);
+    bench
+        .measure(
+            "liquidate_policy/breach_observed",
+            liquidate(&dwell, LiquidateOptions::default())?,
+            &[],
+        )
+        .await?;
+    bench.warp(60).await;
+    bench.set_price(ASSET, strike - 1_000).await;
+    bench
+        .measure(
+            "liquidate_policy/breach_dwell",
+            liquidate(&dwell, LiquidateOptions::default())?,
+            &[],
+        )
+        .await?;
+    let policy: Policy = bench.account(dwell.0).await;
+    if !policy.is_exhausted() {
+        return Err("the sustained breach left its policy open".to_string());
+    }
+    // Halfway from the long strike to the short one, the legs net to half
+    // the coverage
+    let spread = (spread, bench.account::<Policy>(spread).await);
+    bench
+        .measure(
+            "liquidate_policy/spread",
+            liquidate(&spread, LiquidateOptions::default())?,
+            &[],
+        )
+        .await?;
+    let policy: Policy = bench.account(spread.0).await;
+    if policy.coverage_remaining != COVERAGE / 2 {
+        return Err(format!(
+            "spread left {} of {} coverage",
+            policy.coverage_remaining, COVERAGE
+        ));
+    }
+    let batch = |policies: &[(Pubkey, Policy)]| {
+        instructions::liquidate_policies(ASSET, liquidator, price_feed, false, policies)
+    };
+    let single: Vec<_> = triggered.by_ref().take(1).collect();
+    bench
+        .measure("liquidate_policies/1_policy", batch(&single), &[])
+        .await?;
+    let rest: Vec<_> = triggered.by_ref().take(3).collect();
+    bench
+        .measure(
+            &format!("liquidate_policies/{}_policies", rest.len()),
+            batch(&rest),
+            &[],
+        )
+        .await?;
+
+    // A batch reading the price push_price cached this slot
+    bench
+        .measure(
+            "push_price",
+            instructions::push_price(ASSET, liquidator, price_feed),
+            &[],
+        )
+        .await?;
+    let cached: Vec<_> = triggered.collect();
+    bench
+        .measure(
+            "liquidate_policies/price_cache",
+            instructions::liquidate_policies(
+                ASSET,
+                liquidator,
+                seeds::price_cache(program_id, ASSET).0,
+                false,
+                &cached,
+            ),
+            &[],
+        )
+        .await?;
+    let policy: Policy = bench.account(cached[0].0).await;
+    if !policy.is_exhausted() {
+        return Err("the cached batch left its policy open".to_string());
+    }
+
+    // The European policy snapshots its expiry price, then settles on it
+    bench.warp(DAY).await;
+    let price_feed = bench.set_price(ASSET, strike - 1_000).await;
+    bench
+        .measure(
+            "record_settlement_price",
+            build(
+                accounts::RecordSettlementPrice {
+                    policy: european,
+                    price_feed,
+                    feed_registry,
+                    config,
+                },
+                instruction::RecordSettlementPrice {},
+            ),
+            &[],
+        )
+        .await?;
+    bench.warp(SETTLEMENT_PRICE_WINDOW).await;
+    let policy: Policy = bench.account(european).await;
+    // The recorded snapshot doesn't pay while its feed is switched off
+    let set_feed_enabled = |enabled| {
+        build(
+            accounts::UpdateFeed {
+                feed_registry,
+                config,
+                authority: liquidator,
+            },
+            instruction::SetFeedEnabled { enabled },
+        )
+    };
+    let settle = instructions::settle(european, &policy, liquidator, LiquidateOptions::default());
+    bench.send(&[set_feed_enabled(false)], &[]).await?;
+    match bench.send(&[settle], &[]).await {
+        Err(error) if error.contains("FeedDisabled") => {}
+        outcome => return Err(format!("settle with the feed off: {outcome:?}")),
+    }
+    bench.send(&[set_feed_enabled(true)], &[]).await?;
+    bench
+        .measure(
+            "settle",
+            instructions::settle(european, &policy, liquidator, LiquidateOptions::default()),
+            &[],
+        )
+        .await?;
+
+    let policy: Policy = bench.account(closed).await;
+    bench
+        .measure(
+            "close_policy",
+            instructions::close_policy(closed, &policy, owner, None),
+            &[&protocol.owner],
+        )
+        .await?;
+    Ok(())
+}
+
+// Policies that change shape while open: reduced, re-struck, rolled,
+// streamed, vested, barriered and reclaimed
+async fn lifecycle(bench: &mut Bench, protocol: &mut Protocol) -> Result<(), String> {
+    let program_id = &liqguard::ID;
+    let config = seeds::config(program_id).0;
+    let feed_registry = seeds::feed_registry(program_id, ASSET).0;
+    let keeper_queue = seeds::keeper_queue(program_id, ASSET).0;
+    let treasury = seeds::treasury(program_id).0;
+    let owner = protocol.owner.pubkey();
+    let underwriter = protocol.underwriter.pubkey();
+    let price_feed = bench.set_price(ASSET, SPOT).await;
+    let now = bench.now().await;
+    let expiration = now + 30 * DAY;
+    let strike = SPOT * 9 / 10;
+
+    // One policy takes every owner-side change short of settling
+    let params = protocol.params(strike, expiration);
+    let (changed, _) = protocol.open(bench, params, None, None).await?;
+    bench
+        .measure(
+            "request_coverage_reduction",
+            build(
+                accounts::RequestCoverageReduction {
+                    policy: changed,
+                    owner,
+                },
+                instruction::RequestCoverageReduction {
+                    new_coverage_amount: COVERAGE / 2,
+                },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+    let update_strike = || accounts::UpdateStrike {
+        policy: changed,
+        keeper_queue,
+        config,
+        owner,
+    };
+    bench
+        .measure(
+            "request_strike_update",
+            build(
+                update_strike(),
+                instruction::RequestStrikeUpdate {
+                    new_strike_price: usd(SPOT / 2),
+                },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+    bench
+        .measure(
+            "apply_strike_update",
+            build(update_strike(), instruction::ApplyStrikeUpdate {}),
+            &[&protocol.owner],
+        )
+        .await?;
+    bench
+        .measure(
+            "set_permissioned_liquidation",
+            build(
+                accounts::SetPermissionedLiquidation {
+                    policy: changed,
+                    owner,
+                },
+                instruction::SetPermissionedLiquidation { permissioned: true },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+    bench
+        .measure(
+            "record_settlement_attempt",
+            build(
+                accounts::RecordSettlementAttempt {
+                    policy: changed,
+                    config,
+                    feed_registry,
+                    price_feed,
+                    twap_update: None,
+                    switchboard_feed: None,
+                    drift_user: None,
+                    kamino_obligation: None,
+                    audit_log: seeds::audit_log(program_id).0,
+                },
+                instruction::RecordSettlementAttempt {},
+            ),
+            &[],
+        )
+        .await?;
+
+    // Rolls daily, with next period's premium escrowed up front
+    let params = InitializePolicyParams {
+        roll_period_seconds: DAY,
+        ..protocol.params(SPOT / 4, now + DAY)
+    };
+    let (rolled, _) = protocol.open(bench, params, None, None).await?;
+    // Rolls daily too, paying each period from a delegated allowance
+    let params = InitializePolicyParams {
+        roll_period_seconds: DAY,
+        ..protocol.params(SPOT / 4, now + DAY)
+    };
+    let (renewed, _) = protocol.open(bench, params, None, None).await?;
+    let policy: Policy = bench.account(renewed).await;
+    bench
+        .send(
+            &instructions::approve_auto_renew(&policy, 2 * COVERAGE),
+            &[&protocol.owner],
+        )
+        .await?;
+    let roll_premium = || accounts::ManageRollPremium {
+        policy: rolled,
+        owner,
+        system_program: system_program::ID,
+    };
+    bench
+        .measure(
+            "deposit_roll_premium",
+            build(
+                roll_premium(),
+                instruction::DepositRollPremium {
+                    amount: 2 * COVERAGE,
+                },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+    bench
+        .measure(
+            "withdraw_roll_premium",
+            build(
+                roll_premium(),
+                instruction::WithdrawRollPremium {
+                    amount: COVERAGE / 2,
+                },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    // Streams a lamport a second on top of its up-front premium
+    let params = InitializePolicyParams {
+        premium_rate: 1,
+        stream_deposit: 10 * DAY as u64,
+        ..protocol.params(SPOT / 4, expiration)
+    };
+    let (streamed, _) = protocol.open(bench, params, None, None).await?;
+    let stream_premium = || accounts::ManageStreamPremium {
+        policy: streamed,
+        owner,
+        system_program: system_program::ID,
+    };
+    bench
+        .measure(
+            "deposit_stream_premium",
+            build(
+                stream_premium(),
+                instruction::DepositStreamPremium { amount: DAY as u64 },
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    let params = InitializePolicyParams {
+        vesting_period_seconds: DAY,
+        immediate_payout_bps: 2_500,
+        ..protocol.params(strike, expiration)
+    };
+    let vesting = protocol.open(bench, params, None, None).await?;
+    let params = InitializePolicyParams {
+        barrier_kind: BarrierKind::KnockIn,
+        barrier_price: usd(strike * 9 / 10),
+        ..protocol.params(strike, expiration)
+    };
+    let (barrier, _) = protocol.open(bench, params, None, None).await?;
+    let params = protocol.params(SPOT / 4, now + DAY);
+    let (expiring, _) = protocol.open(bench, params, None, None).await?;
+
+    // The price falls through the barrier
+    let price_feed = bench.set_price(ASSET, strike * 8 / 10).await;
+    bench
+        .measure(
+            "record_barrier_touch",
+            build(
+                accounts::RecordBarrierTouch {
+                    policy: barrier,
+                    keeper_queue: Some(keeper_queue),
+                    price_feed,
+                    feed_registry,
+                    config,
+                },
+                instruction::RecordBarrierTouch {},
+            ),
+            &[],
+        )
+        .await?;
+    let liquidator = protocol.admin.pubkey();
+    bench
+        .measure(
+            "liquidate_policy/vesting",
+            instructions::liquidate_policy(
+                vesting.0,
+                &vesting.1,
+                liquidator,
+                price_feed,
+                LiquidateOptions::default(),
+            )
+            .map_err(|error| error.to_string())?,
+            &[],
+        )
+        .await?;
+
+    // A day on: cooldowns are over, the payout has vested and the daily
+    // policies have expired into their roll window
+    bench.warp(DAY + 60).await;
+    let policy: Policy = bench.account(changed).await;
+    bench
+        .measure(
+            "withdraw_excess_coverage",
+            build(
+                accounts::WithdrawExcessCoverage {
+                    policy: changed,
+                    vault: seeds::vault(program_id, &owner, policy.policy_id).0,
+                    underwriter,
+                    caller: owner,
+                    owner_state: seeds::owner_state(program_id, &owner).0,
+                    token_vault: None,
+                    underwriter_token_account: None,
+                    token_program: None,
+                    system_program: system_program::ID,
+                },
+                instruction::WithdrawExcessCoverage {},
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+    let policy: Policy = bench.account(vesting.0).await;
+    bench
+        .measure(
+            "claim_tranche",
+            build(
+                accounts::ClaimTranche {
+                    policy: vesting.0,
+                    claim_state: seeds::claim_state(program_id, &vesting.0).0,
+                    vault: seeds::vault(program_id, &owner, policy.policy_id).0,
+                    beneficiary: policy.beneficiary,
+                    rent_receiver: policy.settlement_liquidator,
+                    cranker: liquidator,
+                    token_vault: None,
+                    beneficiary_token_account: None,
+                    token_program: None,
+                    system_program: system_program::ID,
+                },
+                instruction::ClaimTranche {},
+            ),
+            &[],
+        )
+        .await?;
+
+    let policy: Policy = bench.account(rolled).await;
+    bench
+        .measure(
+            "roll_policy",
+            build(
+                accounts::RollPolicy {
+                    policy: rolled,
+                    vault: seeds::vault(program_id, &owner, policy.policy_id).0,
+                    token_vault: None,
+                    config,
+                    underwriter,
+                    treasury,
+                    backstop: None,
+                    caller: liquidator,
+                },
+                instruction::RollPolicy {},
+            ),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "cancel_auto_roll",
+            build(
+                accounts::CancelAutoRoll {
+                    policy: rolled,
+                    caller: owner,
+                },
+                instruction::CancelAutoRoll {},
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+    let policy: Policy = bench.account(renewed).await;
+    bench
+        .measure(
+            "auto_renew",
+            instructions::auto_renew(renewed, &policy, liquidator, false),
+            &[],
+        )
+        .await?;
+    let renewal: Policy = bench.account(renewed).await;
+    if renewal.expiration_datetime != policy.expiration_datetime + DAY {
+        return Err(format!(
+            "auto_renew left expiry at {}",
+            renewal.expiration_datetime
+        ));
+    }
+
+    bench
+        .measure(
+            "sync_premium_stream",
+            build(
+                accounts::SyncPremiumStream {
+                    policy: streamed,
+                    config,
+                    underwriter,
+                    treasury,
+                    backstop: None,
+                    caller: liquidator,
+                },
+                instruction::SyncPremiumStream {},
+            ),
+            &[],
+        )
+        .await?;
+    bench
+        .measure(
+            "cancel_premium_stream",
+            build(stream_premium(), instruction::CancelPremiumStream {}),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    let policy: Policy = bench.account(expiring).await;
+    bench
+        .measure(
+            "reclaim_vault",
+            build(
+                accounts::ReclaimVault {
+                    policy: expiring,
+                    keeper_queue: None,
+                    vault: seeds::vault(program_id, &owner, policy.policy_id).0,
+                    owner,
+                    underwriter,
+                    caller: underwriter,
+                    owner_state: seeds::owner_state(program_id, &owner).0,
+                    token_vault: None,
+                    underwriter_token_account: None,
+                    portfolio_vault: None,
+                    token_program: None,
+                    system_program: system_program::ID,
+                },
+                instruction::ReclaimVault {},
+            ),
+            &[&protocol.underwriter],
+        )
+        .await?;
+    // Reclaimed without the queue, the policy stays listed until pruned
+    bench
+        .measure(
+            "prune_keeper_queue",
+            instructions::prune_keeper_queue(ASSET, &[expiring]),
+            &[],
+        )
+        .await?;
+    Ok(())
+}
+
+// The treasury's fees and an underwriter's shared vault
+async fn underwriting(bench: &mut Bench, protocol: &Protocol) -> Result<(), String> {
+    let program_id = &liqguard::ID;
+    let underwriter = protocol.underwriter.pubkey();
+    bench
+        .measure(
+            "withdraw_treasury",
+            build(
+                accounts::WithdrawTreasury {
+                    treasury: seeds::treasury(program_id).0,
+                    destination: protocol.admin.pubkey(),
+                    config: seeds::config(program_id).0,
+                    authority: protocol.admin.pubkey(),
+                },
+                instruction::WithdrawTreasury { amount: 1_000 },
+            ),
+            &[],
+        )
+        .await?;
+
+    let portfolio_vault = seeds::portfolio_vault(program_id, &underwriter).0;
+    bench
+        .measure(
+            "initialize_portfolio_vault",
+            build(
+                accounts::InitializePortfolioVault {
+                    portfolio_vault,
+                    underwriter,
+                    payer: underwriter,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializePortfolioVault {},
+            ),
+            &[&protocol.underwriter],
+        )
+        .await?;
+    let manage = || accounts::ManagePortfolioVault {
+        portfolio_vault,
+        underwriter,
+        system_program: system_program::ID,
+    };
+    bench
+        .measure(
+            "fund_portfolio_vault",
+            build(
+                manage(),
+                instruction::FundPortfolioVault { amount: COVERAGE },
+            ),
+            &[&protocol.underwriter],
+        )
+        .await?;
+    bench
+        .measure(
+            "withdraw_portfolio_vault",
+            build(
+                manage(),
+                instruction::WithdrawPortfolioVault { amount: COVERAGE },
+            ),
+            &[&protocol.underwriter],
+        )
+        .await?;
+    Ok(())
+}
+
+// Coverage requests posted by the owner: one cancelled, one filled by
+// the underwriter into a funded policy
+async fn requests(bench: &mut Bench, protocol: &mut Protocol) -> Result<(), String> {
+    let program_id = &liqguard::ID;
+    let owner = protocol.owner.pubkey();
+    bench.set_price(ASSET, SPOT).await;
+    let now = bench.now().await;
+    let request_params = |request_id| CoverageRequestParams {
+        request_id,
+        asset: ASSET,
+        strike_price: usd(SPOT / 2),
+        direction: TriggerDirection::ProtectLong,
+        upper_strike_price: 0,
+        coverage_amount: COVERAGE,
+        expiration_datetime: now + DAY,
+        max_premium: COVERAGE / 10,
+    };
+    let post = |request_id| {
+        build(
+            accounts::PostCoverageRequest {
+                coverage_request: seeds::coverage_request(program_id, &owner, request_id).0,
+                config: seeds::config(program_id).0,
+                owner,
+                payer: owner,
+                system_program: system_program::ID,
+            },
+            instruction::PostCoverageRequest {
+                params: request_params(request_id),
+            },
+        )
+    };
+    bench
+        .measure("post_coverage_request", post(0), &[&protocol.owner])
+        .await?;
+    bench.send(&[post(1)], &[&protocol.owner]).await?;
+    bench
+        .measure(
+            "cancel_coverage_request",
+            build(
+                accounts::CancelCoverageRequest {
+                    coverage_request: seeds::coverage_request(program_id, &owner, 1).0,
+                    owner,
+                },
+                instruction::CancelCoverageRequest {},
+            ),
+            &[&protocol.owner],
+        )
+        .await?;
+
+    let request: CoverageRequest = bench
+        .account(seeds::coverage_request(program_id, &owner, 0).0)
+        .await;
+    let policy_id = protocol.next_policy_id;
+    bench
+        .measure(
+            "fill_coverage_request",
+            instructions::fill_coverage_request(
+                &request,
+                policy_id,
+                protocol.underwriter.pubkey(),
+                seeds::treasury(program_id).0,
+                protocol.price_feed,
+                false,
+                COVERAGE / 20,
+            ),
+            &[&protocol.underwriter],
+        )
+        .await?;
+    protocol.next_policy_id += 1;
+    let policy: Policy = bench
+        .account(seeds::policy(program_id, &owner, policy_id).0)
+        .await;
+    if !policy.is_funded || policy.premium_amount != COVERAGE / 20 {
+        return Err(format!(
+            "filled policy funded={} premium={}",
+            policy.is_funded, policy.premium_amount
+        ));
+    }
+    Ok(())
+}
+
+// A policy nobody closes, swept into the backstop once it is abandoned
+async fn abandonment(bench: &mut Bench, protocol: &mut Protocol) -> Result<(), String> {
+    let program_id = &liqguard::ID;
+    let backstop = seeds::backstop(program_id).0;
+    bench.set_price(ASSET, SPOT).await;
+    let now = bench.now().await;
+    let params = protocol.params(SPOT * 9 / 10, now + DAY);
+    let (abandoned, policy) = protocol.open(bench, params, None, None).await?;
+
+    bench.warp(DAY + ABANDONED_VAULT_GRACE_PERIOD).await;
+    let before = bench.context.banks_client.get_balance(backstop).await;
+    bench
+        .measure(
+            "sweep_abandoned_vault",
+            instructions::sweep_abandoned_vault(abandoned, &policy, protocol.admin.pubkey(), true),
+            &[],
+        )
+        .await?;
+    let after = bench.context.banks_client.get_balance(backstop).await;
+    match (before, after) {
+        (Ok(before), Ok(after)) if after >= before + COVERAGE => Ok(()),
+        _ => Err("the sweep didn't reach the backstop".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budgets::Budgets;
+    use liqguard::{preview_payout, Config, OraclePrice, PayoutDecision};
+
+    async fn balance(bench: &mut Bench, address: Pubkey) -> u64 {
+        bench
+            .context
+            .banks_client
+            .get_balance(address)
+            .await
+            .expect("balance fetch")
+    }
+
+    // The scenarios run natively, unmetered, and budgets.toml covers each
+    #[tokio::test]
+    async fn scenarios_run_and_are_all_budgeted() {
+        let mut bench = Bench::native().await;
+        run(&mut bench).await.unwrap();
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/budgets.toml");
+        let budgets = Budgets::load(std::path::Path::new(path)).unwrap();
+        let mut measured: Vec<&String> = bench
+            .measurements
+            .iter()
+            .map(|measurement| &measurement.name)
+            .collect();
+        measured.sort();
+        assert_eq!(measured, budgets.budgets.keys().collect::<Vec<_>>());
+    }
+
+    // The keeper's preview and the instruction decide alike: the claim,
+    // payout, bounty and fee preview_payout gives for a policy at a price
+    // are what liquidate_policy then books and pays
+    #[tokio::test]
+    async fn liquidation_pays_what_preview_payout_decides() {
+        let mut bench = Bench::native().await;
+        let mut protocol = Protocol {
+            admin: bench.payer.insecure_clone(),
+            owner: bench.funded(1_000).await,
+            underwriter: bench.funded(1_000).await,
+            price_feed: bench.set_price(ASSET, SPOT).await,
+            next_policy_id: 0,
+        };
+        admin(&mut bench, &protocol).await.unwrap();
+        // A payout fee, so all three shares of the claim are checked
+        let program_id = &liqguard::ID;
+        let config = seeds::config(program_id).0;
+        let pending_config_change =
+            Pubkey::find_program_address(&[PENDING_CONFIG_SEED], program_id).0;
+        let authority = protocol.admin.pubkey();
+        let propose = build(
+            accounts::ProposeConfigChange {
+                pending_config_change,
+                config,
+                authority,
+                payer: authority,
+                system_program: system_program::ID,
+            },
+            instruction::ProposeConfigChange {
+                params: ConfigParams {
+                    payout_fee_bps: 100,
+                    ..config_params(authority)
+                },
+            },
+        );
+        let apply = build(
+            accounts::ApplyConfigChange {
+                pending_config_change,
+                config,
+                authority,
+            },
+            instruction::ApplyConfigChange {},
+        );
+        bench.send(&[propose, apply], &[]).await.unwrap();
+
+        let strike = SPOT * 9 / 10;
+        let expiration = bench.now().await + 30 * DAY;
+        let params = protocol.params(strike, expiration);
+        let (address, policy) = protocol.open(&mut bench, params, None, None).await.unwrap();
+        let price_feed = bench.set_price(ASSET, strike - 1_000).await;
+        let mantissa = usd(strike - 1_000);
+        let decision = preview_payout(
+            &policy,
+            &bench.account::<Config>(config).await,
+            OraclePrice::new(mantissa, PRICE_EXPONENT),
+            (mantissa / 10_000).into(),
+            bench.now().await,
+        );
+        let PayoutDecision::Pay {
+            claim,
+            payout,
+            bounty,
+            fee,
+        } = decision
+        else {
+            panic!("preview declined to pay: {decision:?}");
+        };
+
+        let liquidator = bench.funded(1).await;
+        let owner = protocol.owner.pubkey();
+        let treasury = seeds::treasury(program_id).0;
+        let before = (
+            balance(&mut bench, owner).await,
+            balance(&mut bench, liquidator.pubkey()).await,
+            balance(&mut bench, treasury).await,
+        );
+        let liquidate = instructions::liquidate_policy(
+            address,
+            &policy,
+            liquidator.pubkey(),
+            price_feed,
+            LiquidateOptions {
+                treasury: true,
+                ..LiquidateOptions::default()
+            },
+        )
+        .unwrap();
+        bench.send(&[liquidate], &[&liquidator]).await.unwrap();
+        let settled: Policy = bench.account(address).await;
+        assert_eq!(COVERAGE - settled.coverage_remaining, claim);
+        assert_eq!(
+            (
+                balance(&mut bench, owner).await - before.0,
+                balance(&mut bench, liquidator.pubkey()).await - before.1,
+                balance(&mut bench, treasury).await - before.2,
+            ),
+            (payout, bounty, fee)
+        );
+    }
+
+    // An authority holding no lamports, as a multisig vault or program
+    // PDA may, approves while the payer covers the rent
+    #[tokio::test]
+    async fn an_unfunded_authority_initializes_through_a_payer() {
+        let mut bench = Bench::native().await;
+        let authority = Keypair::new();
+        let payer = bench.payer.pubkey();
+        let program_id = &liqguard::ID;
+        let config = seeds::config(program_id).0;
+        bench
+            .send(
+                &[build(
+                    accounts::InitializeConfig {
+                        config,
+                        authority: authority.pubkey(),
+                        payer,
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializeConfig {
+                        params: config_params(authority.pubkey()),
+                    },
+                )],
+                &[&authority],
+            )
+            .await
+            .unwrap();
+        bench
+            .send(
+                &[build(
+                    accounts::InitializeTreasury {
+                        treasury: seeds::treasury(program_id).0,
+                        config,
+                        authority: authority.pubkey(),
+                        payer,
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializeTreasury {},
+                )],
+                &[&authority],
+            )
+            .await
+            .unwrap();
+        let balance = bench
+            .context
+            .banks_client
+            .get_balance(authority.pubkey())
+            .await
+            .unwrap();
+        assert_eq!(balance, 0);
+    }
+}
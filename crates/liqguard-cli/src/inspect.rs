@@ -0,0 +1,298 @@
+// Decoded accounts as JSON, for `inspect` and `list`. Policies also carry
+// what support usually works out by hand: their status, time to expiry,
+// how far the current price sits from the strike and what a liquidation
+// at that price would do
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use clap::ValueEnum;
+use liqguard::{
+    preview_payout, Config, FeedRegistry, OraclePrice, OwnerState, PayoutDecision, Policy,
+    TriggerDirection,
+};
+use liqguard_client::accounts::{decode, PolicyStatus};
+use liqguard_client::errors::ProgramError;
+use liqguard_client::oracle::SpotPrice;
+use liqguard_common::price::align;
+use serde_json::{json, Value};
+
+const BPS_DENOMINATOR: i128 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Unfunded, // opened, vault not yet holding the coverage
+    Active,   // funded and live
+    Claimed,  // coverage fully paid out
+    Terminal, // expired, lapsed or knocked out with coverage left
+}
+
+impl Status {
+    pub fn of(policy: &Policy, now: i64) -> Self {
+        match PolicyStatus::of(policy, now) {
+            PolicyStatus::Unfunded => Status::Unfunded,
+            PolicyStatus::Active => Status::Active,
+            PolicyStatus::Claimed => Status::Claimed,
+            PolicyStatus::Terminal => Status::Terminal,
+        }
+    }
+}
+
+// Enum values as their snake_case serde names
+fn name(value: impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+fn key(key: &Pubkey) -> Value {
+    if *key == Pubkey::default() {
+        Value::Null
+    } else {
+        json!(key.to_string())
+    }
+}
+
+// A price as a decimal string, exact at its exponent
+fn decimal(price: OraclePrice) -> String {
+    if price.exponent >= 0 {
+        return (price.mantissa * 10i128.pow(price.exponent as u32)).to_string();
+    }
+    let scale = 10i128.pow(price.exponent.unsigned_abs());
+    let sign = if price.mantissa < 0 { "-" } else { "" };
+    let (whole, fraction) = (price.mantissa.abs() / scale, price.mantissa.abs() % scale);
+    format!(
+        "{sign}{whole}.{fraction:0width$}",
+        width = price.exponent.unsigned_abs() as usize
+    )
+}
+
+// (spot - strike) / strike in bps, negative below the strike
+fn distance_bps(spot: OraclePrice, strike: OraclePrice) -> Option<i128> {
+    let (spot, strike) = align(spot, strike)?;
+    (strike != 0).then(|| (spot - strike) * BPS_DENOMINATOR / strike)
+}
+
+// What liquidate_policy would do with the policy at a spot price
+fn decision_json(decision: PayoutDecision) -> Value {
+    match decision {
+        PayoutDecision::Pay {
+            claim,
+            payout,
+            bounty,
+            fee,
+        } => json!({
+            "decision": "pay",
+            "claim": claim,
+            "payout": payout,
+            "bounty": bounty,
+            "fee": fee,
+        }),
+        PayoutDecision::ObserveBreach { confirmable_from } => json!({
+            "decision": "observe_breach",
+            "confirmable_from": confirmable_from,
+        }),
+        PayoutDecision::FlagDeviation {
+            ema_price,
+            confirmable_from,
+        } => json!({
+            "decision": "flag_deviation",
+            "ema_price": decimal(ema_price),
+            "confirmable_from": confirmable_from,
+        }),
+        PayoutDecision::Reject(code) => json!({
+            "decision": "reject",
+            "error": ProgramError::from_code(code).map(|error| error.name()),
+        }),
+    }
+}
+
+pub fn policy_json(
+    address: &Pubkey,
+    policy: &Policy,
+    config: &Config,
+    now: i64,
+    spot: Option<SpotPrice>,
+) -> Value {
+    let settled = policy.settlement_slot != 0;
+    let upper = matches!(policy.direction, TriggerDirection::OutsideRange);
+    json!({
+        "type": "policy",
+        "address": address.to_string(),
+        "policy_id": policy.policy_id,
+        "version": policy.version,
+        "owner": key(&policy.owner),
+        "creator": key(&policy.creator),
+        "beneficiary": key(&policy.beneficiary),
+        "underwriter": key(&policy.underwriter),
+        "asset": name(policy.asset),
+        "direction": name(policy.direction),
+        "strike_price": decimal(policy.strike()),
+        "upper_strike_price": upper.then(|| decimal(policy.upper_strike())),
+        "payout_mode": name(policy.payout_mode),
+        "exercise_style": name(policy.exercise_style),
+        "barrier_kind": name(policy.barrier_kind),
+        "coverage_amount": policy.coverage_amount,
+        "coverage_remaining": policy.coverage_remaining,
+        "usd_coverage": policy.usd_coverage,
+        "collateral_mint": key(&policy.collateral_mint),
+        "premium_amount": policy.premium_amount,
+        "created_at": policy.created_at,
+        "expiration_datetime": policy.expiration_datetime,
+        "status": name(Status::of(policy, now)),
+        "is_funded": policy.is_funded,
+        "dispute_open": policy.dispute_open,
+        "unvested_payout": policy.unvested_payout,
+        "holdback_amount": policy.holdback_amount,
+        "settlement": settled.then(|| json!({
+            "price": decimal(OraclePrice::new(policy.settlement_price, policy.settlement_price_exponent)),
+            "publish_time": policy.settlement_publish_time,
+            "slot": policy.settlement_slot,
+            "liquidator": key(&policy.settlement_liquidator),
+            "payout": policy.settlement_payout,
+            "bounty": policy.settlement_bounty,
+        })),
+        "derived": {
+            "seconds_to_expiry": policy.expiration_datetime - now,
+            "spot_price": spot.map(|spot| decimal(spot.price)),
+            "spot_publish_time": spot.map(|spot| spot.publish_time),
+            "distance_to_strike_bps": spot.and_then(|spot| distance_bps(spot.price, policy.strike())),
+            "distance_to_upper_strike_bps": spot
+                .filter(|_| upper)
+                .and_then(|spot| distance_bps(spot.price, policy.upper_strike())),
+            "payout_decision": spot.map(|spot| decision_json(preview_payout(
+                policy,
+                config,
+                spot.price,
+                spot.confidence.into(),
+                now,
+            ))),
+        },
+    })
+}
+
+fn config_json(config: &Config) -> Value {
+    json!({
+        "type": "config",
+        "authority": key(&config.authority),
+        "paused": config.paused,
+        "treasury": key(&config.treasury),
+        "max_confidence_bps": config.max_confidence_bps,
+        "bounty_bps": config.bounty_bps,
+        "bounty_flat_lamports": config.bounty_flat_lamports,
+        "protocol_fee_bps": config.protocol_fee_bps,
+        "payout_fee_bps": config.payout_fee_bps,
+        "backstop_fee_bps": config.backstop_fee_bps,
+        "default_max_price_age": config.default_max_price_age,
+        "max_coverage_amount": config.max_coverage_amount,
+        "max_owner_coverage": config.max_owner_coverage,
+        "min_policy_age": config.min_policy_age,
+        "max_price_deviation_bps": config.max_price_deviation_bps,
+        "config_change_delay": config.config_change_delay,
+        "arbiter": key(&config.arbiter),
+        "dispute_window": config.dispute_window,
+        "liquidator_allowlist": config
+            .liquidator_allowlist
+            .iter()
+            .filter(|keeper| **keeper != Pubkey::default())
+            .map(|keeper| keeper.to_string())
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn feed_registry_json(feed: &FeedRegistry) -> Value {
+    let symbol_len = feed.symbol.iter().position(|byte| *byte == 0).unwrap_or(16);
+    json!({
+        "type": "feed_registry",
+        "asset": name(feed.asset),
+        "symbol": String::from_utf8_lossy(&feed.symbol[..symbol_len]),
+        "feed_id": feed.feed_id.iter().map(|byte| format!("{byte:02x}")).collect::<String>(),
+        "oracle_source": name(feed.oracle_source),
+        "oracle_account": key(&feed.oracle_account),
+        "enabled": feed.enabled,
+        "max_price_age": feed.max_price_age,
+        "max_confidence_bps": feed.max_confidence_bps,
+        "volatility_bps": feed.volatility_bps,
+        "lazer_feed_id": feed.lazer_feed_id,
+    })
+}
+
+fn owner_state_json(state: &OwnerState) -> Value {
+    json!({
+        "type": "owner_state",
+        "owner": key(&state.owner),
+        "policy_count": state.policy_count,
+        "active_coverage": state.active_coverage,
+    })
+}
+
+// A non-policy liqguard account, picked by its discriminator
+pub fn account_json(address: &Pubkey, data: &[u8]) -> Result<Value, String> {
+    let discriminator = data.get(..8).ok_or("account too short")?;
+    let decoded = if discriminator == Config::DISCRIMINATOR {
+        decode::<Config>(address, data).map(|config| config_json(&config))
+    } else if discriminator == FeedRegistry::DISCRIMINATOR {
+        decode::<FeedRegistry>(address, data).map(|feed| feed_registry_json(&feed))
+    } else if discriminator == OwnerState::DISCRIMINATOR {
+        decode::<OwnerState>(address, data).map(|state| owner_state_json(&state))
+    } else {
+        return Err(format!(
+            "{address} is a liqguard account the inspector can't decode"
+        ));
+    };
+    decoded.map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountDeserialize;
+    use liqguard_common::layout::DISCRIMINATOR_LEN;
+    use liqguard_test_support::zeroed_policy;
+
+    fn policy() -> Policy {
+        let mut policy = zeroed_policy();
+        policy.strike_price = 9_500_000_000_000;
+        policy.coverage_amount = 1_000;
+        policy.coverage_remaining = 1_000;
+        policy.is_funded = true;
+        policy.expiration_datetime = 2_000;
+        policy
+    }
+
+    #[test]
+    fn decimal_is_exact() {
+        assert_eq!(
+            decimal(OraclePrice::new(9_500_012_345_678i64, -8)),
+            "95000.12345678"
+        );
+        assert_eq!(decimal(OraclePrice::new(-5i64, -2)), "-0.05");
+        assert_eq!(decimal(OraclePrice::new(12i64, 2)), "1200");
+    }
+
+    #[test]
+    fn policy_json_derives_status_expiry_distance_and_decision() {
+        let mut policy = policy();
+        let spot = SpotPrice {
+            price: OraclePrice::new(9_025_000_000_000i64, -8),
+            confidence: 0,
+            publish_time: 990,
+        };
+        let data = vec![0u8; DISCRIMINATOR_LEN + Config::LEN];
+        let config = Config::try_deserialize_unchecked(&mut &data[..]).unwrap();
+        let json = policy_json(&Pubkey::new_unique(), &policy, &config, 1_000, Some(spot));
+        assert_eq!(json["status"], "active");
+        assert_eq!(json["direction"], "protect_long");
+        assert_eq!(json["strike_price"], "95000.00000000");
+        assert_eq!(json["derived"]["seconds_to_expiry"], 1_000);
+        assert_eq!(json["derived"]["distance_to_strike_bps"], -500);
+        assert_eq!(json["derived"]["payout_decision"]["decision"], "pay");
+        assert_eq!(json["derived"]["payout_decision"]["claim"], 1_000);
+        assert!(json["upper_strike_price"].is_null());
+        assert!(json["settlement"].is_null());
+
+        policy.coverage_remaining = 0;
+        assert_eq!(Status::of(&policy, 1_000), Status::Claimed);
+        policy.coverage_remaining = 1_000;
+        policy.is_funded = false;
+        assert_eq!(Status::of(&policy, 1_000), Status::Unfunded);
+    }
+}
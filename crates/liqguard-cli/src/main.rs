@@ -0,0 +1,649 @@
+// liqguard-cli: protocol and policy operations against the on-chain
+// interface, built on the client SDK's instruction builders so they can't
+// drift from the program. Keys are read from Solana keypair files
+mod inspect;
+mod params;
+mod quote;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, Discriminator, InstructionData, ToAccountMetas};
+use clap::{Parser, Subcommand};
+use liqguard::{check_claimable, Policy, UnderlyingAsset, ABANDONED_VAULT_GRACE_PERIOD};
+use liqguard_client::compute;
+use liqguard_client::errors::ProgramError;
+use liqguard_client::instructions::{self, InitializePolicyAccounts, TokenAccounts};
+use liqguard_client::lookup_tables;
+use liqguard_client::oracle::SpotPrice;
+use liqguard_client::rpc::{LiqGuardClient, PolicyFilter};
+use liqguard_client::{seeds, ClientError};
+use liqguard_risk::{Market, RiskParams, RiskReport};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::message::{v0, VersionedMessage};
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+
+use inspect::Status;
+use params::{ConfigFile, PolicyFile};
+use quote::QuoteArgs;
+
+// Policies per liquidate_policies transaction; each adds three accounts
+const CRANK_BATCH_SIZE: usize = 6;
+
+#[derive(Parser)]
+#[command(version, about = "Operate LiqGuard from the command line")]
+struct Cli {
+    /// RPC endpoint
+    #[arg(
+        short,
+        long,
+        global = true,
+        default_value = "https://api.devnet.solana.com"
+    )]
+    url: String,
+    /// Keypair file that signs and pays
+    #[arg(short, long, global = true, default_value = "~/.config/solana/id.json")]
+    keypair: PathBuf,
+    /// Commitment to read and confirm at
+    #[arg(long, global = true, default_value = "confirmed")]
+    commitment: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the protocol config, with this keypair as its authority
+    InitConfig {
+        /// TOML file of ConfigParams, see config.example.toml
+        params: PathBuf,
+    },
+    /// Open a policy owned by this keypair
+    CreatePolicy {
+        /// TOML file of the policy's params, see policy.example.toml
+        params: PathBuf,
+        /// Fresh price update for the asset, read for the entry price
+        #[arg(long)]
+        price_feed: Pubkey,
+        /// Back the policy from the underwriter's portfolio vault
+        #[arg(long)]
+        portfolio_vault: bool,
+        /// Keypair of the params' underwriter, who co-signs the open.
+        /// Defaults to this keypair
+        #[arg(long)]
+        underwriter_keypair: Option<PathBuf>,
+    },
+    /// Move collateral into a policy's vault
+    FundVault {
+        policy: Pubkey,
+        /// Lamports to deposit
+        amount: u64,
+        /// SOL price update, needed to value USD coverage
+        #[arg(long)]
+        sol_price_feed: Option<Pubkey>,
+    },
+    /// Close a terminal policy, returning rent and residual collateral
+    ClosePolicy { policy: Pubkey },
+    /// Sweep every native policy left unclosed past the abandonment grace
+    /// period into the backstop
+    SweepAbandoned {
+        /// Sweep into the treasury instead, for protocols without a backstop
+        #[arg(long)]
+        treasury: bool,
+    },
+    /// Settle every claimable policy of an asset against one price
+    Crank {
+        #[arg(value_parser = parse_asset)]
+        asset: UnderlyingAsset,
+        /// Price update for the asset to settle against
+        #[arg(long)]
+        price_feed: Pubkey,
+        /// Address lookup table holding the batch's accounts, see
+        /// lookup-table extend. Sends larger batches as v0 transactions
+        #[arg(long)]
+        lookup_table: Option<Pubkey>,
+    },
+    /// Manage address lookup tables for batched cranks
+    #[command(subcommand)]
+    LookupTable(LookupTableCommand),
+    /// Print a liqguard account as JSON
+    Inspect { address: Pubkey },
+    /// Print matching policies as a JSON array
+    List {
+        #[arg(long, value_enum)]
+        status: Option<Status>,
+        #[arg(long, value_parser = parse_asset)]
+        asset: Option<UnderlyingAsset>,
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// Print every open policy's aggregate exposure, stress payouts and
+    /// value at risk, per asset and per underwriter, priced off Pyth's
+    /// sponsored feeds
+    Risk {
+        /// How far ahead value at risk looks
+        #[arg(long, default_value_t = 24)]
+        horizon_hours: i64,
+        /// Share of outcomes value at risk covers
+        #[arg(long, default_value_t = 9_900)]
+        confidence_bps: u16,
+        /// Alert on assets whose value at risk exceeds this share of their
+        /// coverage, 0 = only on unsettled breaches
+        #[arg(long, default_value_t = 0)]
+        max_var_bps: u16,
+    },
+    /// Suggest a premium for a policy's terms off-chain, with the program's
+    /// pricing model, and price the vanilla options that hedge it
+    Quote(QuoteArgs),
+}
+
+#[derive(Subcommand)]
+enum LookupTableCommand {
+    /// Create an empty table owned by this keypair
+    Create,
+    /// Add the accounts an asset's cranks need: the shared ones and every
+    /// open policy's. Run again as policies open
+    Extend {
+        table: Pubkey,
+        #[arg(value_parser = parse_asset)]
+        asset: UnderlyingAsset,
+        /// Price update account the cranks will settle against
+        #[arg(long)]
+        price_feed: Pubkey,
+    },
+}
+
+fn parse_asset(text: &str) -> Result<UnderlyingAsset, String> {
+    match text.to_ascii_lowercase().as_str() {
+        "btc" => Ok(UnderlyingAsset::Btc),
+        "eth" => Ok(UnderlyingAsset::Eth),
+        "sol" => Ok(UnderlyingAsset::Sol),
+        _ => Err(format!("unknown asset {text:?}")),
+    }
+}
+
+fn read_keypair(path: &Path) -> Result<Keypair, String> {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(rest))
+            .unwrap_or_else(|| path.to_path_buf()),
+        Err(_) => path.to_path_buf(),
+    };
+    read_keypair_file(&path).map_err(|error| format!("reading {}: {error}", path.display()))
+}
+
+// The cluster's clock, which expiry and claimability are judged against
+async fn chain_time(client: &LiqGuardClient) -> Result<i64, String> {
+    let slot = client
+        .rpc
+        .get_slot()
+        .await
+        .map_err(|error| error.to_string())?;
+    client
+        .rpc
+        .get_block_time(slot)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+// Spot prices are best effort: a policy still prints without its distance
+// to the strike when the sponsored feed can't be read
+async fn spot_price(client: &LiqGuardClient, asset: UnderlyingAsset) -> Option<SpotPrice> {
+    client.sponsored_price(asset).await.ok()
+}
+
+fn print_json(value: &serde_json::Value) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_or_default()
+    );
+}
+
+// A rejected send names the program error rather than its custom code
+fn send_error(error: solana_client::client_error::ClientError) -> String {
+    match ProgramError::from_rpc_error(&error) {
+        Some(program_error) => format!("transaction rejected: {program_error}"),
+        None => error.to_string(),
+    }
+}
+
+struct Context {
+    client: LiqGuardClient,
+    payer: Keypair,
+}
+
+impl Context {
+    // Send under a compute limit sized for instructions
+    async fn send(&self, instructions: &[Instruction]) -> Result<Signature, String> {
+        self.send_with(instructions, &[]).await
+    }
+
+    // send, also signed by signers
+    async fn send_with(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Signature, String> {
+        let rpc = &self.client.rpc;
+        let blockhash = rpc
+            .get_latest_blockhash()
+            .await
+            .map_err(|error| error.to_string())?;
+        let mut keypairs = vec![&self.payer];
+        keypairs.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &compute::with_compute_budget(instructions, 0),
+            Some(&self.payer.pubkey()),
+            &keypairs,
+            blockhash,
+        );
+        rpc.send_and_confirm_transaction(&transaction)
+            .await
+            .map_err(send_error)
+    }
+
+    // send as a v0 transaction resolving accounts through tables
+    async fn send_v0(
+        &self,
+        instructions: &[Instruction],
+        tables: &[AddressLookupTableAccount],
+    ) -> Result<Signature, String> {
+        let rpc = &self.client.rpc;
+        let blockhash = rpc
+            .get_latest_blockhash()
+            .await
+            .map_err(|error| error.to_string())?;
+        let instructions = compute::with_compute_budget(instructions, 0);
+        let message =
+            v0::Message::try_compile(&self.payer.pubkey(), &instructions, tables, blockhash)
+                .map_err(|error| error.to_string())?;
+        let transaction =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&self.payer])
+                .map_err(|error| error.to_string())?;
+        rpc.send_and_confirm_transaction(&transaction)
+            .await
+            .map_err(send_error)
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+    // Pure math: no keypair or cluster needed
+    if let Command::Quote(args) = &cli.command {
+        print_json(&quote::quote_json(args)?);
+        return Ok(());
+    }
+    let commitment = CommitmentConfig::from_str(&cli.commitment)
+        .map_err(|_| format!("unknown commitment {:?}", cli.commitment))?;
+    let context = Context {
+        client: LiqGuardClient::new(RpcClient::new_with_commitment(cli.url, commitment)),
+        payer: read_keypair(&cli.keypair)?,
+    };
+    let payer = context.payer.pubkey();
+    let client = &context.client;
+
+    match cli.command {
+        Command::InitConfig { params } => {
+            let params = params::load::<ConfigFile>(&params)?.into_params()?;
+            let ix = Instruction {
+                program_id: liqguard::ID,
+                accounts: liqguard::accounts::InitializeConfig {
+                    config: seeds::config(&liqguard::ID).0,
+                    authority: payer,
+                    payer,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: liqguard::instruction::InitializeConfig { params }.data(),
+            };
+            let signature = context.send(&[ix]).await?;
+            println!("initialized config: {signature}");
+        }
+        Command::CreatePolicy {
+            params,
+            price_feed,
+            portfolio_vault,
+            underwriter_keypair,
+        } => {
+            let policy = params::load::<PolicyFile>(&params)?;
+            let underwriter = underwriter_keypair
+                .map(|path| read_keypair(&path))
+                .transpose()?;
+            let config = client.config().await.map_err(|error| error.to_string())?;
+            let policy_id = match client.owner_state(&payer).await {
+                Ok(owner_state) => owner_state.policy_count,
+                Err(ClientError::AccountNotFound(_)) => 0,
+                Err(error) => return Err(error.to_string()),
+            };
+            let ix = instructions::initialize_policy(
+                InitializePolicyAccounts {
+                    owner: payer,
+                    payer,
+                    treasury: config.treasury,
+                    price_feed,
+                    backstop: config.backstop_fee_bps > 0,
+                    portfolio_vault,
+                    compliance_mint: config.compliance_mint,
+                },
+                policy.into_params(policy_id),
+            );
+            let signers: Vec<&Keypair> = underwriter.iter().collect();
+            let signature = context.send_with(&[ix], &signers).await?;
+            let address = seeds::policy(&liqguard::ID, &payer, policy_id).0;
+            println!("opened policy {policy_id} at {address}: {signature}");
+        }
+        Command::FundVault {
+            policy,
+            amount,
+            sol_price_feed,
+        } => {
+            let account = client
+                .policy(&policy)
+                .await
+                .map_err(|error| error.to_string())?;
+            let ix = instructions::fund_vault(
+                policy,
+                &account,
+                amount,
+                sol_price_feed,
+                TokenAccounts::default(),
+            )
+            .map_err(|error| error.to_string())?;
+            let signature = context.send(&[ix]).await?;
+            println!("funded {policy} with {amount}: {signature}");
+        }
+        Command::ClosePolicy { policy } => {
+            let account = client
+                .policy(&policy)
+                .await
+                .map_err(|error| error.to_string())?;
+            let ix = instructions::close_policy(policy, &account, payer, None);
+            let signature = context.send(&[ix]).await?;
+            println!("closed {policy}: {signature}");
+        }
+        Command::SweepAbandoned { treasury } => {
+            let now = chain_time(client).await?;
+            let abandoned: Vec<_> = client
+                .policies(&[])
+                .await
+                .map_err(|error| error.to_string())?
+                .into_iter()
+                .filter(|(_, policy)| {
+                    policy.is_native()
+                        && policy.is_terminal(now)
+                        && now
+                            >= policy
+                                .expiration_datetime
+                                .saturating_add(ABANDONED_VAULT_GRACE_PERIOD)
+                })
+                .collect();
+            if abandoned.is_empty() {
+                println!("no abandoned policies");
+            }
+            for (address, policy) in abandoned {
+                let ix = instructions::sweep_abandoned_vault(address, &policy, payer, !treasury);
+                let signature = context.send(&[ix]).await?;
+                println!("swept {address}: {signature}");
+            }
+        }
+        Command::Crank {
+            asset,
+            price_feed,
+            lookup_table,
+        } => {
+            let config = client.config().await.map_err(|error| error.to_string())?;
+            let now = chain_time(client).await?;
+            // The batch path only settles plain native spot policies and
+            // skips the rest, so leave those out of its accounts
+            let policies: Vec<_> = client
+                .policies(&[PolicyFilter::Asset(asset)])
+                .await
+                .map_err(|error| error.to_string())?
+                .into_iter()
+                .filter(|(_, policy)| {
+                    policy.is_native()
+                        && policy.twap_window_seconds == 0
+                        && policy.may_liquidate(&config, &payer)
+                        && check_claimable(policy, &config, now).is_ok()
+                })
+                .collect();
+            if policies.is_empty() {
+                println!("no claimable {asset:?} policies");
+            }
+            let tables = match lookup_table {
+                Some(address) => vec![client
+                    .lookup_table(&address)
+                    .await
+                    .map_err(|error| error.to_string())?],
+                None => Vec::new(),
+            };
+            let batch_size = if tables.is_empty() {
+                CRANK_BATCH_SIZE
+            } else {
+                lookup_tables::MAX_BATCH_POLICIES
+            };
+            for batch in policies.chunks(batch_size) {
+                let ix = instructions::liquidate_policies(
+                    asset,
+                    payer,
+                    price_feed,
+                    config.payout_fee_bps > 0,
+                    batch,
+                );
+                let signature = if tables.is_empty() {
+                    context.send(&[ix]).await?
+                } else {
+                    context.send_v0(&[ix], &tables).await?
+                };
+                println!("cranked {} {asset:?} policies: {signature}", batch.len());
+            }
+        }
+        Command::LookupTable(LookupTableCommand::Create) => {
+            let recent_slot = client
+                .rpc
+                .get_slot_with_commitment(CommitmentConfig::finalized())
+                .await
+                .map_err(|error| error.to_string())?;
+            let (ix, table) = lookup_tables::create(payer, payer, recent_slot);
+            let signature = context.send(&[ix]).await?;
+            println!("created lookup table {table}: {signature}");
+        }
+        Command::LookupTable(LookupTableCommand::Extend {
+            table,
+            asset,
+            price_feed,
+        }) => {
+            let config = client.config().await.map_err(|error| error.to_string())?;
+            let now = chain_time(client).await?;
+            let existing = client
+                .lookup_table(&table)
+                .await
+                .map_err(|error| error.to_string())?;
+            let policies = client
+                .policies(&[PolicyFilter::Asset(asset)])
+                .await
+                .map_err(|error| error.to_string())?;
+            let wanted =
+                lookup_tables::shared_accounts(asset, price_feed, config.payout_fee_bps > 0)
+                    .into_iter()
+                    .chain(
+                        policies
+                            .iter()
+                            .filter(|(_, policy)| !policy.is_terminal(now))
+                            .flat_map(|(address, policy)| {
+                                lookup_tables::policy_accounts(address, policy)
+                            }),
+                    );
+            let missing = lookup_tables::missing(&existing.addresses, wanted);
+            for ix in lookup_tables::extend(table, payer, payer, &missing) {
+                let signature = context.send(&[ix]).await?;
+                println!("extended {table}: {signature}");
+            }
+            println!("{} addresses added to {table}", missing.len());
+        }
+        Command::Inspect { address } => {
+            let data = client
+                .account_data(&address)
+                .await
+                .map_err(|error| error.to_string())?;
+            let json = if data.starts_with(&Policy::DISCRIMINATOR) {
+                let policy = client
+                    .policy(&address)
+                    .await
+                    .map_err(|error| error.to_string())?;
+                let config = client.config().await.map_err(|error| error.to_string())?;
+                let now = chain_time(client).await?;
+                let spot = spot_price(client, policy.asset).await;
+                inspect::policy_json(&address, &policy, &config, now, spot)
+            } else {
+                inspect::account_json(&address, &data)?
+            };
+            print_json(&json);
+        }
+        Command::Risk {
+            horizon_hours,
+            confidence_bps,
+            max_var_bps,
+        } => {
+            let policies = client
+                .policies(&[])
+                .await
+                .map_err(|error| error.to_string())?;
+            let config = client.config().await.map_err(|error| error.to_string())?;
+            let now = chain_time(client).await?;
+            let mut markets = Vec::new();
+            for asset in [
+                UnderlyingAsset::Btc,
+                UnderlyingAsset::Eth,
+                UnderlyingAsset::Sol,
+            ] {
+                let Some(spot) = spot_price(client, asset).await else {
+                    eprintln!("warning: no {asset:?} price, its policies are left unpriced");
+                    continue;
+                };
+                // The risk oracle's volatility, else the config-wide fallback
+                let volatility_bps = match client.feed_registry(asset).await {
+                    Ok(feed) if feed.volatility_bps > 0 => feed.volatility_bps,
+                    _ => config.volatility_bps,
+                };
+                markets.push(Market {
+                    asset,
+                    spot: spot.price,
+                    volatility_bps,
+                });
+            }
+            let params = RiskParams {
+                horizon_seconds: horizon_hours * 3_600,
+                confidence_bps,
+                ..RiskParams::default()
+            };
+            let report = RiskReport::build(&policies, &markets, &params, now);
+            let alerts = report.alerts(max_var_bps);
+            print_json(&serde_json::json!({ "report": report, "alerts": alerts }));
+        }
+        Command::Quote(_) => unreachable!("quotes are answered before connecting"),
+        Command::List {
+            status,
+            asset,
+            owner,
+        } => {
+            let filters: Vec<_> = asset
+                .map(PolicyFilter::Asset)
+                .into_iter()
+                .chain(owner.map(PolicyFilter::Owner))
+                .collect();
+            let config = client.config().await.map_err(|error| error.to_string())?;
+            let now = chain_time(client).await?;
+            let mut spots = Vec::new();
+            let mut policies = Vec::new();
+            for (address, policy) in client
+                .policies(&filters)
+                .await
+                .map_err(|error| error.to_string())?
+            {
+                if status.is_some_and(|status| Status::of(&policy, now) != status) {
+                    continue;
+                }
+                let spot = match spots.iter().find(|(cached, _)| *cached == policy.asset) {
+                    Some((_, spot)) => *spot,
+                    None => {
+                        let spot = spot_price(client, policy.asset).await;
+                        spots.push((policy.asset, spot));
+                        spot
+                    }
+                };
+                policies.push(inspect::policy_json(&address, &policy, &config, now, spot));
+            }
+            print_json(&serde_json::Value::Array(policies));
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_parses_subcommands() {
+        Cli::command().debug_assert();
+        let cli = Cli::try_parse_from([
+            "liqguard-cli",
+            "crank",
+            "BTC",
+            "--price-feed",
+            "11111111111111111111111111111111",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Crank {
+                asset: UnderlyingAsset::Btc,
+                ..
+            }
+        ));
+        assert!(
+            Cli::try_parse_from(["liqguard-cli", "crank", "doge", "--price-feed", "x"]).is_err()
+        );
+        assert!(matches!(
+            Cli::try_parse_from(["liqguard-cli", "lookup-table", "create"])
+                .unwrap()
+                .command,
+            Command::LookupTable(LookupTableCommand::Create)
+        ));
+        let cli = Cli::try_parse_from([
+            "liqguard-cli",
+            "list",
+            "--status",
+            "active",
+            "--asset",
+            "btc",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::List {
+                status: Some(Status::Active),
+                asset: Some(UnderlyingAsset::Btc),
+                owner: None,
+            }
+        ));
+    }
+}
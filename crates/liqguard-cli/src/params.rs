@@ -0,0 +1,339 @@
+// TOML forms of the instruction params the CLI sends. Each mirrors its
+// program struct field for field, with every field but the essentials
+// optional, and converts with the same checks the program's types imply
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use liqguard::{
+    cluster, BarrierKind, ConfidenceAdjustment, ConfigParams, ExerciseStyle,
+    InitializePolicyParams, PayoutMode, TriggerDirection, UnderlyingAsset,
+    MAX_ALLOWLISTED_LIQUIDATORS,
+};
+use serde::{Deserialize, Deserializer};
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    Pubkey::from_str(&text).map_err(|_| serde::de::Error::custom(format!("bad pubkey {text:?}")))
+}
+
+fn bytes32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+    let text = String::deserialize(deserializer)?;
+    hex32(&text)
+        .ok_or_else(|| serde::de::Error::custom(format!("expected 64 hex digits, got {text:?}")))
+}
+
+fn hex32(text: &str) -> Option<[u8; 32]> {
+    if text.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(text.get(2 * i..2 * i + 2)?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+pub fn load<T: for<'de> Deserialize<'de>>(path: &std::path::Path) -> Result<T, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| format!("reading {}: {error}", path.display()))?;
+    toml::from_str(&text).map_err(|error| format!("{}: {error}", path.display()))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub max_confidence_bps: u16,
+    pub bounty_bps: u16,
+    pub bounty_flat_lamports: u64,
+    #[serde(deserialize_with = "pubkey")]
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub default_max_price_age: u64,
+    pub max_coverage_amount: u64,
+    pub max_owner_coverage: u64,
+    pub strike_update_delay: i64,
+    pub min_policy_age: i64,
+    pub max_price_deviation_bps: u16,
+    pub deviation_confirm_delay: i64,
+    pub volatility_bps: u32,
+    pub premium_markup_bps: u16,
+    pub backstop_fee_bps: u16,
+    pub max_backstop_draw: u64,
+    pub liquidator_allowlist: Vec<String>,
+    pub config_change_delay: i64,
+    pub wormhole_emitter_chain: u16,
+    #[serde(deserialize_with = "bytes32")]
+    pub wormhole_emitter_address: [u8; 32],
+    #[serde(deserialize_with = "pubkey")]
+    pub arbiter: Pubkey,
+    pub dispute_window: i64,
+    pub dispute_holdback_bps: u16,
+    pub payout_fee_bps: u16,
+    #[serde(deserialize_with = "pubkey")]
+    pub risk_oracle: Pubkey,
+    pub max_volatility_age: i64,
+    #[serde(deserialize_with = "pubkey")]
+    pub usdc_mint: Pubkey,
+    pub max_swap_slippage_bps: u16,
+    #[serde(deserialize_with = "pubkey")]
+    pub compliance_mint: Pubkey,
+    pub utilization_kink_bps: u16,
+    pub kink_markup_bps: u16,
+    pub full_utilization_markup_bps: u16,
+}
+
+impl ConfigFile {
+    pub fn into_params(self) -> Result<ConfigParams, String> {
+        if self.liquidator_allowlist.len() > MAX_ALLOWLISTED_LIQUIDATORS {
+            return Err(format!(
+                "liquidator_allowlist holds at most {MAX_ALLOWLISTED_LIQUIDATORS} keepers"
+            ));
+        }
+        let mut liquidator_allowlist = [Pubkey::default(); MAX_ALLOWLISTED_LIQUIDATORS];
+        for (slot, key) in liquidator_allowlist
+            .iter_mut()
+            .zip(&self.liquidator_allowlist)
+        {
+            *slot = Pubkey::from_str(key).map_err(|_| format!("bad pubkey {key:?}"))?;
+        }
+        Ok(ConfigParams {
+            max_confidence_bps: self.max_confidence_bps,
+            bounty_bps: self.bounty_bps,
+            bounty_flat_lamports: self.bounty_flat_lamports,
+            treasury: self.treasury,
+            protocol_fee_bps: self.protocol_fee_bps,
+            default_max_price_age: match self.default_max_price_age {
+                0 => cluster::DEFAULT_MAX_PRICE_AGE,
+                age => age,
+            },
+            max_coverage_amount: self.max_coverage_amount,
+            max_owner_coverage: self.max_owner_coverage,
+            strike_update_delay: self.strike_update_delay,
+            min_policy_age: self.min_policy_age,
+            max_price_deviation_bps: self.max_price_deviation_bps,
+            deviation_confirm_delay: self.deviation_confirm_delay,
+            volatility_bps: self.volatility_bps,
+            premium_markup_bps: self.premium_markup_bps,
+            backstop_fee_bps: self.backstop_fee_bps,
+            max_backstop_draw: self.max_backstop_draw,
+            liquidator_allowlist,
+            config_change_delay: self.config_change_delay,
+            wormhole_emitter_chain: self.wormhole_emitter_chain,
+            wormhole_emitter_address: self.wormhole_emitter_address,
+            arbiter: self.arbiter,
+            dispute_window: self.dispute_window,
+            dispute_holdback_bps: self.dispute_holdback_bps,
+            payout_fee_bps: self.payout_fee_bps,
+            risk_oracle: self.risk_oracle,
+            max_volatility_age: self.max_volatility_age,
+            usdc_mint: self.usdc_mint,
+            max_swap_slippage_bps: self.max_swap_slippage_bps,
+            compliance_mint: self.compliance_mint,
+            utilization_kink_bps: self.utilization_kink_bps,
+            kink_markup_bps: self.kink_markup_bps,
+            full_utilization_markup_bps: self.full_utilization_markup_bps,
+        })
+    }
+}
+
+fn default_confidence_adjustment() -> ConfidenceAdjustment {
+    ConfidenceAdjustment::None
+}
+
+fn default_payout_mode() -> PayoutMode {
+    PayoutMode::Binary
+}
+
+fn default_exercise_style() -> ExerciseStyle {
+    ExerciseStyle::American
+}
+
+fn default_barrier_kind() -> BarrierKind {
+    BarrierKind::None
+}
+
+// All of InitializePolicyParams but policy_id, which comes from the
+// owner's policy count
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyFile {
+    pub asset: UnderlyingAsset,
+    pub direction: TriggerDirection,
+    #[serde(default)]
+    pub strike_price: u64,
+    #[serde(default)]
+    pub upper_strike_price: u64,
+    #[serde(default)]
+    pub inclusive_strike: bool,
+    #[serde(default)]
+    pub trigger_buffer_bps: u16,
+    #[serde(default = "default_confidence_adjustment")]
+    pub confidence_adjustment: ConfidenceAdjustment,
+    #[serde(default)]
+    pub confidence_multiplier_bps: u16,
+    #[serde(default)]
+    pub drawdown_bps: u16,
+    pub coverage_amount: u64,
+    #[serde(default)]
+    pub usd_coverage: bool,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub lst_stake_pool: Pubkey,
+    pub expiration_datetime: i64,
+    #[serde(default)]
+    pub max_price_age: u64,
+    #[serde(default = "default_payout_mode")]
+    pub payout_mode: PayoutMode,
+    #[serde(default)]
+    pub payout_slope_bps: u32,
+    #[serde(default)]
+    pub payout_floor_bps: u16,
+    #[serde(default)]
+    pub payout_cap_bps: u16,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub collateral_mint: Pubkey,
+    #[serde(default)]
+    pub premium_amount: u64,
+    #[serde(deserialize_with = "pubkey")]
+    pub underwriter: Pubkey,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub beneficiary: Pubkey,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub usdc_payout_account: Pubkey,
+    #[serde(default)]
+    pub twap_window_seconds: u64,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub switchboard_feed: Pubkey,
+    #[serde(default)]
+    pub oracle_tolerance_bps: u16,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub chainlink_feed: Pubkey,
+    #[serde(default)]
+    pub roll_period_seconds: i64,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub drift_user: Pubkey,
+    #[serde(default)]
+    pub drift_perp_market_index: u16,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub kamino_obligation: Pubkey,
+    #[serde(default)]
+    pub health_threshold_bps: u16,
+    #[serde(default)]
+    pub vesting_period_seconds: i64,
+    #[serde(default)]
+    pub immediate_payout_bps: u16,
+    #[serde(default = "default_exercise_style")]
+    pub exercise_style: ExerciseStyle,
+    #[serde(default = "default_barrier_kind")]
+    pub barrier_kind: BarrierKind,
+    #[serde(default)]
+    pub barrier_price: u64,
+    #[serde(default)]
+    pub premium_rate: u64,
+    #[serde(default)]
+    pub stream_deposit: u64,
+    #[serde(default)]
+    pub permissioned_liquidation: bool,
+    #[serde(default, deserialize_with = "bytes32")]
+    pub premium_payment_id: [u8; 32],
+    #[serde(default)]
+    pub wormhole_premium: u64,
+    #[serde(default, deserialize_with = "pubkey")]
+    pub compliance_mint: Pubkey,
+    #[serde(default)]
+    pub breach_dwell_seconds: i64,
+    #[serde(default)]
+    pub short_strike_price: u64,
+}
+
+impl PolicyFile {
+    pub fn into_params(self, policy_id: u64) -> InitializePolicyParams {
+        InitializePolicyParams {
+            policy_id,
+            asset: self.asset,
+            strike_price: self.strike_price,
+            direction: self.direction,
+            upper_strike_price: self.upper_strike_price,
+            inclusive_strike: self.inclusive_strike,
+            trigger_buffer_bps: self.trigger_buffer_bps,
+            confidence_adjustment: self.confidence_adjustment,
+            confidence_multiplier_bps: self.confidence_multiplier_bps,
+            drawdown_bps: self.drawdown_bps,
+            coverage_amount: self.coverage_amount,
+            usd_coverage: self.usd_coverage,
+            lst_stake_pool: self.lst_stake_pool,
+            expiration_datetime: self.expiration_datetime,
+            max_price_age: self.max_price_age,
+            payout_mode: self.payout_mode,
+            payout_slope_bps: self.payout_slope_bps,
+            payout_floor_bps: self.payout_floor_bps,
+            payout_cap_bps: self.payout_cap_bps,
+            collateral_mint: self.collateral_mint,
+            premium_amount: self.premium_amount,
+            underwriter: self.underwriter,
+            beneficiary: self.beneficiary,
+            usdc_payout_account: self.usdc_payout_account,
+            twap_window_seconds: self.twap_window_seconds,
+            switchboard_feed: self.switchboard_feed,
+            oracle_tolerance_bps: self.oracle_tolerance_bps,
+            chainlink_feed: self.chainlink_feed,
+            roll_period_seconds: self.roll_period_seconds,
+            drift_user: self.drift_user,
+            drift_perp_market_index: self.drift_perp_market_index,
+            kamino_obligation: self.kamino_obligation,
+            health_threshold_bps: self.health_threshold_bps,
+            vesting_period_seconds: self.vesting_period_seconds,
+            immediate_payout_bps: self.immediate_payout_bps,
+            exercise_style: self.exercise_style,
+            barrier_kind: self.barrier_kind,
+            barrier_price: self.barrier_price,
+            premium_rate: self.premium_rate,
+            stream_deposit: self.stream_deposit,
+            permissioned_liquidation: self.permissioned_liquidation,
+            premium_payment_id: self.premium_payment_id,
+            wormhole_premium: self.wormhole_premium,
+            compliance_mint: self.compliance_mint,
+            breach_dwell_seconds: self.breach_dwell_seconds,
+            short_strike_price: self.short_strike_price,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_files_parse() {
+        let config: ConfigFile = toml::from_str(include_str!("../config.example.toml")).unwrap();
+        let params = config.into_params().unwrap();
+        assert_eq!(params.default_max_price_age, 60);
+        assert_eq!(
+            params.liquidator_allowlist,
+            [Pubkey::default(); MAX_ALLOWLISTED_LIQUIDATORS]
+        );
+
+        let policy: PolicyFile = toml::from_str(include_str!("../policy.example.toml")).unwrap();
+        let params = policy.into_params(3);
+        assert_eq!(params.policy_id, 3);
+        assert_eq!(params.asset, UnderlyingAsset::Btc);
+        assert_eq!(params.direction, TriggerDirection::ProtectLong);
+        assert_eq!(params.exercise_style, ExerciseStyle::American);
+    }
+
+    #[test]
+    fn rejects_malformed_fields() {
+        assert!(toml::from_str::<ConfigFile>("treasury = \"nope\"").is_err());
+        assert!(toml::from_str::<ConfigFile>("wormhole_emitter_address = \"abcd\"").is_err());
+        assert!(toml::from_str::<ConfigFile>("bounty = 5").is_err());
+        let config: ConfigFile = toml::from_str(&format!(
+            "liquidator_allowlist = [{}]",
+            ["\"11111111111111111111111111111111\""; MAX_ALLOWLISTED_LIQUIDATORS + 1].join(",")
+        ))
+        .unwrap();
+        assert!(config.into_params().is_err());
+        let params = ConfigFile::default().into_params().unwrap();
+        assert_eq!(params.default_max_price_age, cluster::DEFAULT_MAX_PRICE_AGE);
+        assert_eq!(hex32(&"0a".repeat(32)), Some([10; 32]));
+    }
+}
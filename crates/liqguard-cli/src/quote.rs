@@ -0,0 +1,167 @@
+// `quote`: the premium the program would suggest for a policy's terms,
+// worked out off-chain with liqguard-pricing, next to the Black-76 price
+// and greeks of the vanilla options that hedge it. Nothing is read from
+// the cluster, so spot and volatility are given
+
+use clap::{Args, ValueEnum};
+use liqguard_pricing::{suggest_premium, trigger_probability, Inputs, Model, OptionKind, Trigger};
+use serde_json::{json, Value};
+
+const BPS: f64 = 10_000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Direction {
+    ProtectLong,  // pays at or below the strike
+    ProtectShort, // pays at or above it
+    OutsideRange, // pays outside [strike, upper_strike]
+}
+
+#[derive(Debug, Args)]
+pub struct QuoteArgs {
+    /// Current price, in USD
+    #[arg(long)]
+    pub spot: f64,
+    /// Strike in USD; the band's lower edge for outside-range
+    #[arg(long)]
+    pub strike: f64,
+    /// The band's upper edge, for outside-range
+    #[arg(long)]
+    pub upper_strike: Option<f64>,
+    #[arg(long, value_enum, default_value = "protect-long")]
+    pub direction: Direction,
+    /// Days until expiry
+    #[arg(long)]
+    pub days: f64,
+    /// Annual volatility, 6000 = 60%
+    #[arg(long)]
+    pub volatility_bps: u32,
+    /// Coverage, in the collateral's base units
+    #[arg(long)]
+    pub coverage: u64,
+    /// Markup over the fair premium
+    #[arg(long, default_value_t = 0)]
+    pub markup_bps: u16,
+    /// Pay only on the price at expiry rather than on any touch
+    #[arg(long)]
+    pub european: bool,
+    /// Annual rate the vanilla prices are discounted at, 0.05 = 5%
+    #[arg(long, default_value_t = 0.0)]
+    pub rate: f64,
+}
+
+impl QuoteArgs {
+    fn trigger(&self) -> Result<Trigger, String> {
+        match (self.direction, self.upper_strike) {
+            (Direction::ProtectLong, None) => Ok(Trigger::Below(self.strike)),
+            (Direction::ProtectShort, None) => Ok(Trigger::Above(self.strike)),
+            (Direction::OutsideRange, Some(upper)) if upper > self.strike => {
+                Ok(Trigger::Outside(self.strike, upper))
+            }
+            (Direction::OutsideRange, _) => {
+                Err("outside-range needs an --upper-strike above --strike".to_string())
+            }
+            (_, Some(_)) => Err("--upper-strike is only for outside-range".to_string()),
+        }
+    }
+}
+
+fn vanilla(kind: OptionKind, inputs: &Inputs) -> Value {
+    let greeks = Model::Black76.greeks(kind, inputs);
+    json!({
+        "kind": match kind {
+            OptionKind::Call => "call",
+            OptionKind::Put => "put",
+        },
+        "strike": inputs.strike,
+        "price": Model::Black76.price(kind, inputs),
+        "delta": greeks.delta,
+        "gamma": greeks.gamma,
+        "vega": greeks.vega,
+        "theta": greeks.theta,
+    })
+}
+
+pub fn quote_json(args: &QuoteArgs) -> Result<Value, String> {
+    let trigger = args.trigger()?;
+    let years = args.days.max(0.0) / 365.0;
+    let volatility = args.volatility_bps as f64 / BPS;
+    let probability = trigger_probability(args.spot, trigger, years, volatility, !args.european)
+        .ok_or("spot and strikes must be positive")?;
+    let coverage = args.coverage as f64;
+    let inputs = |strike| Inputs {
+        forward: args.spot,
+        strike,
+        years,
+        volatility,
+        rate: args.rate,
+    };
+    let vanillas = match trigger {
+        Trigger::Below(strike) => vec![vanilla(OptionKind::Put, &inputs(strike))],
+        Trigger::Above(strike) => vec![vanilla(OptionKind::Call, &inputs(strike))],
+        Trigger::Outside(lower, upper) => vec![
+            vanilla(OptionKind::Put, &inputs(lower)),
+            vanilla(OptionKind::Call, &inputs(upper)),
+        ],
+    };
+    Ok(json!({
+        "probability": probability,
+        "fair_premium": suggest_premium(coverage, probability, 0).round() as u64,
+        "premium": suggest_premium(coverage, probability, args.markup_bps).round() as u64,
+        "black76": vanillas,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> QuoteArgs {
+        QuoteArgs {
+            spot: 100_000.0,
+            strike: 90_000.0,
+            upper_strike: None,
+            direction: Direction::ProtectLong,
+            days: 30.0,
+            volatility_bps: 6_000,
+            coverage: 1_000_000,
+            markup_bps: 2_500,
+            european: false,
+            rate: 0.0,
+        }
+    }
+
+    #[test]
+    fn quotes_mark_up_the_fair_premium_and_price_the_hedge() {
+        let json = quote_json(&args()).unwrap();
+        let fair = json["fair_premium"].as_u64().unwrap();
+        let premium = json["premium"].as_u64().unwrap();
+        assert!(fair > 0 && premium.abs_diff(fair * 5 / 4) <= 1);
+        assert_eq!(json["black76"][0]["kind"], "put");
+        assert!(json["black76"][0]["delta"].as_f64().unwrap() < 0.0);
+
+        let european = quote_json(&QuoteArgs {
+            european: true,
+            ..args()
+        })
+        .unwrap();
+        assert!(european["fair_premium"].as_u64().unwrap() < fair);
+    }
+
+    #[test]
+    fn bands_need_an_upper_strike_above_the_lower() {
+        let band = |upper_strike| QuoteArgs {
+            direction: Direction::OutsideRange,
+            upper_strike,
+            ..args()
+        };
+        assert!(quote_json(&band(None)).is_err());
+        assert!(quote_json(&band(Some(80_000.0))).is_err());
+        let json = quote_json(&band(Some(110_000.0))).unwrap();
+        assert_eq!(json["black76"].as_array().unwrap().len(), 2);
+        assert!(quote_json(&QuoteArgs {
+            upper_strike: Some(110_000.0),
+            ..args()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,154 @@
+// Decoding raw account data into the program's account types. Anchor
+// accounts are borsh behind their discriminator; zero-copy ones are read
+// in place, copied out since RPC buffers carry no alignment guarantee
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator, ZeroCopy};
+use liqguard::Policy;
+
+use crate::{ClientError, Result};
+
+// Byte offsets into a serialized Policy, for getProgramAccounts filters
+pub const POLICY_OWNER_OFFSET: usize = 8;
+pub const POLICY_CREATOR_OFFSET: usize = 40;
+pub const POLICY_BENEFICIARY_OFFSET: usize = 72;
+pub const POLICY_ASSET_OFFSET: usize = 120;
+
+pub fn decode<T: AccountDeserialize + Discriminator>(address: &Pubkey, data: &[u8]) -> Result<T> {
+    let mut data = data;
+    T::try_deserialize(&mut data)
+        .map_err(|_| ClientError::InvalidAccount(*address, std::any::type_name::<T>()))
+}
+
+pub fn decode_zero_copy<T: ZeroCopy>(address: &Pubkey, data: &[u8]) -> Result<T> {
+    let invalid = || ClientError::InvalidAccount(*address, std::any::type_name::<T>());
+    let body = data
+        .strip_prefix(&T::DISCRIMINATOR)
+        .and_then(|body| body.get(..std::mem::size_of::<T>()))
+        .ok_or_else(invalid)?;
+    bytemuck::try_pod_read_unaligned(body).map_err(|_| invalid())
+}
+
+// Where a policy is in its life at a given time, as listings filter on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyStatus {
+    Unfunded, // opened, vault not yet holding the coverage
+    Active,   // funded and live
+    Claimed,  // coverage fully paid out
+    Terminal, // expired, lapsed or knocked out with coverage left
+}
+
+impl PolicyStatus {
+    pub const ALL: [PolicyStatus; 4] = [
+        PolicyStatus::Unfunded,
+        PolicyStatus::Active,
+        PolicyStatus::Claimed,
+        PolicyStatus::Terminal,
+    ];
+
+    pub fn of(policy: &Policy, now: i64) -> Self {
+        if policy.is_exhausted() {
+            PolicyStatus::Claimed
+        } else if policy.is_terminal(now) {
+            PolicyStatus::Terminal
+        } else if !policy.is_funded {
+            PolicyStatus::Unfunded
+        } else {
+            PolicyStatus::Active
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyStatus::Unfunded => "unfunded",
+            PolicyStatus::Active => "active",
+            PolicyStatus::Claimed => "claimed",
+            PolicyStatus::Terminal => "terminal",
+        }
+    }
+}
+
+impl std::str::FromStr for PolicyStatus {
+    type Err = String;
+
+    fn from_str(text: &str) -> std::result::Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == text)
+            .ok_or_else(|| format!("unknown policy status {text:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+    use liqguard::{ClaimHistory, OwnerState, UnderlyingAsset};
+    use liqguard_test_support::zeroed_policy;
+
+    #[test]
+    fn policy_filter_offsets_match_the_layout() {
+        let mut policy = zeroed_policy();
+        policy.owner = Pubkey::new_unique();
+        policy.creator = Pubkey::new_unique();
+        policy.beneficiary = Pubkey::new_unique();
+        policy.asset = UnderlyingAsset::Sol;
+        let mut data = Vec::new();
+        policy.try_serialize(&mut data).unwrap();
+        let at = |offset: usize| &data[offset..offset + 32];
+        assert_eq!(at(POLICY_OWNER_OFFSET), policy.owner.as_ref());
+        assert_eq!(at(POLICY_CREATOR_OFFSET), policy.creator.as_ref());
+        assert_eq!(at(POLICY_BENEFICIARY_OFFSET), policy.beneficiary.as_ref());
+        assert_eq!(data[POLICY_ASSET_OFFSET], UnderlyingAsset::Sol.seed()[0]);
+    }
+
+    #[test]
+    fn decode_round_trips_and_checks_the_discriminator() {
+        let address = Pubkey::new_unique();
+        let state = OwnerState {
+            owner: Pubkey::new_unique(),
+            policy_count: 3,
+            active_coverage: 1_000,
+            bump: 254,
+        };
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        let decoded: OwnerState = decode(&address, &data).unwrap();
+        assert_eq!((decoded.owner, decoded.policy_count), (state.owner, 3));
+
+        data[0] ^= 1;
+        assert!(decode::<OwnerState>(&address, &data).is_err());
+    }
+
+    #[test]
+    fn decode_zero_copy_reads_unaligned_buffers() {
+        let address = Pubkey::new_unique();
+        let mut history: ClaimHistory = bytemuck::Zeroable::zeroed();
+        history.total_claims = 7;
+        // Offset by one byte so the body can't be aligned
+        let mut data = vec![0u8];
+        data.extend_from_slice(&ClaimHistory::DISCRIMINATOR);
+        data.extend_from_slice(bytemuck::bytes_of(&history));
+        let decoded: ClaimHistory = decode_zero_copy(&address, &data[1..]).unwrap();
+        assert_eq!(decoded.total_claims, 7);
+        assert!(decode_zero_copy::<ClaimHistory>(&address, &data[1..40]).is_err());
+    }
+
+    #[test]
+    fn status_follows_funding_and_claims() {
+        let mut policy = zeroed_policy();
+        policy.coverage_amount = 1_000;
+        policy.coverage_remaining = 1_000;
+        policy.expiration_datetime = 2_000;
+        assert_eq!(PolicyStatus::of(&policy, 1_000), PolicyStatus::Unfunded);
+        policy.is_funded = true;
+        assert_eq!(PolicyStatus::of(&policy, 1_000), PolicyStatus::Active);
+        assert_eq!(PolicyStatus::of(&policy, 3_000), PolicyStatus::Terminal);
+        policy.coverage_remaining = 0;
+        assert_eq!(PolicyStatus::of(&policy, 1_000), PolicyStatus::Claimed);
+        for status in PolicyStatus::ALL {
+            assert_eq!(status.as_str().parse(), Ok(status));
+        }
+        assert!("open".parse::<PolicyStatus>().is_err());
+    }
+}
@@ -0,0 +1,303 @@
+// Compute budgets sized per instruction instead of guessed. Each liqguard
+// instruction is given the most liqguard-bench measured it consuming in
+// any of its scenarios (budgets.toml, headroom included), instructions it
+// hasn't measured and other programs' get the runtime's default, and the
+// transaction asks for the sum. Priority fees are bid off what recently
+// landed on the accounts a transaction write-locks
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::Discriminator;
+use liqguard::{instruction, UnderlyingAsset};
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+
+use crate::instructions;
+use crate::rpc::LiqGuardClient;
+use crate::Result;
+
+// What the runtime gives an instruction that doesn't ask for a budget
+pub const DEFAULT_INSTRUCTION_UNITS: u32 = 200_000;
+
+// The most any transaction may ask for
+pub const MAX_TRANSACTION_UNITS: u32 = 1_400_000;
+
+// Builtins such as the system and compute-budget programs cost a flat fee
+const BUILTIN_UNITS: u32 = 150;
+
+// getRecentPrioritizationFees takes at most this many accounts
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+const BUDGETS: &str = include_str!("../../liqguard-bench/budgets.toml");
+
+// Anchor's instruction discriminator for an instruction name
+fn discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("global:{name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+// (instruction, scenario, units) per budgets.toml entry, e.g.
+// ("liquidate_policy", "full_queue", 200_000)
+fn scenarios() -> impl Iterator<Item = (&'static str, &'static str, u32)> {
+    BUDGETS.lines().filter_map(|line| {
+        let (name, units) = line.split_once('=')?;
+        let name = name.trim().trim_matches('"');
+        let (instruction, scenario) = name.split_once('/').unwrap_or((name, ""));
+        Some((instruction, scenario, units.trim().parse().ok()?))
+    })
+}
+
+fn budgets() -> impl Iterator<Item = (&'static str, u32)> {
+    scenarios().map(|(instruction, _, units)| (instruction, units))
+}
+
+// A liquidate_policies batch costs more the more policies it settles, so
+// it gets the largest benchmarked batch's budget for every that many
+// policies it holds
+fn batch_units(instruction: &Instruction) -> Option<u32> {
+    let (size, units) = scenarios()
+        .filter(|(name, _, _)| *name == "liquidate_policies")
+        .filter_map(|(_, scenario, units)| {
+            let size: usize = scenario.split_once('_')?.0.parse().ok()?;
+            Some((size, units))
+        })
+        .max()?;
+    let shared = instructions::liquidate_policies(
+        UnderlyingAsset::Sol,
+        Pubkey::default(),
+        Pubkey::default(),
+        false,
+        &[],
+    )
+    .accounts
+    .len();
+    let policies = instruction.accounts.len().saturating_sub(shared) / 3;
+    let batches = policies.div_ceil(size.max(1)).max(1) as u32;
+    Some(units.saturating_mul(batches))
+}
+
+// The budget of a liqguard instruction by name, None if it isn't benchmarked
+pub fn benchmarked_units(name: &str) -> Option<u32> {
+    budgets()
+        .filter(|(instruction, _)| *instruction == name)
+        .map(|(_, units)| units)
+        .max()
+}
+
+// Units to set aside for instruction
+pub fn instruction_units(instruction: &Instruction) -> u32 {
+    let program_id = instruction.program_id;
+    if program_id == system_program::ID || program_id == compute_budget::ID {
+        return BUILTIN_UNITS;
+    }
+    if program_id != liqguard::ID || instruction.data.len() < 8 {
+        return DEFAULT_INSTRUCTION_UNITS;
+    }
+    if instruction.data[..8] == instruction::LiquidatePolicies::DISCRIMINATOR {
+        if let Some(units) = batch_units(instruction) {
+            return units;
+        }
+    }
+    budgets()
+        .filter(|(name, _)| instruction.data[..8] == discriminator(name))
+        .map(|(_, units)| units)
+        .max()
+        .unwrap_or(DEFAULT_INSTRUCTION_UNITS)
+}
+
+// The limit a transaction of instructions needs, counting the
+// compute-budget instructions with_compute_budget adds
+pub fn compute_unit_limit(instructions: &[Instruction]) -> u32 {
+    instructions
+        .iter()
+        .filter(|instruction| instruction.program_id != compute_budget::ID)
+        .map(instruction_units)
+        .fold(2 * BUILTIN_UNITS, u32::saturating_add)
+        .min(MAX_TRANSACTION_UNITS)
+}
+
+// instructions behind a unit limit sized for them and, when
+// micro_lamports is non-zero, a unit price. Compute-budget instructions
+// already among them are replaced
+pub fn with_compute_budget(instructions: &[Instruction], micro_lamports: u64) -> Vec<Instruction> {
+    let mut budgeted = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        compute_unit_limit(instructions),
+    )];
+    if micro_lamports > 0 {
+        budgeted.push(ComputeBudgetInstruction::set_compute_unit_price(
+            micro_lamports,
+        ));
+    }
+    budgeted.extend(
+        instructions
+            .iter()
+            .filter(|instruction| instruction.program_id != compute_budget::ID)
+            .cloned(),
+    );
+    budgeted
+}
+
+// Accounts the instructions write-lock, which are what local fee markets
+// price
+pub fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = Vec::new();
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts.truncate(MAX_FEE_ACCOUNTS);
+    accounts
+}
+
+// The percentile-th smallest of recent fees, in micro-lamports per CU.
+// Zero when there are no samples
+pub fn percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    fees[rank]
+}
+
+impl LiqGuardClient {
+    // A unit price, in micro-lamports, at the percentile of recent
+    // priority fees paid on the accounts instructions write-lock
+    pub async fn priority_fee(&self, instructions: &[Instruction], percentile: u8) -> Result<u64> {
+        let recent = self
+            .rpc
+            .get_recent_prioritization_fees(&writable_accounts(instructions))
+            .await?;
+        Ok(self::percentile(
+            recent.iter().map(|fee| fee.prioritization_fee).collect(),
+            percentile,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::instruction::AccountMeta;
+    use anchor_lang::InstructionData;
+    use liqguard_test_support::zeroed_policy;
+    use solana_sdk::system_instruction;
+
+    fn liqguard_ix(data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: liqguard::ID,
+            accounts: Vec::new(),
+            data: data.data(),
+        }
+    }
+
+    #[test]
+    fn liqguard_instructions_get_their_benchmarked_budget() {
+        assert_eq!(
+            discriminator("liquidate_policy"),
+            instruction::LiquidatePolicy::DISCRIMINATOR
+        );
+        let liquidate = benchmarked_units("liquidate_policy").unwrap();
+        assert_eq!(
+            instruction_units(&liqguard_ix(instruction::LiquidatePolicy {})),
+            liquidate
+        );
+        // Not benchmarked, and not liqguard's
+        assert_eq!(benchmarked_units("swap_payout"), None);
+        assert_eq!(
+            instruction_units(&liqguard_ix(instruction::SwapPayout {
+                route_data: Vec::new()
+            })),
+            DEFAULT_INSTRUCTION_UNITS
+        );
+        let transfer =
+            system_instruction::transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+        assert_eq!(instruction_units(&transfer), BUILTIN_UNITS);
+    }
+
+    #[test]
+    fn budgets_replace_existing_ones_and_cap_at_the_transaction_limit() {
+        let liquidate = liqguard_ix(instruction::LiquidatePolicy {});
+        let instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(1),
+            liquidate.clone(),
+        ];
+        let budgeted = with_compute_budget(&instructions, 0);
+        assert_eq!(budgeted.len(), 2);
+        assert_eq!(
+            budgeted[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(
+                instruction_units(&liquidate) + 2 * BUILTIN_UNITS
+            )
+        );
+        assert_eq!(with_compute_budget(&instructions, 5_000).len(), 3);
+
+        let many = vec![liquidate; 20];
+        assert_eq!(compute_unit_limit(&many), MAX_TRANSACTION_UNITS);
+    }
+
+    #[test]
+    fn batches_get_a_measured_batch_budget_per_batch_size() {
+        let (size, units) = scenarios()
+            .filter(|(name, scenario, _)| {
+                *name == "liquidate_policies" && scenario.ends_with("_policies")
+            })
+            .map(|(_, scenario, units)| {
+                (
+                    scenario
+                        .split_once('_')
+                        .unwrap()
+                        .0
+                        .parse::<usize>()
+                        .unwrap(),
+                    units,
+                )
+            })
+            .max()
+            .unwrap();
+        let policy = zeroed_policy();
+        let batch = |count: usize| {
+            let policies = vec![(Pubkey::new_unique(), policy.clone()); count];
+            instructions::liquidate_policies(
+                UnderlyingAsset::Btc,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                false,
+                &policies,
+            )
+        };
+        assert_eq!(instruction_units(&batch(1)), units);
+        assert_eq!(instruction_units(&batch(size)), units);
+        assert_eq!(instruction_units(&batch(size + 1)), 2 * units);
+        assert_eq!(instruction_units(&batch(0)), units);
+    }
+
+    #[test]
+    fn fee_accounts_dedupe_and_percentiles_rank() {
+        assert_eq!(percentile(vec![], 75), 0);
+        assert_eq!(percentile(vec![50, 10, 40, 20, 30], 75), 40);
+        assert_eq!(percentile(vec![50, 10, 40, 20, 30], 100), 50);
+
+        let (a, b, c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let ix = |accounts| Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts,
+            data: Vec::new(),
+        };
+        let instructions = [
+            ix(vec![
+                AccountMeta::new(a, true),
+                AccountMeta::new_readonly(b, false),
+            ]),
+            ix(vec![AccountMeta::new(a, false), AccountMeta::new(c, false)]),
+        ];
+        assert_eq!(writable_accounts(&instructions), vec![a, c]);
+    }
+}
@@ -0,0 +1,439 @@
+// Typed program errors. A failed send or simulation only carries the
+// error's number (a custom instruction error, and an "Error Number" in the
+// logs Anchor writes), so callers can match on
+// ProgramError::LiqGuard(LiqGuardError::LiquidationConditionNotMet) instead
+// of grepping for it. Numbers from 6000 are liqguard's own; below that they
+// are Anchor's, raised by the account constraints that guard every
+// instruction. A CPI's failure surfaces under the same custom number as one
+// of liqguard's, so logs are read first when they're available
+
+use std::fmt;
+
+pub use anchor_lang::error::ErrorCode;
+pub use liqguard::LiqGuardError;
+
+use solana_client::client_error::{ClientError as RpcClientError, ClientErrorKind};
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+// anchor_lang::error::ERROR_CODE_OFFSET
+const LIQGUARD_OFFSET: u32 = 6000;
+
+#[derive(Clone, Copy, Debug)]
+pub enum ProgramError {
+    LiqGuard(LiqGuardError),
+    Anchor(ErrorCode),
+}
+
+impl ProgramError {
+    pub fn from_code(code: u32) -> Option<Self> {
+        if code >= LIQGUARD_OFFSET {
+            let index = (code - LIQGUARD_OFFSET) as usize;
+            return LIQGUARD_ERRORS.get(index).copied().map(Self::LiqGuard);
+        }
+        ANCHOR_ERRORS
+            .iter()
+            .find(|error| u32::from(**error) == code)
+            .copied()
+            .map(Self::Anchor)
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::LiqGuard(error) => u32::from(*error),
+            Self::Anchor(error) => u32::from(*error),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::LiqGuard(error) => error.name(),
+            Self::Anchor(error) => error.name(),
+        }
+    }
+
+    // The custom error a transaction failed with, taken to be liqguard's:
+    // without logs there's no telling which program raised it
+    pub fn from_transaction_error(error: &TransactionError) -> Option<Self> {
+        match error {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                Self::from_code(*code)
+            }
+            _ => None,
+        }
+    }
+
+    // The error in a failed transaction's logs, if liqguard raised it. The
+    // first "failed" line is the innermost program; the ones after it are
+    // its callers passing the error up
+    pub fn from_logs(logs: &[String]) -> Option<Self> {
+        let failure = logs.iter().find_map(|line| {
+            let rest = line.strip_prefix("Program ")?;
+            let (program, reason) = rest.split_once(" failed: ")?;
+            Some((program, reason))
+        })?;
+        if failure.0 != liqguard::ID.to_string() {
+            return None;
+        }
+        let hex = failure.1.strip_prefix("custom program error: 0x")?;
+        Self::from_code(u32::from_str_radix(hex.trim(), 16).ok()?)
+    }
+
+    // The error a failed send or simulation ended on: from its logs when
+    // it has any, else from its code
+    pub fn parse(error: &TransactionError, logs: Option<&[String]>) -> Option<Self> {
+        match logs {
+            Some(logs) if !logs.is_empty() => Self::from_logs(logs),
+            _ => Self::from_transaction_error(error),
+        }
+    }
+
+    // From an RPC error, using the preflight simulation's logs when a send
+    // was rejected before reaching the cluster
+    pub fn from_rpc_error(error: &RpcClientError) -> Option<Self> {
+        let logs = match error.kind() {
+            ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+                ..
+            }) => result.logs.as_deref(),
+            _ => None,
+        };
+        Self::parse(&error.get_transaction_error()?, logs)
+    }
+}
+
+impl PartialEq for ProgramError {
+    fn eq(&self, other: &Self) -> bool {
+        self.code() == other.code()
+    }
+}
+
+impl Eq for ProgramError {}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::LiqGuard(error) => error.to_string(),
+            Self::Anchor(error) => error.to_string(),
+        };
+        write!(f, "{} ({}): {message}", self.name(), self.code())
+    }
+}
+
+impl std::error::Error for ProgramError {}
+
+// Every LiqGuardError in declaration order, so index i has code 6000 + i
+const LIQGUARD_ERRORS: [LiqGuardError; 154] = [
+    LiqGuardError::PriceStale,
+    LiqGuardError::MathOverflow,
+    LiqGuardError::LiquidationConditionNotMet,
+    LiqGuardError::AlreadyClaimed,
+    LiqGuardError::Unauthorized,
+    LiqGuardError::InvalidPolicyId,
+    LiqGuardError::VaultNotFunded,
+    LiqGuardError::InvalidAmount,
+    LiqGuardError::InvalidExpiration,
+    LiqGuardError::PolicyExpired,
+    LiqGuardError::PolicyStillActive,
+    LiqGuardError::InvalidUnderwriter,
+    LiqGuardError::InvalidTwapWindow,
+    LiqGuardError::MissingTwapUpdate,
+    LiqGuardError::ProtocolPaused,
+    LiqGuardError::InvalidProtocolFee,
+    LiqGuardError::InvalidTreasury,
+    LiqGuardError::CoverageCapExceeded,
+    LiqGuardError::InvalidNewOwner,
+    LiqGuardError::InvalidStrikePrice,
+    LiqGuardError::InvalidCircuitBreaker,
+    LiqGuardError::DeviationUnconfirmed,
+    LiqGuardError::InvalidBatchAccounts,
+    LiqGuardError::InvalidBookSlot,
+    LiqGuardError::BookSlotInUse,
+    LiqGuardError::BookSlotNotOpen,
+    LiqGuardError::FeedDisabled,
+    LiqGuardError::InvalidFeedId,
+    LiqGuardError::InvalidMinPolicyAge,
+    LiqGuardError::PolicyTooNew,
+    LiqGuardError::InvalidStrikeUpdateDelay,
+    LiqGuardError::NoPendingStrikeUpdate,
+    LiqGuardError::StrikeUpdateDelayActive,
+    LiqGuardError::InvalidRollPeriod,
+    LiqGuardError::AutoRollDisabled,
+    LiqGuardError::RollWindowClosed,
+    LiqGuardError::InsufficientRollPremium,
+    LiqGuardError::MissingDriftUser,
+    LiqGuardError::InvalidDriftUser,
+    LiqGuardError::DriftPositionNotFound,
+    LiqGuardError::DriftPositionNotLiquidated,
+    LiqGuardError::MissingObligation,
+    LiqGuardError::InvalidObligation,
+    LiqGuardError::InvalidObligationPolicy,
+    LiqGuardError::InvalidHealthThreshold,
+    LiqGuardError::ObligationStale,
+    LiqGuardError::NoPendingCoverageReduction,
+    LiqGuardError::CoverageCooldownActive,
+    LiqGuardError::InvalidOracleTolerance,
+    LiqGuardError::MissingSwitchboardFeed,
+    LiqGuardError::InvalidSwitchboardFeed,
+    LiqGuardError::OracleDisagreement,
+    LiqGuardError::InvalidConfidenceThreshold,
+    LiqGuardError::PriceConfidenceTooWide,
+    LiqGuardError::InvalidMaxPriceAge,
+    LiqGuardError::InvalidBounty,
+    LiqGuardError::InvalidBeneficiary,
+    LiqGuardError::InvalidPayoutSlope,
+    LiqGuardError::InvalidCollateralMint,
+    LiqGuardError::MissingTokenAccounts,
+    LiqGuardError::InvalidVestingSchedule,
+    LiqGuardError::MissingClaimState,
+    LiqGuardError::NothingVested,
+    LiqGuardError::InvalidEuropeanPolicy,
+    LiqGuardError::EuropeanPolicy,
+    LiqGuardError::NotEuropeanPolicy,
+    LiqGuardError::OutsideSettlementWindow,
+    LiqGuardError::SettlementPriceNotCloser,
+    LiqGuardError::NoSettlementPrice,
+    LiqGuardError::InvalidPayoutBounds,
+    LiqGuardError::InvalidBarrier,
+    LiqGuardError::PricingDisabled,
+    LiqGuardError::MissingPriceUpdate,
+    LiqGuardError::PremiumBelowQuote,
+    LiqGuardError::MissingBackstop,
+    LiqGuardError::MissingUnderwriterRecord,
+    LiqGuardError::PartiallyClaimed,
+    LiqGuardError::NothingToClaim,
+    LiqGuardError::VestingInProgress,
+    LiqGuardError::NoBarrier,
+    LiqGuardError::BarrierAlreadyTouched,
+    LiqGuardError::BarrierNotTouched,
+    LiqGuardError::BarrierBlocksClaim,
+    LiqGuardError::InvalidStrikeRange,
+    LiqGuardError::InvalidDrawdown,
+    LiqGuardError::InvalidPremiumStream,
+    LiqGuardError::PremiumStreamDisabled,
+    LiqGuardError::CoverageLapsed,
+    LiqGuardError::PremiumStreamNotSynced,
+    LiqGuardError::InvalidPortfolioPolicy,
+    LiqGuardError::MissingPortfolioVault,
+    LiqGuardError::PortfolioExposureExceeded,
+    LiqGuardError::PortfolioPolicy,
+    LiqGuardError::LiquidatorNotAllowed,
+    LiqGuardError::InvalidConfigChangeDelay,
+    LiqGuardError::ConfigChangeDelayActive,
+    LiqGuardError::InvalidCompressedPolicy,
+    LiqGuardError::InvalidOracleAccount,
+    LiqGuardError::InvalidChainlinkFeed,
+    LiqGuardError::InvalidWormholePremium,
+    LiqGuardError::InvalidWormholeMessage,
+    LiqGuardError::WormholePaymentMismatch,
+    LiqGuardError::WormholePremiumUnpaid,
+    LiqGuardError::InvalidUsdCoverage,
+    LiqGuardError::MissingSolPrice,
+    LiqGuardError::OwnerCoverageCapExceeded,
+    LiqGuardError::InvalidDisputeConfig,
+    LiqGuardError::OutsideDisputeWindow,
+    LiqGuardError::HoldbackPending,
+    LiqGuardError::DisputeOpen,
+    LiqGuardError::InvalidAutomation,
+    LiqGuardError::MissingTreasury,
+    LiqGuardError::InvalidVolatility,
+    LiqGuardError::VolatilityStale,
+    LiqGuardError::PriceNotFullyVerified,
+    LiqGuardError::InvalidTriggerBuffer,
+    LiqGuardError::InvalidLstCollateral,
+    LiqGuardError::MissingStakePool,
+    LiqGuardError::InvalidStakePool,
+    LiqGuardError::StakePoolStale,
+    LiqGuardError::InvalidSwapPayout,
+    LiqGuardError::SwapPending,
+    LiqGuardError::InvalidSwapRoute,
+    LiqGuardError::SwapSlippageExceeded,
+    LiqGuardError::StrikeAlreadyBreached,
+    LiqGuardError::InvalidPolicyAccount,
+    LiqGuardError::PolicyAlreadyMigrated,
+    LiqGuardError::InvalidLazerMessage,
+    LiqGuardError::LazerDisabled,
+    LiqGuardError::LazerPriceOutdated,
+    LiqGuardError::MissingChainlinkFeed,
+    LiqGuardError::ChainlinkFeedFrozen,
+    LiqGuardError::InvalidChainlinkRound,
+    LiqGuardError::InvalidConfidenceAdjustment,
+    LiqGuardError::UnknownAccountType,
+    LiqGuardError::AccountAlreadyCurrent,
+    LiqGuardError::InvalidPayoutSplit,
+    LiqGuardError::PayoutSplitUnsupported,
+    LiqGuardError::InvalidSplitRecipient,
+    LiqGuardError::InvalidMasterAgreement,
+    LiqGuardError::AggregateLimitReached,
+    LiqGuardError::PolicyInMasterAgreement,
+    LiqGuardError::PriceCacheStale,
+    LiqGuardError::PriceCacheOutdated,
+    LiqGuardError::PayoutWalletNotCompliant,
+    LiqGuardError::VaultNotAbandoned,
+    LiqGuardError::SweepUnsupported,
+    LiqGuardError::MissingSweepDestination,
+    LiqGuardError::InvalidPremiumSource,
+    LiqGuardError::InvalidBreachDwell,
+    LiqGuardError::BreachUnconfirmed,
+    LiqGuardError::InvalidSpread,
+    LiqGuardError::InvalidUtilizationCurve,
+    LiqGuardError::PremiumAboveRequest,
+];
+
+const ANCHOR_ERRORS: [ErrorCode; 77] = [
+    ErrorCode::InstructionMissing,
+    ErrorCode::InstructionFallbackNotFound,
+    ErrorCode::InstructionDidNotDeserialize,
+    ErrorCode::InstructionDidNotSerialize,
+    ErrorCode::IdlInstructionStub,
+    ErrorCode::IdlInstructionInvalidProgram,
+    ErrorCode::IdlAccountNotEmpty,
+    ErrorCode::EventInstructionStub,
+    ErrorCode::ConstraintMut,
+    ErrorCode::ConstraintHasOne,
+    ErrorCode::ConstraintSigner,
+    ErrorCode::ConstraintRaw,
+    ErrorCode::ConstraintOwner,
+    ErrorCode::ConstraintRentExempt,
+    ErrorCode::ConstraintSeeds,
+    ErrorCode::ConstraintExecutable,
+    ErrorCode::ConstraintState,
+    ErrorCode::ConstraintAssociated,
+    ErrorCode::ConstraintAssociatedInit,
+    ErrorCode::ConstraintClose,
+    ErrorCode::ConstraintAddress,
+    ErrorCode::ConstraintZero,
+    ErrorCode::ConstraintTokenMint,
+    ErrorCode::ConstraintTokenOwner,
+    ErrorCode::ConstraintMintMintAuthority,
+    ErrorCode::ConstraintMintFreezeAuthority,
+    ErrorCode::ConstraintMintDecimals,
+    ErrorCode::ConstraintSpace,
+    ErrorCode::ConstraintAccountIsNone,
+    ErrorCode::ConstraintTokenTokenProgram,
+    ErrorCode::ConstraintMintTokenProgram,
+    ErrorCode::ConstraintAssociatedTokenTokenProgram,
+    ErrorCode::ConstraintMintGroupPointerExtension,
+    ErrorCode::ConstraintMintGroupPointerExtensionAuthority,
+    ErrorCode::ConstraintMintGroupPointerExtensionGroupAddress,
+    ErrorCode::ConstraintMintGroupMemberPointerExtension,
+    ErrorCode::ConstraintMintGroupMemberPointerExtensionAuthority,
+    ErrorCode::ConstraintMintGroupMemberPointerExtensionMemberAddress,
+    ErrorCode::ConstraintMintMetadataPointerExtension,
+    ErrorCode::ConstraintMintMetadataPointerExtensionAuthority,
+    ErrorCode::ConstraintMintMetadataPointerExtensionMetadataAddress,
+    ErrorCode::ConstraintMintCloseAuthorityExtension,
+    ErrorCode::ConstraintMintCloseAuthorityExtensionAuthority,
+    ErrorCode::ConstraintMintPermanentDelegateExtension,
+    ErrorCode::ConstraintMintPermanentDelegateExtensionDelegate,
+    ErrorCode::ConstraintMintTransferHookExtension,
+    ErrorCode::ConstraintMintTransferHookExtensionAuthority,
+    ErrorCode::ConstraintMintTransferHookExtensionProgramId,
+    ErrorCode::RequireViolated,
+    ErrorCode::RequireEqViolated,
+    ErrorCode::RequireKeysEqViolated,
+    ErrorCode::RequireNeqViolated,
+    ErrorCode::RequireKeysNeqViolated,
+    ErrorCode::RequireGtViolated,
+    ErrorCode::RequireGteViolated,
+    ErrorCode::AccountDiscriminatorAlreadySet,
+    ErrorCode::AccountDiscriminatorNotFound,
+    ErrorCode::AccountDiscriminatorMismatch,
+    ErrorCode::AccountDidNotDeserialize,
+    ErrorCode::AccountDidNotSerialize,
+    ErrorCode::AccountNotEnoughKeys,
+    ErrorCode::AccountNotMutable,
+    ErrorCode::AccountOwnedByWrongProgram,
+    ErrorCode::InvalidProgramId,
+    ErrorCode::InvalidProgramExecutable,
+    ErrorCode::AccountNotSigner,
+    ErrorCode::AccountNotSystemOwned,
+    ErrorCode::AccountNotInitialized,
+    ErrorCode::AccountNotProgramData,
+    ErrorCode::AccountNotAssociatedTokenAccount,
+    ErrorCode::AccountSysvarMismatch,
+    ErrorCode::AccountReallocExceedsLimit,
+    ErrorCode::AccountDuplicateReallocs,
+    ErrorCode::DeclaredProgramIdMismatch,
+    ErrorCode::TryingToInitPayerAsProgramAccount,
+    ErrorCode::InvalidNumericConversion,
+    ErrorCode::Deprecated,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The table against the enum's source, so a new error can't be
+    // appended without it
+    #[test]
+    fn the_table_lists_every_liqguard_error_in_order() {
+        let source = include_str!("../../../programs/liqguard/src/lib.rs");
+        let start = source.find("pub enum LiqGuardError {").unwrap();
+        let body = &source[start..];
+        let body = &body[..body.find("\n}\n").unwrap()];
+        let declared: Vec<String> = body
+            .split("#[msg(")
+            .skip(1)
+            .map(|chunk| {
+                let variant = chunk.split_once("\")]").unwrap().1;
+                variant.trim().trim_end_matches(',').to_string()
+            })
+            .collect();
+        let listed: Vec<String> = LIQGUARD_ERRORS.iter().map(|error| error.name()).collect();
+        assert_eq!(listed, declared);
+        for (index, error) in LIQGUARD_ERRORS.iter().enumerate() {
+            assert_eq!(u32::from(*error), LIQGUARD_OFFSET + index as u32);
+        }
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for error in LIQGUARD_ERRORS {
+            let parsed = ProgramError::from_code(error.into()).unwrap();
+            assert_eq!(parsed.name(), error.name());
+        }
+        for error in ANCHOR_ERRORS {
+            let parsed = ProgramError::from_code(error.into()).unwrap();
+            assert_eq!(parsed.name(), error.name());
+        }
+        assert!(ProgramError::from_code(LIQGUARD_OFFSET + LIQGUARD_ERRORS.len() as u32).is_none());
+        assert!(ProgramError::from_code(1).is_none());
+    }
+
+    #[test]
+    fn transaction_errors_and_logs_parse() {
+        let not_met = ProgramError::LiqGuard(LiqGuardError::LiquidationConditionNotMet);
+        let code = not_met.code();
+        let error = TransactionError::InstructionError(1, InstructionError::Custom(code));
+        assert_eq!(ProgramError::from_transaction_error(&error), Some(not_met));
+        assert_eq!(
+            ProgramError::from_transaction_error(&TransactionError::BlockhashNotFound),
+            None
+        );
+
+        let failed = |program: String| {
+            vec![
+                format!("Program {program} invoke [1]"),
+                format!("Program log: AnchorError occurred. Error Code: LiquidationConditionNotMet. Error Number: {code}. Error Message: Liquidation condition not met."),
+                format!("Program {program} failed: custom program error: {code:#x}"),
+            ]
+        };
+        let logs = failed(liqguard::ID.to_string());
+        assert_eq!(ProgramError::parse(&error, Some(&logs)), Some(not_met));
+        // The same number, raised by a program liqguard called
+        let mut logs = failed(anchor_lang::system_program::ID.to_string());
+        logs.push(format!(
+            "Program {} failed: custom program error: {code:#x}",
+            liqguard::ID
+        ));
+        assert_eq!(ProgramError::parse(&error, Some(&logs)), None);
+        assert_eq!(ProgramError::parse(&error, None), Some(not_met));
+
+        assert_eq!(
+            not_met.to_string(),
+            format!("LiquidationConditionNotMet ({code}): Liquidation condition not met")
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// Typed decoding of the program's emit! events out of transaction logs.
+// Anchor logs each event as "Program data: <base64>" while the emitting
+// program is executing, so logs are walked as an invocation stack and
+// only data logged by liqguard itself is decoded
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, AnchorSerialize, Discriminator};
+use base64::Engine;
+
+macro_rules! events {
+    ($($name:ident),* $(,)?) => {
+        pub enum LiqGuardEvent {
+            $($name(liqguard::$name),)*
+        }
+
+        impl LiqGuardEvent {
+            // An event from its discriminator-prefixed borsh bytes
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                let (discriminator, mut body) = (data.get(..8)?, &data[8..]);
+                $(
+                    if discriminator == liqguard::$name::DISCRIMINATOR {
+                        return liqguard::$name::deserialize(&mut body).ok().map(Self::$name);
+                    }
+                )*
+                None
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$name(_) => stringify!($name),)*
+                }
+            }
+
+            // The discriminator-prefixed borsh bytes decode reads
+            pub fn to_bytes(&self) -> Vec<u8> {
+                match self {
+                    $(Self::$name(event) => {
+                        let mut data = liqguard::$name::DISCRIMINATOR.to_vec();
+                        event.serialize(&mut data).expect("events serialize into a Vec");
+                        data
+                    })*
+                }
+            }
+        }
+    };
+}
+
+events!(
+    PolicyInitialized,
+    VaultFunded,
+    VaultToppedUp,
+    VaultPaidOut,
+    AutomationRegistered,
+    AutomationDeregistered,
+    LiquidationDisputed,
+    DisputeResolved,
+    PayoutSwapped,
+    PayoutFeeCollected,
+    TreasuryWithdrawn,
+    VolatilityUpdated,
+    VaultReclaimed,
+    CompressedPolicyOpened,
+    WormholePremiumRedeemed,
+    LiquidationExecuted,
+    BackstopDrawn,
+    PolicyRolled,
+    ConfigChangeProposed,
+    ConfigChangeApplied,
+    PremiumStreamSynced,
+    PolicyMigrated,
+    AccountExtended,
+);
+
+const DATA_PREFIX: &str = "Program data: ";
+
+// Events liqguard emitted in one transaction's logs, in order
+pub fn parse_logs(logs: &[String]) -> Vec<LiqGuardEvent> {
+    let program_id = liqguard::ID.to_string();
+    let mut stack: Vec<bool> = Vec::new(); // whether each frame is liqguard
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(data) = log.strip_prefix(DATA_PREFIX) {
+            if stack.last() == Some(&true) {
+                if let Some(event) = base64::engine::general_purpose::STANDARD
+                    .decode(data)
+                    .ok()
+                    .and_then(|bytes| LiqGuardEvent::decode(&bytes))
+                {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let program = words.next().unwrap_or_default();
+            match words.next() {
+                Some("invoke") => stack.push(program == program_id),
+                Some("success") | Some("failed:") => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+impl LiqGuardEvent {
+    // The policy an event is about, when it names one
+    pub fn policy(&self) -> Option<Pubkey> {
+        match self {
+            LiqGuardEvent::PolicyInitialized(event) => Some(event.policy),
+            LiqGuardEvent::VaultFunded(event) => Some(event.policy),
+            LiqGuardEvent::VaultToppedUp(event) => Some(event.policy),
+            LiqGuardEvent::VaultPaidOut(event) => Some(event.policy),
+            LiqGuardEvent::AutomationRegistered(event) => Some(event.policy),
+            LiqGuardEvent::AutomationDeregistered(event) => Some(event.policy),
+            LiqGuardEvent::LiquidationDisputed(event) => Some(event.policy),
+            LiqGuardEvent::DisputeResolved(event) => Some(event.policy),
+            LiqGuardEvent::PayoutSwapped(event) => Some(event.policy),
+            LiqGuardEvent::PayoutFeeCollected(event) => Some(event.policy),
+            LiqGuardEvent::VaultReclaimed(event) => Some(event.policy),
+            LiqGuardEvent::WormholePremiumRedeemed(event) => Some(event.policy),
+            LiqGuardEvent::LiquidationExecuted(event) => Some(event.policy),
+            LiqGuardEvent::BackstopDrawn(event) => Some(event.policy),
+            LiqGuardEvent::PolicyRolled(event) => Some(event.policy),
+            LiqGuardEvent::PremiumStreamSynced(event) => Some(event.policy),
+            LiqGuardEvent::PolicyMigrated(event) => Some(event.policy),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+
+    fn data_log(event: &impl AnchorSerialize, discriminator: [u8; 8]) -> String {
+        let mut bytes = discriminator.to_vec();
+        event.serialize(&mut bytes).unwrap();
+        format!(
+            "{DATA_PREFIX}{}",
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        )
+    }
+
+    #[test]
+    fn parse_logs_decodes_only_liqguard_frames() {
+        let policy = Pubkey::new_unique();
+        let event = liqguard::PolicyMigrated {
+            policy,
+            version: 2,
+            rent_paid: 10,
+            slot: 5,
+            sequence: 1,
+        };
+        let data = data_log(&event, liqguard::PolicyMigrated::DISCRIMINATOR);
+        let other = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", liqguard::ID),
+            format!("Program {other} invoke [2]"),
+            // Same bytes logged by a CPI'd program are not ours
+            data.clone(),
+            format!("Program {other} success"),
+            data,
+            format!("Program {} success", liqguard::ID),
+        ];
+        let events = parse_logs(&logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name(), "PolicyMigrated");
+        assert_eq!(events[0].policy(), Some(policy));
+        let bytes = events[0].to_bytes();
+        assert_eq!(
+            LiqGuardEvent::decode(&bytes).map(|event| event.name()),
+            Some("PolicyMigrated")
+        );
+    }
+
+    #[test]
+    fn decode_rejects_unknown_discriminators() {
+        assert!(LiqGuardEvent::decode(&[0; 16]).is_none());
+        assert!(LiqGuardEvent::decode(&[1, 2, 3]).is_none());
+    }
+}
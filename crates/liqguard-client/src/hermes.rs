@@ -0,0 +1,131 @@
+// Latest prices from Pyth's Hermes service: the parsed price, to decide
+// what is breached, and the signed accumulator update that proves it on
+// chain
+
+use base64::Engine;
+use liqguard::OraclePrice;
+use serde::Deserialize;
+
+pub struct HermesClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+// One feed's latest price and the accumulator update carrying it
+pub struct HermesUpdate {
+    pub price: OraclePrice,
+    pub confidence: u64,
+    pub publish_time: i64,
+    pub accumulator: Vec<u8>, // PNAU bytes, as posted to the Pyth receiver
+}
+
+#[derive(Deserialize)]
+struct LatestResponse {
+    binary: BinaryData,
+    parsed: Vec<ParsedUpdate>,
+}
+
+#[derive(Deserialize)]
+struct BinaryData {
+    encoding: String,
+    data: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ParsedUpdate {
+    id: String,
+    price: ParsedPrice,
+}
+
+// Hermes sends 64-bit integers as strings
+#[derive(Deserialize)]
+struct ParsedPrice {
+    price: String,
+    conf: String,
+    expo: i32,
+    publish_time: i64,
+}
+
+impl HermesClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn latest(&self, feed_id: &[u8; 32]) -> Result<HermesUpdate, String> {
+        let id = hex(feed_id);
+        let response: LatestResponse = self
+            .http
+            .get(format!("{}/v2/updates/price/latest", self.base_url))
+            .query(&[
+                ("ids[]", id.as_str()),
+                ("encoding", "base64"),
+                ("parsed", "true"),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| format!("hermes request failed: {error}"))?
+            .json()
+            .await
+            .map_err(|error| format!("hermes response unreadable: {error}"))?;
+        parse_latest(response, &id)
+    }
+}
+
+fn parse_latest(response: LatestResponse, id: &str) -> Result<HermesUpdate, String> {
+    if response.binary.encoding != "base64" || response.binary.data.len() != 1 {
+        return Err("hermes returned an unexpected binary encoding".to_string());
+    }
+    let parsed = response
+        .parsed
+        .into_iter()
+        .find(|update| update.id.trim_start_matches("0x") == id)
+        .ok_or_else(|| format!("hermes has no price for feed {id}"))?;
+    let price: i64 = parsed
+        .price
+        .price
+        .parse()
+        .map_err(|_| "unparseable price")?;
+    let confidence: u64 = parsed
+        .price
+        .conf
+        .parse()
+        .map_err(|_| "unparseable confidence")?;
+    let accumulator = base64::engine::general_purpose::STANDARD
+        .decode(&response.binary.data[0])
+        .map_err(|_| "hermes update is not base64")?;
+    Ok(HermesUpdate {
+        price: OraclePrice::new(price, parsed.price.expo),
+        confidence,
+        publish_time: parsed.price.publish_time,
+        accumulator,
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard::BTC_USD_FEED_ID;
+
+    #[test]
+    fn parse_latest_reads_the_requested_feed() {
+        let id = hex(&BTC_USD_FEED_ID);
+        let body = format!(
+            r#"{{"binary":{{"encoding":"base64","data":["UE5BVQ=="]}},
+               "parsed":[{{"id":"{id}","price":{{"price":"9500012345678","conf":"2500000",
+               "expo":-8,"publish_time":1700000000}}}}]}}"#
+        );
+        let response: LatestResponse = serde_json::from_str(&body).unwrap();
+        let update = parse_latest(response, &id).unwrap();
+        assert_eq!(update.price, OraclePrice::new(9_500_012_345_678i64, -8));
+        assert_eq!(update.confidence, 2_500_000);
+        assert_eq!(update.accumulator, b"PNAU");
+    }
+}
@@ -0,0 +1,682 @@
+// Instruction builders for the policy lifecycle. Each derives the PDAs the
+// program's seeds constraints check and fills the optional accounts a
+// policy's settings call for, so callers pass only what can't be derived.
+// Optional accounts left out are encoded as the program id, Anchor's None
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use liqguard::{
+    accounts, instruction, CoverageRequest, InitializePolicyParams, Policy, UnderlyingAsset,
+};
+use liqguard_common::seeds;
+
+use crate::{ClientError, Result};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: liqguard::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn set(key: Pubkey) -> Option<Pubkey> {
+    (key != Pubkey::default()).then_some(key)
+}
+
+// SPL token accounts an SPL-collateral policy's instruction moves tokens
+// between; ignored for native policies
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokenAccounts {
+    pub source: Option<Pubkey>,
+    pub destination: Option<Pubkey>,
+}
+
+// Accounts initialize_policy can't derive from its params
+#[derive(Clone, Copy, Debug)]
+pub struct InitializePolicyAccounts {
+    pub owner: Pubkey,
+    pub payer: Pubkey,           // pays the rent, so the owner can be a multisig
+    pub treasury: Pubkey,        // Config.treasury
+    pub price_feed: Pubkey,      // asset's price account, for the entry price
+    pub backstop: bool,          // pass the backstop, required while backstop_fee_bps > 0
+    pub portfolio_vault: bool,   // the underwriter backs this policy from its portfolio vault
+    pub compliance_mint: Pubkey, // Config.compliance_mint
+}
+
+// The wallet's associated token account for the credential gating its
+// payouts, the config's ahead of the policy's, if either gates them
+fn credential(wallet: &Pubkey, config_mint: Option<Pubkey>, policy_mint: Pubkey) -> Option<Pubkey> {
+    let mint = config_mint.or(set(policy_mint))?;
+    Some(get_associated_token_address(wallet, &mint))
+}
+
+// The owner and params.underwriter both sign, the underwriter agreeing to
+// fund the vault it is paid the premium for
+pub fn initialize_policy(
+    accounts: InitializePolicyAccounts,
+    params: InitializePolicyParams,
+) -> Instruction {
+    let program_id = &liqguard::ID;
+    let owner = accounts.owner;
+    let beneficiary = set(params.beneficiary).unwrap_or(owner);
+    build(
+        accounts::InitializePolicy {
+            owner_state: seeds::owner_state(program_id, &owner).0,
+            policy: seeds::policy(program_id, &owner, params.policy_id).0,
+            keeper_queue: accounts
+                .portfolio_vault
+                .then(|| seeds::keeper_queue(program_id, params.asset).0),
+            vault: seeds::vault(program_id, &owner, params.policy_id).0,
+            underwriter: params.underwriter,
+            config: seeds::config(program_id).0,
+            feed_registry: seeds::feed_registry(program_id, params.asset).0,
+            treasury: accounts.treasury,
+            drift_user: set(params.drift_user),
+            kamino_obligation: set(params.kamino_obligation),
+            price_feed: accounts.price_feed,
+            chainlink_feed: set(params.chainlink_feed),
+            backstop: accounts.backstop.then(|| seeds::backstop(program_id).0),
+            portfolio_vault: accounts
+                .portfolio_vault
+                .then(|| seeds::portfolio_vault(program_id, &params.underwriter).0),
+            beneficiary_credential: credential(
+                &beneficiary,
+                set(accounts.compliance_mint),
+                params.compliance_mint,
+            ),
+            owner,
+            payer: accounts.payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitializePolicy { params },
+    )
+}
+
+// USD and LST coverage is valued at funding time, so those policies also
+// need the SOL price or the stake pool
+pub fn fund_vault(
+    policy_address: Pubkey,
+    policy: &Policy,
+    amount: u64,
+    sol_price_feed: Option<Pubkey>,
+    token_accounts: TokenAccounts,
+) -> Result<Instruction> {
+    let program_id = &liqguard::ID;
+    let spl = !policy.is_native();
+    if policy.usd_coverage && sol_price_feed.is_none() {
+        return Err(ClientError::MissingAccount(
+            "USD coverage needs sol_price_feed",
+        ));
+    }
+    Ok(build(
+        accounts::FundVault {
+            policy: policy_address,
+            keeper_queue: seeds::keeper_queue(program_id, policy.asset).0,
+            vault: seeds::vault(program_id, &policy.creator, policy.policy_id).0,
+            config: seeds::config(program_id).0,
+            underwriter: policy.underwriter,
+            token_vault: spl
+                .then(|| seeds::token_vault(program_id, &policy.creator, policy.policy_id).0),
+            underwriter_token_account: spl.then_some(token_accounts.source).flatten(),
+            sol_feed_registry: sol_price_feed
+                .map(|_| seeds::feed_registry(program_id, UnderlyingAsset::Sol).0),
+            sol_price_feed,
+            lst_stake_pool: set(policy.lst_stake_pool),
+            token_program: spl.then_some(anchor_spl::token::ID),
+            system_program: system_program::ID,
+        },
+        instruction::FundVault { amount },
+    ))
+}
+
+// Open and fund the requester's next policy from a coverage request.
+// policy_id is the requester's OwnerState.policy_count, 0 if they have none
+pub fn fill_coverage_request(
+    request: &CoverageRequest,
+    policy_id: u64,
+    underwriter: Pubkey,
+    treasury: Pubkey,
+    price_feed: Pubkey,
+    backstop: bool,
+    premium: u64,
+) -> Instruction {
+    let program_id = &liqguard::ID;
+    let owner = request.owner;
+    build(
+        accounts::FillCoverageRequest {
+            coverage_request: seeds::coverage_request(program_id, &owner, request.request_id).0,
+            owner_state: seeds::owner_state(program_id, &owner).0,
+            policy: seeds::policy(program_id, &owner, policy_id).0,
+            keeper_queue: seeds::keeper_queue(program_id, request.asset).0,
+            vault: seeds::vault(program_id, &owner, policy_id).0,
+            config: seeds::config(program_id).0,
+            feed_registry: seeds::feed_registry(program_id, request.asset).0,
+            price_feed,
+            treasury,
+            backstop: backstop.then(|| seeds::backstop(program_id).0),
+            owner,
+            underwriter,
+            system_program: system_program::ID,
+        },
+        instruction::FillCoverageRequest { policy_id, premium },
+    )
+}
+
+// A split payout's recipients, in share order, appended to the
+// instructions that pay it: their wallets for native collateral, else
+// their associated token accounts for the collateral mint
+fn split_accounts(policy: &Policy) -> Vec<AccountMeta> {
+    policy
+        .payout_shares()
+        .iter()
+        .map(|share| {
+            let recipient = if policy.is_native() {
+                share.recipient
+            } else {
+                get_associated_token_address(&share.recipient, &policy.collateral_mint)
+            };
+            AccountMeta::new(recipient, false)
+        })
+        .collect()
+}
+
+// Accounts liquidate_policy takes beyond what the policy itself names
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LiquidateOptions {
+    pub sol_price_feed: Option<Pubkey>, // USD coverage policies
+    pub twap_update: Option<Pubkey>,    // TWAP policies
+    pub audit_log: bool,
+    pub claim_history: bool,
+    pub backstop: bool,        // draw on the backstop if the vault falls short
+    pub treasury: bool,        // required while a payout fee is configured
+    pub tokens: TokenAccounts, // beneficiary's, then liquidator's, for SPL collateral
+    pub compliance_mint: Option<Pubkey>, // Config.compliance_mint, while it gates payouts
+    pub skip_keeper_queue: bool, // leave the queue's write lock to others; prune later
+}
+
+pub fn liquidate_policy(
+    policy_address: Pubkey,
+    policy: &Policy,
+    liquidator: Pubkey,
+    price_feed: Pubkey,
+    options: LiquidateOptions,
+) -> Result<Instruction> {
+    let program_id = &liqguard::ID;
+    let spl = !policy.is_native();
+    if policy.usd_coverage && options.sol_price_feed.is_none() {
+        return Err(ClientError::MissingAccount(
+            "USD coverage needs sol_price_feed",
+        ));
+    }
+    if policy.twap_window_seconds > 0 && options.twap_update.is_none() {
+        return Err(ClientError::MissingAccount(
+            "TWAP policies need twap_update",
+        ));
+    }
+    let mut ix = build(
+        accounts::LiquidatePolicy {
+            policy: policy_address,
+            keeper_queue: (!options.skip_keeper_queue)
+                .then(|| seeds::keeper_queue(program_id, policy.asset).0),
+            price_feed,
+            feed_registry: seeds::feed_registry(program_id, policy.asset).0,
+            sol_feed_registry: options
+                .sol_price_feed
+                .map(|_| seeds::feed_registry(program_id, UnderlyingAsset::Sol).0),
+            sol_price_feed: options.sol_price_feed,
+            lst_stake_pool: set(policy.lst_stake_pool),
+            twap_update: options.twap_update,
+            audit_log: options.audit_log.then(|| seeds::audit_log(program_id).0),
+            claim_history: options
+                .claim_history
+                .then(|| seeds::claim_history(program_id, &policy.owner).0),
+            switchboard_feed: set(policy.switchboard_feed),
+            drift_user: set(policy.drift_user),
+            kamino_obligation: set(policy.kamino_obligation),
+            vault: seeds::vault(program_id, &policy.creator, policy.policy_id).0,
+            beneficiary: policy.beneficiary,
+            config: seeds::config(program_id).0,
+            liquidator,
+            claim_state: (policy.vesting_period_seconds > 0)
+                .then(|| seeds::claim_state(program_id, &policy_address).0),
+            backstop: options.backstop.then(|| seeds::backstop(program_id).0),
+            treasury: options.treasury.then(|| seeds::treasury(program_id).0),
+            underwriter_record: options
+                .backstop
+                .then(|| seeds::underwriter(program_id, &policy.underwriter).0),
+            portfolio_vault: set(policy.portfolio_vault),
+            master_agreement: set(policy.master_agreement),
+            beneficiary_credential: credential(
+                &policy.beneficiary,
+                options.compliance_mint,
+                policy.compliance_mint,
+            ),
+            token_vault: spl
+                .then(|| seeds::token_vault(program_id, &policy.creator, policy.policy_id).0),
+            beneficiary_token_account: spl.then_some(options.tokens.destination).flatten(),
+            liquidator_token_account: spl.then_some(options.tokens.source).flatten(),
+            token_program: spl.then_some(anchor_spl::token::ID),
+            system_program: system_program::ID,
+        },
+        instruction::LiquidatePolicy {},
+    );
+    ix.accounts.extend(split_accounts(policy));
+    Ok(ix)
+}
+
+// Settle a European policy on its snapshotted expiry price
+pub fn settle(
+    policy_address: Pubkey,
+    policy: &Policy,
+    liquidator: Pubkey,
+    options: LiquidateOptions,
+) -> Instruction {
+    let program_id = &liqguard::ID;
+    let spl = !policy.is_native();
+    let mut ix = build(
+        accounts::SettlePolicy {
+            policy: policy_address,
+            keeper_queue: Some(seeds::keeper_queue(program_id, policy.asset).0),
+            vault: seeds::vault(program_id, &policy.creator, policy.policy_id).0,
+            beneficiary: policy.beneficiary,
+            config: seeds::config(program_id).0,
+            feed_registry: seeds::feed_registry(program_id, policy.asset).0,
+            liquidator,
+            token_vault: spl
+                .then(|| seeds::token_vault(program_id, &policy.creator, policy.policy_id).0),
+            beneficiary_token_account: spl.then_some(options.tokens.destination).flatten(),
+            liquidator_token_account: spl.then_some(options.tokens.source).flatten(),
+            treasury: options.treasury.then(|| seeds::treasury(program_id).0),
+            audit_log: options.audit_log.then(|| seeds::audit_log(program_id).0),
+            claim_history: options
+                .claim_history
+                .then(|| seeds::claim_history(program_id, &policy.owner).0),
+            master_agreement: set(policy.master_agreement),
+            beneficiary_credential: credential(
+                &policy.beneficiary,
+                options.compliance_mint,
+                policy.compliance_mint,
+            ),
+            token_program: spl.then_some(anchor_spl::token::ID),
+            system_program: system_program::ID,
+        },
+        instruction::Settle {},
+    );
+    ix.accounts.extend(split_accounts(policy));
+    ix
+}
+
+// Settle every eligible policy in policies against one price. Each entry
+// is a policy's address and its account
+pub fn liquidate_policies(
+    asset: UnderlyingAsset,
+    liquidator: Pubkey,
+    price_feed: Pubkey,
+    treasury: bool,
+    policies: &[(Pubkey, Policy)],
+) -> Instruction {
+    let program_id = &liqguard::ID;
+    let mut ix = build(
+        accounts::LiquidatePolicies {
+            price_feed,
+            feed_registry: seeds::feed_registry(program_id, asset).0,
+            keeper_queue: Some(seeds::keeper_queue(program_id, asset).0),
+            config: seeds::config(program_id).0,
+            liquidator,
+            treasury: treasury.then(|| seeds::treasury(program_id).0),
+            system_program: system_program::ID,
+        },
+        instruction::LiquidatePolicies {},
+    );
+    for (address, policy) in policies {
+        ix.accounts.extend([
+            AccountMeta::new(*address, false),
+            AccountMeta::new(
+                seeds::vault(program_id, &policy.creator, policy.policy_id).0,
+                false,
+            ),
+            AccountMeta::new(policy.beneficiary, false),
+        ]);
+    }
+    ix
+}
+
+// Drop those of policies that are closed, unfunded or terminal from the
+// asset's keeper queue. Anyone may send it
+pub fn prune_keeper_queue(asset: UnderlyingAsset, policies: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        accounts::PruneKeeperQueue {
+            keeper_queue: seeds::keeper_queue(&liqguard::ID, asset).0,
+        },
+        instruction::PruneKeeperQueue {},
+    );
+    ix.accounts.extend(
+        policies
+            .iter()
+            .map(|policy| AccountMeta::new_readonly(*policy, false)),
+    );
+    ix
+}
+
+// Validate the asset's oracle price into its price cache. Sent first in a
+// transaction, the cache's address stands in for price_feed in the
+// liquidate_policies batches after it
+pub fn push_price(asset: UnderlyingAsset, keeper: Pubkey, price_feed: Pubkey) -> Instruction {
+    let program_id = &liqguard::ID;
+    build(
+        accounts::PushPrice {
+            price_cache: seeds::price_cache(program_id, asset).0,
+            price_feed,
+            feed_registry: seeds::feed_registry(program_id, asset).0,
+            config: seeds::config(program_id).0,
+            keeper,
+            system_program: system_program::ID,
+        },
+        instruction::PushPrice {},
+    )
+}
+
+// Let auto_renew pull up to allowance lamports of premium from the
+// owner's wrapped SOL account: wraps the allowance and approves the
+// policy's vault as delegate over it
+pub fn approve_auto_renew(policy: &Policy, allowance: u64) -> Vec<Instruction> {
+    let native_mint = spl_token::native_mint::ID;
+    let premium_source = get_associated_token_address(&policy.owner, &native_mint);
+    let vault = seeds::vault(&liqguard::ID, &policy.creator, policy.policy_id).0;
+    // spl_token's builders only fail for another token program's id
+    vec![
+        create_associated_token_account_idempotent(
+            &policy.owner,
+            &policy.owner,
+            &native_mint,
+            &spl_token::ID,
+        ),
+        system_instruction::transfer(&policy.owner, &premium_source, allowance),
+        spl_token::instruction::sync_native(&spl_token::ID, &premium_source)
+            .expect("spl token program"),
+        spl_token::instruction::approve(
+            &spl_token::ID,
+            &premium_source,
+            &vault,
+            &policy.owner,
+            &[],
+            allowance,
+        )
+        .expect("spl token program"),
+    ]
+}
+
+// Roll a due policy, pulling whatever its escrow is short of the premium
+// from the owner's approved wrapped SOL account
+pub fn auto_renew(
+    policy_address: Pubkey,
+    policy: &Policy,
+    caller: Pubkey,
+    backstop: bool,
+) -> Instruction {
+    let program_id = &liqguard::ID;
+    let native_mint = spl_token::native_mint::ID;
+    build(
+        accounts::AutoRenew {
+            policy: policy_address,
+            vault: seeds::vault(program_id, &policy.creator, policy.policy_id).0,
+            token_vault: (!policy.is_native())
+                .then(|| seeds::token_vault(program_id, &policy.creator, policy.policy_id).0),
+            config: seeds::config(program_id).0,
+            underwriter: policy.underwriter,
+            treasury: seeds::treasury(program_id).0,
+            backstop: backstop.then(|| seeds::backstop(program_id).0),
+            premium_source: get_associated_token_address(&policy.owner, &native_mint),
+            renewal_source: seeds::renewal_source(program_id, &policy_address).0,
+            native_mint,
+            caller,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::AutoRenew {},
+    )
+}
+
+// Close a terminal policy, returning rent to the owner and residual
+// collateral to the underwriter
+pub fn close_policy(
+    policy_address: Pubkey,
+    policy: &Policy,
+    closer: Pubkey,
+    underwriter_token_account: Option<Pubkey>,
+) -> Instruction {
+    let program_id = &liqguard::ID;
+    let spl = !policy.is_native();
+    build(
+        accounts::ClosePolicy {
+            policy: policy_address,
+            keeper_queue: Some(seeds::keeper_queue(program_id, policy.asset).0),
+            vault: seeds::vault(program_id, &policy.creator, policy.policy_id).0,
+            owner: policy.owner,
+            underwriter: policy.underwriter,
+            closer,
+            owner_state: seeds::owner_state(program_id, &policy.owner).0,
+            token_vault: spl
+                .then(|| seeds::token_vault(program_id, &policy.creator, policy.policy_id).0),
+            underwriter_token_account: spl.then_some(underwriter_token_account).flatten(),
+            portfolio_vault: set(policy.portfolio_vault),
+            token_program: spl.then_some(anchor_spl::token::ID),
+            system_program: system_program::ID,
+        },
+        instruction::ClosePolicy {},
+    )
+}
+
+// Sweep an abandoned native policy's vault and rent into the backstop,
+// or the treasury when backstop is false
+pub fn sweep_abandoned_vault(
+    policy_address: Pubkey,
+    policy: &Policy,
+    sweeper: Pubkey,
+    backstop: bool,
+) -> Instruction {
+    let program_id = &liqguard::ID;
+    build(
+        accounts::SweepAbandonedVault {
+            policy: policy_address,
+            keeper_queue: Some(seeds::keeper_queue(program_id, policy.asset).0),
+            vault: seeds::vault(program_id, &policy.creator, policy.policy_id).0,
+            owner_state: seeds::owner_state(program_id, &policy.owner).0,
+            backstop: backstop.then(|| seeds::backstop(program_id).0),
+            treasury: (!backstop).then(|| seeds::treasury(program_id).0),
+            portfolio_vault: set(policy.portfolio_vault),
+            sweeper,
+            system_program: system_program::ID,
+        },
+        instruction::SweepAbandonedVault {},
+    )
+}
+
+// Grow a program account to its type's current size; signed by the config
+// authority, with payer covering the added rent
+pub fn extend_account(account: Pubkey, authority: Pubkey, payer: Pubkey) -> Instruction {
+    build(
+        accounts::ExtendAccount {
+            account,
+            config: seeds::config(&liqguard::ID).0,
+            authority,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::ExtendAccount {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard_test_support::zeroed_policy;
+
+    fn native_policy() -> (Pubkey, Policy) {
+        let mut policy = zeroed_policy();
+        policy.owner = Pubkey::new_unique();
+        policy.creator = policy.owner;
+        policy.beneficiary = Pubkey::new_unique();
+        policy.underwriter = Pubkey::new_unique();
+        policy.policy_id = 4;
+        let address = seeds::policy(&liqguard::ID, &policy.creator, policy.policy_id).0;
+        (address, policy)
+    }
+
+    #[test]
+    fn liquidate_policy_derives_pdas_and_skips_unused_accounts() {
+        let (address, mut policy) = native_policy();
+        policy.vesting_period_seconds = 86_400;
+        let liquidator = Pubkey::new_unique();
+        let ix = liquidate_policy(
+            address,
+            &policy,
+            liquidator,
+            Pubkey::new_unique(),
+            Default::default(),
+        )
+        .unwrap();
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(keys[0], address);
+        assert!(keys.contains(&seeds::vault(&liqguard::ID, &policy.creator, 4).0));
+        assert!(keys.contains(&seeds::claim_state(&liqguard::ID, &address).0));
+        // Token program and token accounts are left out for native collateral
+        assert!(!keys.contains(&anchor_spl::token::ID));
+        assert!(ix
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == liquidator && meta.is_signer));
+    }
+
+    #[test]
+    fn liquidations_can_leave_the_keeper_queue_out() {
+        let (address, policy) = native_policy();
+        let queue = seeds::keeper_queue(&liqguard::ID, policy.asset).0;
+        let liquidate =
+            |options| liquidate_policy(address, &policy, address, address, options).unwrap();
+        assert_eq!(
+            liquidate(LiquidateOptions::default()).accounts[1].pubkey,
+            queue
+        );
+        let skipped = liquidate(LiquidateOptions {
+            skip_keeper_queue: true,
+            ..LiquidateOptions::default()
+        });
+        assert_eq!(skipped.accounts[1].pubkey, liqguard::ID);
+    }
+
+    #[test]
+    fn liquidate_policy_requires_the_sol_feed_for_usd_coverage() {
+        let (address, mut policy) = native_policy();
+        policy.usd_coverage = true;
+        let result = liquidate_policy(
+            address,
+            &policy,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Default::default(),
+        );
+        assert!(matches!(result, Err(ClientError::MissingAccount(_))));
+    }
+
+    #[test]
+    fn split_payouts_append_their_recipients_in_share_order() {
+        let (address, mut policy) = native_policy();
+        let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+        policy.payout_splits[0] = liqguard::PayoutShare {
+            recipient: recipients[0],
+            share_bps: 6_000,
+        };
+        policy.payout_splits[1] = liqguard::PayoutShare {
+            recipient: recipients[1],
+            share_bps: 4_000,
+        };
+        let liquidator = Pubkey::new_unique();
+        let ix = liquidate_policy(
+            address,
+            &policy,
+            liquidator,
+            Pubkey::new_unique(),
+            Default::default(),
+        )
+        .unwrap();
+        let tail = &ix.accounts[ix.accounts.len() - 2..];
+        assert_eq!(tail[0].pubkey, recipients[0]);
+        assert_eq!(tail[1].pubkey, recipients[1]);
+        assert!(tail.iter().all(|meta| meta.is_writable));
+
+        // SPL collateral pays their token accounts
+        policy.collateral_mint = Pubkey::new_unique();
+        let ix = settle(address, &policy, liquidator, Default::default());
+        assert_eq!(
+            ix.accounts.last().unwrap().pubkey,
+            get_associated_token_address(&recipients[1], &policy.collateral_mint)
+        );
+    }
+
+    #[test]
+    fn gated_payouts_pass_the_beneficiarys_credential() {
+        let (address, mut policy) = native_policy();
+        let liquidator = Pubkey::new_unique();
+        let price_feed = Pubkey::new_unique();
+        let ungated =
+            liquidate_policy(address, &policy, liquidator, price_feed, Default::default()).unwrap();
+
+        // The config's credential comes ahead of the policy's own
+        policy.compliance_mint = Pubkey::new_unique();
+        let config_mint = Pubkey::new_unique();
+        let gated = liquidate_policy(
+            address,
+            &policy,
+            liquidator,
+            price_feed,
+            LiquidateOptions {
+                compliance_mint: Some(config_mint),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let changed: Vec<_> = ungated
+            .accounts
+            .iter()
+            .zip(&gated.accounts)
+            .filter(|(before, after)| before.pubkey != after.pubkey)
+            .map(|(_, after)| after.pubkey)
+            .collect();
+        assert_eq!(
+            changed,
+            [get_associated_token_address(
+                &policy.beneficiary,
+                &config_mint
+            )]
+        );
+        let ix = settle(address, &policy, liquidator, Default::default());
+        assert!(ix.accounts.iter().any(|meta| meta.pubkey
+            == get_associated_token_address(&policy.beneficiary, &policy.compliance_mint)));
+    }
+
+    #[test]
+    fn liquidate_policies_appends_a_triple_per_policy() {
+        let policies = [native_policy(), native_policy()];
+        let ix = liquidate_policies(
+            UnderlyingAsset::Btc,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            false,
+            &policies,
+        );
+        let triples = &ix.accounts[ix.accounts.len() - 6..];
+        assert_eq!(triples[3].pubkey, policies[1].0);
+        assert_eq!(triples[5].pubkey, policies[1].1.beneficiary);
+        assert!(triples
+            .iter()
+            .all(|meta| meta.is_writable && !meta.is_signer));
+    }
+}
@@ -0,0 +1,63 @@
+// Client SDK for LiqGuard: typed instruction builders, account decoding
+// and RPC fetchers, so integrators don't rebuild them from the IDL. PDA
+// derivations come from liqguard-common and are re-exported as seeds.
+// subscribe streams policy changes and decoded events over the websocket.
+// oracle reads Pyth's sponsored push feeds for current prices, and
+// lookup_tables keeps batch settlements under the transaction size limit.
+// errors turns failed sends and simulations into typed program errors,
+// and compute sizes compute budgets and priority fees. hermes and pyth
+// fetch and post Pyth pull updates, and price_updates bundles a post with
+// the liquidation that reads it
+pub mod accounts;
+pub mod compute;
+pub mod errors;
+pub mod events;
+pub mod hermes;
+pub mod instructions;
+pub mod lookup_tables;
+pub mod oracle;
+pub mod price_updates;
+pub mod pyth;
+pub mod rpc;
+pub mod subscribe;
+
+pub use liqguard;
+pub use liqguard_common::seeds;
+
+use anchor_lang::prelude::Pubkey;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("rpc request failed: {0}")]
+    Rpc(#[from] Box<solana_client::client_error::ClientError>),
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} is not a {1}")]
+    InvalidAccount(Pubkey, &'static str),
+    #[error("{0}")]
+    MissingAccount(&'static str),
+    #[error("price update unusable: {0}")]
+    PriceUpdate(String),
+    #[error("transaction can't be built: {0}")]
+    InvalidTransaction(String),
+    #[error("transaction is {0} bytes, over the packet size limit")]
+    TransactionTooLarge(usize),
+}
+
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(error: solana_client::client_error::ClientError) -> Self {
+        Self::Rpc(Box::new(error))
+    }
+}
+
+impl ClientError {
+    // The program error behind a rejected transaction, if that's what failed
+    pub fn program_error(&self) -> Option<errors::ProgramError> {
+        match self {
+            Self::Rpc(error) => errors::ProgramError::from_rpc_error(error),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,157 @@
+// Address lookup tables for batch settlement. A liquidate_policies
+// transaction spends 32 bytes on every account it names, so without a table
+// a handful of policies fills the 1232-byte packet. Putting the accounts
+// every batch for an asset shares, and each policy's three accounts, in a
+// table shrinks each reference to a one-byte index. A batch is then bound
+// by the runtime's per-transaction account lock limit instead
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::address_lookup_table::instruction::{
+    create_lookup_table, extend_lookup_table,
+};
+use anchor_lang::solana_program::address_lookup_table::state::AddressLookupTable;
+use anchor_lang::solana_program::address_lookup_table::AddressLookupTableAccount;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use liqguard::{Policy, UnderlyingAsset};
+use liqguard_common::seeds;
+
+use crate::{ClientError, Result};
+
+// Addresses per extend instruction, so each extend fits a legacy
+// transaction alongside its signatures
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 20;
+
+// Policies per liquidate_policies batch sent through a table. The runtime
+// locks at most 64 accounts per transaction; the instruction's own seven,
+// the fee payer and the compute budget program leave room for 18 policies
+pub const MAX_BATCH_POLICIES: usize = 18;
+
+// Accounts every liquidate_policies batch for asset names, whichever
+// policies it settles
+pub fn shared_accounts(asset: UnderlyingAsset, price_feed: Pubkey, treasury: bool) -> Vec<Pubkey> {
+    let program_id = &liqguard::ID;
+    let mut accounts = vec![
+        liqguard::ID,
+        system_program::ID,
+        seeds::config(program_id).0,
+        seeds::feed_registry(program_id, asset).0,
+        seeds::keeper_queue(program_id, asset).0,
+        price_feed,
+    ];
+    if treasury {
+        accounts.push(seeds::treasury(program_id).0);
+    }
+    accounts
+}
+
+// The accounts liquidate_policies takes for one policy, in its order
+pub fn policy_accounts(address: &Pubkey, policy: &Policy) -> [Pubkey; 3] {
+    [
+        *address,
+        seeds::vault(&liqguard::ID, &policy.creator, policy.policy_id).0,
+        policy.beneficiary,
+    ]
+}
+
+// A new table owned by authority, at the address create derives from
+// recent_slot, which must be a recent finalized slot
+pub fn create(authority: Pubkey, payer: Pubkey, recent_slot: u64) -> (Instruction, Pubkey) {
+    create_lookup_table(authority, payer, recent_slot)
+}
+
+// The addresses of wanted not yet in table, once each and in order
+pub fn missing(table: &[Pubkey], wanted: impl IntoIterator<Item = Pubkey>) -> Vec<Pubkey> {
+    let mut missing: Vec<Pubkey> = Vec::new();
+    for address in wanted {
+        if !table.contains(&address) && !missing.contains(&address) {
+            missing.push(address);
+        }
+    }
+    missing
+}
+
+// Extend instructions appending addresses to table, one per
+// MAX_ADDRESSES_PER_EXTEND. Appended addresses are usable from the slot
+// after the extend lands
+pub fn extend(
+    table: Pubkey,
+    authority: Pubkey,
+    payer: Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    addresses
+        .chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| extend_lookup_table(table, authority, Some(payer), chunk.to_vec()))
+        .collect()
+}
+
+pub fn decode(address: &Pubkey, data: &[u8]) -> Result<AddressLookupTableAccount> {
+    let table = AddressLookupTable::deserialize(data)
+        .map_err(|_| ClientError::InvalidAccount(*address, "address lookup table"))?;
+    Ok(AddressLookupTableAccount {
+        key: *address,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::liquidate_policies;
+    use liqguard_test_support::zeroed_policy;
+
+    #[test]
+    fn covers_every_account_a_batch_names() {
+        let price_feed = Pubkey::new_unique();
+        let policies: Vec<_> = (0..3)
+            .map(|policy_id| {
+                let mut policy = zeroed_policy();
+                policy.creator = Pubkey::new_unique();
+                policy.beneficiary = Pubkey::new_unique();
+                policy.policy_id = policy_id;
+                (Pubkey::new_unique(), policy)
+            })
+            .collect();
+        let liquidator = Pubkey::new_unique();
+        let ix = liquidate_policies(
+            UnderlyingAsset::Btc,
+            liquidator,
+            price_feed,
+            true,
+            &policies,
+        );
+
+        let table: Vec<_> = shared_accounts(UnderlyingAsset::Btc, price_feed, true)
+            .into_iter()
+            .chain(
+                policies
+                    .iter()
+                    .flat_map(|(address, policy)| policy_accounts(address, policy)),
+            )
+            .collect();
+        for meta in ix.accounts.iter().filter(|meta| meta.pubkey != liquidator) {
+            assert!(
+                table.contains(&meta.pubkey),
+                "{} not in the table",
+                meta.pubkey
+            );
+        }
+        assert!(missing(&table, table.clone()).is_empty());
+        assert_eq!(missing(&table[1..], table.clone()), vec![table[0]]);
+    }
+
+    #[test]
+    fn extends_in_chunks() {
+        let addresses: Vec<_> = (0..45).map(|_| Pubkey::new_unique()).collect();
+        let (create, table) = create(Pubkey::new_unique(), Pubkey::new_unique(), 7);
+        assert!(create.accounts.iter().any(|meta| meta.pubkey == table));
+        let extends = extend(
+            table,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &addresses,
+        );
+        assert_eq!(extends.len(), 3);
+    }
+}
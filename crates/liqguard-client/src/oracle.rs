@@ -0,0 +1,60 @@
+// Pyth's sponsored push feeds: receiver-owned PriceUpdateV2 accounts Pyth
+// keeps updated on shard 0 for major assets, readable without posting
+// anything. The program accepts them like any posted update
+
+use anchor_lang::prelude::Pubkey;
+use liqguard::OraclePrice;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::accounts::decode;
+use crate::Result;
+
+const SPONSORED_SHARD: u16 = 0;
+
+// A feed's latest price as a push feed holds it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpotPrice {
+    pub price: OraclePrice,
+    pub confidence: u64,
+    pub publish_time: i64,
+}
+
+pub fn sponsored_feed(feed_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[&SPONSORED_SHARD.to_le_bytes(), feed_id],
+        &pyth_solana_receiver_sdk::PYTH_PUSH_ORACLE_ID,
+    )
+    .0
+}
+
+pub fn decode_price_update(address: &Pubkey, data: &[u8]) -> Result<SpotPrice> {
+    let update: PriceUpdateV2 = decode(address, data)?;
+    let message = update.price_message;
+    Ok(SpotPrice {
+        price: OraclePrice::new(message.price, message.exponent),
+        confidence: message.conf,
+        publish_time: message.publish_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard_test_support::{Fixture, PriceUpdateBuilder};
+
+    #[test]
+    fn decodes_a_price_update() {
+        let data = PriceUpdateBuilder::new(liqguard::BTC_USD_FEED_ID)
+            .price(9_500_000_000_000, -8)
+            .conf(4_000_000_000)
+            .publish_time(1_700_000_000)
+            .data();
+        let spot = decode_price_update(&Pubkey::new_unique(), &data).unwrap();
+        assert_eq!(spot.price, OraclePrice::new(9_500_000_000_000i64, -8));
+        assert_eq!(
+            (spot.confidence, spot.publish_time),
+            (4_000_000_000, 1_700_000_000)
+        );
+        assert!(decode_price_update(&Pubkey::new_unique(), &data[..40]).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+// Liquidations that carry their own price. Posting a Hermes update in
+// one transaction and liquidating in another leaves the price to go stale
+// in between. Here the update's VAA is written and verified in two setup
+// transactions, then a third posts the price into a fresh price update
+// account, liquidates against it and closes both accounts, so the claim
+// always reads the price posted alongside it. The third transaction is
+// compiled as v0 against lookup tables to stay under the packet limit
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::address_lookup_table::AddressLookupTableAccount;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_instruction;
+use liqguard::Policy;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::message::{v0, VersionedMessage};
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::VersionedTransaction;
+
+use crate::instructions::{liquidate_policy, LiquidateOptions};
+use crate::pyth::{self, ParsedAccumulator};
+use crate::{ClientError, Result};
+
+// Compute for the receiver's post_update and a liquidate_policy together
+pub const POST_AND_LIQUIDATE_COMPUTE_UNITS: u32 = 600_000;
+
+// The three transactions of a self-priced liquidation, in send order
+pub struct PostAndLiquidate {
+    pub encoded_vaa: Keypair,
+    pub price_update: Keypair,
+    pub write: Vec<Instruction>, // create the encoded VAA and write its head
+    pub verify: Vec<Instruction>, // write the VAA's tail and verify its signatures
+    pub liquidate: Vec<Instruction>, // post, liquidate, then close both accounts
+}
+
+impl PostAndLiquidate {
+    // Each transaction with the signers it needs beyond the payer
+    pub fn steps(&self) -> Vec<(Vec<Instruction>, Vec<&Keypair>)> {
+        vec![
+            (self.write.clone(), vec![&self.encoded_vaa]),
+            (self.verify.clone(), Vec::new()),
+            (self.liquidate.clone(), vec![&self.price_update]),
+        ]
+    }
+}
+
+// Build a liquidation of policy that posts the first price in accumulator
+// as it goes. payer pays for and signs everything and is the liquidator;
+// encoded_vaa_rent is the rent for pyth::encoded_vaa_space of the VAA
+pub fn post_and_liquidate(
+    payer: Pubkey,
+    accumulator: ParsedAccumulator,
+    encoded_vaa_rent: u64,
+    treasury_id: u8,
+    policy_address: Pubkey,
+    policy: &Policy,
+    options: LiquidateOptions,
+) -> Result<PostAndLiquidate> {
+    let price_error = |error: String| ClientError::PriceUpdate(error);
+    let merkle_update = accumulator
+        .updates
+        .into_iter()
+        .next()
+        .ok_or_else(|| price_error("Hermes update carries no price".to_string()))?;
+    let vaa = accumulator.vaa;
+    let encoded_vaa = Keypair::new();
+    let price_update = Keypair::new();
+    let (head, tail) = vaa.split_at(pyth::VAA_SPLIT_INDEX.min(vaa.len()));
+
+    let write = vec![
+        system_instruction::create_account(
+            &payer,
+            &encoded_vaa.pubkey(),
+            encoded_vaa_rent,
+            pyth::encoded_vaa_space(&vaa),
+            &pyth::WORMHOLE_PROGRAM_ID,
+        ),
+        pyth::init_encoded_vaa(payer, encoded_vaa.pubkey()),
+        pyth::write_encoded_vaa(payer, encoded_vaa.pubkey(), 0, head),
+    ];
+
+    let mut verify = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        pyth::VERIFY_COMPUTE_UNITS,
+    )];
+    if !tail.is_empty() {
+        verify.push(pyth::write_encoded_vaa(
+            payer,
+            encoded_vaa.pubkey(),
+            head.len() as u32,
+            tail,
+        ));
+    }
+    verify.push(pyth::verify_encoded_vaa(
+        payer,
+        encoded_vaa.pubkey(),
+        pyth::guardian_set(&vaa).map_err(price_error)?,
+    ));
+
+    let liquidate = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(POST_AND_LIQUIDATE_COMPUTE_UNITS),
+        pyth::post_update(
+            payer,
+            encoded_vaa.pubkey(),
+            price_update.pubkey(),
+            merkle_update,
+            treasury_id,
+        ),
+        liquidate_policy(
+            policy_address,
+            policy,
+            payer,
+            price_update.pubkey(),
+            options,
+        )?,
+        pyth::reclaim_rent(payer, price_update.pubkey()),
+        pyth::close_encoded_vaa(payer, encoded_vaa.pubkey()),
+    ];
+    Ok(PostAndLiquidate {
+        encoded_vaa,
+        price_update,
+        write,
+        verify,
+        liquidate,
+    })
+}
+
+// Sign instructions into a v0 transaction that resolves accounts through
+// tables, failing if it still doesn't fit in a packet
+pub fn compile(
+    payer: &Keypair,
+    signers: &[&Keypair],
+    instructions: &[Instruction],
+    tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    let message = v0::Message::try_compile(&payer.pubkey(), instructions, tables, blockhash)
+        .map_err(|error| ClientError::InvalidTransaction(error.to_string()))?;
+    let mut keypairs = vec![payer];
+    keypairs.extend_from_slice(signers);
+    let transaction = VersionedTransaction::try_new(VersionedMessage::V0(message), &keypairs)
+        .map_err(|error| ClientError::InvalidTransaction(error.to_string()))?;
+    let size = bincode::serialized_size(&transaction)
+        .map_err(|error| ClientError::InvalidTransaction(error.to_string()))?
+        as usize;
+    if size > PACKET_DATA_SIZE {
+        return Err(ClientError::TransactionTooLarge(size));
+    }
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard_test_support::zeroed_policy;
+    use pythnet_sdk::accumulators::merkle::MerklePath;
+    use pythnet_sdk::wire::v1::MerklePriceUpdate;
+
+    fn accumulator(vaa_len: usize) -> ParsedAccumulator {
+        let mut vaa = vec![1, 0, 0, 0, 4];
+        vaa.resize(vaa_len, 7);
+        ParsedAccumulator {
+            vaa,
+            updates: vec![MerklePriceUpdate {
+                message: vec![0; 85].into(),
+                proof: MerklePath::new(vec![[3; 20]; 10]),
+            }],
+        }
+    }
+
+    fn policy() -> Policy {
+        let mut policy = zeroed_policy();
+        policy.owner = Pubkey::new_unique();
+        policy.creator = policy.owner;
+        policy.beneficiary = policy.owner;
+        policy.underwriter = Pubkey::new_unique();
+        policy
+    }
+
+    #[test]
+    fn the_liquidation_reads_the_price_it_posts() {
+        let payer = Pubkey::new_unique();
+        let built = post_and_liquidate(
+            payer,
+            accumulator(1_000),
+            1,
+            0,
+            Pubkey::new_unique(),
+            &policy(),
+            LiquidateOptions::default(),
+        )
+        .unwrap();
+        let steps = built.steps();
+        assert_eq!(steps.len(), 3);
+        // The tail is written alongside the verify
+        assert_eq!(built.verify.len(), 3);
+
+        let programs: Vec<Pubkey> = built.liquidate.iter().map(|ix| ix.program_id).collect();
+        assert_eq!(
+            programs[1..],
+            [
+                pyth_solana_receiver_sdk::ID,
+                liqguard::ID,
+                pyth_solana_receiver_sdk::ID,
+                pyth::WORMHOLE_PROGRAM_ID,
+            ]
+        );
+        let price_update = built.price_update.pubkey();
+        assert!(built.liquidate[2]
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == price_update));
+        assert_eq!(built.liquidate[3].accounts[1].pubkey, price_update);
+    }
+
+    #[test]
+    fn short_vaas_skip_the_tail_write() {
+        let built = post_and_liquidate(
+            Pubkey::new_unique(),
+            accumulator(pyth::VAA_SPLIT_INDEX),
+            1,
+            0,
+            Pubkey::new_unique(),
+            &policy(),
+            LiquidateOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(built.verify.len(), 2);
+        let empty = ParsedAccumulator {
+            vaa: vec![1, 0, 0, 0, 4],
+            updates: Vec::new(),
+        };
+        assert!(post_and_liquidate(
+            Pubkey::new_unique(),
+            empty,
+            1,
+            0,
+            Pubkey::new_unique(),
+            &policy(),
+            LiquidateOptions::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn compile_rejects_transactions_over_the_packet_limit() {
+        let payer = Keypair::new();
+        let built = post_and_liquidate(
+            payer.pubkey(),
+            accumulator(1_000),
+            1,
+            0,
+            Pubkey::new_unique(),
+            &policy(),
+            LiquidateOptions::default(),
+        )
+        .unwrap();
+        // Without a table every account costs 32 bytes
+        let oversized = (0..40)
+            .map(|_| system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            compile(&payer, &[], &oversized, &[], Hash::default()),
+            Err(ClientError::TransactionTooLarge(_))
+        ));
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: built
+                .liquidate
+                .iter()
+                .flat_map(|ix| {
+                    ix.accounts
+                        .iter()
+                        .filter(|meta| !meta.is_signer)
+                        .map(|meta| meta.pubkey)
+                        .chain([ix.program_id])
+                })
+                .filter(|key| *key != solana_sdk::compute_budget::ID)
+                .collect(),
+        };
+        compile(
+            &payer,
+            &[&built.price_update],
+            &built.liquidate,
+            &[table],
+            Hash::default(),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+// Posting a Hermes update through the Pyth receiver with full guardian
+// verification, which liqguard requires. The update's VAA is too large
+// for one transaction, so it is written to a Wormhole encoded VAA account
+// in two parts and verified there, then the receiver posts the price from
+// it into a price update account the poster is write authority of
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use borsh::BorshSerialize;
+use pyth_solana_receiver_sdk::{pda, PostUpdateParams};
+use pythnet_sdk::wire::v1::{AccumulatorUpdateData, MerklePriceUpdate, Proof};
+
+// Wormhole core bridge the Pyth receiver verifies against
+// (HDwcJBJXjL9FpJ7UBsYBtaDjsBUhuLCUYoz3zr8SWWaQ)
+pub const WORMHOLE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    241, 11, 180, 229, 13, 86, 253, 161, 61, 254, 31, 50, 155, 141, 57, 61, 210, 74, 1, 69, 145,
+    225, 131, 22, 151, 148, 13, 124, 52, 163, 141, 221,
+]);
+
+// Anchor instruction discriminators: sha256("global:<name>")[..8]
+const INIT_ENCODED_VAA_DISCRIMINATOR: [u8; 8] = [209, 193, 173, 25, 91, 202, 181, 218];
+const WRITE_ENCODED_VAA_DISCRIMINATOR: [u8; 8] = [199, 208, 110, 177, 150, 76, 118, 42];
+const VERIFY_ENCODED_VAA_V1_DISCRIMINATOR: [u8; 8] = [103, 56, 177, 229, 240, 103, 68, 73];
+const CLOSE_ENCODED_VAA_DISCRIMINATOR: [u8; 8] = [48, 221, 174, 198, 231, 7, 152, 38];
+const POST_UPDATE_DISCRIMINATOR: [u8; 8] = [133, 95, 207, 175, 11, 79, 118, 44];
+const RECLAIM_RENT_DISCRIMINATOR: [u8; 8] = [218, 200, 19, 197, 227, 89, 192, 22];
+
+// EncodedVaa header: discriminator, status, write_authority, version,
+// then the u32-prefixed VAA bytes
+const ENCODED_VAA_HEADER_LEN: usize = 8 + 1 + 32 + 1 + 4;
+
+// VAA bytes written by the first transaction; the rest go with verify
+pub const VAA_SPLIT_INDEX: usize = 755;
+
+// Compute verify_encoded_vaa_v1 needs to check a full guardian set
+pub const VERIFY_COMPUTE_UNITS: u32 = 600_000;
+
+// A Hermes accumulator update split into what gets posted
+pub struct ParsedAccumulator {
+    pub vaa: Vec<u8>,
+    pub updates: Vec<MerklePriceUpdate>,
+}
+
+pub fn parse_accumulator(bytes: &[u8]) -> Result<ParsedAccumulator, String> {
+    let data = AccumulatorUpdateData::try_from_slice(bytes)
+        .map_err(|_| "not a Pyth accumulator update".to_string())?;
+    let Proof::WormholeMerkle { vaa, updates } = data.proof;
+    Ok(ParsedAccumulator {
+        vaa: vaa.into(),
+        updates,
+    })
+}
+
+// Guardian set the VAA was signed by: version byte, then a big-endian
+// guardian set index
+pub fn guardian_set(vaa: &[u8]) -> Result<Pubkey, String> {
+    let index: [u8; 4] = vaa
+        .get(1..5)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("VAA too short")?;
+    Ok(Pubkey::find_program_address(&[b"GuardianSet", &index], &WORMHOLE_PROGRAM_ID).0)
+}
+
+pub fn encoded_vaa_space(vaa: &[u8]) -> u64 {
+    (ENCODED_VAA_HEADER_LEN + vaa.len()) as u64
+}
+
+pub fn init_encoded_vaa(write_authority: Pubkey, encoded_vaa: Pubkey) -> Instruction {
+    Instruction {
+        program_id: WORMHOLE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(write_authority, true),
+            AccountMeta::new(encoded_vaa, false),
+        ],
+        data: INIT_ENCODED_VAA_DISCRIMINATOR.to_vec(),
+    }
+}
+
+pub fn write_encoded_vaa(
+    write_authority: Pubkey,
+    encoded_vaa: Pubkey,
+    index: u32,
+    chunk: &[u8],
+) -> Instruction {
+    let mut data = WRITE_ENCODED_VAA_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&index.to_le_bytes());
+    data.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    data.extend_from_slice(chunk);
+    Instruction {
+        program_id: WORMHOLE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(write_authority, true),
+            AccountMeta::new(encoded_vaa, false),
+        ],
+        data,
+    }
+}
+
+pub fn verify_encoded_vaa(
+    write_authority: Pubkey,
+    encoded_vaa: Pubkey,
+    guardian_set: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: WORMHOLE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(write_authority, true),
+            AccountMeta::new(encoded_vaa, false),
+            AccountMeta::new_readonly(guardian_set, false),
+        ],
+        data: VERIFY_ENCODED_VAA_V1_DISCRIMINATOR.to_vec(),
+    }
+}
+
+// Returns the encoded VAA's rent to the write authority
+pub fn close_encoded_vaa(write_authority: Pubkey, encoded_vaa: Pubkey) -> Instruction {
+    Instruction {
+        program_id: WORMHOLE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(write_authority, true),
+            AccountMeta::new(encoded_vaa, false),
+        ],
+        data: CLOSE_ENCODED_VAA_DISCRIMINATOR.to_vec(),
+    }
+}
+
+// Post one feed's update into price_update_account, which must sign the
+// first time it is written. Later posts overwrite it while the same
+// write authority signs
+pub fn post_update(
+    payer: Pubkey,
+    encoded_vaa: Pubkey,
+    price_update_account: Pubkey,
+    merkle_price_update: MerklePriceUpdate,
+    treasury_id: u8,
+) -> Instruction {
+    let mut data = POST_UPDATE_DISCRIMINATOR.to_vec();
+    PostUpdateParams {
+        merkle_price_update,
+        treasury_id,
+    }
+    .serialize(&mut data)
+    .expect("borsh serialization into a Vec can't fail");
+    Instruction {
+        program_id: pyth_solana_receiver_sdk::ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(encoded_vaa, false),
+            AccountMeta::new_readonly(pda::get_config_address(), false),
+            AccountMeta::new(pda::get_treasury_address(treasury_id), false),
+            AccountMeta::new(price_update_account, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(payer, true),
+        ],
+        data,
+    }
+}
+
+// Close a price update account, returning its rent to the write
+// authority that posted it
+pub fn reclaim_rent(write_authority: Pubkey, price_update_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: pyth_solana_receiver_sdk::ID,
+        accounts: vec![
+            AccountMeta::new(write_authority, true),
+            AccountMeta::new(price_update_account, false),
+        ],
+        data: RECLAIM_RENT_DISCRIMINATOR.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_encoded_vaa_borsh_encodes_its_args() {
+        let ix = write_encoded_vaa(Pubkey::new_unique(), Pubkey::new_unique(), 755, &[7, 8, 9]);
+        assert_eq!(ix.data[..8], WRITE_ENCODED_VAA_DISCRIMINATOR);
+        assert_eq!(ix.data[8..], [243, 2, 0, 0, 3, 0, 0, 0, 7, 8, 9]);
+    }
+
+    #[test]
+    fn guardian_set_reads_the_big_endian_index() {
+        let vaa = [1, 0, 0, 0, 4, 13];
+        let expected = Pubkey::find_program_address(
+            &[b"GuardianSet", &4u32.to_be_bytes()],
+            &WORMHOLE_PROGRAM_ID,
+        )
+        .0;
+        assert_eq!(guardian_set(&vaa), Ok(expected));
+        assert!(guardian_set(&[1, 0]).is_err());
+    }
+
+    #[test]
+    fn parse_accumulator_rejects_other_payloads() {
+        assert!(parse_accumulator(b"not an update").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+// Async account fetchers over a nonblocking RPC client. Program-wide
+// listings filter on the account discriminator, plus any PolicyFilter
+// memcmps, so only matching accounts cross the wire
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::address_lookup_table::AddressLookupTableAccount;
+use anchor_lang::{AccountDeserialize, Discriminator, ZeroCopy};
+use liqguard::oracle::OracleSource;
+use liqguard::{
+    ClaimHistory, Config, FeedRegistry, KeeperQueue, OwnerState, Policy, UnderlyingAsset,
+};
+use liqguard_common::seeds;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+
+use crate::accounts::{
+    decode, decode_zero_copy, POLICY_ASSET_OFFSET, POLICY_BENEFICIARY_OFFSET,
+    POLICY_CREATOR_OFFSET, POLICY_OWNER_OFFSET,
+};
+use crate::hermes::HermesClient;
+use crate::instructions::LiquidateOptions;
+use crate::lookup_tables;
+use crate::oracle::{decode_price_update, sponsored_feed, SpotPrice};
+use crate::price_updates::{self, PostAndLiquidate};
+use crate::pyth;
+use crate::{ClientError, Result};
+
+// Narrows a policy listing; filters combine with AND
+#[derive(Clone, Copy, Debug)]
+pub enum PolicyFilter {
+    Owner(Pubkey),
+    Creator(Pubkey),
+    Beneficiary(Pubkey),
+    Asset(UnderlyingAsset),
+}
+
+impl PolicyFilter {
+    fn memcmp(&self) -> RpcFilterType {
+        let (offset, bytes) = match self {
+            PolicyFilter::Owner(key) => (POLICY_OWNER_OFFSET, key.to_bytes().to_vec()),
+            PolicyFilter::Creator(key) => (POLICY_CREATOR_OFFSET, key.to_bytes().to_vec()),
+            PolicyFilter::Beneficiary(key) => (POLICY_BENEFICIARY_OFFSET, key.to_bytes().to_vec()),
+            PolicyFilter::Asset(asset) => (POLICY_ASSET_OFFSET, asset.seed().to_vec()),
+        };
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, bytes))
+    }
+}
+
+// Policy accounts matching every filter
+pub(crate) fn policy_filters(filters: &[PolicyFilter]) -> Vec<RpcFilterType> {
+    let mut rpc_filters = vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        0,
+        Policy::DISCRIMINATOR.to_vec(),
+    ))];
+    rpc_filters.extend(filters.iter().map(PolicyFilter::memcmp));
+    rpc_filters
+}
+
+pub struct LiqGuardClient {
+    pub rpc: RpcClient,
+}
+
+impl LiqGuardClient {
+    pub fn new(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+
+    // Raw data of a liqguard-owned account, discriminator included
+    pub async fn account_data(&self, address: &Pubkey) -> Result<Vec<u8>> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        if account.owner != liqguard::ID {
+            return Err(ClientError::InvalidAccount(*address, "liqguard account"));
+        }
+        Ok(account.data)
+    }
+
+    pub async fn fetch<T: AccountDeserialize + Discriminator>(
+        &self,
+        address: &Pubkey,
+    ) -> Result<T> {
+        decode(address, &self.account_data(address).await?)
+    }
+
+    pub async fn fetch_zero_copy<T: ZeroCopy>(&self, address: &Pubkey) -> Result<T> {
+        decode_zero_copy(address, &self.account_data(address).await?)
+    }
+
+    pub async fn policy(&self, address: &Pubkey) -> Result<Policy> {
+        self.fetch(address).await
+    }
+
+    pub async fn config(&self) -> Result<Config> {
+        self.fetch(&seeds::config(&liqguard::ID).0).await
+    }
+
+    pub async fn feed_registry(&self, asset: UnderlyingAsset) -> Result<FeedRegistry> {
+        self.fetch(&seeds::feed_registry(&liqguard::ID, asset).0)
+            .await
+    }
+
+    pub async fn keeper_queue(&self, asset: UnderlyingAsset) -> Result<KeeperQueue> {
+        self.fetch(&seeds::keeper_queue(&liqguard::ID, asset).0)
+            .await
+    }
+
+    pub async fn owner_state(&self, owner: &Pubkey) -> Result<OwnerState> {
+        self.fetch(&seeds::owner_state(&liqguard::ID, owner).0)
+            .await
+    }
+
+    pub async fn claim_history(&self, owner: &Pubkey) -> Result<ClaimHistory> {
+        self.fetch_zero_copy(&seeds::claim_history(&liqguard::ID, owner).0)
+            .await
+    }
+
+    // asset's current price from Pyth's sponsored push feed
+    pub async fn sponsored_price(&self, asset: UnderlyingAsset) -> Result<SpotPrice> {
+        let address = sponsored_feed(&asset.feed_id());
+        let account = self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(address))?;
+        if account.owner != pyth_solana_receiver_sdk::ID {
+            return Err(ClientError::InvalidAccount(address, "Pyth price update"));
+        }
+        decode_price_update(&address, &account.data)
+    }
+
+    // Fetch the latest Hermes update for policy's feed and build a
+    // liquidation that posts it alongside the claim. payer is the
+    // liquidator
+    pub async fn post_and_liquidate(
+        &self,
+        hermes: &HermesClient,
+        payer: Pubkey,
+        treasury_id: u8,
+        policy_address: Pubkey,
+        policy: &Policy,
+        options: LiquidateOptions,
+    ) -> Result<PostAndLiquidate> {
+        let feed = self.feed_registry(policy.asset).await?;
+        if feed.oracle_source != OracleSource::PythPull
+            || policy.chainlink_feed != Pubkey::default()
+        {
+            return Err(ClientError::PriceUpdate(
+                "the policy doesn't settle on a Pyth pull feed".to_string(),
+            ));
+        }
+        let update = hermes
+            .latest(&feed.feed_id)
+            .await
+            .map_err(ClientError::PriceUpdate)?;
+        let accumulator =
+            pyth::parse_accumulator(&update.accumulator).map_err(ClientError::PriceUpdate)?;
+        let rent = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(
+                pyth::encoded_vaa_space(&accumulator.vaa) as usize
+            )
+            .await?;
+        price_updates::post_and_liquidate(
+            payer,
+            accumulator,
+            rent,
+            treasury_id,
+            policy_address,
+            policy,
+            options,
+        )
+    }
+
+    pub async fn lookup_table(&self, address: &Pubkey) -> Result<AddressLookupTableAccount> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        lookup_tables::decode(address, &account.data)
+    }
+
+    // Every policy matching filters. Accounts that fail to decode, such as
+    // policies still on an older layout, are skipped
+    pub async fn policies(&self, filters: &[PolicyFilter]) -> Result<Vec<(Pubkey, Policy)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(policy_filters(filters)),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(&liqguard::ID, config)
+            .await?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(address, account)| {
+                decode(&address, &account.data)
+                    .ok()
+                    .map(|policy| (address, policy))
+            })
+            .collect())
+    }
+}
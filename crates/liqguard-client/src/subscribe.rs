@@ -0,0 +1,204 @@
+// Push-based updates over the RPC websocket. Each subscription runs on a
+// spawned tokio task that reconnects after the socket drops, and yields
+// Update::Reconnected first so consumers can refetch whatever they missed.
+// The task stops once the returned stream is dropped
+
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use liqguard::Policy;
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsConfig,
+    RpcTransactionLogsFilter,
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+
+use crate::accounts::decode;
+use crate::events::{parse_logs, LiqGuardEvent};
+use crate::rpc::{policy_filters, PolicyFilter};
+
+#[derive(Clone, Debug)]
+pub struct SubscribeConfig {
+    pub ws_url: String,
+    pub commitment: CommitmentConfig,
+    pub reconnect_delay: Duration, // wait between reconnect attempts
+}
+
+impl SubscribeConfig {
+    pub fn new(ws_url: impl Into<String>, commitment: CommitmentConfig) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            commitment,
+            reconnect_delay: Duration::from_secs(1),
+        }
+    }
+
+    fn account_config(&self) -> RpcAccountInfoConfig {
+        RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..RpcAccountInfoConfig::default()
+        }
+    }
+}
+
+pub enum Update<T> {
+    Value { slot: u64, value: T },
+    Reconnected, // the socket dropped; updates in between were missed
+}
+
+pub type UpdateStream<T> = UnboundedReceiver<Update<T>>;
+
+pub struct PolicyUpdate {
+    pub address: Pubkey,
+    pub policy: Policy,
+}
+
+pub struct EventUpdate {
+    pub signature: Signature,
+    pub index: usize, // position among the transaction's events
+    pub event: LiqGuardEvent,
+}
+
+fn decode_policy(address: &Pubkey, account: &UiAccount) -> Option<Policy> {
+    decode(address, &account.data.decode()?).ok()
+}
+
+// Connect, announcing every connection after the first. None once the
+// receiver is gone
+async fn connect<T>(
+    config: &SubscribeConfig,
+    sender: &UnboundedSender<Update<T>>,
+    connected_before: &mut bool,
+) -> Option<PubsubClient> {
+    loop {
+        if sender.is_closed() {
+            return None;
+        }
+        if let Ok(client) = PubsubClient::new(&config.ws_url).await {
+            if std::mem::replace(connected_before, true)
+                && sender.unbounded_send(Update::Reconnected).is_err()
+            {
+                return None;
+            }
+            return Some(client);
+        }
+        tokio::time::sleep(config.reconnect_delay).await;
+    }
+}
+
+// Every change to one policy account
+pub fn policy(config: SubscribeConfig, address: Pubkey) -> UpdateStream<PolicyUpdate> {
+    let (sender, receiver) = unbounded();
+    tokio::spawn(async move {
+        let mut connected_before = false;
+        while let Some(client) = connect(&config, &sender, &mut connected_before).await {
+            if let Ok((mut stream, _unsubscribe)) = client
+                .account_subscribe(&address, Some(config.account_config()))
+                .await
+            {
+                while let Some(response) = stream.next().await {
+                    let Some(policy) = decode_policy(&address, &response.value) else {
+                        continue;
+                    };
+                    let update = Update::Value {
+                        slot: response.context.slot,
+                        value: PolicyUpdate { address, policy },
+                    };
+                    if sender.unbounded_send(update).is_err() {
+                        return;
+                    }
+                }
+            }
+            tokio::time::sleep(config.reconnect_delay).await;
+        }
+    });
+    receiver
+}
+
+// Every change to a policy matching filters
+pub fn policies(config: SubscribeConfig, filters: Vec<PolicyFilter>) -> UpdateStream<PolicyUpdate> {
+    let (sender, receiver) = unbounded();
+    tokio::spawn(async move {
+        let program_config = RpcProgramAccountsConfig {
+            filters: Some(policy_filters(&filters)),
+            account_config: config.account_config(),
+            ..RpcProgramAccountsConfig::default()
+        };
+        let mut connected_before = false;
+        while let Some(client) = connect(&config, &sender, &mut connected_before).await {
+            if let Ok((mut stream, _unsubscribe)) = client
+                .program_subscribe(&liqguard::ID, Some(program_config.clone()))
+                .await
+            {
+                while let Some(response) = stream.next().await {
+                    let Ok(address) = response.value.pubkey.parse::<Pubkey>() else {
+                        continue;
+                    };
+                    let Some(policy) = decode_policy(&address, &response.value.account) else {
+                        continue;
+                    };
+                    let update = Update::Value {
+                        slot: response.context.slot,
+                        value: PolicyUpdate { address, policy },
+                    };
+                    if sender.unbounded_send(update).is_err() {
+                        return;
+                    }
+                }
+            }
+            tokio::time::sleep(config.reconnect_delay).await;
+        }
+    });
+    receiver
+}
+
+// Events from every successful transaction that invokes liqguard. Failed
+// transactions are skipped: their events were rolled back with them
+pub fn events(config: SubscribeConfig) -> UpdateStream<EventUpdate> {
+    let (sender, receiver) = unbounded();
+    tokio::spawn(async move {
+        let mut connected_before = false;
+        while let Some(client) = connect(&config, &sender, &mut connected_before).await {
+            if let Ok((mut stream, _unsubscribe)) = client
+                .logs_subscribe(
+                    RpcTransactionLogsFilter::Mentions(vec![liqguard::ID.to_string()]),
+                    RpcTransactionLogsConfig {
+                        commitment: Some(config.commitment),
+                    },
+                )
+                .await
+            {
+                while let Some(response) = stream.next().await {
+                    let logs = response.value;
+                    let Ok(signature) = logs.signature.parse::<Signature>() else {
+                        continue;
+                    };
+                    if logs.err.is_some() {
+                        continue;
+                    }
+                    for (index, event) in parse_logs(&logs.logs).into_iter().enumerate() {
+                        let update = Update::Value {
+                            slot: response.context.slot,
+                            value: EventUpdate {
+                                signature,
+                                index,
+                                event,
+                            },
+                        };
+                        if sender.unbounded_send(update).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            tokio::time::sleep(config.reconnect_delay).await;
+        }
+    });
+    receiver
+}
@@ -0,0 +1,80 @@
+// Settings that differ between the clusters LiqGuard deploys to, chosen at
+// build time by the devnet or mainnet feature. With neither, the build is
+// for a local validator. Pyth's stable feed ids are the same on every
+// cluster, so those stay in the crate root
+use anchor_lang::prelude::{pubkey, Pubkey};
+
+#[cfg(all(feature = "devnet", feature = "mainnet"))]
+compile_error!("the devnet and mainnet features are mutually exclusive");
+
+pub const WRAPPED_SOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+
+#[cfg(feature = "mainnet")]
+mod selected {
+    use super::*;
+
+    pub const NAME: &str = "mainnet";
+    pub const DEFAULT_MAX_PRICE_AGE: u64 = 60;
+    pub const USDC_MINT: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+    pub const COLLATERAL_MINTS: &[Pubkey] = &[
+        USDC_MINT,
+        pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"), // USDT
+        pubkey!("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So"),  // mSOL
+        WRAPPED_SOL_MINT,
+    ];
+}
+
+#[cfg(all(feature = "devnet", not(feature = "mainnet")))]
+mod selected {
+    use super::*;
+
+    pub const NAME: &str = "devnet";
+    // Sponsored feeds on devnet update less often than on mainnet
+    pub const DEFAULT_MAX_PRICE_AGE: u64 = 300;
+    pub const USDC_MINT: Pubkey = pubkey!("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU");
+    pub const COLLATERAL_MINTS: &[Pubkey] = &[USDC_MINT, WRAPPED_SOL_MINT];
+}
+
+#[cfg(not(any(feature = "devnet", feature = "mainnet")))]
+mod selected {
+    use super::*;
+
+    pub const NAME: &str = "localnet";
+    // Local prices come from mock-pyth at whatever publish time a test
+    // writes, so allow the longest age the config accepts
+    pub const DEFAULT_MAX_PRICE_AGE: u64 = 600;
+    // Tests create their own mints, so there is no fixed USDC mint
+    pub const USDC_MINT: Pubkey = Pubkey::new_from_array([0; 32]);
+    // Empty: any mint may back a policy
+    pub const COLLATERAL_MINTS: &[Pubkey] = &[];
+}
+
+// Cluster name, default Config.default_max_price_age, the USDC mint swapped
+// payouts are delivered in (default pubkey where there's none) and the SPL
+// mints policies may hold as collateral
+pub use selected::{COLLATERAL_MINTS, DEFAULT_MAX_PRICE_AGE, NAME, USDC_MINT};
+
+// Whether mint may back a policy on this cluster. Native SOL policies use
+// the default pubkey and are always allowed
+pub fn collateral_mint_allowed(mint: &Pubkey) -> bool {
+    *mint == Pubkey::default() || COLLATERAL_MINTS.is_empty() || COLLATERAL_MINTS.contains(mint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_collateral_is_always_allowed() {
+        assert!(collateral_mint_allowed(&Pubkey::default()));
+        assert!((5..=600).contains(&DEFAULT_MAX_PRICE_AGE));
+        for mint in COLLATERAL_MINTS {
+            assert!(collateral_mint_allowed(mint));
+        }
+        // Only a local build accepts an arbitrary mint
+        assert_eq!(
+            collateral_mint_allowed(&Pubkey::new_unique()),
+            NAME == "localnet"
+        );
+    }
+}
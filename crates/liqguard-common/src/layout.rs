@@ -0,0 +1,101 @@
+// Sizes and discriminators of LiqGuard's accounts, for clients that
+// allocate, filter or decode them without linking the program. LENs are
+// the bodies; an account's space is DISCRIMINATOR_LEN + its LEN. The
+// program's tests check each against its own definitions
+
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+// Protocol-wide settings, one per deployment
+pub const CONFIG_LEN: usize = 600;
+pub const CONFIG_DISCRIMINATOR: [u8; 8] = [155, 12, 170, 224, 30, 250, 204, 130];
+
+// A proposed config change waiting out config_change_delay
+pub const PENDING_CONFIG_CHANGE_LEN: usize = 583;
+pub const PENDING_CONFIG_CHANGE_DISCRIMINATOR: [u8; 8] = [184, 206, 249, 115, 181, 5, 94, 185];
+
+// Fund that tops up vaults short of a payout
+pub const BACKSTOP_LEN: usize = 17;
+pub const BACKSTOP_DISCRIMINATOR: [u8; 8] = [108, 64, 182, 221, 12, 118, 83, 180];
+
+// Collects payout fees
+pub const TREASURY_LEN: usize = 17;
+pub const TREASURY_DISCRIMINATOR: [u8; 8] = [238, 239, 123, 238, 89, 1, 168, 253];
+
+// An underwriter's outstanding backstop debt
+pub const UNDERWRITER_RECORD_LEN: usize = 41;
+pub const UNDERWRITER_RECORD_DISCRIMINATOR: [u8; 8] = [76, 143, 90, 161, 163, 150, 63, 64];
+
+// Shared vault backing several of an underwriter's policies
+pub const PORTFOLIO_VAULT_LEN: usize = 41;
+pub const PORTFOLIO_VAULT_DISCRIMINATOR: [u8; 8] = [83, 117, 92, 138, 212, 234, 242, 206];
+
+// Per-asset oracle configuration
+pub const FEED_REGISTRY_LEN: usize = 110;
+pub const FEED_REGISTRY_DISCRIMINATOR: [u8; 8] = [165, 19, 142, 137, 18, 194, 61, 247];
+
+// Last verified Pyth Lazer price for an asset
+pub const LAZER_PRICE_LEN: usize = 34;
+pub const LAZER_PRICE_DISCRIMINATOR: [u8; 8] = [185, 157, 20, 125, 30, 242, 227, 41];
+
+// Per-asset index of policies keepers watch
+pub const KEEPER_QUEUE_LEN: usize = 7302;
+pub const KEEPER_QUEUE_DISCRIMINATOR: [u8; 8] = [41, 255, 144, 216, 95, 184, 139, 36];
+
+// Zero-copy ring of settlement attempts
+pub const AUDIT_LOG_LEN: usize = 9232;
+pub const AUDIT_LOG_DISCRIMINATOR: [u8; 8] = [230, 207, 176, 233, 170, 130, 101, 244];
+
+// Zero-copy ring of an owner's claims
+pub const CLAIM_HISTORY_LEN: usize = 2104;
+pub const CLAIM_HISTORY_DISCRIMINATOR: [u8; 8] = [93, 242, 5, 111, 33, 5, 196, 152];
+
+// Zero-copy book of an underwriter's pooled policies
+pub const POLICY_BOOK_LEN: usize = 147504;
+pub const POLICY_BOOK_DISCRIMINATOR: [u8; 8] = [29, 216, 100, 144, 73, 220, 102, 221];
+
+// An underwriter's compressed policy tree
+pub const COMPRESSED_POLICY_TREE_LEN: usize = 81;
+pub const COMPRESSED_POLICY_TREE_DISCRIMINATOR: [u8; 8] = [98, 85, 11, 230, 26, 157, 105, 20];
+
+// A redeemed cross-chain premium payment
+pub const WORMHOLE_PAYMENT_LEN: usize = 49;
+pub const WORMHOLE_PAYMENT_DISCRIMINATOR: [u8; 8] = [246, 40, 87, 23, 171, 201, 64, 164];
+
+// An open dispute over a liquidation
+pub const DISPUTE_LEN: usize = 81;
+pub const DISPUTE_DISCRIMINATOR: [u8; 8] = [36, 49, 241, 67, 40, 36, 241, 74];
+
+// A vesting payout's schedule
+pub const CLAIM_STATE_LEN: usize = 73;
+pub const CLAIM_STATE_DISCRIMINATOR: [u8; 8] = [71, 73, 19, 83, 53, 228, 242, 53];
+
+// An owner's policy counter and open coverage
+pub const OWNER_STATE_LEN: usize = 49;
+pub const OWNER_STATE_DISCRIMINATOR: [u8; 8] = [234, 56, 107, 216, 144, 52, 54, 244];
+
+// One insurance policy
+pub const POLICY_LEN: usize = 1140;
+pub const POLICY_DISCRIMINATOR: [u8; 8] = [222, 135, 7, 163, 235, 177, 33, 68];
+// Version 6 policies, before spreads, version 5, before the breach
+// dwell, version 4, before compliance credentials, version 3, before
+// master agreements, and version 2, before payout splits; extend_account
+// grows them all
+pub const POLICY_V6_LEN: usize = 1132;
+pub const POLICY_V5_LEN: usize = 1116;
+pub const POLICY_V4_LEN: usize = 1084;
+pub const POLICY_V3_LEN: usize = 1052;
+pub const POLICY_V2_LEN: usize = 916;
+// Policies opened before the version byte: the v2 layout without it
+pub const POLICY_V1_LEN: usize = POLICY_V2_LEN - 1;
+
+// A counterparty's aggregate limit shared across member policies
+pub const MASTER_AGREEMENT_LEN: usize = 134;
+pub const MASTER_AGREEMENT_DISCRIMINATOR: [u8; 8] = [4, 22, 143, 104, 174, 194, 57, 81];
+
+// An asset's validated price, cached for batches in the same slot
+pub const PRICE_CACHE_LEN: usize = 75;
+pub const PRICE_CACHE_DISCRIMINATOR: [u8; 8] = [198, 211, 186, 101, 228, 22, 101, 190];
+
+// A buyer's open coverage request, escrowing its max premium
+pub const COVERAGE_REQUEST_LEN: usize = 91;
+pub const COVERAGE_REQUEST_DISCRIMINATOR: [u8; 8] = [162, 138, 2, 221, 225, 107, 168, 204];
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+
+pub mod cluster;
+pub mod layout;
+pub mod payout;
+pub mod price;
+pub mod seeds;
+
+// Pyth V2 price feed IDs (same across all chains), decoded when the
+// program is compiled
+pub const BTC_USD_FEED_ID: [u8; 32] =
+    feed_id_from_hex("e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43");
+pub const ETH_USD_FEED_ID: [u8; 32] =
+    feed_id_from_hex("ff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace");
+pub const SOL_USD_FEED_ID: [u8; 32] =
+    feed_id_from_hex("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d");
+
+// Decode a lowercase hex feed id. Only called in consts, so a malformed
+// id fails the build instead of a liquidation
+pub const fn feed_id_from_hex(hex: &str) -> [u8; 32] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => panic!("feed id must be lowercase hex"),
+        }
+    }
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 64, "feed id must be 32 bytes");
+    let mut id = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        id[i] = (nibble(hex[2 * i]) << 4) | nibble(hex[2 * i + 1]);
+        i += 1;
+    }
+    id
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum UnderlyingAsset {
+    Btc,
+    Eth,
+    Sol,
+}
+
+impl UnderlyingAsset {
+    // FeedRegistry PDA seed
+    pub fn seed(&self) -> [u8; 1] {
+        [*self as u8]
+    }
+
+    // Lowercase ticker, as the CLI, indexer and API spell the asset
+    pub const fn name(&self) -> &'static str {
+        match self {
+            UnderlyingAsset::Btc => "btc",
+            UnderlyingAsset::Eth => "eth",
+            UnderlyingAsset::Sol => "sol",
+        }
+    }
+
+    // Pyth V2 price feed id, for seeding the FeedRegistry; settlement
+    // reads the feed id from the registry
+    pub const fn feed_id(&self) -> [u8; 32] {
+        match self {
+            UnderlyingAsset::Btc => BTC_USD_FEED_ID,
+            UnderlyingAsset::Eth => ETH_USD_FEED_ID,
+            UnderlyingAsset::Sol => SOL_USD_FEED_ID,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum PayoutMode {
+    Binary,       // all-or-nothing: full coverage once triggered
+    Proportional, // scales linearly with how far price breached the strike
+    CappedLinear, // Proportional, clamped between a floor and a cap
+    Spread,       // long the strike, short a second strike past it: pays the net of both legs
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum BarrierKind {
+    None,
+    KnockIn,  // claimable only once the barrier has been touched
+    KnockOut, // void once the barrier is touched before a claim
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ExerciseStyle {
+    American, // pays as soon as price touches the strike
+    European, // pays only on the price snapshotted at expiry
+}
+
+// How a spot read's confidence interval moves the price before the
+// strike comparison
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ConfidenceAdjustment {
+    None,
+    Conservative, // away from the trigger: breached even at the far edge of the interval
+    Lenient,      // toward the trigger: breached anywhere inside the interval
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum TriggerDirection {
+    ProtectLong,  // pays once price drops below strike_price
+    ProtectShort, // pays once price rises above strike_price
+    OutsideRange, // pays once price leaves [strike_price, upper_strike_price]
+}
+
+// Where a feed's prices come from
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum OracleSource {
+    PythPull,    // PriceUpdateV2 posted by the Pyth receiver, keyed by feed id
+    Switchboard, // Switchboard On-Demand pull feed account
+    Chainlink,   // Chainlink OCR2 store feed account
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feed_ids_decode_from_hex() {
+        assert_eq!(BTC_USD_FEED_ID[..4], [0xe6, 0x2d, 0xf6, 0xc8]);
+        assert_eq!(SOL_USD_FEED_ID[31], 0x6d);
+        assert_eq!(UnderlyingAsset::Eth.feed_id(), ETH_USD_FEED_ID);
+    }
+
+    #[test]
+    fn asset_seeds_are_distinct() {
+        assert_eq!(UnderlyingAsset::Btc.seed(), [0]);
+        assert_eq!(UnderlyingAsset::Eth.seed(), [1]);
+        assert_eq!(UnderlyingAsset::Sol.seed(), [2]);
+    }
+}
@@ -0,0 +1,447 @@
+// Payout curves and the bps splits around them (fees, bounties,
+// holdbacks, recipient shares), shared by every settlement path. Amounts are in the
+// policy's collateral units; strike and price must already share one
+// exponent. Intermediate math is u128 throughout and everything rounds
+// down; each returns None rather than truncating a result that doesn't
+// fit, for a non-positive strike or for inverted bounds
+
+use crate::PayoutMode;
+
+const BPS: u128 = 10_000;
+
+// Largest strike (after rescaling) kept at full precision: strike * BPS
+// then fits in a u64, so coverage * breach can't overflow a u128
+const MAX_EXACT_STRIKE: u128 = u64::MAX as u128 / BPS;
+
+// coverage * slope * |strike - price| / strike, capped at coverage
+pub fn linear(coverage: u64, strike: i128, price: i128, slope_bps: u32) -> Option<u64> {
+    if strike <= 0 {
+        return None;
+    }
+    let mut strike = strike as u128;
+    let mut breach = strike.abs_diff(price.max(0) as u128);
+    // Prices aligned onto a fine exponent can carry far more digits than
+    // the payout needs; drop the excess from both sides of the ratio
+    while strike > MAX_EXACT_STRIKE {
+        strike /= 10;
+        breach /= 10;
+    }
+    // Cap the slope-weighted breach at 100% before scaling by coverage
+    let full_breach = strike * BPS;
+    let weighted_breach = breach.saturating_mul(slope_bps as u128).min(full_breach);
+    let payout = (coverage as u128) * weighted_breach / full_breach;
+    Some(payout as u64)
+}
+
+// linear, clamped between floor_bps and cap_bps of coverage
+pub fn capped_linear(
+    coverage: u64,
+    strike: i128,
+    price: i128,
+    slope_bps: u32,
+    floor_bps: u16,
+    cap_bps: u16,
+) -> Option<u64> {
+    let floor = share_of(coverage, floor_bps)?;
+    let cap = share_of(coverage, cap_bps)?;
+    if floor > cap {
+        return None;
+    }
+    Some(linear(coverage, strike, price, slope_bps)?.clamp(floor, cap))
+}
+
+// Net payout of a spread long protection at long_strike and short it at
+// short_strike: coverage * depth / width, where width is the distance
+// between the strikes and depth how far price has gone from the long
+// strike toward the short one, clamped to [0, width]. The short strike
+// can sit on either side, so it serves put and call spreads alike
+pub fn spread(coverage: u64, long_strike: i128, short_strike: i128, price: i128) -> Option<u64> {
+    let width = long_strike.checked_sub(short_strike)?;
+    let depth = long_strike.checked_sub(price)?;
+    let depth = if width > 0 {
+        depth
+    } else {
+        depth.checked_neg()?
+    };
+    let mut width = width.unsigned_abs();
+    if width == 0 {
+        return None;
+    }
+    let mut depth = (depth.max(0) as u128).min(width);
+    // Keep coverage * depth inside a u128
+    while width > u64::MAX as u128 {
+        width /= 10;
+        depth /= 10;
+    }
+    Some(((coverage as u128) * depth / width) as u64)
+}
+
+// bps of amount. Over 10_000 bps it can exceed amount, so it's checked
+// back into a u64
+pub fn share_of(amount: u64, bps: u16) -> Option<u64> {
+    u64::try_from(amount as u128 * bps as u128 / BPS).ok()
+}
+
+// amount moved bps up or down, e.g. a strike pushed out by a buffer
+pub fn shifted(amount: u64, bps: u16, up: bool) -> Option<u64> {
+    let factor = if up {
+        BPS + bps as u128
+    } else {
+        BPS.checked_sub(bps as u128)?
+    };
+    u64::try_from(amount as u128 * factor / BPS).ok()
+}
+
+// Strikes drawdown_bps below and above an entry price
+pub fn drawdown_strikes(entry_price: u64, drawdown_bps: u16) -> Option<(u64, u64)> {
+    Some((
+        shifted(entry_price, drawdown_bps, false)?,
+        shifted(entry_price, drawdown_bps, true)?,
+    ))
+}
+
+// A premium split into (underwriter, protocol fee, backstop fee). None if
+// the fees add up to more than the premium
+pub fn split_premium(
+    premium: u64,
+    protocol_fee_bps: u16,
+    backstop_fee_bps: u16,
+) -> Option<(u64, u64, u64)> {
+    let protocol_fee = share_of(premium, protocol_fee_bps)?;
+    let backstop_fee = share_of(premium, backstop_fee_bps)?;
+    let underwriter = premium
+        .checked_sub(protocol_fee)?
+        .checked_sub(backstop_fee)?;
+    Some((underwriter, protocol_fee, backstop_fee))
+}
+
+// flat + bps of payout, never more than the payout itself
+pub fn liquidator_bounty(payout: u64, bounty_bps: u16, flat: u64) -> Option<u64> {
+    Some(
+        share_of(payout, bounty_bps)?
+            .saturating_add(flat)
+            .min(payout),
+    )
+}
+
+// amount divided across shares_bps, which must add up to 10_000. Each
+// share rounds down and the last takes the remainder, so the parts always
+// add back up to amount
+pub fn split(amount: u64, shares_bps: &[u16]) -> Option<Vec<u64>> {
+    if shares_bps.iter().map(|&bps| bps as u128).sum::<u128>() != BPS {
+        return None;
+    }
+    let mut parts: Vec<u64> = shares_bps
+        .iter()
+        .map(|&bps| share_of(amount, bps))
+        .collect::<Option<_>>()?;
+    let paid: u64 = parts.iter().sum();
+    *parts.last_mut()? += amount - paid;
+    Some(parts)
+}
+
+// Most a policy can pay over its life, however its claims are staged:
+// full coverage, or the cap's share of it for CappedLinear
+pub fn max_payout(payout_mode: PayoutMode, coverage_amount: u64, payout_cap_bps: u16) -> u64 {
+    match payout_mode {
+        PayoutMode::CappedLinear => {
+            share_of(coverage_amount, payout_cap_bps.min(BPS as u16)).unwrap_or(coverage_amount)
+        }
+        _ => coverage_amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const COVERAGE: u64 = 1_000_000_000;
+    const STRIKE: i128 = 100_000;
+
+    #[test]
+    fn linear_scales_with_breach_depth() {
+        // 10% breach at 1x slope pays 10%
+        assert_eq!(linear(COVERAGE, STRIKE, 90_000, 10_000), Some(100_000_000));
+        // Same breach at 2.5x slope pays 25%
+        assert_eq!(linear(COVERAGE, STRIKE, 90_000, 25_000), Some(250_000_000));
+        // Breach direction doesn't matter, only depth
+        assert_eq!(linear(COVERAGE, STRIKE, 110_000, 10_000), Some(100_000_000));
+    }
+
+    #[test]
+    fn linear_caps_at_coverage() {
+        assert_eq!(linear(COVERAGE, STRIKE, 10_000, 50_000), Some(COVERAGE));
+        assert_eq!(linear(COVERAGE, STRIKE, 400_000, 10_000), Some(COVERAGE));
+    }
+
+    #[test]
+    fn linear_clamps_negative_prices_to_zero() {
+        assert_eq!(linear(COVERAGE, STRIKE, -5, 10_000), Some(COVERAGE));
+        assert_eq!(linear(COVERAGE, STRIKE, 0, 5_000), Some(COVERAGE / 2));
+    }
+
+    #[test]
+    fn linear_pays_nothing_at_the_strike_or_with_no_slope() {
+        assert_eq!(linear(COVERAGE, STRIKE, STRIKE, 10_000), Some(0));
+        assert_eq!(linear(COVERAGE, STRIKE, 50_000, 0), Some(0));
+        assert_eq!(linear(0, STRIKE, 50_000, 10_000), Some(0));
+    }
+
+    #[test]
+    fn linear_rejects_non_positive_strikes() {
+        assert_eq!(linear(COVERAGE, 0, 50_000, 10_000), None);
+        assert_eq!(linear(COVERAGE, -1, 50_000, 10_000), None);
+    }
+
+    #[test]
+    fn linear_rounds_down() {
+        // 1/3 of coverage truncates
+        assert_eq!(linear(10, 3, 2, 10_000), Some(3));
+    }
+
+    #[test]
+    fn linear_handles_extreme_inputs() {
+        assert_eq!(linear(u64::MAX, STRIKE, 0, 10_000), Some(u64::MAX));
+        assert_eq!(linear(COVERAGE, i128::MAX, 0, 10_000), Some(COVERAGE));
+        assert_eq!(linear(u64::MAX, 1 << 60, 0, u32::MAX), Some(u64::MAX));
+        assert_eq!(
+            linear(u64::MAX, i128::MAX, i128::MAX / 2, 10_000),
+            Some(u64::MAX / 2)
+        );
+    }
+
+    #[test]
+    fn linear_is_exact_for_large_coverage() {
+        // $95,000 strike at expo -8 against a $90,250 price: a 5% breach
+        let strike = 9_500_000_000_000;
+        let price = 9_025_000_000_000;
+        assert_eq!(
+            linear(u64::MAX / 2, strike, price, 10_000),
+            Some(u64::MAX / 2 / 20)
+        );
+    }
+
+    #[test]
+    fn linear_keeps_precision_on_fine_exponents() {
+        // Same 5% breach with both prices aligned to an expo -18 feed
+        let strike = 9_500_000_000_000 * 10i128.pow(10);
+        let price = 9_025_000_000_000 * 10i128.pow(10);
+        assert_eq!(linear(COVERAGE, strike, price, 10_000), Some(COVERAGE / 20));
+        assert_eq!(
+            linear(u64::MAX, strike, price, 10_000),
+            linear(u64::MAX, 9_500_000_000_000, 9_025_000_000_000, 10_000)
+        );
+    }
+
+    #[test]
+    fn capped_linear_applies_the_floor() {
+        // 1% breach would pay 1%, the floor lifts it to 20%
+        assert_eq!(
+            capped_linear(COVERAGE, STRIKE, 99_000, 10_000, 2_000, 8_000),
+            Some(200_000_000)
+        );
+    }
+
+    #[test]
+    fn capped_linear_applies_the_cap() {
+        // 90% breach would pay 90%, the cap holds it to 80%
+        assert_eq!(
+            capped_linear(COVERAGE, STRIKE, 10_000, 10_000, 2_000, 8_000),
+            Some(800_000_000)
+        );
+    }
+
+    #[test]
+    fn capped_linear_is_linear_between_floor_and_cap() {
+        assert_eq!(
+            capped_linear(COVERAGE, STRIKE, 50_000, 10_000, 2_000, 8_000),
+            linear(COVERAGE, STRIKE, 50_000, 10_000)
+        );
+    }
+
+    #[test]
+    fn capped_linear_with_equal_floor_and_cap_is_flat() {
+        for price in [0, 50_000, 99_999] {
+            assert_eq!(
+                capped_linear(COVERAGE, STRIKE, price, 10_000, 5_000, 5_000),
+                Some(500_000_000)
+            );
+        }
+    }
+
+    #[test]
+    fn capped_linear_full_range_matches_linear() {
+        for price in [-1, 0, 1, 25_000, 99_999, 100_000, 150_000, 250_000] {
+            assert_eq!(
+                capped_linear(COVERAGE, STRIKE, price, 15_000, 0, 10_000),
+                linear(COVERAGE, STRIKE, price, 15_000)
+            );
+        }
+    }
+
+    #[test]
+    fn capped_linear_rejects_inverted_bounds() {
+        assert_eq!(
+            capped_linear(COVERAGE, STRIKE, 50_000, 10_000, 8_000, 2_000),
+            None
+        );
+    }
+
+    #[test]
+    fn capped_linear_rejects_non_positive_strikes() {
+        assert_eq!(capped_linear(COVERAGE, 0, 50_000, 10_000, 0, 10_000), None);
+    }
+
+    #[test]
+    fn spreads_pay_between_their_strikes() {
+        // Put spread long at 100k, short at 80k: nothing above the long
+        // strike, linear down to the short one, flat past it
+        assert_eq!(spread(COVERAGE, STRIKE, 80_000, 110_000), Some(0));
+        assert_eq!(spread(COVERAGE, STRIKE, 80_000, STRIKE), Some(0));
+        assert_eq!(spread(COVERAGE, STRIKE, 80_000, 95_000), Some(COVERAGE / 4));
+        assert_eq!(spread(COVERAGE, STRIKE, 80_000, 80_000), Some(COVERAGE));
+        assert_eq!(spread(COVERAGE, STRIKE, 80_000, -5), Some(COVERAGE));
+        // Call spread long at 100k, short at 120k
+        assert_eq!(spread(COVERAGE, STRIKE, 120_000, 90_000), Some(0));
+        assert_eq!(
+            spread(COVERAGE, STRIKE, 120_000, 110_000),
+            Some(COVERAGE / 2)
+        );
+        assert_eq!(spread(COVERAGE, STRIKE, 120_000, 150_000), Some(COVERAGE));
+        assert_eq!(spread(COVERAGE, STRIKE, STRIKE, 50_000), None);
+    }
+
+    #[test]
+    fn splits_round_down_and_reject_overdrawn_fees() {
+        assert_eq!(split_premium(1_001, 300, 200), Some((951, 30, 20)));
+        assert_eq!(split_premium(1_000, 6_000, 5_000), None);
+        assert_eq!(liquidator_bounty(1_000, 100, 5_000), Some(1_000));
+        assert_eq!(share_of(u64::MAX, u16::MAX), None);
+        assert_eq!(shifted(100, 10_001, false), None);
+        assert_eq!(drawdown_strikes(10_000, 1_500), Some((8_500, 11_500)));
+        assert_eq!(max_payout(PayoutMode::CappedLinear, 1_000, 8_000), 800);
+    }
+
+    #[test]
+    fn payout_splits_give_the_remainder_to_the_last_share() {
+        assert_eq!(split(1_001, &[5_000, 5_000]), Some(vec![500, 501]));
+        assert_eq!(split(100, &[3_333, 3_333, 3_334]), Some(vec![33, 33, 34]));
+        assert_eq!(split(100, &[10_000]), Some(vec![100]));
+        assert_eq!(split(100, &[5_000, 4_999]), None);
+        assert_eq!(split(100, &[]), None);
+    }
+
+    proptest! {
+        #[test]
+        fn linear_never_overflows_or_exceeds_coverage(
+            coverage: u64,
+            strike: i128,
+            price: i128,
+            slope_bps: u32,
+        ) {
+            if let Some(payout) = linear(coverage, strike, price, slope_bps) {
+                prop_assert!(payout <= coverage);
+            } else {
+                prop_assert!(strike <= 0);
+            }
+        }
+
+        #[test]
+        fn linear_grows_with_breach_depth(
+            coverage: u64,
+            strike in 1i128..=i64::MAX as i128,
+            near in 0.0f64..=1.0,
+            far in 0.0f64..=1.0,
+            slope_bps: u32,
+        ) {
+            // Two prices below the strike, `deep` at least as far from it
+            let (near, far) = (near.max(far), near.min(far));
+            let shallow = (strike as f64 * near) as i128;
+            let deep = (strike as f64 * far) as i128;
+            prop_assert!(
+                linear(coverage, strike, deep, slope_bps) >= linear(coverage, strike, shallow, slope_bps)
+            );
+        }
+
+        #[test]
+        fn linear_ignores_the_exponent_prices_share(
+            coverage: u64,
+            strike in 1i128..=u64::MAX as i128,
+            price in 0i128..=u64::MAX as i128,
+            slope_bps: u32,
+            shift in 0u32..=18,
+        ) {
+            let scale = 10i128.pow(shift);
+            prop_assert_eq!(
+                linear(coverage, strike * scale, price * scale, slope_bps),
+                linear(coverage, strike, price, slope_bps)
+            );
+        }
+
+        #[test]
+        fn capped_linear_stays_between_floor_and_cap(
+            coverage: u64,
+            strike in 1i128..=i64::MAX as i128,
+            price: i64,
+            slope_bps: u32,
+            floor_bps in 0u16..=10_000,
+            cap_bps in 0u16..=10_000,
+        ) {
+            let payout = capped_linear(coverage, strike, price as i128, slope_bps, floor_bps, cap_bps);
+            if floor_bps > cap_bps {
+                prop_assert_eq!(payout, None);
+            } else {
+                let payout = payout.unwrap();
+                prop_assert!(payout >= share_of(coverage, floor_bps).unwrap());
+                prop_assert!(payout <= share_of(coverage, cap_bps).unwrap());
+            }
+        }
+
+        #[test]
+        fn spreads_never_exceed_coverage(
+            coverage: u64,
+            long_strike: i64,
+            short_strike: i64,
+            price: i64,
+        ) {
+            let payout = spread(coverage, long_strike as i128, short_strike as i128, price as i128);
+            if long_strike == short_strike {
+                prop_assert_eq!(payout, None);
+            } else {
+                prop_assert!(payout.unwrap() <= coverage);
+            }
+        }
+
+        #[test]
+        fn premium_splits_add_back_up(
+            premium: u64,
+            protocol_fee_bps in 0u16..=10_000,
+            backstop_fee_bps in 0u16..=10_000,
+        ) {
+            let split = split_premium(premium, protocol_fee_bps, backstop_fee_bps);
+            if protocol_fee_bps as u32 + backstop_fee_bps as u32 <= 10_000 {
+                let (underwriter, protocol_fee, backstop_fee) = split.unwrap();
+                prop_assert_eq!(underwriter as u128 + protocol_fee as u128 + backstop_fee as u128, premium as u128);
+            }
+        }
+
+        #[test]
+        fn bounties_and_shares_stay_within_the_amount(
+            amount: u64,
+            bps in 0u16..=10_000,
+            flat: u64,
+        ) {
+            prop_assert!(share_of(amount, bps).unwrap() <= amount);
+            prop_assert!(liquidator_bounty(amount, bps, flat).unwrap() <= amount);
+            let (lower, upper) = drawdown_strikes(amount >> 1, bps).unwrap();
+            prop_assert!(lower <= amount >> 1 && upper >= amount >> 1);
+        }
+
+        #[test]
+        fn payout_splits_add_back_up(amount: u64, first in 0u16..=10_000) {
+            let parts = split(amount, &[first, 10_000 - first]).unwrap();
+            prop_assert_eq!(parts[0] as u128 + parts[1] as u128, amount as u128);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+// Fixed-point oracle prices, the exponent normalization every
+// settlement comparison goes through and the conversions between USD,
+// lamports and USDC. Each returns None on overflow
+
+// Policy.strike_price is fixed-point USD at this exponent, e.g.
+// 95_000.50 is 9_500_050_000_000 and 0.000021 is 2_100
+pub const STRIKE_EXPONENT: i32 = -8;
+
+// USD coverage is converted to lamports at this many per SOL
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+// Decimals of the USDC mint swapped payouts are delivered in
+pub const USDC_DECIMALS: u32 = 6;
+
+// An oracle price as mantissa * 10^exponent, kept in the feed's own
+// exponent so settlement never truncates it to whole dollars
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub mantissa: i128,
+    pub exponent: i32,
+}
+
+impl OraclePrice {
+    pub fn new(mantissa: impl Into<i128>, exponent: i32) -> Self {
+        Self {
+            mantissa: mantissa.into(),
+            exponent,
+        }
+    }
+
+    // Mantissa expressed at a finer (smaller or equal) exponent
+    pub fn rescale(&self, exponent: i32) -> Option<i128> {
+        let shift = u32::try_from(self.exponent.checked_sub(exponent)?).ok()?;
+        10i128
+            .checked_pow(shift)
+            .and_then(|factor| self.mantissa.checked_mul(factor))
+    }
+}
+
+// Bring two prices onto the finer of their exponents so they can be
+// compared without truncating either side. Mantissas only ever get scaled
+// up, in i128, so a 0.000021 strike against an expo -8 feed stays exact
+pub fn align(a: OraclePrice, b: OraclePrice) -> Option<(i128, i128)> {
+    let exponent = a.exponent.min(b.exponent);
+    Some((a.rescale(exponent)?, b.rescale(exponent)?))
+}
+
+// A price truncated onto STRIKE_EXPONENT, for strikes derived on-chain.
+// None for negative prices
+pub fn to_strike_units(price: OraclePrice) -> Option<u64> {
+    let mantissa = if price.exponent >= STRIKE_EXPONENT {
+        price.rescale(STRIKE_EXPONENT)?
+    } else {
+        let shift = u32::try_from(STRIKE_EXPONENT - price.exponent).ok()?;
+        price.mantissa / 10i128.checked_pow(shift)?
+    };
+    u64::try_from(mantissa).ok()
+}
+
+// Lamports worth `usd` (at STRIKE_EXPONENT) at the given SOL/USD price,
+// rounded down. None for a non-positive price
+pub fn usd_to_lamports(usd: u64, sol_price: OraclePrice) -> Option<u64> {
+    let (usd, sol_price) = align(OraclePrice::new(usd, STRIKE_EXPONENT), sol_price)?;
+    if sol_price <= 0 {
+        return None;
+    }
+    u64::try_from(usd.checked_mul(LAMPORTS_PER_SOL as i128)? / sol_price).ok()
+}
+
+// USDC base units worth `lamports` at the given SOL/USD price, rounded
+// down. None for a non-positive price
+pub fn lamports_to_usdc(lamports: u64, sol_price: OraclePrice) -> Option<u64> {
+    if sol_price.mantissa <= 0 {
+        return None;
+    }
+    let value = (lamports as i128).checked_mul(sol_price.mantissa)? / LAMPORTS_PER_SOL as i128;
+    let usd = to_strike_units(OraclePrice::new(value, sol_price.exponent))?;
+    Some(usd / 10u64.pow((-STRIKE_EXPONENT) as u32 - USDC_DECIMALS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn rescale_only_scales_up() {
+        assert_eq!(
+            OraclePrice::new(95_000i64, 0).rescale(-8),
+            Some(9_500_000_000_000)
+        );
+        assert_eq!(OraclePrice::new(7i64, -2).rescale(-2), Some(7));
+        assert_eq!(OraclePrice::new(1i64, -8).rescale(-7), None);
+    }
+
+    #[test]
+    fn rescale_rejects_overflow() {
+        assert_eq!(OraclePrice::new(1i64, 30).rescale(-10), None);
+        assert_eq!(OraclePrice::new(i64::MAX, 20).rescale(0), None);
+    }
+
+    #[test]
+    fn align_uses_the_finer_exponent() {
+        // A 0.000021 strike at expo -8 against a 0.0000205 print at expo -10
+        let strike = OraclePrice::new(2_100i64, -8);
+        let price = OraclePrice::new(205_000i64, -10);
+        assert_eq!(align(strike, price), Some((210_000, 205_000)));
+        assert_eq!(align(price, strike), Some((205_000, 210_000)));
+    }
+
+    #[test]
+    fn conversions_round_down() {
+        let sol_price = OraclePrice::new(15_000_000_000i64, -8);
+        assert_eq!(
+            usd_to_lamports(150_000_000_000, sol_price),
+            Some(10 * LAMPORTS_PER_SOL)
+        );
+        assert_eq!(usd_to_lamports(1, OraclePrice::new(0i64, -8)), None);
+        assert_eq!(
+            lamports_to_usdc(2 * LAMPORTS_PER_SOL, sol_price),
+            Some(300_000_000)
+        );
+        assert_eq!(
+            to_strike_units(OraclePrice::new(95_000_000_000_012_345i64, -12)),
+            Some(9_500_000_000_001)
+        );
+        assert_eq!(to_strike_units(OraclePrice::new(-1i64, -8)), None);
+    }
+
+    proptest! {
+        #[test]
+        fn align_is_exact_and_symmetric(
+            a: i64,
+            a_exponent in -18i32..=18,
+            b: i64,
+            b_exponent in -18i32..=18,
+        ) {
+            let (a, b) = (OraclePrice::new(a, a_exponent), OraclePrice::new(b, b_exponent));
+            let aligned = align(a, b);
+            prop_assert_eq!(aligned.map(|(a, b)| (b, a)), align(b, a));
+            if let Some((a_mantissa, b_mantissa)) = aligned {
+                // Scaled up only, so the coarser side divides back exactly
+                for (mantissa, price) in [(a_mantissa, a), (b_mantissa, b)] {
+                    let shift = price.exponent - a.exponent.min(b.exponent);
+                    prop_assert_eq!(mantissa / 10i128.pow(shift as u32), price.mantissa);
+                }
+            }
+        }
+
+        #[test]
+        fn conversions_ignore_the_price_exponent(
+            // Bounded so the finer mantissa can't be what overflows
+            amount in 0u64..=1 << 56,
+            price in 1i64..=1 << 48,
+            exponent in -12i32..=0,
+            shift in 0u32..=6,
+        ) {
+            let price = OraclePrice::new(price, exponent);
+            let finer = OraclePrice::new(price.mantissa * 10i128.pow(shift), exponent - shift as i32);
+            prop_assert_eq!(usd_to_lamports(amount, price), usd_to_lamports(amount, finer));
+            prop_assert_eq!(lamports_to_usdc(amount, price), lamports_to_usdc(amount, finer));
+            prop_assert_eq!(to_strike_units(price), to_strike_units(finer));
+        }
+
+        #[test]
+        fn usd_round_trips_through_lamports_without_gaining(
+            usd in 0u64..=1 << 48,
+            price in 1_000_000i64..=1 << 48,
+        ) {
+            let sol_price = OraclePrice::new(price, STRIKE_EXPONENT);
+            let lamports = usd_to_lamports(usd, sol_price).unwrap();
+            let back = lamports_to_usdc(lamports, sol_price).unwrap();
+            prop_assert!(back as u128 * 100 <= usd as u128);
+        }
+    }
+}
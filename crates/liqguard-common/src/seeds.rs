@@ -0,0 +1,158 @@
+// PDA derivations for LiqGuard's accounts, so clients and tools find the
+// same addresses the program's seeds constraints check. Each returns the
+// address and its bump
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::UnderlyingAsset;
+
+// Leading seed of each PDA, as the program's seeds constraints use them
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const PENDING_CONFIG_SEED: &[u8] = b"pending_config";
+pub const POLICY_SEED: &[u8] = b"policy";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const TOKEN_VAULT_SEED: &[u8] = b"token_vault";
+pub const OWNER_STATE_SEED: &[u8] = b"owner";
+pub const FEED_REGISTRY_SEED: &[u8] = b"feed";
+pub const KEEPER_QUEUE_SEED: &[u8] = b"keeper_queue";
+pub const LAZER_PRICE_SEED: &[u8] = b"lazer_price";
+pub const CLAIM_STATE_SEED: &[u8] = b"claim";
+pub const CLAIM_HISTORY_SEED: &[u8] = b"claim_history";
+pub const UNDERWRITER_SEED: &[u8] = b"underwriter";
+pub const PORTFOLIO_VAULT_SEED: &[u8] = b"portfolio";
+pub const BACKSTOP_SEED: &[u8] = b"backstop";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const AUDIT_LOG_SEED: &[u8] = b"audit_log";
+pub const BOOK_VAULT_SEED: &[u8] = b"book_vault";
+pub const COMPRESSED_TREE_SEED: &[u8] = b"compressed_tree";
+pub const DISPUTE_SEED: &[u8] = b"dispute";
+pub const SWAP_SOURCE_SEED: &[u8] = b"swap_source";
+pub const WORMHOLE_PAYMENT_SEED: &[u8] = b"wormhole_payment";
+pub const MASTER_AGREEMENT_SEED: &[u8] = b"master_agreement";
+pub const PRICE_CACHE_SEED: &[u8] = b"price_cache";
+pub const RENEWAL_SOURCE_SEED: &[u8] = b"renewal_source";
+pub const COVERAGE_REQUEST_SEED: &[u8] = b"coverage_request";
+
+pub fn config(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+pub fn policy(program_id: &Pubkey, creator: &Pubkey, policy_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[POLICY_SEED, creator.as_ref(), &policy_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn vault(program_id: &Pubkey, creator: &Pubkey, policy_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_SEED, creator.as_ref(), &policy_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn token_vault(program_id: &Pubkey, creator: &Pubkey, policy_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TOKEN_VAULT_SEED, creator.as_ref(), &policy_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn owner_state(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OWNER_STATE_SEED, owner.as_ref()], program_id)
+}
+
+pub fn feed_registry(program_id: &Pubkey, asset: UnderlyingAsset) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEED_REGISTRY_SEED, asset.seed().as_ref()], program_id)
+}
+
+pub fn keeper_queue(program_id: &Pubkey, asset: UnderlyingAsset) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[KEEPER_QUEUE_SEED, asset.seed().as_ref()], program_id)
+}
+
+pub fn lazer_price(program_id: &Pubkey, asset: UnderlyingAsset) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LAZER_PRICE_SEED, asset.seed().as_ref()], program_id)
+}
+
+pub fn price_cache(program_id: &Pubkey, asset: UnderlyingAsset) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PRICE_CACHE_SEED, asset.seed().as_ref()], program_id)
+}
+
+pub fn renewal_source(program_id: &Pubkey, policy: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RENEWAL_SOURCE_SEED, policy.as_ref()], program_id)
+}
+
+pub fn coverage_request(program_id: &Pubkey, owner: &Pubkey, request_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            COVERAGE_REQUEST_SEED,
+            owner.as_ref(),
+            &request_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+pub fn claim_state(program_id: &Pubkey, policy: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_STATE_SEED, policy.as_ref()], program_id)
+}
+
+pub fn claim_history(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_HISTORY_SEED, owner.as_ref()], program_id)
+}
+
+pub fn underwriter(program_id: &Pubkey, underwriter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UNDERWRITER_SEED, underwriter.as_ref()], program_id)
+}
+
+pub fn portfolio_vault(program_id: &Pubkey, underwriter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PORTFOLIO_VAULT_SEED, underwriter.as_ref()], program_id)
+}
+
+pub fn backstop(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BACKSTOP_SEED], program_id)
+}
+
+pub fn treasury(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED], program_id)
+}
+
+pub fn audit_log(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUDIT_LOG_SEED], program_id)
+}
+
+pub fn master_agreement(
+    program_id: &Pubkey,
+    counterparty: &Pubkey,
+    agreement_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            MASTER_AGREEMENT_SEED,
+            counterparty.as_ref(),
+            &agreement_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_and_vault_addresses_differ_per_policy_id() {
+        let program_id = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let (policy_0, bump) = policy(&program_id, &creator, 0);
+        assert_ne!(policy_0, policy(&program_id, &creator, 1).0);
+        assert_ne!(policy_0, vault(&program_id, &creator, 0).0);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[POLICY_SEED, creator.as_ref(), &0u64.to_le_bytes(), &[bump]],
+                &program_id,
+            ),
+            Ok(policy_0)
+        );
+    }
+}
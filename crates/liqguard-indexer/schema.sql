@@ -0,0 +1,71 @@
+-- LiqGuard's indexed state. The indexer applies this at startup; every
+-- statement is idempotent. Amounts are lamports (or the collateral's base
+-- units), prices are mantissas at their exponent, times are unix seconds.
+-- Rows written from confirmed data carry their slot and are corrected once
+-- that slot is finalized or turns out to be on a dropped fork
+
+CREATE TABLE IF NOT EXISTS policies (
+    address             TEXT PRIMARY KEY,
+    owner               TEXT NOT NULL,
+    creator             TEXT NOT NULL,
+    beneficiary         TEXT NOT NULL,
+    underwriter         TEXT NOT NULL,
+    asset               TEXT NOT NULL,     -- btc, eth or sol
+    policy_id           BIGINT NOT NULL,
+    status              TEXT NOT NULL,     -- unfunded, active, claimed or terminal, as of updated_at
+    coverage_amount     BIGINT NOT NULL,
+    coverage_remaining  BIGINT NOT NULL,
+    is_funded           BOOLEAN NOT NULL,
+    expiration_datetime BIGINT NOT NULL,
+    settlement_slot     BIGINT NOT NULL,   -- 0 = never paid out
+    data                BYTEA NOT NULL,    -- the account as the program serializes it
+    slot                BIGINT NOT NULL,   -- slot the data was read at
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS policies_owner ON policies (owner);
+CREATE INDEX IF NOT EXISTS policies_beneficiary ON policies (beneficiary);
+CREATE INDEX IF NOT EXISTS policies_underwriter ON policies (underwriter);
+CREATE INDEX IF NOT EXISTS policies_asset_status ON policies (asset, status);
+
+-- Every event liqguard emitted in a successful transaction
+CREATE TABLE IF NOT EXISTS events (
+    signature   TEXT NOT NULL,
+    event_index INTEGER NOT NULL,      -- position among the transaction's events
+    slot        BIGINT NOT NULL,
+    name        TEXT NOT NULL,
+    policy      TEXT,                  -- the policy the event is about, when it names one
+    data        BYTEA NOT NULL,        -- discriminator-prefixed borsh, as logged
+    finalized   BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS events_policy ON events (policy, slot);
+CREATE INDEX IF NOT EXISTS events_unfinalized ON events (slot) WHERE NOT finalized;
+
+-- Payouts, one per LiquidationExecuted event
+CREATE TABLE IF NOT EXISTS claims (
+    signature          TEXT NOT NULL,
+    event_index        INTEGER NOT NULL,
+    slot               BIGINT NOT NULL,
+    policy             TEXT NOT NULL,
+    liquidator         TEXT NOT NULL,
+    asset              TEXT NOT NULL,
+    price              BIGINT NOT NULL,
+    price_exponent     INTEGER NOT NULL,
+    payout             BIGINT NOT NULL,
+    bounty             BIGINT NOT NULL,
+    coverage_remaining BIGINT NOT NULL,
+    finalized          BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS claims_policy ON claims (policy, slot);
+
+CREATE OR REPLACE VIEW stats AS
+SELECT
+    asset,
+    count(*) AS policies,
+    count(*) FILTER (WHERE status = 'active') AS active_policies,
+    coalesce(sum(coverage_remaining) FILTER (WHERE status = 'active'), 0)::BIGINT AS active_coverage,
+    (SELECT count(*) FROM claims WHERE claims.asset = policies.asset) AS claims,
+    (SELECT coalesce(sum(payout), 0) FROM claims WHERE claims.asset = policies.asset)::BIGINT AS total_paid
+FROM policies
+GROUP BY asset;
@@ -0,0 +1,88 @@
+// The indexer's config file (TOML): endpoints, the database and how often
+// to reconcile with the finalized chain. See indexer.example.toml
+
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexerConfig {
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub database_url: String,
+    #[serde(default = "default_commitment")]
+    pub commitment: String,
+    #[serde(default = "default_finality_interval_seconds")]
+    pub finality_interval_seconds: u64,
+    #[serde(default = "default_resync_interval_seconds")]
+    pub resync_interval_seconds: u64,
+    #[serde(default = "default_backfill_limit")]
+    pub backfill_limit: usize,
+}
+
+fn default_commitment() -> String {
+    "confirmed".to_string()
+}
+
+fn default_finality_interval_seconds() -> u64 {
+    10
+}
+
+fn default_resync_interval_seconds() -> u64 {
+    300
+}
+
+fn default_backfill_limit() -> usize {
+    1_000
+}
+
+impl IndexerConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("reading {}: {error}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|error| error.to_string())?;
+        config.commitment()?;
+        if config.finality_interval_seconds == 0 || config.resync_interval_seconds == 0 {
+            return Err("finality and resync intervals must be at least a second".to_string());
+        }
+        Ok(config)
+    }
+
+    pub fn commitment(&self) -> Result<CommitmentConfig, String> {
+        CommitmentConfig::from_str(&self.commitment)
+            .map_err(|_| format!("unknown commitment {:?}", self.commitment))
+    }
+
+    pub fn finality_interval(&self) -> Duration {
+        Duration::from_secs(self.finality_interval_seconds)
+    }
+
+    pub fn resync_interval(&self) -> Duration {
+        Duration::from_secs(self.resync_interval_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_config_parses() {
+        let config = IndexerConfig::parse(include_str!("../indexer.example.toml")).unwrap();
+        assert_eq!(config.commitment().unwrap(), CommitmentConfig::confirmed());
+        assert_eq!(config.resync_interval(), Duration::from_secs(300));
+
+        let base = "rpc_url = \"a\"\nws_url = \"b\"\ndatabase_url = \"c\"\n";
+        assert!(IndexerConfig::parse(&format!("{base}commitment = \"soon\"")).is_err());
+        assert!(IndexerConfig::parse(&format!("{base}resync_interval_seconds = 0")).is_err());
+        assert_eq!(IndexerConfig::parse(base).unwrap().backfill_limit, 1_000);
+    }
+}
@@ -0,0 +1,240 @@
+// Keeps the database in step with the chain. Policy changes and events
+// stream in over the websocket at the configured commitment, and are
+// written with the slot they were seen at. Two timers then reconcile that
+// with the finalized chain:
+//
+// - finality: events at or below the finalized slot are looked up by
+//   signature; finalized ones are marked so, and ones the cluster no
+//   longer knows were on a dropped fork and are deleted
+// - resync: every policy is reloaded at finalized commitment, which
+//   repairs rows written from a dropped fork, drops closed policies and
+//   refreshes statuses that change with time alone
+//
+// Events missed while the socket was down are backfilled from the
+// program's signature history
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use liqguard_client::events::parse_logs;
+use liqguard_client::rpc::LiqGuardClient;
+use liqguard_client::subscribe::{self, SubscribeConfig, Update};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::config::IndexerConfig;
+use crate::store::{EventRow, PolicyRow, Store};
+
+// getSignatureStatuses takes at most this many signatures per call
+const MAX_SIGNATURE_STATUSES: usize = 256;
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+pub struct Indexer {
+    config: IndexerConfig,
+    commitment: CommitmentConfig,
+    // Reads at finalized commitment, for the reconciliation passes
+    finalized: LiqGuardClient,
+    store: Store,
+}
+
+impl Indexer {
+    pub async fn new(config: IndexerConfig) -> Result<Self, String> {
+        let commitment = config.commitment()?;
+        let store = Store::connect(&config.database_url).await?;
+        let finalized = LiqGuardClient::new(RpcClient::new_with_commitment(
+            config.rpc_url.clone(),
+            CommitmentConfig::finalized(),
+        ));
+        Ok(Self {
+            config,
+            commitment,
+            finalized,
+            store,
+        })
+    }
+
+    pub async fn run(self) -> Result<(), String> {
+        let subscribe_config = SubscribeConfig::new(&self.config.ws_url, self.commitment);
+        let mut policies = subscribe::policies(subscribe_config.clone(), Vec::new());
+        let mut events = subscribe::events(subscribe_config);
+        self.resync().await?;
+        self.backfill().await?;
+
+        let mut finality = tokio::time::interval(self.config.finality_interval());
+        let mut resync = tokio::time::interval(self.config.resync_interval());
+        resync.tick().await; // the first tick is immediate; resync just ran
+        loop {
+            tokio::select! {
+                update = policies.next() => match update {
+                    Some(Update::Value { slot, value }) => {
+                        let row = PolicyRow::new(&value.address, &value.policy, slot, unix_now());
+                        self.store.upsert_policy(&row).await?;
+                    }
+                    Some(Update::Reconnected) => self.resync().await?,
+                    None => return Err("policy subscription ended".to_string()),
+                },
+                update = events.next() => match update {
+                    Some(Update::Value { slot, value }) => {
+                        let row = EventRow::new(&value.signature, value.index, slot, &value.event);
+                        self.store.insert_event(&row).await?;
+                    }
+                    Some(Update::Reconnected) => self.backfill().await?,
+                    None => return Err("event subscription ended".to_string()),
+                },
+                _ = finality.tick() => {
+                    if let Err(error) = self.settle_finality().await {
+                        log::warn!("finality pass failed: {error}");
+                    }
+                }
+                _ = resync.tick() => {
+                    if let Err(error) = self.resync().await {
+                        log::warn!("resync failed: {error}");
+                    }
+                }
+            }
+        }
+    }
+
+    async fn finalized_slot(&self) -> Result<u64, String> {
+        self.finalized
+            .rpc
+            .get_slot()
+            .await
+            .map_err(|error| format!("reading the finalized slot: {error}"))
+    }
+
+    // Reload every policy as finalized. The slot is read first, so the
+    // snapshot is at least that new: rows read at or before it are
+    // replaced, or deleted when the snapshot no longer holds them
+    async fn resync(&self) -> Result<(), String> {
+        let slot = self.finalized_slot().await?;
+        let policies = self
+            .finalized
+            .policies(&[])
+            .await
+            .map_err(|error| format!("listing policies: {error}"))?;
+        let now = unix_now();
+        let mut kept = Vec::with_capacity(policies.len());
+        for (address, policy) in &policies {
+            let row = PolicyRow::new(address, policy, slot, now);
+            self.store.upsert_policy(&row).await?;
+            kept.push(row.address);
+        }
+        let closed = self.store.delete_policies_except(&kept, slot).await?;
+        log::info!(
+            "resynced {} policies at slot {slot}, {closed} closed",
+            policies.len()
+        );
+        Ok(())
+    }
+
+    // Settle every unfinalized event at or below the finalized slot
+    async fn settle_finality(&self) -> Result<(), String> {
+        let slot = self.finalized_slot().await?;
+        let pending = self.store.unfinalized_signatures(slot).await?;
+        let (mut finalized, mut orphaned) = (Vec::new(), Vec::new());
+        for chunk in pending.chunks(MAX_SIGNATURE_STATUSES) {
+            let signatures: Vec<Signature> = chunk
+                .iter()
+                .filter_map(|signature| signature.parse().ok())
+                .collect();
+            let statuses = self
+                .finalized
+                .rpc
+                .get_signature_statuses_with_history(&signatures)
+                .await
+                .map_err(|error| format!("reading signature statuses: {error}"))?
+                .value;
+            for (signature, status) in chunk.iter().zip(statuses) {
+                match status {
+                    Some(status) if status.satisfies_commitment(CommitmentConfig::finalized()) => {
+                        finalized.push(signature.clone())
+                    }
+                    Some(_) => {} // still settling
+                    None => orphaned.push(signature.clone()),
+                }
+            }
+        }
+        self.store.mark_finalized(&finalized).await?;
+        if !orphaned.is_empty() {
+            log::warn!(
+                "dropping {} transactions from a dropped fork",
+                orphaned.len()
+            );
+            self.store.drop_orphaned(&orphaned).await?;
+        }
+        Ok(())
+    }
+
+    // Index the events of every program transaction since the newest one
+    // indexed, or of the last backfill_limit on a first start, oldest first
+    async fn backfill(&self) -> Result<(), String> {
+        let until = self
+            .store
+            .latest_signature()
+            .await?
+            .and_then(|signature| signature.parse().ok());
+        let rpc = &self.finalized.rpc;
+        let mut signatures = Vec::new();
+        let mut before = None;
+        while signatures.len() < self.config.backfill_limit {
+            let page = rpc
+                .get_signatures_for_address_with_config(
+                    &liqguard::ID,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(self.config.backfill_limit - signatures.len()),
+                        commitment: Some(self.commitment),
+                    },
+                )
+                .await
+                .map_err(|error| format!("listing program signatures: {error}"))?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = last.signature.parse().ok();
+            signatures.extend(page.into_iter().filter(|status| status.err.is_none()));
+            if before.is_none() {
+                break;
+            }
+        }
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(self.commitment),
+            max_supported_transaction_version: Some(0),
+        };
+        for status in signatures.iter().rev() {
+            let Ok(signature) = status.signature.parse::<Signature>() else {
+                continue;
+            };
+            let transaction = rpc
+                .get_transaction_with_config(&signature, config)
+                .await
+                .map_err(|error| format!("fetching {signature}: {error}"))?;
+            let logs = match transaction.transaction.meta.map(|meta| meta.log_messages) {
+                Some(OptionSerializer::Some(logs)) => logs,
+                _ => continue,
+            };
+            for (index, event) in parse_logs(&logs).iter().enumerate() {
+                let row = EventRow::new(&signature, index, transaction.slot, event);
+                self.store.insert_event(&row).await?;
+            }
+        }
+        if !signatures.is_empty() {
+            log::info!("backfilled {} transactions", signatures.len());
+        }
+        Ok(())
+    }
+}
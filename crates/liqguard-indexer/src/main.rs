@@ -0,0 +1,36 @@
+// indexer [CONFIG]: mirror LiqGuard's policies, events and claims into
+// Postgres with the given config file, indexer.toml by default. See
+// schema.sql for the tables
+mod config;
+mod indexer;
+mod store;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use config::IndexerConfig;
+use indexer::Indexer;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or("indexer.toml".to_string()),
+    );
+    let result = match IndexerConfig::load(&path) {
+        Ok(config) => match Indexer::new(config).await {
+            Ok(indexer) => indexer.run().await,
+            Err(error) => Err(error),
+        },
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            log::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,367 @@
+// Rows the indexer writes and the statements that write them; schema.sql
+// holds the tables. Postgres has no unsigned integers, so u64 amounts are
+// stored as BIGINT, saturating at i64::MAX. Policy writes only land when
+// they are at least as new as the row they replace, so a late or replayed
+// update can't roll a policy back
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountSerialize;
+use liqguard::{LiquidationExecuted, Policy};
+use liqguard_client::accounts::PolicyStatus;
+use liqguard_client::events::LiqGuardEvent;
+use solana_sdk::signature::Signature;
+use tokio_postgres::{Client, NoTls};
+
+pub const SCHEMA: &str = include_str!("../schema.sql");
+
+const UPSERT_POLICY: &str = "\
+    INSERT INTO policies (address, owner, creator, beneficiary, underwriter, asset, policy_id, \
+        status, coverage_amount, coverage_remaining, is_funded, expiration_datetime, \
+        settlement_slot, data, slot) \
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) \
+    ON CONFLICT (address) DO UPDATE SET \
+        owner = excluded.owner, beneficiary = excluded.beneficiary, \
+        underwriter = excluded.underwriter, status = excluded.status, \
+        coverage_amount = excluded.coverage_amount, \
+        coverage_remaining = excluded.coverage_remaining, is_funded = excluded.is_funded, \
+        expiration_datetime = excluded.expiration_datetime, \
+        settlement_slot = excluded.settlement_slot, data = excluded.data, \
+        slot = excluded.slot, updated_at = now() \
+    WHERE policies.slot <= excluded.slot";
+
+const INSERT_EVENT: &str = "\
+    INSERT INTO events (signature, event_index, slot, name, policy, data) \
+    VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT DO NOTHING";
+
+const INSERT_CLAIM: &str = "\
+    INSERT INTO claims (signature, event_index, slot, policy, liquidator, asset, price, \
+        price_exponent, payout, bounty, coverage_remaining) \
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) ON CONFLICT DO NOTHING";
+
+fn int(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+pub struct PolicyRow {
+    pub address: String,
+    pub owner: String,
+    pub creator: String,
+    pub beneficiary: String,
+    pub underwriter: String,
+    pub asset: &'static str,
+    pub policy_id: i64,
+    pub status: &'static str,
+    pub coverage_amount: i64,
+    pub coverage_remaining: i64,
+    pub is_funded: bool,
+    pub expiration_datetime: i64,
+    pub settlement_slot: i64,
+    pub data: Vec<u8>,
+    pub slot: i64,
+}
+
+impl PolicyRow {
+    // policy as read at slot, with its status at now
+    pub fn new(address: &Pubkey, policy: &Policy, slot: u64, now: i64) -> Self {
+        let mut data = Vec::new();
+        policy
+            .try_serialize(&mut data)
+            .expect("policies serialize into a Vec");
+        Self {
+            address: address.to_string(),
+            owner: policy.owner.to_string(),
+            creator: policy.creator.to_string(),
+            beneficiary: policy.beneficiary.to_string(),
+            underwriter: policy.underwriter.to_string(),
+            asset: policy.asset.name(),
+            policy_id: int(policy.policy_id),
+            status: PolicyStatus::of(policy, now).as_str(),
+            coverage_amount: int(policy.coverage_amount),
+            coverage_remaining: int(policy.coverage_remaining),
+            is_funded: policy.is_funded,
+            expiration_datetime: policy.expiration_datetime,
+            settlement_slot: int(policy.settlement_slot),
+            data,
+            slot: int(slot),
+        }
+    }
+}
+
+pub struct EventRow {
+    pub signature: String,
+    pub event_index: i32,
+    pub slot: i64,
+    pub name: &'static str,
+    pub policy: Option<String>,
+    pub data: Vec<u8>,
+    pub claim: Option<ClaimRow>,
+}
+
+pub struct ClaimRow {
+    pub policy: String,
+    pub liquidator: String,
+    pub asset: &'static str,
+    pub price: i64,
+    pub price_exponent: i32,
+    pub payout: i64,
+    pub bounty: i64,
+    pub coverage_remaining: i64,
+}
+
+impl ClaimRow {
+    fn new(event: &LiquidationExecuted) -> Self {
+        Self {
+            policy: event.policy.to_string(),
+            liquidator: event.liquidator.to_string(),
+            asset: event.asset.name(),
+            price: i64::try_from(event.price).unwrap_or(i64::MAX),
+            price_exponent: event.price_exponent,
+            payout: int(event.payout),
+            bounty: int(event.bounty),
+            coverage_remaining: int(event.coverage_remaining),
+        }
+    }
+}
+
+impl EventRow {
+    // The event_index'th event of signature's transaction, landed at slot
+    pub fn new(
+        signature: &Signature,
+        event_index: usize,
+        slot: u64,
+        event: &LiqGuardEvent,
+    ) -> Self {
+        let claim = match event {
+            LiqGuardEvent::LiquidationExecuted(event) => Some(ClaimRow::new(event)),
+            _ => None,
+        };
+        Self {
+            signature: signature.to_string(),
+            event_index: i32::try_from(event_index).unwrap_or(i32::MAX),
+            slot: int(slot),
+            name: event.name(),
+            policy: event.policy().map(|policy| policy.to_string()),
+            data: event.to_bytes(),
+            claim,
+        }
+    }
+}
+
+pub struct Store {
+    client: Client,
+}
+
+impl Store {
+    // Connect and apply the schema. The connection is driven on its own
+    // task, which logs and exits if it drops
+    pub async fn connect(database_url: &str) -> Result<Self, String> {
+        let (client, connection) = tokio_postgres::connect(database_url, NoTls)
+            .await
+            .map_err(|error| format!("connecting to postgres: {error}"))?;
+        tokio::spawn(async move {
+            if let Err(error) = connection.await {
+                log::error!("postgres connection closed: {error}");
+            }
+        });
+        client
+            .batch_execute(SCHEMA)
+            .await
+            .map_err(|error| format!("applying schema: {error}"))?;
+        Ok(Self { client })
+    }
+
+    pub async fn upsert_policy(&self, row: &PolicyRow) -> Result<(), String> {
+        self.client
+            .execute(
+                UPSERT_POLICY,
+                &[
+                    &row.address,
+                    &row.owner,
+                    &row.creator,
+                    &row.beneficiary,
+                    &row.underwriter,
+                    &row.asset,
+                    &row.policy_id,
+                    &row.status,
+                    &row.coverage_amount,
+                    &row.coverage_remaining,
+                    &row.is_funded,
+                    &row.expiration_datetime,
+                    &row.settlement_slot,
+                    &row.data,
+                    &row.slot,
+                ],
+            )
+            .await
+            .map(|_| ())
+            .map_err(|error| format!("upserting policy {}: {error}", row.address))
+    }
+
+    // Drop policies read at or before slot that the snapshot taken after it
+    // no longer holds: they were closed
+    pub async fn delete_policies_except(&self, kept: &[String], slot: u64) -> Result<u64, String> {
+        self.client
+            .execute(
+                "DELETE FROM policies WHERE slot <= $1 AND NOT (address = ANY($2))",
+                &[&int(slot), &kept],
+            )
+            .await
+            .map_err(|error| format!("deleting closed policies: {error}"))
+    }
+
+    pub async fn insert_event(&self, row: &EventRow) -> Result<(), String> {
+        let error = |error| {
+            format!(
+                "inserting event {}#{}: {error}",
+                row.signature, row.event_index
+            )
+        };
+        self.client
+            .execute(
+                INSERT_EVENT,
+                &[
+                    &row.signature,
+                    &row.event_index,
+                    &row.slot,
+                    &row.name,
+                    &row.policy,
+                    &row.data,
+                ],
+            )
+            .await
+            .map_err(error)?;
+        if let Some(claim) = &row.claim {
+            self.client
+                .execute(
+                    INSERT_CLAIM,
+                    &[
+                        &row.signature,
+                        &row.event_index,
+                        &row.slot,
+                        &claim.policy,
+                        &claim.liquidator,
+                        &claim.asset,
+                        &claim.price,
+                        &claim.price_exponent,
+                        &claim.payout,
+                        &claim.bounty,
+                        &claim.coverage_remaining,
+                    ],
+                )
+                .await
+                .map_err(error)?;
+        }
+        Ok(())
+    }
+
+    // The newest indexed transaction, where a backfill can stop
+    pub async fn latest_signature(&self) -> Result<Option<String>, String> {
+        self.client
+            .query_opt(
+                "SELECT signature FROM events ORDER BY slot DESC LIMIT 1",
+                &[],
+            )
+            .await
+            .map(|row| row.map(|row| row.get(0)))
+            .map_err(|error| format!("reading the latest signature: {error}"))
+    }
+
+    // Transactions with unfinalized events at or before slot
+    pub async fn unfinalized_signatures(&self, slot: u64) -> Result<Vec<String>, String> {
+        self.client
+            .query(
+                "SELECT DISTINCT signature FROM events WHERE NOT finalized AND slot <= $1",
+                &[&int(slot)],
+            )
+            .await
+            .map(|rows| rows.iter().map(|row| row.get(0)).collect())
+            .map_err(|error| format!("reading unfinalized events: {error}"))
+    }
+
+    pub async fn mark_finalized(&self, signatures: &[String]) -> Result<(), String> {
+        for table in ["events", "claims"] {
+            self.client
+                .execute(
+                    &format!("UPDATE {table} SET finalized = TRUE WHERE signature = ANY($1)"),
+                    &[&signatures],
+                )
+                .await
+                .map_err(|error| format!("finalizing {table}: {error}"))?;
+        }
+        Ok(())
+    }
+
+    // Forget transactions that landed on a fork the cluster dropped
+    pub async fn drop_orphaned(&self, signatures: &[String]) -> Result<(), String> {
+        for table in ["events", "claims"] {
+            self.client
+                .execute(
+                    &format!("DELETE FROM {table} WHERE signature = ANY($1)"),
+                    &[&signatures],
+                )
+                .await
+                .map_err(|error| format!("dropping orphaned {table}: {error}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountDeserialize;
+    use liqguard::UnderlyingAsset;
+    use liqguard_test_support::zeroed_policy;
+
+    #[test]
+    fn policy_rows_carry_the_account_and_its_status() {
+        let mut policy = zeroed_policy();
+        policy.owner = Pubkey::new_unique();
+        policy.asset = UnderlyingAsset::Eth;
+        policy.coverage_amount = u64::MAX;
+        policy.coverage_remaining = 1_000;
+        policy.expiration_datetime = 2_000;
+        policy.is_funded = true;
+        let address = Pubkey::new_unique();
+        let row = PolicyRow::new(&address, &policy, 42, 1_000);
+        assert_eq!((row.asset, row.status, row.slot), ("eth", "active", 42));
+        assert_eq!(row.coverage_amount, i64::MAX);
+        assert_eq!(row.owner, policy.owner.to_string());
+        let decoded = Policy::try_deserialize(&mut &row.data[..]).unwrap();
+        assert_eq!(decoded.coverage_remaining, 1_000);
+        assert_eq!(
+            PolicyRow::new(&address, &policy, 42, 3_000).status,
+            "terminal"
+        );
+    }
+
+    #[test]
+    fn liquidations_also_become_claims() {
+        let event = LiqGuardEvent::LiquidationExecuted(LiquidationExecuted {
+            policy: Pubkey::new_unique(),
+            liquidator: Pubkey::new_unique(),
+            asset: UnderlyingAsset::Btc,
+            price: 9_000_000_000_000,
+            price_exponent: -8,
+            strike_price: 9_500_000_000_000,
+            strike_exponent: -8,
+            payout: 990,
+            bounty: 10,
+            coverage_remaining: 0,
+            slot: 7,
+            sequence: 3,
+        });
+        let row = EventRow::new(&Signature::default(), 1, 7, &event);
+        assert_eq!(
+            (row.name, row.event_index, row.slot),
+            ("LiquidationExecuted", 1, 7)
+        );
+        assert_eq!(
+            LiqGuardEvent::decode(&row.data).map(|event| event.name()),
+            Some(row.name)
+        );
+        let claim = row.claim.unwrap();
+        assert_eq!((claim.asset, claim.payout, claim.bounty), ("btc", 990, 10));
+        assert_eq!(Some(claim.policy), row.policy);
+    }
+}
@@ -0,0 +1,181 @@
+// The keeper's config file (TOML): endpoints, the liquidator wallet and
+// which assets to keep. See keeper.example.toml
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use liqguard::UnderlyingAsset;
+use liqguard_notifier::AlertConfig;
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+#[derive(Debug, Deserialize)]
+pub struct KeeperConfig {
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    pub ws_url: String,
+    #[serde(default = "default_hermes_url")]
+    pub hermes_url: String,
+    #[serde(default = "default_commitment")]
+    pub commitment: String,
+    pub keypair_path: PathBuf,
+    #[serde(default)]
+    pub assets: Vec<String>,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(default)]
+    pub treasury_id: u8,
+    #[serde(default)]
+    pub use_backstop: bool,
+    #[serde(default = "default_max_send_attempts")]
+    pub max_send_attempts: u32,
+    #[serde(default = "default_priority_fee_percentile")]
+    pub priority_fee_percentile: u8,
+    #[serde(default)]
+    pub min_priority_fee: u64,
+    #[serde(default = "default_max_priority_fee")]
+    pub max_priority_fee: u64,
+    #[serde(default = "default_priority_fee_retry_multiplier")]
+    pub priority_fee_retry_multiplier: u64,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub jito_url: Option<String>,
+    #[serde(default = "default_jito_tip_lamports")]
+    pub jito_tip_lamports: u64,
+    #[serde(default)]
+    pub metrics_addr: Option<String>,
+    #[serde(default = "default_max_tick_age_seconds")]
+    pub max_tick_age_seconds: i64,
+    #[serde(default)]
+    pub risk_interval_seconds: i64,
+    #[serde(default)]
+    pub risk_max_var_bps: u16,
+    #[serde(default)]
+    pub notifier: Option<AlertConfig>,
+}
+
+fn default_hermes_url() -> String {
+    "https://hermes.pyth.network".to_string()
+}
+
+fn default_commitment() -> String {
+    "confirmed".to_string()
+}
+
+fn default_poll_interval_ms() -> u64 {
+    2_000
+}
+
+fn default_max_send_attempts() -> u32 {
+    3
+}
+
+fn default_priority_fee_percentile() -> u8 {
+    75
+}
+
+fn default_max_priority_fee() -> u64 {
+    100_000
+}
+
+fn default_priority_fee_retry_multiplier() -> u64 {
+    2
+}
+
+fn default_jito_tip_lamports() -> u64 {
+    100_000
+}
+
+fn default_max_tick_age_seconds() -> i64 {
+    60
+}
+
+impl KeeperConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("reading {}: {error}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|error| error.to_string())?;
+        config.commitment()?;
+        config.assets()?;
+        if let Some(notifier) = &config.notifier {
+            notifier.owners()?;
+        }
+        Ok(config)
+    }
+
+    // rpc_url first, then the fallbacks in the order given
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(self.rpc_url.clone())
+            .chain(self.fallback_rpc_urls.iter().cloned())
+            .collect()
+    }
+
+    pub fn commitment(&self) -> Result<CommitmentConfig, String> {
+        CommitmentConfig::from_str(&self.commitment)
+            .map_err(|_| format!("unknown commitment {:?}", self.commitment))
+    }
+
+    pub fn assets(&self) -> Result<Vec<UnderlyingAsset>, String> {
+        if self.assets.is_empty() {
+            return Ok(vec![
+                UnderlyingAsset::Btc,
+                UnderlyingAsset::Eth,
+                UnderlyingAsset::Sol,
+            ]);
+        }
+        self.assets
+            .iter()
+            .map(|asset| match asset.to_ascii_lowercase().as_str() {
+                "btc" => Ok(UnderlyingAsset::Btc),
+                "eth" => Ok(UnderlyingAsset::Eth),
+                "sol" => Ok(UnderlyingAsset::Sol),
+                _ => Err(format!("unknown asset {asset:?}")),
+            })
+            .collect()
+    }
+
+    pub fn keypair(&self) -> Result<Keypair, String> {
+        let path = match self.keypair_path.strip_prefix("~") {
+            Ok(rest) => std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(rest))
+                .unwrap_or_else(|| self.keypair_path.clone()),
+            Err(_) => self.keypair_path.clone(),
+        };
+        read_keypair_file(&path).map_err(|error| format!("reading {}: {error}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_config_parses() {
+        let config = KeeperConfig::parse(include_str!("../keeper.example.toml")).unwrap();
+        assert_eq!(config.assets().unwrap().len(), 3);
+        assert_eq!(config.commitment().unwrap(), CommitmentConfig::confirmed());
+        assert_eq!(config.rpc_urls().len(), 2);
+        assert_eq!(config.risk_interval_seconds, 300);
+        assert_eq!(config.notifier.unwrap().sinks.len(), 1);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_assets_and_commitments() {
+        let base = "rpc_url = \"a\"\nws_url = \"b\"\nkeypair_path = \"k.json\"\n";
+        assert!(KeeperConfig::parse(&format!("{base}assets = [\"doge\"]")).is_err());
+        assert!(KeeperConfig::parse(&format!("{base}commitment = \"soon\"")).is_err());
+        let config = KeeperConfig::parse(&format!("{base}assets = [\"SOL\"]")).unwrap();
+        assert_eq!(config.assets().unwrap(), vec![UnderlyingAsset::Sol]);
+        assert_eq!(config.poll_interval_ms, 2_000);
+        assert_eq!(config.rpc_urls(), vec!["a".to_string()]);
+        assert_eq!(config.max_send_attempts, 3);
+        assert!(config.notifier.is_none());
+    }
+}
@@ -0,0 +1,105 @@
+// The keeper's RPC endpoints: the primary rpc_url, then each of
+// fallback_rpc_urls in order. Every request goes to the active endpoint;
+// when it fails a health check or a send can't reach it, the keeper moves
+// on to the next endpoint that answers getHealth, so one flaky RPC doesn't
+// miss settlements during a crash
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use liqguard_client::errors::{LiqGuardError, ProgramError};
+use liqguard_client::rpc::LiqGuardClient;
+use log::warn;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::transaction::TransactionError;
+
+pub struct RpcPool {
+    endpoints: Vec<(String, LiqGuardClient)>,
+    active: AtomicUsize,
+}
+
+impl RpcPool {
+    pub fn new(urls: Vec<String>, commitment: CommitmentConfig) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| {
+                let rpc = RpcClient::new_with_commitment(url.clone(), commitment);
+                (url, LiqGuardClient::new(rpc))
+            })
+            .collect();
+        Self {
+            endpoints,
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn client(&self) -> &LiqGuardClient {
+        &self.endpoints[self.active.load(Ordering::Relaxed)].1
+    }
+
+    // Keep the active endpoint while it reports healthy, else fail over.
+    // Returns false when no endpoint is healthy
+    pub async fn check_health(&self) -> bool {
+        if self.client().rpc.get_health().await.is_ok() {
+            return true;
+        }
+        self.fail_over().await
+    }
+
+    // Switch to the next endpoint, after the active one, that answers
+    // getHealth. Stays put and returns false when none does
+    pub async fn fail_over(&self) -> bool {
+        let active = self.active.load(Ordering::Relaxed);
+        for step in 1..=self.endpoints.len() {
+            let next = (active + step) % self.endpoints.len();
+            let (url, client) = &self.endpoints[next];
+            if client.rpc.get_health().await.is_ok() {
+                if next != active {
+                    warn!("failing over to {url}");
+                    self.active.store(next, Ordering::Relaxed);
+                }
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// A send failed before the cluster ruled on the transaction, or on a
+// blockhash the endpoint never saw, so it's worth signing again
+pub fn retryable(error: Option<&TransactionError>) -> bool {
+    matches!(error, None | Some(TransactionError::BlockhashNotFound))
+}
+
+// liquidate_policy was rejected because the claim already paid, whether by
+// an attempt that landed unseen or by another keeper. Retrying a
+// liquidation is idempotent: at most one copy can pay out
+pub fn already_settled(error: &TransactionError) -> bool {
+    matches!(
+        ProgramError::from_transaction_error(error),
+        Some(ProgramError::LiqGuard(
+            LiqGuardError::AlreadyClaimed | LiqGuardError::NothingToClaim
+        ))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::InstructionError;
+
+    #[test]
+    fn classifies_send_failures() {
+        let custom = |error: LiqGuardError| {
+            TransactionError::InstructionError(1, InstructionError::Custom(error.into()))
+        };
+        assert!(already_settled(&custom(LiqGuardError::AlreadyClaimed)));
+        assert!(already_settled(&custom(LiqGuardError::NothingToClaim)));
+        assert!(!already_settled(&custom(LiqGuardError::PriceStale)));
+        assert!(!already_settled(&TransactionError::BlockhashNotFound));
+
+        assert!(retryable(None));
+        assert!(retryable(Some(&TransactionError::BlockhashNotFound)));
+        assert!(!retryable(Some(&custom(LiqGuardError::AlreadyClaimed))));
+    }
+}
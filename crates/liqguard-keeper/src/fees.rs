@@ -0,0 +1,37 @@
+// Priority fees sized from what recently landed. The keeper asks the RPC
+// for recent prioritization fees on the accounts a transaction write-locks
+// (liqguard_client::compute), bids a percentile of them, and raises the
+// bid on every retry, so it neither overpays in calm markets nor gets
+// dropped in a crash
+
+use crate::config::KeeperConfig;
+
+// The bid for attempt (1-based): the estimate, multiplied up on each retry,
+// then clamped into the configured range
+pub fn bid(config: &KeeperConfig, estimate: u64, attempt: u32) -> u64 {
+    let escalation = config
+        .priority_fee_retry_multiplier
+        .saturating_pow(attempt.saturating_sub(1));
+    estimate
+        .max(config.min_priority_fee)
+        .saturating_mul(escalation)
+        .min(config.max_priority_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bids_escalate_within_bounds() {
+        let base = "rpc_url = \"a\"\nws_url = \"b\"\nkeypair_path = \"k.json\"\n";
+        let config = KeeperConfig::parse(&format!(
+            "{base}min_priority_fee = 1000\nmax_priority_fee = 50000"
+        ))
+        .unwrap();
+        assert_eq!(bid(&config, 0, 1), 1_000);
+        assert_eq!(bid(&config, 8_000, 1), 8_000);
+        assert_eq!(bid(&config, 8_000, 2), 16_000);
+        assert_eq!(bid(&config, 8_000, 4), 50_000);
+    }
+}
@@ -0,0 +1,212 @@
+// Submitting settlements as Jito bundles. A bundle's transactions land
+// together and in order or not at all, so a price post and the
+// liquidations it unlocks can't be split up, reordered or sandwiched, and
+// the tip buys them a place in congested crash blocks. A bundle that
+// doesn't land leaves nothing behind and is rebuilt on the next poll
+
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::hash::Hash;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+// The block engine takes at most this many transactions per bundle
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+// How long to wait for a bundle to land before giving up on it
+const CONFIRM_POLLS: usize = 15;
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Jito's mainnet tip accounts; a bundle pays one of them to be included
+pub const TIP_ACCOUNTS: [Pubkey; 8] = [
+    Pubkey::new_from_array([
+        120, 82, 28, 177, 121, 206, 187, 133, 137, 181, 86, 162, 213, 236, 148, 210, 73, 134, 130,
+        253, 249, 187, 42, 245, 173, 100, 228, 145, 204, 65, 83, 218,
+    ]),
+    Pubkey::new_from_array([
+        241, 135, 236, 135, 209, 247, 69, 203, 58, 3, 56, 74, 38, 166, 158, 218, 12, 162, 209, 170,
+        15, 65, 228, 36, 22, 55, 126, 145, 255, 91, 93, 49,
+    ]),
+    Pubkey::new_from_array([
+        177, 78, 13, 229, 94, 159, 186, 134, 57, 110, 191, 213, 72, 207, 248, 201, 32, 17, 234,
+        199, 183, 91, 170, 155, 45, 156, 106, 134, 245, 161, 113, 65,
+    ]),
+    Pubkey::new_from_array([
+        136, 241, 255, 163, 162, 223, 230, 23, 189, 196, 227, 87, 50, 81, 163, 34, 227, 252, 174,
+        129, 229, 164, 87, 57, 14, 100, 117, 28, 0, 164, 101, 226,
+    ]),
+    Pubkey::new_from_array([
+        188, 43, 87, 6, 94, 241, 221, 102, 84, 48, 190, 96, 107, 166, 89, 108, 2, 149, 48, 27, 173,
+        239, 139, 90, 252, 65, 1, 65, 80, 244, 18, 116,
+    ]),
+    Pubkey::new_from_array([
+        137, 7, 125, 85, 165, 187, 19, 48, 118, 62, 183, 103, 245, 94, 192, 119, 180, 26, 13, 7,
+        95, 125, 225, 215, 63, 186, 202, 60, 99, 213, 84, 113,
+    ]),
+    Pubkey::new_from_array([
+        191, 151, 27, 89, 16, 139, 91, 133, 160, 79, 176, 147, 241, 226, 27, 78, 63, 212, 196, 200,
+        244, 135, 221, 9, 185, 87, 82, 118, 159, 13, 216, 195,
+    ]),
+    Pubkey::new_from_array([
+        32, 38, 16, 30, 194, 3, 40, 150, 74, 50, 171, 171, 19, 108, 84, 5, 185, 31, 58, 227, 142,
+        228, 246, 76, 182, 189, 232, 121, 184, 104, 56, 210,
+    ]),
+];
+
+pub struct JitoClient {
+    http: reqwest::Client,
+    bundles_url: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct BundleStatuses {
+    value: Vec<Option<BundleStatus>>,
+}
+
+#[derive(Deserialize)]
+struct BundleStatus {
+    slot: u64,
+    confirmation_status: Option<String>,
+}
+
+// Pays lamports to a tip account. The account is picked by blockhash so
+// tips spread across all of them, as Jito asks, without a random source
+pub fn tip(payer: &Pubkey, lamports: u64, blockhash: &Hash) -> Instruction {
+    let account = TIP_ACCOUNTS[blockhash.as_ref()[0] as usize % TIP_ACCOUNTS.len()];
+    system_instruction::transfer(payer, &account, lamports)
+}
+
+impl JitoClient {
+    // block_engine_url is the block engine's base URL, e.g.
+    // https://mainnet.block-engine.jito.wtf
+    pub fn new(block_engine_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            bundles_url: format!("{}/api/v1/bundles", block_engine_url.trim_end_matches('/')),
+        }
+    }
+
+    async fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, String> {
+        let response: RpcResponse<T> = self
+            .http
+            .post(&self.bundles_url)
+            .json(&request(method, params))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| format!("block engine request failed: {error}"))?
+            .json()
+            .await
+            .map_err(|error| format!("block engine response unreadable: {error}"))?;
+        match (response.result, response.error) {
+            (Some(result), _) => Ok(result),
+            (None, error) => Err(format!("{method} failed: {}", error.unwrap_or_default())),
+        }
+    }
+
+    // Submit signed transactions as one bundle, returning its id
+    pub async fn send_bundle(&self, transactions: &[Transaction]) -> Result<String, String> {
+        self.call("sendBundle", bundle_params(transactions)?).await
+    }
+
+    // Wait for the bundle to be confirmed, returning the slot it landed in
+    pub async fn confirm(&self, bundle_id: &str) -> Result<u64, String> {
+        for _ in 0..CONFIRM_POLLS {
+            tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+            let statuses: BundleStatuses =
+                self.call("getBundleStatuses", json!([[bundle_id]])).await?;
+            if let Some(Some(status)) = statuses.value.into_iter().next() {
+                if matches!(
+                    status.confirmation_status.as_deref(),
+                    Some("confirmed" | "finalized")
+                ) {
+                    return Ok(status.slot);
+                }
+            }
+        }
+        Err(format!("bundle {bundle_id} didn't land"))
+    }
+}
+
+fn request(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+}
+
+fn bundle_params(transactions: &[Transaction]) -> Result<Value, String> {
+    if transactions.is_empty() || transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+        return Err(format!(
+            "bundles carry 1 to {MAX_BUNDLE_TRANSACTIONS} transactions, not {}",
+            transactions.len()
+        ));
+    }
+    let encoded = transactions
+        .iter()
+        .map(|transaction| {
+            bincode::serialize(transaction)
+                .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+                .map_err(|error| error.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(json!([encoded, { "encoding": "base64" }]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn tip_accounts_match_jito() {
+        let expected = [
+            "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+            "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+            "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+            "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+            "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+            "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+            "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+            "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+        ];
+        for (account, expected) in TIP_ACCOUNTS.iter().zip(expected) {
+            assert_eq!(account.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn bundle_params_encode_each_transaction() {
+        let payer = Keypair::new();
+        let blockhash = Hash::new_unique();
+        let transaction = Transaction::new_signed_with_payer(
+            &[tip(&payer.pubkey(), 10_000, &blockhash)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            blockhash,
+        );
+        let params = bundle_params(&[transaction.clone(), transaction.clone()]).unwrap();
+        assert_eq!(params[0].as_array().unwrap().len(), 2);
+        assert_eq!(params[1]["encoding"], "base64");
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(params[0][0].as_str().unwrap())
+            .unwrap();
+        let decoded: Transaction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded, transaction);
+
+        assert!(bundle_params(&[]).is_err());
+        assert!(bundle_params(&vec![transaction; MAX_BUNDLE_TRANSACTIONS + 1]).is_err());
+    }
+}
@@ -0,0 +1,665 @@
+// The keeper loop: track every policy through a program subscription,
+// pull each kept asset's price from Hermes on a fixed interval, and when
+// a policy is breached post that price on chain and liquidate it.
+// Policies are checked with the program's own trigger and claimability
+// rules first, so transactions only go out for claims that should land.
+// Every risk_interval_seconds the tracked book is also run through the
+// risk engine, and its alerts logged. With a [notifier] table, policy
+// updates and Hermes prices also go to the notifier, which alerts owners
+// on near-strike, liquidated and underfunded policies
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use futures::StreamExt;
+use liqguard::oracle::OracleSource;
+use liqguard::{
+    check_claimable, preview_payout, Config, FeedRegistry, PayoutDecision, Policy, UnderlyingAsset,
+};
+use liqguard_client::hermes::{HermesClient, HermesUpdate};
+use liqguard_client::instructions::{liquidate_policy, LiquidateOptions};
+use liqguard_client::oracle::sponsored_feed;
+use liqguard_client::pyth;
+use liqguard_client::subscribe::{self, SubscribeConfig, Update};
+use liqguard_notifier::Notifier;
+use liqguard_risk::{Market, RiskParams, RiskReport};
+use log::{info, warn};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use crate::config::KeeperConfig;
+use crate::failover::{self, RpcPool};
+use crate::fees;
+use crate::jito::{self, JitoClient};
+use crate::metrics::{self, unix_now, Metrics};
+use crate::simulate;
+
+// Compute requested for each liquidate_policy transaction
+const LIQUIDATE_COMPUTE_UNITS: u32 = 400_000;
+
+pub struct Keeper {
+    config: KeeperConfig,
+    rpc: RpcPool,
+    hermes: HermesClient,
+    jito: Option<JitoClient>,
+    payer: Keypair,
+    assets: Vec<UnderlyingAsset>,
+    policies: HashMap<Pubkey, Policy>,
+    protocol: Option<Config>,
+    feeds: Vec<(UnderlyingAsset, FeedRegistry)>,
+    // One price update account per asset, indexed by its seed. The keeper
+    // is their write authority, so each post overwrites the last
+    price_accounts: Vec<Keypair>,
+    metrics: Arc<Metrics>,
+    last_risk_check: i64, // unix seconds
+    notifier: Option<Notifier>,
+}
+
+impl Keeper {
+    pub fn new(config: KeeperConfig) -> Result<Self, String> {
+        Ok(Self {
+            rpc: RpcPool::new(config.rpc_urls(), config.commitment()?),
+            hermes: HermesClient::new(&config.hermes_url),
+            jito: config.jito_url.as_deref().map(JitoClient::new),
+            payer: config.keypair()?,
+            assets: config.assets()?,
+            policies: HashMap::new(),
+            protocol: None,
+            feeds: Vec::new(),
+            price_accounts: (0..3).map(|_| Keypair::new()).collect(),
+            metrics: Arc::new(Metrics::default()),
+            last_risk_check: 0,
+            notifier: config.notifier.as_ref().map(Notifier::new).transpose()?,
+            config,
+        })
+    }
+
+    pub async fn run(mut self) -> Result<(), String> {
+        info!("keeping {:?} as {}", self.assets, self.payer.pubkey());
+        if let Some(addr) = self.config.metrics_addr.clone() {
+            tokio::spawn(metrics::serve(
+                addr,
+                self.metrics.clone(),
+                self.config.max_tick_age_seconds,
+            ));
+        }
+        let mut updates = subscribe::policies(
+            SubscribeConfig::new(self.config.ws_url.clone(), self.config.commitment()?),
+            Vec::new(),
+        );
+        self.refresh().await?;
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(self.config.poll_interval_ms));
+        loop {
+            tokio::select! {
+                update = updates.next() => match update {
+                    Some(Update::Value { value, .. }) => {
+                        if let Some(notifier) = &self.notifier {
+                            notifier.observe_policy(value.address, value.policy.clone()).await;
+                        }
+                        self.policies.insert(value.address, value.policy);
+                        self.record_tracked();
+                    }
+                    Some(Update::Reconnected) => {
+                        if let Err(error) = self.refresh().await {
+                            warn!("refresh after reconnect failed: {error}");
+                        }
+                    }
+                    None => return Err("policy subscription ended".to_string()),
+                },
+                _ = interval.tick() => {
+                    if !self.rpc.check_health().await {
+                        warn!("no RPC endpoint is healthy");
+                    }
+                    for asset in self.assets.clone() {
+                        if let Err(error) = self.check_asset(asset).await {
+                            warn!("{asset:?}: {error}");
+                        }
+                    }
+                    if let Some(notifier) = &self.notifier {
+                        notifier.check_funding(unix_now()).await;
+                    }
+                    self.metrics.record_tick();
+                    let interval = self.config.risk_interval_seconds;
+                    if interval > 0 && unix_now() - self.last_risk_check >= interval {
+                        self.last_risk_check = unix_now();
+                        self.check_risk().await;
+                    }
+                }
+            }
+        }
+    }
+
+    // Reload every policy, the protocol config and the kept feeds
+    async fn refresh(&mut self) -> Result<(), String> {
+        let policies = self
+            .rpc
+            .client()
+            .policies(&[])
+            .await
+            .map_err(|error| self.rpc_error(error))?;
+        if let Some(notifier) = &self.notifier {
+            for (address, policy) in &policies {
+                notifier.observe_policy(*address, policy.clone()).await;
+            }
+        }
+        self.policies = policies.into_iter().collect();
+        self.record_tracked();
+        self.protocol = Some(
+            self.rpc
+                .client()
+                .config()
+                .await
+                .map_err(|error| self.rpc_error(error))?,
+        );
+        self.feeds.clear();
+        for asset in self.assets.clone() {
+            match self.rpc.client().feed_registry(asset).await {
+                Ok(feed) if feed.oracle_source == OracleSource::PythPull => {
+                    self.feeds.push((asset, feed))
+                }
+                Ok(_) => warn!("{asset:?} doesn't settle on Pyth, skipping it"),
+                Err(error) => warn!("{asset:?} feed registry unavailable: {error}"),
+            }
+        }
+        info!("tracking {} policies", self.policies.len());
+        Ok(())
+    }
+
+    // Policies the keeper can settle: native collateral, priced on Pyth
+    // spot alone. TWAP, Chainlink-bound and Kamino health policies need
+    // inputs the keeper doesn't post
+    fn keepable(policy: &Policy) -> bool {
+        policy.is_native()
+            && policy.twap_window_seconds == 0
+            && policy.chainlink_feed == Pubkey::default()
+            && policy.kamino_obligation == Pubkey::default()
+    }
+
+    // Price the tracked book at the kept assets' Hermes prices and each
+    // asset's volatility, the risk oracle's or the config-wide fallback
+    async fn check_risk(&self) {
+        let Some(protocol) = self.protocol.as_ref() else {
+            return;
+        };
+        let mut markets = Vec::new();
+        for (asset, feed) in &self.feeds {
+            match self.hermes.latest(&feed.feed_id).await {
+                Ok(update) => markets.push(Market {
+                    asset: *asset,
+                    spot: update.price,
+                    volatility_bps: match feed.volatility_bps {
+                        0 => protocol.volatility_bps,
+                        volatility_bps => volatility_bps,
+                    },
+                }),
+                Err(error) => warn!("{asset:?}: no price for the risk check: {error}"),
+            }
+        }
+        let policies: Vec<(Pubkey, Policy)> = self
+            .policies
+            .iter()
+            .map(|(address, policy)| (*address, policy.clone()))
+            .collect();
+        let report = RiskReport::build(&policies, &markets, &RiskParams::default(), unix_now());
+        let alerts = report.alerts(self.config.risk_max_var_bps);
+        for alert in &alerts {
+            warn!("risk: {alert}");
+        }
+        self.metrics
+            .risk_alerts
+            .store(alerts.len() as u64, Ordering::Relaxed);
+    }
+
+    async fn check_asset(&self, asset: UnderlyingAsset) -> Result<(), String> {
+        let (Some(protocol), Some((_, feed))) = (
+            self.protocol.as_ref(),
+            self.feeds
+                .iter()
+                .find(|(feed_asset, _)| *feed_asset == asset),
+        ) else {
+            return Ok(());
+        };
+        if protocol.paused || !feed.enabled {
+            return Ok(());
+        }
+        let now = unix_now();
+        let liquidator = self.payer.pubkey();
+        let candidates: Vec<(Pubkey, Policy)> = self
+            .policies
+            .iter()
+            .filter(|(_, policy)| {
+                policy.asset == asset
+                    && Self::keepable(policy)
+                    && policy.may_liquidate(protocol, &liquidator)
+                    && check_claimable(policy, protocol, now).is_ok()
+            })
+            .map(|(address, policy)| (*address, policy.clone()))
+            .collect();
+        // The notifier wants every price, not just ones with claims to make
+        if candidates.is_empty() && self.notifier.is_none() {
+            return Ok(());
+        }
+
+        let update = self.hermes.latest(&feed.feed_id).await?;
+        self.metrics
+            .record_oracle_latency(asset, update.publish_time);
+        if let Some(notifier) = &self.notifier {
+            notifier.observe_price(asset, update.price, now).await;
+        }
+        // Same decision the program makes, confidence shift and breach
+        // dwell included. A dwell's first breach is sent too, to record it
+        let breached: Vec<(Pubkey, Policy)> = candidates
+            .into_iter()
+            .filter(|(_, policy)| {
+                matches!(
+                    preview_payout(
+                        policy,
+                        protocol,
+                        update.price,
+                        update.confidence.into(),
+                        now
+                    ),
+                    PayoutDecision::Pay { .. } | PayoutDecision::ObserveBreach { .. }
+                )
+            })
+            .collect();
+        if breached.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "{asset:?} at {}e{} (conf {}, published {}): {} policies breached",
+            update.price.mantissa,
+            update.price.exponent,
+            update.confidence,
+            update.publish_time,
+            breached.len()
+        );
+
+        let dry_run = self.config.dry_run;
+        let price_feed = if dry_run {
+            sponsored_feed(&feed.feed_id)
+        } else {
+            self.price_account(asset).pubkey()
+        };
+        // USD coverage is paid at the SOL price, posted first
+        let sol_price_feed = if !breached.iter().any(|(_, policy)| policy.usd_coverage) {
+            None
+        } else if asset == UnderlyingAsset::Sol {
+            Some(price_feed)
+        } else if dry_run {
+            Some(sponsored_feed(&UnderlyingAsset::Sol.feed_id()))
+        } else {
+            Some(self.post_sol_price().await?)
+        };
+        let treasury = protocol.payout_fee_bps > 0;
+        let mut liquidations = Vec::new();
+        for (address, policy) in breached {
+            let options = LiquidateOptions {
+                sol_price_feed: sol_price_feed.filter(|_| policy.usd_coverage),
+                backstop: self.config.use_backstop,
+                treasury,
+                compliance_mint: Some(protocol.compliance_mint)
+                    .filter(|mint| *mint != Pubkey::default()),
+                ..LiquidateOptions::default()
+            };
+            let ix = liquidate_policy(address, &policy, liquidator, price_feed, options)
+                .map_err(|error| error.to_string())?;
+            let budget = ComputeBudgetInstruction::set_compute_unit_limit(LIQUIDATE_COMPUTE_UNITS);
+            liquidations.push((address, vec![budget, ix]));
+        }
+
+        if dry_run {
+            self.simulate_each(liquidations).await;
+            return Ok(());
+        }
+        let price = self.prepare_price(asset, &update).await?;
+        match &self.jito {
+            Some(jito) => self.liquidate_bundled(jito, price, liquidations).await,
+            None => {
+                self.send_price(&price).await?;
+                self.liquidate_each(liquidations).await;
+                Ok(())
+            }
+        }
+    }
+
+    async fn liquidate_each(&self, liquidations: Vec<(Pubkey, Vec<Instruction>)>) {
+        for (address, instructions) in liquidations {
+            Metrics::inc(&self.metrics.liquidations_attempted);
+            match self.send(&instructions, &[]).await {
+                Ok(signature) => {
+                    Metrics::inc(&self.metrics.liquidations_succeeded);
+                    info!("liquidated {address}: {signature}")
+                }
+                Err(SendError::Rejected(error)) if failover::already_settled(&error) => {
+                    info!("{address} was already settled")
+                }
+                Err(error) => warn!("liquidating {address} failed: {}", String::from(error)),
+            }
+        }
+    }
+
+    // Log what each liquidation would do, sending nothing
+    async fn simulate_each(&self, liquidations: Vec<(Pubkey, Vec<Instruction>)>) {
+        for (address, instructions) in liquidations {
+            let transaction = self.sign(&instructions, &[], Hash::default());
+            match simulate::simulate(&self.rpc.client().rpc, &transaction).await {
+                Ok(simulate::Simulation {
+                    error: None,
+                    units_consumed,
+                    payout,
+                    ..
+                }) => info!(
+                    "dry run: {address} is eligible, paying {} in {} CU",
+                    payout.map_or("?".to_string(), |payout| payout.to_string()),
+                    units_consumed.unwrap_or_default()
+                ),
+                Ok(simulation) => {
+                    let reason = match (simulation.program_error, simulation.error) {
+                        (Some(program_error), _) => program_error.to_string(),
+                        (None, error) => error.map(|error| error.to_string()).unwrap_or_default(),
+                    };
+                    info!("dry run: {address} would fail: {reason}");
+                    for line in simulation.logs {
+                        log::debug!("  {line}");
+                    }
+                }
+                Err(error) => {
+                    warn!(
+                        "dry run: simulating {address} failed: {}",
+                        self.rpc_error(error)
+                    )
+                }
+            }
+        }
+    }
+
+    // Land the price post and the liquidations as Jito bundles: the first
+    // carries the three price transactions and as many liquidations as
+    // fit, later ones the rest. The last transaction of each pays the tip
+    async fn liquidate_bundled(
+        &self,
+        jito: &JitoClient,
+        price: PreparedPrice<'_>,
+        liquidations: Vec<(Pubkey, Vec<Instruction>)>,
+    ) -> Result<(), String> {
+        let blockhash = self
+            .rpc
+            .client()
+            .rpc
+            .get_latest_blockhash()
+            .await
+            .map_err(|error| self.rpc_error(error))?;
+        let mut pending = price.steps();
+        let mut liquidations = liquidations.into_iter().peekable();
+        while !pending.is_empty() || liquidations.peek().is_some() {
+            let mut addresses = Vec::new();
+            while pending.len() < jito::MAX_BUNDLE_TRANSACTIONS {
+                let Some((address, instructions)) = liquidations.next() else {
+                    break;
+                };
+                addresses.push(address);
+                pending.push((instructions, Vec::new()));
+            }
+            if let Some((instructions, _)) = pending.last_mut() {
+                instructions.push(jito::tip(
+                    &self.payer.pubkey(),
+                    self.config.jito_tip_lamports,
+                    &blockhash,
+                ));
+            }
+            let transactions: Vec<Transaction> = pending
+                .drain(..)
+                .map(|(instructions, signers)| self.sign(&instructions, &signers, blockhash))
+                .collect();
+
+            for _ in &addresses {
+                Metrics::inc(&self.metrics.liquidations_attempted);
+            }
+            let bundle_id = jito.send_bundle(&transactions).await?;
+            let slot = jito.confirm(&bundle_id).await?;
+            for address in addresses {
+                Metrics::inc(&self.metrics.liquidations_succeeded);
+                info!("liquidated {address} in bundle {bundle_id} (slot {slot})");
+            }
+        }
+        Ok(())
+    }
+
+    async fn post_sol_price(&self) -> Result<Pubkey, String> {
+        let feed_id = self
+            .rpc
+            .client()
+            .feed_registry(UnderlyingAsset::Sol)
+            .await
+            .map_err(|error| self.rpc_error(error))?
+            .feed_id;
+        let update = self.hermes.latest(&feed_id).await?;
+        self.metrics
+            .record_oracle_latency(UnderlyingAsset::Sol, update.publish_time);
+        let price = self.prepare_price(UnderlyingAsset::Sol, &update).await?;
+        self.send_price(&price).await?;
+        Ok(price.price_account.pubkey())
+    }
+
+    fn price_account(&self, asset: UnderlyingAsset) -> &Keypair {
+        &self.price_accounts[asset.seed()[0] as usize]
+    }
+
+    // The three transactions that write, verify and post update into
+    // asset's price update account
+    async fn prepare_price(
+        &self,
+        asset: UnderlyingAsset,
+        update: &HermesUpdate,
+    ) -> Result<PreparedPrice<'_>, String> {
+        let accumulator = pyth::parse_accumulator(&update.accumulator)?;
+        let merkle_update = accumulator
+            .updates
+            .into_iter()
+            .next()
+            .ok_or("Hermes update carries no price")?;
+        let vaa = accumulator.vaa;
+        let payer = self.payer.pubkey();
+        let encoded_vaa = Keypair::new();
+        let space = pyth::encoded_vaa_space(&vaa);
+        let rent = self
+            .rpc
+            .client()
+            .rpc
+            .get_minimum_balance_for_rent_exemption(space as usize)
+            .await
+            .map_err(|error| self.rpc_error(error))?;
+        let (head, tail) = vaa.split_at(pyth::VAA_SPLIT_INDEX.min(vaa.len()));
+
+        let write = vec![
+            system_instruction::create_account(
+                &payer,
+                &encoded_vaa.pubkey(),
+                rent,
+                space,
+                &pyth::WORMHOLE_PROGRAM_ID,
+            ),
+            pyth::init_encoded_vaa(payer, encoded_vaa.pubkey()),
+            pyth::write_encoded_vaa(payer, encoded_vaa.pubkey(), 0, head),
+        ];
+
+        let mut verify = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            pyth::VERIFY_COMPUTE_UNITS,
+        )];
+        if !tail.is_empty() {
+            verify.push(pyth::write_encoded_vaa(
+                payer,
+                encoded_vaa.pubkey(),
+                head.len() as u32,
+                tail,
+            ));
+        }
+        verify.push(pyth::verify_encoded_vaa(
+            payer,
+            encoded_vaa.pubkey(),
+            pyth::guardian_set(&vaa)?,
+        ));
+
+        let price_account = self.price_account(asset);
+        let post = vec![
+            pyth::post_update(
+                payer,
+                encoded_vaa.pubkey(),
+                price_account.pubkey(),
+                merkle_update,
+                self.config.treasury_id,
+            ),
+            pyth::close_encoded_vaa(payer, encoded_vaa.pubkey()),
+        ];
+        Ok(PreparedPrice {
+            encoded_vaa,
+            price_account,
+            write,
+            verify,
+            post,
+        })
+    }
+
+    async fn send_price(&self, price: &PreparedPrice<'_>) -> Result<(), String> {
+        for (instructions, signers) in price.steps() {
+            self.send(&instructions, &signers).await?;
+        }
+        Ok(())
+    }
+
+    // Recent priority fees on the accounts instructions write-lock, at
+    // the configured percentile. Falls back to the minimum bid when the
+    // RPC can't say
+    async fn estimate_priority_fee(&self, instructions: &[Instruction]) -> u64 {
+        match self
+            .rpc
+            .client()
+            .priority_fee(instructions, self.config.priority_fee_percentile)
+            .await
+        {
+            Ok(estimate) => estimate,
+            Err(error) => {
+                warn!("priority fee estimate failed: {}", self.rpc_error(error));
+                0
+            }
+        }
+    }
+
+    // Sign and send, with a fresh blockhash and a higher priority fee on
+    // every attempt. Attempts that don't reach the cluster fail over to the
+    // next healthy RPC
+    async fn send(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Signature, SendError> {
+        let estimate = self.estimate_priority_fee(instructions).await;
+        let mut last_error = String::new();
+        for attempt in 1..=self.config.max_send_attempts.max(1) {
+            let price = fees::bid(&self.config, estimate, attempt);
+            let mut priced = Vec::with_capacity(instructions.len() + 1);
+            if price > 0 {
+                priced.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+            }
+            priced.extend_from_slice(instructions);
+            let rpc = &self.rpc.client().rpc;
+            let blockhash = match rpc.get_latest_blockhash().await {
+                Ok(blockhash) => blockhash,
+                Err(error) => {
+                    last_error = self.rpc_error(error);
+                    self.rpc.fail_over().await;
+                    continue;
+                }
+            };
+            let transaction = self.sign(&priced, signers, blockhash);
+            match rpc.send_and_confirm_transaction(&transaction).await {
+                Ok(signature) => return Ok(signature),
+                Err(error) => {
+                    let transaction_error = error.get_transaction_error();
+                    if !failover::retryable(transaction_error.as_ref()) {
+                        return Err(SendError::Rejected(transaction_error.unwrap()));
+                    }
+                    last_error = self.rpc_error(error);
+                    warn!("send attempt {attempt} failed: {last_error}");
+                    if transaction_error.is_none() {
+                        self.rpc.fail_over().await;
+                    }
+                }
+            }
+        }
+        Err(SendError::Unreachable(last_error))
+    }
+
+    fn sign(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+        blockhash: Hash,
+    ) -> Transaction {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        )
+    }
+
+    fn record_tracked(&self) {
+        self.metrics
+            .policies_tracked
+            .store(self.policies.len() as u64, Ordering::Relaxed);
+    }
+
+    fn rpc_error(&self, error: impl Display) -> String {
+        Metrics::inc(&self.metrics.rpc_errors);
+        error.to_string()
+    }
+}
+
+// A price post in its three transactions, each with the signers it needs
+// beyond the payer
+struct PreparedPrice<'a> {
+    encoded_vaa: Keypair,
+    price_account: &'a Keypair,
+    write: Vec<Instruction>,
+    verify: Vec<Instruction>,
+    post: Vec<Instruction>,
+}
+
+impl PreparedPrice<'_> {
+    fn steps(&self) -> Vec<(Vec<Instruction>, Vec<&Keypair>)> {
+        vec![
+            (self.write.clone(), vec![&self.encoded_vaa]),
+            (self.verify.clone(), Vec::new()),
+            (self.post.clone(), vec![self.price_account]),
+        ]
+    }
+}
+
+// Why a send gave up: the cluster ruled against the transaction, or no
+// attempt got a ruling
+enum SendError {
+    Rejected(TransactionError),
+    Unreachable(String),
+}
+
+impl From<SendError> for String {
+    fn from(error: SendError) -> Self {
+        match error {
+            SendError::Rejected(error) => error.to_string(),
+            SendError::Unreachable(error) => error,
+        }
+    }
+}
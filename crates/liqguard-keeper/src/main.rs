@@ -0,0 +1,40 @@
+// keeper [--dry-run] [CONFIG]: run the LiqGuard keeper with the given
+// config file, keeper.toml by default. --dry-run simulates liquidations
+// instead of sending them
+mod config;
+mod failover;
+mod fees;
+mod jito;
+mod keeper;
+mod metrics;
+mod simulate;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use config::KeeperConfig;
+use keeper::Keeper;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    args.retain(|arg| arg != "--dry-run");
+    let path = PathBuf::from(args.first().map_or("keeper.toml", String::as_str));
+    let config = KeeperConfig::load(&path).map(|mut config| {
+        config.dry_run |= dry_run;
+        config
+    });
+    let result = match config.and_then(Keeper::new) {
+        Ok(keeper) => keeper.run().await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            log::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// Prometheus metrics and a health check, served over plain HTTP on
+// metrics_addr. /metrics renders the text exposition format; /healthz
+// answers 503 once the price loop has stalled, so operators can alert
+// before claims are missed
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use liqguard::UnderlyingAsset;
+use log::{info, warn};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const ASSETS: [UnderlyingAsset; 3] = [
+    UnderlyingAsset::Btc,
+    UnderlyingAsset::Eth,
+    UnderlyingAsset::Sol,
+];
+
+#[derive(Default)]
+pub struct Metrics {
+    pub policies_tracked: AtomicU64,
+    pub liquidations_attempted: AtomicU64,
+    pub liquidations_succeeded: AtomicU64,
+    pub rpc_errors: AtomicU64,
+    pub risk_alerts: AtomicU64, // alerts raised by the last risk check
+    oracle_latency_ms: [AtomicI64; 3], // per asset, by seed: now - publish_time of the last Hermes price
+    last_tick: AtomicI64,              // unix seconds the price loop last completed
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+impl Metrics {
+    pub fn inc(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_oracle_latency(&self, asset: UnderlyingAsset, publish_time: i64) {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as i64)
+            .unwrap_or_default();
+        self.oracle_latency_ms[asset.seed()[0] as usize]
+            .store(now_ms - publish_time * 1_000, Ordering::Relaxed);
+    }
+
+    pub fn record_tick(&self) {
+        self.last_tick.store(unix_now(), Ordering::Relaxed);
+    }
+
+    // Healthy while the price loop completed within max_tick_age seconds
+    pub fn healthy(&self, now: i64, max_tick_age: i64) -> bool {
+        let last_tick = self.last_tick.load(Ordering::Relaxed);
+        last_tick > 0 && now - last_tick <= max_tick_age
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = writeln!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}"
+            );
+        };
+        metric(
+            "liqguard_keeper_policies_tracked",
+            "gauge",
+            "Policies the keeper is tracking",
+            self.policies_tracked.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "liqguard_keeper_liquidations_attempted_total",
+            "counter",
+            "liquidate_policy transactions sent",
+            self.liquidations_attempted
+                .load(Ordering::Relaxed)
+                .to_string(),
+        );
+        metric(
+            "liqguard_keeper_liquidations_succeeded_total",
+            "counter",
+            "liquidate_policy transactions confirmed",
+            self.liquidations_succeeded
+                .load(Ordering::Relaxed)
+                .to_string(),
+        );
+        metric(
+            "liqguard_keeper_rpc_errors_total",
+            "counter",
+            "Failed RPC requests",
+            self.rpc_errors.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "liqguard_keeper_risk_alerts",
+            "gauge",
+            "Alerts raised by the last risk check",
+            self.risk_alerts.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "liqguard_keeper_last_tick_timestamp_seconds",
+            "gauge",
+            "Unix time the price loop last completed",
+            self.last_tick.load(Ordering::Relaxed).to_string(),
+        );
+        let name = "liqguard_keeper_oracle_latency_seconds";
+        let _ = writeln!(
+            out,
+            "# HELP {name} Age of the last Hermes price when it was fetched\n# TYPE {name} gauge"
+        );
+        for asset in ASSETS {
+            let latency_ms =
+                self.oracle_latency_ms[asset.seed()[0] as usize].load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "{name}{{asset=\"{}\"}} {:.3}",
+                format!("{asset:?}").to_ascii_lowercase(),
+                latency_ms as f64 / 1_000.0
+            );
+        }
+        out
+    }
+}
+
+// Serve /metrics and /healthz until the process exits
+pub async fn serve(addr: String, metrics: Arc<Metrics>, max_tick_age: i64) {
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            warn!("metrics server can't bind {addr}: {error}");
+            return;
+        }
+    };
+    info!("serving metrics on {addr}");
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            let Ok(read) = stream.read(&mut request).await else {
+                return;
+            };
+            let response = respond(&request[..read], &metrics, unix_now(), max_tick_age);
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+fn respond(request: &[u8], metrics: &Metrics, now: i64, max_tick_age: i64) -> String {
+    let request_line = std::str::from_utf8(request)
+        .unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            ("200 OK", "text/plain; version=0.0.4", metrics.render())
+        }
+        (Some("GET"), Some("/healthz")) if metrics.healthy(now, max_tick_age) => {
+            ("200 OK", "text/plain", "ok\n".to_string())
+        }
+        (Some("GET"), Some("/healthz")) => (
+            "503 Service Unavailable",
+            "text/plain",
+            "stalled\n".to_string(),
+        ),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_exposes_counters_and_per_asset_latency() {
+        let metrics = Metrics::default();
+        Metrics::inc(&metrics.liquidations_attempted);
+        Metrics::inc(&metrics.liquidations_attempted);
+        metrics.oracle_latency_ms[2].store(1_500, Ordering::Relaxed);
+        let text = metrics.render();
+        assert!(text.contains("liqguard_keeper_liquidations_attempted_total 2\n"));
+        assert!(text.contains("liqguard_keeper_oracle_latency_seconds{asset=\"sol\"} 1.500\n"));
+        assert!(text.contains("# TYPE liqguard_keeper_rpc_errors_total counter\n"));
+    }
+
+    #[test]
+    fn healthz_fails_once_the_loop_stalls() {
+        let metrics = Metrics::default();
+        let get = |now| respond(b"GET /healthz HTTP/1.1\r\n\r\n", &metrics, now, 30);
+        assert!(get(100).starts_with("HTTP/1.1 503"));
+        metrics.last_tick.store(100, Ordering::Relaxed);
+        assert!(get(130).starts_with("HTTP/1.1 200"));
+        assert!(get(131).starts_with("HTTP/1.1 503"));
+        assert!(respond(b"GET /nope HTTP/1.1", &metrics, 100, 30).starts_with("HTTP/1.1 404"));
+    }
+}
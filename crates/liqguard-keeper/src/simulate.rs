@@ -0,0 +1,71 @@
+// Dry runs: every liquidation the keeper would send is simulated against
+// current cluster state instead, and the outcome logged. Nothing is posted,
+// so simulations price against Pyth's sponsored push feeds rather than the
+// keeper's own update accounts
+
+use base64::Engine;
+use liqguard_client::errors::ProgramError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+// What a simulated liquidation would have done
+pub struct Simulation {
+    pub error: Option<TransactionError>,
+    pub program_error: Option<ProgramError>, // error, if liqguard raised it
+    pub units_consumed: Option<u64>,
+    pub payout: Option<u64>, // liquidate_policy's return value, in collateral units
+    pub logs: Vec<String>,
+}
+
+// Simulate without checking signatures, on the latest blockhash
+pub async fn simulate(rpc: &RpcClient, transaction: &Transaction) -> Result<Simulation, String> {
+    let result = rpc
+        .simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(rpc.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .await
+        .map_err(|error| error.to_string())?
+        .value;
+    let logs = result.logs.unwrap_or_default();
+    Ok(Simulation {
+        payout: result
+            .return_data
+            .filter(|_| result.err.is_none())
+            .and_then(|return_data| decode_u64(&return_data.data.0)),
+        program_error: result
+            .err
+            .as_ref()
+            .and_then(|error| ProgramError::parse(error, Some(&logs))),
+        error: result.err,
+        units_consumed: result.units_consumed,
+        logs,
+    })
+}
+
+// A u64 return value, base64-encoded in the simulation response
+fn decode_u64(data: &str) -> Option<u64> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .ok()?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_u64_reads_little_endian_return_data() {
+        let data = base64::engine::general_purpose::STANDARD.encode(1_500_000_000u64.to_le_bytes());
+        assert_eq!(decode_u64(&data), Some(1_500_000_000));
+        assert_eq!(decode_u64("AQID"), None);
+        assert_eq!(decode_u64("not base64!"), None);
+    }
+}
@@ -0,0 +1,379 @@
+// What a policy holder should hear about, worked out from policy updates
+// and prices as they arrive. Each condition alerts once when it starts
+// and stays quiet until it clears: a near-strike alert re-arms once the
+// price moves back out past twice the threshold, so a price hovering at
+// the line doesn't alert on every tick
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+use liqguard::{OraclePrice, Policy, TriggerDirection, UnderlyingAsset};
+use liqguard_common::price::align;
+
+const BPS: i128 = 10_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Alert {
+    // Spot is within the threshold of a strike; headroom is how far it
+    // can still move before the policy triggers, negative once past it
+    NearStrike {
+        policy: Pubkey,
+        owner: Pubkey,
+        asset: UnderlyingAsset,
+        price: OraclePrice,
+        headroom_bps: i128,
+    },
+    // A liquidation settled the policy
+    Liquidated {
+        policy: Pubkey,
+        owner: Pubkey,
+        price: OraclePrice,
+        payout: u64,
+    },
+    // Still live, but its vault doesn't hold its coverage
+    Underfunded {
+        policy: Pubkey,
+        owner: Pubkey,
+        coverage: u64,
+    },
+}
+
+impl Alert {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Alert::NearStrike { .. } => "near_strike",
+            Alert::Liquidated { .. } => "liquidated",
+            Alert::Underfunded { .. } => "underfunded",
+        }
+    }
+
+    pub fn policy(&self) -> Pubkey {
+        match self {
+            Alert::NearStrike { policy, .. }
+            | Alert::Liquidated { policy, .. }
+            | Alert::Underfunded { policy, .. } => *policy,
+        }
+    }
+
+    pub fn owner(&self) -> Pubkey {
+        match self {
+            Alert::NearStrike { owner, .. }
+            | Alert::Liquidated { owner, .. }
+            | Alert::Underfunded { owner, .. } => *owner,
+        }
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Alert::NearStrike {
+                policy,
+                asset,
+                price,
+                headroom_bps,
+                ..
+            } if *headroom_bps <= 0 => write!(
+                f,
+                "policy {policy}: {asset:?} at {} is past its strike",
+                decimal(*price)
+            ),
+            Alert::NearStrike {
+                policy,
+                asset,
+                price,
+                headroom_bps,
+                ..
+            } => write!(
+                f,
+                "policy {policy}: {asset:?} at {} is {}.{:02}% from its strike",
+                decimal(*price),
+                headroom_bps / 100,
+                headroom_bps % 100
+            ),
+            Alert::Liquidated {
+                policy,
+                price,
+                payout,
+                ..
+            } => write!(
+                f,
+                "policy {policy} was liquidated at {}, paying {payout}",
+                decimal(*price)
+            ),
+            Alert::Underfunded {
+                policy, coverage, ..
+            } => write!(
+                f,
+                "policy {policy}: vault doesn't hold its {coverage} coverage"
+            ),
+        }
+    }
+}
+
+// price written out exactly, e.g. 95000.12345678
+pub fn decimal(price: OraclePrice) -> String {
+    if price.exponent >= 0 {
+        return (price.mantissa * 10i128.pow(price.exponent as u32)).to_string();
+    }
+    let scale = 10i128.pow(price.exponent.unsigned_abs());
+    let sign = if price.mantissa < 0 { "-" } else { "" };
+    let (whole, fraction) = (price.mantissa.abs() / scale, price.mantissa.abs() % scale);
+    format!(
+        "{sign}{whole}.{fraction:0width$}",
+        width = price.exponent.unsigned_abs() as usize
+    )
+}
+
+// How far, in bps of the strike, spot can move before policy triggers;
+// negative once it has crossed. OutsideRange takes the nearer edge.
+// Trigger buffers and confidence shifts are ignored, and health-triggered
+// policies have no strike to be near
+pub fn headroom_bps(policy: &Policy, spot: OraclePrice) -> Option<i128> {
+    if policy.kamino_obligation != Pubkey::default() {
+        return None;
+    }
+    let above = |strike: OraclePrice| {
+        let (spot, strike) = align(spot, strike)?;
+        (strike != 0).then(|| (spot - strike) * BPS / strike)
+    };
+    match policy.direction {
+        TriggerDirection::ProtectLong => above(policy.strike()),
+        TriggerDirection::ProtectShort => above(policy.strike()).map(|distance| -distance),
+        TriggerDirection::OutsideRange => {
+            let lower = above(policy.strike())?;
+            let upper = -above(policy.upper_strike())?;
+            Some(lower.min(upper))
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tracked {
+    policy: Option<Policy>,
+    near_strike: bool,
+    underfunded: bool,
+}
+
+pub struct Watcher {
+    near_strike_bps: u16,
+    underfunded_after_seconds: i64,
+    tracked: HashMap<Pubkey, Tracked>,
+}
+
+impl Watcher {
+    // Alert within near_strike_bps of a strike, and on policies still
+    // unfunded underfunded_after_seconds after they opened
+    pub fn new(near_strike_bps: u16, underfunded_after_seconds: i64) -> Self {
+        Self {
+            near_strike_bps,
+            underfunded_after_seconds,
+            tracked: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracked.is_empty()
+    }
+
+    // Track policy's latest state. A settlement it didn't have when last
+    // seen is a liquidation; the first sighting only sets the baseline
+    pub fn observe_policy(&mut self, address: Pubkey, policy: Policy) -> Option<Alert> {
+        let tracked = self.tracked.entry(address).or_default();
+        let settled = tracked.policy.as_ref().is_some_and(|previous| {
+            policy.settlement_slot != 0 && policy.settlement_slot != previous.settlement_slot
+        });
+        let alert = settled.then(|| Alert::Liquidated {
+            policy: address,
+            owner: policy.owner,
+            price: OraclePrice::new(policy.settlement_price, policy.settlement_price_exponent),
+            payout: policy.settlement_payout,
+        });
+        tracked.policy = Some(policy);
+        alert
+    }
+
+    // Near-strike alerts for asset's live policies at price
+    pub fn observe_price(
+        &mut self,
+        asset: UnderlyingAsset,
+        price: OraclePrice,
+        now: i64,
+    ) -> Vec<Alert> {
+        let threshold = self.near_strike_bps as i128;
+        let mut alerts = Vec::new();
+        for (address, tracked) in &mut self.tracked {
+            let Some(policy) = tracked.policy.as_ref() else {
+                continue;
+            };
+            if policy.asset != asset || policy.is_terminal(now) {
+                continue;
+            }
+            let Some(headroom_bps) = headroom_bps(policy, price) else {
+                continue;
+            };
+            if headroom_bps > 2 * threshold {
+                tracked.near_strike = false;
+            } else if headroom_bps <= threshold && !tracked.near_strike {
+                tracked.near_strike = true;
+                alerts.push(Alert::NearStrike {
+                    policy: *address,
+                    owner: policy.owner,
+                    asset,
+                    price,
+                    headroom_bps,
+                });
+            }
+        }
+        alerts
+    }
+
+    // Underfunded alerts for live policies whose vault isn't funded past
+    // the grace period
+    pub fn check_funding(&mut self, now: i64) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for (address, tracked) in &mut self.tracked {
+            let Some(policy) = tracked.policy.as_ref() else {
+                continue;
+            };
+            let underfunded = !policy.is_funded
+                && !policy.is_terminal(now)
+                && now.saturating_sub(policy.created_at) >= self.underfunded_after_seconds;
+            if underfunded && !tracked.underfunded {
+                alerts.push(Alert::Underfunded {
+                    policy: *address,
+                    owner: policy.owner,
+                    coverage: policy.coverage_remaining,
+                });
+            }
+            tracked.underfunded = underfunded;
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard::STRIKE_EXPONENT;
+    use liqguard_test_support::zeroed_policy;
+
+    const DOLLAR: i64 = 100_000_000; // $1 at STRIKE_EXPONENT
+
+    // A funded ProtectLong BTC policy struck at $100, live until t = 1_000
+    fn policy() -> Policy {
+        let mut policy = zeroed_policy();
+        policy.asset = UnderlyingAsset::Btc;
+        policy.direction = TriggerDirection::ProtectLong;
+        policy.strike_price = 100 * DOLLAR as u64;
+        policy.coverage_amount = 1_000;
+        policy.coverage_remaining = 1_000;
+        policy.expiration_datetime = 1_000;
+        policy.is_funded = true;
+        policy
+    }
+
+    fn usd(dollars: i64) -> OraclePrice {
+        OraclePrice::new(dollars * DOLLAR, STRIKE_EXPONENT)
+    }
+
+    #[test]
+    fn headroom_points_toward_the_trigger() {
+        let mut policy = policy();
+        assert_eq!(headroom_bps(&policy, usd(105)), Some(500));
+        assert_eq!(headroom_bps(&policy, usd(90)), Some(-1_000));
+        policy.direction = TriggerDirection::ProtectShort;
+        assert_eq!(headroom_bps(&policy, usd(90)), Some(1_000));
+        policy.direction = TriggerDirection::OutsideRange;
+        policy.upper_strike_price = 200 * DOLLAR as u64;
+        assert_eq!(headroom_bps(&policy, usd(190)), Some(500));
+        assert_eq!(headroom_bps(&policy, usd(110)), Some(1_000));
+        policy.kamino_obligation = Pubkey::new_unique();
+        assert_eq!(headroom_bps(&policy, usd(110)), None);
+    }
+
+    #[test]
+    fn near_strike_alerts_once_until_the_price_backs_off() {
+        let mut watcher = Watcher::new(500, 3_600);
+        let address = Pubkey::new_unique();
+        assert!(watcher.observe_policy(address, policy()).is_none());
+        assert!(watcher
+            .observe_price(UnderlyingAsset::Btc, usd(120), 0)
+            .is_empty());
+        let alerts = watcher.observe_price(UnderlyingAsset::Btc, usd(104), 0);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind(), "near_strike");
+        assert!(alerts[0].to_string().ends_with("is 4.00% from its strike"));
+        // Hovering, then backing off less than twice the threshold
+        assert!(watcher
+            .observe_price(UnderlyingAsset::Btc, usd(103), 0)
+            .is_empty());
+        assert!(watcher
+            .observe_price(UnderlyingAsset::Btc, usd(108), 0)
+            .is_empty());
+        assert!(watcher
+            .observe_price(UnderlyingAsset::Btc, usd(104), 0)
+            .is_empty());
+        // Re-armed past it
+        watcher.observe_price(UnderlyingAsset::Btc, usd(111), 0);
+        assert_eq!(
+            watcher
+                .observe_price(UnderlyingAsset::Btc, usd(99), 0)
+                .len(),
+            1
+        );
+        // Other assets and expired policies stay quiet
+        watcher.observe_price(UnderlyingAsset::Btc, usd(111), 0);
+        assert!(watcher
+            .observe_price(UnderlyingAsset::Eth, usd(99), 0)
+            .is_empty());
+        assert!(watcher
+            .observe_price(UnderlyingAsset::Btc, usd(99), 1_000)
+            .is_empty());
+    }
+
+    #[test]
+    fn new_settlements_alert_as_liquidations() {
+        let mut watcher = Watcher::new(500, 3_600);
+        let (fresh, old) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut settled = policy();
+        settled.settlement_slot = 10;
+        settled.settlement_price = 90 * DOLLAR as i128;
+        settled.settlement_price_exponent = STRIKE_EXPONENT;
+        settled.settlement_payout = 1_000;
+        // Already settled when first seen
+        assert!(watcher.observe_policy(old, settled.clone()).is_none());
+        assert!(watcher.observe_policy(old, settled.clone()).is_none());
+
+        assert!(watcher.observe_policy(fresh, policy()).is_none());
+        let alert = watcher.observe_policy(fresh, settled).unwrap();
+        assert_eq!(
+            alert.to_string(),
+            format!("policy {fresh} was liquidated at 90.00000000, paying 1000")
+        );
+    }
+
+    #[test]
+    fn unfunded_policies_alert_after_the_grace_period() {
+        let mut watcher = Watcher::new(500, 100);
+        let address = Pubkey::new_unique();
+        let mut unfunded = policy();
+        unfunded.is_funded = false;
+        watcher.observe_policy(address, unfunded.clone());
+        assert!(watcher.check_funding(50).is_empty());
+        assert_eq!(watcher.check_funding(100).len(), 1);
+        assert!(watcher.check_funding(200).is_empty());
+        unfunded.is_funded = true;
+        watcher.observe_policy(address, unfunded.clone());
+        assert!(watcher.check_funding(300).is_empty());
+        unfunded.is_funded = false;
+        watcher.observe_policy(address, unfunded);
+        assert_eq!(watcher.check_funding(400).len(), 1);
+        assert!(watcher.check_funding(1_000).is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+// The notifier's config file (TOML): endpoints, which assets to price,
+// the alert thresholds and where alerts go. See notifier.example.toml
+
+use std::path::Path;
+use std::str::FromStr;
+
+use liqguard::UnderlyingAsset;
+use liqguard_notifier::AlertConfig;
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+#[derive(Debug, Deserialize)]
+pub struct NotifierConfig {
+    pub rpc_url: String,
+    pub ws_url: String,
+    #[serde(default = "default_commitment")]
+    pub commitment: String,
+    #[serde(default)]
+    pub assets: Vec<String>,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(flatten)]
+    pub alerts: AlertConfig,
+}
+
+fn default_commitment() -> String {
+    "confirmed".to_string()
+}
+
+fn default_poll_interval_ms() -> u64 {
+    10_000
+}
+
+impl NotifierConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("reading {}: {error}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|error| error.to_string())?;
+        config.commitment()?;
+        config.assets()?;
+        config.alerts.owners()?;
+        Ok(config)
+    }
+
+    pub fn commitment(&self) -> Result<CommitmentConfig, String> {
+        CommitmentConfig::from_str(&self.commitment)
+            .map_err(|_| format!("unknown commitment {:?}", self.commitment))
+    }
+
+    pub fn assets(&self) -> Result<Vec<UnderlyingAsset>, String> {
+        if self.assets.is_empty() {
+            return Ok(vec![
+                UnderlyingAsset::Btc,
+                UnderlyingAsset::Eth,
+                UnderlyingAsset::Sol,
+            ]);
+        }
+        self.assets
+            .iter()
+            .map(|asset| match asset.to_ascii_lowercase().as_str() {
+                "btc" => Ok(UnderlyingAsset::Btc),
+                "eth" => Ok(UnderlyingAsset::Eth),
+                "sol" => Ok(UnderlyingAsset::Sol),
+                _ => Err(format!("unknown asset {asset:?}")),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard_notifier::sinks::Sink;
+
+    #[test]
+    fn example_config_parses() {
+        let config = NotifierConfig::parse(include_str!("../notifier.example.toml")).unwrap();
+        assert_eq!(config.assets().unwrap().len(), 3);
+        assert_eq!(config.alerts.near_strike_bps, 500);
+        assert_eq!(config.alerts.sinks.len(), 3);
+        assert!(matches!(config.alerts.sinks[2], Sink::Telegram { .. }));
+    }
+
+    #[test]
+    fn parse_rejects_bad_owners_and_sinks() {
+        let base = "rpc_url = \"a\"\nws_url = \"b\"\n";
+        let config = NotifierConfig::parse(base).unwrap();
+        assert_eq!(config.alerts.underfunded_after_seconds, 3_600);
+        assert!(config.alerts.sinks.is_empty());
+        assert!(NotifierConfig::parse(&format!("{base}owners = [\"me\"]")).is_err());
+        let sink = "[[sinks]]\nkind = \"pager\"\nurl = \"c\"\n";
+        assert!(NotifierConfig::parse(&format!("{base}{sink}")).is_err());
+    }
+}
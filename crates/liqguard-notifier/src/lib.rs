@@ -0,0 +1,123 @@
+// Alerts for LiqGuard policies: when spot comes within a threshold of a
+// strike, when a policy is liquidated, and when a live policy's vault
+// isn't funded. alerts decides what to send from policy updates and
+// prices, sinks delivers it. Notifier ties the two together for the
+// notifier binary and for the keeper, which feeds it what it already
+// tracks
+pub mod alerts;
+pub mod sinks;
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use liqguard::{OraclePrice, Policy, UnderlyingAsset};
+use log::warn;
+use serde::Deserialize;
+
+use alerts::{Alert, Watcher};
+use sinks::Sink;
+
+// The alerting settings the notifier and the keeper's [notifier] table share
+#[derive(Debug, Deserialize)]
+pub struct AlertConfig {
+    #[serde(default = "default_near_strike_bps")]
+    pub near_strike_bps: u16,
+    #[serde(default = "default_underfunded_after_seconds")]
+    pub underfunded_after_seconds: i64,
+    // Only alert on these owners' policies; empty alerts on every policy
+    #[serde(default)]
+    pub owners: Vec<String>,
+    #[serde(default)]
+    pub sinks: Vec<Sink>,
+}
+
+fn default_near_strike_bps() -> u16 {
+    500
+}
+
+fn default_underfunded_after_seconds() -> i64 {
+    3_600
+}
+
+impl AlertConfig {
+    pub fn owners(&self) -> Result<HashSet<Pubkey>, String> {
+        self.owners
+            .iter()
+            .map(|owner| {
+                owner
+                    .parse()
+                    .map_err(|_| format!("owner {owner:?} is not a pubkey"))
+            })
+            .collect()
+    }
+}
+
+// A sink slower than this is given up on, so the caller's loop doesn't stall
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Notifier {
+    http: reqwest::Client,
+    sinks: Vec<Sink>,
+    owners: HashSet<Pubkey>,
+    watcher: Mutex<Watcher>,
+}
+
+impl Notifier {
+    pub fn new(config: &AlertConfig) -> Result<Self, String> {
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .timeout(SEND_TIMEOUT)
+                .build()
+                .map_err(|error| error.to_string())?,
+            sinks: config.sinks.clone(),
+            owners: config.owners()?,
+            watcher: Mutex::new(Watcher::new(
+                config.near_strike_bps,
+                config.underfunded_after_seconds,
+            )),
+        })
+    }
+
+    fn watcher(&self) -> std::sync::MutexGuard<'_, Watcher> {
+        self.watcher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn tracked(&self) -> usize {
+        self.watcher().len()
+    }
+
+    pub async fn observe_policy(&self, address: Pubkey, policy: Policy) {
+        if !self.owners.is_empty() && !self.owners.contains(&policy.owner) {
+            return;
+        }
+        let alert = self.watcher().observe_policy(address, policy);
+        self.send(alert.into_iter().collect()).await;
+    }
+
+    pub async fn observe_price(&self, asset: UnderlyingAsset, price: OraclePrice, now: i64) {
+        let alerts = self.watcher().observe_price(asset, price, now);
+        self.send(alerts).await;
+    }
+
+    pub async fn check_funding(&self, now: i64) {
+        let alerts = self.watcher().check_funding(now);
+        self.send(alerts).await;
+    }
+
+    // Deliver each alert to every sink. A sink that fails is logged and
+    // skipped; the alert isn't retried
+    async fn send(&self, alerts: Vec<Alert>) {
+        for alert in &alerts {
+            log::info!("alert: {alert}");
+            for sink in &self.sinks {
+                if let Err(error) = sink.send(&self.http, alert).await {
+                    warn!("{error}");
+                }
+            }
+        }
+    }
+}
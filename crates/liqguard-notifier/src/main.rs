@@ -0,0 +1,103 @@
+// notifier [CONFIG]: watch LiqGuard policies and alert on them with the
+// given config file, notifier.toml by default. Policies are followed over
+// a program subscription and priced off Pyth's sponsored push feeds, so
+// nothing is signed or sent on chain
+mod config;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use liqguard_client::rpc::LiqGuardClient;
+use liqguard_client::subscribe::{self, SubscribeConfig, Update};
+use liqguard_notifier::Notifier;
+use log::{info, warn};
+use solana_client::nonblocking::rpc_client::RpcClient;
+
+use config::NotifierConfig;
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+// Load every policy into the notifier, as after a (re)connect
+async fn refresh(client: &LiqGuardClient, notifier: &Notifier) -> Result<(), String> {
+    let policies = client
+        .policies(&[])
+        .await
+        .map_err(|error| error.to_string())?;
+    for (address, policy) in policies {
+        notifier.observe_policy(address, policy).await;
+    }
+    info!("watching {} policies", notifier.tracked());
+    Ok(())
+}
+
+async fn run(config: NotifierConfig) -> Result<(), String> {
+    let commitment = config.commitment()?;
+    let assets = config.assets()?;
+    let client = LiqGuardClient::new(RpcClient::new_with_commitment(
+        config.rpc_url.clone(),
+        commitment,
+    ));
+    let notifier = Notifier::new(&config.alerts)?;
+    if config.alerts.sinks.is_empty() {
+        warn!("no sinks configured, alerts are only logged");
+    }
+    let mut updates = subscribe::policies(
+        SubscribeConfig::new(config.ws_url.clone(), commitment),
+        Vec::new(),
+    );
+    refresh(&client, &notifier).await?;
+    let mut interval = tokio::time::interval(Duration::from_millis(config.poll_interval_ms));
+    loop {
+        tokio::select! {
+            update = updates.next() => match update {
+                Some(Update::Value { value, .. }) => {
+                    notifier.observe_policy(value.address, value.policy).await;
+                }
+                Some(Update::Reconnected) => {
+                    if let Err(error) = refresh(&client, &notifier).await {
+                        warn!("refresh after reconnect failed: {error}");
+                    }
+                }
+                None => return Err("policy subscription ended".to_string()),
+            },
+            _ = interval.tick() => {
+                let now = unix_now();
+                for asset in &assets {
+                    match client.sponsored_price(*asset).await {
+                        Ok(spot) => notifier.observe_price(*asset, spot.price, now).await,
+                        Err(error) => warn!("{asset:?}: no price: {error}"),
+                    }
+                }
+                notifier.check_funding(now).await;
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or("notifier.toml".to_string()),
+    );
+    let result = match NotifierConfig::load(&path) {
+        Ok(config) => run(config).await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            log::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
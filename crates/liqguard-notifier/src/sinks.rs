@@ -0,0 +1,125 @@
+// Where alerts go. A webhook gets every field as JSON, Slack the message
+// through an incoming webhook, and Telegram the message through a bot
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::alerts::Alert;
+
+const TELEGRAM_API: &str = "https://api.telegram.org";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Sink {
+    Webhook { url: String },
+    Slack { webhook_url: String },
+    Telegram { bot_token: String, chat_id: String },
+}
+
+impl Sink {
+    // The URL alert is POSTed to and its JSON body
+    pub fn request(&self, alert: &Alert) -> (String, Value) {
+        match self {
+            Sink::Webhook { url } => (url.clone(), payload(alert)),
+            Sink::Slack { webhook_url } => {
+                (webhook_url.clone(), json!({ "text": alert.to_string() }))
+            }
+            Sink::Telegram { bot_token, chat_id } => (
+                format!("{TELEGRAM_API}/bot{bot_token}/sendMessage"),
+                json!({ "chat_id": chat_id, "text": alert.to_string() }),
+            ),
+        }
+    }
+
+    pub async fn send(&self, http: &reqwest::Client, alert: &Alert) -> Result<(), String> {
+        let (url, body) = self.request(alert);
+        http.post(url)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| format!("{} sink: {}", self.name(), error.without_url()))?;
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Sink::Webhook { .. } => "webhook",
+            Sink::Slack { .. } => "slack",
+            Sink::Telegram { .. } => "telegram",
+        }
+    }
+}
+
+// Every field of alert, prices as exact decimal strings
+fn payload(alert: &Alert) -> Value {
+    let mut body = json!({
+        "kind": alert.kind(),
+        "policy": alert.policy().to_string(),
+        "owner": alert.owner().to_string(),
+        "message": alert.to_string(),
+    });
+    let fields = match alert {
+        Alert::NearStrike {
+            asset,
+            price,
+            headroom_bps,
+            ..
+        } => json!({
+            "asset": format!("{asset:?}").to_ascii_lowercase(),
+            "price": crate::alerts::decimal(*price),
+            "headroom_bps": headroom_bps,
+        }),
+        Alert::Liquidated { price, payout, .. } => json!({
+            "price": crate::alerts::decimal(*price),
+            "payout": payout,
+        }),
+        Alert::Underfunded { coverage, .. } => json!({ "coverage": coverage }),
+    };
+    if let (Some(body), Value::Object(fields)) = (body.as_object_mut(), fields) {
+        body.extend(fields);
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use liqguard::{OraclePrice, UnderlyingAsset};
+
+    #[test]
+    fn each_sink_shapes_its_request() {
+        let alert = Alert::NearStrike {
+            policy: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            asset: UnderlyingAsset::Sol,
+            price: OraclePrice::new(15_025i64, -2),
+            headroom_bps: 250,
+        };
+        let (url, body) = Sink::Webhook {
+            url: "https://example.com/hook".to_string(),
+        }
+        .request(&alert);
+        assert_eq!(url, "https://example.com/hook");
+        assert_eq!(body["kind"], "near_strike");
+        assert_eq!(body["asset"], "sol");
+        assert_eq!(body["price"], "150.25");
+        assert_eq!(body["headroom_bps"], 250);
+        assert_eq!(body["policy"], alert.policy().to_string());
+
+        let (_, body) = Sink::Slack {
+            webhook_url: "https://hooks.slack.com/x".to_string(),
+        }
+        .request(&alert);
+        assert_eq!(body, json!({ "text": alert.to_string() }));
+
+        let (url, body) = Sink::Telegram {
+            bot_token: "123:abc".to_string(),
+            chat_id: "-42".to_string(),
+        }
+        .request(&alert);
+        assert_eq!(url, "https://api.telegram.org/bot123:abc/sendMessage");
+        assert_eq!(body["chat_id"], "-42");
+    }
+}
@@ -0,0 +1,77 @@
+// Bachelier: options on a normally distributed forward F, whose volatility
+// sigma is in price units, paid at expiry and discounted at rate. With
+// s = sigma * sqrt(T) and d = (F - K) / s:
+//   call = e^-rT ((F - K) N(d) + s n(d)), put = e^-rT ((K - F) N(-d) + s n(d))
+// It prices strikes near or below zero, where Black-76 breaks down.
+// Expired or volatility-free options are worth their discounted payoff
+
+use crate::{norm_cdf, norm_pdf, Greeks, Inputs, OptionKind};
+
+// (d, s), or None once nothing is left to diffuse
+fn terms(inputs: &Inputs) -> Option<(f64, f64)> {
+    let s = inputs.volatility * inputs.years.max(0.0).sqrt();
+    if s <= 0.0 {
+        return None;
+    }
+    Some(((inputs.forward - inputs.strike) / s, s))
+}
+
+pub fn price(kind: OptionKind, inputs: &Inputs) -> f64 {
+    let Some((d, s)) = terms(inputs) else {
+        return inputs.settled(kind).0;
+    };
+    let moneyness = inputs.forward - inputs.strike;
+    let time_value = s * norm_pdf(d);
+    inputs.discount()
+        * match kind {
+            OptionKind::Call => moneyness * norm_cdf(d) + time_value,
+            OptionKind::Put => -moneyness * norm_cdf(-d) + time_value,
+        }
+}
+
+pub fn greeks(kind: OptionKind, inputs: &Inputs) -> Greeks {
+    let Some((d, s)) = terms(inputs) else {
+        return inputs.settled(kind).1;
+    };
+    let discount = inputs.discount();
+    let density = norm_pdf(d);
+    let delta = match kind {
+        OptionKind::Call => norm_cdf(d),
+        OptionKind::Put => norm_cdf(d) - 1.0,
+    };
+    Greeks {
+        delta: discount * delta,
+        gamma: discount * density / s,
+        vega: discount * density * inputs.years.sqrt(),
+        theta: inputs.rate * price(kind, inputs) - discount * density * s / (2.0 * inputs.years),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_match_the_closed_form_and_parity() {
+        let inputs = Inputs {
+            forward: 100.0,
+            strike: 100.0,
+            years: 1.0,
+            volatility: 20.0,
+            rate: 0.0,
+        };
+        // At the money: s / sqrt(2 pi)
+        let call = price(OptionKind::Call, &inputs);
+        assert!((call - 20.0 * norm_pdf(0.0)).abs() < 1e-12);
+
+        // Strikes below zero still price
+        let inputs = Inputs {
+            strike: -10.0,
+            rate: 0.05,
+            ..inputs
+        };
+        let parity = price(OptionKind::Call, &inputs) - price(OptionKind::Put, &inputs);
+        let forward_value = inputs.discount() * (inputs.forward - inputs.strike);
+        assert!((parity - forward_value).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,91 @@
+// Black-76: options on a lognormal forward F, paid at expiry and
+// discounted at rate. With s = sigma * sqrt(T):
+//   d1 = (ln(F/K) + s^2 / 2) / s, d2 = d1 - s
+//   call = e^-rT (F N(d1) - K N(d2)), put = e^-rT (K N(-d2) - F N(-d1))
+// Expired or volatility-free options are worth their discounted payoff
+
+use crate::{norm_cdf, norm_pdf, Greeks, Inputs, OptionKind};
+
+// (d1, d2, s), or None once nothing is left to diffuse
+fn terms(inputs: &Inputs) -> Option<(f64, f64, f64)> {
+    let s = inputs.volatility * inputs.years.max(0.0).sqrt();
+    if s <= 0.0 || inputs.forward <= 0.0 || inputs.strike <= 0.0 {
+        return None;
+    }
+    let d1 = ((inputs.forward / inputs.strike).ln() + s * s / 2.0) / s;
+    Some((d1, d1 - s, s))
+}
+
+pub fn price(kind: OptionKind, inputs: &Inputs) -> f64 {
+    let Some((d1, d2, _)) = terms(inputs) else {
+        return inputs.settled(kind).0;
+    };
+    let (forward, strike) = (inputs.forward, inputs.strike);
+    inputs.discount()
+        * match kind {
+            OptionKind::Call => forward * norm_cdf(d1) - strike * norm_cdf(d2),
+            OptionKind::Put => strike * norm_cdf(-d2) - forward * norm_cdf(-d1),
+        }
+}
+
+pub fn greeks(kind: OptionKind, inputs: &Inputs) -> Greeks {
+    let Some((d1, _, s)) = terms(inputs) else {
+        return inputs.settled(kind).1;
+    };
+    let discount = inputs.discount();
+    let density = norm_pdf(d1);
+    let delta = match kind {
+        OptionKind::Call => norm_cdf(d1),
+        OptionKind::Put => norm_cdf(d1) - 1.0,
+    };
+    // The undiscounted price's growth with T, the same for calls and puts
+    let time_value = inputs.forward * density * s / (2.0 * inputs.years);
+    Greeks {
+        delta: discount * delta,
+        gamma: discount * density / (inputs.forward * s),
+        vega: discount * inputs.forward * density * inputs.years.sqrt(),
+        theta: inputs.rate * price(kind, inputs) - discount * time_value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_match_the_closed_form_and_parity() {
+        let inputs = Inputs {
+            forward: 100.0,
+            strike: 100.0,
+            years: 1.0,
+            volatility: 0.2,
+            rate: 0.0,
+        };
+        // At the money: F (2 N(s / 2) - 1)
+        let call = price(OptionKind::Call, &inputs);
+        assert!((call - 7.965_567_5).abs() < 1e-5, "{call}");
+
+        let inputs = Inputs {
+            strike: 90.0,
+            rate: 0.05,
+            ..inputs
+        };
+        let parity = price(OptionKind::Call, &inputs) - price(OptionKind::Put, &inputs);
+        let forward_value = inputs.discount() * (inputs.forward - inputs.strike);
+        assert!((parity - forward_value).abs() < 1e-9);
+    }
+
+    #[test]
+    fn expired_options_pay_their_intrinsic_value() {
+        let inputs = Inputs {
+            forward: 80.0,
+            strike: 100.0,
+            years: 0.0,
+            volatility: 0.6,
+            rate: 0.05,
+        };
+        assert_eq!(price(OptionKind::Put, &inputs), 20.0);
+        assert_eq!(price(OptionKind::Call, &inputs), 0.0);
+        assert_eq!(greeks(OptionKind::Put, &inputs).delta, -1.0);
+    }
+}
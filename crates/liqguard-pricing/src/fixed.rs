@@ -0,0 +1,264 @@
+// Fixed-point premium model, the one the program quotes with. Prices
+// follow a driftless lognormal walk at the configured annual volatility,
+// so with d = |ln(K/S)| / (vol * sqrt(T)):
+// - a European digital finishes past the strike with probability N(-d)
+// - an American touch reaches it before expiry with probability 2 * N(-d)
+// Every quantity is scaled by SCALE (1e12) and each function returns None
+// on overflow or out-of-domain input
+
+pub const SCALE: i128 = 1_000_000_000_000;
+
+const BPS: i128 = 10_000;
+const SECONDS_PER_YEAR: i128 = 365 * 86_400;
+const LN_2: i128 = 693_147_180_560;
+const INV_SQRT_2PI: i128 = 398_942_280_401;
+
+// Past this many standard deviations N(-d) is zero at SCALE precision
+const MAX_D: i128 = 40 * SCALE;
+
+// ln(x) for x > 0: x = m * 2^k with m in [1, 2), and
+// ln(m) = 2 * atanh((m - 1) / (m + 1)) as a fast-converging odd series
+pub fn ln(x: i128) -> Option<i128> {
+    if x <= 0 {
+        return None;
+    }
+    let (mut m, mut k) = (x, 0i128);
+    while m >= 2 * SCALE {
+        m /= 2;
+        k += 1;
+    }
+    while m < SCALE {
+        m *= 2;
+        k -= 1;
+    }
+    let z = (m - SCALE) * SCALE / (m + SCALE);
+    let z2 = z * z / SCALE;
+    let (mut term, mut sum, mut n) = (z, 0i128, 1i128);
+    while term != 0 {
+        sum += term / n;
+        term = term * z2 / SCALE;
+        n += 2;
+    }
+    Some(2 * sum + k * LN_2)
+}
+
+// e^-y for y >= 0: y = k * ln2 + r, e^-y = e^-r / 2^k
+pub fn exp_neg(y: i128) -> Option<i128> {
+    if y < 0 {
+        return None;
+    }
+    let k = y / LN_2;
+    if k >= 64 {
+        return Some(0);
+    }
+    let r = y - k * LN_2;
+    let (mut term, mut sum, mut n) = (SCALE, SCALE, 1i128);
+    while term != 0 {
+        term = term * r / SCALE / n;
+        sum += if n % 2 == 1 { -term } else { term };
+        n += 1;
+    }
+    Some(sum >> k)
+}
+
+// Standard normal CDF (Abramowitz & Stegun 26.2.17, |error| < 7.5e-8)
+pub fn norm_cdf(x: i128) -> Option<i128> {
+    if x < 0 {
+        return Some(SCALE - norm_cdf(-x)?);
+    }
+    if x >= MAX_D {
+        return Some(SCALE);
+    }
+    const P: i128 = 231_641_900_000;
+    const B: [i128; 5] = [
+        319_381_530_000,
+        -356_563_782_000,
+        1_781_477_937_000,
+        -1_821_255_978_000,
+        1_330_274_429_000,
+    ];
+    let t = SCALE * SCALE / (SCALE + P * x / SCALE);
+    let pdf = INV_SQRT_2PI * exp_neg(x * x / SCALE / 2)? / SCALE;
+    let (mut t_pow, mut poly) = (t, 0i128);
+    for b in B {
+        poly += b * t_pow / SCALE;
+        t_pow = t_pow * t / SCALE;
+    }
+    Some((SCALE - pdf * poly / SCALE).clamp(0, SCALE))
+}
+
+// d = |ln(strike / spot)| / (vol * sqrt(T)), or None when there's no time
+// or volatility left for the price to move
+fn distance_in_sigmas(
+    spot: i128,
+    strike: i128,
+    volatility_bps: u32,
+    seconds: i64,
+) -> Option<Option<i128>> {
+    if spot <= 0 || strike <= 0 {
+        return None;
+    }
+    let log_distance = ln(strike.checked_mul(SCALE)? / spot)?.abs();
+    let years = (seconds.max(0) as i128).checked_mul(SCALE)? / SECONDS_PER_YEAR;
+    let sqrt_years = isqrt(years.checked_mul(SCALE)?);
+    let sigma_sqrt_t = volatility_bps as i128 * SCALE / BPS * sqrt_years / SCALE;
+    if sigma_sqrt_t == 0 {
+        return Some(None);
+    }
+    Some(Some(
+        (log_distance.checked_mul(SCALE)? / sigma_sqrt_t).min(MAX_D),
+    ))
+}
+
+// Probability that price is past the strike at expiry
+pub fn finish_probability(
+    spot: i128,
+    strike: i128,
+    volatility_bps: u32,
+    seconds: i64,
+) -> Option<i128> {
+    match distance_in_sigmas(spot, strike, volatility_bps, seconds)? {
+        Some(d) => norm_cdf(-d),
+        None => Some(0),
+    }
+}
+
+// Probability that price touches the strike at any point before expiry
+pub fn touch_probability(
+    spot: i128,
+    strike: i128,
+    volatility_bps: u32,
+    seconds: i64,
+) -> Option<i128> {
+    Some((2 * finish_probability(spot, strike, volatility_bps, seconds)?).min(SCALE))
+}
+
+// coverage * probability, marked up by markup_bps and capped at coverage
+pub fn premium(coverage: u64, probability: i128, markup_bps: u16) -> Option<u64> {
+    let fair = (coverage as i128).checked_mul(probability.clamp(0, SCALE))? / SCALE;
+    let marked_up = fair.checked_mul(BPS + markup_bps as i128)? / BPS;
+    Some(marked_up.min(coverage as i128) as u64)
+}
+
+// Extra markup for a pool at utilization_bps, on a kinked curve: rising
+// linearly to kink_markup_bps at kink_bps, then to full_markup_bps at 100%
+pub fn utilization_markup(
+    utilization_bps: u64,
+    kink_bps: u16,
+    kink_markup_bps: u16,
+    full_markup_bps: u16,
+) -> u16 {
+    let utilization = utilization_bps.min(BPS as u64) as i128;
+    let (kink, at_kink, at_full) = (
+        kink_bps as i128,
+        kink_markup_bps as i128,
+        full_markup_bps as i128,
+    );
+    let markup = if utilization <= kink {
+        at_kink * utilization / kink.max(1)
+    } else {
+        at_kink + (at_full - at_kink) * (utilization - kink) / (BPS - kink).max(1)
+    };
+    markup.clamp(0, u16::MAX as i128) as u16
+}
+
+fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Within 1e-7 of the float reference
+    fn assert_close(actual: i128, expected: f64) {
+        let expected = (expected * SCALE as f64) as i128;
+        assert!(
+            (actual - expected).abs() < 100_000,
+            "{actual} vs {expected}"
+        );
+    }
+
+    #[test]
+    fn ln_matches_reference_values() {
+        assert_eq!(ln(SCALE), Some(0));
+        assert_close(ln(2 * SCALE).unwrap(), std::f64::consts::LN_2);
+        assert_close(ln(SCALE / 10).unwrap(), (0.1f64).ln());
+        assert_close(ln(95 * SCALE / 100).unwrap(), (0.95f64).ln());
+        assert_close(ln(1_000 * SCALE).unwrap(), (1_000f64).ln());
+        assert_eq!(ln(0), None);
+    }
+
+    #[test]
+    fn exp_neg_matches_reference_values() {
+        assert_eq!(exp_neg(0), Some(SCALE));
+        assert_close(exp_neg(SCALE).unwrap(), (-1f64).exp());
+        assert_close(exp_neg(SCALE / 2).unwrap(), (-0.5f64).exp());
+        assert_close(exp_neg(10 * SCALE).unwrap(), (-10f64).exp());
+        assert_eq!(exp_neg(100 * SCALE), Some(0));
+    }
+
+    #[test]
+    fn norm_cdf_matches_reference_values() {
+        assert_close(norm_cdf(0).unwrap(), 0.5);
+        assert_close(norm_cdf(SCALE).unwrap(), 0.841_344_746);
+        assert_close(norm_cdf(-SCALE).unwrap(), 0.158_655_254);
+        assert_close(norm_cdf(-2 * SCALE).unwrap(), 0.022_750_132);
+        assert_eq!(norm_cdf(MAX_D), Some(SCALE));
+    }
+
+    #[test]
+    fn touch_is_twice_as_likely_as_finishing_past_the_strike() {
+        // 10% out of the money, 60% vol, 30 days
+        let finish = finish_probability(100_000, 90_000, 6_000, 30 * 86_400).unwrap();
+        let touch = touch_probability(100_000, 90_000, 6_000, 30 * 86_400).unwrap();
+        assert_eq!(touch, 2 * finish);
+        assert!(finish > 0 && touch < SCALE);
+    }
+
+    #[test]
+    fn probability_falls_with_distance_and_rises_with_time_and_vol() {
+        let near = touch_probability(100_000, 95_000, 6_000, 7 * 86_400).unwrap();
+        let far = touch_probability(100_000, 80_000, 6_000, 7 * 86_400).unwrap();
+        let longer = touch_probability(100_000, 80_000, 6_000, 90 * 86_400).unwrap();
+        let wilder = touch_probability(100_000, 80_000, 12_000, 7 * 86_400).unwrap();
+        assert!(near > far);
+        assert!(longer > far);
+        assert!(wilder > far);
+    }
+
+    #[test]
+    fn no_time_or_vol_means_no_touch() {
+        assert_eq!(touch_probability(100_000, 90_000, 6_000, 0), Some(0));
+        assert_eq!(touch_probability(100_000, 90_000, 0, 86_400), Some(0));
+    }
+
+    #[test]
+    fn premium_applies_markup_and_caps_at_coverage() {
+        assert_eq!(premium(1_000, SCALE / 10, 0), Some(100));
+        assert_eq!(premium(1_000, SCALE / 10, 2_500), Some(125));
+        assert_eq!(premium(1_000, SCALE, 2_500), Some(1_000));
+    }
+
+    #[test]
+    fn utilization_markup_steepens_past_the_kink() {
+        // 5% at 80% utilization, 50% when full
+        let markup = |utilization| utilization_markup(utilization, 8_000, 500, 5_000);
+        assert_eq!(markup(0), 0);
+        assert_eq!(markup(4_000), 250);
+        assert_eq!(markup(8_000), 500);
+        assert_eq!(markup(9_000), 2_750);
+        assert_eq!(markup(10_000), 5_000);
+        assert_eq!(markup(20_000), 5_000);
+        assert_eq!(utilization_markup(10_000, 0, 0, 0), 0);
+    }
+}
@@ -0,0 +1,293 @@
+// LiqGuard's pricing, in one crate so the program, the CLI and the quoting
+// backend agree on what a premium is:
+// - fixed: the integer premium model the program quotes with on-chain
+// - black76 and bachelier: vanilla option prices and greeks off a forward
+// - trigger_probability and suggest_premium: fixed's premium model in
+//   floating point, for quoting off-chain. It tracks fixed to within
+//   fixed's own 1e-7 error
+pub mod bachelier;
+pub mod black76;
+pub mod fixed;
+
+// Matches fixed's 365-day year
+pub const SECONDS_PER_YEAR: f64 = 365.0 * 86_400.0;
+
+const BPS: f64 = 10_000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionKind {
+    Call,
+    Put,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    Black76,
+    Bachelier,
+}
+
+// A vanilla option's terms. volatility is annual: a fraction of the
+// forward for Black-76 (0.6 = 60%), price units per sqrt(year) for
+// Bachelier. rate discounts the payoff and is continuously compounded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Inputs {
+    pub forward: f64,
+    pub strike: f64,
+    pub years: f64,
+    pub volatility: f64,
+    pub rate: f64,
+}
+
+// Sensitivities of an option's price
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Greeks {
+    pub delta: f64, // per unit of forward
+    pub gamma: f64, // per unit of forward, squared
+    pub vega: f64,  // per unit of volatility
+    pub theta: f64, // per year of calendar time; decay is negative
+}
+
+impl Model {
+    pub fn price(self, kind: OptionKind, inputs: &Inputs) -> f64 {
+        match self {
+            Model::Black76 => black76::price(kind, inputs),
+            Model::Bachelier => bachelier::price(kind, inputs),
+        }
+    }
+
+    pub fn greeks(self, kind: OptionKind, inputs: &Inputs) -> Greeks {
+        match self {
+            Model::Black76 => black76::greeks(kind, inputs),
+            Model::Bachelier => bachelier::greeks(kind, inputs),
+        }
+    }
+}
+
+impl Inputs {
+    fn discount(&self) -> f64 {
+        (-self.rate * self.years.max(0.0)).exp()
+    }
+
+    // Discounted price and greeks at expiry, or with no volatility: the
+    // payoff is already known
+    fn settled(&self, kind: OptionKind) -> (f64, Greeks) {
+        let discount = self.discount();
+        let (intrinsic, in_the_money) = match kind {
+            OptionKind::Call => (self.forward - self.strike, self.forward > self.strike),
+            OptionKind::Put => (self.strike - self.forward, self.forward < self.strike),
+        };
+        let delta = match (kind, in_the_money) {
+            (_, false) => 0.0,
+            (OptionKind::Call, true) => discount,
+            (OptionKind::Put, true) => -discount,
+        };
+        let price = discount * intrinsic.max(0.0);
+        let greeks = Greeks {
+            delta,
+            theta: self.rate * price,
+            ..Greeks::default()
+        };
+        (price, greeks)
+    }
+}
+
+// Years from now until expiration, both unix timestamps; 0 once expired
+pub fn years_until(now: i64, expiration: i64) -> f64 {
+    expiration.saturating_sub(now).max(0) as f64 / SECONDS_PER_YEAR
+}
+
+pub fn norm_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+// Standard normal CDF through erfc (Numerical Recipes' erfcc, relative
+// error < 1.2e-7)
+pub fn norm_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + z / 2.0);
+    let poly = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |sum, coefficient| coefficient + t * sum);
+    let erfc = t * (-z * z + poly).exp();
+    if x >= 0.0 {
+        1.0 - erfc / 2.0
+    } else {
+        erfc / 2.0
+    }
+}
+
+// What fires a policy, in the program's terms: ProtectLong at or below its
+// strike, ProtectShort at or above it, OutsideRange outside its band
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Trigger {
+    Below(f64),
+    Above(f64),
+    Outside(f64, f64),
+}
+
+impl Trigger {
+    pub fn crossed(self, spot: f64) -> bool {
+        match self {
+            Trigger::Below(strike) => spot <= strike,
+            Trigger::Above(strike) => spot >= strike,
+            Trigger::Outside(lower, upper) => spot <= lower || spot >= upper,
+        }
+    }
+}
+
+// Probability the trigger fires before expiry (touch, American policies)
+// or is past at expiry (European ones), as the program prices it: certain
+// once crossed, and a band sums its two sides. volatility is annual and
+// lognormal. None for a non-positive spot or strike
+pub fn trigger_probability(
+    spot: f64,
+    trigger: Trigger,
+    years: f64,
+    volatility: f64,
+    touch: bool,
+) -> Option<f64> {
+    let side = |strike: f64| -> Option<f64> {
+        if spot <= 0.0 || strike <= 0.0 {
+            return None;
+        }
+        let sigma_sqrt_t = volatility * years.max(0.0).sqrt();
+        if sigma_sqrt_t <= 0.0 {
+            return Some(0.0);
+        }
+        let finish = norm_cdf(-(strike / spot).ln().abs() / sigma_sqrt_t);
+        Some(if touch {
+            (2.0 * finish).min(1.0)
+        } else {
+            finish
+        })
+    };
+    let probability = match trigger {
+        Trigger::Below(strike) | Trigger::Above(strike) => side(strike)?,
+        Trigger::Outside(lower, upper) => (side(lower)? + side(upper)?).min(1.0),
+    };
+    Some(if trigger.crossed(spot) {
+        1.0
+    } else {
+        probability
+    })
+}
+
+// coverage * probability, marked up by markup_bps and capped at coverage
+pub fn suggest_premium(coverage: f64, probability: f64, markup_bps: u16) -> f64 {
+    let fair = coverage * probability.clamp(0.0, 1.0);
+    (fair * (BPS + markup_bps as f64) / BPS).min(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn norm_cdf_matches_reference_values() {
+        for (x, expected) in [
+            (0.0, 0.5),
+            (1.0, 0.841_344_746),
+            (-1.0, 0.158_655_254),
+            (-2.0, 0.022_750_132),
+            (3.0, 0.998_650_102),
+        ] {
+            assert!((norm_cdf(x) - expected).abs() < 1e-7, "N({x})");
+        }
+    }
+
+    #[test]
+    fn float_premiums_track_the_fixed_point_model() {
+        let spot = 100_000.0;
+        for (strike, volatility_bps, days) in [(90_000, 6_000, 30), (99_000, 3_000, 1)] {
+            let seconds = days * 86_400;
+            let years = years_until(0, seconds);
+            let volatility = volatility_bps as f64 / BPS;
+            let trigger = Trigger::Below(strike as f64);
+            let touch = trigger_probability(spot, trigger, years, volatility, true).unwrap();
+            let finish = trigger_probability(spot, trigger, years, volatility, false).unwrap();
+            let scale = fixed::SCALE as f64;
+            let fixed_touch =
+                fixed::touch_probability(100_000, strike, volatility_bps, seconds).unwrap();
+            let fixed_finish =
+                fixed::finish_probability(100_000, strike, volatility_bps, seconds).unwrap();
+            assert!((touch - fixed_touch as f64 / scale).abs() < 1e-6);
+            assert!((finish - fixed_finish as f64 / scale).abs() < 1e-6);
+
+            let premium = suggest_premium(1e9, touch, 2_500);
+            let fixed_premium = fixed::premium(1_000_000_000, fixed_touch, 2_500).unwrap();
+            assert!((premium - fixed_premium as f64).abs() < 1e4);
+        }
+    }
+
+    #[test]
+    fn crossed_triggers_are_certain_and_bands_sum_their_sides() {
+        let years = 30.0 / 365.0;
+        let below = trigger_probability(100.0, Trigger::Below(90.0), years, 0.6, true).unwrap();
+        let above = trigger_probability(100.0, Trigger::Above(110.0), years, 0.6, true).unwrap();
+        let band =
+            trigger_probability(100.0, Trigger::Outside(90.0, 110.0), years, 0.6, true).unwrap();
+        assert!((band - (below + above).min(1.0)).abs() < 1e-12);
+        assert_eq!(
+            trigger_probability(89.0, Trigger::Below(90.0), years, 0.6, false),
+            Some(1.0)
+        );
+        assert_eq!(
+            trigger_probability(100.0, Trigger::Below(90.0), 0.0, 0.6, true),
+            Some(0.0)
+        );
+        assert_eq!(
+            trigger_probability(0.0, Trigger::Below(90.0), years, 0.6, true),
+            None
+        );
+        assert_eq!(suggest_premium(1_000.0, 0.9, 2_500), 1_000.0);
+    }
+
+    // Greeks against central differences of the price, for both models
+    #[test]
+    fn greeks_match_finite_differences() {
+        let cases = [
+            (Model::Black76, 0.6),
+            (Model::Bachelier, 60.0), // the same scale of move, in price units
+        ];
+        for (model, volatility) in cases {
+            for kind in [OptionKind::Call, OptionKind::Put] {
+                let inputs = Inputs {
+                    forward: 100.0,
+                    strike: 95.0,
+                    years: 0.25,
+                    volatility,
+                    rate: 0.05,
+                };
+                let greeks = model.greeks(kind, &inputs);
+                let price = |inputs: Inputs| model.price(kind, &inputs);
+                let h = 1e-3;
+                let bumped = |f: fn(&mut Inputs, f64)| {
+                    let (mut up, mut down) = (inputs, inputs);
+                    f(&mut up, h);
+                    f(&mut down, -h);
+                    (price(up), price(down))
+                };
+                let (up, down) = bumped(|inputs, h| inputs.forward += h);
+                assert!((greeks.delta - (up - down) / (2.0 * h)).abs() < 1e-5);
+                let gamma = (up - 2.0 * price(inputs) + down) / (h * h);
+                assert!((greeks.gamma - gamma).abs() < 1e-3);
+                let (up, down) = bumped(|inputs, h| inputs.volatility += h);
+                assert!((greeks.vega - (up - down) / (2.0 * h)).abs() < 1e-4);
+                let (up, down) = bumped(|inputs, h| inputs.years -= h);
+                assert!((greeks.theta - (up - down) / (2.0 * h)).abs() < 1e-3);
+            }
+        }
+    }
+}
@@ -0,0 +1,468 @@
+// Aggregate risk over LiqGuard policies, as read from the chain or the
+// indexer: what every open policy could pay out, per asset and per
+// underwriter, under a grid of price shocks and over a horizon at a
+// confidence level. Amounts never mix denominations: lamports, USD at
+// STRIKE_EXPONENT and each SPL mint are reported apart. Assets move
+// independently, and an underwriter's figures add up each asset's, which
+// can only overstate a diversified book. See scenario.rs for how payouts
+// are estimated
+pub mod scenario;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+use liqguard::{OraclePrice, Policy, UnderlyingAsset};
+use liqguard_client::accounts::PolicyStatus;
+use serde::{Serialize, Serializer};
+
+use scenario::{lognormal_factors, payout_at, quantile, scaled, shocked};
+
+const BPS: u128 = 10_000;
+
+// What a policy's coverage is counted in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Denomination {
+    Lamports,
+    Usd, // at STRIKE_EXPONENT, paid in lamports at claim time
+    Token(Pubkey),
+}
+
+impl Denomination {
+    pub fn of(policy: &Policy) -> Self {
+        if policy.usd_coverage {
+            Denomination::Usd
+        } else if policy.is_native() {
+            Denomination::Lamports
+        } else {
+            Denomination::Token(policy.collateral_mint)
+        }
+    }
+}
+
+impl fmt::Display for Denomination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Denomination::Lamports => write!(f, "lamports"),
+            Denomination::Usd => write!(f, "usd"),
+            Denomination::Token(mint) => write!(f, "{mint}"),
+        }
+    }
+}
+
+impl Serialize for Denomination {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// An asset's current price and annual volatility
+#[derive(Clone, Copy, Debug)]
+pub struct Market {
+    pub asset: UnderlyingAsset,
+    pub spot: OraclePrice,
+    pub volatility_bps: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct RiskParams {
+    pub shocks_bps: Vec<i32>, // price moves to stress, -2_000 = down 20%
+    pub horizon_seconds: i64, // how far ahead value at risk looks
+    pub confidence_bps: u16,  // share of outcomes value at risk covers
+}
+
+impl Default for RiskParams {
+    fn default() -> Self {
+        Self {
+            shocks_bps: vec![
+                -9_000, -7_500, -5_000, -3_000, -2_000, -1_000, -500, 500, 1_000, 2_000, 3_000,
+                5_000, 10_000, 20_000,
+            ],
+            horizon_seconds: 86_400,
+            confidence_bps: 9_900,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Scenario {
+    pub shock_bps: i32,
+    pub payout: u64,
+}
+
+// Open policies on one asset, in one denomination
+#[derive(Clone, Debug, Serialize)]
+pub struct AssetRisk {
+    pub asset: UnderlyingAsset,
+    pub denomination: Denomination,
+    pub policies: usize,
+    pub coverage: u64,        // what every policy has left, paid in full
+    pub payout_at_spot: u64,  // owed at the current price, not yet settled
+    pub worst_payout: u64,    // largest payout across the scenarios
+    pub value_at_risk: u64,   // payout at confidence over the horizon
+    pub expected_payout: u64, // mean payout over the horizon
+    pub scenarios: Vec<Scenario>,
+}
+
+// What one underwriter's open policies could cost it, in one denomination
+#[derive(Clone, Debug, Serialize)]
+pub struct UnderwriterRisk {
+    pub underwriter: String,
+    pub denomination: Denomination,
+    pub policies: usize,
+    pub coverage: u64,
+    pub worst_payout: u64,  // each asset's worst scenario, summed
+    pub value_at_risk: u64, // collateral that covers the horizon at confidence
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RiskReport {
+    pub assets: Vec<AssetRisk>,
+    pub underwriters: Vec<UnderwriterRisk>,
+    pub unpriced: usize, // open policies on assets without a Market
+}
+
+// Report conditions an operator should hear about
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Alert {
+    // Breached at the current price but not yet settled
+    Owed {
+        asset: UnderlyingAsset,
+        denomination: Denomination,
+        amount: u64,
+    },
+    // Value at risk above the limit's share of open coverage
+    ValueAtRisk {
+        asset: UnderlyingAsset,
+        denomination: Denomination,
+        value_at_risk: u64,
+        coverage: u64,
+    },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Alert::Owed {
+                asset,
+                denomination,
+                amount,
+            } => write!(
+                f,
+                "{asset:?}: {amount} {denomination} owed at spot, unsettled"
+            ),
+            Alert::ValueAtRisk {
+                asset,
+                denomination,
+                value_at_risk,
+                coverage,
+            } => write!(
+                f,
+                "{asset:?}: value at risk {value_at_risk} {denomination} of {coverage} covered"
+            ),
+        }
+    }
+}
+
+// Policies sharing a denomination, and their combined payout at each price
+#[derive(Default)]
+struct Book {
+    policies: usize,
+    coverage: u64,
+    payouts: Vec<u64>,
+}
+
+impl Book {
+    fn add(&mut self, policy: &Policy, payouts: &[u64]) {
+        self.policies += 1;
+        self.coverage = self.coverage.saturating_add(policy.coverage_remaining);
+        self.payouts.resize(payouts.len(), 0);
+        for (total, payout) in self.payouts.iter_mut().zip(payouts) {
+            *total = total.saturating_add(*payout);
+        }
+    }
+}
+
+// Where each kind of price sits in a Book's payouts
+struct Prices {
+    prices: Vec<OraclePrice>,
+    shocks_bps: Vec<i32>,
+}
+
+impl Prices {
+    // spot, then each shock, then the horizon's samples
+    fn new(market: &Market, params: &RiskParams) -> Self {
+        let shocks: Vec<(i32, OraclePrice)> = params
+            .shocks_bps
+            .iter()
+            .filter_map(|shock| Some((*shock, shocked(market.spot, *shock)?)))
+            .collect();
+        let samples = lognormal_factors(market.volatility_bps, params.horizon_seconds)
+            .into_iter()
+            .map(|factor| scaled(market.spot, factor));
+        let prices = std::iter::once(market.spot)
+            .chain(shocks.iter().map(|(_, price)| *price))
+            .chain(samples)
+            .collect();
+        Self {
+            prices,
+            shocks_bps: shocks.into_iter().map(|(shock, _)| shock).collect(),
+        }
+    }
+
+    fn scenarios(&self, book: &Book) -> Vec<Scenario> {
+        self.shocks_bps
+            .iter()
+            .zip(&book.payouts[1..])
+            .map(|(shock_bps, payout)| Scenario {
+                shock_bps: *shock_bps,
+                payout: *payout,
+            })
+            .collect()
+    }
+
+    fn samples<'a>(&self, book: &'a Book) -> &'a [u64] {
+        &book.payouts[1 + self.shocks_bps.len()..]
+    }
+}
+
+fn worst(scenarios: &[Scenario]) -> u64 {
+    scenarios
+        .iter()
+        .map(|scenario| scenario.payout)
+        .max()
+        .unwrap_or(0)
+}
+
+fn mean(samples: &[u64]) -> u64 {
+    let total: u128 = samples.iter().map(|sample| *sample as u128).sum();
+    (total / samples.len().max(1) as u128) as u64
+}
+
+impl RiskReport {
+    // Risk of the policies open at now, priced off markets
+    pub fn build(
+        policies: &[(Pubkey, Policy)],
+        markets: &[Market],
+        params: &RiskParams,
+        now: i64,
+    ) -> Self {
+        let open: Vec<&Policy> = policies
+            .iter()
+            .map(|(_, policy)| policy)
+            .filter(|policy| PolicyStatus::of(policy, now) == PolicyStatus::Active)
+            .collect();
+        let mut assets = Vec::new();
+        let mut underwriters: BTreeMap<(Pubkey, Denomination), UnderwriterRisk> = BTreeMap::new();
+        for market in markets {
+            let prices = Prices::new(market, params);
+            let mut books: BTreeMap<Denomination, Book> = BTreeMap::new();
+            let mut underwriter_books: BTreeMap<(Pubkey, Denomination), Book> = BTreeMap::new();
+            for policy in open.iter().filter(|policy| policy.asset == market.asset) {
+                let payouts: Vec<u64> = prices
+                    .prices
+                    .iter()
+                    .map(|price| payout_at(policy, *price))
+                    .collect();
+                let denomination = Denomination::of(policy);
+                books.entry(denomination).or_default().add(policy, &payouts);
+                underwriter_books
+                    .entry((policy.underwriter, denomination))
+                    .or_default()
+                    .add(policy, &payouts);
+            }
+            for (denomination, book) in books {
+                let scenarios = prices.scenarios(&book);
+                let samples = prices.samples(&book);
+                assets.push(AssetRisk {
+                    asset: market.asset,
+                    denomination,
+                    policies: book.policies,
+                    coverage: book.coverage,
+                    payout_at_spot: book.payouts[0],
+                    worst_payout: worst(&scenarios),
+                    value_at_risk: quantile(samples, params.confidence_bps),
+                    expected_payout: mean(samples),
+                    scenarios,
+                });
+            }
+            for ((underwriter, denomination), book) in underwriter_books {
+                let risk = underwriters
+                    .entry((underwriter, denomination))
+                    .or_insert_with(|| UnderwriterRisk {
+                        underwriter: underwriter.to_string(),
+                        denomination,
+                        policies: 0,
+                        coverage: 0,
+                        worst_payout: 0,
+                        value_at_risk: 0,
+                    });
+                let var = quantile(prices.samples(&book), params.confidence_bps);
+                risk.policies += book.policies;
+                risk.coverage = risk.coverage.saturating_add(book.coverage);
+                risk.worst_payout = risk
+                    .worst_payout
+                    .saturating_add(worst(&prices.scenarios(&book)));
+                risk.value_at_risk = risk.value_at_risk.saturating_add(var);
+            }
+        }
+        let unpriced = open
+            .iter()
+            .filter(|policy| !markets.iter().any(|market| market.asset == policy.asset))
+            .count();
+        Self {
+            assets,
+            underwriters: underwriters.into_values().collect(),
+            unpriced,
+        }
+    }
+
+    // Unsettled breaches, and assets whose value at risk exceeds
+    // max_var_bps of their coverage (0 = no limit)
+    pub fn alerts(&self, max_var_bps: u16) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for risk in &self.assets {
+            if risk.payout_at_spot > 0 {
+                alerts.push(Alert::Owed {
+                    asset: risk.asset,
+                    denomination: risk.denomination,
+                    amount: risk.payout_at_spot,
+                });
+            }
+            if max_var_bps > 0
+                && risk.value_at_risk as u128 * BPS > risk.coverage as u128 * max_var_bps as u128
+            {
+                alerts.push(Alert::ValueAtRisk {
+                    asset: risk.asset,
+                    denomination: risk.denomination,
+                    value_at_risk: risk.value_at_risk,
+                    coverage: risk.coverage,
+                });
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liqguard::{PayoutMode, TriggerDirection, STRIKE_EXPONENT};
+    use liqguard_test_support::zeroed_policy;
+
+    const DOLLAR: u64 = 100_000_000; // $1 at STRIKE_EXPONENT
+
+    // A funded binary ProtectLong policy live until t = 1_000_000
+    fn policy(asset: UnderlyingAsset, strike: u64, coverage: u64, underwriter: Pubkey) -> Policy {
+        let mut policy = zeroed_policy();
+        policy.asset = asset;
+        policy.direction = TriggerDirection::ProtectLong;
+        policy.payout_mode = PayoutMode::Binary;
+        policy.strike_price = strike;
+        policy.coverage_amount = coverage;
+        policy.coverage_remaining = coverage;
+        policy.expiration_datetime = 1_000_000;
+        policy.is_funded = true;
+        policy.underwriter = underwriter;
+        policy
+    }
+
+    fn btc(spot_dollars: u64) -> Market {
+        Market {
+            asset: UnderlyingAsset::Btc,
+            spot: OraclePrice::new(spot_dollars * DOLLAR, STRIKE_EXPONENT),
+            volatility_bps: 6_000,
+        }
+    }
+
+    #[test]
+    fn stress_and_var_aggregate_per_asset_and_underwriter() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let policies: Vec<(Pubkey, Policy)> = [
+            policy(UnderlyingAsset::Btc, 90_000 * DOLLAR, 1_000, alice), // 10% down
+            policy(UnderlyingAsset::Btc, 50_000 * DOLLAR, 2_000, alice), // 50% down
+            policy(UnderlyingAsset::Btc, 95_000 * DOLLAR, 500, bob),     // 5% down
+            policy(UnderlyingAsset::Eth, 1_000 * DOLLAR, 700, bob),      // no market
+        ]
+        .into_iter()
+        .map(|policy| (Pubkey::new_unique(), policy))
+        .collect();
+        let report = RiskReport::build(&policies, &[btc(100_000)], &RiskParams::default(), 0);
+
+        assert_eq!(report.unpriced, 1);
+        let [btc] = &report.assets[..] else {
+            panic!("one BTC book expected");
+        };
+        assert_eq!(
+            (btc.policies, btc.coverage, btc.payout_at_spot),
+            (3, 3_500, 0)
+        );
+        let payout_at = |shock_bps| {
+            btc.scenarios
+                .iter()
+                .find(|scenario| scenario.shock_bps == shock_bps)
+                .unwrap()
+                .payout
+        };
+        assert_eq!(payout_at(-500), 0); // strictly past the strike pays
+        assert_eq!(payout_at(-1_000), 500);
+        assert_eq!(payout_at(-2_000), 1_500);
+        assert_eq!(payout_at(-7_500), 3_500);
+        assert_eq!(payout_at(1_000), 0);
+        assert_eq!(btc.worst_payout, 3_500);
+        // A day at 60% vol: ~3.1% daily sigma, so the 1% tail clears the
+        // 5% strike but not the 10% one
+        assert_eq!(btc.value_at_risk, 500);
+        assert!(btc.expected_payout < 500);
+
+        let alice_risk = report
+            .underwriters
+            .iter()
+            .find(|risk| risk.underwriter == alice.to_string())
+            .unwrap();
+        assert_eq!((alice_risk.policies, alice_risk.coverage), (2, 3_000));
+        assert_eq!(
+            (alice_risk.worst_payout, alice_risk.value_at_risk),
+            (3_000, 0)
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["assets"][0]["asset"], "btc");
+        assert_eq!(json["assets"][0]["denomination"], "lamports");
+    }
+
+    #[test]
+    fn alerts_flag_unsettled_breaches_and_var_over_limit() {
+        let underwriter = Pubkey::new_unique();
+        let policies = vec![
+            (
+                Pubkey::new_unique(),
+                policy(UnderlyingAsset::Btc, 95_000 * DOLLAR, 1_000, underwriter),
+            ),
+            (
+                Pubkey::new_unique(),
+                policy(UnderlyingAsset::Btc, 50_000 * DOLLAR, 9_000, underwriter),
+            ),
+        ];
+        let quiet = RiskReport::build(&policies, &[btc(100_000)], &RiskParams::default(), 0);
+        assert!(quiet.alerts(0).is_empty());
+        // VaR 1_000 of 10_000 covered: 10%
+        assert_eq!(quiet.alerts(1_001), vec![]);
+        assert!(matches!(quiet.alerts(999)[..], [Alert::ValueAtRisk { .. }]));
+
+        let breached = RiskReport::build(&policies, &[btc(94_000)], &RiskParams::default(), 0);
+        assert_eq!(
+            breached.alerts(0),
+            vec![Alert::Owed {
+                asset: UnderlyingAsset::Btc,
+                denomination: Denomination::Lamports,
+                amount: 1_000,
+            }]
+        );
+
+        // Expired policies carry no risk
+        let expired =
+            RiskReport::build(&policies, &[btc(94_000)], &RiskParams::default(), 2_000_000);
+        assert!(expired.assets.is_empty());
+    }
+}
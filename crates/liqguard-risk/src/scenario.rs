@@ -0,0 +1,121 @@
+// What open policies would owe at hypothetical prices. Each payout is the
+// program's own trigger check and payout curve at that price, net of
+// earlier claims. Confidence shifts, TWAP and second-oracle checks and
+// untouched knock-in barriers are ignored, so payouts are an upper bound
+
+use liqguard::{is_triggered, payout_amount, OraclePrice, Policy};
+use liqguard_pricing::{norm_cdf, years_until};
+
+const BPS: i128 = 10_000;
+
+// Equally likely outcomes a value at risk is read off
+pub const VAR_SAMPLES: usize = 1_000;
+
+// Owed by policy were it settled at price. A payout the math can't
+// represent counts as everything the policy has left
+pub fn payout_at(policy: &Policy, price: OraclePrice) -> u64 {
+    match is_triggered(policy, price) {
+        Ok(true) => payout_amount(policy, price)
+            .map_or(policy.coverage_remaining, |owed| policy.claimable(owed)),
+        Ok(false) => 0,
+        Err(_) => policy.coverage_remaining,
+    }
+}
+
+// price moved shock_bps, e.g. -2_000 = down 20%. None past -100%
+pub fn shocked(price: OraclePrice, shock_bps: i32) -> Option<OraclePrice> {
+    let factor = BPS + shock_bps as i128;
+    if factor < 0 {
+        return None;
+    }
+    Some(OraclePrice {
+        mantissa: price.mantissa.checked_mul(factor)? / BPS,
+        exponent: price.exponent,
+    })
+}
+
+// price times factor, rounded onto its exponent
+pub fn scaled(price: OraclePrice, factor: f64) -> OraclePrice {
+    OraclePrice {
+        mantissa: (price.mantissa as f64 * factor).round() as i128,
+        exponent: price.exponent,
+    }
+}
+
+// Price multipliers over horizon_seconds at an annual volatility, one per
+// equally likely slice of the driftless lognormal walk the program prices
+// premiums with, taken at each slice's midpoint
+pub fn lognormal_factors(volatility_bps: u32, horizon_seconds: i64) -> Vec<f64> {
+    let sigma = volatility_bps as f64 / BPS as f64 * years_until(0, horizon_seconds).sqrt();
+    (0..VAR_SAMPLES)
+        .map(|sample| {
+            let quantile = (sample as f64 + 0.5) / VAR_SAMPLES as f64;
+            (sigma * inverse_norm_cdf(quantile)).exp()
+        })
+        .collect()
+}
+
+// The confidence_bps quantile of losses: the loss only the worst
+// 1 - confidence of outcomes exceed
+pub fn quantile(losses: &[u64], confidence_bps: u16) -> u64 {
+    let mut sorted = losses.to_vec();
+    sorted.sort_unstable();
+    let index = sorted.len() * confidence_bps as usize / BPS as usize;
+    sorted
+        .get(index.min(sorted.len().saturating_sub(1)))
+        .copied()
+        .unwrap_or(0)
+}
+
+// N^-1(p), bisected on norm_cdf; |x| <= 10 covers every p a sample asks for
+fn inverse_norm_cdf(p: f64) -> f64 {
+    let (mut low, mut high) = (-10.0, 10.0);
+    for _ in 0..64 {
+        let mid = (low + high) / 2.0;
+        if norm_cdf(mid) < p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shocks_scale_the_mantissa() {
+        let price = OraclePrice::new(10_000_000i64, -8);
+        assert_eq!(shocked(price, -2_000).unwrap().mantissa, 8_000_000);
+        assert_eq!(shocked(price, 5_000).unwrap().mantissa, 15_000_000);
+        assert_eq!(shocked(price, -10_000).unwrap().mantissa, 0);
+        assert!(shocked(price, -10_001).is_none());
+        assert_eq!(scaled(price, 0.5).mantissa, 5_000_000);
+    }
+
+    #[test]
+    fn lognormal_factors_are_symmetric_in_log_space() {
+        let factors = lognormal_factors(6_000, 86_400);
+        assert_eq!(factors.len(), VAR_SAMPLES);
+        assert!(factors.windows(2).all(|pair| pair[0] < pair[1]));
+        let (first, last) = (factors[0], factors[VAR_SAMPLES - 1]);
+        assert!((first.ln() + last.ln()).abs() < 1e-9);
+        // The 0.05% slice sits ~3.29 daily sigmas out
+        let daily_sigma = 0.6 / 365f64.sqrt();
+        assert!((last.ln() / daily_sigma - 3.29).abs() < 0.01);
+        assert!(lognormal_factors(0, 86_400)
+            .iter()
+            .all(|factor| *factor == 1.0));
+    }
+
+    #[test]
+    fn quantiles_leave_the_tail_above() {
+        let losses: Vec<u64> = (0..100).rev().collect();
+        assert_eq!(quantile(&losses, 9_900), 99);
+        assert_eq!(quantile(&losses, 9_000), 90);
+        assert_eq!(quantile(&losses, 0), 0);
+        assert_eq!(quantile(&[], 9_900), 0);
+    }
+}
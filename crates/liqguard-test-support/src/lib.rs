@@ -0,0 +1,277 @@
+// Pyth account fixtures for Rust tests: builders for PriceUpdateV2 and
+// TwapUpdate accounts at a chosen price, confidence, exponent and publish
+// time, serialized exactly as the receiver writes them, so the program's
+// oracle checks run against them unchanged, and a zeroed Policy for unit
+// tests of code that reads one. With the program-test or litesvm feature,
+// fixtures load straight into either test harness
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use liqguard::Policy;
+use liqguard_common::layout::DISCRIMINATOR_LEN;
+use liqguard_common::UnderlyingAsset;
+use pyth_solana_receiver_sdk::price_update::{
+    PriceFeedMessage, PriceUpdateV2, TwapPrice, TwapUpdate, VerificationLevel,
+};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+
+// An account a fixture builds, owned by the Pyth receiver and sized like
+// the ones it posts
+pub trait Fixture {
+    fn data(&self) -> Vec<u8>;
+
+    fn account(&self) -> Account {
+        Account {
+            lamports: Rent::default().minimum_balance(self.data().len()),
+            data: self.data(),
+            owner: pyth_solana_receiver_sdk::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+}
+
+fn serialize(account: &impl AccountSerialize, len: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(len);
+    account
+        .try_serialize(&mut data)
+        .expect("fixture serializes");
+    // The receiver allocates room for a partial verification level, which
+    // serializes a byte longer than a full one
+    data.resize(len, 0);
+    data
+}
+
+#[derive(Clone)]
+pub struct PriceUpdateBuilder {
+    update: PriceUpdateV2,
+}
+
+impl PriceUpdateBuilder {
+    // A fully verified update of feed_id at price 0, exponent -8, published
+    // at time 0. Set the fields a test cares about on top
+    pub fn new(feed_id: [u8; 32]) -> Self {
+        Self {
+            update: PriceUpdateV2 {
+                write_authority: Pubkey::default(),
+                verification_level: VerificationLevel::Full,
+                price_message: PriceFeedMessage {
+                    feed_id,
+                    price: 0,
+                    conf: 0,
+                    exponent: -8,
+                    publish_time: 0,
+                    prev_publish_time: 0,
+                    ema_price: 0,
+                    ema_conf: 0,
+                },
+                posted_slot: 0,
+            },
+        }
+    }
+
+    pub fn asset(asset: UnderlyingAsset) -> Self {
+        Self::new(asset.feed_id())
+    }
+
+    // Spot and EMA both at price, so deviation checks stay quiet unless a
+    // test moves the EMA with ema()
+    pub fn price(mut self, price: i64, exponent: i32) -> Self {
+        self.update.price_message.price = price;
+        self.update.price_message.ema_price = price;
+        self.update.price_message.exponent = exponent;
+        self
+    }
+
+    pub fn conf(mut self, conf: u64) -> Self {
+        self.update.price_message.conf = conf;
+        self.update.price_message.ema_conf = conf;
+        self
+    }
+
+    pub fn ema(mut self, ema_price: i64, ema_conf: u64) -> Self {
+        self.update.price_message.ema_price = ema_price;
+        self.update.price_message.ema_conf = ema_conf;
+        self
+    }
+
+    pub fn publish_time(mut self, publish_time: i64) -> Self {
+        self.update.price_message.publish_time = publish_time;
+        self.update.price_message.prev_publish_time = publish_time.saturating_sub(1);
+        self
+    }
+
+    pub fn partially_verified(mut self, num_signatures: u8) -> Self {
+        self.update.verification_level = VerificationLevel::Partial { num_signatures };
+        self
+    }
+
+    pub fn posted_slot(mut self, posted_slot: u64) -> Self {
+        self.update.posted_slot = posted_slot;
+        self
+    }
+
+    pub fn write_authority(mut self, write_authority: Pubkey) -> Self {
+        self.update.write_authority = write_authority;
+        self
+    }
+
+    pub fn build(&self) -> PriceUpdateV2 {
+        self.update.clone()
+    }
+}
+
+impl Fixture for PriceUpdateBuilder {
+    fn data(&self) -> Vec<u8> {
+        serialize(&self.update, PriceUpdateV2::LEN)
+    }
+}
+
+#[derive(Clone)]
+pub struct TwapUpdateBuilder {
+    update: TwapUpdate,
+}
+
+impl TwapUpdateBuilder {
+    // A TWAP of feed_id at price 0, exponent -8, over an empty window at
+    // time 0 with no missed slots
+    pub fn new(feed_id: [u8; 32]) -> Self {
+        Self {
+            update: TwapUpdate {
+                write_authority: Pubkey::default(),
+                twap: TwapPrice {
+                    feed_id,
+                    start_time: 0,
+                    end_time: 0,
+                    price: 0,
+                    conf: 0,
+                    exponent: -8,
+                    down_slots_ratio: 0,
+                },
+            },
+        }
+    }
+
+    pub fn asset(asset: UnderlyingAsset) -> Self {
+        Self::new(asset.feed_id())
+    }
+
+    pub fn price(mut self, price: i64, exponent: i32) -> Self {
+        self.update.twap.price = price;
+        self.update.twap.exponent = exponent;
+        self
+    }
+
+    pub fn conf(mut self, conf: u64) -> Self {
+        self.update.twap.conf = conf;
+        self
+    }
+
+    // The window the TWAP averages over, ending at end_time
+    pub fn window(mut self, start_time: i64, end_time: i64) -> Self {
+        self.update.twap.start_time = start_time;
+        self.update.twap.end_time = end_time;
+        self
+    }
+
+    // Out of 1_000_000, the share of the window's slots that were missed
+    pub fn down_slots_ratio(mut self, down_slots_ratio: u32) -> Self {
+        self.update.twap.down_slots_ratio = down_slots_ratio;
+        self
+    }
+
+    pub fn write_authority(mut self, write_authority: Pubkey) -> Self {
+        self.update.write_authority = write_authority;
+        self
+    }
+
+    pub fn build(&self) -> TwapUpdate {
+        self.update.clone()
+    }
+}
+
+impl Fixture for TwapUpdateBuilder {
+    fn data(&self) -> Vec<u8> {
+        serialize(&self.update, TwapUpdate::LEN)
+    }
+}
+
+// A Policy with every field zeroed, as the program's allocated account
+// reads before it's written. Set the fields a test cares about on top
+pub fn zeroed_policy() -> Policy {
+    let data = vec![0u8; DISCRIMINATOR_LEN + Policy::LEN];
+    Policy::try_deserialize_unchecked(&mut &data[..]).expect("zeroed policy decodes")
+}
+
+// A test harness fixtures can be written into before the test runs
+pub trait LoadFixture {
+    fn load(&mut self, address: Pubkey, fixture: &impl Fixture);
+}
+
+#[cfg(feature = "program-test")]
+impl LoadFixture for solana_program_test::ProgramTest {
+    fn load(&mut self, address: Pubkey, fixture: &impl Fixture) {
+        self.add_account(address, fixture.account());
+    }
+}
+
+#[cfg(feature = "litesvm")]
+impl LoadFixture for litesvm::LiteSVM {
+    fn load(&mut self, address: Pubkey, fixture: &impl Fixture) {
+        self.set_account(address, fixture.account())
+            .expect("fixture account loads");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Clock;
+
+    #[test]
+    fn price_updates_read_back_through_the_receiver_sdk() {
+        let fixture = PriceUpdateBuilder::asset(UnderlyingAsset::Btc)
+            .price(9_500_000_000_000, -8)
+            .conf(2_000_000_000)
+            .publish_time(1_700_000_000);
+        let account = fixture.account();
+        assert_eq!(account.owner, pyth_solana_receiver_sdk::ID);
+        assert_eq!(account.data.len(), PriceUpdateV2::LEN);
+
+        let update = PriceUpdateV2::try_deserialize(&mut &account.data[..]).unwrap();
+        assert!(update.verification_level.gte(VerificationLevel::Full));
+        let clock = Clock {
+            unix_timestamp: 1_700_000_020,
+            ..Clock::default()
+        };
+        let feed_id = UnderlyingAsset::Btc.feed_id();
+        let price = update
+            .get_price_no_older_than(&clock, 30, &feed_id)
+            .unwrap();
+        assert_eq!(
+            (price.price, price.conf, price.exponent),
+            (9_500_000_000_000, 2_000_000_000, -8)
+        );
+        assert!(update
+            .get_price_no_older_than(&clock, 10, &feed_id)
+            .is_err());
+
+        let partial = fixture.partially_verified(5).data();
+        let update = PriceUpdateV2::try_deserialize(&mut &partial[..]).unwrap();
+        assert!(!update.verification_level.gte(VerificationLevel::Full));
+    }
+
+    #[test]
+    fn twap_updates_read_back_through_the_receiver_sdk() {
+        let data = TwapUpdateBuilder::asset(UnderlyingAsset::Eth)
+            .price(300_000_000_000, -8)
+            .window(1_700_000_000, 1_700_003_600)
+            .data();
+        assert_eq!(data.len(), TwapUpdate::LEN);
+
+        let update = TwapUpdate::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(update.twap.feed_id, UnderlyingAsset::Eth.feed_id());
+        assert_eq!(update.twap.end_time - update.twap.start_time, 3_600);
+        assert_eq!(update.twap.price, 300_000_000_000);
+    }
+}
@@ -0,0 +1,42 @@
+// Writes the program id and deploy authority the program is built with
+// into OUT_DIR/ids.rs, so forks and per-environment deployments set them
+// from the environment instead of editing declare_id! in source:
+//
+//   LIQGUARD_PROGRAM_ID  program id, default the localnet id
+//   LIQGUARD_AUTHORITY   the only key initialize_config accepts, default
+//                        any signer
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_PROGRAM_ID: &str = "EAVJioMyQKbHEKNAr5kRg4e4gFahmgwd9bWVCBD4Svnc";
+
+// A base58 pubkey from variable, or default when it's unset or empty.
+// declare_id! checks the length; this only catches typos early with a
+// message naming the variable
+fn pubkey_var(variable: &str) -> Option<String> {
+    println!("cargo:rerun-if-env-changed={variable}");
+    let value = env::var(variable).ok()?.trim().to_string();
+    if value.is_empty() {
+        return None;
+    }
+    const ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    if !(32..=44).contains(&value.len()) || !value.chars().all(|c| ALPHABET.contains(c)) {
+        panic!("{variable} must be a base58 pubkey, got {value:?}");
+    }
+    Some(value)
+}
+
+fn main() {
+    let program_id = pubkey_var("LIQGUARD_PROGRAM_ID").unwrap_or(DEFAULT_PROGRAM_ID.to_string());
+    let authority = match pubkey_var("LIQGUARD_AUTHORITY") {
+        Some(authority) => format!("Some(anchor_lang::pubkey!(\"{authority}\"))"),
+        None => "None".to_string(),
+    };
+    let ids = format!(
+        "declare_id!(\"{program_id}\");\n\n\
+         pub const DEPLOY_AUTHORITY: Option<Pubkey> = {authority};\n"
+    );
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    fs::write(Path::new(&out_dir).join("ids.rs"), ids).expect("writing ids.rs");
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+use crate::{LiqGuardError, OraclePrice};
+
+// Chainlink OCR2 store program that owns feed (Transmissions) accounts
+// (HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny)
+pub const CHAINLINK_STORE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    241, 75, 246, 90, 213, 107, 210, 186, 113, 94, 69, 116, 44, 35, 31, 39, 214, 54, 33, 207, 91,
+    119, 143, 55, 193, 162, 72, 149, 29, 23, 86, 2,
+]);
+
+// Anchor discriminator of Transmissions: sha256("account:Transmissions")[..8]
+const TRANSMISSIONS_DISCRIMINATOR: [u8; 8] = [96, 179, 69, 66, 128, 129, 73, 117];
+
+// Byte offsets into a Transmissions account (including the discriminator).
+// A packed header (version, state, owner, proposed_owner, writer,
+// description, ...) is followed by a ring buffer of live rounds starting
+// at HEADER_SIZE
+const STATE_OFFSET: usize = 9;
+const DECIMALS_OFFSET: usize = 138;
+const LATEST_ROUND_ID_OFFSET: usize = 143;
+const LIVE_LENGTH_OFFSET: usize = 148;
+const LIVE_CURSOR_OFFSET: usize = 152;
+const HEADER_SIZE: usize = 8 + 192;
+
+// A Transmission: slot u64, timestamp u32, padding, answer i128, padding
+const TRANSMISSION_SIZE: usize = 48;
+const TRANSMISSION_TIMESTAMP_OFFSET: usize = 8;
+const TRANSMISSION_ANSWER_OFFSET: usize = 16;
+
+// Transmissions.state of a feed still accepting rounds; the owner can
+// freeze a feed it is retiring
+const STATE_NORMAL: u8 = 0;
+
+pub struct ChainlinkRound {
+    pub round_id: u32,
+    pub answer: i128, // scaled by 10^decimals
+    pub decimals: u8,
+    pub timestamp: i64,
+}
+
+// Read the latest round out of a Chainlink feed, rejecting foreign or
+// malformed accounts, frozen feeds, rounds without a positive answer and
+// rounds older than max_age seconds
+pub fn get_price_no_older_than(
+    feed: &AccountInfo,
+    clock: &Clock,
+    max_age: u64,
+) -> Result<ChainlinkRound> {
+    require_keys_eq!(
+        *feed.owner,
+        CHAINLINK_STORE_PROGRAM_ID,
+        LiqGuardError::InvalidChainlinkFeed
+    );
+
+    let data = feed.try_borrow_data()?;
+    require!(
+        data.len() >= HEADER_SIZE && data[..8] == TRANSMISSIONS_DISCRIMINATOR,
+        LiqGuardError::InvalidChainlinkFeed
+    );
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    require!(
+        data[STATE_OFFSET] == STATE_NORMAL,
+        LiqGuardError::ChainlinkFeedFrozen
+    );
+
+    // The cursor points at the slot the next round will be written to
+    let live_length = read_u32(LIVE_LENGTH_OFFSET) as usize;
+    let live_cursor = read_u32(LIVE_CURSOR_OFFSET) as usize;
+    require!(
+        live_length > 0
+            && live_cursor < live_length
+            && data.len() >= HEADER_SIZE + live_length * TRANSMISSION_SIZE,
+        LiqGuardError::InvalidChainlinkFeed
+    );
+    let latest = (live_cursor + live_length - 1) % live_length;
+    let round = HEADER_SIZE + latest * TRANSMISSION_SIZE;
+
+    let timestamp = read_u32(round + TRANSMISSION_TIMESTAMP_OFFSET) as i64;
+    let answer = i128::from_le_bytes(
+        data[round + TRANSMISSION_ANSWER_OFFSET..round + TRANSMISSION_ANSWER_OFFSET + 16]
+            .try_into()
+            .unwrap(),
+    );
+    let round_id = read_u32(LATEST_ROUND_ID_OFFSET);
+    require!(
+        round_id > 0 && timestamp > 0 && answer > 0,
+        LiqGuardError::InvalidChainlinkRound
+    );
+    require!(
+        timestamp.saturating_add(max_age as i64) >= clock.unix_timestamp,
+        LiqGuardError::PriceStale
+    );
+
+    Ok(ChainlinkRound {
+        round_id,
+        answer,
+        decimals: data[DECIMALS_OFFSET],
+        timestamp,
+    })
+}
+
+impl ChainlinkRound {
+    pub fn price(&self) -> OraclePrice {
+        OraclePrice::new(self.answer, -(self.decimals as i32))
+    }
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+// SPL Account Compression program that owns concurrent Merkle trees
+// (cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KSNc)
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    0, 40, 115, 26, 46, 184, 88, 197, 106, 20, 134, 156, 12, 63, 249, 158, 193, 175, 229, 209, 36,
+    238, 218, 0, 86, 15, 182, 84, 121, 109, 241, 137,
+]);
+
+// SPL Noop program the compression program logs tree changelogs through
+// (noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV)
+pub const NOOP_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    11, 188, 15, 192, 187, 71, 202, 47, 116, 196, 17, 46, 148, 171, 19, 207, 163, 198, 52, 229,
+    220, 23, 234, 203, 3, 205, 26, 35, 205, 126, 120, 124,
+]);
+
+// Anchor instruction discriminators: sha256("global:<name>")[..8]
+const INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+const APPEND_DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+const REPLACE_LEAF_DISCRIMINATOR: [u8; 8] = [204, 165, 76, 100, 73, 147, 0, 128];
+
+// Value of a spent leaf. Settled and released policies are replaced with
+// it, so the same leaf can't be proven twice
+pub const EMPTY_LEAF: [u8; 32] = [0; 32];
+
+// The accounts every compression CPI needs. The authority is the PDA the
+// tree was created under and signs with authority_seeds
+pub struct MerkleTree<'a, 'info> {
+    pub compression_program: &'a AccountInfo<'info>,
+    pub noop_program: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub authority_seeds: &'a [&'a [u8]],
+}
+
+impl<'a, 'info> MerkleTree<'a, 'info> {
+    // Initialize a client-allocated tree account with our PDA as authority
+    pub fn init_empty(&self, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+        let mut data = INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&max_depth.to_le_bytes());
+        data.extend_from_slice(&max_buffer_size.to_le_bytes());
+        self.invoke(data, &[])
+    }
+
+    // Append a leaf at the next free index
+    pub fn append(&self, leaf: [u8; 32]) -> Result<()> {
+        let mut data = APPEND_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&leaf);
+        self.invoke(data, &[])
+    }
+
+    // Swap previous_leaf at index for new_leaf. The compression program
+    // checks the proof against root (or a recent changelog entry) and fails
+    // the instruction if previous_leaf isn't in the tree
+    pub fn replace_leaf(
+        &self,
+        root: [u8; 32],
+        previous_leaf: [u8; 32],
+        new_leaf: [u8; 32],
+        index: u32,
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let mut data = REPLACE_LEAF_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&root);
+        data.extend_from_slice(&previous_leaf);
+        data.extend_from_slice(&new_leaf);
+        data.extend_from_slice(&index.to_le_bytes());
+        self.invoke(data, proof)
+    }
+
+    fn invoke(&self, data: Vec<u8>, proof: &[AccountInfo<'info>]) -> Result<()> {
+        let mut accounts = vec![
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(self.authority.key(), true),
+            AccountMeta::new_readonly(self.noop_program.key(), false),
+        ];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+
+        let mut account_infos = vec![
+            self.merkle_tree.clone(),
+            self.authority.clone(),
+            self.noop_program.clone(),
+            self.compression_program.clone(),
+        ];
+        account_infos.extend_from_slice(proof);
+
+        let instruction = Instruction {
+            program_id: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            accounts,
+            data,
+        };
+        invoke_signed(&instruction, &account_infos, &[self.authority_seeds])?;
+        Ok(())
+    }
+}
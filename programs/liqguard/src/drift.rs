@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::LiqGuardError;
+
+// Drift v2 program that owns User accounts
+// (dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH)
+pub const DRIFT_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    9, 84, 219, 190, 158, 201, 96, 201, 138, 122, 41, 63, 226, 19, 54, 150, 111, 225, 128, 209, 81,
+    174, 75, 129, 121, 86, 31, 137, 133, 74, 83, 246,
+]);
+
+// Anchor discriminator of User: sha256("account:User")[..8]
+const USER_DISCRIMINATOR: [u8; 8] = [159, 117, 95, 227, 239, 151, 58, 236];
+
+// Drift's User is a #[repr(C)] zero-copy account of fixed size:
+// authority/delegate/name, 8 spot positions (40 bytes), 8 perp positions
+// (96 bytes), 32 orders (96 bytes), then the counters/flags read below
+const USER_SIZE: usize = 4376;
+const AUTHORITY_OFFSET: usize = 8;
+const PERP_POSITIONS_OFFSET: usize = 424;
+const PERP_POSITION_SIZE: usize = 96;
+const PERP_POSITION_COUNT: usize = 8;
+const PERP_BASE_ASSET_AMOUNT_OFFSET: usize = 8; // within a PerpPosition
+const PERP_MARKET_INDEX_OFFSET: usize = 92; // within a PerpPosition
+const NEXT_LIQUIDATION_ID_OFFSET: usize = 4344;
+const STATUS_OFFSET: usize = 4348;
+
+// UserStatus bit flags
+const STATUS_BEING_LIQUIDATED: u8 = 1;
+const STATUS_BANKRUPT: u8 = 2;
+
+pub struct DriftUser {
+    pub authority: Pubkey,
+    pub next_liquidation_id: u16,
+    pub status: u8,
+    perp_positions: [(u16, i64); PERP_POSITION_COUNT], // (market_index, base_asset_amount)
+}
+
+impl DriftUser {
+    // Liquidation in progress/bankrupt now, or a liquidation has completed
+    // since `liquidation_id_at_open` was recorded
+    pub fn liquidated_since(&self, liquidation_id_at_open: u16) -> bool {
+        self.status & (STATUS_BEING_LIQUIDATED | STATUS_BANKRUPT) != 0
+            || self.next_liquidation_id > liquidation_id_at_open
+    }
+
+    pub fn has_perp_position(&self, market_index: u16) -> bool {
+        self.perp_positions
+            .iter()
+            .any(|&(index, base)| index == market_index && base != 0)
+    }
+}
+
+// Read a Drift User account, rejecting foreign or malformed accounts
+pub fn load_user(user: &AccountInfo) -> Result<DriftUser> {
+    require_keys_eq!(*user.owner, DRIFT_PROGRAM_ID, LiqGuardError::InvalidDriftUser);
+
+    let data = user.try_borrow_data()?;
+    require!(
+        data.len() >= USER_SIZE && data[..8] == USER_DISCRIMINATOR,
+        LiqGuardError::InvalidDriftUser
+    );
+
+    let authority =
+        Pubkey::new_from_array(data[AUTHORITY_OFFSET..AUTHORITY_OFFSET + 32].try_into().unwrap());
+    let next_liquidation_id = u16::from_le_bytes(
+        data[NEXT_LIQUIDATION_ID_OFFSET..NEXT_LIQUIDATION_ID_OFFSET + 2]
+            .try_into()
+            .unwrap(),
+    );
+    let status = data[STATUS_OFFSET];
+
+    let mut perp_positions = [(0u16, 0i64); PERP_POSITION_COUNT];
+    for (i, position) in perp_positions.iter_mut().enumerate() {
+        let start = PERP_POSITIONS_OFFSET + i * PERP_POSITION_SIZE;
+        let base = start + PERP_BASE_ASSET_AMOUNT_OFFSET;
+        let market = start + PERP_MARKET_INDEX_OFFSET;
+        *position = (
+            u16::from_le_bytes(data[market..market + 2].try_into().unwrap()),
+            i64::from_le_bytes(data[base..base + 8].try_into().unwrap()),
+        );
+    }
+
+    Ok(DriftUser {
+        authority,
+        next_liquidation_id,
+        status,
+        perp_positions,
+    })
+}
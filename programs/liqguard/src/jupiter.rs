@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+// Jupiter aggregator v6 (JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4)
+pub const JUPITER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    4, 121, 213, 91, 242, 49, 192, 110, 238, 116, 197, 110, 206, 104, 21, 7, 253, 177, 178, 222,
+    163, 244, 142, 81, 2, 177, 205, 162, 86, 188, 19, 143,
+]);
+
+// Run a swap route built off-chain (Jupiter's swap-instructions API, with
+// the authority PDA as the user). route_accounts are the route's accounts
+// in order; the authority signs with authority_seeds wherever it appears
+pub fn swap<'info>(
+    jupiter_program: &AccountInfo<'info>,
+    route_data: Vec<u8>,
+    route_accounts: &[AccountInfo<'info>],
+    authority: &Pubkey,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    let accounts = route_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key == authority,
+            is_writable: account.is_writable,
+        })
+        .collect();
+
+    let mut account_infos = route_accounts.to_vec();
+    account_infos.push(jupiter_program.clone());
+
+    let instruction = Instruction {
+        program_id: JUPITER_PROGRAM_ID,
+        accounts,
+        data: route_data,
+    };
+    invoke_signed(&instruction, &account_infos, &[authority_seeds])?;
+    Ok(())
+}
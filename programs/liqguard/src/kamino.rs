@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use crate::{LiqGuardError, BPS_DENOMINATOR};
+
+// Kamino Lend program that owns Obligation accounts
+// (KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD)
+pub const KAMINO_LEND_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    4, 178, 172, 177, 18, 88, 204, 227, 104, 44, 65, 139, 168, 114, 255, 61, 249, 17, 2, 113, 47,
+    21, 175, 18, 182, 190, 105, 179, 67, 91, 0, 8,
+]);
+
+// Anchor discriminator of Obligation: sha256("account:Obligation")[..8]
+const OBLIGATION_DISCRIMINATOR: [u8; 8] = [168, 206, 141, 106, 88, 76, 172, 167];
+
+// Byte offsets into the zero-copy Obligation (including the discriminator):
+// tag, LastUpdate, lending_market, owner, 8 deposits (136 bytes),
+// lowest_reserve_deposit_liquidation_ltv, deposited_value_sf, 5 borrows
+// (200 bytes), then the aggregate debt/limit values
+const LAST_UPDATE_SLOT_OFFSET: usize = 16;
+const LAST_UPDATE_STALE_OFFSET: usize = 24;
+const OWNER_OFFSET: usize = 64;
+const BORROW_FACTOR_ADJUSTED_DEBT_VALUE_OFFSET: usize = 2208;
+const UNHEALTHY_BORROW_VALUE_OFFSET: usize = 2256;
+
+// Kamino refreshes obligations in the same transaction it acts on them;
+// allow a few slots of slack for the refresh-then-settle pattern
+pub const MAX_OBLIGATION_AGE_SLOTS: u64 = 25;
+
+pub struct Obligation {
+    pub owner: Pubkey,
+    pub last_update_slot: u64,
+    pub stale: bool,
+    debt_value_sf: u128,           // borrow-factor-adjusted debt
+    unhealthy_borrow_value_sf: u128, // debt level at which Kamino liquidates
+}
+
+impl Obligation {
+    // unhealthy_borrow_value / debt in bps: 10_000 is Kamino's liquidation
+    // line, higher is safer. An obligation without debt can't be liquidated
+    pub fn health_bps(&self) -> Result<u128> {
+        if self.debt_value_sf == 0 {
+            return Ok(u128::MAX);
+        }
+        let health = self
+            .unhealthy_borrow_value_sf
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(LiqGuardError::MathOverflow)?
+            / self.debt_value_sf;
+        Ok(health)
+    }
+
+    // Freshly refreshed relative to the current slot
+    pub fn is_fresh(&self, clock: &Clock) -> bool {
+        !self.stale && self.last_update_slot.saturating_add(MAX_OBLIGATION_AGE_SLOTS) >= clock.slot
+    }
+}
+
+// Read a Kamino Obligation, rejecting foreign or malformed accounts
+pub fn load_obligation(obligation: &AccountInfo) -> Result<Obligation> {
+    require_keys_eq!(
+        *obligation.owner,
+        KAMINO_LEND_PROGRAM_ID,
+        LiqGuardError::InvalidObligation
+    );
+
+    let data = obligation.try_borrow_data()?;
+    require!(
+        data.len() >= UNHEALTHY_BORROW_VALUE_OFFSET + 16 && data[..8] == OBLIGATION_DISCRIMINATOR,
+        LiqGuardError::InvalidObligation
+    );
+
+    let read_u128 = |offset: usize| u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+
+    Ok(Obligation {
+        owner: Pubkey::new_from_array(data[OWNER_OFFSET..OWNER_OFFSET + 32].try_into().unwrap()),
+        last_update_slot: u64::from_le_bytes(
+            data[LAST_UPDATE_SLOT_OFFSET..LAST_UPDATE_SLOT_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        ),
+        stale: data[LAST_UPDATE_STALE_OFFSET] != 0,
+        debt_value_sf: read_u128(BORROW_FACTOR_ADJUSTED_DEBT_VALUE_OFFSET),
+        unhealthy_borrow_value_sf: read_u128(UNHEALTHY_BORROW_VALUE_OFFSET),
+    })
+}
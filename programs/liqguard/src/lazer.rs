@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+
+use crate::LiqGuardError;
+
+// Pyth Lazer verifier, which checks a signed price message against the
+// ed25519 instruction carrying its signature and Pyth's trusted signers
+// (pytd2yyk641x7ak7mkaasSJVXh6YYZnC7wTmtgAyxPt)
+pub const PYTH_LAZER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    12, 74, 159, 176, 3, 249, 12, 128, 32, 17, 101, 150, 154, 165, 132, 195, 182, 126, 234, 138,
+    69, 43, 85, 3, 6, 14, 175, 224, 214, 116, 116, 91,
+]);
+
+// Anchor instruction discriminator: sha256("global:verify_message")[..8]
+const VERIFY_MESSAGE_DISCRIMINATOR: [u8; 8] = [180, 193, 120, 55, 189, 135, 203, 83];
+
+// A Solana-format message: magic, ed25519 signature, signer public key,
+// then a u16-length-prefixed payload
+const SOLANA_FORMAT_MAGIC: [u8; 4] = [0xb9, 0x01, 0x1a, 0x82];
+const PAYLOAD_LEN_OFFSET: usize = 4 + 64 + 32;
+const PAYLOAD_OFFSET: usize = PAYLOAD_LEN_OFFSET + 2;
+
+// The payload: magic, timestamp_us, channel and feed count, then each
+// feed's id and its (property id, value) pairs
+const PAYLOAD_FORMAT_MAGIC: [u8; 4] = [0x75, 0xd3, 0xc7, 0x93];
+
+// Property ids we decode. Other properties have encodings we don't read,
+// so keepers must subscribe to these only
+const PROPERTY_PRICE: u8 = 0;
+const PROPERTY_BEST_BID_PRICE: u8 = 1;
+const PROPERTY_BEST_ASK_PRICE: u8 = 2;
+const PROPERTY_PUBLISHER_COUNT: u8 = 3;
+const PROPERTY_EXPONENT: u8 = 4;
+const PROPERTY_CONFIDENCE: u8 = 5;
+
+pub struct LazerFeedPrice {
+    pub price: i64,
+    pub exponent: i16,
+    pub confidence: u64,
+    pub timestamp_us: u64, // when Lazer published the message, in microseconds
+}
+
+// The accounts the Lazer program's verify_message needs. The payer covers
+// Lazer's per-update fee, which goes to the treasury
+pub struct LazerVerifier<'a, 'info> {
+    pub lazer_program: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub storage: &'a AccountInfo<'info>,
+    pub treasury: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> LazerVerifier<'a, 'info> {
+    // Fails unless message carries a signature from a trusted Lazer signer
+    // that the ed25519 instruction at ed25519_instruction_index verified.
+    // The Lazer program checks storage and treasury itself
+    pub fn verify_message(
+        &self,
+        message: &[u8],
+        ed25519_instruction_index: u16,
+        signature_index: u8,
+    ) -> Result<()> {
+        require_keys_eq!(
+            self.lazer_program.key(),
+            PYTH_LAZER_PROGRAM_ID,
+            LiqGuardError::InvalidLazerMessage
+        );
+
+        let mut data = VERIFY_MESSAGE_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        data.extend_from_slice(message);
+        data.extend_from_slice(&ed25519_instruction_index.to_le_bytes());
+        data.push(signature_index);
+
+        let instruction = Instruction {
+            program_id: PYTH_LAZER_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.payer.key(), true),
+                AccountMeta::new_readonly(self.storage.key(), false),
+                AccountMeta::new(self.treasury.key(), false),
+                AccountMeta::new_readonly(self.system_program.key(), false),
+                AccountMeta::new_readonly(self.instructions_sysvar.key(), false),
+            ],
+            data,
+        };
+        invoke(
+            &instruction,
+            &[
+                self.payer.clone(),
+                self.storage.clone(),
+                self.treasury.clone(),
+                self.system_program.clone(),
+                self.instructions_sysvar.clone(),
+                self.lazer_program.clone(),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+// Reads the payload front to back, failing on a truncated message
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        require!(self.data.len() >= N, LiqGuardError::InvalidLazerMessage);
+        let (head, rest) = self.data.split_at(N);
+        self.data = rest;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+}
+
+// feed_id's price, exponent and confidence out of a Solana-format message.
+// Only parse messages verify_message has accepted: this checks the layout,
+// not the signature
+pub fn parse_feed_price(message: &[u8], feed_id: u32) -> Result<LazerFeedPrice> {
+    require!(
+        message.len() >= PAYLOAD_OFFSET && message[..4] == SOLANA_FORMAT_MAGIC,
+        LiqGuardError::InvalidLazerMessage
+    );
+    let payload_len = u16::from_le_bytes(
+        message[PAYLOAD_LEN_OFFSET..PAYLOAD_OFFSET].try_into().unwrap(),
+    ) as usize;
+    require!(
+        message.len() == PAYLOAD_OFFSET + payload_len,
+        LiqGuardError::InvalidLazerMessage
+    );
+
+    let mut reader = Reader {
+        data: &message[PAYLOAD_OFFSET..],
+    };
+    require!(
+        reader.take::<4>()? == PAYLOAD_FORMAT_MAGIC,
+        LiqGuardError::InvalidLazerMessage
+    );
+    let timestamp_us = u64::from_le_bytes(reader.take()?);
+    let _channel = reader.u8()?;
+
+    let (mut price, mut exponent, mut confidence) = (None, None, None);
+    for _ in 0..reader.u8()? {
+        let id = u32::from_le_bytes(reader.take()?);
+        for _ in 0..reader.u8()? {
+            let property = reader.u8()?;
+            match property {
+                PROPERTY_PRICE | PROPERTY_BEST_BID_PRICE | PROPERTY_BEST_ASK_PRICE
+                | PROPERTY_CONFIDENCE => {
+                    let value = i64::from_le_bytes(reader.take()?);
+                    if id == feed_id && property == PROPERTY_PRICE {
+                        price = Some(value);
+                    } else if id == feed_id && property == PROPERTY_CONFIDENCE {
+                        confidence = Some(value);
+                    }
+                }
+                PROPERTY_PUBLISHER_COUNT => {
+                    reader.take::<2>()?;
+                }
+                PROPERTY_EXPONENT => {
+                    let value = i16::from_le_bytes(reader.take()?);
+                    if id == feed_id {
+                        exponent = Some(value);
+                    }
+                }
+                _ => return err!(LiqGuardError::InvalidLazerMessage),
+            }
+        }
+    }
+
+    // Lazer encodes a feed with no current price as 0
+    match (price, exponent, confidence) {
+        (Some(price), Some(exponent), Some(confidence)) if price > 0 && confidence >= 0 => {
+            Ok(LazerFeedPrice {
+                price,
+                exponent,
+                confidence: confidence as u64,
+                timestamp_us,
+            })
+        }
+        _ => err!(LiqGuardError::InvalidLazerMessage),
+    }
+}
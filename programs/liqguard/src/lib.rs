@@ -1,237 +1,9874 @@
 use anchor_lang::prelude::*;
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use pyth_solana_receiver_sdk::price_update::TwapUpdate;
 
-declare_id!("EAVJioMyQKbHEKNAr5kRg4e4gFahmgwd9bWVCBD4Svnc");
+use oracle::{FeedRef, OracleQuote, OracleSource};
+
+pub mod chainlink;
+pub mod compression;
+pub mod drift;
+pub mod jupiter;
+pub mod kamino;
+pub mod lazer;
+pub mod oracle;
+pub mod stake_pool;
+pub mod switchboard;
+pub mod wormhole;
+
+use liqguard_common::seeds::{
+    AUDIT_LOG_SEED, BACKSTOP_SEED, BOOK_VAULT_SEED, CLAIM_HISTORY_SEED, CLAIM_STATE_SEED,
+    COMPRESSED_TREE_SEED, CONFIG_SEED, COVERAGE_REQUEST_SEED, DISPUTE_SEED, FEED_REGISTRY_SEED, KEEPER_QUEUE_SEED,
+    LAZER_PRICE_SEED, MASTER_AGREEMENT_SEED, OWNER_STATE_SEED, PENDING_CONFIG_SEED, POLICY_SEED, PORTFOLIO_VAULT_SEED,
+    PRICE_CACHE_SEED, RENEWAL_SOURCE_SEED,
+    SWAP_SOURCE_SEED, TOKEN_VAULT_SEED, TREASURY_SEED, UNDERWRITER_SEED, VAULT_SEED,
+    WORMHOLE_PAYMENT_SEED,
+};
+
+pub use liqguard_common::price::{OraclePrice, LAMPORTS_PER_SOL, STRIKE_EXPONENT, USDC_DECIMALS};
+pub use liqguard_common::{
+    cluster, payout, seeds, BarrierKind, ConfidenceAdjustment, ExerciseStyle, PayoutMode, TriggerDirection,
+    UnderlyingAsset, BTC_USD_FEED_ID, ETH_USD_FEED_ID, SOL_USD_FEED_ID,
+};
+pub use liqguard_pricing::fixed as pricing;
+
+// declare_id! and DEPLOY_AUTHORITY, the only key initialize_config
+// accepts (None = any signer). build.rs takes them from the
+// LIQGUARD_PROGRAM_ID and LIQGUARD_AUTHORITY environment variables
+include!(concat!(env!("OUT_DIR"), "/ids.rs"));
 
 #[program]
 pub mod liqguard {
     use super::*;
 
     // initialize global config
-    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
         let config = &mut ctx.accounts.config;
         config.authority = ctx.accounts.authority.key();
+        config.paused = false;
         config.bump = ctx.bumps.config;
+        config.apply(&params)
+    }
+
+    // Admin: queue a replacement of the tunable protocol parameters. It can
+    // only be applied after the current config_change_delay, so
+    // integrators see it coming; a new proposal restarts the clock
+    pub fn propose_config_change(
+        ctx: Context<ProposeConfigChange>,
+        params: ConfigParams,
+    ) -> Result<()> {
+        Config::validate(&params)?;
+        let now = Clock::get()?.unix_timestamp;
+        let executable_at = now
+            .checked_add(ctx.accounts.config.config_change_delay)
+            .ok_or(LiqGuardError::MathOverflow)?;
+
+        let pending = &mut ctx.accounts.pending_config_change;
+        pending.params = params.clone();
+        pending.proposed_at = now;
+        pending.executable_at = executable_at;
+        pending.bump = ctx.bumps.pending_config_change;
+
+        emit!(ConfigChangeProposed {
+            params,
+            proposed_at: now,
+            executable_at,
+        });
         Ok(())
     }
 
-    pub fn initialize_policy(
-        ctx: Context<InitializePolicy>,
-        strike_price: u64,
-        is_long_insurance: bool,
-        coverage_amount: u64,
+    // Admin: apply the queued parameters once their delay has passed
+    pub fn apply_config_change(ctx: Context<ApplyConfigChange>) -> Result<()> {
+        let pending = &ctx.accounts.pending_config_change;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= pending.executable_at,
+            LiqGuardError::ConfigChangeDelayActive
+        );
+        ctx.accounts.config.apply(&pending.params)?;
+
+        emit!(ConfigChangeApplied {
+            params: pending.params.clone(),
+            proposed_at: pending.proposed_at,
+            applied_at: now,
+        });
+        Ok(())
+    }
+
+    // Admin: drop a queued change before it is applied
+    pub fn cancel_config_change(ctx: Context<ApplyConfigChange>) -> Result<()> {
+        msg!(
+            "Config change cancelled: ProposedAt={}",
+            ctx.accounts.pending_config_change.proposed_at
+        );
+        Ok(())
+    }
+
+    // Admin: halt opening, funding and settling policies. Exits (reclaim,
+    // close, excess withdrawal) stay open so collateral is never trapped.
+    // Pausing is the emergency brake, so unlike parameters it isn't
+    // timelocked
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.config.paused = paused;
+        msg!("Config paused={}", paused);
+        Ok(())
+    }
+
+    // Create the protocol backstop that premiums pay into and short vaults
+    // draw from. Its lamports live on the account itself
+    pub fn initialize_backstop(ctx: Context<InitializeBackstop>) -> Result<()> {
+        let backstop = &mut ctx.accounts.backstop;
+        backstop.total_contributed = 0;
+        backstop.total_drawn = 0;
+        backstop.bump = ctx.bumps.backstop;
+        Ok(())
+    }
+
+    // Admin creates the treasury PDA that collects payout fees
+    pub fn initialize_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.total_collected = 0;
+        treasury.total_withdrawn = 0;
+        treasury.bump = ctx.bumps.treasury;
+        Ok(())
+    }
+
+    // Admin withdraws collected payout fees, leaving the treasury rent-exempt
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let rent_floor = Rent::get()?.minimum_balance(8 + Treasury::LEN);
+        require!(
+            amount > 0 && amount <= treasury.get_lamports().saturating_sub(rent_floor),
+            LiqGuardError::InvalidAmount
+        );
+        treasury.sub_lamports(amount)?;
+        ctx.accounts.destination.add_lamports(amount)?;
+        treasury.total_withdrawn = treasury
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+
+        emit!(TreasuryWithdrawn {
+            destination: ctx.accounts.destination.key(),
+            amount,
+            treasury_balance: treasury.get_lamports(),
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    // Underwriter pays back lamports its vaults drew from the backstop
+    pub fn repay_backstop(ctx: Context<RepayBackstop>, amount: u64) -> Result<()> {
+        let record = &mut ctx.accounts.underwriter_record;
+        require!(
+            amount > 0 && amount <= record.backstop_debt,
+            LiqGuardError::InvalidAmount
+        );
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.underwriter.to_account_info(),
+                to: ctx.accounts.backstop.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+
+        record.backstop_debt -= amount;
+        let backstop = &mut ctx.accounts.backstop;
+        backstop.total_contributed = backstop
+            .total_contributed
+            .checked_add(amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        msg!(
+            "Backstop repaid: Underwriter={}, Amount={}, Remaining={}",
+            record.underwriter,
+            amount,
+            record.backstop_debt
+        );
+        Ok(())
+    }
+
+    // Underwriter opens a shared vault that can back several of their
+    // native policies at once instead of one segregated vault each
+    pub fn initialize_portfolio_vault(ctx: Context<InitializePortfolioVault>) -> Result<()> {
+        let portfolio = &mut ctx.accounts.portfolio_vault;
+        portfolio.underwriter = ctx.accounts.underwriter.key();
+        portfolio.reserved_exposure = 0;
+        portfolio.bump = ctx.bumps.portfolio_vault;
+        Ok(())
+    }
+
+    pub fn fund_portfolio_vault(ctx: Context<ManagePortfolioVault>, amount: u64) -> Result<()> {
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.underwriter.to_account_info(),
+                to: ctx.accounts.portfolio_vault.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+        msg!(
+            "Portfolio vault funded: Amount={}, Unreserved={}",
+            amount,
+            unreserved_balance(&ctx.accounts.portfolio_vault)?
+        );
+        Ok(())
+    }
+
+    // Only lamports not reserved for open policies can leave the vault
+    pub fn withdraw_portfolio_vault(ctx: Context<ManagePortfolioVault>, amount: u64) -> Result<()> {
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        let portfolio = &mut ctx.accounts.portfolio_vault;
+        require!(
+            amount <= unreserved_balance(portfolio)?,
+            LiqGuardError::PortfolioExposureExceeded
+        );
+        portfolio.sub_lamports(amount)?;
+        ctx.accounts.underwriter.add_lamports(amount)?;
+        msg!(
+            "Portfolio vault withdrawal: Amount={}, Reserved={}",
+            amount,
+            portfolio.reserved_exposure
+        );
+        Ok(())
+    }
+
+    // A counterparty and an underwriter sign a master agreement: policies
+    // the counterparty holds with that underwriter can join it to share
+    // aggregate_limit, which every member's claim draws down, and must
+    // expire by its expiry. Members all pay in the agreement's collateral
+    // and coverage units, so the limit is in those too
+    pub fn initialize_master_agreement(
+        ctx: Context<InitializeMasterAgreement>,
+        params: MasterAgreementParams,
     ) -> Result<()> {
-        let policy = &mut ctx.accounts.policy;
-        policy.owner = ctx.accounts.owner.key();
-        policy.strike_price = strike_price;
-        policy.is_long_insurance = is_long_insurance;
-        policy.coverage_amount = coverage_amount;
-        policy.is_claimed = false;
-        policy.policy_bump = ctx.bumps.policy;
-        policy.vault_bump = ctx.bumps.vault;
+        require!(params.aggregate_limit > 0, LiqGuardError::InvalidAmount);
+        require!(
+            params.expiration_datetime > Clock::get()?.unix_timestamp,
+            LiqGuardError::InvalidExpiration
+        );
+        require!(
+            !params.usd_coverage || params.collateral_mint == Pubkey::default(),
+            LiqGuardError::InvalidMasterAgreement
+        );
+
+        let agreement = &mut ctx.accounts.master_agreement;
+        agreement.counterparty = ctx.accounts.counterparty.key();
+        agreement.underwriter = ctx.accounts.underwriter.key();
+        agreement.agreement_id = params.agreement_id;
+        agreement.collateral_mint = params.collateral_mint;
+        agreement.usd_coverage = params.usd_coverage;
+        agreement.aggregate_limit = params.aggregate_limit;
+        agreement.aggregate_paid = 0;
+        agreement.expiration_datetime = params.expiration_datetime;
+        agreement.member_count = 0;
+        agreement.bump = ctx.bumps.master_agreement;
+        msg!(
+            "Master agreement opened: Counterparty={}, Underwriter={}, AgreementId={}, AggregateLimit={}, Expiry={}",
+            agreement.counterparty,
+            agreement.underwriter,
+            agreement.agreement_id,
+            agreement.aggregate_limit,
+            agreement.expiration_datetime
+        );
         Ok(())
     }
 
-    pub fn liquidate_policy(ctx: Context<LiquidatePolicy>) -> Result<()> {
+    // Owner puts a policy under a master agreement for good: from here on
+    // its claims are capped by what's left of the aggregate limit. The
+    // policy must be the agreement's counterparty's, with its underwriter,
+    // collateral and coverage units, and expire by the agreement's expiry
+    pub fn join_master_agreement(ctx: Context<JoinMasterAgreement>) -> Result<()> {
+        let agreement = &mut ctx.accounts.master_agreement;
         let policy = &mut ctx.accounts.policy;
-        
-        // Check if already claimed
-        require!(!policy.is_claimed, LiqGuardError::AlreadyClaimed);
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        require!(
+            policy.master_agreement == Pubkey::default()
+                && policy.underwriter == agreement.underwriter
+                && policy.collateral_mint == agreement.collateral_mint
+                && policy.usd_coverage == agreement.usd_coverage
+                && policy.roll_period_seconds == 0
+                && policy.expiration_datetime <= agreement.expiration_datetime,
+            LiqGuardError::InvalidMasterAgreement
+        );
+
+        policy.master_agreement = agreement.key();
+        agreement.member_count = agreement
+            .member_count
+            .checked_add(1)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        msg!(
+            "Master agreement joined: PolicyId={}, Agreement={}, Members={}",
+            policy.policy_id,
+            policy.master_agreement,
+            agreement.member_count
+        );
+        Ok(())
+    }
+
+    // Admin: register the oracle feed and limits for an asset
+    pub fn register_feed(
+        ctx: Context<RegisterFeed>,
+        asset: UnderlyingAsset,
+        params: FeedRegistryParams,
+    ) -> Result<()> {
+        let feed = &mut ctx.accounts.feed_registry;
+        feed.asset = asset;
+        feed.bump = ctx.bumps.feed_registry;
+        feed.volatility_bps = 0;
+        feed.volatility_updated_at = 0;
+        feed.apply(&params)
+    }
+
+    // Admin: change a registered feed, e.g. disable it while it misbehaves
+    pub fn update_feed(ctx: Context<UpdateFeed>, params: FeedRegistryParams) -> Result<()> {
+        ctx.accounts.feed_registry.apply(&params)?;
+        msg!(
+            "Feed updated: Asset={:?}, Enabled={}",
+            ctx.accounts.feed_registry.asset,
+            params.enabled
+        );
+        Ok(())
+    }
+
+    // Admin: halt or resume one asset without pausing the protocol, e.g.
+    // during an oracle incident on its symbol. A disabled feed blocks new
+    // policies and settlement for the asset, as update_feed's flag does,
+    // without re-sending the feed's limits. Like set_paused it isn't
+    // timelocked
+    pub fn set_feed_enabled(ctx: Context<UpdateFeed>, enabled: bool) -> Result<()> {
+        let feed = &mut ctx.accounts.feed_registry;
+        feed.enabled = enabled;
+        msg!("Feed updated: Asset={:?}, Enabled={}", feed.asset, enabled);
+        Ok(())
+    }
 
-        // BTC/USD Feed ID: e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43
-        let btc_feed_id = get_feed_id_from_hex(
-            "e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43"
+    // Risk oracle publishes an asset's annualized volatility. Quotes use it
+    // over Config.volatility_bps, and refuse it once it goes stale
+    pub fn update_volatility(ctx: Context<UpdateVolatility>, volatility_bps: u32) -> Result<()> {
+        require!(
+            volatility_bps > 0 && volatility_bps <= MAX_VOLATILITY_BPS,
+            LiqGuardError::InvalidVolatility
+        );
+        let clock = Clock::get()?;
+        let feed = &mut ctx.accounts.feed_registry;
+        feed.volatility_bps = volatility_bps;
+        feed.volatility_updated_at = clock.unix_timestamp;
+
+        emit!(VolatilityUpdated {
+            asset: feed.asset,
+            volatility_bps,
+            updated_at: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Keeper: verify a Pyth Lazer signed message through the Lazer program
+    // and store the asset's price from it. Passed as price_feed later in
+    // the same transaction, it settles with sub-second latency where the
+    // pull oracle would lag. Only feeds with a lazer_feed_id take it, and
+    // an older message can't roll the stored price back
+    pub fn post_lazer_price(
+        ctx: Context<PostLazerPrice>,
+        message: Vec<u8>,
+        ed25519_instruction_index: u16,
+        signature_index: u8,
+    ) -> Result<()> {
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.lazer_feed_id != 0, LiqGuardError::LazerDisabled);
+
+        lazer::LazerVerifier {
+            lazer_program: &ctx.accounts.lazer_program,
+            payer: &ctx.accounts.keeper,
+            storage: &ctx.accounts.lazer_storage,
+            treasury: &ctx.accounts.lazer_treasury,
+            system_program: &ctx.accounts.system_program,
+            instructions_sysvar: &ctx.accounts.instructions_sysvar,
+        }
+        .verify_message(&message, ed25519_instruction_index, signature_index)?;
+        let feed_price = lazer::parse_feed_price(&message, feed.lazer_feed_id)?;
+
+        let lazer_price = &mut ctx.accounts.lazer_price;
+        require!(
+            feed_price.timestamp_us > lazer_price.timestamp_us,
+            LiqGuardError::LazerPriceOutdated
+        );
+        lazer_price.asset = feed.asset;
+        lazer_price.lazer_feed_id = feed.lazer_feed_id;
+        lazer_price.price = feed_price.price;
+        lazer_price.exponent = feed_price.exponent as i32;
+        lazer_price.confidence = feed_price.confidence;
+        lazer_price.timestamp_us = feed_price.timestamp_us;
+        lazer_price.bump = ctx.bumps.lazer_price;
+        Ok(())
+    }
+
+    // Keeper: validate the asset's oracle price once and cache it for the
+    // slot, so a liquidate_policies batch passed the cache as price_feed
+    // skips verifying the update again. Later pushes in the same slot are
+    // no-ops, and an older price can't roll the cache back
+    pub fn push_price(ctx: Context<PushPrice>) -> Result<()> {
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.enabled, LiqGuardError::FeedDisabled);
+        let clock = Clock::get()?;
+        let cache = &mut ctx.accounts.price_cache;
+        if cache.slot == clock.slot && cache.publish_time != 0 {
+            return Ok(());
+        }
+
+        let quote = oracle::get_price(
+            &feed.feed_ref(),
+            &ctx.accounts.price_feed,
+            &clock,
+            feed.max_price_age,
+            ctx.accounts.config.max_confidence_bps.min(feed.max_confidence_bps),
         )?;
+        require!(
+            quote.publish_time >= cache.publish_time,
+            LiqGuardError::PriceCacheOutdated
+        );
+        cache.asset = feed.asset;
+        cache.price = quote.price.mantissa;
+        cache.exponent = quote.price.exponent;
+        cache.confidence = quote.confidence;
+        cache.has_ema = quote.ema_price.is_some();
+        if let Some(ema) = quote.ema_price {
+            cache.ema_price = ema.mantissa;
+            cache.ema_exponent = ema.exponent;
+        }
+        cache.publish_time = quote.publish_time;
+        cache.slot = clock.slot;
+        cache.bump = ctx.bumps.price_cache;
+        Ok(())
+    }
 
-        // Get price from Pyth price update account
-        let price_update = &ctx.accounts.price_update;
-        
-        // Get price no older than 60 seconds
-        let price_info = price_update.get_price_no_older_than(&btc_feed_id, 60)
-            .ok_or(LiqGuardError::PriceStale)?;
-
-        // Step 3: Normalize Price
-        // Pyth returns price as i64 with an exponent
-        // Example: price = 9500000000000, expo = -8
-        // Normalized = 9500000000000 / 10^8 = 95000
-        let price_magnitude = price_info.price.magnitude;
-        let price_exponent = price_info.price.exponent;
-        
-        // Handle negative prices (shouldn't happen for BTC, but be safe)
-        require!(price_magnitude >= 0, LiqGuardError::MathOverflow);
-        
-        // Calculate normalization factor: 10^|exponent|
-        // Since exponent is negative (e.g., -8), we need to divide by 10^8
-        let normalization_factor = 10u64
-            .checked_pow(price_exponent.abs() as u32)
-            .ok_or(LiqGuardError::MathOverflow)?;
-        
-        // Normalize to USD (divide by 10^|exponent|)
-        let current_price = (price_magnitude as u64)
-            .checked_div(normalization_factor)
+    // Admin: create the per-asset keeper queue that new policies join
+    pub fn initialize_keeper_queue(
+        ctx: Context<InitializeKeeperQueue>,
+        asset: UnderlyingAsset,
+    ) -> Result<()> {
+        let queue = &mut ctx.accounts.keeper_queue;
+        queue.asset = asset;
+        queue.entries = Vec::new();
+        queue.bump = ctx.bumps.keeper_queue;
+        Ok(())
+    }
+
+    // Permissionless: drop keeper queue entries whose policies are no
+    // longer live, so stale entries can't crowd real policies out of a
+    // full queue. remaining_accounts holds the listed policies to check;
+    // an entry goes if its policy is closed, unfunded or terminal.
+    // Returns how many were dropped
+    pub fn prune_keeper_queue<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PruneKeeperQueue<'info>>,
+    ) -> Result<u32> {
+        let now = Clock::get()?.unix_timestamp;
+        let queue = &mut ctx.accounts.keeper_queue;
+        let listed = queue.entries.len();
+        for policy_info in ctx.remaining_accounts {
+            let live = policy_info.owner == &crate::ID
+                && !policy_info.data_is_empty()
+                && Account::<Policy>::try_from(policy_info)
+                    .map(|policy| policy.is_funded && !policy.is_terminal(now))?;
+            if !live {
+                queue.remove(policy_info.key);
+            }
+        }
+        let pruned = (listed - queue.entries.len()) as u32;
+        msg!("Keeper queue pruned: Asset={:?}, Removed={}", queue.asset, pruned);
+        Ok(pruned)
+    }
+
+    // Admin creates the protocol-wide settlement audit log
+    pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+        let mut audit_log = ctx.accounts.audit_log.load_init()?;
+        audit_log.total_entries = 0;
+        audit_log.next_index = 0;
+        audit_log.bump = ctx.bumps.audit_log;
+        Ok(())
+    }
+
+    // An owner opts into an on-chain record of their last
+    // CLAIM_HISTORY_CAPACITY claims, which settlements append to when it
+    // is passed. Risk models and discounts read it instead of the chain
+    pub fn initialize_claim_history(ctx: Context<InitializeClaimHistory>) -> Result<()> {
+        let mut history = ctx.accounts.claim_history.load_init()?;
+        history.owner = ctx.accounts.owner.key();
+        history.total_claims = 0;
+        history.total_paid = 0;
+        history.next_index = 0;
+        history.bump = ctx.bumps.claim_history;
+        Ok(())
+    }
+
+    // Premium the protocol would charge for the given terms right now, in
+    // coverage units. Read it via simulation or from return data. Pass the
+    // underwriter's portfolio vault to price in its utilization; the full
+    // coverage is counted against it, so capped payouts pay no more than
+    // this
+    pub fn quote(ctx: Context<Quote>, params: QuoteParams) -> Result<u64> {
+        require!(
+            pricing_enabled(&ctx.accounts.config, &ctx.accounts.feed_registry),
+            LiqGuardError::PricingDisabled
+        );
+        let utilization_bps = match ctx.accounts.portfolio_vault.as_ref() {
+            Some(portfolio) => portfolio_utilization(portfolio, params.coverage_amount)?,
+            None => 0,
+        };
+        quote_premium(
+            &ctx.accounts.config,
+            &ctx.accounts.feed_registry,
+            &ctx.accounts.price_feed,
+            &params,
+            utilization_bps,
+        )
+    }
+
+    pub fn initialize_policy(
+        ctx: Context<InitializePolicy>,
+        params: InitializePolicyParams,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        require!(ctx.accounts.feed_registry.enabled, LiqGuardError::FeedDisabled);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            params.expiration_datetime > now,
+            LiqGuardError::InvalidExpiration
+        );
+
+        // The oracle price at open is kept as the entry price, read from
+        // the source the policy settles on. Drawdown policies derive their
+        // strike (and band top) from it here, so every settlement path
+        // keeps comparing against absolute strikes
+        let entry_feed = if params.chainlink_feed != Pubkey::default() {
+            ctx.accounts
+                .chainlink_feed
+                .as_ref()
+                .ok_or(LiqGuardError::MissingChainlinkFeed)?
+        } else {
+            &ctx.accounts.price_feed
+        };
+        let entry = current_spot_quote(
+            config,
+            &ctx.accounts.feed_registry,
+            &policy_feed_ref(&ctx.accounts.feed_registry, params.chainlink_feed),
+            entry_feed,
+            &Clock::get()?,
+        )?;
+        let entry_price = entry.price;
+        let mut params = params;
+        if params.drawdown_bps > 0 {
+            require!(
+                params.drawdown_bps < BPS_DENOMINATOR
+                    && params.strike_price == 0
+                    && params.upper_strike_price == 0
+                    && params.kamino_obligation == Pubkey::default(),
+                LiqGuardError::InvalidDrawdown
+            );
+            let (lower, upper) = drawdown_strikes(to_strike_units(entry_price)?, params.drawdown_bps)?;
+            match params.direction {
+                TriggerDirection::ProtectLong => params.strike_price = lower,
+                TriggerDirection::ProtectShort => params.strike_price = upper,
+                TriggerDirection::OutsideRange => {
+                    params.strike_price = lower;
+                    params.upper_strike_price = upper;
+                }
+            }
+        }
+
+        // 0 opts into the protocol-wide staleness default
+        let max_price_age = if params.max_price_age == 0 {
+            config.default_max_price_age
+        } else {
+            params.max_price_age
+        };
+        require!(
+            (MIN_PRICE_AGE..=MAX_PRICE_AGE).contains(&max_price_age),
+            LiqGuardError::InvalidMaxPriceAge
+        );
+        require!(
+            matches!(params.payout_mode, PayoutMode::Binary | PayoutMode::Spread)
+                || params.payout_slope_bps > 0,
+            LiqGuardError::InvalidPayoutSlope
+        );
+        require!(
+            params.payout_mode != PayoutMode::CappedLinear
+                || (params.payout_cap_bps > 0
+                    && params.payout_floor_bps <= params.payout_cap_bps
+                    && params.payout_cap_bps <= BPS_DENOMINATOR),
+            LiqGuardError::InvalidPayoutBounds
+        );
+        require!(
+            params.switchboard_feed == Pubkey::default()
+                || (params.oracle_tolerance_bps > 0 && params.oracle_tolerance_bps <= BPS_DENOMINATOR),
+            LiqGuardError::InvalidOracleTolerance
+        );
+        require!(
+            params.trigger_buffer_bps < BPS_DENOMINATOR,
+            LiqGuardError::InvalidTriggerBuffer
+        );
+        // Only live spot reads carry a confidence; a European snapshot
+        // doesn't keep one
+        require!(
+            if params.confidence_adjustment == ConfidenceAdjustment::None {
+                params.confidence_multiplier_bps == 0
+            } else {
+                params.confidence_multiplier_bps > 0
+                    && params.exercise_style == ExerciseStyle::American
+            },
+            LiqGuardError::InvalidConfidenceAdjustment
+        );
+        // TWAPs are read from Pyth, so they need a Pyth-backed feed
+        require!(
+            params.twap_window_seconds == 0
+                || ((MIN_TWAP_WINDOW..=MAX_TWAP_WINDOW).contains(&params.twap_window_seconds)
+                    && ctx.accounts.feed_registry.oracle_source == OracleSource::PythPull
+                    && params.chainlink_feed == Pubkey::default()),
+            LiqGuardError::InvalidTwapWindow
+        );
+        require!(
+            params.roll_period_seconds == 0
+                || (MIN_ROLL_PERIOD..=MAX_ROLL_PERIOD).contains(&params.roll_period_seconds),
+            LiqGuardError::InvalidRollPeriod
+        );
+        // Streaming policies pay for coverage as it elapses out of an
+        // escrow, so they can't also roll or settle on an expiry snapshot
+        require!(
+            if params.premium_rate > 0 {
+                params.stream_deposit > 0
+                    && params.roll_period_seconds == 0
+                    && params.exercise_style == ExerciseStyle::American
+            } else {
+                params.stream_deposit == 0
+            },
+            LiqGuardError::InvalidPremiumStream
+        );
+        // European policies settle on one snapshot at expiry, so they can't
+        // also auto-roll, stream their payout or trigger off-price
+        require!(
+            params.exercise_style == ExerciseStyle::American
+                || (params.roll_period_seconds == 0
+                    && params.vesting_period_seconds == 0
+                    && params.twap_window_seconds == 0
+                    && params.switchboard_feed == Pubkey::default()
+                    && params.drift_user == Pubkey::default()
+                    && params.kamino_obligation == Pubkey::default()),
+            LiqGuardError::InvalidEuropeanPolicy
+        );
+        // A dwell makes a claim wait for a second reading past the strike,
+        // which a single expiry snapshot can't give
+        require!(
+            params.breach_dwell_seconds == 0
+                || ((MIN_BREACH_DWELL..=MAX_BREACH_DWELL).contains(&params.breach_dwell_seconds)
+                    && params.exercise_style == ExerciseStyle::American),
+            LiqGuardError::InvalidBreachDwell
+        );
+        require!(
+            valid_strike_range(params.direction, params.strike_price, params.upper_strike_price),
+            LiqGuardError::InvalidStrikeRange
+        );
+        require!(
+            valid_spread(params.payout_mode, params.direction, params.strike_price, params.short_strike_price),
+            LiqGuardError::InvalidSpread
+        );
+        // Barrier levels sit on either side of a single strike and are
+        // touched moving away from it; a barrier state can't survive a roll
+        require!(
+            params.barrier_kind == BarrierKind::None
+                || (params.barrier_price > 0
+                    && params.barrier_price != params.strike_price
+                    && params.direction != TriggerDirection::OutsideRange
+                    && params.roll_period_seconds == 0),
+            LiqGuardError::InvalidBarrier
+        );
+        require!(
+            params.vesting_period_seconds == 0
+                || ((MIN_VESTING_PERIOD..=MAX_VESTING_PERIOD).contains(&params.vesting_period_seconds)
+                    && params.immediate_payout_bps < BPS_DENOMINATOR),
+            LiqGuardError::InvalidVestingSchedule
+        );
+        // Only mints allowlisted for the cluster the program was built for
+        require!(
+            cluster::collateral_mint_allowed(&params.collateral_mint),
+            LiqGuardError::InvalidCollateralMint
+        );
+        // USD coverage is paid from a SOL vault at the SOL/USD price of the
+        // claim, so its size in lamports is only known at funding and claim
+        // time. It stays fixed once open and pays through liquidate_policy
+        require!(
+            !params.usd_coverage
+                || (params.collateral_mint == Pubkey::default()
+                    && params.exercise_style == ExerciseStyle::American
+                    && params.roll_period_seconds == 0
+                    && ctx.accounts.portfolio_vault.is_none()),
+            LiqGuardError::InvalidUsdCoverage
+        );
+        // LST collateral keeps earning staking yield while it backs
+        // lamport coverage; the vault holds the pool's token and claims pay
+        // it out at the pool's exchange rate of the day. Like USD coverage
+        // its size in tokens moves, so it stays fixed once open
+        require!(
+            params.lst_stake_pool == Pubkey::default()
+                || (params.collateral_mint != Pubkey::default()
+                    && !params.usd_coverage
+                    && params.exercise_style == ExerciseStyle::American
+                    && params.roll_period_seconds == 0
+                    && ctx.accounts.portfolio_vault.is_none()),
+            LiqGuardError::InvalidLstCollateral
+        );
+        // Swapped payouts are SOL claimed through liquidate_policy in one
+        // piece, so there is a single amount for swap_payout to convert
+        require!(
+            params.usdc_payout_account == Pubkey::default()
+                || (params.collateral_mint == Pubkey::default()
+                    && params.exercise_style == ExerciseStyle::American
+                    && params.vesting_period_seconds == 0
+                    && ctx.accounts.config.usdc_mint != Pubkey::default()),
+            LiqGuardError::InvalidSwapPayout
+        );
+
+        // USD coverage is held to the cap in lamports when it is funded
+        require!(
+            params.usd_coverage || within_coverage_cap(&ctx.accounts.config, params.coverage_amount),
+            LiqGuardError::CoverageCapExceeded
+        );
+
+        require!(
+            params.underwriter != Pubkey::default() && params.underwriter != ctx.accounts.owner.key(),
+            LiqGuardError::InvalidUnderwriter
+        );
+
+        // A premium paid on EVM replaces the up-front and streamed lamport
+        // premiums. Its USDC price isn't quoted on-chain; the underwriter's
+        // co-signature, which every policy carries, accepts it. The policy
+        // can't pay out until the payment is redeemed through
+        // redeem_wormhole_premium
+        let cross_chain_premium = params.premium_payment_id != [0; 32];
+        if cross_chain_premium {
+            require!(
+                config.wormhole_emitter_address != [0; 32]
+                    && params.wormhole_premium > 0
+                    && params.premium_amount == 0
+                    && params.premium_rate == 0
+                    && params.roll_period_seconds == 0,
+                LiqGuardError::InvalidWormholePremium
+            );
+        } else {
+            require!(params.wormhole_premium == 0, LiqGuardError::InvalidWormholePremium);
+        }
+
+        // With on-chain pricing on, native policies must pay at least the
+        // quoted premium, counting a streaming rate over the full term.
+        // Premiums are lamports, so SPL-collateral coverage is still priced
+        // off-chain, as is USD coverage. Portfolio-backed policies are
+        // quoted at the vault's utilization once they are added to it; a
+        // segregated vault is always exactly funded, so carries no
+        // utilization markup
+        if pricing_enabled(config, &ctx.accounts.feed_registry)
+            && params.collateral_mint == Pubkey::default()
+            && !params.usd_coverage
+            && !cross_chain_premium
+        {
+            let utilization_bps = match ctx.accounts.portfolio_vault.as_ref() {
+                Some(portfolio) => portfolio_utilization(
+                    portfolio,
+                    payout::max_payout(
+                        params.payout_mode,
+                        params.coverage_amount,
+                        params.payout_cap_bps,
+                    ),
+                )?,
+                None => 0,
+            };
+            let quote = quote_premium(
+                config,
+                &ctx.accounts.feed_registry,
+                &ctx.accounts.price_feed,
+                &QuoteParams {
+                    asset: params.asset,
+                    strike_price: params.strike_price,
+                    direction: params.direction,
+                    upper_strike_price: params.upper_strike_price,
+                    coverage_amount: params.coverage_amount,
+                    expiration_datetime: params.expiration_datetime,
+                    exercise_style: params.exercise_style,
+                },
+                utilization_bps,
+            )?;
+            let streamed = (params.premium_rate as u128)
+                .checked_mul((params.expiration_datetime - now) as u128)
+                .ok_or(LiqGuardError::MathOverflow)?;
+            require!(
+                params.premium_amount as u128 + streamed >= quote as u128,
+                LiqGuardError::PremiumBelowQuote
+            );
+        }
+        require!(
+            params.beneficiary != params.underwriter,
+            LiqGuardError::InvalidBeneficiary
+        );
+
+        // Premium is paid up front to the underwriter who takes on the risk,
+        // so coverage can never be live unpaid. The protocol fee is carved
+        // out of it for the treasury, and the backstop fee for the backstop
+        let (underwriter_premium, protocol_fee, backstop_fee) = payout::split_premium(
+            params.premium_amount,
+            config.protocol_fee_bps,
+            config.backstop_fee_bps,
+        )
+        .ok_or(LiqGuardError::MathOverflow)?;
+        if underwriter_premium > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.underwriter.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, underwriter_premium)?;
+        }
+        if protocol_fee > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, protocol_fee)?;
+        }
+        if backstop_fee > 0 {
+            let backstop = ctx
+                .accounts
+                .backstop
+                .as_mut()
+                .ok_or(LiqGuardError::MissingBackstop)?;
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: backstop.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, backstop_fee)?;
+            backstop.total_contributed = backstop
+                .total_contributed
+                .checked_add(backstop_fee)
+                .ok_or(LiqGuardError::MathOverflow)?;
+        }
+
+        // A streaming premium is escrowed in the policy account and
+        // accrues to the underwriter through sync_premium_stream
+        if params.stream_deposit > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.policy.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, params.stream_deposit)?;
+        }
+
+        // Portfolio-backed policies draw on the underwriter's shared vault
+        // instead of a segregated one, so they are live at once. The
+        // policy's worst-case payout must fit in what the vault hasn't
+        // already reserved for its other policies
+        let portfolio_exposure = match ctx.accounts.portfolio_vault.as_mut() {
+            Some(portfolio) => {
+                require!(
+                    params.collateral_mint == Pubkey::default()
+                        && params.roll_period_seconds == 0
+                        && params.exercise_style == ExerciseStyle::American,
+                    LiqGuardError::InvalidPortfolioPolicy
+                );
+                let exposure = payout::max_payout(
+                    params.payout_mode,
+                    params.coverage_amount,
+                    params.payout_cap_bps,
+                );
+                require!(
+                    exposure <= unreserved_balance(portfolio)?,
+                    LiqGuardError::PortfolioExposureExceeded
+                );
+                portfolio.reserved_exposure += exposure;
+                Some((portfolio.key(), exposure))
+            }
+            None => None,
+        };
+
+        // Obligation-keyed policies trigger on health alone, so the other
+        // price-based settlement options don't apply to them
+        if params.kamino_obligation != Pubkey::default() {
+            require!(
+                params.payout_mode == PayoutMode::Binary
+                    && params.twap_window_seconds == 0
+                    && params.switchboard_feed == Pubkey::default()
+                    && params.drift_user == Pubkey::default(),
+                LiqGuardError::InvalidObligationPolicy
+            );
+            require!(
+                params.health_threshold_bps >= BPS_DENOMINATOR,
+                LiqGuardError::InvalidHealthThreshold
+            );
+            let obligation = kamino::load_obligation(
+                ctx.accounts
+                    .kamino_obligation
+                    .as_ref()
+                    .ok_or(LiqGuardError::MissingObligation)?,
+            )?;
+            require_keys_eq!(
+                obligation.owner,
+                ctx.accounts.owner.key(),
+                LiqGuardError::InvalidObligation
+            );
+        }
+
+        // Drift-verified policies must point at the owner's own Drift user
+        // holding the covered perp position. Its liquidation counter is
+        // snapshotted so only later liquidations count
+        let drift_liquidation_id = if params.drift_user != Pubkey::default() {
+            let drift_user = drift::load_user(
+                ctx.accounts
+                    .drift_user
+                    .as_ref()
+                    .ok_or(LiqGuardError::MissingDriftUser)?,
+            )?;
+            require_keys_eq!(
+                drift_user.authority,
+                ctx.accounts.owner.key(),
+                LiqGuardError::InvalidDriftUser
+            );
+            require!(
+                drift_user.has_perp_position(params.drift_perp_market_index),
+                LiqGuardError::DriftPositionNotFound
+            );
+            drift_user.next_liquidation_id
+        } else {
+            0
+        };
+
+        // Track the owner's policy counter so each new policy gets a fresh
+        // PDA, and their open coverage so no one owner concentrates it
+        let owner_state = &mut ctx.accounts.owner_state;
+        owner_state.owner = ctx.accounts.owner.key();
+        owner_state.policy_count = owner_state
+            .policy_count
+            .checked_add(1)
             .ok_or(LiqGuardError::MathOverflow)?;
+        owner_state.bump = ctx.bumps.owner_state;
+        book_owner_coverage(&ctx.accounts.config, owner_state, params.coverage_amount)?;
 
-        // Step 4: Check Direction
-        let should_liquidate = if policy.is_long_insurance {
-            // Protect Long: Pay if price drops below strike
-            // is_long_insurance = true: "I am Long BTC. I am afraid it will drop. Pay me if Price < Strike."
-            current_price < policy.strike_price
+        let policy = &mut ctx.accounts.policy;
+        policy.owner = ctx.accounts.owner.key();
+        policy.creator = ctx.accounts.owner.key();
+        policy.created_at = now;
+        policy.beneficiary = if params.beneficiary == Pubkey::default() {
+            ctx.accounts.owner.key()
         } else {
-            // Protect Short: Pay if price rises above strike
-            // is_long_insurance = false: "I am Short BTC. I am afraid it will moon. Pay me if Price > Strike."
-            current_price > policy.strike_price
+            params.beneficiary
         };
+        policy.policy_id = params.policy_id;
+        policy.asset = params.asset;
+        policy.strike_price = params.strike_price;
+        policy.direction = params.direction;
+        policy.upper_strike_price = params.upper_strike_price;
+        policy.inclusive_strike = params.inclusive_strike;
+        policy.trigger_buffer_bps = params.trigger_buffer_bps;
+        policy.confidence_adjustment = params.confidence_adjustment;
+        policy.confidence_multiplier_bps = params.confidence_multiplier_bps;
+        policy.drawdown_bps = params.drawdown_bps;
+        policy.entry_price = entry_price.mantissa;
+        policy.entry_price_exponent = entry_price.exponent;
+        policy.entry_publish_time = entry.publish_time;
+        policy.coverage_amount = params.coverage_amount;
+        policy.usd_coverage = params.usd_coverage;
+        policy.lst_stake_pool = params.lst_stake_pool;
+        policy.usdc_payout_account = params.usdc_payout_account;
+        policy.pending_swap_amount = 0;
+        policy.expiration_datetime = params.expiration_datetime;
+        policy.max_price_age = max_price_age;
+        policy.payout_mode = params.payout_mode;
+        policy.payout_slope_bps = params.payout_slope_bps;
+        policy.payout_floor_bps = params.payout_floor_bps;
+        policy.payout_cap_bps = params.payout_cap_bps;
+        policy.collateral_mint = params.collateral_mint;
+        policy.premium_amount = params.premium_amount;
+        policy.underwriter = params.underwriter;
+        policy.twap_window_seconds = params.twap_window_seconds;
+        policy.switchboard_feed = params.switchboard_feed;
+        policy.oracle_tolerance_bps = params.oracle_tolerance_bps;
+        policy.chainlink_feed = params.chainlink_feed;
+        policy.permissioned_liquidation = params.permissioned_liquidation;
+        policy.compliance_mint = params.compliance_mint;
+        policy.breach_dwell_seconds = params.breach_dwell_seconds;
+        policy.short_strike_price = params.short_strike_price;
+        policy.breach_observed_at = 0;
+        check_payout_wallet(
+            &ctx.accounts.config,
+            policy,
+            &policy.beneficiary,
+            ctx.accounts.beneficiary_credential.as_ref(),
+        )?;
+        policy.automation_thread = Pubkey::default();
+        policy.automation_price_feed = Pubkey::default();
+        policy.premium_payment_id = params.premium_payment_id;
+        policy.wormhole_premium = params.wormhole_premium;
+        policy.wormhole_premium_paid = false;
+        policy.pending_coverage_amount = 0;
+        policy.coverage_unlock_at = 0;
+        policy.pending_strike_price = 0;
+        policy.strike_unlock_at = 0;
+        policy.roll_period_seconds = params.roll_period_seconds;
+        policy.roll_premium_escrow = 0;
+        policy.premium_rate = params.premium_rate;
+        policy.stream_escrow = params.stream_deposit;
+        policy.stream_accrued_until = now;
+        policy.stream_stopped_at = 0;
+        policy.drift_user = params.drift_user;
+        policy.drift_perp_market_index = params.drift_perp_market_index;
+        policy.drift_liquidation_id = drift_liquidation_id;
+        policy.kamino_obligation = params.kamino_obligation;
+        policy.health_threshold_bps = params.health_threshold_bps;
+        policy.deviation_flagged_at = 0;
+        policy.vesting_period_seconds = params.vesting_period_seconds;
+        policy.immediate_payout_bps = params.immediate_payout_bps;
+        policy.unvested_payout = 0;
+        policy.exercise_style = params.exercise_style;
+        policy.expiry_price = 0;
+        policy.expiry_price_exponent = 0;
+        policy.expiry_price_publish_time = 0;
+        policy.barrier_kind = params.barrier_kind;
+        policy.barrier_price = params.barrier_price;
+        policy.barrier_touched_at = 0;
+        policy.backstop_draw = 0;
+        policy.holdback_amount = 0;
+        policy.holdback_release_at = 0;
+        policy.dispute_open = false;
+        policy.coverage_remaining = params.coverage_amount;
+        let (portfolio_vault, portfolio_exposure) = portfolio_exposure.unwrap_or_default();
+        policy.portfolio_vault = portfolio_vault;
+        policy.portfolio_exposure = portfolio_exposure;
+        policy.is_funded = portfolio_vault != Pubkey::default();
+        policy.event_sequence = 0;
+        // Coverage that would pay out at the entry price is already in the
+        // money; health-triggered policies don't compare against a strike
+        require!(
+            policy.kamino_obligation != Pubkey::default() || !is_triggered(policy, entry_price)?,
+            LiqGuardError::StrikeAlreadyBreached
+        );
 
-        require!(should_liquidate, LiqGuardError::LiquidationConditionNotMet);
+        policy.policy_bump = ctx.bumps.policy;
+        policy.vault_bump = ctx.bumps.vault;
+        policy.version = POLICY_VERSION;
+        if let Some(keeper_queue) = ctx.accounts.keeper_queue.as_mut() {
+            keeper_queue.track(policy.key(), policy);
+        }
 
-        // Step 5: Transfer SOL from vault to user and mark as claimed
-        let seeds = &[
-            b"vault",
-            policy.owner.as_ref(),
-            &[policy.vault_bump],
-        ];
-        let signer = &[&seeds[..]];
+        emit!(PolicyInitialized {
+            policy: policy.key(),
+            owner: policy.owner,
+            underwriter: policy.underwriter,
+            asset: policy.asset,
+            strike_price: policy.strike_price,
+            strike_exponent: STRIKE_EXPONENT,
+            direction: policy.direction,
+            upper_strike_price: policy.upper_strike_price,
+            coverage_amount: policy.coverage_amount,
+            expiration_datetime: policy.expiration_datetime,
+            entry_price: policy.entry_price,
+            entry_price_exponent: policy.entry_price_exponent,
+            entry_publish_time: policy.entry_publish_time,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
 
-        let cpi_context = CpiContext::new_with_signer(
+    // Buyer posts the coverage they want and escrows the most they'll pay
+    // for it on the request account. Any underwriter can fill it until the
+    // buyer cancels
+    pub fn post_coverage_request(
+        ctx: Context<PostCoverageRequest>,
+        params: CoverageRequestParams,
+    ) -> Result<()> {
+        require!(!ctx.accounts.config.paused, LiqGuardError::ProtocolPaused);
+        let now = Clock::get()?.unix_timestamp;
+        require!(params.expiration_datetime > now, LiqGuardError::InvalidExpiration);
+        require!(
+            params.coverage_amount > 0 && params.max_premium > 0,
+            LiqGuardError::InvalidAmount
+        );
+        require!(
+            params.strike_price > 0
+                && valid_strike_range(params.direction, params.strike_price, params.upper_strike_price),
+            LiqGuardError::InvalidStrikeRange
+        );
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.coverage_request.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, params.max_premium)?;
+
+        let request = &mut ctx.accounts.coverage_request;
+        request.owner = ctx.accounts.owner.key();
+        request.request_id = params.request_id;
+        request.asset = params.asset;
+        request.strike_price = params.strike_price;
+        request.direction = params.direction;
+        request.upper_strike_price = params.upper_strike_price;
+        request.coverage_amount = params.coverage_amount;
+        request.expiration_datetime = params.expiration_datetime;
+        request.max_premium = params.max_premium;
+        request.created_at = now;
+        request.bump = ctx.bumps.coverage_request;
+        msg!(
+            "Coverage requested: Owner={}, RequestId={}, Strike={}, Coverage={}, MaxPremium={}",
+            request.owner,
+            request.request_id,
+            request.strike_price,
+            request.coverage_amount,
+            request.max_premium
+        );
+        Ok(())
+    }
+
+    // Buyer withdraws an unfilled request; the escrow and rent go back
+    // with the account
+    pub fn cancel_coverage_request(ctx: Context<CancelCoverageRequest>) -> Result<()> {
+        msg!(
+            "Coverage request cancelled: Owner={}, RequestId={}",
+            ctx.accounts.owner.key(),
+            ctx.accounts.coverage_request.request_id
+        );
+        Ok(())
+    }
+
+    // An underwriter takes a request on at `premium`, within its
+    // max_premium and no less than the on-chain quote while pricing is
+    // on. The buyer's policy is opened and its vault funded in the same
+    // instruction, so coverage is live as soon as the fill lands. Filled
+    // policies are binary, American and spot-settled on the asset's feed.
+    // The premium comes out of the escrow with the usual treasury and
+    // backstop cuts; the rest of it goes back to the buyer with the rent
+    pub fn fill_coverage_request(
+        ctx: Context<FillCoverageRequest>,
+        policy_id: u64,
+        premium: u64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.enabled, LiqGuardError::FeedDisabled);
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let request = &ctx.accounts.coverage_request;
+        require!(
+            request.expiration_datetime > now,
+            LiqGuardError::InvalidExpiration
+        );
+        require!(
+            premium <= request.max_premium,
+            LiqGuardError::PremiumAboveRequest
+        );
+        require!(
+            within_coverage_cap(config, request.coverage_amount),
+            LiqGuardError::CoverageCapExceeded
+        );
+        if pricing_enabled(config, feed) {
+            let quote = quote_premium(
+                config,
+                feed,
+                &ctx.accounts.price_feed,
+                &QuoteParams {
+                    asset: request.asset,
+                    strike_price: request.strike_price,
+                    direction: request.direction,
+                    upper_strike_price: request.upper_strike_price,
+                    coverage_amount: request.coverage_amount,
+                    expiration_datetime: request.expiration_datetime,
+                    exercise_style: ExerciseStyle::American,
+                },
+                0,
+            )?;
+            require!(premium >= quote, LiqGuardError::PremiumBelowQuote);
+        }
+        let entry = current_spot_quote(
+            config,
+            feed,
+            &policy_feed_ref(feed, Pubkey::default()),
+            &ctx.accounts.price_feed,
+            &clock,
+        )?;
+
+        // The vault is funded through the system program before the
+        // escrow's lamports are moved directly, so no CPI runs after them
+        let cpi_context = CpiContext::new(
             ctx.accounts.system_program.to_account_info(),
             anchor_lang::system_program::Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.user.to_account_info(),
+                from: ctx.accounts.underwriter.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
             },
-            signer,
         );
+        anchor_lang::system_program::transfer(cpi_context, request.coverage_amount)?;
+        distribute_escrowed_premium(
+            config,
+            &ctx.accounts.coverage_request.to_account_info(),
+            &ctx.accounts.underwriter.to_account_info(),
+            &ctx.accounts.treasury,
+            ctx.accounts.backstop.as_mut(),
+            premium,
+        )?;
+        let request = &ctx.accounts.coverage_request;
 
-        anchor_lang::system_program::transfer(cpi_context, policy.coverage_amount)?;
+        let owner_state = &mut ctx.accounts.owner_state;
+        owner_state.owner = request.owner;
+        owner_state.policy_count = owner_state
+            .policy_count
+            .checked_add(1)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        owner_state.bump = ctx.bumps.owner_state;
+        book_owner_coverage(config, owner_state, request.coverage_amount)?;
 
-        policy.is_claimed = true;
+        // Everything not set here keeps its zero default: no buffer,
+        // confidence shift, barrier, roll, stream or vesting
+        let policy = &mut ctx.accounts.policy;
+        policy.owner = request.owner;
+        policy.creator = request.owner;
+        policy.beneficiary = request.owner;
+        policy.created_at = now;
+        policy.policy_id = policy_id;
+        policy.asset = request.asset;
+        policy.strike_price = request.strike_price;
+        policy.direction = request.direction;
+        policy.upper_strike_price = request.upper_strike_price;
+        policy.confidence_adjustment = ConfidenceAdjustment::None;
+        policy.entry_price = entry.price.mantissa;
+        policy.entry_price_exponent = entry.price.exponent;
+        policy.entry_publish_time = entry.publish_time;
+        policy.coverage_amount = request.coverage_amount;
+        policy.expiration_datetime = request.expiration_datetime;
+        policy.max_price_age = config.default_max_price_age;
+        policy.payout_mode = PayoutMode::Binary;
+        policy.premium_amount = premium;
+        policy.underwriter = ctx.accounts.underwriter.key();
+        policy.stream_accrued_until = now;
+        policy.exercise_style = ExerciseStyle::American;
+        policy.barrier_kind = BarrierKind::None;
+        policy.coverage_remaining = request.coverage_amount;
+        policy.is_funded = true;
+        require!(
+            !is_triggered(policy, entry.price)?,
+            LiqGuardError::StrikeAlreadyBreached
+        );
+        check_payout_wallet(config, policy, &policy.beneficiary, None)?;
+        policy.policy_bump = ctx.bumps.policy;
+        policy.vault_bump = ctx.bumps.vault;
+        policy.version = POLICY_VERSION;
+        ctx.accounts.keeper_queue.track(policy.key(), policy);
 
+        emit!(PolicyInitialized {
+            policy: policy.key(),
+            owner: policy.owner,
+            underwriter: policy.underwriter,
+            asset: policy.asset,
+            strike_price: policy.strike_price,
+            strike_exponent: STRIKE_EXPONENT,
+            direction: policy.direction,
+            upper_strike_price: policy.upper_strike_price,
+            coverage_amount: policy.coverage_amount,
+            expiration_datetime: policy.expiration_datetime,
+            entry_price: policy.entry_price,
+            entry_price_exponent: policy.entry_price_exponent,
+            entry_publish_time: policy.entry_publish_time,
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        emit!(VaultFunded {
+            policy: policy.key(),
+            amount: policy.coverage_amount,
+            vault_balance: ctx.accounts.vault.lamports(),
+            coverage_amount: policy.coverage_amount,
+            is_funded: true,
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
         msg!(
-            "Liquidation executed: Price={}, Strike={}, Direction={}, Amount={}",
-            current_price,
-            policy.strike_price,
-            if policy.is_long_insurance { "Long" } else { "Short" },
-            policy.coverage_amount
+            "Coverage request filled: RequestId={}, PolicyId={}, Underwriter={}, Premium={}",
+            request.request_id,
+            policy_id,
+            policy.underwriter,
+            premium
+        );
+        Ok(())
+    }
+
+    // Create the SPL token account backing a token-collateral policy. The
+    // lamport vault PDA is its authority so claims are signed the same way
+    pub fn initialize_token_vault(ctx: Context<InitializeTokenVault>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.is_native(), LiqGuardError::InvalidCollateralMint);
+        policy.token_vault_bump = ctx.bumps.token_vault;
+
+        msg!(
+            "Token vault initialized: Mint={}, Vault={}",
+            policy.collateral_mint,
+            ctx.accounts.token_vault.key()
         );
 
         Ok(())
     }
-}
 
-#[account]
-pub struct Config {
-    pub authority: Pubkey,
-    pub bump: u8,
-}
+    // Underwriter deposits collateral into the policy vault; coverage goes
+    // live once the vault holds at least coverage_amount. USD coverage goes
+    // live once the vault holds its lamport value at the current SOL price
+    pub fn fund_vault(ctx: Context<FundVault>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.config.paused, LiqGuardError::ProtocolPaused);
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        require!(!ctx.accounts.policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        require!(
+            ctx.accounts.policy.portfolio_vault == Pubkey::default(),
+            LiqGuardError::PortfolioPolicy
+        );
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.policy.expiration_datetime,
+            LiqGuardError::PolicyExpired
+        );
 
-impl Config {
-    pub const LEN: usize = 32 + 1; // authority + bump
-}
+        let vault_balance = if ctx.accounts.policy.is_native() {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.underwriter.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, amount)?;
+            ctx.accounts.vault.lamports()
+        } else {
+            let (Some(token_program), Some(token_vault), Some(underwriter_token_account)) = (
+                ctx.accounts.token_program.as_ref(),
+                ctx.accounts.token_vault.as_mut(),
+                ctx.accounts.underwriter_token_account.as_ref(),
+            ) else {
+                return err!(LiqGuardError::MissingTokenAccounts);
+            };
+            let cpi_context = CpiContext::new(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: underwriter_token_account.to_account_info(),
+                    to: token_vault.to_account_info(),
+                    authority: ctx.accounts.underwriter.to_account_info(),
+                },
+            );
+            token::transfer(cpi_context, amount)?;
+            token_vault.reload()?;
+            token_vault.amount
+        };
 
-#[derive(Accounts)]
-pub struct InitializeConfig<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Config::LEN,
-        seeds = [b"config"],
-        bump
+        let required_balance = if ctx.accounts.policy.usd_coverage {
+            let sol_price = sol_usd_price(
+                &ctx.accounts.config,
+                ctx.accounts.sol_feed_registry.as_ref(),
+                ctx.accounts.sol_price_feed.as_ref(),
+                &Clock::get()?,
+            )?;
+            let required_balance = usd_to_lamports(ctx.accounts.policy.coverage_amount, sol_price)?;
+            require!(
+                within_coverage_cap(&ctx.accounts.config, required_balance),
+                LiqGuardError::CoverageCapExceeded
+            );
+            required_balance
+        } else if ctx.accounts.policy.lst_stake_pool != Pubkey::default() {
+            lst_amount(
+                &ctx.accounts.policy,
+                ctx.accounts.lst_stake_pool.as_ref(),
+                &Clock::get()?,
+                ctx.accounts.policy.coverage_amount,
+                true,
+            )?
+        } else {
+            ctx.accounts.policy.coverage_amount
+        };
+
+        let policy = &mut ctx.accounts.policy;
+        if vault_balance >= required_balance {
+            policy.is_funded = true;
+            ctx.accounts.keeper_queue.track(policy.key(), policy);
+        }
+
+        emit!(VaultFunded {
+            policy: policy.key(),
+            amount,
+            vault_balance,
+            coverage_amount: policy.coverage_amount,
+            is_funded: policy.is_funded,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        Ok(())
+    }
+
+    // Grow an active policy: the owner or underwriter deposits `amount` more
+    // collateral and coverage rises by the same amount. Whatever the owner
+    // deposits is collateral like any other and is swept to the underwriter
+    // if the policy ends without a claim
+    pub fn top_up_coverage(ctx: Context<TopUpCoverage>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.config.paused, LiqGuardError::ProtocolPaused);
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        require!(!ctx.accounts.policy.usd_coverage, LiqGuardError::InvalidUsdCoverage);
+        require!(
+            ctx.accounts.policy.lst_stake_pool == Pubkey::default(),
+            LiqGuardError::InvalidLstCollateral
+        );
+        require!(
+            ctx.accounts.policy.claimed_amount() == 0,
+            LiqGuardError::PartiallyClaimed
+        );
+        require!(
+            ctx.accounts.policy.portfolio_vault == Pubkey::default(),
+            LiqGuardError::PortfolioPolicy
+        );
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.policy.expiration_datetime,
+            LiqGuardError::PolicyExpired
+        );
+
+        let coverage_amount = ctx
+            .accounts
+            .policy
+            .coverage_amount
+            .checked_add(amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        require!(
+            within_coverage_cap(&ctx.accounts.config, coverage_amount),
+            LiqGuardError::CoverageCapExceeded
+        );
+        book_owner_coverage(&ctx.accounts.config, &mut ctx.accounts.owner_state, amount)?;
+
+        let vault_balance = if ctx.accounts.policy.is_native() {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, amount)?;
+            ctx.accounts.vault.lamports()
+        } else {
+            let (Some(token_program), Some(token_vault), Some(depositor_token_account)) = (
+                ctx.accounts.token_program.as_ref(),
+                ctx.accounts.token_vault.as_mut(),
+                ctx.accounts.depositor_token_account.as_ref(),
+            ) else {
+                return err!(LiqGuardError::MissingTokenAccounts);
+            };
+            let cpi_context = CpiContext::new(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: depositor_token_account.to_account_info(),
+                    to: token_vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            );
+            token::transfer(cpi_context, amount)?;
+            token_vault.reload()?;
+            token_vault.amount
+        };
+
+        let policy = &mut ctx.accounts.policy;
+        policy.coverage_amount = coverage_amount;
+        policy.coverage_remaining = coverage_amount;
+        policy.is_funded = vault_balance >= coverage_amount;
+        // A top-up supersedes any pending reduction
+        policy.pending_coverage_amount = 0;
+        policy.coverage_unlock_at = 0;
+        ctx.accounts.keeper_queue.track(policy.key(), policy);
+
+        emit!(VaultToppedUp {
+            policy: policy.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+            vault_balance,
+            coverage_amount: policy.coverage_amount,
+            is_funded: policy.is_funded,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        Ok(())
+    }
+
+    // Owner asks to lower coverage. The reduction only takes effect after
+    // COVERAGE_REDUCTION_COOLDOWN, so collateral can't be pulled out from
+    // under a trigger that is about to fire
+    pub fn request_coverage_reduction(
+        ctx: Context<RequestCoverageReduction>,
+        new_coverage_amount: u64,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        let now = Clock::get()?.unix_timestamp;
+        require!(policy.claimed_amount() == 0, LiqGuardError::PartiallyClaimed);
+        require!(
+            policy.portfolio_vault == Pubkey::default(),
+            LiqGuardError::PortfolioPolicy
+        );
+        require!(!policy.usd_coverage, LiqGuardError::InvalidUsdCoverage);
+        require!(
+            policy.lst_stake_pool == Pubkey::default(),
+            LiqGuardError::InvalidLstCollateral
+        );
+        require!(now < policy.expiration_datetime, LiqGuardError::PolicyExpired);
+        require!(
+            new_coverage_amount > 0 && new_coverage_amount < policy.coverage_amount,
+            LiqGuardError::InvalidAmount
+        );
+
+        policy.pending_coverage_amount = new_coverage_amount;
+        policy.coverage_unlock_at = now
+            .checked_add(COVERAGE_REDUCTION_COOLDOWN)
+            .ok_or(LiqGuardError::MathOverflow)?;
+
+        msg!(
+            "Coverage reduction requested: Coverage={}, NewCoverage={}, UnlockAt={}",
+            policy.coverage_amount,
+            new_coverage_amount,
+            policy.coverage_unlock_at
+        );
+
+        Ok(())
+    }
+
+    // Apply a matured reduction and return the collateral above the new
+    // coverage to the underwriter. Until this runs the old coverage stays
+    // fully claimable
+    pub fn withdraw_excess_coverage(ctx: Context<WithdrawExcessCoverage>) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        require!(policy.holdback_amount == 0, LiqGuardError::HoldbackPending);
+        require!(policy.pending_swap_amount == 0, LiqGuardError::SwapPending);
+        let now = Clock::get()?.unix_timestamp;
+        require!(policy.claimed_amount() == 0, LiqGuardError::PartiallyClaimed);
+        require!(now < policy.expiration_datetime, LiqGuardError::PolicyExpired);
+        require!(
+            policy.coverage_unlock_at != 0,
+            LiqGuardError::NoPendingCoverageReduction
+        );
+        require!(
+            now >= policy.coverage_unlock_at,
+            LiqGuardError::CoverageCooldownActive
+        );
+
+        let new_coverage_amount = policy.pending_coverage_amount;
+        let vault = ctx.accounts.vault.to_account_info();
+        let excess = if policy.is_native() {
+            let excess = vault.lamports().saturating_sub(new_coverage_amount);
+            if excess > 0 {
+                vault_transfer(
+                    &ctx.accounts.system_program.to_account_info(),
+                    &vault,
+                    &ctx.accounts.underwriter,
+                    policy,
+                    excess,
+                )?;
+            }
+            excess
+        } else {
+            let (Some(token_program), Some(token_vault), Some(underwriter_token_account)) = (
+                ctx.accounts.token_program.as_ref(),
+                ctx.accounts.token_vault.as_ref(),
+                ctx.accounts.underwriter_token_account.as_ref(),
+            ) else {
+                return err!(LiqGuardError::MissingTokenAccounts);
+            };
+            let excess = token_vault.amount.saturating_sub(new_coverage_amount);
+            if excess > 0 {
+                token_vault_transfer(
+                    &token_program.to_account_info(),
+                    &token_vault.to_account_info(),
+                    &underwriter_token_account.to_account_info(),
+                    &vault,
+                    policy,
+                    excess,
+                )?;
+            }
+            excess
+        };
+        let vault_balance = collateral_balance(
+            &ctx.accounts.policy,
+            &ctx.accounts.vault,
+            ctx.accounts.token_vault.as_mut(),
+        )?;
+
+        let policy = &mut ctx.accounts.policy;
+        let owner_state = &mut ctx.accounts.owner_state;
+        owner_state.active_coverage = owner_state
+            .active_coverage
+            .saturating_sub(policy.coverage_amount - new_coverage_amount);
+        policy.coverage_amount = new_coverage_amount;
+        policy.coverage_remaining = new_coverage_amount;
+        policy.pending_coverage_amount = 0;
+        policy.coverage_unlock_at = 0;
+
+        msg!(
+            "Excess coverage withdrawn: Amount={}, Coverage={}",
+            excess,
+            policy.coverage_amount
+        );
+        emit!(VaultReclaimed {
+            policy: policy.key(),
+            underwriter: policy.underwriter,
+            amount: excess,
+            vault_balance,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        Ok(())
+    }
+
+    // Owner queues a new strike. It only applies after the config's
+    // strike_update_delay, so a strike can't be dragged across the market
+    // just as the price gets there
+    pub fn request_strike_update(ctx: Context<UpdateStrike>, new_strike_price: u64) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        let now = Clock::get()?.unix_timestamp;
+        require!(policy.claimed_amount() == 0, LiqGuardError::PartiallyClaimed);
+        require!(now < policy.expiration_datetime, LiqGuardError::PolicyExpired);
+        // A drawdown policy's strike is pinned to its entry price
+        require!(
+            new_strike_price > 0
+                && new_strike_price != policy.strike_price
+                && policy.drawdown_bps == 0,
+            LiqGuardError::InvalidStrikePrice
+        );
+        require!(
+            valid_strike_range(policy.direction, new_strike_price, policy.upper_strike_price),
+            LiqGuardError::InvalidStrikeRange
+        );
+        require!(
+            valid_spread(policy.payout_mode, policy.direction, new_strike_price, policy.short_strike_price),
+            LiqGuardError::InvalidSpread
+        );
+
+        policy.pending_strike_price = new_strike_price;
+        policy.strike_unlock_at = now
+            .checked_add(ctx.accounts.config.strike_update_delay)
+            .ok_or(LiqGuardError::MathOverflow)?;
+
+        msg!(
+            "Strike update requested: Strike={}, NewStrike={}, UnlockAt={}",
+            policy.strike_price,
+            new_strike_price,
+            policy.strike_unlock_at
+        );
+
+        Ok(())
+    }
+
+    pub fn apply_strike_update(ctx: Context<UpdateStrike>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        let now = Clock::get()?.unix_timestamp;
+        require!(policy.claimed_amount() == 0, LiqGuardError::PartiallyClaimed);
+        require!(now < policy.expiration_datetime, LiqGuardError::PolicyExpired);
+        require!(
+            policy.strike_unlock_at != 0,
+            LiqGuardError::NoPendingStrikeUpdate
+        );
+        require!(
+            now >= policy.strike_unlock_at,
+            LiqGuardError::StrikeUpdateDelayActive
+        );
+
+        let previous_strike = policy.strike_price;
+        policy.strike_price = policy.pending_strike_price;
+        policy.pending_strike_price = 0;
+        policy.strike_unlock_at = 0;
+        ctx.accounts.keeper_queue.track(policy.key(), policy);
+
+        msg!(
+            "Strike updated: From={}, To={}",
+            previous_strike,
+            policy.strike_price
+        );
+
+        Ok(())
+    }
+
+    // Owner redirects future payouts without giving up control of the policy
+    pub fn set_beneficiary(ctx: Context<SetBeneficiary>, beneficiary: Pubkey) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        require!(
+            beneficiary != Pubkey::default() && beneficiary != policy.underwriter,
+            LiqGuardError::InvalidBeneficiary
+        );
+        check_payout_wallet(
+            &ctx.accounts.config,
+            policy,
+            &beneficiary,
+            ctx.accounts.beneficiary_credential.as_ref(),
+        )?;
+
+        policy.beneficiary = beneficiary;
+        policy.usdc_payout_account = Pubkey::default();
+        msg!(
+            "Beneficiary updated: PolicyId={}, Beneficiary={}",
+            policy.policy_id,
+            beneficiary
+        );
+
+        Ok(())
+    }
+
+    // Owner splits future payouts across up to MAX_PAYOUT_SPLITS
+    // recipients by basis-point shares adding up to 100%, so a shared
+    // treasury is paid out on-chain rather than by a trusted distributor.
+    // Liquidations then pay each recipient its share in place of the
+    // beneficiary. An empty list pays the beneficiary alone again
+    pub fn set_payout_split(ctx: Context<SetBeneficiary>, shares: Vec<PayoutShare>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        require!(
+            shares.is_empty()
+                || (policy.vesting_period_seconds == 0
+                    && policy.usdc_payout_account == Pubkey::default()
+                    && compliance_mint(&ctx.accounts.config, policy) == Pubkey::default()),
+            LiqGuardError::PayoutSplitUnsupported
+        );
+        check_payout_split(&shares, &policy.underwriter)?;
+
+        policy.payout_splits = [PayoutShare::default(); MAX_PAYOUT_SPLITS];
+        policy.payout_splits[..shares.len()].copy_from_slice(&shares);
+        msg!(
+            "Payout split updated: PolicyId={}, Recipients={}",
+            policy.policy_id,
+            shares.len()
+        );
+
+        Ok(())
+    }
+
+    // Owner chooses whether anyone may settle the policy, or only the owner
+    // and the keepers on the config allowlist
+    pub fn set_permissioned_liquidation(
+        ctx: Context<SetPermissionedLiquidation>,
+        permissioned: bool,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        policy.permissioned_liquidation = permissioned;
+        msg!(
+            "Liquidation permissions updated: PolicyId={}, Permissioned={}",
+            policy.policy_id,
+            permissioned
+        );
+        Ok(())
+    }
+
+    // Owner hands liquidation to an automation thread (a Clockwork-style
+    // thread or keeper-registry signer). The thread cranks liquidate_policy
+    // with the accounts from automation_crank_accounts, and may do so even
+    // on a permissioned policy. Only plain native American policies can be
+    // automated: TWAP updates and USD conversions need keeper-posted data
+    pub fn register_automation(
+        ctx: Context<ManageAutomation>,
+        thread: Pubkey,
+        price_feed: Pubkey,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        require!(
+            thread != Pubkey::default()
+                && price_feed != Pubkey::default()
+                && policy.is_native()
+                && policy.exercise_style == ExerciseStyle::American
+                && policy.twap_window_seconds == 0
+                && !policy.usd_coverage,
+            LiqGuardError::InvalidAutomation
+        );
+        policy.automation_thread = thread;
+        policy.automation_price_feed = price_feed;
+
+        emit!(AutomationRegistered {
+            policy: policy.key(),
+            thread,
+            price_feed,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    pub fn deregister_automation(ctx: Context<ManageAutomation>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(
+            policy.automation_thread != Pubkey::default(),
+            LiqGuardError::InvalidAutomation
+        );
+        let thread = policy.automation_thread;
+        policy.automation_thread = Pubkey::default();
+        policy.automation_price_feed = Pubkey::default();
+
+        emit!(AutomationDeregistered {
+            policy: policy.key(),
+            thread,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // The account metas an automated policy's thread passes to
+    // liquidate_policy, in order. Optional accounts it doesn't need are the
+    // program id, as Anchor expects. Pass the backstop, treasury, audit log
+    // and claim history to have them included. A payout split's recipients
+    // follow the named accounts. Read it via simulation or from return data
+    pub fn automation_crank_accounts(ctx: Context<AutomationCrankAccounts>) -> Result<Vec<CrankAccount>> {
+        let policy = &ctx.accounts.policy;
+        require!(
+            policy.automation_thread != Pubkey::default(),
+            LiqGuardError::InvalidAutomation
+        );
+        let optional = |key: Pubkey, is_writable: bool| {
+            if key == Pubkey::default() {
+                CrankAccount::readonly(crate::ID)
+            } else {
+                CrankAccount { pubkey: key, is_signer: false, is_writable }
+            }
+        };
+        let backstop = ctx.accounts.backstop.as_ref().map(|backstop| backstop.key());
+
+        let mut accounts = vec![
+            CrankAccount::writable(policy.key()),
+            CrankAccount::writable(seeds::keeper_queue(&crate::ID, policy.asset).0),
+            CrankAccount::readonly(policy.automation_price_feed),
+            CrankAccount::readonly(seeds::feed_registry(&crate::ID, policy.asset).0),
+            CrankAccount::readonly(crate::ID), // sol_feed_registry
+            CrankAccount::readonly(crate::ID), // sol_price_feed
+            CrankAccount::readonly(crate::ID), // lst_stake_pool
+            CrankAccount::readonly(crate::ID), // twap_update
+            optional(
+                ctx.accounts.audit_log.as_ref().map(|audit_log| audit_log.key()).unwrap_or_default(),
+                true,
+            ),
+            optional(
+                ctx.accounts
+                    .claim_history
+                    .as_ref()
+                    .map(|claim_history| claim_history.key())
+                    .unwrap_or_default(),
+                true,
+            ),
+            optional(policy.switchboard_feed, false),
+            optional(policy.drift_user, false),
+            optional(policy.kamino_obligation, false),
+            CrankAccount::writable(seeds::vault(&crate::ID, &policy.creator, policy.policy_id).0),
+            CrankAccount::writable(policy.beneficiary),
+            CrankAccount::readonly(seeds::config(&crate::ID).0),
+            CrankAccount {
+                pubkey: policy.automation_thread,
+                is_signer: true,
+                is_writable: true,
+            },
+            if policy.vesting_period_seconds > 0 {
+                CrankAccount::writable(seeds::claim_state(&crate::ID, &policy.key()).0)
+            } else {
+                CrankAccount::readonly(crate::ID)
+            },
+            optional(backstop.unwrap_or_default(), true),
+            optional(
+                ctx.accounts.treasury.as_ref().map(|treasury| treasury.key()).unwrap_or_default(),
+                true,
+            ),
+            if backstop.is_some() {
+                CrankAccount::writable(seeds::underwriter(&crate::ID, &policy.underwriter).0)
+            } else {
+                CrankAccount::readonly(crate::ID)
+            },
+            optional(policy.portfolio_vault, true),
+            optional(policy.master_agreement, true),
+            // beneficiary_credential, for policies gated on their own
+            // credential; the crank can't see the config's
+            optional(
+                if policy.compliance_mint == Pubkey::default() {
+                    Pubkey::default()
+                } else {
+                    anchor_spl::associated_token::get_associated_token_address(
+                        &policy.beneficiary,
+                        &policy.compliance_mint,
+                    )
+                },
+                false,
+            ),
+            CrankAccount::readonly(crate::ID), // token_vault
+            CrankAccount::readonly(crate::ID), // beneficiary_token_account
+            CrankAccount::readonly(crate::ID), // liquidator_token_account
+            CrankAccount::readonly(crate::ID), // token_program
+            CrankAccount::readonly(System::id()),
+        ];
+        accounts.extend(
+            policy
+                .payout_shares()
+                .iter()
+                .map(|share| CrankAccount::writable(share.recipient)),
+        );
+        Ok(accounts)
+    }
+
+    // Permissionless: activate a policy whose premium was paid on EVM. The
+    // VAA must come from the configured collector, name this policy and its
+    // payment id, and cover the agreed USDC amount. The payment receipt PDA
+    // makes each payment id redeemable once
+    pub fn redeem_wormhole_premium(ctx: Context<RedeemWormholePremium>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let vaa = wormhole::load_posted_vaa(&ctx.accounts.posted_vaa)?;
+        require!(
+            config.wormhole_emitter_address != [0; 32]
+                && vaa.emitter_chain == config.wormhole_emitter_chain
+                && vaa.emitter_address == config.wormhole_emitter_address,
+            LiqGuardError::InvalidWormholeMessage
+        );
+        let payment = wormhole::PremiumPayment::parse(&vaa.payload)?;
+
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.wormhole_premium_paid, LiqGuardError::WormholePaymentMismatch);
+        require!(
+            payment.policy == policy.key()
+                && payment.payment_id == policy.premium_payment_id
+                && payment.amount >= policy.wormhole_premium,
+            LiqGuardError::WormholePaymentMismatch
+        );
+        policy.wormhole_premium_paid = true;
+
+        let receipt = &mut ctx.accounts.wormhole_payment;
+        receipt.policy = policy.key();
+        receipt.amount = payment.amount;
+        receipt.vaa_sequence = vaa.sequence;
+        receipt.bump = ctx.bumps.wormhole_payment;
+
+        emit!(WormholePremiumRedeemed {
+            policy: policy.key(),
+            payment_id: payment.payment_id,
+            amount: payment.amount,
+            vaa_sequence: vaa.sequence,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // Hand a policy (and its payout) to a new wallet. PDAs are derived
+    // from the immutable creator, so the accounts don't move
+    pub fn transfer_policy(ctx: Context<TransferPolicy>, new_owner: Pubkey) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        require!(
+            policy.master_agreement == Pubkey::default(),
+            LiqGuardError::PolicyInMasterAgreement
+        );
+        require!(
+            new_owner != Pubkey::default()
+                && new_owner != policy.owner
+                && new_owner != policy.underwriter,
+            LiqGuardError::InvalidNewOwner
+        );
+
+        // The payout follows the policy to its new holder, and so does its
+        // coverage, which must fit under the new owner's cap
+        let owner_state = &mut ctx.accounts.owner_state;
+        owner_state.active_coverage = owner_state
+            .active_coverage
+            .saturating_sub(policy.coverage_amount);
+        let new_owner_state = &mut ctx.accounts.new_owner_state;
+        new_owner_state.owner = new_owner;
+        new_owner_state.bump = ctx.bumps.new_owner_state;
+        book_owner_coverage(&ctx.accounts.config, new_owner_state, policy.coverage_amount)?;
+
+        let previous_owner = policy.owner;
+        policy.owner = new_owner;
+        policy.beneficiary = new_owner;
+        policy.usdc_payout_account = Pubkey::default();
+        policy.payout_splits = [PayoutShare::default(); MAX_PAYOUT_SPLITS];
+        policy.automation_thread = Pubkey::default();
+        policy.automation_price_feed = Pubkey::default();
+
+        msg!(
+            "Policy transferred: PolicyId={}, From={}, To={}",
+            policy.policy_id,
+            previous_owner,
+            new_owner
+        );
+
+        Ok(())
+    }
+
+    // Returns the claim's payout (after the bounty and fee) via return data,
+    // or 0 when the attempt only recorded a breach or flagged a deviation
+    // A policy with a payout split takes its recipients' accounts as
+    // remaining_accounts, in share order
+    pub fn liquidate_policy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LiquidatePolicy<'info>>,
+    ) -> Result<u64> {
+        require!(!ctx.accounts.config.paused, LiqGuardError::ProtocolPaused);
+        let policy = &mut ctx.accounts.policy;
+        require!(
+            policy.may_liquidate(&ctx.accounts.config, &ctx.accounts.liquidator.key()),
+            LiqGuardError::LiquidatorNotAllowed
+        );
+        
+        // Policy-side checks: coverage left and live, claimable through
+        // this path, and not expired, lapsed or too new
+        let now = Clock::get()?.unix_timestamp;
+        check_claimable(policy, &ctx.accounts.config, now)?;
+        check_payout_wallet(
+            &ctx.accounts.config,
+            policy,
+            &policy.beneficiary,
+            ctx.accounts.beneficiary_credential.as_ref(),
+        )?;
+
+        // A spot price inside the feed's staleness and confidence limits,
+        // then every trigger the policy carries (price or Kamino health,
+        // TWAP, Switchboard, Drift)
+        let clock = Clock::get()?;
+        let feed = &ctx.accounts.feed_registry;
+        let price_info = read_spot_price(policy, &ctx.accounts.config, feed, &ctx.accounts.price_feed, &clock)?;
+        let spot_price = price_info.price;
+        let TriggerReading {
+            settlement_price: current_price,
+            publish_time: price_publish_time,
+            drift_liquidation_id,
+        } = evaluate_trigger(
+            policy,
+            &ctx.accounts.config,
+            feed,
+            &price_info,
+            &TriggerAccounts {
+                twap_update: ctx.accounts.twap_update.as_ref(),
+                switchboard_feed: ctx.accounts.switchboard_feed.as_ref(),
+                drift_user: ctx.accounts.drift_user.as_ref(),
+                kamino_obligation: ctx.accounts.kamino_obligation.as_ref(),
+            },
+            &clock,
+        )?;
+
+        // Hysteresis, the deviation breaker and claim sizing, as
+        // decide_payout documents. The master agreement draw and the USD
+        // and LST conversions are the steps that need this instruction's
+        // accounts
+        let config = &ctx.accounts.config;
+        let master_agreement = ctx.accounts.master_agreement.as_mut();
+        let sol_feed_registry = ctx.accounts.sol_feed_registry.as_ref();
+        let sol_price_feed = ctx.accounts.sol_price_feed.as_ref();
+        let lst_stake_pool = ctx.accounts.lst_stake_pool.as_ref();
+        let decision = decide_payout(
+            policy,
+            config,
+            &price_info,
+            current_price,
+            now,
+            |claim_amount| draw_master_agreement(policy, master_agreement, claim_amount),
+            |claim_amount| {
+                if policy.usd_coverage {
+                    let sol_price = sol_usd_price(config, sol_feed_registry, sol_price_feed, &clock)?;
+                    usd_to_lamports(claim_amount, sol_price)
+                } else if policy.lst_stake_pool != Pubkey::default() {
+                    lst_amount(policy, lst_stake_pool, &clock, claim_amount, false)
+                } else {
+                    Ok(claim_amount)
+                }
+            },
+        )?;
+        let (claim_amount, payout, bounty, fee) = match decision {
+            PayoutDecision::Pay { claim, payout, bounty, fee } => (claim, payout, bounty, fee),
+            PayoutDecision::ObserveBreach { confirmable_from } => {
+                policy.breach_observed_at = now;
+                msg!(
+                    "Strike breach observed: Price={}e{}, ConfirmAfter={}",
+                    current_price.mantissa,
+                    current_price.exponent,
+                    confirmable_from
+                );
+                record_audit(
+                    ctx.accounts.audit_log.as_ref(),
+                    AuditEntry::new(policy.key(), current_price, AUDIT_BREACH_OBSERVED, 0, &clock),
+                )?;
+                return Ok(0);
+            }
+            PayoutDecision::FlagDeviation { ema_price, confirmable_from } => {
+                policy.deviation_flagged_at = now;
+                msg!(
+                    "Price deviation flagged: Price={}e{}, Ema={}e{}, ConfirmAfter={}",
+                    spot_price.mantissa,
+                    spot_price.exponent,
+                    ema_price.mantissa,
+                    ema_price.exponent,
+                    confirmable_from
+                );
+                record_audit(
+                    ctx.accounts.audit_log.as_ref(),
+                    AuditEntry::new(policy.key(), spot_price, AUDIT_DEVIATION_FLAGGED, 0, &clock),
+                )?;
+                return Ok(0);
+            }
+            PayoutDecision::Reject(code) => return Err(ProgramError::Custom(code).into()),
+        };
+        policy.deviation_flagged_at = 0;
+        policy.breach_observed_at = 0;
+
+        // Vesting policies only pay the first tranche now and stream the
+        // rest out of the vault through claim_tranche. The bounty is paid
+        // in full either way
+        let mut paid_now = if policy.vesting_period_seconds > 0 {
+            let claim_state = ctx
+                .accounts
+                .claim_state
+                .as_mut()
+                .ok_or(LiqGuardError::MissingClaimState)?;
+            let immediate = payout::share_of(payout, policy.immediate_payout_bps)
+                .ok_or(LiqGuardError::MathOverflow)?;
+            claim_state.policy = policy.key();
+            claim_state.total_amount = payout;
+            claim_state.immediate_amount = immediate;
+            claim_state.claimed_amount = immediate;
+            claim_state.vesting_start = now;
+            claim_state.vesting_end = now.saturating_add(policy.vesting_period_seconds);
+            claim_state.bump = ctx.bumps.claim_state.ok_or(LiqGuardError::MissingClaimState)?;
+            policy.unvested_payout = payout - immediate;
+            immediate
+        } else {
+            require!(
+                ctx.accounts.claim_state.is_none(),
+                LiqGuardError::InvalidVestingSchedule
+            );
+            payout
+        };
+
+        // Portfolio-backed policies move the claim out of the shared vault
+        // into their own first, then pay and vest from it like any other
+        if policy.portfolio_vault != Pubkey::default() {
+            let portfolio = ctx
+                .accounts
+                .portfolio_vault
+                .as_mut()
+                .ok_or(LiqGuardError::MissingPortfolioVault)?;
+            portfolio.sub_lamports(claim_amount)?;
+            ctx.accounts.vault.add_lamports(claim_amount)?;
+            portfolio.reserved_exposure = portfolio.reserved_exposure.saturating_sub(claim_amount);
+            policy.portfolio_exposure = policy.portfolio_exposure.saturating_sub(claim_amount);
+        }
+
+        // A native vault that can't cover the claim is topped up from the
+        // backstop, up to max_backstop_draw, and the draw is booked as the
+        // underwriter's debt. Whatever the backstop can't cover comes out
+        // of the payout
+        let payable = paid_now
+            .checked_add(bounty)
+            .and_then(|payable| payable.checked_add(fee))
+            .ok_or(LiqGuardError::MathOverflow)?;
+        let shortfall = payable.saturating_sub(ctx.accounts.vault.lamports());
+        if policy.is_native() && shortfall > 0 {
+            let backstop = ctx
+                .accounts
+                .backstop
+                .as_mut()
+                .ok_or(LiqGuardError::MissingBackstop)?;
+            let rent_floor = Rent::get()?.minimum_balance(8 + Backstop::LEN);
+            let draw = shortfall
+                .min(ctx.accounts.config.max_backstop_draw)
+                .min(backstop.get_lamports().saturating_sub(rent_floor));
+            if draw > 0 {
+                backstop.sub_lamports(draw)?;
+                ctx.accounts.vault.add_lamports(draw)?;
+                backstop.total_drawn = backstop
+                    .total_drawn
+                    .checked_add(draw)
+                    .ok_or(LiqGuardError::MathOverflow)?;
+
+                let record = ctx
+                    .accounts
+                    .underwriter_record
+                    .as_mut()
+                    .ok_or(LiqGuardError::MissingUnderwriterRecord)?;
+                record.underwriter = policy.underwriter;
+                record.backstop_debt = record
+                    .backstop_debt
+                    .checked_add(draw)
+                    .ok_or(LiqGuardError::MathOverflow)?;
+                record.bump = ctx
+                    .bumps
+                    .underwriter_record
+                    .ok_or(LiqGuardError::MissingUnderwriterRecord)?;
+                policy.backstop_draw = draw;
+
+                emit!(BackstopDrawn {
+                    policy: policy.key(),
+                    underwriter: policy.underwriter,
+                    amount: draw,
+                    shortfall,
+                    underwriter_debt: record.backstop_debt,
+                    slot: clock.slot,
+                    sequence: next_event_sequence(policy)?,
+                });
+            }
+            let uncovered = shortfall - draw;
+            require!(uncovered <= paid_now, LiqGuardError::VaultNotFunded);
+            paid_now -= uncovered;
+        }
+        paid_now -= hold_back(&ctx.accounts.config, policy, paid_now, now)?;
+        if policy.is_native() {
+            paid_now = rent_exempt_payout(
+                ctx.accounts.vault.lamports(),
+                paid_now,
+                bounty + fee,
+                Rent::get()?.minimum_balance(0),
+            );
+        }
+        // USDC-payout policies leave the payout in the vault for
+        // swap_payout to convert and deliver
+        if policy.usdc_payout_account != Pubkey::default() {
+            policy.pending_swap_amount = paid_now;
+            paid_now = 0;
+        }
+
+        // Step 6: Transfer collateral from vault to user and liquidator, mark as claimed
+        pay_claim(
+            policy,
+            &ctx.accounts.vault,
+            &ctx.accounts.beneficiary,
+            &ctx.accounts.liquidator,
+            ctx.accounts.token_vault.as_ref(),
+            ctx.accounts.beneficiary_token_account.as_ref(),
+            ctx.accounts.liquidator_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            paid_now,
+            bounty,
+        )?;
+        collect_payout_fee(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.vault.to_account_info(),
+            ctx.accounts.treasury.as_mut(),
+            policy,
+            fee,
+        )?;
+        let vault_balance =
+            collateral_balance(policy, &ctx.accounts.vault, ctx.accounts.token_vault.as_mut())?;
+        emit!(VaultPaidOut {
+            policy: policy.key(),
+            beneficiary: policy.beneficiary,
+            amount: paid_now,
+            bounty,
+            vault_balance,
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        policy.coverage_remaining -= claim_amount;
+        policy.drift_liquidation_id = drift_liquidation_id;
+        policy.record_settlement(
+            current_price,
+            price_publish_time,
+            clock.slot,
+            ctx.accounts.liquidator.key(),
+            payout,
+            bounty,
+        );
+        if policy.is_exhausted() {
+            policy.stop_premium_stream(now);
+            unlist_policy(ctx.accounts.keeper_queue.as_mut(), &policy.key());
+        }
+
+        emit!(LiquidationExecuted {
+            policy: policy.key(),
+            liquidator: ctx.accounts.liquidator.key(),
+            asset: policy.asset,
+            price: current_price.mantissa,
+            price_exponent: current_price.exponent,
+            strike_price: policy.strike_price,
+            strike_exponent: STRIKE_EXPONENT,
+            payout,
+            bounty,
+            coverage_remaining: policy.coverage_remaining,
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        record_audit(
+            ctx.accounts.audit_log.as_ref(),
+            AuditEntry::new(policy.key(), current_price, AUDIT_PAID, 0, &clock),
+        )?;
+        record_claim(
+            ctx.accounts.claim_history.as_ref(),
+            ClaimRecord::new(policy.key(), current_price, payout, clock.unix_timestamp),
+        )?;
+
+        Ok(payout)
+    }
+
+    // Underwriter or admin disputes a liquidation as oracle manipulation
+    // while its holdback is still in the vault. The policy's vault is
+    // frozen until the arbiter resolves it
+    pub fn flag_dispute(ctx: Context<FlagDispute>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            policy.holdback_amount > 0 && now < policy.holdback_release_at,
+            LiqGuardError::OutsideDisputeWindow
+        );
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.policy = policy.key();
+        dispute.flagged_by = ctx.accounts.flagger.key();
+        dispute.holdback_amount = policy.holdback_amount;
+        dispute.flagged_at = now;
+        dispute.bump = ctx.bumps.dispute;
+        policy.dispute_open = true;
+
+        emit!(LiquidationDisputed {
+            policy: policy.key(),
+            flagged_by: dispute.flagged_by,
+            holdback_amount: dispute.holdback_amount,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // Arbiter settles a dispute: a confirmed liquidation releases the
+    // holdback to the beneficiary, a rejected one claws it back to the
+    // underwriter. Either way the vault unfreezes
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, confirm: bool) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        let amount = policy.holdback_amount;
+        let recipient = if confirm {
+            &ctx.accounts.beneficiary
+        } else {
+            &ctx.accounts.underwriter
+        };
+        vault_transfer(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.vault.to_account_info(),
+            recipient,
+            policy,
+            amount,
+        )?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.holdback_amount = 0;
+        policy.holdback_release_at = 0;
+        policy.dispute_open = false;
+
+        emit!(DisputeResolved {
+            policy: policy.key(),
+            arbiter: ctx.accounts.arbiter.key(),
+            confirmed: confirm,
+            amount,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // Permissionless: pay out an undisputed holdback once its window
+    // closes. Split payouts take the recipients as remaining_accounts, as
+    // liquidate_policy does
+    pub fn release_holdback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseHoldback<'info>>,
+    ) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        require!(policy.holdback_amount > 0, LiqGuardError::NothingToClaim);
+        require!(!policy.dispute_open, LiqGuardError::DisputeOpen);
+        require!(
+            Clock::get()?.unix_timestamp >= policy.holdback_release_at,
+            LiqGuardError::HoldbackPending
+        );
+        let amount = policy.holdback_amount;
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let vault = ctx.accounts.vault.to_account_info();
+        if policy.has_payout_split() {
+            pay_split(policy, &system_program, &vault, None, ctx.remaining_accounts, amount)?;
+        } else {
+            vault_transfer(&system_program, &vault, &ctx.accounts.beneficiary, policy, amount)?;
+        }
+
+        let policy = &mut ctx.accounts.policy;
+        policy.holdback_amount = 0;
+        policy.holdback_release_at = 0;
+        emit!(VaultPaidOut {
+            policy: policy.key(),
+            beneficiary: policy.beneficiary,
+            amount,
+            bounty: 0,
+            vault_balance: ctx.accounts.vault.lamports(),
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // Permissionless: swap a USDC-payout policy's pending payout through
+    // a Jupiter route and deliver the USDC to the beneficiary. The vault
+    // signs as the route's user; remaining_accounts are the route's
+    // accounts. The swap must return at least the oracle value of the SOL
+    // less max_swap_slippage_bps and may not touch any other vault funds
+    pub fn swap_payout<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapPayout<'info>>,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        let policy = &ctx.accounts.policy;
+        let amount = policy.pending_swap_amount;
+        require!(amount > 0, LiqGuardError::NothingToClaim);
+
+        let clock = Clock::get()?;
+        let sol_price = sol_usd_price(
+            config,
+            Some(&ctx.accounts.sol_feed_registry),
+            Some(&ctx.accounts.sol_price_feed),
+            &clock,
+        )?;
+        let min_out = payout::shifted(
+            lamports_to_usdc(amount, sol_price)?,
+            config.max_swap_slippage_bps,
+            false,
+        )
+        .ok_or(LiqGuardError::MathOverflow)?;
+
+        // Wrap the payout in the vault-owned swap source
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let vault = ctx.accounts.vault.to_account_info();
+        let swap_source = ctx.accounts.swap_source.to_account_info();
+        vault_transfer(
+            &ctx.accounts.system_program.to_account_info(),
+            &vault,
+            &swap_source,
+            policy,
+            amount,
+        )?;
+        token::sync_native(CpiContext::new(
+            token_program.clone(),
+            token::SyncNative {
+                account: swap_source.clone(),
+            },
+        ))?;
+
+        let vault_lamports = vault.lamports();
+        let usdc_before = ctx.accounts.usdc_destination.amount;
+        let policy_id_bytes = policy.policy_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            VAULT_SEED,
+            policy.creator.as_ref(),
+            policy_id_bytes.as_ref(),
+            &[policy.vault_bump],
+        ];
+        jupiter::swap(
+            &ctx.accounts.jupiter_program.to_account_info(),
+            route_data,
+            ctx.remaining_accounts,
+            &vault.key(),
+            seeds,
+        )?;
+
+        ctx.accounts.swap_source.reload()?;
+        ctx.accounts.usdc_destination.reload()?;
+        require!(
+            ctx.accounts.swap_source.amount == 0 && vault.lamports() == vault_lamports,
+            LiqGuardError::InvalidSwapRoute
+        );
+        let usdc_amount = ctx
+            .accounts
+            .usdc_destination
+            .amount
+            .saturating_sub(usdc_before);
+        require!(usdc_amount >= min_out, LiqGuardError::SwapSlippageExceeded);
+
+        // The emptied swap source's rent goes back to the keeper
+        token::close_account(CpiContext::new_with_signer(
+            token_program,
+            token::CloseAccount {
+                account: swap_source,
+                destination: ctx.accounts.keeper.to_account_info(),
+                authority: vault,
+            },
+            &[seeds],
+        ))?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.pending_swap_amount = 0;
+        emit!(PayoutSwapped {
+            policy: policy.key(),
+            beneficiary: policy.beneficiary,
+            lamports: amount,
+            usdc_amount,
+            min_usdc_amount: min_out,
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // Owner takes a pending swapped payout in SOL instead, e.g. when no
+    // route clears the slippage bound
+    pub fn cancel_payout_swap(ctx: Context<CancelPayoutSwap>) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        let amount = policy.pending_swap_amount;
+        require!(amount > 0, LiqGuardError::NothingToClaim);
+        vault_transfer(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.beneficiary,
+            policy,
+            amount,
+        )?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.pending_swap_amount = 0;
+        emit!(VaultPaidOut {
+            policy: policy.key(),
+            beneficiary: policy.beneficiary,
+            amount,
+            bounty: 0,
+            vault_balance: ctx.accounts.vault.lamports(),
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // Permissionless: log a settlement attempt in the audit log, including
+    // ones that fail. A failed transaction can't write anything, so keepers
+    // post this after an attempt fails; the policy and price checks are
+    // re-run here, so the logged reason doesn't rest on the keeper's word
+    pub fn record_settlement_attempt(ctx: Context<RecordSettlementAttempt>) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        let config = &ctx.accounts.config;
+        let feed = &ctx.accounts.feed_registry;
+        let clock = Clock::get()?;
+        let (price, outcome) = check_eligibility(
+            policy,
+            config,
+            feed,
+            &ctx.accounts.price_feed,
+            &TriggerAccounts {
+                twap_update: ctx.accounts.twap_update.as_ref(),
+                switchboard_feed: ctx.accounts.switchboard_feed.as_ref(),
+                drift_user: ctx.accounts.drift_user.as_ref(),
+                kamino_obligation: ctx.accounts.kamino_obligation.as_ref(),
+            },
+            &clock,
+        );
+
+        // Checks only read state, so a failed one can be logged and the
+        // instruction still succeed
+        let entry = match outcome {
+            Ok(()) => AuditEntry::new(policy.key(), price, AUDIT_TRIGGERABLE, 0, &clock),
+            Err(error) => AuditEntry::new(
+                policy.key(),
+                price,
+                AUDIT_REJECTED,
+                error_code_number(&error),
+                &clock,
+            ),
+        };
+        msg!(
+            "Settlement attempt logged: Policy={}, Decision={}, ErrorCode={}",
+            entry.policy,
+            entry.decision,
+            entry.error_code
+        );
+        ctx.accounts.audit_log.load_mut()?.record(entry);
+        Ok(())
+    }
+
+    // Whether liquidate_policy would pay this policy right now, with the
+    // spot price it read and the error code it would fail with (0 when
+    // eligible). Runs the same policy, oracle and trigger checks without
+    // writing anything; the deviation circuit breaker and liquidator
+    // permissions aren't evaluated. Read it via simulation or from return
+    // data
+    pub fn check_liquidation_eligibility(
+        ctx: Context<CheckLiquidationEligibility>,
+    ) -> Result<LiquidationEligibility> {
+        let clock = Clock::get()?;
+        let (price, outcome) = check_eligibility(
+            &ctx.accounts.policy,
+            &ctx.accounts.config,
+            &ctx.accounts.feed_registry,
+            &ctx.accounts.price_feed,
+            &TriggerAccounts {
+                twap_update: ctx.accounts.twap_update.as_ref(),
+                switchboard_feed: ctx.accounts.switchboard_feed.as_ref(),
+                drift_user: ctx.accounts.drift_user.as_ref(),
+                kamino_obligation: ctx.accounts.kamino_obligation.as_ref(),
+            },
+            &clock,
+        );
+        Ok(LiquidationEligibility {
+            eligible: outcome.is_ok(),
+            price: price.mantissa,
+            price_exponent: price.exponent,
+            reason: outcome.err().as_ref().map_or(0, error_code_number),
+        })
+    }
+
+    // Permissionless crank releasing whatever has vested of a tranched
+    // payout to the beneficiary. The final tranche closes the claim state
+    // and refunds its rent to the liquidator who opened it. Returns the
+    // amount released via return data
+    pub fn claim_tranche(ctx: Context<ClaimTranche>) -> Result<u64> {
+        require!(!ctx.accounts.policy.dispute_open, LiqGuardError::DisputeOpen);
+        let now = Clock::get()?.unix_timestamp;
+        let claim_state = &mut ctx.accounts.claim_state;
+        let amount = claim_state
+            .vested_amount(now)?
+            .saturating_sub(claim_state.claimed_amount);
+        require!(amount > 0, LiqGuardError::NothingVested);
+
+        let vault = ctx.accounts.vault.to_account_info();
+        if ctx.accounts.policy.is_native() {
+            vault_transfer(
+                &ctx.accounts.system_program.to_account_info(),
+                &vault,
+                &ctx.accounts.beneficiary,
+                &ctx.accounts.policy,
+                amount,
+            )?;
+        } else {
+            let (Some(token_program), Some(token_vault), Some(beneficiary_token_account)) = (
+                ctx.accounts.token_program.as_ref(),
+                ctx.accounts.token_vault.as_ref(),
+                ctx.accounts.beneficiary_token_account.as_ref(),
+            ) else {
+                return err!(LiqGuardError::MissingTokenAccounts);
+            };
+            token_vault_transfer(
+                &token_program.to_account_info(),
+                &token_vault.to_account_info(),
+                &beneficiary_token_account.to_account_info(),
+                &vault,
+                &ctx.accounts.policy,
+                amount,
+            )?;
+        }
+        let vault_balance = collateral_balance(
+            &ctx.accounts.policy,
+            &ctx.accounts.vault,
+            ctx.accounts.token_vault.as_mut(),
+        )?;
+
+        let policy = &mut ctx.accounts.policy;
+        claim_state.claimed_amount = claim_state
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        policy.unvested_payout = claim_state.total_amount - claim_state.claimed_amount;
+
+        msg!(
+            "Tranche claimed: PolicyId={}, Amount={}, Remaining={}",
+            policy.policy_id,
+            amount,
+            policy.unvested_payout
+        );
+        emit!(VaultPaidOut {
+            policy: policy.key(),
+            beneficiary: policy.beneficiary,
+            amount,
+            bounty: 0,
+            vault_balance,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        if policy.unvested_payout == 0 {
+            claim_state.close(ctx.accounts.rent_receiver.to_account_info())?;
+        }
+
+        Ok(amount)
+    }
+
+    // Permissionless crank recording the first oracle price at or beyond a
+    // policy's barrier. Only prices published while the policy was live
+    // count, so a touch can't be backdated to before open
+    pub fn record_barrier_touch(ctx: Context<RecordBarrierTouch>) -> Result<()> {
+        require!(!ctx.accounts.config.paused, LiqGuardError::ProtocolPaused);
+        let policy = &mut ctx.accounts.policy;
+        require!(policy.barrier_kind != BarrierKind::None, LiqGuardError::NoBarrier);
+        require!(policy.barrier_touched_at == 0, LiqGuardError::BarrierAlreadyTouched);
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+
+        let clock = Clock::get()?;
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.enabled, LiqGuardError::FeedDisabled);
+        let price_info = oracle::get_price(
+            &policy.feed_ref(feed),
+            &ctx.accounts.price_feed,
+            &clock,
+            policy.max_price_age.min(feed.max_price_age),
+            ctx.accounts.config.max_confidence_bps.min(feed.max_confidence_bps),
+        )?;
+        require!(
+            price_info.publish_time >= policy.created_at
+                && price_info.publish_time < policy.expiration_datetime,
+            LiqGuardError::PolicyExpired
+        );
+        // A touch is permanent, so a print far off the EMA can't record one
+        check_ema_deviation(&price_info, ctx.accounts.config.max_price_deviation_bps)?;
+
+        let price = price_info.price;
+        require!(barrier_touched(policy, price)?, LiqGuardError::BarrierNotTouched);
+
+        policy.barrier_touched_at = price_info.publish_time;
+        if policy.is_knocked_out() {
+            unlist_policy(ctx.accounts.keeper_queue.as_mut(), &policy.key());
+        }
+        msg!(
+            "Barrier touched: PolicyId={}, Kind={:?}, Price={}e{}",
+            policy.policy_id,
+            policy.barrier_kind,
+            price_info.price.mantissa,
+            price_info.price.exponent
+        );
+
+        Ok(())
+    }
+
+    // Snapshot the oracle price for a European policy. Anyone may post any
+    // update published within SETTLEMENT_PRICE_WINDOW of expiry; the one
+    // published closest to expiry wins, so the snapshot doesn't depend on
+    // who cranks it first
+    pub fn record_settlement_price(ctx: Context<RecordSettlementPrice>) -> Result<()> {
+        require!(!ctx.accounts.config.paused, LiqGuardError::ProtocolPaused);
+        let policy = &mut ctx.accounts.policy;
+        require!(
+            policy.exercise_style == ExerciseStyle::European,
+            LiqGuardError::NotEuropeanPolicy
+        );
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+
+        let clock = Clock::get()?;
+        let window_start = policy.expiration_datetime - SETTLEMENT_PRICE_WINDOW;
+        let window_end = policy.expiration_datetime + SETTLEMENT_PRICE_WINDOW;
+        require!(
+            (window_start..window_end).contains(&clock.unix_timestamp),
+            LiqGuardError::OutsideSettlementWindow
+        );
+
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.enabled, LiqGuardError::FeedDisabled);
+        let price_info = oracle::get_price(
+            &policy.feed_ref(feed),
+            &ctx.accounts.price_feed,
+            &clock,
+            policy.max_price_age.min(feed.max_price_age),
+            ctx.accounts.config.max_confidence_bps.min(feed.max_confidence_bps),
+        )?;
+        require!(
+            (window_start..window_end).contains(&price_info.publish_time),
+            LiqGuardError::OutsideSettlementWindow
+        );
+        // Nor can it become the snapshot; another print in the window can
+        check_ema_deviation(&price_info, ctx.accounts.config.max_price_deviation_bps)?;
+        let distance = price_info.publish_time.abs_diff(policy.expiration_datetime);
+        require!(
+            policy.expiry_price_publish_time == 0
+                || distance
+                    < policy
+                        .expiry_price_publish_time
+                        .abs_diff(policy.expiration_datetime),
+            LiqGuardError::SettlementPriceNotCloser
+        );
+
+        policy.expiry_price = price_info.price.mantissa;
+        policy.expiry_price_exponent = price_info.price.exponent;
+        policy.expiry_price_publish_time = price_info.publish_time;
+        msg!(
+            "Expiry price recorded: PolicyId={}, Price={}e{}, PublishTime={}",
+            policy.policy_id,
+            price_info.price.mantissa,
+            price_info.price.exponent,
+            price_info.publish_time
+        );
+
+        Ok(())
+    }
+
+    // Pay a European policy whose expiry snapshot finished past the
+    // strike. Permissionless like liquidate_policy, with the same bounty.
+    // The snapshot came from the asset's feed, so it can't pay while that
+    // feed is switched off
+    pub fn settle<'info>(ctx: Context<'_, '_, 'info, 'info, SettlePolicy<'info>>) -> Result<()> {
+        require!(!ctx.accounts.config.paused, LiqGuardError::ProtocolPaused);
+        require!(ctx.accounts.feed_registry.enabled, LiqGuardError::FeedDisabled);
+        let policy = &mut ctx.accounts.policy;
+        require!(
+            policy.may_liquidate(&ctx.accounts.config, &ctx.accounts.liquidator.key()),
+            LiqGuardError::LiquidatorNotAllowed
+        );
+        require!(
+            policy.exercise_style == ExerciseStyle::European,
+            LiqGuardError::NotEuropeanPolicy
+        );
+        require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+        require!(policy.holdback_amount == 0, LiqGuardError::HoldbackPending);
+        require!(policy.pending_swap_amount == 0, LiqGuardError::SwapPending);
+        require!(policy.is_funded, LiqGuardError::VaultNotFunded);
+        require!(policy.barrier_allows_claim(), LiqGuardError::BarrierBlocksClaim);
+        require!(policy.is_premium_paid(), LiqGuardError::WormholePremiumUnpaid);
+        require!(
+            policy.expiry_price_publish_time != 0,
+            LiqGuardError::NoSettlementPrice
+        );
+        check_payout_wallet(
+            &ctx.accounts.config,
+            policy,
+            &policy.beneficiary,
+            ctx.accounts.beneficiary_credential.as_ref(),
+        )?;
+
+        // Settle only once the snapshot is final, and before the collateral
+        // is released back to the underwriter
+        let clock = Clock::get()?;
+        let window_end = policy.expiration_datetime + SETTLEMENT_PRICE_WINDOW;
+        require!(
+            clock.unix_timestamp >= window_end,
+            LiqGuardError::OutsideSettlementWindow
+        );
+        require!(
+            !policy.is_terminal(clock.unix_timestamp),
+            LiqGuardError::PolicyExpired
+        );
+
+        let expiry_price = OraclePrice::new(policy.expiry_price, policy.expiry_price_exponent);
+        require!(
+            is_triggered(policy, expiry_price)?,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+
+        let total_payout = draw_master_agreement(
+            policy,
+            ctx.accounts.master_agreement.as_mut(),
+            payout_amount(policy, expiry_price)?,
+        )?;
+        let bounty = liquidator_bounty(&ctx.accounts.config, total_payout, policy.is_native())?;
+        let payout = total_payout
+            .checked_sub(bounty)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        let fee = payout_fee(&ctx.accounts.config, payout, policy.is_native())?;
+        let payout = payout - fee;
+        let mut paid_now =
+            payout - hold_back(&ctx.accounts.config, policy, payout, clock.unix_timestamp)?;
+        if policy.is_native() {
+            paid_now = rent_exempt_payout(
+                ctx.accounts.vault.lamports(),
+                paid_now,
+                bounty + fee,
+                Rent::get()?.minimum_balance(0),
+            );
+        }
+        pay_claim(
+            policy,
+            &ctx.accounts.vault,
+            &ctx.accounts.beneficiary,
+            &ctx.accounts.liquidator,
+            ctx.accounts.token_vault.as_ref(),
+            ctx.accounts.beneficiary_token_account.as_ref(),
+            ctx.accounts.liquidator_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            paid_now,
+            bounty,
+        )?;
+        collect_payout_fee(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.vault.to_account_info(),
+            ctx.accounts.treasury.as_mut(),
+            policy,
+            fee,
+        )?;
+        let vault_balance =
+            collateral_balance(policy, &ctx.accounts.vault, ctx.accounts.token_vault.as_mut())?;
+        emit!(VaultPaidOut {
+            policy: policy.key(),
+            beneficiary: policy.beneficiary,
+            amount: paid_now,
+            bounty,
+            vault_balance,
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        // European policies settle once; unclaimed coverage goes back to
+        // the underwriter
+        let publish_time = policy.expiry_price_publish_time;
+        policy.coverage_remaining = 0;
+        policy.record_settlement(
+            expiry_price,
+            publish_time,
+            clock.slot,
+            ctx.accounts.liquidator.key(),
+            payout,
+            bounty,
+        );
+        unlist_policy(ctx.accounts.keeper_queue.as_mut(), &policy.key());
+
+        emit!(LiquidationExecuted {
+            policy: policy.key(),
+            liquidator: ctx.accounts.liquidator.key(),
+            asset: policy.asset,
+            price: expiry_price.mantissa,
+            price_exponent: expiry_price.exponent,
+            strike_price: policy.strike_price,
+            strike_exponent: STRIKE_EXPONENT,
+            payout,
+            bounty,
+            coverage_remaining: policy.coverage_remaining,
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        record_audit(
+            ctx.accounts.audit_log.as_ref(),
+            AuditEntry::new(policy.key(), expiry_price, AUDIT_PAID, 0, &clock),
+        )?;
+        record_claim(
+            ctx.accounts.claim_history.as_ref(),
+            ClaimRecord::new(policy.key(), expiry_price, payout, clock.unix_timestamp),
+        )?;
+
+        Ok(())
+    }
+
+    // Owner pre-pays premiums for future auto-roll periods. The lamports sit
+    // on the policy account and go back to the owner when it closes
+    pub fn deposit_roll_premium(ctx: Context<ManageRollPremium>, amount: u64) -> Result<()> {
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.policy.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.roll_premium_escrow = policy
+            .roll_premium_escrow
+            .checked_add(amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw_roll_premium(ctx: Context<ManageRollPremium>, amount: u64) -> Result<()> {
+        require!(
+            amount > 0 && amount <= ctx.accounts.policy.roll_premium_escrow,
+            LiqGuardError::InvalidAmount
+        );
+        ctx.accounts.policy.roll_premium_escrow -= amount;
+        ctx.accounts.policy.sub_lamports(amount)?;
+        ctx.accounts.owner.add_lamports(amount)?;
+        Ok(())
+    }
+
+    // Either side can opt a policy out of future rolls
+    pub fn cancel_auto_roll(ctx: Context<CancelAutoRoll>) -> Result<()> {
+        ctx.accounts.policy.roll_period_seconds = 0;
+        msg!("Auto-roll cancelled: PolicyId={}", ctx.accounts.policy.policy_id);
+        Ok(())
+    }
+
+    // Permissionless crank: within ROLL_WINDOW after an unclaimed expiry,
+    // renew coverage for another period. The vault must still hold the full
+    // coverage and the premium is debited from the owner's escrow
+    pub fn roll_policy(ctx: Context<RollPolicy>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        let clock = Clock::get()?;
+        check_rollable(&ctx.accounts.policy, clock.unix_timestamp)?;
+
+        let vault_balance = if ctx.accounts.policy.is_native() {
+            ctx.accounts.vault.lamports()
+        } else {
+            ctx.accounts
+                .token_vault
+                .as_ref()
+                .ok_or(LiqGuardError::MissingTokenAccounts)?
+                .amount
+        };
+        roll_period(
+            config,
+            &mut ctx.accounts.policy,
+            vault_balance,
+            &ctx.accounts.underwriter,
+            &ctx.accounts.treasury,
+            ctx.accounts.backstop.as_mut(),
+            clock.slot,
+        )
+    }
+
+    // Permissionless crank: roll_policy for owners who approved the
+    // policy's vault PDA as delegate on a wrapped SOL account rather than
+    // pre-paying the escrow. Whatever the escrow is short of the next
+    // premium is pulled from that account, within the allowance the owner
+    // approved, so coverage renews without the owner signing each period
+    pub fn auto_renew(ctx: Context<AutoRenew>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        let clock = Clock::get()?;
+        check_rollable(&ctx.accounts.policy, clock.unix_timestamp)?;
+
+        let policy = &ctx.accounts.policy;
+        let shortfall = policy
+            .premium_amount
+            .saturating_sub(policy.roll_premium_escrow);
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let vault = ctx.accounts.vault.to_account_info();
+        let renewal_source = ctx.accounts.renewal_source.to_account_info();
+        if shortfall > 0 {
+            token_vault_transfer(
+                &token_program,
+                &ctx.accounts.premium_source.to_account_info(),
+                &renewal_source,
+                &vault,
+                policy,
+                shortfall,
+            )?;
+        }
+        // Unwrapping credits the policy account with the premium and the
+        // wrapped account's rent, which goes back to the caller who paid it
+        let rent = renewal_source
+            .lamports()
+            .checked_sub(shortfall)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        close_token_vault(
+            &token_program,
+            &renewal_source,
+            &ctx.accounts.policy.to_account_info(),
+            &vault,
+            policy,
+        )?;
+        ctx.accounts.policy.sub_lamports(rent)?;
+        ctx.accounts.caller.add_lamports(rent)?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.roll_premium_escrow = policy
+            .roll_premium_escrow
+            .checked_add(shortfall)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        let vault_balance = if policy.is_native() {
+            ctx.accounts.vault.lamports()
+        } else {
+            ctx.accounts
+                .token_vault
+                .as_ref()
+                .ok_or(LiqGuardError::MissingTokenAccounts)?
+                .amount
+        };
+        roll_period(
+            config,
+            policy,
+            vault_balance,
+            &ctx.accounts.underwriter,
+            &ctx.accounts.treasury,
+            ctx.accounts.backstop.as_mut(),
+            clock.slot,
+        )
+    }
+
+    // Owner tops up a streaming premium. A lapsed stream can't be revived,
+    // or coverage could be bought back only once the strike is in reach
+    pub fn deposit_stream_premium(ctx: Context<ManageStreamPremium>, amount: u64) -> Result<()> {
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        let policy = &ctx.accounts.policy;
+        require!(policy.premium_rate > 0, LiqGuardError::PremiumStreamDisabled);
+        require!(
+            !policy.is_lapsed(Clock::get()?.unix_timestamp),
+            LiqGuardError::CoverageLapsed
+        );
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.policy.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.stream_escrow = policy
+            .stream_escrow
+            .checked_add(amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        Ok(())
+    }
+
+    // Owner ends a streaming policy's coverage now and takes back the
+    // premium that hasn't accrued yet. What has accrued stays escrowed
+    // for the next sync
+    pub fn cancel_premium_stream(ctx: Context<ManageStreamPremium>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let policy = &mut ctx.accounts.policy;
+        require!(policy.premium_rate > 0, LiqGuardError::PremiumStreamDisabled);
+        require!(policy.stream_stopped_at == 0, LiqGuardError::CoverageLapsed);
+
+        let accrued = policy.accrued_stream_premium(now);
+        let refund = policy.stream_escrow - accrued;
+        policy.stream_escrow = accrued;
+        policy.stream_stopped_at = now;
+        if refund > 0 {
+            policy.sub_lamports(refund)?;
+            ctx.accounts.owner.add_lamports(refund)?;
+        }
+
+        msg!(
+            "Premium stream cancelled: PolicyId={}, Refund={}, Accrued={}",
+            policy.policy_id,
+            refund,
+            accrued
+        );
+        Ok(())
+    }
+
+    // Permissionless crank: pay the streaming premium accrued so far to
+    // the underwriter, split with the treasury and backstop like any premium
+    pub fn sync_premium_stream(ctx: Context<SyncPremiumStream>) -> Result<()> {
+        let clock = Clock::get()?;
+        let policy = &ctx.accounts.policy;
+        require!(policy.premium_rate > 0, LiqGuardError::PremiumStreamDisabled);
+        let accrued = policy.accrued_stream_premium(clock.unix_timestamp);
+        let accrued_until = policy.stream_accrual_end(clock.unix_timestamp);
+
+        distribute_escrowed_premium(
+            &ctx.accounts.config,
+            &ctx.accounts.policy.to_account_info(),
+            &ctx.accounts.underwriter,
+            &ctx.accounts.treasury,
+            ctx.accounts.backstop.as_mut(),
+            accrued,
+        )?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.stream_escrow -= accrued;
+        policy.stream_accrued_until = policy.stream_accrued_until.max(accrued_until);
+
+        emit!(PremiumStreamSynced {
+            policy: policy.key(),
+            amount: accrued,
+            stream_escrow: policy.stream_escrow,
+            lapsed: policy.is_lapsed(clock.unix_timestamp),
+            slot: clock.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        Ok(())
+    }
+
+    // Settle many policies of one asset against a single oracle price.
+    // remaining_accounts holds (policy, vault, beneficiary) triples. Only
+    // plain native spot policies with their own vault, paying out at once
+    // to their beneficiary, are settled here;
+    // anything not eligible (untriggered, nothing owed beyond earlier
+    // claims, or needing extra settlement accounts) is skipped so one bad
+    // entry can't sink the batch
+    pub fn liquidate_policies<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LiquidatePolicies<'info>>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.enabled, LiqGuardError::FeedDisabled);
+        require!(
+            ctx.remaining_accounts.len().is_multiple_of(3),
+            LiqGuardError::InvalidBatchAccounts
+        );
+
+        let clock = Clock::get()?;
+        let price_info = batch_price(config, feed, &ctx.accounts.price_feed, &clock)?;
+        let spot_price = price_info.price;
+
+        // Batches can't run the two-step deviation confirmation
+        check_ema_deviation(&price_info, config.max_price_deviation_bps)?;
+
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let liquidator = ctx.accounts.liquidator.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(0);
+        let mut settled: u32 = 0;
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            let [policy_info, vault, beneficiary] = accounts else {
+                unreachable!()
+            };
+            let mut policy = Account::<Policy>::try_from(policy_info)?;
+            let eligible = !policy.is_exhausted()
+                && policy.is_funded
+                && policy.is_native()
+                && !policy.usd_coverage
+                && policy.asset == feed.asset
+                && policy.max_price_age >= clock.unix_timestamp.saturating_sub(price_info.publish_time) as u64
+                && policy.twap_window_seconds == 0
+                && policy.switchboard_feed == Pubkey::default()
+                && policy.chainlink_feed == Pubkey::default()
+                && policy.drift_user == Pubkey::default()
+                && policy.kamino_obligation == Pubkey::default()
+                && policy.portfolio_vault == Pubkey::default()
+                && policy.may_liquidate(config, liquidator.key)
+                && policy.vesting_period_seconds == 0
+                && policy.holdback_amount == 0
+                && policy.usdc_payout_account == Pubkey::default()
+                && !policy.has_payout_split()
+                && policy.master_agreement == Pubkey::default()
+                && compliance_mint(config, &policy) == Pubkey::default()
+                && policy.breach_dwell_seconds == 0
+                && policy.exercise_style == ExerciseStyle::American
+                && policy.barrier_allows_claim()
+                && policy.is_premium_paid()
+                && clock.unix_timestamp < policy.expiration_datetime
+                && !policy.is_lapsed(clock.unix_timestamp)
+                && clock.unix_timestamp >= policy.created_at.saturating_add(config.min_policy_age)
+                && spot_triggered(&policy, &price_info)?;
+            if !eligible {
+                continue;
+            }
+            let claim_amount = policy.claimable(payout_amount(&policy, spot_price)?);
+            if claim_amount == 0 {
+                continue;
+            }
+
+            let vault_key = Pubkey::create_program_address(
+                &[
+                    VAULT_SEED,
+                    policy.creator.as_ref(),
+                    &policy.policy_id.to_le_bytes(),
+                    &[policy.vault_bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| LiqGuardError::InvalidBatchAccounts)?;
+            require_keys_eq!(vault.key(), vault_key, LiqGuardError::InvalidBatchAccounts);
+            require_keys_eq!(
+                beneficiary.key(),
+                policy.beneficiary,
+                LiqGuardError::InvalidBeneficiary
+            );
+
+            let bounty = liquidator_bounty(config, claim_amount, true)?;
+            let fee = payout_fee(config, claim_amount - bounty, true)?;
+            let payout = claim_amount - bounty - fee;
+            let paid_now = rent_exempt_payout(
+                vault.lamports(),
+                payout - hold_back(config, &mut policy, payout, clock.unix_timestamp)?,
+                bounty + fee,
+                rent_floor,
+            );
+            vault_transfer(&system_program, vault, beneficiary, &policy, paid_now)?;
+            if bounty > 0 {
+                vault_transfer(&system_program, vault, &liquidator, &policy, bounty)?;
+            }
+            collect_payout_fee(&system_program, vault, ctx.accounts.treasury.as_mut(), &mut policy, fee)?;
+            emit!(VaultPaidOut {
+                policy: policy.key(),
+                beneficiary: policy.beneficiary,
+                amount: paid_now,
+                bounty,
+                vault_balance: vault.lamports(),
+                slot: clock.slot,
+                sequence: next_event_sequence(&mut policy)?,
+            });
+
+            policy.coverage_remaining -= claim_amount;
+            policy.deviation_flagged_at = 0;
+            policy.record_settlement(
+                spot_price,
+                price_info.publish_time,
+                clock.slot,
+                liquidator.key(),
+                payout,
+                bounty,
+            );
+            if policy.is_exhausted() {
+                policy.stop_premium_stream(clock.unix_timestamp);
+                unlist_policy(ctx.accounts.keeper_queue.as_mut(), &policy.key());
+            }
+            emit!(LiquidationExecuted {
+                policy: policy.key(),
+                liquidator: liquidator.key(),
+                asset: policy.asset,
+                price: spot_price.mantissa,
+                price_exponent: spot_price.exponent,
+                strike_price: policy.strike_price,
+                strike_exponent: STRIKE_EXPONENT,
+                payout,
+                bounty,
+                coverage_remaining: policy.coverage_remaining,
+                slot: clock.slot,
+                sequence: next_event_sequence(&mut policy)?,
+            });
+            policy.exit(&crate::ID)?;
+            settled += 1;
+        }
+
+        msg!(
+            "Batch liquidation: Settled={}, Submitted={}",
+            settled,
+            ctx.remaining_accounts.len() / 3
+        );
+
+        Ok(())
+    }
+
+    // Return whatever is left in the vault to the underwriter and close the
+    // policy once it can no longer pay out (expired or already claimed)
+    pub fn reclaim_vault(ctx: Context<ReclaimVault>) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        require!(policy.holdback_amount == 0, LiqGuardError::HoldbackPending);
+        require!(policy.pending_swap_amount == 0, LiqGuardError::SwapPending);
+        let now = Clock::get()?.unix_timestamp;
+        let expired = now >= policy.expiration_datetime;
+        require!(
+            policy.is_terminal(now),
+            LiqGuardError::PolicyStillActive
+        );
+
+        unlist_policy(ctx.accounts.keeper_queue.as_mut(), &ctx.accounts.policy.key());
+        release_portfolio_exposure(policy, ctx.accounts.portfolio_vault.as_mut())?;
+        let owner_state = &mut ctx.accounts.owner_state;
+        owner_state.active_coverage = owner_state
+            .active_coverage
+            .saturating_sub(policy.coverage_amount);
+
+        // Collateral handed back, in the policy's units
+        let reclaimed = match ctx.accounts.token_vault.as_ref() {
+            Some(token_vault) if !policy.is_native() => token_vault.amount,
+            _ => ctx.accounts.vault.lamports(),
+        };
+        let remaining = sweep_vault(
+            policy,
+            &ctx.accounts.vault,
+            &ctx.accounts.owner,
+            &ctx.accounts.underwriter,
+            ctx.accounts.token_vault.as_ref(),
+            ctx.accounts.underwriter_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            &ctx.accounts.system_program,
+        )?;
+
+        msg!(
+            "Vault reclaimed: Amount={}, Underwriter={}, CoverageRemaining={}, Expired={}",
+            remaining,
+            ctx.accounts.underwriter.key(),
+            policy.coverage_remaining,
+            expired
+        );
+        let policy = &mut ctx.accounts.policy;
+        emit!(VaultReclaimed {
+            policy: policy.key(),
+            underwriter: policy.underwriter,
+            amount: reclaimed,
+            vault_balance: 0,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        Ok(())
+    }
+
+    // Bring a policy opened before the version byte up to POLICY_VERSION.
+    // Older accounts no longer deserialize, so every other instruction
+    // rejects them until this grows the account by realloc (the payer
+    // covers the extra rent) and stamps the version. Anyone may crank it;
+    // no existing field changes
+    pub fn migrate_policy_v2(ctx: Context<MigratePolicyV2>) -> Result<()> {
+        let policy_info = ctx.accounts.policy.to_account_info();
+        {
+            let data = policy_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == <Policy as anchor_lang::Discriminator>::DISCRIMINATOR,
+                LiqGuardError::InvalidPolicyAccount
+            );
+            require!(
+                data.len() == 8 + Policy::V1_LEN,
+                LiqGuardError::PolicyAlreadyMigrated
+            );
+        }
+
+        let new_len = 8 + Policy::LEN;
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(policy_info.lamports());
+        if rent_due > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: policy_info.clone(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, rent_due)?;
+        }
+        policy_info.realloc(new_len, true)?;
+
+        let mut policy = Policy::try_deserialize(&mut &policy_info.try_borrow_data()?[..])?;
+        policy.version = POLICY_VERSION;
+        msg!(
+            "Policy migrated: Owner={}, PolicyId={}, Version={}",
+            policy.owner,
+            policy.policy_id,
+            policy.version
+        );
+        emit!(PolicyMigrated {
+            policy: policy_info.key(),
+            version: policy.version,
+            rent_paid: rent_due,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(&mut policy)?,
+        });
+        policy.try_serialize(&mut &mut policy_info.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    // Grow any program account to its type's current size after fields
+    // were appended to the struct, so a new field doesn't need a parallel
+    // account type and a migration of its own. The type is read from the
+    // discriminator; the added bytes are zeroed, which every appended field
+    // must treat as its default. The payer covers the extra rent. A
+    // policy is also stamped with POLICY_VERSION
+    pub fn extend_account(ctx: Context<ExtendAccount>) -> Result<()> {
+        let account_info = ctx.accounts.account.to_account_info();
+        let (old_len, new_len) = {
+            let data = account_info.try_borrow_data()?;
+            let new_len = data
+                .get(..8)
+                .and_then(current_account_space)
+                .ok_or(LiqGuardError::UnknownAccountType)?;
+            (data.len(), new_len)
+        };
+        require!(old_len < new_len, LiqGuardError::AccountAlreadyCurrent);
+
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(account_info.lamports());
+        if rent_due > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, rent_due)?;
+        }
+        account_info.realloc(new_len, true)?;
+
+        if account_info.try_borrow_data()?[..8] == <Policy as anchor_lang::Discriminator>::DISCRIMINATOR {
+            let mut policy = Policy::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+            policy.version = POLICY_VERSION;
+            policy.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+        }
+
+        msg!(
+            "Account extended: Account={}, From={}, To={}",
+            account_info.key(),
+            old_len,
+            new_len
+        );
+        emit!(AccountExtended {
+            account: account_info.key(),
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+            rent_paid: rent_due,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    // Permissionless cleanup of a terminal policy: anyone may crank it, but
+    // residual collateral always goes to the underwriter and rent to the
+    // owner, so the caller only pays the transaction fee
+    pub fn close_policy(ctx: Context<ClosePolicy>) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        require!(policy.holdback_amount == 0, LiqGuardError::HoldbackPending);
+        require!(policy.pending_swap_amount == 0, LiqGuardError::SwapPending);
+        let now = Clock::get()?.unix_timestamp;
+        require!(policy.is_terminal(now), LiqGuardError::PolicyStillActive);
+        // Closing refunds the policy account to the owner, so accrued
+        // streaming premium must reach the underwriter first
+        require!(
+            policy.accrued_stream_premium(now) == 0,
+            LiqGuardError::PremiumStreamNotSynced
+        );
+
+        unlist_policy(ctx.accounts.keeper_queue.as_mut(), &ctx.accounts.policy.key());
+        release_portfolio_exposure(policy, ctx.accounts.portfolio_vault.as_mut())?;
+        let owner_state = &mut ctx.accounts.owner_state;
+        owner_state.active_coverage = owner_state
+            .active_coverage
+            .saturating_sub(policy.coverage_amount);
+
+        // Collateral handed back, in the policy's units
+        let reclaimed = match ctx.accounts.token_vault.as_ref() {
+            Some(token_vault) if !policy.is_native() => token_vault.amount,
+            _ => ctx.accounts.vault.lamports(),
+        };
+        let remaining = sweep_vault(
+            policy,
+            &ctx.accounts.vault,
+            &ctx.accounts.owner,
+            &ctx.accounts.underwriter,
+            ctx.accounts.token_vault.as_ref(),
+            ctx.accounts.underwriter_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            &ctx.accounts.system_program,
+        )?;
+
+        msg!(
+            "Policy closed: Owner={}, PolicyId={}, Residual={}, Closer={}",
+            policy.owner,
+            policy.policy_id,
+            remaining,
+            ctx.accounts.closer.key()
+        );
+        let policy = &mut ctx.accounts.policy;
+        emit!(VaultReclaimed {
+            policy: policy.key(),
+            underwriter: policy.underwriter,
+            amount: reclaimed,
+            vault_balance: 0,
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless: a native policy nobody closed within
+    // ABANDONED_VAULT_GRACE_PERIOD of expiry is abandoned. Its vault's
+    // residual lamports and the policy account's lamports go to the
+    // backstop, or the treasury when none is passed, so dead capital stops
+    // sitting in vaults. Holdbacks, swaps and streams still pending by
+    // then are forfeited with it, as is the owner's unwithdrawn roll and
+    // stream premium escrow, which the policy account holds beside its rent
+    pub fn sweep_abandoned_vault(ctx: Context<SweepAbandonedVault>) -> Result<()> {
+        let policy = &ctx.accounts.policy;
+        require!(policy.is_native(), LiqGuardError::SweepUnsupported);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            policy.is_terminal(now)
+                && now >= policy
+                    .expiration_datetime
+                    .saturating_add(ABANDONED_VAULT_GRACE_PERIOD),
+            LiqGuardError::VaultNotAbandoned
+        );
+
+        unlist_policy(ctx.accounts.keeper_queue.as_mut(), &ctx.accounts.policy.key());
+        release_portfolio_exposure(policy, ctx.accounts.portfolio_vault.as_mut())?;
+        let owner_state = &mut ctx.accounts.owner_state;
+        owner_state.active_coverage = owner_state
+            .active_coverage
+            .saturating_sub(policy.coverage_amount);
+
+        let destination = match (ctx.accounts.backstop.as_ref(), ctx.accounts.treasury.as_ref()) {
+            (Some(backstop), _) => backstop.to_account_info(),
+            (None, Some(treasury)) => treasury.to_account_info(),
+            (None, None) => return err!(LiqGuardError::MissingSweepDestination),
+        };
+        let residual = ctx.accounts.vault.lamports();
+        if residual > 0 {
+            vault_transfer(
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.vault.to_account_info(),
+                &destination,
+                policy,
+                residual,
+            )?;
+        }
+        let escrow = policy.roll_premium_escrow.saturating_add(policy.stream_escrow);
+        let lamports = ctx.accounts.policy.get_lamports();
+        let rent = lamports.saturating_sub(escrow);
+        let swept = residual.checked_add(lamports).ok_or(LiqGuardError::MathOverflow)?;
+        if let Some(backstop) = ctx.accounts.backstop.as_mut() {
+            backstop.total_contributed = backstop
+                .total_contributed
+                .checked_add(swept)
+                .ok_or(LiqGuardError::MathOverflow)?;
+        } else if let Some(treasury) = ctx.accounts.treasury.as_mut() {
+            treasury.total_collected = treasury
+                .total_collected
+                .checked_add(swept)
+                .ok_or(LiqGuardError::MathOverflow)?;
+        }
+
+        let policy = &mut ctx.accounts.policy;
+        emit!(AbandonedVaultSwept {
+            policy: policy.key(),
+            owner: policy.owner,
+            underwriter: policy.underwriter,
+            residual,
+            escrow: lamports - rent,
+            rent,
+            destination: destination.key(),
+            slot: Clock::get()?.slot,
+            sequence: next_event_sequence(policy)?,
+        });
+        ctx.accounts.policy.close(destination)
+    }
+
+    // Underwriter sets up a pre-allocated PolicyBook: thousands of
+    // fixed-size policy slots backed by one pooled lamport vault
+    pub fn initialize_policy_book(ctx: Context<InitializePolicyBook>) -> Result<()> {
+        let mut book = ctx.accounts.policy_book.load_init()?;
+        book.underwriter = ctx.accounts.underwriter.key();
+        book.reserved_coverage = 0;
+        book.open_count = 0;
+        book.vault_bump = ctx.bumps.book_vault;
+        Ok(())
+    }
+
+    pub fn fund_policy_book(ctx: Context<FundPolicyBook>, amount: u64) -> Result<()> {
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.underwriter.to_account_info(),
+                to: ctx.accounts.book_vault.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)
+    }
+
+    // Underwriter withdraws collateral not reserved by open slots
+    pub fn withdraw_policy_book(ctx: Context<FundPolicyBook>, amount: u64) -> Result<()> {
+        let book = ctx.accounts.policy_book.load()?;
+        let free = ctx
+            .accounts
+            .book_vault
+            .lamports()
+            .saturating_sub(book.reserved_coverage);
+        require!(amount > 0 && amount <= free, LiqGuardError::InvalidAmount);
+        book_vault_transfer(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.book_vault.to_account_info(),
+            &ctx.accounts.underwriter.to_account_info(),
+            &ctx.accounts.policy_book.key(),
+            book.vault_bump,
+            amount,
+        )
+    }
+
+    // Open binary spot-settled coverage in a free slot. Coverage is
+    // reserved out of the book's pooled collateral, so it is live at once
+    pub fn open_book_policy(
+        ctx: Context<OpenBookPolicy>,
+        slot_index: u32,
+        params: BookPolicyParams,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        require!(ctx.accounts.feed_registry.enabled, LiqGuardError::FeedDisabled);
+        let now = Clock::get()?.unix_timestamp;
+        require!(params.expiration_datetime > now, LiqGuardError::InvalidExpiration);
+        require!(
+            params.coverage_amount > 0 && params.strike_price > 0,
+            LiqGuardError::InvalidAmount
+        );
+        require!(
+            within_coverage_cap(config, params.coverage_amount),
+            LiqGuardError::CoverageCapExceeded
+        );
+
+        let vault_balance = ctx.accounts.book_vault.lamports();
+        let mut book = ctx.accounts.policy_book.load_mut()?;
+        let reserved_coverage = book
+            .reserved_coverage
+            .checked_add(params.coverage_amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        require!(reserved_coverage <= vault_balance, LiqGuardError::VaultNotFunded);
+
+        let slot = book.slot_mut(slot_index)?;
+        require!(slot.status == BOOK_SLOT_FREE, LiqGuardError::BookSlotInUse);
+        *slot = PolicySlot {
+            owner: ctx.accounts.owner.key(),
+            strike_price: params.strike_price,
+            coverage_amount: params.coverage_amount,
+            expiration_datetime: params.expiration_datetime,
+            created_at: now,
+            asset: params.asset as u8,
+            is_long_insurance: params.is_long_insurance as u8,
+            status: BOOK_SLOT_OPEN,
+            _padding: [0; 5],
+        };
+        book.reserved_coverage = reserved_coverage;
+        book.open_count += 1;
+        drop(book);
+
+        pay_pooled_premium(
+            config,
+            &ctx.accounts.system_program,
+            &ctx.accounts.owner,
+            &ctx.accounts.underwriter,
+            &ctx.accounts.treasury,
+            params.premium_amount,
+        )?;
+
+        msg!(
+            "Book policy opened: Slot={}, Owner={}, Strike={}, Coverage={}",
+            slot_index,
+            ctx.accounts.owner.key(),
+            params.strike_price,
+            params.coverage_amount
+        );
+
+        Ok(())
+    }
+
+    // Permissionless settlement of a triggered slot against the feed's oracle,
+    // paying the slot owner and the liquidator bounty from the pooled vault
+    pub fn settle_book_policy(ctx: Context<SettleBookPolicy>, slot_index: u32) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.enabled, LiqGuardError::FeedDisabled);
+        let clock = Clock::get()?;
+
+        let mut book = ctx.accounts.policy_book.load_mut()?;
+        let vault_bump = book.vault_bump;
+        let slot = *book.slot_mut(slot_index)?;
+        require!(slot.status == BOOK_SLOT_OPEN, LiqGuardError::BookSlotNotOpen);
+        require!(slot.asset == feed.asset as u8, LiqGuardError::InvalidFeedId);
+        require_keys_eq!(
+            ctx.accounts.beneficiary.key(),
+            slot.owner,
+            LiqGuardError::InvalidBeneficiary
+        );
+        require!(
+            clock.unix_timestamp < slot.expiration_datetime,
+            LiqGuardError::PolicyExpired
+        );
+        require!(
+            clock.unix_timestamp >= slot.created_at.saturating_add(config.min_policy_age),
+            LiqGuardError::PolicyTooNew
+        );
+
+        let price_info = oracle::get_price(
+            &feed.feed_ref(),
+            &ctx.accounts.price_feed,
+            &clock,
+            feed.max_price_age,
+            config.max_confidence_bps.min(feed.max_confidence_bps),
+        )?;
+        check_ema_deviation(&price_info, config.max_price_deviation_bps)?;
+        let price = price_info.price;
+        require!(
+            strike_crossed(
+                if slot.is_long_insurance != 0 {
+                    TriggerDirection::ProtectLong
+                } else {
+                    TriggerDirection::ProtectShort
+                },
+                OraclePrice::new(slot.strike_price, STRIKE_EXPONENT),
+                OraclePrice::new(0, STRIKE_EXPONENT),
+                price,
+            )?,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+
+        book.slot_mut(slot_index)?.status = BOOK_SLOT_FREE;
+        book.reserved_coverage -= slot.coverage_amount;
+        book.open_count -= 1;
+        drop(book);
+
+        let bounty = liquidator_bounty(config, slot.coverage_amount, true)?;
+        let payout = slot.coverage_amount - bounty;
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let book_vault = ctx.accounts.book_vault.to_account_info();
+        let book_key = ctx.accounts.policy_book.key();
+        book_vault_transfer(
+            &system_program,
+            &book_vault,
+            &ctx.accounts.beneficiary,
+            &book_key,
+            vault_bump,
+            payout,
+        )?;
+        if bounty > 0 {
+            book_vault_transfer(
+                &system_program,
+                &book_vault,
+                &ctx.accounts.liquidator.to_account_info(),
+                &book_key,
+                vault_bump,
+                bounty,
+            )?;
+        }
+
+        msg!(
+            "Book policy settled: Slot={}, Price={}e{}, Amount={}, Bounty={}",
+            slot_index,
+            price.mantissa,
+            price.exponent,
+            payout,
+            bounty
+        );
+
+        Ok(())
+    }
+
+    // Permissionless: free an expired slot and release its reserved coverage
+    pub fn release_book_policy(ctx: Context<ReleaseBookPolicy>, slot_index: u32) -> Result<()> {
+        let mut book = ctx.accounts.policy_book.load_mut()?;
+        let slot = *book.slot_mut(slot_index)?;
+        require!(slot.status == BOOK_SLOT_OPEN, LiqGuardError::BookSlotNotOpen);
+        require!(
+            Clock::get()?.unix_timestamp >= slot.expiration_datetime,
+            LiqGuardError::PolicyStillActive
+        );
+
+        book.slot_mut(slot_index)?.status = BOOK_SLOT_FREE;
+        book.reserved_coverage -= slot.coverage_amount;
+        book.open_count -= 1;
+        Ok(())
+    }
+
+    // Underwriter sets up a compressed policy tree: policies live as
+    // leaves of an SPL concurrent Merkle tree and share the collateral
+    // held on the tree's PDA. The tree account is allocated by the client
+    // and handed to the compression program with the PDA as authority
+    pub fn initialize_compressed_tree(
+        ctx: Context<InitializeCompressedTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let compressed_tree = &mut ctx.accounts.compressed_tree;
+        compressed_tree.underwriter = ctx.accounts.underwriter.key();
+        compressed_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+        compressed_tree.reserved_coverage = 0;
+        compressed_tree.leaf_count = 0;
+        compressed_tree.open_count = 0;
+        compressed_tree.bump = ctx.bumps.compressed_tree;
+
+        let merkle_tree_key = ctx.accounts.merkle_tree.key();
+        let seeds: &[&[u8]] = &[
+            COMPRESSED_TREE_SEED,
+            merkle_tree_key.as_ref(),
+            &[ctx.bumps.compressed_tree],
+        ];
+        compression::MerkleTree {
+            compression_program: &ctx.accounts.compression_program,
+            noop_program: &ctx.accounts.noop_program,
+            merkle_tree: &ctx.accounts.merkle_tree,
+            authority: &ctx.accounts.compressed_tree.to_account_info(),
+            authority_seeds: seeds,
+        }
+        .init_empty(max_depth, max_buffer_size)
+    }
+
+    pub fn fund_compressed_tree(ctx: Context<ManageCompressedTree>, amount: u64) -> Result<()> {
+        require!(amount > 0, LiqGuardError::InvalidAmount);
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.underwriter.to_account_info(),
+                to: ctx.accounts.compressed_tree.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)
+    }
+
+    // Underwriter withdraws collateral not reserved by open leaves
+    pub fn withdraw_compressed_tree(ctx: Context<ManageCompressedTree>, amount: u64) -> Result<()> {
+        let compressed_tree = &mut ctx.accounts.compressed_tree;
+        require!(
+            amount > 0 && amount <= unreserved_tree_balance(compressed_tree)?,
+            LiqGuardError::InvalidAmount
+        );
+        compressed_tree.sub_lamports(amount)?;
+        ctx.accounts.underwriter.add_lamports(amount)?;
+        Ok(())
+    }
+
+    // Open binary spot-settled coverage as a new leaf. Nothing but the leaf
+    // hash is stored on chain, so the full leaf goes out in
+    // CompressedPolicyOpened for indexers to rebuild the tree from
+    pub fn open_compressed_policy(
+        ctx: Context<OpenCompressedPolicy>,
+        params: CompressedPolicyParams,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        require!(ctx.accounts.feed_registry.enabled, LiqGuardError::FeedDisabled);
+        let clock = Clock::get()?;
+        require!(
+            params.expiration_datetime > clock.unix_timestamp,
+            LiqGuardError::InvalidExpiration
+        );
+        require!(
+            params.coverage_amount > 0 && params.strike_price > 0,
+            LiqGuardError::InvalidAmount
+        );
+        require!(
+            valid_strike_range(params.direction, params.strike_price, 0),
+            LiqGuardError::InvalidStrikeRange
+        );
+        require!(
+            within_coverage_cap(config, params.coverage_amount),
+            LiqGuardError::CoverageCapExceeded
+        );
+
+        let compressed_tree = &mut ctx.accounts.compressed_tree;
+        require!(
+            params.coverage_amount <= unreserved_tree_balance(compressed_tree)?,
+            LiqGuardError::VaultNotFunded
+        );
+        let leaf = CompressedPolicy {
+            tree: compressed_tree.key(),
+            leaf_index: compressed_tree.leaf_count,
+            owner: ctx.accounts.owner.key(),
+            asset: params.asset,
+            direction: params.direction,
+            strike_price: params.strike_price,
+            coverage_amount: params.coverage_amount,
+            expiration_datetime: params.expiration_datetime,
+            created_at: clock.unix_timestamp,
+        };
+        let leaf_hash = leaf.hash()?;
+
+        let merkle_tree_key = ctx.accounts.merkle_tree.key();
+        let seeds: &[&[u8]] = &[
+            COMPRESSED_TREE_SEED,
+            merkle_tree_key.as_ref(),
+            &[compressed_tree.bump],
+        ];
+        compression::MerkleTree {
+            compression_program: &ctx.accounts.compression_program,
+            noop_program: &ctx.accounts.noop_program,
+            merkle_tree: &ctx.accounts.merkle_tree,
+            authority: &compressed_tree.to_account_info(),
+            authority_seeds: seeds,
+        }
+        .append(leaf_hash)?;
+
+        compressed_tree.leaf_count = compressed_tree
+            .leaf_count
+            .checked_add(1)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        compressed_tree.open_count += 1;
+        compressed_tree.reserved_coverage = compressed_tree
+            .reserved_coverage
+            .checked_add(params.coverage_amount)
+            .ok_or(LiqGuardError::MathOverflow)?;
+
+        pay_pooled_premium(
+            config,
+            &ctx.accounts.system_program,
+            &ctx.accounts.owner,
+            &ctx.accounts.underwriter,
+            &ctx.accounts.treasury,
+            params.premium_amount,
+        )?;
+
+        emit!(CompressedPolicyOpened {
+            merkle_tree: merkle_tree_key,
+            leaf,
+            leaf_hash,
+            slot: clock.slot,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless settlement of a triggered compressed policy against
+    // the feed's oracle. The caller supplies the leaf, a recent root and the
+    // proof nodes as remaining accounts; swapping the leaf for EMPTY_LEAF
+    // verifies the proof and spends the policy in one CPI
+    pub fn settle_compressed_policy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleCompressedPolicy<'info>>,
+        root: [u8; 32],
+        leaf: CompressedPolicy,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        let feed = &ctx.accounts.feed_registry;
+        require!(feed.enabled, LiqGuardError::FeedDisabled);
+        require_keys_eq!(
+            leaf.tree,
+            ctx.accounts.compressed_tree.key(),
+            LiqGuardError::InvalidCompressedPolicy
+        );
+        require_keys_eq!(
+            ctx.accounts.beneficiary.key(),
+            leaf.owner,
+            LiqGuardError::InvalidBeneficiary
+        );
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp < leaf.expiration_datetime,
+            LiqGuardError::PolicyExpired
+        );
+        require!(
+            clock.unix_timestamp >= leaf.created_at.saturating_add(config.min_policy_age),
+            LiqGuardError::PolicyTooNew
+        );
+
+        let price_info = oracle::get_price(
+            &feed.feed_ref(),
+            &ctx.accounts.price_feed,
+            &clock,
+            feed.max_price_age,
+            config.max_confidence_bps.min(feed.max_confidence_bps),
+        )?;
+        check_ema_deviation(&price_info, config.max_price_deviation_bps)?;
+        let price = price_info.price;
+        require!(
+            strike_crossed(
+                leaf.direction,
+                OraclePrice::new(leaf.strike_price, STRIKE_EXPONENT),
+                OraclePrice::new(0, STRIKE_EXPONENT),
+                price,
+            )?,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+
+        spend_compressed_policy(
+            &mut ctx.accounts.compressed_tree,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.noop_program,
+            root,
+            &leaf,
+            ctx.remaining_accounts,
+        )?;
+
+        let bounty = liquidator_bounty(config, leaf.coverage_amount, true)?;
+        let payout = leaf.coverage_amount - bounty;
+        ctx.accounts.compressed_tree.sub_lamports(leaf.coverage_amount)?;
+        ctx.accounts.beneficiary.add_lamports(payout)?;
+        ctx.accounts.liquidator.add_lamports(bounty)?;
+
+        msg!(
+            "Compressed policy settled: Leaf={}, Price={}e{}, Amount={}, Bounty={}",
+            leaf.leaf_index,
+            price.mantissa,
+            price.exponent,
+            payout,
+            bounty
+        );
+
+        Ok(())
+    }
+
+    // Permissionless: spend an expired leaf and release its reserved
+    // coverage back to the underwriter
+    pub fn release_compressed_policy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseCompressedPolicy<'info>>,
+        root: [u8; 32],
+        leaf: CompressedPolicy,
+    ) -> Result<()> {
+        require_keys_eq!(
+            leaf.tree,
+            ctx.accounts.compressed_tree.key(),
+            LiqGuardError::InvalidCompressedPolicy
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= leaf.expiration_datetime,
+            LiqGuardError::PolicyStillActive
+        );
+        spend_compressed_policy(
+            &mut ctx.accounts.compressed_tree,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.noop_program,
+            root,
+            &leaf,
+            ctx.remaining_accounts,
+        )
+    }
+}
+
+// The current allocation, discriminator included, of the account type
+// discriminator names; None for anything that isn't a LiqGuard account
+fn current_account_space(discriminator: &[u8]) -> Option<usize> {
+    use anchor_lang::Discriminator;
+    macro_rules! spaces {
+        ($($account:ty),* $(,)?) => {
+            [$((<$account as Discriminator>::DISCRIMINATOR, <$account>::LEN)),*]
+        };
+    }
+    let spaces = spaces![
+        Config, PendingConfigChange, Backstop, Treasury, UnderwriterRecord, PortfolioVault,
+        FeedRegistry, LazerPrice, KeeperQueue, AuditLog, ClaimHistory, PolicyBook,
+        CompressedPolicyTree, WormholePayment, Dispute, ClaimState, OwnerState, Policy,
+        MasterAgreement, PriceCache, CoverageRequest,
+    ];
+    spaces
+        .iter()
+        .find(|(account, _)| account[..] == *discriminator)
+        .map(|(_, len)| 8 + len)
+}
+
+// Reject wide-confidence prints: conf / price must stay within the
+// configured threshold (in basis points)
+fn check_confidence(price: i64, conf: u64, max_confidence_bps: u16) -> Result<()> {
+    // Handle negative prices (shouldn't happen for spot crypto, but be safe)
+    require!(price > 0, LiqGuardError::PriceConfidenceTooWide);
+    let confidence_bps = (conf as u128)
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(LiqGuardError::MathOverflow)?
+        / (price as u128);
+    require!(
+        confidence_bps <= max_confidence_bps as u128,
+        LiqGuardError::PriceConfidenceTooWide
+    );
+    Ok(())
+}
+
+// Bring two prices onto the finer of their exponents so they can be
+// compared without truncating either side. Mantissas only ever get scaled
+// up, in i128, so a 0.000021 strike against an expo -8 feed stays exact
+fn align_prices(a: OraclePrice, b: OraclePrice) -> Result<(i128, i128)> {
+    liqguard_common::price::align(a, b).ok_or(error!(LiqGuardError::MathOverflow))
+}
+
+// Settlement paths without liquidate_policy's two-step confirmation
+// refuse a spot print more than max_deviation_bps off the Pyth EMA
+// outright. Sources without an EMA, and a 0 limit, skip the check
+fn check_ema_deviation(quote: &OracleQuote, max_deviation_bps: u16) -> Result<()> {
+    if let Some(ema_price) = quote.ema_price.filter(|_| max_deviation_bps > 0) {
+        require!(
+            price_deviation_bps(ema_price, quote.price)? <= max_deviation_bps as u128,
+            LiqGuardError::DeviationUnconfirmed
+        );
+    }
+    Ok(())
+}
+
+// Two oracle readings agree when |a - b| / a stays within tolerance_bps
+fn check_oracle_agreement(price: OraclePrice, other_price: OraclePrice, tolerance_bps: u16) -> Result<()> {
+    require!(
+        price_deviation_bps(price, other_price)? <= tolerance_bps as u128,
+        LiqGuardError::OracleDisagreement
+    );
+    Ok(())
+}
+
+// |other - reference| / reference in bps
+fn price_deviation_bps(reference: OraclePrice, other: OraclePrice) -> Result<u128> {
+    let (reference, other) = align_prices(reference, other)?;
+    require!(reference > 0, LiqGuardError::OracleDisagreement);
+    Ok(reference
+        .abs_diff(other)
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(LiqGuardError::MathOverflow)?
+        / reference as u128)
+}
+
+pub fn is_triggered(policy: &Policy, current_price: OraclePrice) -> Result<bool> {
+    triggered_at(policy, current_price, current_price)
+}
+
+// The spot trigger check, with the price shifted by
+// confidence_multiplier_bps of the quote's confidence. Conservative
+// compares price + shift against lower bounds (the ProtectLong strike, a
+// band's bottom) and price - shift against upper ones, so settlement
+// needs a breach beyond the oracle's own uncertainty; Lenient shifts the
+// other way
+fn spot_triggered(policy: &Policy, quote: &OracleQuote) -> Result<bool> {
+    let shift = quote
+        .confidence
+        .checked_mul(policy.confidence_multiplier_bps as u128)
+        .ok_or(LiqGuardError::MathOverflow)?
+        / BPS_DENOMINATOR as u128;
+    let shift = i128::try_from(shift).map_err(|_| error!(LiqGuardError::MathOverflow))?;
+    let shift = match policy.confidence_adjustment {
+        ConfidenceAdjustment::None => 0,
+        ConfidenceAdjustment::Conservative => shift,
+        ConfidenceAdjustment::Lenient => -shift,
+    };
+    let shifted = |shift: i128| -> Result<OraclePrice> {
+        Ok(OraclePrice {
+            mantissa: quote
+                .price
+                .mantissa
+                .checked_add(shift)
+                .ok_or(LiqGuardError::MathOverflow)?,
+            exponent: quote.price.exponent,
+        })
+    };
+    triggered_at(policy, shifted(shift)?, shifted(-shift)?)
+}
+
+// price_vs_lower is compared against the lower bound (the ProtectLong
+// strike or a band's bottom), price_vs_upper against the upper one
+fn triggered_at(
+    policy: &Policy,
+    price_vs_lower: OraclePrice,
+    price_vs_upper: OraclePrice,
+) -> Result<bool> {
+    // The buffer pushes each bound away from the covered range: the
+    // strike down for ProtectLong and the bottom of a band, up for
+    // ProtectShort and the top of a band
+    let buffer_bps = policy.trigger_buffer_bps;
+    let strike = buffered_strike(
+        policy.strike_price,
+        buffer_bps,
+        policy.direction == TriggerDirection::ProtectShort,
+    )?;
+    let upper_strike = buffered_strike(policy.upper_strike_price, buffer_bps, true)?;
+    let inclusive = policy.inclusive_strike;
+    let below = |price| strike_breached(TriggerDirection::ProtectLong, strike, upper_strike, price, inclusive);
+    match policy.direction {
+        TriggerDirection::ProtectLong => below(price_vs_lower),
+        TriggerDirection::ProtectShort => {
+            strike_breached(TriggerDirection::ProtectShort, strike, upper_strike, price_vs_upper, inclusive)
+        }
+        TriggerDirection::OutsideRange => Ok(below(price_vs_lower)?
+            || strike_breached(
+                TriggerDirection::ProtectShort,
+                upper_strike,
+                upper_strike,
+                price_vs_upper,
+                inclusive,
+            )?),
+    }
+}
+
+// A strike moved buffer_bps away from the covered range, up or down
+fn buffered_strike(strike_price: u64, buffer_bps: u16, up: bool) -> Result<OraclePrice> {
+    let strike =
+        payout::shifted(strike_price, buffer_bps, up).ok_or(LiqGuardError::MathOverflow)?;
+    Ok(OraclePrice::new(strike, STRIKE_EXPONENT))
+}
+
+// A spread's short strike sits past its long one in the direction the
+// policy pays, below for ProtectLong and above for ProtectShort. Only
+// spreads carry one
+fn valid_spread(payout_mode: PayoutMode, direction: TriggerDirection, strike_price: u64, short_strike_price: u64) -> bool {
+    match (payout_mode, direction) {
+        (PayoutMode::Spread, TriggerDirection::ProtectLong) => {
+            short_strike_price > 0 && short_strike_price < strike_price
+        }
+        (PayoutMode::Spread, TriggerDirection::ProtectShort) => short_strike_price > strike_price,
+        (PayoutMode::Spread, TriggerDirection::OutsideRange) => false,
+        _ => short_strike_price == 0,
+    }
+}
+
+// OutsideRange policies pay outside [strike, upper_strike]; the one-sided
+// directions have a single strike and no upper bound
+fn valid_strike_range(direction: TriggerDirection, strike_price: u64, upper_strike_price: u64) -> bool {
+    match direction {
+        TriggerDirection::OutsideRange => strike_price > 0 && upper_strike_price > strike_price,
+        _ => upper_strike_price == 0,
+    }
+}
+
+// The bound a triggered price is measured from: the upper one once an
+// OutsideRange policy has broken out above its band, else the strike
+fn breached_strike(policy: &Policy, current_price: OraclePrice) -> Result<OraclePrice> {
+    if policy.direction == TriggerDirection::OutsideRange {
+        let (current_price, upper_strike_price) = align_prices(current_price, policy.upper_strike())?;
+        if current_price > upper_strike_price {
+            return Ok(policy.upper_strike());
+        }
+    }
+    Ok(policy.strike())
+}
+
+// The barrier is touched moving away from the strike: at or above a
+// barrier set above it, at or below one set below it
+fn barrier_touched(policy: &Policy, current_price: OraclePrice) -> Result<bool> {
+    let (current_price, barrier_price) = align_prices(current_price, policy.barrier())?;
+    if policy.barrier_price > policy.strike_price {
+        Ok(current_price >= barrier_price)
+    } else {
+        Ok(current_price <= barrier_price)
+    }
+}
+
+// Strictly past the strike: a price exactly at it doesn't trigger
+fn strike_crossed(
+    direction: TriggerDirection,
+    strike: OraclePrice,
+    upper_strike: OraclePrice,
+    current_price: OraclePrice,
+) -> Result<bool> {
+    strike_breached(direction, strike, upper_strike, current_price, false)
+}
+
+// Inclusive comparisons also trigger at the strike itself
+fn strike_breached(
+    direction: TriggerDirection,
+    strike: OraclePrice,
+    upper_strike: OraclePrice,
+    current_price: OraclePrice,
+    inclusive: bool,
+) -> Result<bool> {
+    let (price, strike_price) = align_prices(current_price, strike)?;
+    let below = |price: i128, bound: i128| price < bound || (inclusive && price == bound);
+    let above = |price: i128, bound: i128| price > bound || (inclusive && price == bound);
+    match direction {
+        // Protect Long: Pay if price drops below strike
+        // "I am Long. I am afraid it will drop. Pay me if Price < Strike."
+        TriggerDirection::ProtectLong => Ok(below(price, strike_price)),
+        // Protect Short: Pay if price rises above strike
+        // "I am Short. I am afraid it will moon. Pay me if Price > Strike."
+        TriggerDirection::ProtectShort => Ok(above(price, strike_price)),
+        // Outside Range: Pay if price leaves the band in either direction
+        // "I am delta-neutral. I am afraid of a big move. Pay me if Price < Strike or Price > Upper."
+        TriggerDirection::OutsideRange => {
+            let (price_vs_upper, upper_strike_price) = align_prices(current_price, upper_strike)?;
+            Ok(below(price, strike_price) || above(price_vs_upper, upper_strike_price))
+        }
+    }
+}
+
+// Lamports in a portfolio vault above rent and what its open policies
+// have reserved
+fn unreserved_balance(portfolio: &Account<PortfolioVault>) -> Result<u64> {
+    let rent_floor = Rent::get()?.minimum_balance(8 + PortfolioVault::LEN);
+    Ok(portfolio
+        .get_lamports()
+        .saturating_sub(rent_floor)
+        .saturating_sub(portfolio.reserved_exposure))
+}
+
+// Share of a portfolio vault's collateral above rent its open policies
+// would have reserved with exposure more, in bps. An empty vault is fully
+// utilized
+fn portfolio_utilization(portfolio: &Account<PortfolioVault>, exposure: u64) -> Result<u64> {
+    let rent_floor = Rent::get()?.minimum_balance(8 + PortfolioVault::LEN);
+    let collateral = portfolio.get_lamports().saturating_sub(rent_floor);
+    let reserved = portfolio.reserved_exposure.saturating_add(exposure);
+    let full = BPS_DENOMINATOR as u128;
+    if collateral == 0 {
+        return Ok(full as u64);
+    }
+    Ok((reserved as u128 * full / collateral as u128).min(full) as u64)
+}
+
+// A closing portfolio-backed policy hands back what it still had reserved
+fn release_portfolio_exposure(
+    policy: &Policy,
+    portfolio: Option<&mut Account<PortfolioVault>>,
+) -> Result<()> {
+    if policy.portfolio_vault == Pubkey::default() {
+        return Ok(());
+    }
+    let portfolio = portfolio.ok_or(LiqGuardError::MissingPortfolioVault)?;
+    portfolio.reserved_exposure = portfolio
+        .reserved_exposure
+        .saturating_sub(policy.portfolio_exposure);
+    Ok(())
+}
+
+// Pay a premium escrowed in the policy account to the underwriter, with
+// the same treasury and backstop carve-outs as an opening premium
+fn distribute_escrowed_premium<'info>(
+    config: &Config,
+    policy: &AccountInfo<'info>,
+    underwriter: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    backstop: Option<&mut Account<'info, Backstop>>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let (underwriter_premium, protocol_fee, backstop_fee) =
+        payout::split_premium(amount, config.protocol_fee_bps, config.backstop_fee_bps)
+            .ok_or(LiqGuardError::MathOverflow)?;
+    policy.sub_lamports(amount)?;
+    underwriter.add_lamports(underwriter_premium)?;
+    treasury.add_lamports(protocol_fee)?;
+    if backstop_fee > 0 {
+        let backstop = backstop.ok_or(LiqGuardError::MissingBackstop)?;
+        backstop.add_lamports(backstop_fee)?;
+        backstop.total_contributed = backstop
+            .total_contributed
+            .checked_add(backstop_fee)
+            .ok_or(LiqGuardError::MathOverflow)?;
+    }
+    Ok(())
+}
+
+// Pay a triggered claim out of the policy vault: payout to the beneficiary,
+// or across the payout split to split_accounts, and the bounty to whoever
+// cranked the settlement
+#[allow(clippy::too_many_arguments)]
+fn pay_claim<'info>(
+    policy: &Policy,
+    vault: &SystemAccount<'info>,
+    beneficiary: &AccountInfo<'info>,
+    liquidator: &Signer<'info>,
+    token_vault: Option<&Account<'info, TokenAccount>>,
+    beneficiary_token_account: Option<&Account<'info, TokenAccount>>,
+    liquidator_token_account: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+    system_program: &Program<'info, System>,
+    split_accounts: &[AccountInfo<'info>],
+    payout: u64,
+    bounty: u64,
+) -> Result<()> {
+    let vault = vault.to_account_info();
+    if policy.is_native() {
+        let system_program = system_program.to_account_info();
+        if policy.has_payout_split() {
+            pay_split(policy, &system_program, &vault, None, split_accounts, payout)?;
+        } else {
+            vault_transfer(&system_program, &vault, beneficiary, policy, payout)?;
+        }
+        if bounty > 0 {
+            vault_transfer(&system_program, &vault, &liquidator.to_account_info(), policy, bounty)?;
+        }
+        return Ok(());
+    }
+
+    let (Some(token_program), Some(token_vault)) = (token_program, token_vault) else {
+        return err!(LiqGuardError::MissingTokenAccounts);
+    };
+    let token_program = token_program.to_account_info();
+    let token_vault = token_vault.to_account_info();
+    if policy.has_payout_split() {
+        pay_split(
+            policy,
+            &token_program,
+            &vault,
+            Some(&token_vault),
+            split_accounts,
+            payout,
+        )?;
+    } else {
+        let beneficiary_token_account =
+            beneficiary_token_account.ok_or(LiqGuardError::MissingTokenAccounts)?;
+        token_vault_transfer(
+            &token_program,
+            &token_vault,
+            &beneficiary_token_account.to_account_info(),
+            &vault,
+            policy,
+            payout,
+        )?;
+    }
+    if bounty > 0 {
+        let liquidator_token_account =
+            liquidator_token_account.ok_or(LiqGuardError::MissingTokenAccounts)?;
+        token_vault_transfer(
+            &token_program,
+            &token_vault,
+            &liquidator_token_account.to_account_info(),
+            &vault,
+            policy,
+            bounty,
+        )?;
+    }
+    Ok(())
+}
+
+// A payout split is up to MAX_PAYOUT_SPLITS distinct recipients, none of
+// them the underwriter, each with a share, adding up to 100%. No shares
+// at all clears the split
+fn check_payout_split(shares: &[PayoutShare], underwriter: &Pubkey) -> Result<()> {
+    if shares.is_empty() {
+        return Ok(());
+    }
+    let distinct = shares.iter().enumerate().all(|(index, share)| {
+        share.share_bps > 0
+            && share.recipient != Pubkey::default()
+            && share.recipient != *underwriter
+            && shares[..index]
+                .iter()
+                .all(|earlier| earlier.recipient != share.recipient)
+    });
+    let total: u32 = shares.iter().map(|share| share.share_bps as u32).sum();
+    require!(
+        shares.len() <= MAX_PAYOUT_SPLITS && distinct && total == BPS_DENOMINATOR as u32,
+        LiqGuardError::InvalidPayoutSplit
+    );
+    Ok(())
+}
+
+// Pay amount out of the vault across the policy's payout shares. The
+// recipients' accounts come in share order: their wallets for native
+// collateral (program is the system program), otherwise token accounts
+// of the collateral mint they own (program is the token program). The
+// last share takes the rounding remainder
+fn pay_split<'info>(
+    policy: &Policy,
+    program: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    token_vault: Option<&AccountInfo<'info>>,
+    recipients: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let shares = policy.payout_shares();
+    require!(
+        recipients.len() == shares.len(),
+        LiqGuardError::InvalidSplitRecipient
+    );
+    let shares_bps: Vec<u16> = shares.iter().map(|share| share.share_bps).collect();
+    let amounts = payout::split(amount, &shares_bps).ok_or(LiqGuardError::MathOverflow)?;
+    for ((share, recipient), amount) in shares.iter().zip(recipients).zip(amounts) {
+        match token_vault {
+            None => {
+                require_keys_eq!(
+                    recipient.key(),
+                    share.recipient,
+                    LiqGuardError::InvalidSplitRecipient
+                );
+                vault_transfer(program, vault, recipient, policy, amount)?;
+            }
+            Some(token_vault) => {
+                require_keys_eq!(*recipient.owner, token::ID, LiqGuardError::InvalidSplitRecipient);
+                let account = TokenAccount::try_deserialize(&mut &recipient.try_borrow_data()?[..])?;
+                require!(
+                    account.mint == policy.collateral_mint && account.owner == share.recipient,
+                    LiqGuardError::InvalidSplitRecipient
+                );
+                token_vault_transfer(program, token_vault, recipient, vault, policy, amount)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Return residual collateral to the underwriter and delete the vault(s).
+// Returns the lamports drained from the vault PDA
+#[allow(clippy::too_many_arguments)]
+fn sweep_vault<'info>(
+    policy: &Policy,
+    vault: &SystemAccount<'info>,
+    owner: &AccountInfo<'info>,
+    underwriter: &AccountInfo<'info>,
+    token_vault: Option<&Account<'info, TokenAccount>>,
+    underwriter_token_account: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+    system_program: &Program<'info, System>,
+) -> Result<u64> {
+    // Return residual tokens and close the token account (rent goes
+    // back to the owner who created it)
+    if !policy.is_native() {
+        let (Some(token_program), Some(token_vault), Some(underwriter_token_account)) =
+            (token_program, token_vault, underwriter_token_account)
+        else {
+            return err!(LiqGuardError::MissingTokenAccounts);
+        };
+        let token_program = token_program.to_account_info();
+        let vault = vault.to_account_info();
+        if token_vault.amount > 0 {
+            token_vault_transfer(
+                &token_program,
+                &token_vault.to_account_info(),
+                &underwriter_token_account.to_account_info(),
+                &vault,
+                policy,
+                token_vault.amount,
+            )?;
+        }
+        close_token_vault(
+            &token_program,
+            &token_vault.to_account_info(),
+            owner,
+            &vault,
+            policy,
+        )?;
+        msg!("Token vault reclaimed: Amount={}", token_vault.amount);
+    }
+
+    // Draining every lamport deletes the vault system account
+    let remaining = vault.lamports();
+    if remaining > 0 {
+        vault_transfer(
+            &system_program.to_account_info(),
+            &vault.to_account_info(),
+            underwriter,
+            policy,
+            remaining,
+        )?;
+    }
+
+    Ok(remaining)
+}
+
+// Move lamports out of a PolicyBook's pooled vault
+fn book_vault_transfer<'info>(
+    system_program: &AccountInfo<'info>,
+    book_vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    policy_book: &Pubkey,
+    vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[BOOK_VAULT_SEED, policy_book.as_ref(), &[vault_bump]];
+    let signer = &[&seeds[..]];
+
+    let cpi_context = CpiContext::new_with_signer(
+        system_program.clone(),
+        anchor_lang::system_program::Transfer {
+            from: book_vault.clone(),
+            to: to.clone(),
+        },
+        signer,
+    );
+    anchor_lang::system_program::transfer(cpi_context, amount)
+}
+
+// Move lamports out of a policy vault, signing with the vault PDA seeds
+fn vault_transfer<'info>(
+    system_program: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    policy: &Policy,
+    amount: u64,
+) -> Result<()> {
+    let policy_id_bytes = policy.policy_id.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        policy.creator.as_ref(),
+        policy_id_bytes.as_ref(),
+        &[policy.vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_context = CpiContext::new_with_signer(
+        system_program.clone(),
+        anchor_lang::system_program::Transfer {
+            from: vault.clone(),
+            to: to.clone(),
+        },
+        signer,
+    );
+    anchor_lang::system_program::transfer(cpi_context, amount)
+}
+
+// Move SPL tokens out of a policy token vault; the lamport vault PDA is the
+// token account authority
+fn token_vault_transfer<'info>(
+    token_program: &AccountInfo<'info>,
+    token_vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    policy: &Policy,
+    amount: u64,
+) -> Result<()> {
+    let policy_id_bytes = policy.policy_id.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        policy.creator.as_ref(),
+        policy_id_bytes.as_ref(),
+        &[policy.vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_context = CpiContext::new_with_signer(
+        token_program.clone(),
+        token::Transfer {
+            from: token_vault.clone(),
+            to: to.clone(),
+            authority: vault.clone(),
+        },
+        signer,
+    );
+    token::transfer(cpi_context, amount)
+}
+
+// Close an emptied policy token vault, sending its rent to destination
+fn close_token_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    token_vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    policy: &Policy,
+) -> Result<()> {
+    let policy_id_bytes = policy.policy_id.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        policy.creator.as_ref(),
+        policy_id_bytes.as_ref(),
+        &[policy.vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_context = CpiContext::new_with_signer(
+        token_program.clone(),
+        token::CloseAccount {
+            account: token_vault.clone(),
+            destination: destination.clone(),
+            authority: vault.clone(),
+        },
+        signer,
+    );
+    token::close_account(cpi_context)
+}
+
+// Split a pooled policy's opening premium between the underwriter and the
+// treasury, the same way a standalone policy's is
+fn pay_pooled_premium<'info>(
+    config: &Config,
+    system_program: &Program<'info, System>,
+    owner: &Signer<'info>,
+    underwriter: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    premium_amount: u64,
+) -> Result<()> {
+    let (underwriter_premium, protocol_fee, _) =
+        payout::split_premium(premium_amount, config.protocol_fee_bps, 0)
+            .ok_or(LiqGuardError::MathOverflow)?;
+    for (to, amount) in [
+        (underwriter.clone(), underwriter_premium),
+        (treasury.clone(), protocol_fee),
+    ] {
+        if amount > 0 {
+            let cpi_context = CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: owner.to_account_info(),
+                    to,
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, amount)?;
+        }
+    }
+    Ok(())
+}
+
+// Lamports on a compressed tree PDA beyond its rent and open reservations
+fn unreserved_tree_balance(compressed_tree: &Account<CompressedPolicyTree>) -> Result<u64> {
+    let rent_floor = Rent::get()?.minimum_balance(8 + CompressedPolicyTree::LEN);
+    Ok(compressed_tree
+        .get_lamports()
+        .saturating_sub(rent_floor)
+        .saturating_sub(compressed_tree.reserved_coverage))
+}
+
+// Replace a live leaf with EMPTY_LEAF and release its reservation. The
+// compression program rejects the swap unless the proof shows the leaf
+// is in the tree, so a spent or forged leaf can't get through
+fn spend_compressed_policy<'info>(
+    compressed_tree: &mut Account<'info, CompressedPolicyTree>,
+    merkle_tree: &AccountInfo<'info>,
+    compression_program: &AccountInfo<'info>,
+    noop_program: &AccountInfo<'info>,
+    root: [u8; 32],
+    leaf: &CompressedPolicy,
+    proof: &[AccountInfo<'info>],
+) -> Result<()> {
+    let merkle_tree_key = merkle_tree.key();
+    let seeds: &[&[u8]] = &[
+        COMPRESSED_TREE_SEED,
+        merkle_tree_key.as_ref(),
+        &[compressed_tree.bump],
+    ];
+    compression::MerkleTree {
+        compression_program,
+        noop_program,
+        merkle_tree,
+        authority: &compressed_tree.to_account_info(),
+        authority_seeds: seeds,
+    }
+    .replace_leaf(root, leaf.hash()?, compression::EMPTY_LEAF, leaf.leaf_index, proof)?;
+
+    compressed_tree.reserved_coverage = compressed_tree
+        .reserved_coverage
+        .checked_sub(leaf.coverage_amount)
+        .ok_or(LiqGuardError::MathOverflow)?;
+    compressed_tree.open_count -= 1;
+    Ok(())
+}
+
+// Collateral left in a policy vault, in the policy's units. Token vaults
+// are reloaded, since a CPI transfer doesn't refresh the deserialized copy
+fn collateral_balance(
+    policy: &Policy,
+    vault: &SystemAccount,
+    token_vault: Option<&mut Account<TokenAccount>>,
+) -> Result<u64> {
+    if policy.is_native() {
+        return Ok(vault.lamports());
+    }
+    let token_vault = token_vault.ok_or(LiqGuardError::MissingTokenAccounts)?;
+    token_vault.reload()?;
+    Ok(token_vault.amount)
+}
+
+// A roll is due once an unclaimed auto-roll policy has expired, until
+// ROLL_WINDOW has passed
+fn check_rollable(policy: &Policy, now: i64) -> Result<()> {
+    require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+    require!(policy.roll_period_seconds > 0, LiqGuardError::AutoRollDisabled);
+    require!(
+        now >= policy.expiration_datetime,
+        LiqGuardError::PolicyStillActive
+    );
+    require!(
+        now < policy.expiration_datetime.saturating_add(ROLL_WINDOW),
+        LiqGuardError::RollWindowClosed
+    );
+    Ok(())
+}
+
+// Renew a due policy for another roll period, paying the premium out of
+// its escrow. The vault must still hold the full coverage
+fn roll_period<'info>(
+    config: &Config,
+    policy: &mut Account<'info, Policy>,
+    vault_balance: u64,
+    underwriter: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    backstop: Option<&mut Account<'info, Backstop>>,
+    slot: u64,
+) -> Result<()> {
+    require!(
+        vault_balance >= policy.coverage_amount,
+        LiqGuardError::VaultNotFunded
+    );
+    require!(
+        policy.roll_premium_escrow >= policy.premium_amount,
+        LiqGuardError::InsufficientRollPremium
+    );
+
+    let premium_amount = policy.premium_amount;
+    distribute_escrowed_premium(
+        config,
+        &policy.to_account_info(),
+        underwriter,
+        treasury,
+        backstop,
+        premium_amount,
+    )?;
+
+    // Each period starts with its full coverage again
+    policy.roll_premium_escrow -= premium_amount;
+    policy.coverage_remaining = policy.coverage_amount;
+    policy.expiration_datetime = policy
+        .expiration_datetime
+        .checked_add(policy.roll_period_seconds)
+        .ok_or(LiqGuardError::MathOverflow)?;
+    policy.is_funded = true;
+    policy.pending_coverage_amount = 0;
+    policy.coverage_unlock_at = 0;
+
+    emit!(PolicyRolled {
+        policy: policy.key(),
+        premium_amount,
+        expiration_datetime: policy.expiration_datetime,
+        slot,
+        sequence: next_event_sequence(policy)?,
+    });
+    Ok(())
+}
+
+// Per-policy event counter so indexers can order events and detect gaps.
+// Returns the sequence number for the event being emitted
+fn next_event_sequence(policy: &mut Policy) -> Result<u64> {
+    let sequence = policy.event_sequence;
+    policy.event_sequence = sequence
+        .checked_add(1)
+        .ok_or(LiqGuardError::MathOverflow)?;
+    Ok(sequence)
+}
+
+// Policy-state checks a price-triggered liquidation must pass before its
+// oracle is read
+pub fn check_claimable(policy: &Policy, config: &Config, now: i64) -> Result<()> {
+    // Coverage must be left to claim, with no earlier claim still
+    // streaming out
+    require!(!policy.is_exhausted(), LiqGuardError::AlreadyClaimed);
+    require!(policy.unvested_payout == 0, LiqGuardError::VestingInProgress);
+    require!(policy.holdback_amount == 0, LiqGuardError::HoldbackPending);
+    require!(policy.pending_swap_amount == 0, LiqGuardError::SwapPending);
+
+    // Coverage is only live once the vault has been fully funded
+    require!(policy.is_funded, LiqGuardError::VaultNotFunded);
+
+    // European policies ignore touches and pay through settle
+    require!(
+        policy.exercise_style == ExerciseStyle::American,
+        LiqGuardError::EuropeanPolicy
+    );
+    require!(policy.barrier_allows_claim(), LiqGuardError::BarrierBlocksClaim);
+    require!(policy.is_premium_paid(), LiqGuardError::WormholePremiumUnpaid);
+
+    // Coverage ends at expiration, or once a streaming premium runs dry
+    require!(now < policy.expiration_datetime, LiqGuardError::PolicyExpired);
+    require!(!policy.is_lapsed(now), LiqGuardError::CoverageLapsed);
+
+    // A fresh policy can't be claimed right away, so nobody can buy
+    // coverage after the strike has already been crossed
+    require!(
+        now >= policy.created_at.saturating_add(config.min_policy_age),
+        LiqGuardError::PolicyTooNew
+    );
+    Ok(())
+}
+
+// Where a breaching reading at now leaves a policy's breach dwell: the
+// time it becomes confirmable if this reading only starts (or restarts)
+// the dwell, None once it has been served or for policies without one.
+// Fails while a recorded breach is still dwelling
+fn breach_dwell(policy: &Policy, now: i64) -> Result<Option<i64>> {
+    if policy.breach_dwell_seconds == 0 {
+        return Ok(None);
+    }
+    let confirmable_from = policy
+        .breach_observed_at
+        .saturating_add(policy.breach_dwell_seconds);
+    let confirmable_until = confirmable_from.saturating_add(BREACH_CONFIRM_WINDOW);
+    if policy.breach_observed_at == 0 || now >= confirmable_until {
+        return Ok(Some(now.saturating_add(policy.breach_dwell_seconds)));
+    }
+    require!(now >= confirmable_from, LiqGuardError::BreachUnconfirmed);
+    Ok(None)
+}
+
+// The decision liquidate_policy acts on once a policy's trigger has
+// fired at settlement_price, quote being the spot read behind it. A breach
+// dwell still to serve, or a spot print too far from its EMA, only gets
+// recorded. Otherwise the claim is what the payout curve owes beyond
+// earlier claims, capped by draw (the master agreement), then turned by
+// convert into what the vault pays: lamports at the SOL/USD price for USD
+// coverage, pool tokens for LST coverage. The liquidator bounty and then
+// the protocol fee come out of that. preview_payout runs the same steps
+// with draw and convert passed through
+fn decide_payout(
+    policy: &Policy,
+    config: &Config,
+    quote: &OracleQuote,
+    settlement_price: OraclePrice,
+    now: i64,
+    draw: impl FnOnce(u64) -> Result<u64>,
+    convert: impl FnOnce(u64) -> Result<u64>,
+) -> Result<PayoutDecision> {
+    // Hysteresis: a policy with a breach dwell pays only once a second
+    // reading, at least breach_dwell_seconds after the first, still
+    // breaches. The first attempt records the breach and pays nothing;
+    // one left unconfirmed for BREACH_CONFIRM_WINDOW is recorded anew
+    if let Some(confirmable_from) = breach_dwell(policy, now)? {
+        return Ok(PayoutDecision::ObserveBreach { confirmable_from });
+    }
+
+    // Circuit breaker: a spot print far from the Pyth EMA has to be
+    // confirmed by a second liquidation once deviation_confirm_delay has
+    // passed. The first attempt only records the flag and pays nothing.
+    // Sources without an EMA skip it
+    if let Some(ema_price) = quote.ema_price.filter(|_| config.max_price_deviation_bps > 0) {
+        if price_deviation_bps(ema_price, quote.price)? > config.max_price_deviation_bps as u128 {
+            let confirmable_from = policy
+                .deviation_flagged_at
+                .saturating_add(config.deviation_confirm_delay);
+            let confirmable_until = confirmable_from.saturating_add(DEVIATION_CONFIRM_WINDOW);
+            if policy.deviation_flagged_at == 0 || now >= confirmable_until {
+                return Ok(PayoutDecision::FlagDeviation {
+                    ema_price,
+                    confirmable_from: now.saturating_add(config.deviation_confirm_delay),
+                });
+            }
+            require!(now >= confirmable_from, LiqGuardError::DeviationUnconfirmed);
+        }
+    }
+
+    let claim = policy.claimable(payout_amount(policy, settlement_price)?);
+    require!(claim > 0, LiqGuardError::NothingToClaim);
+    let claim = draw(claim)?;
+    let value = convert(claim)?;
+    let bounty = liquidator_bounty(config, value, policy.is_native())?;
+    let payout = value.checked_sub(bounty).ok_or(LiqGuardError::MathOverflow)?;
+    // The protocol takes its payout fee before vesting or holdbacks
+    let fee = payout_fee(config, payout, policy.is_native())?;
+    Ok(PayoutDecision::Pay {
+        claim,
+        payout: payout - fee,
+        bounty,
+        fee,
+    })
+}
+
+// What liquidate_policy would decide for policy at a spot price and its
+// confidence (both in the price's exponent) at now, from the policy and
+// config alone. It runs the instruction's claimable checks and
+// confidence-adjusted spot trigger, then decide_payout, so keepers, the
+// CLI and tests get the program's answer without a transaction. Steps that
+// need other accounts are left out: oracle staleness and confidence
+// limits, Kamino, TWAP, Switchboard and Drift triggers, the deviation
+// breaker (which needs the EMA) and liquidator permissions. Master
+// agreement limits and USD or LST conversion are taken as 1:1, so those
+// policies' figures are in coverage units and cap at their own coverage
+pub fn preview_payout(
+    policy: &Policy,
+    config: &Config,
+    price: OraclePrice,
+    confidence: u128,
+    now: i64,
+) -> PayoutDecision {
+    let decision = (|| {
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        check_claimable(policy, config, now)?;
+        let quote = OracleQuote {
+            price,
+            publish_time: now,
+            confidence,
+            ema_price: None,
+        };
+        require!(
+            spot_triggered(policy, &quote)?,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+        decide_payout(policy, config, &quote, price, now, Ok, Ok)
+    })();
+    decision.unwrap_or_else(|error| PayoutDecision::Reject(error_code_number(&error)))
+}
+
+// Optional accounts a policy's extra triggers are read from
+pub struct TriggerAccounts<'a, 'info> {
+    pub twap_update: Option<&'a Account<'info, TwapUpdate>>,
+    pub switchboard_feed: Option<&'a UncheckedAccount<'info>>,
+    pub drift_user: Option<&'a UncheckedAccount<'info>>,
+    pub kamino_obligation: Option<&'a UncheckedAccount<'info>>,
+}
+
+// What a triggered policy settles at: the spot price, or the TWAP for
+// TWAP policies, and the Drift liquidation id a claim consumes up to
+pub struct TriggerReading {
+    pub settlement_price: OraclePrice,
+    pub publish_time: i64,
+    pub drift_liquidation_id: u16,
+}
+
+// Staleness and confidence limits for a policy's reads; the stricter of
+// the registry and policy/config limits wins
+fn oracle_limits(policy: &Policy, config: &Config, feed: &FeedRegistry) -> (u64, u16) {
+    (
+        policy.max_price_age.min(feed.max_price_age),
+        config.max_confidence_bps.min(feed.max_confidence_bps),
+    )
+}
+
+// A spot price for the policy's asset from the feed's oracle source,
+// within the policy's staleness window and confidence limit. It stays in
+// the feed's exponent
+fn read_spot_price(
+    policy: &Policy,
+    config: &Config,
+    feed: &FeedRegistry,
+    price_feed: &AccountInfo,
+    clock: &Clock,
+) -> Result<OracleQuote> {
+    require!(feed.enabled, LiqGuardError::FeedDisabled);
+    let (max_price_age, max_confidence_bps) = oracle_limits(policy, config, feed);
+    oracle::get_price(&policy.feed_ref(feed), price_feed, clock, max_price_age, max_confidence_bps)
+}
+
+// Every trigger a policy carries, evaluated against the spot quote.
+// Fails with the reason the first unmet one gives
+fn evaluate_trigger(
+    policy: &Policy,
+    config: &Config,
+    feed: &FeedRegistry,
+    spot: &OracleQuote,
+    accounts: &TriggerAccounts,
+    clock: &Clock,
+) -> Result<TriggerReading> {
+    let (max_price_age, max_confidence_bps) = oracle_limits(policy, config, feed);
+
+    // Step 4: Check Direction. Obligation-keyed policies trigger when
+    // the Kamino health factor drops under their threshold instead
+    if policy.kamino_obligation != Pubkey::default() {
+        let obligation = kamino::load_obligation(
+            accounts
+                .kamino_obligation
+                .ok_or(LiqGuardError::MissingObligation)?,
+        )?;
+        require!(obligation.is_fresh(clock), LiqGuardError::ObligationStale);
+        require!(
+            obligation.health_bps()? < policy.health_threshold_bps as u128,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+    } else {
+        require!(
+            spot_triggered(policy, spot)?,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+    }
+
+    // TWAP policies also need the time-weighted price to have breached,
+    // and settle against it so a one-slot wick can't size the payout
+    let (settlement_price, publish_time) = if policy.twap_window_seconds > 0 {
+        let twap_update = accounts
+            .twap_update
+            .ok_or(LiqGuardError::MissingTwapUpdate)?;
+        let twap = twap_update
+            .get_twap_no_older_than(
+                clock,
+                max_price_age,
+                policy.twap_window_seconds,
+                &feed.feed_id,
+            )
+            .map_err(|_| LiqGuardError::PriceStale)?;
+        check_confidence(twap.price, twap.conf, max_confidence_bps)?;
+        let twap_price = OraclePrice::new(twap.price, twap.exponent);
+        require!(
+            is_triggered(policy, twap_price)?,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+        (twap_price, twap.end_time)
+    } else {
+        (spot.price, spot.publish_time)
+    };
+
+    // Dual-oracle policies also need Switchboard to see the breach and
+    // to agree with Pyth within the policy's tolerance
+    if policy.switchboard_feed != Pubkey::default() {
+        let switchboard_feed = accounts
+            .switchboard_feed
+            .ok_or(LiqGuardError::MissingSwitchboardFeed)?;
+        let switchboard_price = oracle::get_price(
+            &FeedRef {
+                source: OracleSource::Switchboard,
+                feed_id: [0; 32],
+                oracle_account: policy.switchboard_feed,
+                lazer_feed_id: 0,
+            },
+            switchboard_feed,
+            clock,
+            max_price_age,
+            max_confidence_bps,
+        )?
+        .price;
+        require!(
+            is_triggered(policy, switchboard_price)?,
+            LiqGuardError::LiquidationConditionNotMet
+        );
+        check_oracle_agreement(settlement_price, switchboard_price, policy.oracle_tolerance_bps)?;
+    }
+
+    // Drift-verified policies only pay once the covered account has
+    // actually been liquidated, not merely because price crossed. Each
+    // claim consumes the liquidations seen so far
+    let drift_liquidation_id = if policy.drift_user != Pubkey::default() {
+        let drift_user = drift::load_user(
+            accounts
+                .drift_user
+                .ok_or(LiqGuardError::MissingDriftUser)?,
+        )?;
+        require!(
+            drift_user.liquidated_since(policy.drift_liquidation_id),
+            LiqGuardError::DriftPositionNotLiquidated
+        );
+        drift_user.next_liquidation_id
+    } else {
+        policy.drift_liquidation_id
+    };
+
+    Ok(TriggerReading {
+        settlement_price,
+        publish_time,
+        drift_liquidation_id,
+    })
+}
+
+// Read-only run of liquidate_policy's policy, oracle and trigger checks:
+// the spot price read (zero if none was) and whether they all passed
+fn check_eligibility(
+    policy: &Policy,
+    config: &Config,
+    feed: &FeedRegistry,
+    price_feed: &AccountInfo,
+    accounts: &TriggerAccounts,
+    clock: &Clock,
+) -> (OraclePrice, Result<()>) {
+    let spot = (|| {
+        require!(!config.paused, LiqGuardError::ProtocolPaused);
+        check_claimable(policy, config, clock.unix_timestamp)?;
+        read_spot_price(policy, config, feed, price_feed, clock)
+    })();
+    match spot {
+        Ok(spot) => (
+            spot.price,
+            evaluate_trigger(policy, config, feed, &spot, accounts, clock).map(|_| ()),
+        ),
+        Err(error) => (OraclePrice::new(0, 0), Err(error)),
+    }
+}
+
+// Protocol cut of a payout for the treasury PDA. Like the flat bounty it
+// is lamport-denominated, so SPL payouts aren't charged
+fn payout_fee(config: &Config, payout: u64, is_native: bool) -> Result<u64> {
+    if !is_native {
+        return Ok(0);
+    }
+    Ok(payout::share_of(payout, config.payout_fee_bps).ok_or(LiqGuardError::MathOverflow)?)
+}
+
+// Move a payout fee from a native vault into the treasury PDA
+fn collect_payout_fee<'info>(
+    system_program: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    treasury: Option<&mut Account<'info, Treasury>>,
+    policy: &mut Account<'info, Policy>,
+    fee: u64,
+) -> Result<()> {
+    if fee == 0 {
+        return Ok(());
+    }
+    let treasury = treasury.ok_or(LiqGuardError::MissingTreasury)?;
+    vault_transfer(system_program, vault, &treasury.to_account_info(), policy, fee)?;
+    treasury.total_collected = treasury
+        .total_collected
+        .checked_add(fee)
+        .ok_or(LiqGuardError::MathOverflow)?;
+
+    emit!(PayoutFeeCollected {
+        policy: policy.key(),
+        amount: fee,
+        treasury_balance: treasury.get_lamports(),
+        slot: Clock::get()?.slot,
+        sequence: next_event_sequence(policy)?,
+    });
+    Ok(())
+}
+
+// Most of a native payout a vault holding balance lamports can make once
+// reserved (the bounty and fee) is out. A system account may be drained
+// to zero or left rent-exempt, never in between, so a payout that would
+// strand less than rent_floor is trimmed to leave rent_floor behind.
+// close_policy and reclaim_vault sweep that dust to the underwriter
+fn rent_exempt_payout(balance: u64, payout: u64, reserved: u64, rent_floor: u64) -> u64 {
+    let available = balance.saturating_sub(reserved);
+    if payout >= available {
+        return available;
+    }
+    let residual = available - payout;
+    if residual >= rent_floor {
+        payout
+    } else {
+        available.saturating_sub(rent_floor)
+    }
+}
+
+// Hold dispute_holdback_bps of a native payout back in the vault until the
+// dispute window closes, so a liquidation off a manipulated print can be
+// clawed back. SPL payouts aren't held back
+fn hold_back(config: &Config, policy: &mut Policy, payout: u64, now: i64) -> Result<u64> {
+    if !policy.is_native() || config.dispute_holdback_bps == 0 {
+        return Ok(0);
+    }
+    policy.holdback_amount = payout::share_of(payout, config.dispute_holdback_bps)
+        .ok_or(LiqGuardError::MathOverflow)?;
+    policy.holdback_release_at = now.saturating_add(config.dispute_window);
+    Ok(policy.holdback_amount)
+}
+
+// Cap a member policy's claim at what's left of its master agreement's
+// aggregate limit and book it there. Other policies claim in full
+fn draw_master_agreement(
+    policy: &Policy,
+    master_agreement: Option<&mut Account<MasterAgreement>>,
+    claim_amount: u64,
+) -> Result<u64> {
+    if policy.master_agreement == Pubkey::default() {
+        return Ok(claim_amount);
+    }
+    let agreement = master_agreement.ok_or(LiqGuardError::InvalidMasterAgreement)?;
+    let drawn = agreement.draw(claim_amount);
+    require!(drawn > 0, LiqGuardError::AggregateLimitReached);
+    Ok(drawn)
+}
+
+// Price a batch settles against: the asset's price cache when it is passed
+// as price_feed, which push_price must have refreshed this slot, else the
+// feed's oracle
+fn batch_price(
+    config: &Config,
+    feed: &FeedRegistry,
+    price_feed: &AccountInfo,
+    clock: &Clock,
+) -> Result<oracle::OracleQuote> {
+    let cached = *price_feed.owner == crate::ID
+        && price_feed.try_borrow_data()?.starts_with(&<PriceCache as anchor_lang::Discriminator>::DISCRIMINATOR);
+    if !cached {
+        return oracle::get_price(
+            &feed.feed_ref(),
+            price_feed,
+            clock,
+            feed.max_price_age,
+            config.max_confidence_bps.min(feed.max_confidence_bps),
+        );
+    }
+    let cache = PriceCache::try_deserialize(&mut &price_feed.try_borrow_data()?[..])?;
+    require!(cache.asset == feed.asset, LiqGuardError::InvalidOracleAccount);
+    require!(cache.slot == clock.slot, LiqGuardError::PriceCacheStale);
+    Ok(cache.quote())
+}
+
+// Mint of the credential a policy's payout wallet must hold, e.g. a KYC
+// attestation token: the config-wide one, else the policy's own.
+// Pubkey::default() when neither gates payouts
+fn compliance_mint(config: &Config, policy: &Policy) -> Pubkey {
+    if config.compliance_mint != Pubkey::default() {
+        config.compliance_mint
+    } else {
+        policy.compliance_mint
+    }
+}
+
+// Regulated underwriters can only pay wallets that hold their credential,
+// so a gated policy checks it when the wallet is named and again when it
+// is paid
+fn check_payout_wallet(
+    config: &Config,
+    policy: &Policy,
+    wallet: &Pubkey,
+    credential: Option<&Account<TokenAccount>>,
+) -> Result<()> {
+    let mint = compliance_mint(config, policy);
+    if mint == Pubkey::default() {
+        return Ok(());
+    }
+    // Split recipients aren't checked, so gated payouts go to one wallet
+    require!(!policy.has_payout_split(), LiqGuardError::PayoutSplitUnsupported);
+    let credential = credential.ok_or(LiqGuardError::PayoutWalletNotCompliant)?;
+    require!(
+        credential.mint == mint && credential.owner == *wallet && credential.amount > 0,
+        LiqGuardError::PayoutWalletNotCompliant
+    );
+    Ok(())
+}
+
+// Append to the audit log when the caller passed it
+fn record_audit(audit_log: Option<&AccountLoader<AuditLog>>, entry: AuditEntry) -> Result<()> {
+    if let Some(audit_log) = audit_log {
+        audit_log.load_mut()?.record(entry);
+    }
+    Ok(())
+}
+
+// Drop a policy from its asset's keeper queue when the caller passed it
+fn unlist_policy(keeper_queue: Option<&mut Account<KeeperQueue>>, policy: &Pubkey) {
+    if let Some(keeper_queue) = keeper_queue {
+        keeper_queue.remove(policy);
+    }
+}
+
+// Append to the owner's claim history when the caller passed it
+fn record_claim(claim_history: Option<&AccountLoader<ClaimHistory>>, record: ClaimRecord) -> Result<()> {
+    if let Some(claim_history) = claim_history {
+        claim_history.load_mut()?.record(record);
+    }
+    Ok(())
+}
+
+// Numeric code of a program or Anchor error, as clients see it
+fn error_code_number(error: &Error) -> u32 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number,
+        Error::ProgramError(error) => u64::from(error.program_error.clone()) as u32,
+    }
+}
+
+// A max_coverage_amount of 0 leaves coverage uncapped
+fn within_coverage_cap(config: &Config, coverage_amount: u64) -> bool {
+    config.max_coverage_amount == 0 || coverage_amount <= config.max_coverage_amount
+}
+
+// Book more coverage against an owner, held to max_owner_coverage (0 =
+// uncapped). Like max_coverage_amount it counts coverage_amount as-is
+fn book_owner_coverage(config: &Config, owner_state: &mut OwnerState, amount: u64) -> Result<()> {
+    let active_coverage = owner_state
+        .active_coverage
+        .checked_add(amount)
+        .ok_or(LiqGuardError::MathOverflow)?;
+    require!(
+        config.max_owner_coverage == 0 || active_coverage <= config.max_owner_coverage,
+        LiqGuardError::OwnerCoverageCapExceeded
+    );
+    owner_state.active_coverage = active_coverage;
+    Ok(())
+}
+
+// Bounty owed to whoever cranks a valid liquidation: flat + bps of payout,
+// never more than the payout itself. The flat part is denominated in
+// lamports and so only applies to native-SOL policies
+fn liquidator_bounty(config: &Config, payout: u64, is_native: bool) -> Result<u64> {
+    let flat_part = if is_native { config.bounty_flat_lamports } else { 0 };
+    Ok(payout::liquidator_bounty(payout, config.bounty_bps, flat_part)
+        .ok_or(LiqGuardError::MathOverflow)?)
+}
+
+// The registry's source for the asset, unless the policy is bound to a
+// Chainlink feed. Those settle on it alone, so no Lazer price either
+fn policy_feed_ref(feed: &FeedRegistry, chainlink_feed: Pubkey) -> FeedRef {
+    if chainlink_feed == Pubkey::default() {
+        return feed.feed_ref();
+    }
+    FeedRef {
+        source: OracleSource::Chainlink,
+        feed_id: [0; 32],
+        oracle_account: chainlink_feed,
+        lazer_feed_id: 0,
+    }
+}
+
+// Fresh, confident spot price for an asset under the config-wide limits,
+// for pricing and entry-price snapshots rather than settlement
+fn current_spot_price(
+    config: &Config,
+    feed: &FeedRegistry,
+    price_feed: &AccountInfo,
+    clock: &Clock,
+) -> Result<OraclePrice> {
+    Ok(current_spot_quote(config, feed, &feed.feed_ref(), price_feed, clock)?.price)
+}
+
+fn current_spot_quote(
+    config: &Config,
+    feed: &FeedRegistry,
+    feed_ref: &FeedRef,
+    price_feed: &AccountInfo,
+    clock: &Clock,
+) -> Result<OracleQuote> {
+    oracle::get_price(
+        feed_ref,
+        price_feed,
+        clock,
+        config.default_max_price_age.min(feed.max_price_age),
+        config.max_confidence_bps.min(feed.max_confidence_bps),
+    )
+}
+
+// SOL/USD spot price for valuing USD coverage, from the registered SOL feed
+fn sol_usd_price(
+    config: &Config,
+    feed: Option<&Account<FeedRegistry>>,
+    price_feed: Option<&UncheckedAccount>,
+    clock: &Clock,
+) -> Result<OraclePrice> {
+    let (Some(feed), Some(price_feed)) = (feed, price_feed) else {
+        return err!(LiqGuardError::MissingSolPrice);
+    };
+    require!(feed.enabled, LiqGuardError::FeedDisabled);
+    current_spot_price(config, feed, price_feed, clock)
+}
+
+// Lamports worth `usd` (at STRIKE_EXPONENT) at the given SOL/USD price,
+// rounded down
+fn usd_to_lamports(usd: u64, sol_price: OraclePrice) -> Result<u64> {
+    require!(sol_price.mantissa > 0, LiqGuardError::MissingSolPrice);
+    Ok(liqguard_common::price::usd_to_lamports(usd, sol_price).ok_or(LiqGuardError::MathOverflow)?)
+}
+
+// USDC base units worth `lamports` at the given SOL/USD price, rounded
+// down
+fn lamports_to_usdc(lamports: u64, sol_price: OraclePrice) -> Result<u64> {
+    require!(sol_price.mantissa > 0, LiqGuardError::MissingSolPrice);
+    Ok(liqguard_common::price::lamports_to_usdc(lamports, sol_price).ok_or(LiqGuardError::MathOverflow)?)
+}
+
+// An LST policy's lamport amount in its stake pool's tokens. The pool
+// must still mint the vault's collateral
+fn lst_amount(
+    policy: &Policy,
+    stake_pool: Option<&UncheckedAccount>,
+    clock: &Clock,
+    lamports: u64,
+    round_up: bool,
+) -> Result<u64> {
+    let pool = stake_pool::load_stake_pool(
+        stake_pool.ok_or(LiqGuardError::MissingStakePool)?,
+        clock,
+    )?;
+    require_keys_eq!(
+        pool.pool_mint,
+        policy.collateral_mint,
+        LiqGuardError::InvalidStakePool
+    );
+    pool.pool_tokens_for(lamports, round_up)
+}
+
+// A price truncated onto STRIKE_EXPONENT, for strikes derived on-chain
+fn to_strike_units(price: OraclePrice) -> Result<u64> {
+    Ok(liqguard_common::price::to_strike_units(price).ok_or(LiqGuardError::MathOverflow)?)
+}
+
+// Strikes drawdown_bps below and above an entry price
+fn drawdown_strikes(entry_price: u64, drawdown_bps: u16) -> Result<(u64, u64)> {
+    Ok(payout::drawdown_strikes(entry_price, drawdown_bps).ok_or(LiqGuardError::MathOverflow)?)
+}
+
+// On-chain pricing is on for an asset with any volatility to price off
+fn pricing_enabled(config: &Config, feed: &FeedRegistry) -> bool {
+    feed.volatility_bps > 0 || config.volatility_bps > 0
+}
+
+// Volatility to quote an asset at: the risk oracle's while it is fresh,
+// else the config-wide fallback for assets it hasn't published
+fn asset_volatility(config: &Config, feed: &FeedRegistry, now: i64) -> Result<u32> {
+    if feed.volatility_bps == 0 {
+        return Ok(config.volatility_bps);
+    }
+    require!(
+        config.max_volatility_age == 0
+            || now.saturating_sub(feed.volatility_updated_at) <= config.max_volatility_age,
+        LiqGuardError::VolatilityStale
+    );
+    Ok(feed.volatility_bps)
+}
+
+// Premium for coverage on the given terms at the current oracle price:
+// coverage times the chance the trigger fires (a touch for American
+// policies, finishing past the strike for European ones), plus the
+// configured markup and the utilization curve's at utilization_bps.
+// Payouts never exceed coverage, so this bounds every payout mode;
+// barriers aren't discounted
+fn quote_premium(
+    config: &Config,
+    feed: &FeedRegistry,
+    price_feed: &AccountInfo,
+    params: &QuoteParams,
+    utilization_bps: u64,
+) -> Result<u64> {
+    require!(feed.enabled, LiqGuardError::FeedDisabled);
+    let clock = Clock::get()?;
+    require!(
+        params.expiration_datetime > clock.unix_timestamp,
+        LiqGuardError::InvalidExpiration
+    );
+    let spot_price = current_spot_price(config, feed, price_feed, &clock)?;
+    require!(
+        valid_strike_range(params.direction, params.strike_price, params.upper_strike_price),
+        LiqGuardError::InvalidStrikeRange
+    );
+    let strike = OraclePrice::new(params.strike_price, STRIKE_EXPONENT);
+    let upper_strike = OraclePrice::new(params.upper_strike_price, STRIKE_EXPONENT);
+
+    let volatility_bps = asset_volatility(config, feed, clock.unix_timestamp)?;
+    let seconds = params.expiration_datetime - clock.unix_timestamp;
+    let side_probability = |strike: OraclePrice| -> Result<i128> {
+        let (spot, strike) = align_prices(spot_price, strike)?;
+        let probability = match params.exercise_style {
+            ExerciseStyle::American => {
+                pricing::touch_probability(spot, strike, volatility_bps, seconds)
+            }
+            ExerciseStyle::European => {
+                pricing::finish_probability(spot, strike, volatility_bps, seconds)
+            }
+        };
+        Ok(probability.ok_or(LiqGuardError::MathOverflow)?)
+    };
+    // Already past the strike: the trigger is a certainty. A band pays on
+    // either side; summing the two can only overstate an American touch
+    let probability = if strike_crossed(params.direction, strike, upper_strike, spot_price)? {
+        pricing::SCALE
+    } else if params.direction == TriggerDirection::OutsideRange {
+        (side_probability(strike)? + side_probability(upper_strike)?).min(pricing::SCALE)
+    } else {
+        side_probability(strike)?
+    };
+    let markup_bps = config
+        .premium_markup_bps
+        .saturating_add(pricing::utilization_markup(
+            utilization_bps,
+            config.utilization_kink_bps,
+            config.kink_markup_bps,
+            config.full_utilization_markup_bps,
+        ));
+    let quote = pricing::premium(params.coverage_amount, probability, markup_bps)
+        .ok_or(LiqGuardError::MathOverflow)?;
+    Ok(quote)
+}
+
+// Collateral owed for a triggered policy at current_price.
+// Binary pays full coverage; Proportional pays
+// coverage * slope * |strike - price| / strike, capped at coverage,
+// measured from whichever band edge an OutsideRange price broke;
+// CappedLinear clamps that between the policy's floor and cap; Spread
+// nets its long and short legs into coverage * depth / width between the
+// strike and short_strike_price
+pub fn payout_amount(policy: &Policy, current_price: OraclePrice) -> Result<u64> {
+    let linear_inputs = || align_prices(current_price, breached_strike(policy, current_price)?);
+    let payout = match policy.payout_mode {
+        PayoutMode::Binary => Some(policy.coverage_amount),
+        PayoutMode::Proportional => {
+            let (current_price, strike_price) = linear_inputs()?;
+            payout::linear(
+                policy.coverage_amount,
+                strike_price,
+                current_price,
+                policy.payout_slope_bps,
+            )
+        }
+        PayoutMode::CappedLinear => {
+            let (current_price, strike_price) = linear_inputs()?;
+            payout::capped_linear(
+                policy.coverage_amount,
+                strike_price,
+                current_price,
+                policy.payout_slope_bps,
+                policy.payout_floor_bps,
+                policy.payout_cap_bps,
+            )
+        }
+        PayoutMode::Spread => {
+            let (price, strike_price) = align_prices(current_price, policy.strike())?;
+            let (_, short_strike_price) = align_prices(current_price, policy.short_strike())?;
+            payout::spread(policy.coverage_amount, strike_price, short_strike_price, price)
+        }
+    };
+    Ok(payout.ok_or(LiqGuardError::MathOverflow)?)
+}
+
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+// Settlement attempts kept in the audit log before the oldest is overwritten
+pub const AUDIT_LOG_CAPACITY: usize = 128;
+
+// AuditEntry.decision values
+pub const AUDIT_PAID: u8 = 1;              // liquidated or settled
+pub const AUDIT_DEVIATION_FLAGGED: u8 = 2; // spot/EMA deviation flagged, nothing paid
+pub const AUDIT_TRIGGERABLE: u8 = 3;       // attempt logged, policy could be liquidated
+pub const AUDIT_REJECTED: u8 = 4;          // attempt logged, failed with error_code
+pub const AUDIT_BREACH_OBSERVED: u8 = 5;   // first breach of a dwell policy, nothing paid
+
+// Claims kept in one owner's ClaimHistory
+pub const CLAIM_HISTORY_CAPACITY: usize = 32;
+
+// Bounds (seconds) for a policy's price staleness window
+pub const MIN_PRICE_AGE: u64 = 5;
+pub const MAX_PRICE_AGE: u64 = 600;
+
+// Seconds between requesting a coverage reduction and withdrawing it
+pub const COVERAGE_REDUCTION_COOLDOWN: i64 = 86_400;
+
+// Bounds (seconds) for an auto-roll coverage period
+pub const MIN_ROLL_PERIOD: i64 = 3_600;
+pub const MAX_ROLL_PERIOD: i64 = 365 * 86_400;
+
+// Seconds after expiry during which an auto-roll policy can be rolled
+pub const ROLL_WINDOW: i64 = 3_600;
+
+// Policy slots in one PolicyBook
+pub const POLICY_BOOK_CAPACITY: usize = 2048;
+
+// PolicySlot.status values
+pub const BOOK_SLOT_FREE: u8 = 0;
+pub const BOOK_SLOT_OPEN: u8 = 1;
+
+// Max policies listed in one asset's keeper queue
+pub const KEEPER_QUEUE_CAPACITY: usize = 128;
+
+// Longest a config change can be made to wait
+pub const MAX_CONFIG_CHANGE_DELAY: i64 = 30 * 86_400;
+
+// Highest volatility the risk oracle may publish (1000% annualized)
+pub const MAX_VOLATILITY_BPS: u32 = 100_000;
+
+// Longest a payout holdback can wait out the dispute window
+pub const MAX_DISPUTE_WINDOW: i64 = 7 * 86_400;
+
+// Keepers the config can allowlist for permissioned policies
+pub const MAX_ALLOWLISTED_LIQUIDATORS: usize = 8;
+
+// Seconds a matured deviation flag stays confirmable before it must be re-flagged
+pub const DEVIATION_CONFIRM_WINDOW: i64 = 300;
+
+// Bounds (seconds) a breach must be sustained for on a dwell policy
+pub const MIN_BREACH_DWELL: i64 = 1;
+pub const MAX_BREACH_DWELL: i64 = 3_600;
+
+// Seconds a matured breach observation stays confirmable before it must
+// be observed again
+pub const BREACH_CONFIRM_WINDOW: i64 = 300;
+
+// Bounds (seconds) for an optional TWAP settlement window
+pub const MIN_TWAP_WINDOW: u64 = 60;
+pub const MAX_TWAP_WINDOW: u64 = 86_400;
+
+// European policies snapshot a Pyth price published within this many
+// seconds of expiry, keeping whichever is closest
+pub const SETTLEMENT_PRICE_WINDOW: i64 = 300;
+
+// After the snapshot window closes, a European policy can be settled for
+// this long before its collateral goes back to the underwriter
+pub const EUROPEAN_SETTLEMENT_PERIOD: i64 = 86_400;
+
+// Bounds (seconds) on how long a tranched payout may stream over
+pub const MIN_VESTING_PERIOD: i64 = 3_600;
+pub const MAX_VESTING_PERIOD: i64 = 90 * 86_400;
+
+// Recipients a policy's payout can be split between
+pub const MAX_PAYOUT_SPLITS: usize = 4;
+
+// Seconds after expiry before an unclosed native policy counts as
+// abandoned and its vault can be swept
+pub const ABANDONED_VAULT_GRACE_PERIOD: i64 = 365 * 86_400;
+
+// Policy layout version written at open, by migrate_policy_v2 and by
+// extend_account. 3 added payout_splits, 4 master_agreement, 5
+// compliance_mint, 6 the breach dwell, 7 short_strike_price
+pub const POLICY_VERSION: u8 = 7;
+
+#[account]
+pub struct Config {
+    pub authority: Pubkey,
+    pub paused: bool,            // blocks opening, funding and settling policies
+    pub max_confidence_bps: u16, // max conf / price accepted at settlement
+    pub bounty_bps: u16,         // liquidator bounty as bps of coverage
+    pub bounty_flat_lamports: u64, // flat liquidator bounty on top of bounty_bps
+    pub treasury: Pubkey,        // protocol treasury
+    pub protocol_fee_bps: u16,   // treasury cut of each premium
+    pub default_max_price_age: u64, // used by policies opened with max_price_age = 0
+    pub max_coverage_amount: u64, // per-policy coverage cap, 0 = uncapped
+    pub max_owner_coverage: u64, // cap on one owner's total open coverage, 0 = uncapped
+    pub strike_update_delay: i64, // seconds between requesting and applying a strike change
+    pub min_policy_age: i64,     // seconds after creation before a policy can pay out
+    pub max_price_deviation_bps: u16, // spot vs EMA deviation that needs confirming, 0 = off
+    pub deviation_confirm_delay: i64, // seconds before a flagged deviation can be confirmed
+    pub volatility_bps: u32,     // annualized vol for assets without their own, 0 = none
+    pub premium_markup_bps: u16, // margin charged over the fair premium
+    pub backstop_fee_bps: u16,   // backstop fund cut of each premium
+    pub max_backstop_draw: u64,  // most lamports one claim may draw from the backstop
+    pub liquidator_allowlist: [Pubkey; MAX_ALLOWLISTED_LIQUIDATORS], // keepers for permissioned policies, default = empty slot
+    pub config_change_delay: i64, // seconds a proposed config change waits before it applies
+    pub wormhole_emitter_chain: u16, // Wormhole chain id of the EVM premium collector
+    pub wormhole_emitter_address: [u8; 32], // collector's emitter address, zero = cross-chain premiums off
+    pub arbiter: Pubkey,         // resolves disputed liquidations, default = disputes off
+    pub dispute_window: i64,     // seconds a liquidation can be disputed for
+    pub dispute_holdback_bps: u16, // share of each native payout held back for the window
+    pub payout_fee_bps: u16,     // treasury PDA cut of each native payout
+    pub risk_oracle: Pubkey,     // key that publishes per-asset volatility
+    pub max_volatility_age: i64, // seconds a published volatility stays usable, 0 = no limit
+    pub usdc_mint: Pubkey,       // mint swapped payouts are delivered in, default = swaps off
+    pub max_swap_slippage_bps: u16, // most a swapped payout may fall short of the oracle value
+    pub compliance_mint: Pubkey, // credential every payout wallet must hold, default = ungated
+    pub utilization_kink_bps: u16, // portfolio utilization where the markup curve steepens
+    pub kink_markup_bps: u16,    // extra markup at the kink
+    pub full_utilization_markup_bps: u16, // extra markup at 100% utilization, 0 with kink_markup_bps = curve off
+    pub bump: u8,
+}
+
+impl Config {
+    pub const LEN: usize = 32 + 1 + 2 + 2 + 8 + 32 + 2 + 8 + 8 + 8 + 8 + 8 + 2 + 8 + 4 + 2 + 2 + 8 + 32 * MAX_ALLOWLISTED_LIQUIDATORS + 8 + 2 + 32 + 32 + 8 + 2 + 2 + 32 + 8 + 32 + 2 + 32 + 2 + 2 + 2 + 1; // authority + paused + max_confidence_bps + bounty_bps + bounty_flat_lamports + treasury + protocol_fee_bps + default_max_price_age + max_coverage_amount + max_owner_coverage + strike_update_delay + min_policy_age + max_price_deviation_bps + deviation_confirm_delay + volatility_bps + premium_markup_bps + backstop_fee_bps + max_backstop_draw + liquidator_allowlist + config_change_delay + wormhole_emitter_chain + wormhole_emitter_address + arbiter + dispute_window + dispute_holdback_bps + payout_fee_bps + risk_oracle + max_volatility_age + usdc_mint + max_swap_slippage_bps + compliance_mint + utilization_kink_bps + kink_markup_bps + full_utilization_markup_bps + bump
+
+    fn apply(&mut self, params: &ConfigParams) -> Result<()> {
+        Self::validate(params)?;
+        self.max_confidence_bps = params.max_confidence_bps;
+        self.bounty_bps = params.bounty_bps;
+        self.bounty_flat_lamports = params.bounty_flat_lamports;
+        self.treasury = params.treasury;
+        self.protocol_fee_bps = params.protocol_fee_bps;
+        self.default_max_price_age = params.default_max_price_age;
+        self.max_coverage_amount = params.max_coverage_amount;
+        self.max_owner_coverage = params.max_owner_coverage;
+        self.strike_update_delay = params.strike_update_delay;
+        self.min_policy_age = params.min_policy_age;
+        self.max_price_deviation_bps = params.max_price_deviation_bps;
+        self.deviation_confirm_delay = params.deviation_confirm_delay;
+        self.volatility_bps = params.volatility_bps;
+        self.premium_markup_bps = params.premium_markup_bps;
+        self.backstop_fee_bps = params.backstop_fee_bps;
+        self.max_backstop_draw = params.max_backstop_draw;
+        self.liquidator_allowlist = params.liquidator_allowlist;
+        self.config_change_delay = params.config_change_delay;
+        self.wormhole_emitter_chain = params.wormhole_emitter_chain;
+        self.wormhole_emitter_address = params.wormhole_emitter_address;
+        self.arbiter = params.arbiter;
+        self.dispute_window = params.dispute_window;
+        self.dispute_holdback_bps = params.dispute_holdback_bps;
+        self.payout_fee_bps = params.payout_fee_bps;
+        self.risk_oracle = params.risk_oracle;
+        self.max_volatility_age = params.max_volatility_age;
+        self.usdc_mint = params.usdc_mint;
+        self.max_swap_slippage_bps = params.max_swap_slippage_bps;
+        self.compliance_mint = params.compliance_mint;
+        self.utilization_kink_bps = params.utilization_kink_bps;
+        self.kink_markup_bps = params.kink_markup_bps;
+        self.full_utilization_markup_bps = params.full_utilization_markup_bps;
+        Ok(())
+    }
+
+    fn validate(params: &ConfigParams) -> Result<()> {
+        require!(
+            params.max_confidence_bps > 0 && params.max_confidence_bps <= BPS_DENOMINATOR,
+            LiqGuardError::InvalidConfidenceThreshold
+        );
+        require!(params.bounty_bps <= BPS_DENOMINATOR, LiqGuardError::InvalidBounty);
+        require!(
+            params.protocol_fee_bps as u32 + params.backstop_fee_bps as u32 <= BPS_DENOMINATOR as u32
+                && params.payout_fee_bps <= BPS_DENOMINATOR,
+            LiqGuardError::InvalidProtocolFee
+        );
+        require!(
+            (MIN_PRICE_AGE..=MAX_PRICE_AGE).contains(&params.default_max_price_age),
+            LiqGuardError::InvalidMaxPriceAge
+        );
+        require!(
+            params.strike_update_delay >= 0,
+            LiqGuardError::InvalidStrikeUpdateDelay
+        );
+        require!(params.min_policy_age >= 0, LiqGuardError::InvalidMinPolicyAge);
+        require!(
+            params.max_price_deviation_bps <= BPS_DENOMINATOR && params.deviation_confirm_delay >= 0,
+            LiqGuardError::InvalidCircuitBreaker
+        );
+        require!(
+            (0..=MAX_CONFIG_CHANGE_DELAY).contains(&params.config_change_delay),
+            LiqGuardError::InvalidConfigChangeDelay
+        );
+        // A holdback needs someone to resolve disputes over it and a window
+        // to raise them in
+        require!(
+            params.dispute_holdback_bps <= BPS_DENOMINATOR
+                && (0..=MAX_DISPUTE_WINDOW).contains(&params.dispute_window)
+                && (params.dispute_holdback_bps == 0
+                    || (params.arbiter != Pubkey::default() && params.dispute_window > 0)),
+            LiqGuardError::InvalidDisputeConfig
+        );
+        require!(params.max_volatility_age >= 0, LiqGuardError::InvalidVolatility);
+        require!(
+            params.max_swap_slippage_bps <= BPS_DENOMINATOR,
+            LiqGuardError::InvalidSwapPayout
+        );
+        // The markup can only rise with utilization
+        require!(
+            params.utilization_kink_bps <= BPS_DENOMINATOR
+                && params.kink_markup_bps <= params.full_utilization_markup_bps,
+            LiqGuardError::InvalidUtilizationCurve
+        );
+        Ok(())
+    }
+
+    pub fn is_allowlisted_liquidator(&self, liquidator: &Pubkey) -> bool {
+        *liquidator != Pubkey::default() && self.liquidator_allowlist.contains(liquidator)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfigParams {
+    pub max_confidence_bps: u16,
+    pub bounty_bps: u16,
+    pub bounty_flat_lamports: u64,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub default_max_price_age: u64,
+    pub max_coverage_amount: u64,
+    pub max_owner_coverage: u64,
+    pub strike_update_delay: i64,
+    pub min_policy_age: i64,
+    pub max_price_deviation_bps: u16,
+    pub deviation_confirm_delay: i64,
+    pub volatility_bps: u32,
+    pub premium_markup_bps: u16,
+    pub backstop_fee_bps: u16,
+    pub max_backstop_draw: u64,
+    pub liquidator_allowlist: [Pubkey; MAX_ALLOWLISTED_LIQUIDATORS],
+    pub config_change_delay: i64,
+    pub wormhole_emitter_chain: u16,
+    pub wormhole_emitter_address: [u8; 32],
+    pub arbiter: Pubkey,
+    pub dispute_window: i64,
+    pub dispute_holdback_bps: u16,
+    pub payout_fee_bps: u16,
+    pub risk_oracle: Pubkey,
+    pub max_volatility_age: i64,
+    pub usdc_mint: Pubkey,
+    pub max_swap_slippage_bps: u16,
+    pub compliance_mint: Pubkey,
+    pub utilization_kink_bps: u16,
+    pub kink_markup_bps: u16,
+    pub full_utilization_markup_bps: u16,
+}
+
+impl ConfigParams {
+    pub const LEN: usize = 2 + 2 + 8 + 32 + 2 + 8 + 8 + 8 + 8 + 8 + 2 + 8 + 4 + 2 + 2 + 8 + 32 * MAX_ALLOWLISTED_LIQUIDATORS + 8 + 2 + 32 + 32 + 8 + 2 + 2 + 32 + 8 + 32 + 2 + 32 + 2 + 2 + 2; // max_confidence_bps + bounty_bps + bounty_flat_lamports + treasury + protocol_fee_bps + default_max_price_age + max_coverage_amount + max_owner_coverage + strike_update_delay + min_policy_age + max_price_deviation_bps + deviation_confirm_delay + volatility_bps + premium_markup_bps + backstop_fee_bps + max_backstop_draw + liquidator_allowlist + config_change_delay + wormhole_emitter_chain + wormhole_emitter_address + arbiter + dispute_window + dispute_holdback_bps + payout_fee_bps + risk_oracle + max_volatility_age + usdc_mint + max_swap_slippage_bps + compliance_mint + utilization_kink_bps + kink_markup_bps + full_utilization_markup_bps
+}
+
+// Config parameters waiting out the change delay
+#[account]
+pub struct PendingConfigChange {
+    pub params: ConfigParams,
+    pub proposed_at: i64,
+    pub executable_at: i64, // unix timestamp the change can be applied from
+    pub bump: u8,
+}
+
+impl PendingConfigChange {
+    pub const LEN: usize = ConfigParams::LEN + 8 + 8 + 1; // params + proposed_at + executable_at + bump
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Config::LEN,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        constraint = DEPLOY_AUTHORITY.unwrap_or(authority.key()) == authority.key() @ LiqGuardError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeConfigChange<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PendingConfigChange::LEN,
+        seeds = [PENDING_CONFIG_SEED],
+        bump
+    )]
+    pub pending_config_change: Account<'info, PendingConfigChange>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [PENDING_CONFIG_SEED],
+        bump = pending_config_change.bump,
+        close = authority
+    )]
+    pub pending_config_change: Account<'info, PendingConfigChange>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct Backstop {
+    pub total_contributed: u64, // premium fees and repayments received
+    pub total_drawn: u64,       // lamports paid into short vaults
+    pub bump: u8,
+}
+
+impl Backstop {
+    pub const LEN: usize = 8 + 8 + 1; // total_contributed + total_drawn + bump
+}
+
+// Program-owned pot of payout fees, withdrawn by the config authority
+#[account]
+pub struct Treasury {
+    pub total_collected: u64, // payout fees received
+    pub total_withdrawn: u64,
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub const LEN: usize = 8 + 8 + 1; // total_collected + total_withdrawn + bump
+}
+
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Treasury::LEN,
+        seeds = [TREASURY_SEED],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: Any account the authority sends the fees to
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+}
+
+// Backstop draws made on behalf of an underwriter's vaults
+#[account]
+pub struct UnderwriterRecord {
+    pub underwriter: Pubkey,
+    pub backstop_debt: u64, // lamports owed back to the backstop
+    pub bump: u8,
+}
+
+impl UnderwriterRecord {
+    pub const LEN: usize = 32 + 8 + 1; // underwriter + backstop_debt + bump
+}
+
+#[derive(Accounts)]
+pub struct InitializeBackstop<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Backstop::LEN,
+        seeds = [BACKSTOP_SEED],
+        bump
+    )]
+    pub backstop: Account<'info, Backstop>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RepayBackstop<'info> {
+    #[account(
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Account<'info, Backstop>,
+
+    #[account(
+        mut,
+        seeds = [UNDERWRITER_SEED, underwriter.key().as_ref()],
+        bump = underwriter_record.bump,
+        has_one = underwriter
+    )]
+    pub underwriter_record: Account<'info, UnderwriterRecord>,
+
+    #[account(mut)]
+    pub underwriter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// An underwriter's shared collateral for portfolio-backed policies. It
+// holds lamports directly; reserved_exposure is the sum of its open
+// policies' worst-case payouts still outstanding
+#[account]
+pub struct PortfolioVault {
+    pub underwriter: Pubkey,
+    pub reserved_exposure: u64, // lamports that can't be withdrawn
+    pub bump: u8,
+}
+
+impl PortfolioVault {
+    pub const LEN: usize = 32 + 8 + 1; // underwriter + reserved_exposure + bump
+}
+
+#[derive(Accounts)]
+pub struct InitializePortfolioVault<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PortfolioVault::LEN,
+        seeds = [PORTFOLIO_VAULT_SEED, underwriter.key().as_ref()],
+        bump
+    )]
+    pub portfolio_vault: Account<'info, PortfolioVault>,
+
+    pub underwriter: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// A counterparty's program-level terms with one underwriter. Member
+// policies share aggregate_limit, in their coverage units, and expire by
+// expiration_datetime
+#[account]
+pub struct MasterAgreement {
+    pub counterparty: Pubkey,     // owns every member policy
+    pub underwriter: Pubkey,      // underwrites every member policy
+    pub agreement_id: u64,        // index into the counterparty's agreements
+    pub collateral_mint: Pubkey,  // members' collateral, default = SOL
+    pub usd_coverage: bool,       // members' coverage is in USD
+    pub aggregate_limit: u64,     // most the members' claims may total
+    pub aggregate_paid: u64,      // claimed by members so far
+    pub expiration_datetime: i64, // members expire by this
+    pub member_count: u32,
+    pub bump: u8,
+}
+
+impl MasterAgreement {
+    pub const LEN: usize = 32 + 32 + 8 + 32 + 1 + 8 + 8 + 8 + 4 + 1; // counterparty + underwriter + agreement_id + collateral_mint + usd_coverage + aggregate_limit + aggregate_paid + expiration_datetime + member_count + bump
+
+    // Book a member's claim against the aggregate, cut down to what's
+    // left of it. Returns the amount booked
+    pub fn draw(&mut self, amount: u64) -> u64 {
+        let drawn = amount.min(self.aggregate_limit.saturating_sub(self.aggregate_paid));
+        self.aggregate_paid += drawn;
+        drawn
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MasterAgreementParams {
+    pub agreement_id: u64,
+    pub collateral_mint: Pubkey,
+    pub usd_coverage: bool,
+    pub aggregate_limit: u64,
+    pub expiration_datetime: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: MasterAgreementParams)]
+pub struct InitializeMasterAgreement<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MasterAgreement::LEN,
+        seeds = [
+            MASTER_AGREEMENT_SEED,
+            counterparty.key().as_ref(),
+            &params.agreement_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub master_agreement: Account<'info, MasterAgreement>,
+
+    pub counterparty: Signer<'info>,
+
+    pub underwriter: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// A buyer's open request for coverage, escrowing up to max_premium in
+// its own lamports until an underwriter fills it or the buyer cancels
+#[account]
+pub struct CoverageRequest {
+    pub owner: Pubkey,            // buyer; owns the policy a fill opens
+    pub request_id: u64,          // index into the owner's requests
+    pub asset: UnderlyingAsset,
+    pub strike_price: u64,        // USD at STRIKE_EXPONENT
+    pub direction: TriggerDirection,
+    pub upper_strike_price: u64,  // OutsideRange only, else 0
+    pub coverage_amount: u64,     // lamports
+    pub expiration_datetime: i64,
+    pub max_premium: u64,         // lamports escrowed; a fill may charge up to this
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl CoverageRequest {
+    pub const LEN: usize = 32 + 8 + 1 + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1; // owner + request_id + asset + strike_price + direction + upper_strike_price + coverage_amount + expiration_datetime + max_premium + created_at + bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CoverageRequestParams {
+    pub request_id: u64,
+    pub asset: UnderlyingAsset,
+    pub strike_price: u64,
+    pub direction: TriggerDirection,
+    pub upper_strike_price: u64,
+    pub coverage_amount: u64,
+    pub expiration_datetime: i64,
+    pub max_premium: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CoverageRequestParams)]
+pub struct PostCoverageRequest<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CoverageRequest::LEN,
+        seeds = [COVERAGE_REQUEST_SEED, owner.key().as_ref(), &params.request_id.to_le_bytes()],
+        bump
+    )]
+    pub coverage_request: Account<'info, CoverageRequest>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    // Escrows the premium
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelCoverageRequest<'info> {
+    #[account(
+        mut,
+        seeds = [
+            COVERAGE_REQUEST_SEED,
+            coverage_request.owner.as_ref(),
+            &coverage_request.request_id.to_le_bytes()
+        ],
+        bump = coverage_request.bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub coverage_request: Account<'info, CoverageRequest>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(policy_id: u64)]
+pub struct FillCoverageRequest<'info> {
+    #[account(
+        mut,
+        seeds = [
+            COVERAGE_REQUEST_SEED,
+            coverage_request.owner.as_ref(),
+            &coverage_request.request_id.to_le_bytes()
+        ],
+        bump = coverage_request.bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub coverage_request: Account<'info, CoverageRequest>,
+
+    #[account(
+        init_if_needed,
+        payer = underwriter,
+        space = 8 + OwnerState::LEN,
+        seeds = [OWNER_STATE_SEED, owner.key().as_ref()],
+        bump,
+        constraint = owner_state.policy_count == policy_id @ LiqGuardError::InvalidPolicyId
+    )]
+    pub owner_state: Account<'info, OwnerState>,
+
+    #[account(
+        init,
+        payer = underwriter,
+        space = 8 + Policy::LEN,
+        seeds = [POLICY_SEED, owner.key().as_ref(), &policy_id.to_le_bytes()],
+        bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, coverage_request.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Account<'info, KeeperQueue>,
+
+    /// Lamport-only PDA; the fill funds it with the coverage
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, owner.key().as_ref(), &policy_id.to_le_bytes()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, coverage_request.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Price account for the entry price and quote; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the protocol fee; must match config.treasury
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LiqGuardError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Receives the backstop fee, required while backstop_fee_bps > 0
+    #[account(
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Option<Account<'info, Backstop>>,
+
+    /// CHECK: The requester; gets the unspent escrow and the request's
+    /// rent back
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    // Funds the vault, pays the rent and earns the premium
+    #[account(
+        mut,
+        constraint = underwriter.key() != owner.key() @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinMasterAgreement<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [
+            MASTER_AGREEMENT_SEED,
+            master_agreement.counterparty.as_ref(),
+            &master_agreement.agreement_id.to_le_bytes()
+        ],
+        bump = master_agreement.bump,
+        constraint = master_agreement.counterparty == owner.key() @ LiqGuardError::InvalidMasterAgreement
+    )]
+    pub master_agreement: Account<'info, MasterAgreement>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManagePortfolioVault<'info> {
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_VAULT_SEED, underwriter.key().as_ref()],
+        bump = portfolio_vault.bump,
+        has_one = underwriter
+    )]
+    pub portfolio_vault: Account<'info, PortfolioVault>,
+
+    #[account(mut)]
+    pub underwriter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct FeedRegistry {
+    pub asset: UnderlyingAsset,
+    pub symbol: [u8; 16],        // display name, NUL-padded (e.g. "BTC/USD")
+    pub feed_id: [u8; 32],       // Pyth price feed id, for the PythPull source
+    pub max_price_age: u64,      // cap on any policy's staleness window
+    pub max_confidence_bps: u16, // cap on conf / price for this feed
+    pub enabled: bool,           // disabled feeds block new policies and settlement
+    pub bump: u8,
+    pub oracle_source: OracleSource, // backend settlement reads this asset from
+    pub oracle_account: Pubkey,  // feed account for account-based sources, else default
+    pub volatility_bps: u32,     // annualized vol from the risk oracle, 0 = use Config's
+    pub volatility_updated_at: i64, // unix timestamp volatility_bps was published at
+    pub lazer_feed_id: u32,      // Pyth Lazer feed id, 0 = Lazer prices not accepted
+}
+
+impl FeedRegistry {
+    pub const LEN: usize = 1 + 16 + 32 + 8 + 2 + 1 + 1 + 1 + 32 + 4 + 8 + 4; // asset + symbol + feed_id + max_price_age + max_confidence_bps + enabled + bump + oracle_source + oracle_account + volatility_bps + volatility_updated_at + lazer_feed_id
+
+    pub fn feed_ref(&self) -> FeedRef {
+        FeedRef {
+            source: self.oracle_source,
+            feed_id: self.feed_id,
+            oracle_account: self.oracle_account,
+            lazer_feed_id: self.lazer_feed_id,
+        }
+    }
+
+    fn apply(&mut self, params: &FeedRegistryParams) -> Result<()> {
+        require!(
+            (MIN_PRICE_AGE..=MAX_PRICE_AGE).contains(&params.max_price_age),
+            LiqGuardError::InvalidMaxPriceAge
+        );
+        require!(
+            params.max_confidence_bps > 0 && params.max_confidence_bps <= BPS_DENOMINATOR,
+            LiqGuardError::InvalidConfidenceThreshold
+        );
+        // Pyth needs the feed id; account-based sources need their account
+        require!(
+            match params.oracle_source {
+                OracleSource::PythPull => params.feed_id != [0u8; 32],
+                OracleSource::Switchboard | OracleSource::Chainlink => {
+                    params.oracle_account != Pubkey::default()
+                }
+            },
+            LiqGuardError::InvalidFeedId
+        );
+
+        self.symbol = params.symbol;
+        self.feed_id = params.feed_id;
+        self.max_price_age = params.max_price_age;
+        self.max_confidence_bps = params.max_confidence_bps;
+        self.enabled = params.enabled;
+        self.oracle_source = params.oracle_source;
+        self.oracle_account = params.oracle_account;
+        self.lazer_feed_id = params.lazer_feed_id;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeedRegistryParams {
+    pub symbol: [u8; 16],
+    pub feed_id: [u8; 32],
+    pub max_price_age: u64,
+    pub max_confidence_bps: u16,
+    pub enabled: bool,
+    pub oracle_source: OracleSource,
+    pub oracle_account: Pubkey,
+    pub lazer_feed_id: u32,
+}
+
+#[derive(Accounts)]
+#[instruction(asset: UnderlyingAsset)]
+pub struct RegisterFeed<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + FeedRegistry::LEN,
+        seeds = [FEED_REGISTRY_SEED, asset.seed().as_ref()],
+        bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeed<'info> {
+    #[account(
+        mut,
+        seeds = [FEED_REGISTRY_SEED, feed_registry.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVolatility<'info> {
+    #[account(
+        mut,
+        seeds = [FEED_REGISTRY_SEED, feed_registry.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = risk_oracle
+    )]
+    pub config: Account<'info, Config>,
+
+    pub risk_oracle: Signer<'info>,
+}
+
+// Latest verified Pyth Lazer price for an asset, written by post_lazer_price
+#[account]
+pub struct LazerPrice {
+    pub asset: UnderlyingAsset,
+    pub lazer_feed_id: u32,      // feed the price was read from
+    pub price: i64,
+    pub exponent: i32,
+    pub confidence: u64,
+    pub timestamp_us: u64,       // Lazer publish time, microseconds
+    pub bump: u8,
+}
+
+impl LazerPrice {
+    pub const LEN: usize = 1 + 4 + 8 + 4 + 8 + 8 + 1; // asset + lazer_feed_id + price + exponent + confidence + timestamp_us + bump
+}
+
+#[derive(Accounts)]
+pub struct PostLazerPrice<'info> {
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + LazerPrice::LEN,
+        seeds = [LAZER_PRICE_SEED, feed_registry.asset.seed().as_ref()],
+        bump
+    )]
+    pub lazer_price: Account<'info, LazerPrice>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, feed_registry.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    // Pays the Lazer update fee and the price account's rent
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    /// CHECK: The Pyth Lazer verifier program
+    #[account(address = lazer::PYTH_LAZER_PROGRAM_ID)]
+    pub lazer_program: UncheckedAccount<'info>,
+
+    /// CHECK: Lazer's storage account of trusted signers; validated by the
+    /// Lazer program
+    pub lazer_storage: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the Lazer update fee; the Lazer program checks it
+    /// against its storage
+    #[account(mut)]
+    pub lazer_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, read by Lazer to find the ed25519
+    /// signature check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// An asset's oracle price as push_price last validated it, for batches
+// settling in the same slot
+#[account]
+pub struct PriceCache {
+    pub asset: UnderlyingAsset,
+    pub price: i128,
+    pub exponent: i32,
+    pub confidence: u128,        // in price's exponent
+    pub has_ema: bool,
+    pub ema_price: i128,         // only read when has_ema
+    pub ema_exponent: i32,
+    pub publish_time: i64,
+    pub slot: u64,               // slot the price was pushed in
+    pub bump: u8,
+}
+
+impl PriceCache {
+    pub const LEN: usize = 1 + 16 + 4 + 16 + 1 + 16 + 4 + 8 + 8 + 1; // asset + price + exponent + confidence + has_ema + ema_price + ema_exponent + publish_time + slot + bump
+
+    pub fn quote(&self) -> oracle::OracleQuote {
+        oracle::OracleQuote {
+            price: OraclePrice::new(self.price, self.exponent),
+            publish_time: self.publish_time,
+            confidence: self.confidence,
+            ema_price: self
+                .has_ema
+                .then(|| OraclePrice::new(self.ema_price, self.ema_exponent)),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct PushPrice<'info> {
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + PriceCache::LEN,
+        seeds = [PRICE_CACHE_SEED, feed_registry.asset.seed().as_ref()],
+        bump
+    )]
+    pub price_cache: Account<'info, PriceCache>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, feed_registry.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    // Pays the cache account's rent on first push
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// One strike-triggered policy in a keeper queue
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct KeeperEntry {
+    pub policy: Pubkey,
+    pub strike_price: u64,
+    pub coverage_amount: u64,
+    pub direction: TriggerDirection,
+    pub upper_strike_price: u64, // OutsideRange only
+}
+
+impl KeeperEntry {
+    pub const LEN: usize = 32 + 8 + 8 + 1 + 8; // policy + strike_price + coverage_amount + direction + upper_strike_price
+}
+
+// Live strike-triggered policies for one asset, sorted by strike so a keeper
+// can fetch this single account and scan outward from the current price.
+// Listing is a convenience for keepers: unlisted policies (e.g. once the
+// queue is full) can still be liquidated. Every transaction that writes the
+// queue takes its write lock, so they run one at a time per asset. Funding,
+// top-ups and strike changes need it, and fills and portfolio-backed opens
+// list through it. Settlement and exit paths take it optionally, so a
+// crash-time liquidation can leave it out and let prune_keeper_queue drop
+// the entry later
+#[account]
+pub struct KeeperQueue {
+    pub asset: UnderlyingAsset,
+    pub entries: Vec<KeeperEntry>, // ascending strike_price, at most KEEPER_QUEUE_CAPACITY
+    pub bump: u8,
+}
+
+impl KeeperQueue {
+    pub const LEN: usize = 1 + 4 + KEEPER_QUEUE_CAPACITY * KeeperEntry::LEN + 1; // asset + entries + bump
+
+    // Insert or re-sort a policy once it is funded or its strike/coverage
+    // moves. Unfunded policies aren't listed, so opening throwaway policies
+    // can't fill the queue
+    fn track(&mut self, key: Pubkey, policy: &Policy) {
+        self.remove(&key);
+        if !policy.is_funded || policy.kamino_obligation != Pubkey::default() {
+            return;
+        }
+        if self.entries.len() >= KEEPER_QUEUE_CAPACITY {
+            msg!("Keeper queue full, policy not listed: {}", key);
+            return;
+        }
+        let index = self
+            .entries
+            .partition_point(|entry| entry.strike_price <= policy.strike_price);
+        self.entries.insert(
+            index,
+            KeeperEntry {
+                policy: key,
+                strike_price: policy.strike_price,
+                coverage_amount: policy.coverage_amount,
+                direction: policy.direction,
+                upper_strike_price: policy.upper_strike_price,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &Pubkey) {
+        self.entries.retain(|entry| entry.policy != *key);
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(asset: UnderlyingAsset)]
+pub struct InitializeKeeperQueue<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + KeeperQueue::LEN,
+        seeds = [KEEPER_QUEUE_SEED, asset.seed().as_ref()],
+        bump
+    )]
+    pub keeper_queue: Account<'info, KeeperQueue>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PruneKeeperQueue<'info> {
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, keeper_queue.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Account<'info, KeeperQueue>,
+}
+
+// One settlement attempt. Prices are clamped into an i64 mantissa
+#[zero_copy]
+pub struct AuditEntry {
+    pub policy: Pubkey,
+    pub price: i64,          // oracle price mantissa, 0 if it couldn't be read
+    pub slot: u64,
+    pub timestamp: i64,
+    pub error_code: u32,     // 0 unless the attempt was rejected
+    pub price_exponent: i32,
+    pub decision: u8,        // AUDIT_PAID / AUDIT_DEVIATION_FLAGGED / ...
+    pub _padding: [u8; 7],
+}
+
+impl AuditEntry {
+    fn new(policy: Pubkey, price: OraclePrice, decision: u8, error_code: u32, clock: &Clock) -> Self {
+        AuditEntry {
+            policy,
+            price: price.mantissa.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+            error_code,
+            price_exponent: price.exponent,
+            decision,
+            _padding: [0; 7],
+        }
+    }
+}
+
+// Ring buffer of the last AUDIT_LOG_CAPACITY settlement attempts, for
+// post-incident forensics that don't depend on an RPC having kept the
+// transactions
+#[account(zero_copy)]
+pub struct AuditLog {
+    pub total_entries: u64, // entries ever written
+    pub next_index: u32,    // slot the next entry overwrites
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    pub entries: [AuditEntry; AUDIT_LOG_CAPACITY],
+}
+
+impl AuditLog {
+    pub const LEN: usize = 8 + 4 + 1 + 3 + AUDIT_LOG_CAPACITY * 72; // total_entries + next_index + bump + padding + entries
+
+    fn record(&mut self, entry: AuditEntry) {
+        self.entries[self.next_index as usize] = entry;
+        self.next_index = (self.next_index + 1) % AUDIT_LOG_CAPACITY as u32;
+        self.total_entries = self.total_entries.saturating_add(1);
+    }
+}
+
+// One paid claim. Prices are clamped into an i64 mantissa like AuditEntry's
+#[zero_copy]
+pub struct ClaimRecord {
+    pub policy: Pubkey,
+    pub price: i64,          // settlement price mantissa
+    pub amount: u64,         // paid to the beneficiary, in the policy's collateral units
+    pub timestamp: i64,
+    pub price_exponent: i32,
+    pub _padding: [u8; 4],
+}
+
+impl ClaimRecord {
+    fn new(policy: Pubkey, price: OraclePrice, amount: u64, timestamp: i64) -> Self {
+        ClaimRecord {
+            policy,
+            price: price.mantissa.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+            amount,
+            timestamp,
+            price_exponent: price.exponent,
+            _padding: [0; 4],
+        }
+    }
+}
+
+// Ring buffer of an owner's last CLAIM_HISTORY_CAPACITY claims, plus
+// lifetime totals that survive the wraparound
+#[account(zero_copy)]
+pub struct ClaimHistory {
+    pub owner: Pubkey,
+    pub total_claims: u64,  // claims ever recorded
+    pub total_paid: u64,    // summed amounts, across collateral units
+    pub next_index: u32,    // slot the next record overwrites
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    pub records: [ClaimRecord; CLAIM_HISTORY_CAPACITY],
+}
+
+impl ClaimHistory {
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 1 + 3 + CLAIM_HISTORY_CAPACITY * 64; // owner + total_claims + total_paid + next_index + bump + padding + records
+
+    fn record(&mut self, record: ClaimRecord) {
+        self.total_paid = self.total_paid.saturating_add(record.amount);
+        self.records[self.next_index as usize] = record;
+        self.next_index = (self.next_index + 1) % CLAIM_HISTORY_CAPACITY as u32;
+        self.total_claims = self.total_claims.saturating_add(1);
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeClaimHistory<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ClaimHistory::LEN,
+        seeds = [CLAIM_HISTORY_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub claim_history: AccountLoader<'info, ClaimHistory>,
+
+    pub owner: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAuditLog<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AuditLog::LEN,
+        seeds = [AUDIT_LOG_SEED],
+        bump
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordSettlementAttempt<'info> {
+    #[account(
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, policy.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// Pyth TWAP update, required for policies with a TWAP window
+    pub twap_update: Option<Account<'info, TwapUpdate>>,
+
+    /// CHECK: Switchboard pull feed for dual-oracle policies; owner and
+    /// layout are verified when it is read
+    #[account(
+        constraint = switchboard_feed.key() == policy.switchboard_feed
+            @ LiqGuardError::InvalidSwitchboardFeed
+    )]
+    pub switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Drift user for Drift-verified policies; owner and layout are
+    /// verified when it is read
+    #[account(
+        constraint = drift_user.key() == policy.drift_user @ LiqGuardError::InvalidDriftUser
+    )]
+    pub drift_user: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Kamino obligation for health-triggered policies; owner and
+    /// layout are verified when it is read
+    #[account(
+        constraint = kamino_obligation.key() == policy.kamino_obligation
+            @ LiqGuardError::InvalidObligation
+    )]
+    pub kamino_obligation: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.load()?.bump
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct CheckLiquidationEligibility<'info> {
+    #[account(
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, policy.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// Pyth TWAP update, required for policies with a TWAP window
+    pub twap_update: Option<Account<'info, TwapUpdate>>,
+
+    /// CHECK: Switchboard pull feed for dual-oracle policies; owner and
+    /// layout are verified when it is read
+    #[account(
+        constraint = switchboard_feed.key() == policy.switchboard_feed
+            @ LiqGuardError::InvalidSwitchboardFeed
+    )]
+    pub switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Drift user for Drift-verified policies; owner and layout are
+    /// verified when it is read
+    #[account(
+        constraint = drift_user.key() == policy.drift_user @ LiqGuardError::InvalidDriftUser
+    )]
+    pub drift_user: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Kamino obligation for health-triggered policies; owner and
+    /// layout are verified when it is read
+    #[account(
+        constraint = kamino_obligation.key() == policy.kamino_obligation
+            @ LiqGuardError::InvalidObligation
+    )]
+    pub kamino_obligation: Option<UncheckedAccount<'info>>,
+}
+
+// check_liquidation_eligibility's answer. price is the spot price read, at
+// price_exponent; reason is the error code liquidate_policy would fail
+// with, 0 when eligible
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LiquidationEligibility {
+    pub eligible: bool,
+    pub price: i128,
+    pub price_exponent: i32,
+    pub reason: u32,
+}
+
+// decide_payout's and preview_payout's answer. Pay carries the claim in
+// the policy's coverage units, then the payout, liquidator bounty and
+// protocol fee it comes to in what the vault pays; ObserveBreach means the
+// call only records a breach for the dwell, which can be confirmed from
+// confirmable_from; FlagDeviation means it only flags a print too far from
+// ema_price; Reject carries the error code liquidate_policy would fail with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayoutDecision {
+    Pay { claim: u64, payout: u64, bounty: u64, fee: u64 },
+    ObserveBreach { confirmable_from: i64 },
+    FlagDeviation { ema_price: OraclePrice, confirmable_from: i64 },
+    Reject(u32),
+}
+
+// A compact binary, spot-settled policy living in a PolicyBook slot
+#[zero_copy]
+pub struct PolicySlot {
+    pub owner: Pubkey,            // receives the payout
+    pub strike_price: u64,        // USD at STRIKE_EXPONENT
+    pub coverage_amount: u64,     // lamports reserved from the book vault
+    pub expiration_datetime: i64,
+    pub created_at: i64,
+    pub asset: u8,                // UnderlyingAsset as u8
+    pub is_long_insurance: u8,    // 1 = Protect Long, 0 = Protect Short
+    pub status: u8,               // BOOK_SLOT_FREE / BOOK_SLOT_OPEN
+    pub _padding: [u8; 5],
+}
+
+// One underwriter's book of small-ticket policies in a single large
+// account, so each policy costs a slot instead of its own rent-exempt PDAs.
+// Allocated by the client (too large for CPI init) and claimed here
+#[account(zero_copy)]
+pub struct PolicyBook {
+    pub underwriter: Pubkey,
+    pub reserved_coverage: u64,   // lamports backing open slots
+    pub open_count: u32,
+    pub vault_bump: u8,
+    pub _padding: [u8; 3],
+    pub slots: [PolicySlot; POLICY_BOOK_CAPACITY],
+}
+
+impl PolicyBook {
+    pub const LEN: usize = 32 + 8 + 4 + 1 + 3 + POLICY_BOOK_CAPACITY * 72; // underwriter + reserved_coverage + open_count + vault_bump + padding + slots
+
+    fn slot_mut(&mut self, slot_index: u32) -> Result<&mut PolicySlot> {
+        self.slots
+            .get_mut(slot_index as usize)
+            .ok_or(error!(LiqGuardError::InvalidBookSlot))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BookPolicyParams {
+    pub asset: UnderlyingAsset,
+    pub strike_price: u64,
+    pub is_long_insurance: bool,
+    pub coverage_amount: u64,
+    pub expiration_datetime: i64,
+    pub premium_amount: u64, // lamports paid to the underwriter at open
+}
+
+#[derive(Accounts)]
+pub struct InitializePolicyBook<'info> {
+    #[account(zero)]
+    pub policy_book: AccountLoader<'info, PolicyBook>,
+
+    /// Lamport-only PDA pooling the book's collateral
+    #[account(
+        seeds = [BOOK_VAULT_SEED, policy_book.key().as_ref()],
+        bump
+    )]
+    pub book_vault: SystemAccount<'info>,
+
+    pub underwriter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundPolicyBook<'info> {
+    #[account(has_one = underwriter)]
+    pub policy_book: AccountLoader<'info, PolicyBook>,
+
+    #[account(
+        mut,
+        seeds = [BOOK_VAULT_SEED, policy_book.key().as_ref()],
+        bump = policy_book.load()?.vault_bump
+    )]
+    pub book_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub underwriter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(slot_index: u32, params: BookPolicyParams)]
+pub struct OpenBookPolicy<'info> {
+    #[account(mut, has_one = underwriter)]
+    pub policy_book: AccountLoader<'info, PolicyBook>,
+
+    #[account(
+        seeds = [BOOK_VAULT_SEED, policy_book.key().as_ref()],
+        bump = policy_book.load()?.vault_bump
+    )]
+    pub book_vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, params.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Premium recipient; must be the book's underwriter, who
+    /// agreed to the book's terms by opening and funding it
+    #[account(mut)]
+    pub underwriter: AccountInfo<'info>,
+
+    /// CHECK: Receives the protocol fee; must match config.treasury
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LiqGuardError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleBookPolicy<'info> {
+    #[account(mut)]
+    pub policy_book: AccountLoader<'info, PolicyBook>,
+
+    #[account(
+        mut,
+        seeds = [BOOK_VAULT_SEED, policy_book.key().as_ref()],
+        bump = policy_book.load()?.vault_bump
+    )]
+    pub book_vault: SystemAccount<'info>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Payout destination; must be the slot owner
+    #[account(mut)]
+    pub beneficiary: AccountInfo<'info>,
+
+    // Anyone may crank a valid settlement and collect the bounty
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseBookPolicy<'info> {
+    #[account(mut)]
+    pub policy_book: AccountLoader<'info, PolicyBook>,
+}
+
+// One underwriter's tree of compressed policies. The PDA is the tree's
+// authority and holds the pooled collateral as lamports, so a policy
+// costs a leaf instead of its own rent-exempt accounts
+#[account]
+pub struct CompressedPolicyTree {
+    pub underwriter: Pubkey,
+    pub merkle_tree: Pubkey,    // SPL concurrent Merkle tree account
+    pub reserved_coverage: u64, // lamports backing open leaves
+    pub leaf_count: u32,        // next leaf index; the tree is append-only
+    pub open_count: u32,
+    pub bump: u8,
+}
+
+impl CompressedPolicyTree {
+    pub const LEN: usize = 32 + 32 + 8 + 4 + 4 + 1; // underwriter + merkle_tree + reserved_coverage + leaf_count + open_count + bump
+}
+
+// A binary spot-settled policy stored as a leaf: only keccak(borsh(leaf))
+// lives in the tree, and callers pass the full leaf back to settle it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompressedPolicy {
+    pub tree: Pubkey,         // CompressedPolicyTree the leaf belongs to
+    pub leaf_index: u32,
+    pub owner: Pubkey,        // receives the payout
+    pub asset: UnderlyingAsset,
+    pub direction: TriggerDirection, // ProtectLong / ProtectShort only
+    pub strike_price: u64,    // USD at STRIKE_EXPONENT
+    pub coverage_amount: u64, // lamports reserved from the tree
+    pub expiration_datetime: i64,
+    pub created_at: i64,
+}
+
+impl CompressedPolicy {
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        Ok(keccak::hashv(&[&self.try_to_vec()?]).0)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CompressedPolicyParams {
+    pub asset: UnderlyingAsset,
+    pub strike_price: u64,
+    pub direction: TriggerDirection,
+    pub coverage_amount: u64,
+    pub expiration_datetime: i64,
+    pub premium_amount: u64, // lamports paid to the underwriter at open
+}
+
+#[derive(Accounts)]
+pub struct InitializeCompressedTree<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CompressedPolicyTree::LEN,
+        seeds = [COMPRESSED_TREE_SEED, merkle_tree.key().as_ref()],
+        bump
+    )]
+    pub compressed_tree: Account<'info, CompressedPolicyTree>,
+
+    /// CHECK: Zeroed tree account allocated by the client for the
+    /// compression program, which initializes it in this instruction
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub underwriter: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = compression::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Noop program
+    #[account(address = compression::NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageCompressedTree<'info> {
+    #[account(mut, has_one = underwriter)]
+    pub compressed_tree: Account<'info, CompressedPolicyTree>,
+
+    #[account(mut)]
+    pub underwriter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CompressedPolicyParams)]
+pub struct OpenCompressedPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [COMPRESSED_TREE_SEED, merkle_tree.key().as_ref()],
+        bump = compressed_tree.bump,
+        has_one = underwriter,
+        has_one = merkle_tree
+    )]
+    pub compressed_tree: Account<'info, CompressedPolicyTree>,
+
+    /// CHECK: Tree account; the compression program validates it
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, params.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Premium recipient; must be the tree's underwriter, who
+    /// agreed to its terms by opening and funding it
+    #[account(mut)]
+    pub underwriter: AccountInfo<'info>,
+
+    /// CHECK: Receives the protocol fee; must match config.treasury
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LiqGuardError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = compression::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Noop program
+    #[account(address = compression::NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32], leaf: CompressedPolicy)]
+pub struct SettleCompressedPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [COMPRESSED_TREE_SEED, merkle_tree.key().as_ref()],
+        bump = compressed_tree.bump,
+        has_one = merkle_tree
+    )]
+    pub compressed_tree: Account<'info, CompressedPolicyTree>,
+
+    /// CHECK: Tree account; the compression program validates it
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, leaf.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Payout destination; must be the leaf owner
+    #[account(mut)]
+    pub beneficiary: AccountInfo<'info>,
+
+    // Anyone may crank a valid settlement and collect the bounty
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = compression::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Noop program
+    #[account(address = compression::NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseCompressedPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [COMPRESSED_TREE_SEED, merkle_tree.key().as_ref()],
+        bump = compressed_tree.bump,
+        has_one = merkle_tree
+    )]
+    pub compressed_tree: Account<'info, CompressedPolicyTree>,
+
+    /// CHECK: Tree account; the compression program validates it
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Account Compression program
+    #[account(address = compression::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Noop program
+    #[account(address = compression::NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct QuoteParams {
+    pub asset: UnderlyingAsset,
+    pub strike_price: u64,       // USD at STRIKE_EXPONENT
+    pub direction: TriggerDirection,
+    pub upper_strike_price: u64, // OutsideRange only, else 0
+    pub coverage_amount: u64,
+    pub expiration_datetime: i64,
+    pub exercise_style: ExerciseStyle,
+}
+
+#[derive(Accounts)]
+#[instruction(params: QuoteParams)]
+pub struct Quote<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, params.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// Underwriter's shared vault, to quote at its utilization
+    pub portfolio_vault: Option<Account<'info, PortfolioVault>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitializePolicyParams {
+    pub policy_id: u64,
+    pub asset: UnderlyingAsset,
+    pub strike_price: u64,
+    pub direction: TriggerDirection,
+    pub upper_strike_price: u64, // OutsideRange only, else 0
+    pub inclusive_strike: bool,  // trigger at the strike, not only past it
+    pub trigger_buffer_bps: u16, // 0 = trigger as soon as price crosses the strike
+    pub confidence_adjustment: ConfidenceAdjustment,
+    pub confidence_multiplier_bps: u16, // share of the oracle confidence to shift by, 0 with None
+    pub drawdown_bps: u16,       // >0: derive strike(s) from the entry price, leave them 0
+    pub coverage_amount: u64,
+    pub usd_coverage: bool, // coverage_amount is USD at STRIKE_EXPONENT
+    pub lst_stake_pool: Pubkey, // set: coverage_amount is lamports, collateral is this pool's LST
+    pub expiration_datetime: i64,
+    pub max_price_age: u64, // 0 = Config.default_max_price_age
+    pub payout_mode: PayoutMode,
+    pub payout_slope_bps: u32,
+    pub payout_floor_bps: u16,   // CappedLinear only
+    pub payout_cap_bps: u16,     // CappedLinear only
+    pub collateral_mint: Pubkey, // Pubkey::default() for native SOL
+    pub premium_amount: u64,     // lamports paid to the underwriter at open
+    pub underwriter: Pubkey,     // funds the vault and earns the premium
+    pub beneficiary: Pubkey,     // payout destination, default = owner
+    pub usdc_payout_account: Pubkey, // beneficiary's USDC account to swap payouts into, default = pay SOL
+    pub twap_window_seconds: u64, // 0 = settle on spot only
+    pub switchboard_feed: Pubkey, // Pubkey::default() = Pyth only
+    pub oracle_tolerance_bps: u16,
+    pub chainlink_feed: Pubkey,   // set: settle on this Chainlink feed, not the registry's source
+    pub roll_period_seconds: i64, // >0 opts into auto-roll at expiry
+    pub drift_user: Pubkey,       // set: payout also requires a Drift liquidation
+    pub drift_perp_market_index: u16,
+    pub kamino_obligation: Pubkey, // set: trigger on this obligation's health
+    pub health_threshold_bps: u16, // pay when health < this (10_000 = liquidatable)
+    pub vesting_period_seconds: i64, // >0: stream the payout over this period
+    pub immediate_payout_bps: u16, // share of a vesting payout paid at claim time
+    pub exercise_style: ExerciseStyle,
+    pub barrier_kind: BarrierKind,
+    pub barrier_price: u64,        // USD at STRIKE_EXPONENT, ignored without a barrier
+    pub premium_rate: u64,         // >0: lamports per second streamed to the underwriter
+    pub stream_deposit: u64,       // lamports escrowed for the stream at open
+    pub permissioned_liquidation: bool, // only the owner and allowlisted keepers settle
+    pub premium_payment_id: [u8; 32], // non-zero: premium paid on EVM, redeemed via Wormhole
+    pub wormhole_premium: u64,     // USDC owed on EVM for a cross-chain premium
+    pub compliance_mint: Pubkey,   // set: the payout wallet must hold this credential
+    pub breach_dwell_seconds: i64, // >0: a breach must still hold this long later to pay
+    pub short_strike_price: u64,   // Spread: the short leg's strike, else 0
+}
+
+#[derive(Accounts)]
+#[instruction(params: InitializePolicyParams)]
+pub struct InitializePolicy<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + OwnerState::LEN,
+        seeds = [OWNER_STATE_SEED, owner.key().as_ref()],
+        bump,
+        constraint = owner_state.policy_count == params.policy_id @ LiqGuardError::InvalidPolicyId
+    )]
+    pub owner_state: Account<'info, OwnerState>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Policy::LEN,
+        seeds = [POLICY_SEED, owner.key().as_ref(), &params.policy_id.to_le_bytes()],
+        bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// Lists portfolio-backed policies, the only ones funded at open
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, params.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
+    
+    /// Lamport-only PDA; it becomes a live system account once funded
+    #[account(
+        seeds = [VAULT_SEED, owner.key().as_ref(), &params.policy_id.to_le_bytes()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// Premium recipient; must match params.underwriter and co-sign, so
+    /// the premium only goes to a wallet that agreed to fund the vault
+    #[account(
+        mut,
+        constraint = underwriter.key() == params.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, params.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Receives the protocol fee; must match config.treasury
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LiqGuardError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// CHECK: Owner's Drift user for Drift-verified policies; owner and
+    /// layout are verified when it is read
+    #[account(
+        constraint = drift_user.key() == params.drift_user @ LiqGuardError::InvalidDriftUser
+    )]
+    pub drift_user: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Owner's Kamino obligation for health-triggered policies;
+    /// owner and layout are verified when it is read
+    #[account(
+        constraint = kamino_obligation.key() == params.kamino_obligation
+            @ LiqGuardError::InvalidObligation
+    )]
+    pub kamino_obligation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Price account for the entry price and quote; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: The policy's Chainlink feed, required when it settles on one
+    /// and read for the entry price; validated by the oracle adapter
+    pub chainlink_feed: Option<UncheckedAccount<'info>>,
+
+    /// Receives the backstop fee, required while backstop_fee_bps > 0
+    #[account(
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Option<Account<'info, Backstop>>,
+
+    /// Underwriter's shared vault, for portfolio-backed policies only
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_VAULT_SEED, params.underwriter.as_ref()],
+        bump = portfolio_vault.bump
+    )]
+    pub portfolio_vault: Option<Account<'info, PortfolioVault>>,
+
+    /// Beneficiary's compliance credential, for gated policies only
+    pub beneficiary_credential: Option<Account<'info, TokenAccount>>,
+    
+    // Pays the premium
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTokenVault<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner,
+        constraint = policy.collateral_mint == mint.key() @ LiqGuardError::InvalidCollateralMint
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump,
+        token::mint = mint,
+        token::authority = vault
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    pub owner: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundVault<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// Lists the policy once it is funded
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Account<'info, KeeperQueue>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: Signer<'info>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = underwriter
+    )]
+    pub underwriter_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// SOL/USD feed, required for USD-denominated coverage
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, UnderlyingAsset::Sol.seed().as_ref()],
+        bump = sol_feed_registry.bump
+    )]
+    pub sol_feed_registry: Option<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Price account for the SOL feed's oracle source; validated by
+    /// the oracle adapter
+    pub sol_price_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Stake pool of an LST policy's collateral; owner and layout
+    /// are verified when it is read
+    #[account(
+        constraint = lst_stake_pool.key() == policy.lst_stake_pool @ LiqGuardError::InvalidStakePool
+    )]
+    pub lst_stake_pool: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpCoverage<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Account<'info, KeeperQueue>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = depositor.key() == policy.owner || depositor.key() == policy.underwriter
+            @ LiqGuardError::Unauthorized
+    )]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_STATE_SEED, policy.owner.as_ref()],
+        bump = owner_state.bump
+    )]
+    pub owner_state: Account<'info, OwnerState>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = depositor
+    )]
+    pub depositor_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestCoverageReduction<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawExcessCoverage<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Receives the withdrawn collateral
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: AccountInfo<'info>,
+
+    // Either party may apply a matured reduction
+    #[account(
+        constraint = caller.key() == policy.owner || caller.key() == policy.underwriter
+            @ LiqGuardError::Unauthorized
+    )]
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_STATE_SEED, policy.owner.as_ref()],
+        bump = owner_state.bump
+    )]
+    pub owner_state: Account<'info, OwnerState>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = underwriter
+    )]
+    pub underwriter_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStrike<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Account<'info, KeeperQueue>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBeneficiary<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// New beneficiary's compliance credential, for gated policies only
+    pub beneficiary_credential: Option<Account<'info, TokenAccount>>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPermissionedLiquidation<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageAutomation<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AutomationCrankAccounts<'info> {
+    #[account(
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Option<Account<'info, Backstop>>,
+
+    #[account(
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    #[account(
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.load()?.bump
+    )]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+
+    #[account(
+        seeds = [CLAIM_HISTORY_SEED, policy.owner.as_ref()],
+        bump = claim_history.load()?.bump
+    )]
+    pub claim_history: Option<AccountLoader<'info, ClaimHistory>>,
+}
+
+// One account meta of an automated crank instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CrankAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl CrankAccount {
+    fn readonly(pubkey: Pubkey) -> Self {
+        CrankAccount { pubkey, is_signer: false, is_writable: false }
+    }
+
+    fn writable(pubkey: Pubkey) -> Self {
+        CrankAccount { pubkey, is_signer: false, is_writable: true }
+    }
+}
+
+// Receipt for a redeemed cross-chain premium, keyed by payment id
+#[account]
+pub struct WormholePayment {
+    pub policy: Pubkey,
+    pub amount: u64,       // USDC the payment carried
+    pub vaa_sequence: u64, // collector message sequence, for reconciliation
+    pub bump: u8,
+}
+
+impl WormholePayment {
+    pub const LEN: usize = 32 + 8 + 8 + 1; // policy + amount + vaa_sequence + bump
+}
+
+#[derive(Accounts)]
+pub struct RedeemWormholePremium<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        constraint = policy.premium_payment_id != [0; 32] @ LiqGuardError::InvalidWormholePremium
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + WormholePayment::LEN,
+        seeds = [WORMHOLE_PAYMENT_SEED, policy.premium_payment_id.as_ref()],
+        bump
+    )]
+    pub wormhole_payment: Account<'info, WormholePayment>,
+
+    /// CHECK: PostedVAA written by the Wormhole core bridge; owner and
+    /// layout are checked when it is loaded
+    pub posted_vaa: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// An open dispute over a policy's last liquidation
+#[account]
+pub struct Dispute {
+    pub policy: Pubkey,
+    pub flagged_by: Pubkey, // gets the rent back on resolution
+    pub holdback_amount: u64,
+    pub flagged_at: i64,
+    pub bump: u8,
+}
+
+impl Dispute {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1; // policy + flagged_by + holdback_amount + flagged_at + bump
+}
+
+#[derive(Accounts)]
+pub struct FlagDispute<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        init,
+        payer = flagger,
+        space = 8 + Dispute::LEN,
+        seeds = [DISPUTE_SEED, policy.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = flagger.key() == policy.underwriter || flagger.key() == config.authority
+            @ LiqGuardError::Unauthorized
+    )]
+    pub flagger: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [DISPUTE_SEED, policy.key().as_ref()],
+        bump = dispute.bump,
+        close = flagged_by
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Receives a confirmed holdback; must be the policy's beneficiary
+    #[account(
+        mut,
+        constraint = beneficiary.key() == policy.beneficiary @ LiqGuardError::InvalidBeneficiary
+    )]
+    pub beneficiary: AccountInfo<'info>,
+
+    /// CHECK: Receives a clawed-back holdback
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: AccountInfo<'info>,
+
+    /// CHECK: Gets the dispute rent back; must be whoever flagged it
+    #[account(
+        mut,
+        constraint = flagged_by.key() == dispute.flagged_by @ LiqGuardError::Unauthorized
+    )]
+    pub flagged_by: AccountInfo<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = arbiter
+    )]
+    pub config: Account<'info, Config>,
+
+    pub arbiter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseHoldback<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Payout destination; must be the policy's stored beneficiary
+    #[account(
+        mut,
+        constraint = beneficiary.key() == policy.beneficiary @ LiqGuardError::InvalidBeneficiary
+    )]
+    pub beneficiary: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SwapPayout<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, UnderlyingAsset::Sol.seed().as_ref()],
+        bump = sol_feed_registry.bump
+    )]
+    pub sol_feed_registry: Account<'info, FeedRegistry>,
+
+    /// CHECK: Price account for the SOL feed's oracle source; validated by
+    /// the oracle adapter
+    pub sol_price_feed: UncheckedAccount<'info>,
+
+    /// Wrapped SOL the route swaps from, owned by the vault and closed
+    /// once emptied
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        seeds = [SWAP_SOURCE_SEED, policy.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = vault
+    )]
+    pub swap_source: Account<'info, TokenAccount>,
+
+    #[account(address = token::spl_token::native_mint::ID)]
+    pub native_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = usdc_destination.key() == policy.usdc_payout_account
+            @ LiqGuardError::InvalidSwapPayout,
+        constraint = usdc_destination.mint == config.usdc_mint @ LiqGuardError::InvalidSwapPayout,
+        constraint = usdc_destination.owner == policy.beneficiary @ LiqGuardError::InvalidSwapPayout
+    )]
+    pub usdc_destination: Account<'info, TokenAccount>,
+
+    /// CHECK: Jupiter aggregator program
+    #[account(address = jupiter::JUPITER_PROGRAM_ID)]
+    pub jupiter_program: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPayoutSwap<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Payout destination; must be the policy's stored beneficiary
+    #[account(
+        mut,
+        constraint = beneficiary.key() == policy.beneficiary @ LiqGuardError::InvalidBeneficiary
+    )]
+    pub beneficiary: AccountInfo<'info>,
+
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_STATE_SEED, owner.key().as_ref()],
+        bump = owner_state.bump
+    )]
+    pub owner_state: Account<'info, OwnerState>,
+
+    /// New owner's stats, opened on their first policy. The payer fronts
+    /// the rent
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + OwnerState::LEN,
+        seeds = [OWNER_STATE_SEED, new_owner.as_ref()],
+        bump
+    )]
+    pub new_owner_state: Account<'info, OwnerState>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub owner: Signer<'info>,
+
+    // Pays the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LiquidatePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// Pass to unlist the policy once it is fully claimed
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, policy.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// SOL/USD feed, required for USD-denominated coverage
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, UnderlyingAsset::Sol.seed().as_ref()],
+        bump = sol_feed_registry.bump
+    )]
+    pub sol_feed_registry: Option<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Price account for the SOL feed's oracle source; validated by
+    /// the oracle adapter
+    pub sol_price_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Stake pool of an LST policy's collateral; owner and layout
+    /// are verified when it is read
+    #[account(
+        constraint = lst_stake_pool.key() == policy.lst_stake_pool @ LiqGuardError::InvalidStakePool
+    )]
+    pub lst_stake_pool: Option<UncheckedAccount<'info>>,
+
+    /// Pyth TWAP update, required for policies with a TWAP window
+    pub twap_update: Option<Account<'info, TwapUpdate>>,
+
+    /// Settlement audit log, appended to when passed
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.load()?.bump
+    )]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+
+    /// The owner's claim history, appended to when passed
+    #[account(
+        mut,
+        seeds = [CLAIM_HISTORY_SEED, policy.owner.as_ref()],
+        bump = claim_history.load()?.bump
+    )]
+    pub claim_history: Option<AccountLoader<'info, ClaimHistory>>,
+
+    /// CHECK: Switchboard pull feed for dual-oracle policies; owner and
+    /// layout are verified when it is read
+    #[account(
+        constraint = switchboard_feed.key() == policy.switchboard_feed
+            @ LiqGuardError::InvalidSwitchboardFeed
+    )]
+    pub switchboard_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Drift user for Drift-verified policies; owner and layout are
+    /// verified when it is read
+    #[account(
+        constraint = drift_user.key() == policy.drift_user @ LiqGuardError::InvalidDriftUser
+    )]
+    pub drift_user: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Kamino obligation for health-triggered policies; owner and
+    /// layout are verified when it is read
+    #[account(
+        constraint = kamino_obligation.key() == policy.kamino_obligation
+            @ LiqGuardError::InvalidObligation
+    )]
+    pub kamino_obligation: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Payout destination; must be the policy's stored beneficiary
+    #[account(
+        mut,
+        constraint = beneficiary.key() == policy.beneficiary @ LiqGuardError::InvalidBeneficiary
+    )]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    // Anyone may crank a valid liquidation and collect the bounty
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    /// Vesting schedule, required for policies that pay in tranches. The
+    /// liquidator fronts its rent and gets it back after the last tranche
+    #[account(
+        init,
+        payer = liquidator,
+        space = 8 + ClaimState::LEN,
+        seeds = [CLAIM_STATE_SEED, policy.key().as_ref()],
+        bump
+    )]
+    pub claim_state: Option<Account<'info, ClaimState>>,
+
+    /// Protocol backstop, required when the vault can't cover the claim
+    #[account(
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Option<Account<'info, Backstop>>,
+
+    /// Treasury PDA, required while a payout fee is configured
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Underwriter's backstop debt, opened on its first draw
+    #[account(
+        init_if_needed,
+        payer = liquidator,
+        space = 8 + UnderwriterRecord::LEN,
+        seeds = [UNDERWRITER_SEED, policy.underwriter.as_ref()],
+        bump
+    )]
+    pub underwriter_record: Option<Account<'info, UnderwriterRecord>>,
+
+    /// Underwriter's shared vault, for portfolio-backed policies only
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_VAULT_SEED, policy.underwriter.as_ref()],
+        bump = portfolio_vault.bump
+    )]
+    pub portfolio_vault: Option<Account<'info, PortfolioVault>>,
+
+    /// Master agreement whose aggregate limit the claim draws on, for
+    /// member policies only
+    #[account(
+        mut,
+        constraint = master_agreement.key() == policy.master_agreement
+            @ LiqGuardError::InvalidMasterAgreement
+    )]
+    pub master_agreement: Option<Account<'info, MasterAgreement>>,
+
+    /// Beneficiary's compliance credential, for gated policies only
+    pub beneficiary_credential: Option<Account<'info, TokenAccount>>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = policy.beneficiary
+    )]
+    pub beneficiary_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = liquidator
+    )]
+    pub liquidator_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordBarrierTouch<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// Pass to unlist the policy if the touch knocks it out
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, policy.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RecordSettlementPrice<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// CHECK: Price account for the feed's oracle source; validated by
+    /// the oracle adapter
+    pub price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, policy.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SettlePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// Pass to unlist the policy once it is fully claimed
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Payout destination; must be the policy's stored beneficiary
+    #[account(
+        mut,
+        constraint = beneficiary.key() == policy.beneficiary @ LiqGuardError::InvalidBeneficiary
+    )]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [FEED_REGISTRY_SEED, policy.asset.seed().as_ref()],
+        bump = feed_registry.bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    // Anyone may crank a valid settlement and collect the bounty
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = policy.beneficiary
+    )]
+    pub beneficiary_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = liquidator
+    )]
+    pub liquidator_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury PDA, required while a payout fee is configured
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Settlement audit log, appended to when passed
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.load()?.bump
+    )]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+
+    /// The owner's claim history, appended to when passed
+    #[account(
+        mut,
+        seeds = [CLAIM_HISTORY_SEED, policy.owner.as_ref()],
+        bump = claim_history.load()?.bump
+    )]
+    pub claim_history: Option<AccountLoader<'info, ClaimHistory>>,
+
+    /// Master agreement whose aggregate limit the claim draws on, for
+    /// member policies only
+    #[account(
+        mut,
+        constraint = master_agreement.key() == policy.master_agreement
+            @ LiqGuardError::InvalidMasterAgreement
+    )]
+    pub master_agreement: Option<Account<'info, MasterAgreement>>,
+
+    /// Beneficiary's compliance credential, for gated policies only
+    pub beneficiary_credential: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Vesting schedule of a tranched payout: immediate_amount is released at
+// claim time, the remainder linearly from vesting_start to vesting_end
+#[account]
+pub struct ClaimState {
+    pub policy: Pubkey,
+    pub total_amount: u64,     // beneficiary's payout, net of the bounty
+    pub immediate_amount: u64, // released at claim time
+    pub claimed_amount: u64,   // released so far
+    pub vesting_start: i64,
+    pub vesting_end: i64,
+    pub bump: u8,
+}
+
+impl ClaimState {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 1; // policy + total_amount + immediate_amount + claimed_amount + vesting_start + vesting_end + bump
+
+    // Total released by `now`, including the immediate tranche
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        if now >= self.vesting_end {
+            return Ok(self.total_amount);
+        }
+        let elapsed = now.saturating_sub(self.vesting_start).max(0) as u128;
+        let duration = self.vesting_end.saturating_sub(self.vesting_start) as u128;
+        let streamed = ((self.total_amount - self.immediate_amount) as u128)
+            .checked_mul(elapsed)
+            .ok_or(LiqGuardError::MathOverflow)?
+            / duration;
+        Ok(self.immediate_amount + streamed as u64)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimTranche<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        mut,
+        seeds = [CLAIM_STATE_SEED, policy.key().as_ref()],
+        bump = claim_state.bump,
+        has_one = policy
+    )]
+    pub claim_state: Account<'info, ClaimState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Payout destination; must be the policy's stored beneficiary
+    #[account(
+        mut,
+        constraint = beneficiary.key() == policy.beneficiary @ LiqGuardError::InvalidBeneficiary
+    )]
+    pub beneficiary: AccountInfo<'info>,
+
+    /// CHECK: Liquidator that paid the claim state rent
+    #[account(
+        mut,
+        constraint = rent_receiver.key() == policy.settlement_liquidator @ LiqGuardError::Unauthorized
+    )]
+    pub rent_receiver: AccountInfo<'info>,
+
+    // Anyone can crank a vested tranche out to the beneficiary
+    pub cranker: Signer<'info>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = policy.beneficiary
+    )]
+    pub beneficiary_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRollPremium<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAutoRoll<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        constraint = caller.key() == policy.owner || caller.key() == policy.underwriter
+            @ LiqGuardError::Unauthorized
+    )]
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RollPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Receives the roll premium
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: AccountInfo<'info>,
+
+    /// CHECK: Receives the protocol fee; must match config.treasury
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LiqGuardError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Receives the backstop fee, required while backstop_fee_bps > 0
+    #[account(
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Option<Account<'info, Backstop>>,
+
+    // Anyone may crank a due roll
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AutoRenew<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    // Delegate on premium_source, and authority of renewal_source
+    #[account(
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Receives the roll premium
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: AccountInfo<'info>,
+
+    /// CHECK: Receives the protocol fee; must match config.treasury
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LiqGuardError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Receives the backstop fee, required while backstop_fee_bps > 0
+    #[account(
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Option<Account<'info, Backstop>>,
+
+    /// The owner's wrapped SOL, with the vault approved as its delegate
+    #[account(
+        mut,
+        token::mint = native_mint,
+        constraint = premium_source.owner == policy.owner @ LiqGuardError::InvalidPremiumSource
+    )]
+    pub premium_source: Account<'info, TokenAccount>,
+
+    /// Wrapped SOL the premium passes through, owned by the vault and
+    /// closed once unwrapped into the policy's escrow
+    #[account(
+        init_if_needed,
+        payer = caller,
+        seeds = [RENEWAL_SOURCE_SEED, policy.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = vault
+    )]
+    pub renewal_source: Account<'info, TokenAccount>,
+
+    #[account(address = token::spl_token::native_mint::ID)]
+    pub native_mint: Account<'info, Mint>,
+
+    // Anyone may crank a due renewal; fronts renewal_source's rent, which
+    // is refunded
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageStreamPremium<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncPremiumStream<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Receives the accrued premium
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: AccountInfo<'info>,
+
+    /// CHECK: Receives the protocol fee; must match config.treasury
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LiqGuardError::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Receives the backstop fee, required while backstop_fee_bps > 0
+    #[account(
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
+    )]
+    pub backstop: Option<Account<'info, Backstop>>,
+
+    // Anyone may sync a stream
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LiquidatePolicies<'info> {
+    /// CHECK: Price account for the feed's oracle source, or the asset's
+    /// price cache pushed this slot; validated by batch_price
+    pub price_feed: UncheckedAccount<'info>,
+
+    pub feed_registry: Account<'info, FeedRegistry>,
+
+    /// Pass to unlist the policies the batch fully claims
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, feed_registry.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    // Anyone may crank a batch and collect the bounties
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    /// Treasury PDA, required while a payout fee is configured
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimVault<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// Pass to unlist the policy as it closes
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Receives the policy account rent
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    /// CHECK: Receives the residual vault collateral
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: AccountInfo<'info>,
+
+    // The underwriter reclaims; the owner may only close a policy that was
+    // never funded
+    #[account(
+        constraint = caller.key() == policy.underwriter
+            || (caller.key() == policy.owner && !policy.is_funded)
+            @ LiqGuardError::Unauthorized
+    )]
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_STATE_SEED, policy.owner.as_ref()],
+        bump = owner_state.bump
+    )]
+    pub owner_state: Account<'info, OwnerState>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
+    )]
+    pub token_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = underwriter
+    )]
+    pub underwriter_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Underwriter's shared vault, for portfolio-backed policies only
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_VAULT_SEED, policy.underwriter.as_ref()],
+        bump = portfolio_vault.bump
+    )]
+    pub portfolio_vault: Option<Account<'info, PortfolioVault>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePolicyV2<'info> {
+    /// CHECK: A policy in the pre-version layout, which Account<Policy>
+    /// can't load; owner, discriminator and length are checked by hand
+    #[account(mut, owner = crate::ID @ LiqGuardError::InvalidPolicyAccount)]
+    pub policy: UncheckedAccount<'info>,
+
+    // Pays the rent for the added bytes
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendAccount<'info> {
+    /// CHECK: Any program account; its type is read from the discriminator
+    /// by hand, since the old layout may not deserialize
+    #[account(mut, owner = crate::ID @ LiqGuardError::UnknownAccountType)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    // Pays the rent for the added bytes
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.policy_bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub policy: Account<'info, Policy>,
+
+    /// Pass to unlist the policy as it closes
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Receives the policy account rent
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    /// CHECK: Receives the residual vault collateral
+    #[account(
+        mut,
+        constraint = underwriter.key() == policy.underwriter @ LiqGuardError::InvalidUnderwriter
+    )]
+    pub underwriter: AccountInfo<'info>,
+
+    // Anyone can close a terminal policy
+    pub closer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OWNER_STATE_SEED, policy.owner.as_ref()],
+        bump = owner_state.bump
+    )]
+    pub owner_state: Account<'info, OwnerState>,
+
+    /// Token vault for SPL-collateral policies
+    #[account(
+        mut,
+        seeds = [TOKEN_VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.token_vault_bump
     )]
-    pub config: Account<'info, Config>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    pub token_vault: Option<Account<'info, TokenAccount>>,
 
-#[derive(Accounts)]
-#[instruction(strike_price: u64, is_long_insurance: bool, coverage_amount: u64)]
-pub struct InitializePolicy<'info> {
     #[account(
-        init,
-        payer = owner,
-        space = 8 + Policy::LEN,
-        seeds = [
-            b"policy",
-            owner.key().as_ref(),
-            strike_price.to_le_bytes(),
-            &[is_long_insurance as u8],
-            &coverage_amount.to_le_bytes(),
-        ],
-        bump
+        mut,
+        token::mint = policy.collateral_mint,
+        token::authority = underwriter
     )]
-    pub policy: Account<'info, Policy>,
-    
+    pub underwriter_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Underwriter's shared vault, for portfolio-backed policies only
     #[account(
-        init,
-        payer = owner,
-        space = 8,
-        seeds = [b"vault", owner.key().as_ref()],
-        bump
+        mut,
+        seeds = [PORTFOLIO_VAULT_SEED, policy.underwriter.as_ref()],
+        bump = portfolio_vault.bump
     )]
-    pub vault: SystemAccount<'info>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    
+    pub portfolio_vault: Option<Account<'info, PortfolioVault>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct LiquidatePolicy<'info> {
+pub struct SweepAbandonedVault<'info> {
     #[account(
         mut,
-        seeds = [
-            b"policy",
-            policy.owner.as_ref(),
-            &policy.strike_price.to_le_bytes(),
-            &[policy.is_long_insurance as u8],
-            &policy.coverage_amount.to_le_bytes(),
-        ],
+        seeds = [POLICY_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
         bump = policy.policy_bump
     )]
     pub policy: Account<'info, Policy>,
 
-    /// CHECK: Pyth price update account
-    pub price_update: Account<'info, PriceUpdateV2>,
+    /// Pass to unlist the policy as it closes
+    #[account(
+        mut,
+        seeds = [KEEPER_QUEUE_SEED, policy.asset.seed().as_ref()],
+        bump = keeper_queue.bump
+    )]
+    pub keeper_queue: Option<Account<'info, KeeperQueue>>,
 
     #[account(
         mut,
-        seeds = [b"vault", policy.owner.as_ref()],
+        seeds = [VAULT_SEED, policy.creator.as_ref(), &policy.policy_id.to_le_bytes()],
         bump = policy.vault_bump
     )]
     pub vault: SystemAccount<'info>,
 
-    /// CHECK: User account to receive payout
-    #[account(mut)]
-    pub user: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [OWNER_STATE_SEED, policy.owner.as_ref()],
+        bump = owner_state.bump
+    )]
+    pub owner_state: Account<'info, OwnerState>,
 
+    /// Receives the sweep when passed
     #[account(
-        seeds = [b"config"],
-        bump = config.bump,
-        has_one = authority @ LiqGuardError::Unauthorized
+        mut,
+        seeds = [BACKSTOP_SEED],
+        bump = backstop.bump
     )]
-    pub config: Account<'info, Config>,
+    pub backstop: Option<Account<'info, Backstop>>,
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// Receives the sweep when no backstop is passed
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Underwriter's shared vault, for portfolio-backed policies only
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_VAULT_SEED, policy.underwriter.as_ref()],
+        bump = portfolio_vault.bump
+    )]
+    pub portfolio_vault: Option<Account<'info, PortfolioVault>>,
+
+    // Anyone can sweep an abandoned vault
+    pub sweeper: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[account]
-pub struct Policy {
+pub struct OwnerState {
     pub owner: Pubkey,
-    pub strike_price: u64,        // USD price (e.g., 95000)
-    pub is_long_insurance: bool,  // true = Protect Long, false = Protect Short
-    pub coverage_amount: u64,     // lamports
-    pub is_claimed: bool,
+    pub policy_count: u64, // next policy_id to assign
+    pub active_coverage: u64, // coverage of the owner's policies that haven't been closed
+    pub bump: u8,
+}
+
+impl OwnerState {
+    pub const LEN: usize = 32 + 8 + 8 + 1; // owner + policy_count + active_coverage + bump
+}
+
+// One recipient's cut of a split payout. An empty slot has no share
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PayoutShare {
+    pub recipient: Pubkey,
+    pub share_bps: u16,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Policy {
+    pub owner: Pubkey,            // current holder, controls the policy
+    pub creator: Pubkey,          // opened the policy; PDA seeds use this
+    pub beneficiary: Pubkey,      // receives the payout
+    pub created_at: i64,          // unix timestamp the policy was opened at
+    pub policy_id: u64,           // index into the creator's policies
+    pub asset: UnderlyingAsset,   // which Pyth feed settles this policy
+    pub strike_price: u64,        // USD at STRIKE_EXPONENT (e.g., 9_500_000_000_000 = $95,000)
+    pub direction: TriggerDirection, // which side(s) of the strike pay
+    pub upper_strike_price: u64,  // OutsideRange: top of the band at STRIKE_EXPONENT, else 0
+    pub inclusive_strike: bool,   // a price exactly at the (buffered) strike triggers too
+    pub trigger_buffer_bps: u16,  // price must clear the strike by this much to trigger
+    pub confidence_adjustment: ConfidenceAdjustment, // how spot confidence shifts the trigger check
+    pub confidence_multiplier_bps: u16, // shift = conf * this / 10_000
+    pub drawdown_bps: u16,        // >0: strike(s) were set this far from entry_price at open
+    pub entry_price: i128,        // oracle price mantissa at open
+    pub entry_price_exponent: i32,
+    pub entry_publish_time: i64,  // publish time of the entry price
+    pub coverage_amount: u64,     // lamports, or USD at STRIKE_EXPONENT if usd_coverage
+    pub usd_coverage: bool,       // coverage is paid in lamports at the claim-time SOL/USD price
+    pub lst_stake_pool: Pubkey,   // set: coverage is lamports, held and paid in this pool's LST
+    pub expiration_datetime: i64, // unix timestamp coverage ends at
+    pub max_price_age: u64,       // seconds a price update stays usable
+    pub payout_mode: PayoutMode,
+    pub payout_slope_bps: u32,    // Proportional: payout bps per 100% breach
+    pub payout_floor_bps: u16,    // CappedLinear: minimum payout, bps of coverage
+    pub payout_cap_bps: u16,      // CappedLinear: maximum payout, bps of coverage
+    pub collateral_mint: Pubkey,  // SPL mint backing the vault, default = SOL
+    pub premium_amount: u64,      // lamports paid by the owner at open
+    pub premium_payment_id: [u8; 32], // set: premium is paid in USDC on EVM under this id
+    pub wormhole_premium: u64,    // USDC the cross-chain payment must cover
+    pub wormhole_premium_paid: bool, // a matching Wormhole payment has been redeemed
+    pub twap_window_seconds: u64, // >0: TWAP over this window must breach too
+    pub switchboard_feed: Pubkey, // set: Switchboard must agree with Pyth
+    pub oracle_tolerance_bps: u16, // max Pyth/Switchboard deviation
+    pub chainlink_feed: Pubkey,   // set: every settlement price comes from this Chainlink feed
+    pub permissioned_liquidation: bool, // settled only by the owner or allowlisted keepers
+    pub automation_thread: Pubkey, // automation thread cranking liquidations, default = none
+    pub automation_price_feed: Pubkey, // price account the thread settles against
+    pub pending_coverage_amount: u64, // coverage after a requested reduction
+    pub coverage_unlock_at: i64,  // when the reduction can apply, 0 = none
+    pub pending_strike_price: u64, // strike after a requested update
+    pub strike_unlock_at: i64,    // when the strike update can apply, 0 = none
+    pub roll_period_seconds: i64, // auto-roll period, 0 = no auto-roll
+    pub roll_premium_escrow: u64, // lamports pre-paid for future roll premiums
+    pub premium_rate: u64,        // streaming premium, lamports per second, 0 = paid up front
+    pub stream_escrow: u64,       // lamports escrowed for the stream, not yet synced out
+    pub stream_accrued_until: i64, // premium is synced through this timestamp
+    pub stream_stopped_at: i64,   // cancel or final claim ended the stream, 0 = running
+    pub drift_user: Pubkey,       // Drift user that must be liquidated, default = off
+    pub drift_perp_market_index: u16, // covered Drift perp market
+    pub drift_liquidation_id: u16, // Drift next_liquidation_id when the policy opened
+    pub kamino_obligation: Pubkey, // Kamino obligation whose health triggers, default = off
+    pub health_threshold_bps: u16, // health factor (bps) below which the policy pays
+    pub deviation_flagged_at: i64, // when the circuit breaker last deferred a payout, 0 = clear
+    pub vesting_period_seconds: i64, // payout streams over this period, 0 = paid at once
+    pub immediate_payout_bps: u16, // share of a vesting payout paid at claim time
+    pub unvested_payout: u64,     // claimed payout still owed through claim_tranche
+    pub exercise_style: ExerciseStyle,
+    pub expiry_price: i128,       // European: Pyth price snapshotted at expiry
+    pub expiry_price_exponent: i32,
+    pub expiry_price_publish_time: i64, // publish time of the snapshot, 0 = none yet
+    pub barrier_kind: BarrierKind,
+    pub barrier_price: u64,       // USD at STRIKE_EXPONENT
+    pub barrier_touched_at: i64,  // publish time of the first touching price, 0 = untouched
+    pub backstop_draw: u64,       // lamports the claim drew from the backstop
+    pub holdback_amount: u64,     // lamports of the last payout held in the vault for disputes
+    pub holdback_release_at: i64, // unix timestamp the holdback can be released from
+    pub dispute_open: bool,       // the last liquidation is disputed, vault frozen
+    pub usdc_payout_account: Pubkey, // set: payouts are swapped into USDC delivered here
+    pub pending_swap_amount: u64, // lamports of a payout waiting in the vault for swap_payout
+    pub portfolio_vault: Pubkey,  // shared vault backing the policy, default = own vault
+    pub portfolio_exposure: u64,  // worst-case payout still reserved in it
+    pub underwriter: Pubkey,      // funds the vault, earns premium, reclaims residual
+    pub coverage_remaining: u64,  // coverage not yet paid out, 0 = fully claimed
+    pub is_funded: bool,          // vault holds at least coverage_amount
+    pub event_sequence: u64,      // sequence number of the next emitted event
+    // Settlement record, written when the policy pays out
+    pub settlement_price: i128,   // oracle price mantissa used to settle
+    pub settlement_price_exponent: i32,
+    pub settlement_publish_time: i64, // oracle publish (or TWAP end) time
+    pub settlement_slot: u64,
+    pub settlement_liquidator: Pubkey,
+    pub settlement_payout: u64,   // paid to the beneficiary
+    pub settlement_bounty: u64,   // paid to the liquidator
     pub policy_bump: u8,
     pub vault_bump: u8,
+    pub token_vault_bump: u8,
+    // Layout version, last so older layouts stay a prefix of newer ones;
+    // fields added later go after it and bump POLICY_VERSION, and
+    // extend_account grows existing policies to fit them
+    pub version: u8,
+    // v3: the payout is split across these recipients, in order; all
+    // empty pays the beneficiary alone
+    pub payout_splits: [PayoutShare; MAX_PAYOUT_SPLITS],
+    // v4: master agreement the policy's claims draw down, default = none
+    pub master_agreement: Pubkey,
+    // v5: credential the payout wallet must hold while the config sets
+    // none, default = ungated
+    pub compliance_mint: Pubkey,
+    // v6: seconds a strike breach must be sustained before a claim pays,
+    // 0 = pay on the first breaching reading
+    pub breach_dwell_seconds: i64,
+    pub breach_observed_at: i64, // first reading of the breach awaiting confirmation, 0 = none
+    // v7: Spread policies' short leg, netted against the long leg at
+    // strike_price into one payout; USD at STRIKE_EXPONENT, else 0
+    pub short_strike_price: u64,
 }
 
 impl Policy {
-    pub const LEN: usize = 32 + 8 + 1 + 8 + 1 + 1 + 1; // owner + strike_price + is_long_insurance + coverage_amount + is_claimed + policy_bump + vault_bump
+    // Derived from the fields, so adding one can't leave it stale
+    pub const LEN: usize = Policy::INIT_SPACE;
+    // Version 6 policies: the current layout without short_strike_price
+    pub const V6_LEN: usize = Policy::LEN - 8;
+    // Version 5 policies: the v6 layout without the breach dwell
+    pub const V5_LEN: usize = Policy::V6_LEN - 16;
+    // Version 4 policies: the v5 layout without compliance_mint
+    pub const V4_LEN: usize = Policy::V5_LEN - 32;
+    // Version 3 policies: the v4 layout without master_agreement
+    pub const V3_LEN: usize = Policy::V4_LEN - 32;
+    // Version 2 policies: the v3 layout without payout_splits
+    pub const V2_LEN: usize = Policy::V3_LEN - MAX_PAYOUT_SPLITS * PayoutShare::INIT_SPACE;
+    // Policies opened before the version byte: the v2 layout without it
+    pub const V1_LEN: usize = Policy::V2_LEN - 1;
+
+    // Where this policy's settlement prices come from
+    pub fn feed_ref(&self, feed: &FeedRegistry) -> FeedRef {
+        policy_feed_ref(feed, self.chainlink_feed)
+    }
+
+    pub fn strike(&self) -> OraclePrice {
+        OraclePrice::new(self.strike_price, STRIKE_EXPONENT)
+    }
+
+    pub fn upper_strike(&self) -> OraclePrice {
+        OraclePrice::new(self.upper_strike_price, STRIKE_EXPONENT)
+    }
+
+    pub fn short_strike(&self) -> OraclePrice {
+        OraclePrice::new(self.short_strike_price, STRIKE_EXPONENT)
+    }
+
+    // Claimed, or expired with no roll left to crank. Auto-roll policies
+    // keep their collateral locked through ROLL_WINDOW so a close can't
+    // race the roll, European ones until their settlement period is
+    // over, and a vesting payout keeps the vault open until its
+    // last tranche is out. A knocked-out or lapsed policy is over at once
+    pub fn is_terminal(&self, now: i64) -> bool {
+        if self.unvested_payout > 0 {
+            return false;
+        }
+        if self.is_knocked_out() || self.is_lapsed(now) {
+            return true;
+        }
+        let grace = if self.roll_period_seconds > 0 {
+            ROLL_WINDOW
+        } else if self.exercise_style == ExerciseStyle::European {
+            SETTLEMENT_PRICE_WINDOW + EUROPEAN_SETTLEMENT_PERIOD
+        } else {
+            0
+        };
+        self.is_exhausted() || now >= self.expiration_datetime.saturating_add(grace)
+    }
+
+    // Streaming premium accrues until expiry, a cancel or the final
+    // claim, whichever comes first
+    pub fn stream_accrual_end(&self, now: i64) -> i64 {
+        let end = now.min(self.expiration_datetime);
+        if self.stream_stopped_at != 0 {
+            end.min(self.stream_stopped_at)
+        } else {
+            end
+        }
+    }
+
+    // Streaming premium owed to the underwriter but not yet synced out,
+    // never more than the escrow holds
+    pub fn accrued_stream_premium(&self, now: i64) -> u64 {
+        let elapsed = self
+            .stream_accrual_end(now)
+            .saturating_sub(self.stream_accrued_until)
+            .max(0) as u128;
+        elapsed
+            .saturating_mul(self.premium_rate as u128)
+            .min(self.stream_escrow as u128) as u64
+    }
+
+    // A streaming policy's coverage lapses once its escrow has run dry or
+    // the stream was stopped
+    pub fn is_lapsed(&self, now: i64) -> bool {
+        self.premium_rate > 0
+            && (self.stream_stopped_at != 0 || self.accrued_stream_premium(now) >= self.stream_escrow)
+    }
+
+    fn stop_premium_stream(&mut self, now: i64) {
+        if self.premium_rate > 0 && self.stream_stopped_at == 0 {
+            self.stream_stopped_at = now;
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.coverage_remaining == 0
+    }
+
+    // Coverage already paid out by earlier claims
+    pub fn claimed_amount(&self) -> u64 {
+        self.coverage_amount.saturating_sub(self.coverage_remaining)
+    }
+
+    // Claims are cumulative: a claim pays what the payout curve owes at
+    // its price beyond what earlier claims already paid
+    pub fn claimable(&self, owed: u64) -> u64 {
+        owed.saturating_sub(self.claimed_amount())
+            .min(self.coverage_remaining)
+    }
+
+    pub fn barrier(&self) -> OraclePrice {
+        OraclePrice::new(self.barrier_price, STRIKE_EXPONENT)
+    }
+
+    pub fn is_knocked_out(&self) -> bool {
+        self.barrier_kind == BarrierKind::KnockOut && self.barrier_touched_at != 0
+    }
+
+    // Knock-ins need a touch before they can pay, knock-outs must not
+    // have been touched
+    pub fn barrier_allows_claim(&self) -> bool {
+        match self.barrier_kind {
+            BarrierKind::None => true,
+            BarrierKind::KnockIn => self.barrier_touched_at != 0,
+            BarrierKind::KnockOut => self.barrier_touched_at == 0,
+        }
+    }
+
+    // Keep the evidence for a payout on the account itself
+    fn record_settlement(
+        &mut self,
+        price: OraclePrice,
+        publish_time: i64,
+        slot: u64,
+        liquidator: Pubkey,
+        payout: u64,
+        bounty: u64,
+    ) {
+        self.settlement_price = price.mantissa;
+        self.settlement_price_exponent = price.exponent;
+        self.settlement_publish_time = publish_time;
+        self.settlement_slot = slot;
+        self.settlement_liquidator = liquidator;
+        self.settlement_payout = payout;
+        self.settlement_bounty = bounty;
+    }
+
+    // Native policies hold lamports in the vault PDA; others hold SPL tokens
+    pub fn is_native(&self) -> bool {
+        self.collateral_mint == Pubkey::default()
+    }
+
+    // The shares the payout is split into, empty when the beneficiary
+    // takes it all
+    pub fn payout_shares(&self) -> Vec<PayoutShare> {
+        self.payout_splits
+            .iter()
+            .filter(|share| share.share_bps > 0)
+            .copied()
+            .collect()
+    }
+
+    pub fn has_payout_split(&self) -> bool {
+        self.payout_splits.iter().any(|share| share.share_bps > 0)
+    }
+
+    // Premiums paid on EVM only count once their Wormhole payment is in
+    pub fn is_premium_paid(&self) -> bool {
+        self.premium_payment_id == [0; 32] || self.wormhole_premium_paid
+    }
+
+    pub fn may_liquidate(&self, config: &Config, liquidator: &Pubkey) -> bool {
+        !self.permissioned_liquidation
+            || *liquidator == self.owner
+            || (*liquidator == self.automation_thread && *liquidator != Pubkey::default())
+            || config.is_allowlisted_liquidator(liquidator)
+    }
+}
+
+#[event]
+pub struct PolicyInitialized {
+    pub policy: Pubkey,
+    pub owner: Pubkey,
+    pub underwriter: Pubkey,
+    pub asset: UnderlyingAsset,
+    pub strike_price: u64,
+    pub strike_exponent: i32,
+    pub direction: TriggerDirection,
+    pub upper_strike_price: u64,
+    pub coverage_amount: u64,
+    pub expiration_datetime: i64,
+    pub entry_price: i128,
+    pub entry_price_exponent: i32,
+    pub entry_publish_time: i64,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct VaultFunded {
+    pub policy: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub coverage_amount: u64,
+    pub is_funded: bool,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct VaultToppedUp {
+    pub policy: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64, // after the deposit
+    pub coverage_amount: u64,
+    pub is_funded: bool,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct VaultPaidOut {
+    pub policy: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64, // sent to the beneficiary, excluding the bounty
+    pub bounty: u64,
+    pub vault_balance: u64, // after the payout
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct AutomationRegistered {
+    pub policy: Pubkey,
+    pub thread: Pubkey,
+    pub price_feed: Pubkey,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct AutomationDeregistered {
+    pub policy: Pubkey,
+    pub thread: Pubkey,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct LiquidationDisputed {
+    pub policy: Pubkey,
+    pub flagged_by: Pubkey,
+    pub holdback_amount: u64,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub policy: Pubkey,
+    pub arbiter: Pubkey,
+    pub confirmed: bool, // true: holdback paid out, false: clawed back
+    pub amount: u64,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct PayoutSwapped {
+    pub policy: Pubkey,
+    pub beneficiary: Pubkey,
+    pub lamports: u64,        // SOL payout swapped
+    pub usdc_amount: u64,     // USDC delivered
+    pub min_usdc_amount: u64, // oracle-derived minimum the route had to meet
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct PayoutFeeCollected {
+    pub policy: Pubkey,
+    pub amount: u64,
+    pub treasury_balance: u64, // after the fee
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub treasury_balance: u64, // after the withdrawal
+    pub slot: u64,
+}
+
+#[event]
+pub struct VolatilityUpdated {
+    pub asset: UnderlyingAsset,
+    pub volatility_bps: u32,
+    pub updated_at: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AbandonedVaultSwept {
+    pub policy: Pubkey,
+    pub owner: Pubkey,
+    pub underwriter: Pubkey,
+    pub residual: u64,       // vault lamports swept
+    pub escrow: u64,         // roll and stream premium escrow swept with them
+    pub rent: u64,           // policy account rent swept with them
+    pub destination: Pubkey, // backstop or treasury
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct VaultReclaimed {
+    pub policy: Pubkey,
+    pub underwriter: Pubkey,
+    pub amount: u64,        // collateral returned to the underwriter
+    pub vault_balance: u64, // after the withdrawal, 0 once swept
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct CompressedPolicyOpened {
+    pub merkle_tree: Pubkey,
+    pub leaf: CompressedPolicy,
+    pub leaf_hash: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct WormholePremiumRedeemed {
+    pub policy: Pubkey,
+    pub payment_id: [u8; 32],
+    pub amount: u64, // USDC paid on EVM
+    pub vaa_sequence: u64,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct LiquidationExecuted {
+    pub policy: Pubkey,
+    pub liquidator: Pubkey,
+    pub asset: UnderlyingAsset,
+    pub price: i128, // settlement price mantissa
+    pub price_exponent: i32,
+    pub strike_price: u64,
+    pub strike_exponent: i32,
+    pub payout: u64,
+    pub bounty: u64,
+    pub coverage_remaining: u64, // left for later claims, 0 = fully claimed
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct BackstopDrawn {
+    pub policy: Pubkey,
+    pub underwriter: Pubkey,
+    pub amount: u64,    // lamports drawn into the vault
+    pub shortfall: u64, // what the vault was short by
+    pub underwriter_debt: u64,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct PolicyRolled {
+    pub policy: Pubkey,
+    pub premium_amount: u64,
+    pub expiration_datetime: i64, // end of the new period
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct ConfigChangeProposed {
+    pub params: ConfigParams,
+    pub proposed_at: i64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct ConfigChangeApplied {
+    pub params: ConfigParams,
+    pub proposed_at: i64,
+    pub applied_at: i64,
+}
+
+#[event]
+pub struct PremiumStreamSynced {
+    pub policy: Pubkey,
+    pub amount: u64,        // accrued premium paid out, before fees
+    pub stream_escrow: u64, // left escrowed after the sync
+    pub lapsed: bool,
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct PolicyMigrated {
+    pub policy: Pubkey,
+    pub version: u8,
+    pub rent_paid: u64, // lamports the payer added for the larger account
+    pub slot: u64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct AccountExtended {
+    pub account: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub rent_paid: u64, // lamports the authority added for the larger account
+    pub slot: u64,
 }
 
 #[error_code]
@@ -242,9 +9879,836 @@ pub enum LiqGuardError {
     MathOverflow,
     #[msg("Liquidation condition not met")]
     LiquidationConditionNotMet,
-    #[msg("Policy has already been claimed")]
+    #[msg("Policy coverage has been fully claimed")]
     AlreadyClaimed,
     #[msg("Unauthorized: signer is not the authority")]
     Unauthorized,
+    #[msg("Policy id does not match the owner's next policy id")]
+    InvalidPolicyId,
+    #[msg("Vault does not hold the full coverage amount")]
+    VaultNotFunded,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Expiration must be in the future")]
+    InvalidExpiration,
+    #[msg("Policy has expired")]
+    PolicyExpired,
+    #[msg("Policy is still active")]
+    PolicyStillActive,
+    #[msg("Account is not the policy underwriter")]
+    InvalidUnderwriter,
+    #[msg("TWAP window is outside the allowed bounds")]
+    InvalidTwapWindow,
+    #[msg("A TWAP update account is required for this policy")]
+    MissingTwapUpdate,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Protocol and backstop fees must total at most 10000 bps")]
+    InvalidProtocolFee,
+    #[msg("Treasury account does not match config")]
+    InvalidTreasury,
+    #[msg("Coverage exceeds the configured per-policy cap")]
+    CoverageCapExceeded,
+    #[msg("New owner must differ from the current owner and the underwriter")]
+    InvalidNewOwner,
+    #[msg("Strike price must be non-zero and differ from the current strike")]
+    InvalidStrikePrice,
+    #[msg("Invalid price deviation circuit breaker parameters")]
+    InvalidCircuitBreaker,
+    #[msg("Price deviation is flagged and awaiting confirmation")]
+    DeviationUnconfirmed,
+    #[msg("Batch accounts must be (policy, vault, beneficiary) triples")]
+    InvalidBatchAccounts,
+    #[msg("Policy book slot index out of range")]
+    InvalidBookSlot,
+    #[msg("Policy book slot is already in use")]
+    BookSlotInUse,
+    #[msg("Policy book slot has no open policy")]
+    BookSlotNotOpen,
+    #[msg("Price feed is disabled")]
+    FeedDisabled,
+    #[msg("Feed id cannot be empty")]
+    InvalidFeedId,
+    #[msg("Minimum policy age cannot be negative")]
+    InvalidMinPolicyAge,
+    #[msg("Policy is too new to be liquidated")]
+    PolicyTooNew,
+    #[msg("Strike update delay cannot be negative")]
+    InvalidStrikeUpdateDelay,
+    #[msg("No strike update has been requested")]
+    NoPendingStrikeUpdate,
+    #[msg("Strike update is still timelocked")]
+    StrikeUpdateDelayActive,
+    #[msg("Roll period must be between 1 hour and 365 days")]
+    InvalidRollPeriod,
+    #[msg("Auto-roll is not enabled for this policy")]
+    AutoRollDisabled,
+    #[msg("The roll window for this expiry has passed")]
+    RollWindowClosed,
+    #[msg("Roll premium escrow cannot cover the next premium")]
+    InsufficientRollPremium,
+    #[msg("A Drift user account is required for this policy")]
+    MissingDriftUser,
+    #[msg("Account is not the policy owner's Drift user")]
+    InvalidDriftUser,
+    #[msg("Drift user has no position in the covered perp market")]
+    DriftPositionNotFound,
+    #[msg("Covered Drift position has not been liquidated")]
+    DriftPositionNotLiquidated,
+    #[msg("A Kamino obligation account is required for this policy")]
+    MissingObligation,
+    #[msg("Account is not the policy owner's Kamino obligation")]
+    InvalidObligation,
+    #[msg("Obligation policies must be binary and price-trigger free")]
+    InvalidObligationPolicy,
+    #[msg("Health threshold must be at least 10000 bps")]
+    InvalidHealthThreshold,
+    #[msg("Obligation must be refreshed before settlement")]
+    ObligationStale,
+    #[msg("No coverage reduction has been requested")]
+    NoPendingCoverageReduction,
+    #[msg("Coverage reduction is still in its cooldown")]
+    CoverageCooldownActive,
+    #[msg("Oracle tolerance must be between 1 and 10000 bps")]
+    InvalidOracleTolerance,
+    #[msg("A Switchboard feed account is required for this policy")]
+    MissingSwitchboardFeed,
+    #[msg("Account is not a valid Switchboard pull feed")]
+    InvalidSwitchboardFeed,
+    #[msg("Pyth and Switchboard prices disagree")]
+    OracleDisagreement,
+    #[msg("Confidence threshold must be between 1 and 10000 bps")]
+    InvalidConfidenceThreshold,
+    #[msg("Price confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    #[msg("Max price age is outside the allowed bounds")]
+    InvalidMaxPriceAge,
+    #[msg("Bounty bps must not exceed 10000")]
+    InvalidBounty,
+    #[msg("Payout account is not the policy beneficiary")]
+    InvalidBeneficiary,
+    #[msg("Proportional payouts need a non-zero slope")]
+    InvalidPayoutSlope,
+    #[msg("Mint does not match the policy collateral")]
+    InvalidCollateralMint,
+    #[msg("Token accounts are required for SPL-collateral policies")]
+    MissingTokenAccounts,
+    #[msg("Vesting period must be between 1 hour and 90 days with a partial immediate tranche")]
+    InvalidVestingSchedule,
+    #[msg("A claim state account is required for this policy")]
+    MissingClaimState,
+    #[msg("No payout has vested since the last tranche")]
+    NothingVested,
+    #[msg("European policies can't auto-roll, vest or use alternative triggers")]
+    InvalidEuropeanPolicy,
+    #[msg("European policies settle on their expiry price")]
+    EuropeanPolicy,
+    #[msg("Policy is not European-style")]
+    NotEuropeanPolicy,
+    #[msg("Outside the expiry settlement price window")]
+    OutsideSettlementWindow,
+    #[msg("Price is no closer to expiry than the recorded snapshot")]
+    SettlementPriceNotCloser,
+    #[msg("No expiry price has been recorded")]
+    NoSettlementPrice,
+    #[msg("Payout cap must be 1-10000 bps and at least the floor")]
+    InvalidPayoutBounds,
+    #[msg("Barrier must be non-zero, differ from the strike and not auto-roll")]
+    InvalidBarrier,
+    #[msg("On-chain pricing is disabled")]
+    PricingDisabled,
+    #[msg("A price account is required to quote the premium")]
+    MissingPriceUpdate,
+    #[msg("Premium is below the on-chain quote")]
+    PremiumBelowQuote,
+    #[msg("The backstop account is required")]
+    MissingBackstop,
+    #[msg("An underwriter record is required to draw from the backstop")]
+    MissingUnderwriterRecord,
+    #[msg("Coverage and strike are fixed once a claim has paid out")]
+    PartiallyClaimed,
+    #[msg("Nothing is owed beyond earlier claims at this price")]
+    NothingToClaim,
+    #[msg("Earlier claim is still vesting")]
+    VestingInProgress,
+    #[msg("Policy has no barrier")]
+    NoBarrier,
+    #[msg("Barrier has already been touched")]
+    BarrierAlreadyTouched,
+    #[msg("Price has not reached the barrier")]
+    BarrierNotTouched,
+    #[msg("Knock-in barrier not yet touched, or knock-out barrier already touched")]
+    BarrierBlocksClaim,
+    #[msg("Range policies need an upper strike above the strike; other directions take none")]
+    InvalidStrikeRange,
+    #[msg("Drawdown must be under 100% and leaves strikes to be derived; not for Kamino policies")]
+    InvalidDrawdown,
+    #[msg("Streaming premium needs a deposit and can't roll or be European; others take no deposit")]
+    InvalidPremiumStream,
+    #[msg("Policy has no premium stream")]
+    PremiumStreamDisabled,
+    #[msg("Streaming premium ran dry or was cancelled; coverage has lapsed")]
+    CoverageLapsed,
+    #[msg("Accrued streaming premium must be synced first")]
+    PremiumStreamNotSynced,
+    #[msg("Portfolio-backed policies must be native, American and not auto-roll")]
+    InvalidPortfolioPolicy,
+    #[msg("Portfolio vault account is required for this policy")]
+    MissingPortfolioVault,
+    #[msg("Portfolio vault's unreserved balance can't cover this")]
+    PortfolioExposureExceeded,
+    #[msg("A portfolio-backed policy's collateral and coverage are fixed at open")]
+    PortfolioPolicy,
+    #[msg("Only the owner or an allowlisted keeper may settle this policy")]
+    LiquidatorNotAllowed,
+    #[msg("Config change delay must be between 0 and 30 days")]
+    InvalidConfigChangeDelay,
+    #[msg("Config change delay has not elapsed")]
+    ConfigChangeDelayActive,
+    #[msg("Compressed policy leaf does not belong to this tree")]
+    InvalidCompressedPolicy,
+    #[msg("Price account does not match the feed's oracle source")]
+    InvalidOracleAccount,
+    #[msg("Invalid Chainlink feed account")]
+    InvalidChainlinkFeed,
+    #[msg("Invalid cross-chain premium terms")]
+    InvalidWormholePremium,
+    #[msg("Not a Wormhole message from the premium collector")]
+    InvalidWormholeMessage,
+    #[msg("Wormhole payment does not match this policy's premium")]
+    WormholePaymentMismatch,
+    #[msg("The policy's cross-chain premium hasn't been paid")]
+    WormholePremiumUnpaid,
+    #[msg("Not available for USD-denominated coverage")]
+    InvalidUsdCoverage,
+    #[msg("A live SOL/USD price is required to value USD coverage")]
+    MissingSolPrice,
+    #[msg("Owner's total coverage would exceed the per-owner cap")]
+    OwnerCoverageCapExceeded,
+    #[msg("Invalid dispute arbiter, window or holdback")]
+    InvalidDisputeConfig,
+    #[msg("No liquidation holdback is open to dispute")]
+    OutsideDisputeWindow,
+    #[msg("A payout holdback is still pending release")]
+    HoldbackPending,
+    #[msg("The policy's last liquidation is under dispute")]
+    DisputeOpen,
+    #[msg("Policy can't be automated with this thread and price feed")]
+    InvalidAutomation,
+    #[msg("The treasury account is required to collect the payout fee")]
+    MissingTreasury,
+    #[msg("Volatility is outside the allowed bounds")]
+    InvalidVolatility,
+    #[msg("The asset's published volatility is too stale to quote with")]
+    VolatilityStale,    #[msg("Pyth price update was only partially verified")]
+    PriceNotFullyVerified,
+    #[msg("Trigger buffer must be under 100%")]
+    InvalidTriggerBuffer,
+    #[msg("LST collateral needs an SPL mint and a fixed American policy")]
+    InvalidLstCollateral,
+    #[msg("The LST stake pool account is required to value this policy")]
+    MissingStakePool,
+    #[msg("Stake pool account is invalid or doesn't mint the collateral")]
+    InvalidStakePool,
+    #[msg("Stake pool hasn't been updated this epoch")]
+    StakePoolStale,
+    #[msg("USDC payouts need a native American policy and a matching USDC account")]
+    InvalidSwapPayout,
+    #[msg("A swapped payout is still waiting in the vault")]
+    SwapPending,
+    #[msg("Swap route left wrapped SOL behind or moved other vault funds")]
+    InvalidSwapRoute,
+    #[msg("Swap returned less USDC than the oracle-derived minimum")]
+    SwapSlippageExceeded,
+    #[msg("The strike is already breached at the entry price")]
+    StrikeAlreadyBreached,
+    #[msg("Account is not a LiqGuard policy")]
+    InvalidPolicyAccount,
+    #[msg("Policy is already on the current layout")]
+    PolicyAlreadyMigrated,
+    #[msg("Pyth Lazer message is malformed or doesn't carry the feed")]
+    InvalidLazerMessage,
+    #[msg("Pyth Lazer prices are not enabled for this feed")]
+    LazerDisabled,
+    #[msg("Pyth Lazer message is not newer than the stored price")]
+    LazerPriceOutdated,
+    #[msg("Chainlink feed account required for Chainlink-settled policies")]
+    MissingChainlinkFeed,
+    #[msg("Chainlink feed is frozen by its owner")]
+    ChainlinkFeedFrozen,
+    #[msg("Chainlink round has no valid answer")]
+    InvalidChainlinkRound,
+    #[msg("Invalid confidence adjustment")]
+    InvalidConfidenceAdjustment,
+    #[msg("Account is not a LiqGuard account type")]
+    UnknownAccountType,
+    #[msg("Account is already at its type's current size")]
+    AccountAlreadyCurrent,
+    #[msg("Payout shares must name distinct recipients and add up to 100%")]
+    InvalidPayoutSplit,
+    #[msg("Payout splits can't be combined with vesting or USDC payouts")]
+    PayoutSplitUnsupported,
+    #[msg("Split recipient accounts must follow the policy's payout shares")]
+    InvalidSplitRecipient,
+    #[msg("Policy doesn't fit the master agreement's terms, or its account is missing")]
+    InvalidMasterAgreement,
+    #[msg("The master agreement's aggregate limit is used up")]
+    AggregateLimitReached,
+    #[msg("Policies in a master agreement can't be transferred")]
+    PolicyInMasterAgreement,
+    #[msg("Price cache was not pushed this slot")]
+    PriceCacheStale,
+    #[msg("Oracle price is older than the cached price")]
+    PriceCacheOutdated,
+    #[msg("Payout wallet does not hold the compliance credential")]
+    PayoutWalletNotCompliant,
+    #[msg("Policy is not past its abandonment grace period")]
+    VaultNotAbandoned,
+    #[msg("Only native-collateral vaults can be swept")]
+    SweepUnsupported,
+    #[msg("Backstop or treasury required to receive the sweep")]
+    MissingSweepDestination,
+    #[msg("Premium source must be the owner's wrapped SOL account")]
+    InvalidPremiumSource,
+    #[msg("Breach dwell must be within bounds, and American exercise only")]
+    InvalidBreachDwell,
+    #[msg("Strike breach has not been sustained for the policy's dwell time")]
+    BreachUnconfirmed,
+    #[msg("Spread short strike must sit past the long strike, on one-sided policies only")]
+    InvalidSpread,
+    #[msg("Utilization markup must not fall as utilization rises")]
+    InvalidUtilizationCurve,
+    #[msg("Premium exceeds the coverage request's max premium")]
+    PremiumAboveRequest,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // $95,000 strike
+    const STRIKE: u64 = 9_500_000_000_000;
+    // $105,000 top of an OutsideRange band
+    const UPPER_STRIKE: u64 = 10_500_000_000_000;
+
+    fn no_upper() -> OraclePrice {
+        OraclePrice::new(0, STRIKE_EXPONENT)
+    }
+
+    fn zeroed_policy() -> Policy {
+        let data = vec![0u8; 8 + Policy::LEN];
+        Policy::try_deserialize_unchecked(&mut &data[..]).unwrap()
+    }
+
+    #[test]
+    fn align_prices_handles_every_exponent_sign() {
+        let strike = OraclePrice::new(STRIKE, STRIKE_EXPONENT);
+        // $95,000 quoted at negative, zero and positive exponents
+        for price in [
+            OraclePrice::new(95_000_000i64, -3),
+            OraclePrice::new(95_000i64, 0),
+            OraclePrice::new(95i64, 3),
+        ] {
+            let (price, strike) = align_prices(price, strike).unwrap();
+            assert_eq!(price, strike);
+        }
+    }
+
+    #[test]
+    fn align_prices_keeps_the_finer_exponent() {
+        let (a, b) = align_prices(OraclePrice::new(7i64, 2), OraclePrice::new(3i64, -2)).unwrap();
+        assert_eq!((a, b), (70_000, 3));
+    }
+
+    #[test]
+    fn audit_log_overwrites_the_oldest_entry() {
+        let mut audit_log: Box<AuditLog> = Box::new(bytemuck::Zeroable::zeroed());
+        let clock = Clock::default();
+        for i in 0..=AUDIT_LOG_CAPACITY {
+            let price = OraclePrice::new(i as i64, -8);
+            audit_log.record(AuditEntry::new(Pubkey::default(), price, AUDIT_REJECTED, 0, &clock));
+        }
+        assert_eq!(audit_log.total_entries, AUDIT_LOG_CAPACITY as u64 + 1);
+        assert_eq!(audit_log.next_index, 1);
+        assert_eq!(audit_log.entries[0].price, AUDIT_LOG_CAPACITY as i64);
+        assert_eq!(audit_log.entries[1].price, 1);
+    }
+
+    #[test]
+    fn usd_to_lamports_converts_at_the_sol_price() {
+        // $1,500 at $150/SOL (Pyth's exponent) is 10 SOL
+        let sol_price = OraclePrice::new(15_000_000_000i64, -8);
+        assert_eq!(usd_to_lamports(150_000_000_000, sol_price).unwrap(), 10 * LAMPORTS_PER_SOL);
+        // and a price at a coarser exponent scales the same way
+        assert_eq!(
+            usd_to_lamports(150_000_000_000, OraclePrice::new(150i64, 0)).unwrap(),
+            10 * LAMPORTS_PER_SOL
+        );
+        assert!(usd_to_lamports(1, OraclePrice::new(0i64, -8)).is_err());
+    }
+
+    #[test]
+    fn rescale_rejects_shifts_that_overflow() {
+        assert!(OraclePrice::new(1i64, 30).rescale(-10).is_none());
+        assert!(OraclePrice::new(i64::MAX, 20).rescale(0).is_none());
+        assert!(OraclePrice::new(1i64, -8).rescale(-7).is_none());
+    }
+
+    #[test]
+    fn strike_crossed_with_positive_exponents() {
+        let strike = OraclePrice::new(STRIKE, STRIKE_EXPONENT);
+        // $94,900 and $95,100 at expo +2
+        let below = OraclePrice::new(949i64, 2);
+        let above = OraclePrice::new(951i64, 2);
+        assert!(strike_crossed(TriggerDirection::ProtectLong, strike, no_upper(), below).unwrap());
+        assert!(!strike_crossed(TriggerDirection::ProtectLong, strike, no_upper(), above).unwrap());
+        assert!(strike_crossed(TriggerDirection::ProtectShort, strike, no_upper(), above).unwrap());
+        assert!(!strike_crossed(TriggerDirection::ProtectShort, strike, no_upper(), below).unwrap());
+    }
+
+    #[test]
+    fn strike_crossed_with_zero_exponent_is_exact_at_the_strike() {
+        let strike = OraclePrice::new(STRIKE, STRIKE_EXPONENT);
+        let at_strike = OraclePrice::new(95_000i64, 0);
+        assert!(!strike_crossed(TriggerDirection::ProtectLong, strike, no_upper(), at_strike).unwrap());
+        assert!(!strike_crossed(TriggerDirection::ProtectShort, strike, no_upper(), at_strike).unwrap());
+    }
+
+    #[test]
+    fn inclusive_strikes_trigger_at_the_buffered_strike() {
+        // 1% buffer: ProtectLong triggers at $94,050, ProtectShort at $95,950
+        let lower = buffered_strike(STRIKE, 100, false).unwrap();
+        let upper = buffered_strike(STRIKE, 100, true).unwrap();
+        assert_eq!(lower, OraclePrice::new(9_405_000_000_000i64, STRIKE_EXPONENT));
+        assert_eq!(upper, OraclePrice::new(9_595_000_000_000i64, STRIKE_EXPONENT));
+
+        let long = |price: i64, inclusive| {
+            strike_breached(
+                TriggerDirection::ProtectLong,
+                lower,
+                no_upper(),
+                OraclePrice::new(price, 0),
+                inclusive,
+            )
+            .unwrap()
+        };
+        assert!(long(94_050, true));
+        assert!(!long(94_050, false));
+        assert!(long(94_049, false));
+        // Crossing the strike alone isn't enough under a buffer
+        assert!(!long(94_500, true));
+
+        let short = |price: i64, inclusive| {
+            strike_breached(
+                TriggerDirection::ProtectShort,
+                upper,
+                no_upper(),
+                OraclePrice::new(price, 0),
+                inclusive,
+            )
+            .unwrap()
+        };
+        assert!(short(95_950, true));
+        assert!(!short(95_950, false));
+    }
+
+    #[test]
+    fn outside_range_triggers_on_either_side_of_the_band() {
+        let lower = OraclePrice::new(STRIKE, STRIKE_EXPONENT);
+        let upper = OraclePrice::new(UPPER_STRIKE, STRIKE_EXPONENT);
+        let crossed = |price: OraclePrice| {
+            strike_crossed(TriggerDirection::OutsideRange, lower, upper, price).unwrap()
+        };
+        assert!(crossed(OraclePrice::new(94_999i64, 0)));
+        assert!(crossed(OraclePrice::new(105_001i64, 0)));
+        // Both edges are inside the band
+        assert!(!crossed(OraclePrice::new(95_000i64, 0)));
+        assert!(!crossed(OraclePrice::new(100_000i64, 0)));
+        assert!(!crossed(OraclePrice::new(105_000i64, 0)));
+    }
+
+    #[test]
+    fn preview_payout_applies_confidence_dwell_and_claim_sizing() {
+        let mut policy = zeroed_policy();
+        policy.strike_price = STRIKE;
+        policy.coverage_amount = 1_000_000;
+        policy.coverage_remaining = 1_000_000;
+        policy.is_funded = true;
+        policy.expiration_datetime = 10_000;
+        let data = vec![0u8; 8 + Config::LEN];
+        let mut config = Config::try_deserialize_unchecked(&mut &data[..]).unwrap();
+        config.bounty_bps = 100;
+        config.payout_fee_bps = 50;
+        let reject = |error: LiqGuardError| PayoutDecision::Reject(u32::from(error));
+        let preview = |policy: &Policy, dollars: i64, confidence: u128, now: i64| {
+            preview_payout(policy, &config, OraclePrice::new(dollars, 0), confidence, now)
+        };
+        let paused = Config { paused: true, ..config.clone() };
+
+        // 1% bounty, then 0.5% of the rest to the protocol
+        let pay = PayoutDecision::Pay { claim: 1_000_000, payout: 985_050, bounty: 10_000, fee: 4_950 };
+        assert_eq!(preview(&policy, 94_900, 0, 1_000), pay);
+        assert_eq!(preview(&policy, 95_100, 0, 1_000), reject(LiqGuardError::LiquidationConditionNotMet));
+        assert_eq!(preview(&policy, 94_900, 0, 10_000), reject(LiqGuardError::PolicyExpired));
+
+        // A $200 band around $94,900 straddles the strike, so a
+        // conservative policy waits
+        policy.confidence_adjustment = ConfidenceAdjustment::Conservative;
+        policy.confidence_multiplier_bps = 10_000;
+        assert_eq!(preview(&policy, 94_900, 200, 1_000), reject(LiqGuardError::LiquidationConditionNotMet));
+        assert_eq!(preview(&policy, 94_900, 50, 1_000), pay);
+
+        // The first breach only starts the dwell, which pays once served
+        policy.breach_dwell_seconds = 60;
+        assert_eq!(
+            preview(&policy, 94_900, 0, 1_000),
+            PayoutDecision::ObserveBreach { confirmable_from: 1_060 }
+        );
+        policy.breach_observed_at = 1_000;
+        assert_eq!(preview(&policy, 94_900, 0, 1_030), reject(LiqGuardError::BreachUnconfirmed));
+        assert_eq!(preview(&policy, 94_900, 0, 1_060), pay);
+
+        assert_eq!(
+            preview_payout(&policy, &paused, OraclePrice::new(94_900i64, 0), 0, 1_060),
+            reject(LiqGuardError::ProtocolPaused)
+        );
+    }
+
+    #[test]
+    fn keeper_queue_lists_funded_policies_only() {
+        let mut queue = KeeperQueue {
+            asset: UnderlyingAsset::Btc,
+            entries: Vec::new(),
+            bump: 0,
+        };
+        let key = Pubkey::new_unique();
+        let mut policy = zeroed_policy();
+        queue.track(key, &policy);
+        assert!(queue.entries.is_empty());
+        policy.is_funded = true;
+        queue.track(key, &policy);
+        queue.track(key, &policy);
+        assert_eq!(queue.entries.len(), 1);
+    }
+
+    #[test]
+    fn strike_range_needs_an_upper_bound_only_for_bands() {
+        assert!(valid_strike_range(TriggerDirection::OutsideRange, STRIKE, UPPER_STRIKE));
+        assert!(!valid_strike_range(TriggerDirection::OutsideRange, STRIKE, STRIKE));
+        assert!(!valid_strike_range(TriggerDirection::OutsideRange, 0, UPPER_STRIKE));
+        assert!(valid_strike_range(TriggerDirection::ProtectLong, STRIKE, 0));
+        assert!(!valid_strike_range(TriggerDirection::ProtectShort, STRIKE, UPPER_STRIKE));
+    }
+
+    #[test]
+    fn spread_short_strikes_sit_past_the_long_strike() {
+        assert!(valid_spread(PayoutMode::Spread, TriggerDirection::ProtectLong, STRIKE, STRIKE / 2));
+        assert!(!valid_spread(PayoutMode::Spread, TriggerDirection::ProtectLong, STRIKE, UPPER_STRIKE));
+        assert!(!valid_spread(PayoutMode::Spread, TriggerDirection::ProtectLong, STRIKE, 0));
+        assert!(valid_spread(PayoutMode::Spread, TriggerDirection::ProtectShort, STRIKE, UPPER_STRIKE));
+        assert!(!valid_spread(PayoutMode::Spread, TriggerDirection::ProtectShort, STRIKE, STRIKE));
+        assert!(!valid_spread(PayoutMode::Spread, TriggerDirection::OutsideRange, STRIKE, UPPER_STRIKE));
+        assert!(valid_spread(PayoutMode::Binary, TriggerDirection::ProtectLong, STRIKE, 0));
+        assert!(!valid_spread(PayoutMode::Binary, TriggerDirection::ProtectLong, STRIKE, STRIKE / 2));
+    }
+
+    #[test]
+    fn to_strike_units_truncates_fine_exponents() {
+        assert_eq!(to_strike_units(OraclePrice::new(95_000i64, 0)).unwrap(), STRIKE);
+        assert_eq!(to_strike_units(OraclePrice::new(9_500_000_000_012i64, -8)).unwrap(), 9_500_000_000_012);
+        assert_eq!(to_strike_units(OraclePrice::new(95_000_000_000_012_345i64, -12)).unwrap(), 9_500_000_000_001);
+        assert!(to_strike_units(OraclePrice::new(-1i64, -8)).is_err());
+    }
+
+    #[test]
+    fn drawdown_strikes_sit_either_side_of_entry() {
+        // 15% from a $100,000 entry
+        let entry = 10_000_000_000_000;
+        assert_eq!(
+            drawdown_strikes(entry, 1_500).unwrap(),
+            (8_500_000_000_000, 11_500_000_000_000)
+        );
+    }
+
+    #[test]
+    fn common_layout_matches_the_accounts() {
+        use anchor_lang::Discriminator;
+        use liqguard_common::layout::*;
+        macro_rules! check {
+            ($($account:ty => $len:expr, $discriminator:expr;)*) => {$(
+                assert_eq!(<$account>::LEN, $len, stringify!($account));
+                assert_eq!(<$account as Discriminator>::DISCRIMINATOR, $discriminator, stringify!($account));
+            )*};
+        }
+        check! {
+            Config => CONFIG_LEN, CONFIG_DISCRIMINATOR;
+            PendingConfigChange => PENDING_CONFIG_CHANGE_LEN, PENDING_CONFIG_CHANGE_DISCRIMINATOR;
+            Backstop => BACKSTOP_LEN, BACKSTOP_DISCRIMINATOR;
+            Treasury => TREASURY_LEN, TREASURY_DISCRIMINATOR;
+            UnderwriterRecord => UNDERWRITER_RECORD_LEN, UNDERWRITER_RECORD_DISCRIMINATOR;
+            PortfolioVault => PORTFOLIO_VAULT_LEN, PORTFOLIO_VAULT_DISCRIMINATOR;
+            FeedRegistry => FEED_REGISTRY_LEN, FEED_REGISTRY_DISCRIMINATOR;
+            LazerPrice => LAZER_PRICE_LEN, LAZER_PRICE_DISCRIMINATOR;
+            KeeperQueue => KEEPER_QUEUE_LEN, KEEPER_QUEUE_DISCRIMINATOR;
+            AuditLog => AUDIT_LOG_LEN, AUDIT_LOG_DISCRIMINATOR;
+            ClaimHistory => CLAIM_HISTORY_LEN, CLAIM_HISTORY_DISCRIMINATOR;
+            PolicyBook => POLICY_BOOK_LEN, POLICY_BOOK_DISCRIMINATOR;
+            CompressedPolicyTree => COMPRESSED_POLICY_TREE_LEN, COMPRESSED_POLICY_TREE_DISCRIMINATOR;
+            WormholePayment => WORMHOLE_PAYMENT_LEN, WORMHOLE_PAYMENT_DISCRIMINATOR;
+            Dispute => DISPUTE_LEN, DISPUTE_DISCRIMINATOR;
+            ClaimState => CLAIM_STATE_LEN, CLAIM_STATE_DISCRIMINATOR;
+            OwnerState => OWNER_STATE_LEN, OWNER_STATE_DISCRIMINATOR;
+            Policy => POLICY_LEN, POLICY_DISCRIMINATOR;
+            MasterAgreement => MASTER_AGREEMENT_LEN, MASTER_AGREEMENT_DISCRIMINATOR;
+            PriceCache => PRICE_CACHE_LEN, PRICE_CACHE_DISCRIMINATOR;
+            CoverageRequest => COVERAGE_REQUEST_LEN, COVERAGE_REQUEST_DISCRIMINATOR;
+        }
+        assert_eq!(Policy::V6_LEN, POLICY_V6_LEN);
+        assert_eq!(Policy::V5_LEN, POLICY_V5_LEN);
+        assert_eq!(Policy::V4_LEN, POLICY_V4_LEN);
+        assert_eq!(Policy::V3_LEN, POLICY_V3_LEN);
+        assert_eq!(Policy::V2_LEN, POLICY_V2_LEN);
+        assert_eq!(Policy::V1_LEN, POLICY_V1_LEN);
+    }
+
+    #[test]
+    fn policy_space_is_derived_from_its_fields() {
+        use anchor_lang::Discriminator;
+        assert_eq!(Policy::INIT_SPACE, liqguard_common::layout::POLICY_LEN);
+        assert_eq!(current_account_space(&Policy::DISCRIMINATOR), Some(8 + Policy::LEN));
+        assert_eq!(current_account_space(&OwnerState::DISCRIMINATOR), Some(8 + OwnerState::LEN));
+        assert_eq!(current_account_space(&PolicyMigrated::DISCRIMINATOR), None);
+    }
+
+    #[test]
+    fn max_payout_is_the_cap_for_capped_linear_only() {
+        assert_eq!(payout::max_payout(PayoutMode::Binary, 1_000, 0), 1_000);
+        assert_eq!(payout::max_payout(PayoutMode::Proportional, 1_000, 0), 1_000);
+        assert_eq!(payout::max_payout(PayoutMode::CappedLinear, 1_000, 8_000), 800);
+    }
+
+    #[test]
+    fn price_deviation_across_exponents() {
+        let reference = OraclePrice::new(100i64, 3);
+        let other = OraclePrice::new(99_000_000i64, -3);
+        assert_eq!(price_deviation_bps(reference, other).unwrap(), 100);
+    }
+
+    fn compressed_leaf() -> CompressedPolicy {
+        CompressedPolicy {
+            tree: Pubkey::new_from_array([1; 32]),
+            leaf_index: 0,
+            owner: Pubkey::new_from_array([2; 32]),
+            asset: UnderlyingAsset::Btc,
+            direction: TriggerDirection::ProtectLong,
+            strike_price: STRIKE,
+            coverage_amount: 20_000_000,
+            expiration_datetime: 1_800_000_000,
+            created_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn compressed_leaf_hash_commits_to_every_field() {
+        let leaf = compressed_leaf();
+        let hash = leaf.hash().unwrap();
+        assert_eq!(hash, compressed_leaf().hash().unwrap());
+        assert_ne!(hash, compression::EMPTY_LEAF);
+        // Identical terms at another index are a different leaf
+        assert_ne!(hash, CompressedPolicy { leaf_index: 1, ..leaf }.hash().unwrap());
+        assert_ne!(hash, CompressedPolicy { coverage_amount: 20_000_001, ..leaf }.hash().unwrap());
+        assert_ne!(
+            hash,
+            CompressedPolicy { direction: TriggerDirection::ProtectShort, ..leaf }.hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn wormhole_premium_payment_parses_the_collector_payload() {
+        let mut payload = vec![1u8];
+        payload.extend_from_slice(&[7; 32]);
+        payload.extend_from_slice(&[9; 32]);
+        payload.extend_from_slice(&25_000_000u64.to_be_bytes());
+        let payment = wormhole::PremiumPayment::parse(&payload).unwrap();
+        assert_eq!(payment.payment_id, [7; 32]);
+        assert_eq!(payment.policy, Pubkey::new_from_array([9; 32]));
+        assert_eq!(payment.amount, 25_000_000);
+
+        // Wrong payload type or length is rejected
+        payload[0] = 2;
+        assert!(wormhole::PremiumPayment::parse(&payload).is_err());
+        payload[0] = 1;
+        payload.push(0);
+        assert!(wormhole::PremiumPayment::parse(&payload).is_err());
+    }
+
+    #[test]
+    fn rent_exempt_payout_never_strands_a_rent_paying_vault() {
+        const RENT_FLOOR: u64 = 890_880;
+        // Plenty left over, or the vault drained exactly
+        assert_eq!(rent_exempt_payout(10_000_000, 5_000_000, 1_000_000, RENT_FLOOR), 5_000_000);
+        assert_eq!(rent_exempt_payout(6_000_000, 5_000_000, 1_000_000, RENT_FLOOR), 5_000_000);
+        // Dust under the floor is left behind instead of paid
+        assert_eq!(rent_exempt_payout(6_500_000, 5_000_000, 1_000_000, RENT_FLOOR), 4_609_120);
+        // An underfunded vault pays what it has
+        assert_eq!(rent_exempt_payout(4_000_000, 5_000_000, 1_000_000, RENT_FLOOR), 3_000_000);
+        assert_eq!(rent_exempt_payout(500_000, 5_000_000, 1_000_000, RENT_FLOOR), 0);
+    }
+
+    #[test]
+    fn stake_pool_rate_converts_lamports_to_pool_tokens() {
+        // 1.1 SOL per pool token
+        let pool = stake_pool::StakePool {
+            pool_mint: Pubkey::new_unique(),
+            total_lamports: 11_000_000_000,
+            pool_token_supply: 10_000_000_000,
+            last_update_epoch: 500,
+        };
+        assert_eq!(pool.pool_tokens_for(1_100_000_000, false).unwrap(), 1_000_000_000);
+        // Collateral rounds up, payouts down
+        assert_eq!(pool.pool_tokens_for(1_000_000_000, true).unwrap(), 909_090_910);
+        assert_eq!(pool.pool_tokens_for(1_000_000_000, false).unwrap(), 909_090_909);
+    }
+
+    #[test]
+    fn lamports_to_usdc_values_sol_at_the_oracle_price() {
+        // $150.25 at expo -8 and at expo -5
+        let price = OraclePrice::new(15_025_000_000i64, -8);
+        assert_eq!(lamports_to_usdc(2 * LAMPORTS_PER_SOL, price).unwrap(), 300_500_000);
+        assert_eq!(
+            lamports_to_usdc(LAMPORTS_PER_SOL / 2, OraclePrice::new(15_025_000i64, -5)).unwrap(),
+            75_125_000
+        );
+        assert!(lamports_to_usdc(LAMPORTS_PER_SOL, OraclePrice::new(0i64, -8)).is_err());
+    }
+
+    #[test]
+    fn const_feed_ids_match_the_pyth_hex_ids() {
+        use pyth_solana_receiver_sdk::price_update::get_feed_id_from_hex;
+        for (asset, hex) in [
+            (UnderlyingAsset::Btc, "0xe62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43"),
+            (UnderlyingAsset::Eth, "0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace"),
+            (UnderlyingAsset::Sol, "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"),
+        ] {
+            assert_eq!(asset.feed_id(), get_feed_id_from_hex(hex).unwrap());
+        }
+    }
+
+    #[test]
+    fn lazer_message_yields_the_requested_feed() {
+        // Two feeds: 1 (BTC, $95,000.5 at expo -8) and 2 (ETH)
+        let mut payload = vec![0x75, 0xd3, 0xc7, 0x93];
+        payload.extend_from_slice(&1_700_000_000_250_000u64.to_le_bytes());
+        payload.extend_from_slice(&[1, 2]); // channel, feed count
+        for (feed_id, price) in [(1u32, 9_500_050_000_000i64), (2, 350_000_000_000)] {
+            payload.extend_from_slice(&feed_id.to_le_bytes());
+            payload.push(4); // properties
+            payload.push(0);
+            payload.extend_from_slice(&price.to_le_bytes());
+            payload.push(3);
+            payload.extend_from_slice(&17u16.to_le_bytes());
+            payload.push(4);
+            payload.extend_from_slice(&(-8i16).to_le_bytes());
+            payload.push(5);
+            payload.extend_from_slice(&(price / 1_000).to_le_bytes());
+        }
+        let mut message = vec![0xb9, 0x01, 0x1a, 0x82];
+        message.extend_from_slice(&[0; 96]); // signature and signer, checked by Lazer
+        message.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        message.extend_from_slice(&payload);
+
+        let btc = lazer::parse_feed_price(&message, 1).unwrap();
+        assert_eq!((btc.price, btc.exponent, btc.confidence), (9_500_050_000_000, -8, 9_500_050_000));
+        assert_eq!(btc.timestamp_us, 1_700_000_000_250_000);
+        assert_eq!(lazer::parse_feed_price(&message, 2).unwrap().price, 350_000_000_000);
+        // Missing feeds and truncated messages are rejected
+        assert!(lazer::parse_feed_price(&message, 3).is_err());
+        assert!(lazer::parse_feed_price(&message[..message.len() - 1], 1).is_err());
+    }
+
+    #[test]
+    fn switchboard_spread_is_measured_against_the_median() {
+        // $95,000 median with a $47.50 sample spread
+        let result = switchboard::SwitchboardPrice {
+            value: 95_000 * 10i128.pow(switchboard::SWITCHBOARD_DECIMALS),
+            std_dev: 4_750 * 10i128.pow(switchboard::SWITCHBOARD_DECIMALS - 2),
+            last_update_timestamp: 0,
+        };
+        assert_eq!(result.std_dev_bps().unwrap(), 5);
+        assert!(switchboard::SwitchboardPrice { value: 0, ..result }.std_dev_bps().is_err());
+    }
+
+    #[test]
+    fn ema_deviation_bounds_single_step_settlement() {
+        // Spot $95,000 against an EMA of $100,000: 5% off
+        let quote = OracleQuote {
+            price: OraclePrice::new(9_500_000_000_000i64, -8),
+            publish_time: 0,
+            confidence: 0,
+            ema_price: Some(OraclePrice::new(10_000_000_000_000i64, -8)),
+        };
+        assert!(check_ema_deviation(&quote, 500).is_ok());
+        assert!(check_ema_deviation(&quote, 499).is_err());
+        // Off, or no EMA to compare against
+        assert!(check_ema_deviation(&quote, 0).is_ok());
+        assert!(check_ema_deviation(&OracleQuote { ema_price: None, ..quote }, 1).is_ok());
+    }
+
+    #[test]
+    fn payout_splits_need_distinct_recipients_summing_to_100_percent() {
+        let underwriter = Pubkey::new_unique();
+        let share = |share_bps| PayoutShare {
+            recipient: Pubkey::new_unique(),
+            share_bps,
+        };
+        assert!(check_payout_split(&[], &underwriter).is_ok());
+        assert!(check_payout_split(&[share(6_000), share(4_000)], &underwriter).is_ok());
+        assert!(check_payout_split(&[share(6_000), share(3_000)], &underwriter).is_err());
+        assert!(check_payout_split(&[share(10_000), share(0)], &underwriter).is_err());
+        let too_many: Vec<PayoutShare> = (0..5).map(|_| share(2_000)).collect();
+        assert!(check_payout_split(&too_many, &underwriter).is_err());
+        let repeated = share(5_000);
+        assert!(check_payout_split(&[repeated, repeated], &underwriter).is_err());
+        let to_underwriter = PayoutShare {
+            recipient: underwriter,
+            share_bps: 10_000,
+        };
+        assert!(check_payout_split(&[to_underwriter], &underwriter).is_err());
+    }
+
+    #[test]
+    fn master_agreements_cap_claims_at_the_remaining_limit() {
+        let mut agreement = MasterAgreement {
+            counterparty: Pubkey::new_unique(),
+            underwriter: Pubkey::new_unique(),
+            agreement_id: 0,
+            collateral_mint: Pubkey::default(),
+            usd_coverage: false,
+            aggregate_limit: 1_000,
+            aggregate_paid: 0,
+            expiration_datetime: 0,
+            member_count: 2,
+            bump: 0,
+        };
+        assert_eq!(agreement.draw(600), 600);
+        assert_eq!(agreement.draw(600), 400);
+        assert_eq!(agreement.draw(600), 0);
+        assert_eq!(agreement.aggregate_paid, 1_000);
+    }
+
+    #[test]
+    fn claim_history_keeps_the_latest_claims() {
+        let mut history: ClaimHistory = bytemuck::Zeroable::zeroed();
+        let price = OraclePrice::new(9_000_000_000_000i64, -8);
+        for claim in 0..CLAIM_HISTORY_CAPACITY as i64 + 2 {
+            history.record(ClaimRecord::new(Pubkey::new_unique(), price, 1_000, claim));
+        }
+        assert_eq!(history.total_claims, CLAIM_HISTORY_CAPACITY as u64 + 2);
+        assert_eq!(history.total_paid, (CLAIM_HISTORY_CAPACITY as u64 + 2) * 1_000);
+        // The two newest overwrote the two oldest
+        assert_eq!(history.next_index, 2);
+        assert_eq!(history.records[1].timestamp, CLAIM_HISTORY_CAPACITY as i64 + 1);
+        assert_eq!(history.records[2].timestamp, 2);
+    }
+}
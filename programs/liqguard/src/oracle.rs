@@ -0,0 +1,122 @@
+// Oracle adapter: every settlement path reads prices through get_price,
+// and the feed registry decides which backend answers for an asset.
+// Backends check their own ownership, staleness and confidence, so callers
+// only ever see a price that is safe to settle against
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+
+use crate::{chainlink, check_confidence, switchboard, LazerPrice, LiqGuardError, OraclePrice};
+
+pub use liqguard_common::OracleSource;
+
+// How to find a feed's price: Pyth updates land in whichever account the
+// caller posted them to, so they are matched by feed id; account-based
+// sources must pass the registered account itself. A nonzero
+// lazer_feed_id also accepts the asset's LazerPrice account
+#[derive(Clone, Copy)]
+pub struct FeedRef {
+    pub source: OracleSource,
+    pub feed_id: [u8; 32],
+    pub oracle_account: Pubkey,
+    pub lazer_feed_id: u32,
+}
+
+pub struct OracleQuote {
+    pub price: OraclePrice,
+    pub publish_time: i64,
+    pub confidence: u128, // in price's exponent, 0 where the source publishes none
+    pub ema_price: Option<OraclePrice>, // only Pyth publishes one
+}
+
+// A price from feed_ref no older than max_age seconds. Pyth, Lazer and
+// Switchboard prints are also held to max_confidence_bps (for Switchboard,
+// the spread of its oracle samples); Chainlink doesn't publish one
+pub fn get_price(
+    feed_ref: &FeedRef,
+    price_feed: &AccountInfo,
+    clock: &Clock,
+    max_age: u64,
+    max_confidence_bps: u16,
+) -> Result<OracleQuote> {
+    // Lazer prices were verified when posted, so only the feed, age and
+    // confidence are left to check. They carry no EMA
+    if feed_ref.lazer_feed_id != 0 && *price_feed.owner == crate::ID {
+        let lazer_price = LazerPrice::try_deserialize(&mut &price_feed.try_borrow_data()?[..])?;
+        require!(
+            lazer_price.lazer_feed_id == feed_ref.lazer_feed_id,
+            LiqGuardError::InvalidOracleAccount
+        );
+        let publish_time = (lazer_price.timestamp_us / 1_000_000) as i64;
+        require!(
+            publish_time.saturating_add(max_age as i64) >= clock.unix_timestamp,
+            LiqGuardError::PriceStale
+        );
+        check_confidence(lazer_price.price, lazer_price.confidence, max_confidence_bps)?;
+        return Ok(OracleQuote {
+            price: OraclePrice::new(lazer_price.price, lazer_price.exponent),
+            publish_time,
+            confidence: lazer_price.confidence as u128,
+            ema_price: None,
+        });
+    }
+
+    match feed_ref.source {
+        OracleSource::PythPull => {
+            require_keys_eq!(
+                *price_feed.owner,
+                PriceUpdateV2::owner(),
+                LiqGuardError::InvalidOracleAccount
+            );
+            let price_update = PriceUpdateV2::try_deserialize(&mut &price_feed.try_borrow_data()?[..])?;
+            // Only updates with every guardian signature checked settle;
+            // partially verified ones never move funds
+            require!(
+                price_update.verification_level.gte(VerificationLevel::Full),
+                LiqGuardError::PriceNotFullyVerified
+            );
+            let price_info = price_update
+                .get_price_no_older_than(clock, max_age, &feed_ref.feed_id)
+                .map_err(|_| LiqGuardError::PriceStale)?;
+            check_confidence(price_info.price, price_info.conf, max_confidence_bps)?;
+            let message = &price_update.price_message;
+            Ok(OracleQuote {
+                price: OraclePrice::new(price_info.price, price_info.exponent),
+                publish_time: price_info.publish_time,
+                confidence: price_info.conf as u128,
+                ema_price: Some(OraclePrice::new(message.ema_price, message.exponent)),
+            })
+        }
+        OracleSource::Switchboard => {
+            require_keys_eq!(
+                price_feed.key(),
+                feed_ref.oracle_account,
+                LiqGuardError::InvalidOracleAccount
+            );
+            let result = switchboard::get_price_no_older_than(price_feed, clock, max_age)?;
+            require!(
+                result.std_dev_bps()? <= max_confidence_bps as u128,
+                LiqGuardError::PriceConfidenceTooWide
+            );
+            Ok(OracleQuote {
+                price: result.price(),
+                publish_time: result.last_update_timestamp,
+                confidence: result.std_dev.unsigned_abs(),
+                ema_price: None,
+            })
+        }
+        OracleSource::Chainlink => {
+            require_keys_eq!(
+                price_feed.key(),
+                feed_ref.oracle_account,
+                LiqGuardError::InvalidOracleAccount
+            );
+            let round = chainlink::get_price_no_older_than(price_feed, clock, max_age)?;
+            Ok(OracleQuote {
+                price: round.price(),
+                publish_time: round.timestamp,
+                confidence: 0,
+                ema_price: None,
+            })
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use crate::LiqGuardError;
+
+// SPL Stake Pool program, which runs jitoSOL and most other stake-pool LSTs
+// (SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy)
+pub const STAKE_POOL_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    6, 129, 78, 212, 202, 246, 138, 23, 70, 114, 253, 172, 134, 3, 26, 99, 232, 78, 161, 94, 250,
+    29, 68, 183, 34, 147, 246, 219, 219, 0, 22, 80,
+]);
+
+// AccountType tag of an initialized StakePool
+const STAKE_POOL_ACCOUNT_TYPE: u8 = 1;
+
+// Byte offsets into a borsh StakePool: account_type, manager, staker,
+// stake_deposit_authority, stake_withdraw_bump_seed, validator_list,
+// reserve_stake, then the fields read below
+const POOL_MINT_OFFSET: usize = 162;
+const TOTAL_LAMPORTS_OFFSET: usize = 258;
+const POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+const LAST_UPDATE_EPOCH_OFFSET: usize = 274;
+const MIN_STAKE_POOL_LEN: usize = 282;
+
+pub struct StakePool {
+    pub pool_mint: Pubkey,
+    pub total_lamports: u64,    // SOL the pool's tokens are redeemable for
+    pub pool_token_supply: u64,
+    pub last_update_epoch: u64,
+}
+
+impl StakePool {
+    // Pool tokens worth lamports at the pool's exchange rate. Round up for
+    // collateral a vault must hold, down for what it pays out
+    pub fn pool_tokens_for(&self, lamports: u64, round_up: bool) -> Result<u64> {
+        let numerator = (lamports as u128)
+            .checked_mul(self.pool_token_supply as u128)
+            .ok_or(LiqGuardError::MathOverflow)?;
+        let denominator = self.total_lamports as u128;
+        let tokens = if round_up {
+            numerator.div_ceil(denominator)
+        } else {
+            numerator / denominator
+        };
+        u64::try_from(tokens).map_err(|_| error!(LiqGuardError::MathOverflow))
+    }
+}
+
+// Read a stake pool's exchange rate, rejecting foreign or malformed
+// accounts and pools not yet updated this epoch, whose rate lags the
+// rewards already earned
+pub fn load_stake_pool(stake_pool: &AccountInfo, clock: &Clock) -> Result<StakePool> {
+    require_keys_eq!(
+        *stake_pool.owner,
+        STAKE_POOL_PROGRAM_ID,
+        LiqGuardError::InvalidStakePool
+    );
+
+    let data = stake_pool.try_borrow_data()?;
+    require!(
+        data.len() >= MIN_STAKE_POOL_LEN && data[0] == STAKE_POOL_ACCOUNT_TYPE,
+        LiqGuardError::InvalidStakePool
+    );
+    let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+    let pool = StakePool {
+        pool_mint: Pubkey::new_from_array(
+            data[POOL_MINT_OFFSET..POOL_MINT_OFFSET + 32].try_into().unwrap(),
+        ),
+        total_lamports: read_u64(TOTAL_LAMPORTS_OFFSET),
+        pool_token_supply: read_u64(POOL_TOKEN_SUPPLY_OFFSET),
+        last_update_epoch: read_u64(LAST_UPDATE_EPOCH_OFFSET),
+    };
+    require!(
+        pool.total_lamports > 0 && pool.pool_token_supply > 0,
+        LiqGuardError::InvalidStakePool
+    );
+    require!(
+        pool.last_update_epoch == clock.epoch,
+        LiqGuardError::StakePoolStale
+    );
+    Ok(pool)
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::{LiqGuardError, OraclePrice, BPS_DENOMINATOR};
+
+// Switchboard On-Demand program that owns pull feed accounts
+// (SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv)
+pub const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    6, 115, 189, 70, 242, 228, 126, 4, 241, 43, 217, 47, 183, 49, 150, 142, 205, 157, 151, 87,
+    194, 116, 218, 135, 71, 111, 70, 92, 4, 12, 101, 115,
+]);
+
+// Anchor discriminator of PullFeedAccountData: sha256("account:PullFeedAccountData")[..8]
+const PULL_FEED_DISCRIMINATOR: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+
+// Byte offsets into the pull feed account (including the discriminator).
+// The layout is a #[repr(C)] struct, so these are fixed:
+// 32 submissions * 64 bytes, then authority/queue/feed_hash/... and the
+// CurrentResult: the i128 median value, its std_dev, then mean, range,
+// min and max before num_samples
+const LAST_UPDATE_TIMESTAMP_OFFSET: usize = 2216;
+const RESULT_VALUE_OFFSET: usize = 2264;
+const RESULT_STD_DEV_OFFSET: usize = 2280;
+const RESULT_NUM_SAMPLES_OFFSET: usize = 2360;
+
+// Switchboard values are fixed-point with 18 decimals
+pub const SWITCHBOARD_DECIMALS: u32 = 18;
+
+pub struct SwitchboardPrice {
+    pub value: i128,   // scaled by 10^SWITCHBOARD_DECIMALS
+    pub std_dev: i128, // spread of the oracle samples, same scale
+    pub last_update_timestamp: i64,
+}
+
+// Read the latest median out of a Switchboard On-Demand pull feed, rejecting
+// foreign accounts, empty results and values older than max_age seconds.
+// Oracle signatures were checked by the Switchboard program when the bundle
+// was submitted, so an account it owns only holds verified results
+pub fn get_price_no_older_than(
+    feed: &AccountInfo,
+    clock: &Clock,
+    max_age: u64,
+) -> Result<SwitchboardPrice> {
+    require_keys_eq!(
+        *feed.owner,
+        SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        LiqGuardError::InvalidSwitchboardFeed
+    );
+
+    let data = feed.try_borrow_data()?;
+    require!(
+        data.len() > RESULT_NUM_SAMPLES_OFFSET && data[..8] == PULL_FEED_DISCRIMINATOR,
+        LiqGuardError::InvalidSwitchboardFeed
+    );
+
+    let last_update_timestamp = i64::from_le_bytes(
+        data[LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8]
+            .try_into()
+            .unwrap(),
+    );
+    let value = i128::from_le_bytes(
+        data[RESULT_VALUE_OFFSET..RESULT_VALUE_OFFSET + 16]
+            .try_into()
+            .unwrap(),
+    );
+    let std_dev = i128::from_le_bytes(
+        data[RESULT_STD_DEV_OFFSET..RESULT_STD_DEV_OFFSET + 16]
+            .try_into()
+            .unwrap(),
+    );
+    let num_samples = data[RESULT_NUM_SAMPLES_OFFSET];
+
+    require!(num_samples > 0, LiqGuardError::InvalidSwitchboardFeed);
+    require!(
+        last_update_timestamp.saturating_add(max_age as i64) >= clock.unix_timestamp,
+        LiqGuardError::PriceStale
+    );
+
+    Ok(SwitchboardPrice {
+        value,
+        std_dev,
+        last_update_timestamp,
+    })
+}
+
+impl SwitchboardPrice {
+    pub fn price(&self) -> OraclePrice {
+        OraclePrice::new(self.value, -(SWITCHBOARD_DECIMALS as i32))
+    }
+
+    // Sample spread in bps of the median, Switchboard's counterpart to a
+    // Pyth confidence interval
+    pub fn std_dev_bps(&self) -> Result<u128> {
+        require!(self.value > 0, LiqGuardError::PriceConfidenceTooWide);
+        Ok(self
+            .std_dev
+            .unsigned_abs()
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(LiqGuardError::MathOverflow)?
+            / self.value as u128)
+    }
+}
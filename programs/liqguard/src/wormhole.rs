@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+use crate::LiqGuardError;
+
+// Wormhole core bridge, which writes PostedVAA accounts once a VAA's
+// guardian signatures have been verified
+// (worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth)
+pub const CORE_BRIDGE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    14, 10, 88, 154, 65, 165, 95, 189, 102, 197, 42, 71, 95, 45, 146, 166, 211, 220, 155, 71, 71,
+    17, 76, 185, 175, 130, 90, 152, 181, 69, 211, 206,
+]);
+
+// PostedVAA accounts start with a "vaa" magic instead of an Anchor
+// discriminator, followed by the borsh MessageData (version 1 first)
+const POSTED_VAA_MAGIC: &[u8; 4] = b"vaa\x01";
+
+// Byte offsets into a PostedVAA account: magic and version, consistency_level,
+// vaa_time, vaa_signature_account, submission_time, nonce, then the fields
+// read below and a u32-length-prefixed payload
+const SEQUENCE_OFFSET: usize = 49;
+const EMITTER_CHAIN_OFFSET: usize = 57;
+const EMITTER_ADDRESS_OFFSET: usize = 59;
+const PAYLOAD_LEN_OFFSET: usize = 91;
+const PAYLOAD_OFFSET: usize = 95;
+
+// Payload the EVM collector publishes for each premium it receives: a
+// type byte, then payment_id, the Solana policy and the USDC amount
+// (big-endian, as EVM encodes it)
+const PREMIUM_PAYMENT_PAYLOAD_ID: u8 = 1;
+const PREMIUM_PAYMENT_PAYLOAD_LEN: usize = 1 + 32 + 32 + 8;
+
+pub struct PostedVaa {
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub sequence: u64,
+    pub payload: Vec<u8>,
+}
+
+pub struct PremiumPayment {
+    pub payment_id: [u8; 32],
+    pub policy: Pubkey,
+    pub amount: u64, // USDC in the collector token's base units
+}
+
+// Read a verified VAA, rejecting accounts the core bridge didn't write
+pub fn load_posted_vaa(posted_vaa: &AccountInfo) -> Result<PostedVaa> {
+    require_keys_eq!(
+        *posted_vaa.owner,
+        CORE_BRIDGE_PROGRAM_ID,
+        LiqGuardError::InvalidWormholeMessage
+    );
+
+    let data = posted_vaa.try_borrow_data()?;
+    require!(
+        data.len() >= PAYLOAD_OFFSET && data[..4] == POSTED_VAA_MAGIC[..],
+        LiqGuardError::InvalidWormholeMessage
+    );
+    let payload_len = u32::from_le_bytes(
+        data[PAYLOAD_LEN_OFFSET..PAYLOAD_OFFSET].try_into().unwrap(),
+    ) as usize;
+    require!(
+        data.len() >= PAYLOAD_OFFSET + payload_len,
+        LiqGuardError::InvalidWormholeMessage
+    );
+
+    Ok(PostedVaa {
+        emitter_chain: u16::from_le_bytes(
+            data[EMITTER_CHAIN_OFFSET..EMITTER_ADDRESS_OFFSET].try_into().unwrap(),
+        ),
+        emitter_address: data[EMITTER_ADDRESS_OFFSET..PAYLOAD_LEN_OFFSET].try_into().unwrap(),
+        sequence: u64::from_le_bytes(data[SEQUENCE_OFFSET..EMITTER_CHAIN_OFFSET].try_into().unwrap()),
+        payload: data[PAYLOAD_OFFSET..PAYLOAD_OFFSET + payload_len].to_vec(),
+    })
+}
+
+impl PremiumPayment {
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        require!(
+            payload.len() == PREMIUM_PAYMENT_PAYLOAD_LEN && payload[0] == PREMIUM_PAYMENT_PAYLOAD_ID,
+            LiqGuardError::InvalidWormholeMessage
+        );
+        Ok(PremiumPayment {
+            payment_id: payload[1..33].try_into().unwrap(),
+            policy: Pubkey::new_from_array(payload[33..65].try_into().unwrap()),
+            amount: u64::from_be_bytes(payload[65..73].try_into().unwrap()),
+        })
+    }
+}
@@ -0,0 +1,127 @@
+// Localnet stand-in for the Pyth Solana receiver. It is declared under the
+// receiver's program id, so the accounts it writes pass liqguard's owner
+// check, and it writes real PriceUpdateV2 accounts at whatever price and
+// publish time a test asks for. That lets the whole liquidation path run
+// on a local validator without Hermes or guardian signatures. Its id is
+// the receiver's, so it can only ever be loaded where the receiver isn't
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{PriceFeedMessage, PriceUpdateV2, VerificationLevel};
+
+declare_id!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+pub const PRICE_UPDATE_SEED: &[u8] = b"mock_price_update";
+
+#[program]
+pub mod mock_pyth {
+    use super::*;
+
+    // Create or overwrite the feed's price update with params. A zero
+    // posted_slot is filled in with the current slot
+    pub fn set_price(ctx: Context<SetPrice>, params: SetPriceParams) -> Result<()> {
+        let mut update = params.into_update(ctx.accounts.payer.key());
+        if update.posted_slot == 0 {
+            update.posted_slot = Clock::get()?.slot;
+        }
+        ctx.accounts.price_update.set_inner(update);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetPriceParams {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+    pub verified: bool, // false writes a partially verified update
+    pub posted_slot: u64,
+}
+
+impl SetPriceParams {
+    pub fn into_update(self, write_authority: Pubkey) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority,
+            verification_level: if self.verified {
+                VerificationLevel::Full
+            } else {
+                VerificationLevel::Partial { num_signatures: 0 }
+            },
+            price_message: PriceFeedMessage {
+                feed_id: self.feed_id,
+                price: self.price,
+                conf: self.conf,
+                exponent: self.exponent,
+                publish_time: self.publish_time,
+                prev_publish_time: self.publish_time.saturating_sub(1),
+                ema_price: self.ema_price,
+                ema_conf: self.ema_conf,
+            },
+            posted_slot: self.posted_slot,
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetPriceParams)]
+pub struct SetPrice<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PriceUpdateV2::LEN,
+        seeds = [PRICE_UPDATE_SEED, params.feed_id.as_ref()],
+        bump
+    )]
+    pub price_update: Account<'info, PriceUpdateV2>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// The feed's price update account
+pub fn price_update_address(feed_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[PRICE_UPDATE_SEED, feed_id], &ID).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_receiver_compatible_update() {
+        assert_eq!(ID, pyth_solana_receiver_sdk::ID);
+        let params = SetPriceParams {
+            feed_id: [7; 32],
+            price: 9_000_000_000_000,
+            conf: 1_000_000,
+            exponent: -8,
+            publish_time: 1_700_000_000,
+            ema_price: 9_100_000_000_000,
+            ema_conf: 2_000_000,
+            verified: true,
+            posted_slot: 5,
+        };
+        let mut data = Vec::new();
+        params
+            .into_update(Pubkey::default())
+            .try_serialize(&mut data)
+            .unwrap();
+        assert!(data.len() <= PriceUpdateV2::LEN);
+
+        let update = PriceUpdateV2::try_deserialize(&mut &data[..]).unwrap();
+        assert!(update.verification_level.gte(VerificationLevel::Full));
+        let clock = Clock {
+            unix_timestamp: 1_700_000_030,
+            ..Clock::default()
+        };
+        let price = update
+            .get_price_no_older_than(&clock, 60, &[7; 32])
+            .unwrap();
+        assert_eq!((price.price, price.exponent), (9_000_000_000_000, -8));
+        assert!(update
+            .get_price_no_older_than(&clock, 10, &[7; 32])
+            .is_err());
+    }
+}